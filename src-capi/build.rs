@@ -0,0 +1,28 @@
+//! Generates `flowstt.h` from the exported functions into `OUT_DIR`.
+//!
+//! The build never touches the committed `include/flowstt.h`; the
+//! `committed_header_is_up_to_date` test compares it with this output. After
+//! changing the C API, refresh it with
+//!
+//! ```text
+//! cbindgen --config src-capi/cbindgen.toml --output src-capi/include/flowstt.h src-capi
+//! ```
+
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is invalid");
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Failed to generate flowstt.h")
+        .write_to_file(out_dir.join("flowstt.h"));
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,102 @@
+#ifndef FLOWSTT_H
+#define FLOWSTT_H
+
+/* Generated by cbindgen from src-capi/src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of a FlowSTT call
+typedef enum FlowsttStatus {
+  // The call succeeded
+  FLOWSTT_STATUS_OK = 0,
+  // The call failed; see `flowstt_last_error()`
+  FLOWSTT_STATUS_ERROR = 1,
+  // An argument was null where it may not be, or not valid UTF-8
+  FLOWSTT_STATUS_INVALID_ARGUMENT = 2,
+  // `flowstt_init()` has not been called
+  FLOWSTT_STATUS_NOT_INITIALIZED = 3,
+  // `flowstt_init()` was already called in this process
+  FLOWSTT_STATUS_ALREADY_INITIALIZED = 4,
+} FlowsttStatus;
+
+// Which devices `flowstt_list_devices()` reports
+typedef enum FlowsttDeviceKind {
+  // Microphones and other inputs
+  FLOWSTT_DEVICE_KIND_INPUT = 0,
+  // System audio (monitor or loopback) sources
+  FLOWSTT_DEVICE_KIND_SYSTEM = 1,
+  // Both
+  FLOWSTT_DEVICE_KIND_ALL = 2,
+} FlowsttDeviceKind;
+
+// Called once per device with its ID, display name and the caller's
+// `user_data`
+typedef void (*FlowsttDeviceCallback)(const char *id, const char *name, void *user_data);
+
+// Called with the text of each completed transcription and the caller's
+// `user_data`
+typedef void (*FlowsttTranscriptCallback)(const char *text, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Start the engine with the saved configuration. Capture does not start
+// until `flowstt_start()` or `flowstt_set_sources()`, and no IPC server is
+// opened.
+//
+// The engine can be started once per process.
+enum FlowsttStatus flowstt_init(void);
+
+// Call `callback` for each audio device of `kind`, a `FlowsttDeviceKind`
+// value.
+//
+// # Safety
+//
+// `callback` must be a valid function pointer; `user_data` is passed to it
+// unchanged.
+enum FlowsttStatus flowstt_list_devices(uint32_t kind,
+                                        FlowsttDeviceCallback callback,
+                                        void *user_data);
+
+// Choose the primary source (usually a microphone) and an optional
+// secondary source (system audio). Either may be null. Capture starts once
+// a primary source is set.
+//
+// # Safety
+//
+// Each argument must be null or point to a NUL-terminated string.
+enum FlowsttStatus flowstt_set_sources(const char *source1_id, const char *source2_id);
+
+// Start capturing from the chosen sources.
+enum FlowsttStatus flowstt_start(void);
+
+// Stop capturing. Speech already recorded is still transcribed.
+enum FlowsttStatus flowstt_stop(void);
+
+// Register `callback` for completed transcriptions, replacing any earlier
+// one. A null callback unregisters. May be called before `flowstt_init()`.
+//
+// # Safety
+//
+// `callback` must stay valid until it is replaced or the process exits,
+// and `user_data` must be usable from other threads.
+enum FlowsttStatus flowstt_set_transcript_callback(FlowsttTranscriptCallback callback,
+                                                   void *user_data);
+
+// Stop capture, wait for queued transcriptions (up to 30 seconds), save
+// history and stop the engine.
+enum FlowsttStatus flowstt_shutdown(void);
+
+// Message describing the last failed call on this thread, or null. The
+// string is valid until the next FlowSTT call on the same thread.
+const char *flowstt_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FLOWSTT_H */
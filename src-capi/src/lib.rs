@@ -0,0 +1,483 @@
+//! C API for the FlowSTT engine.
+//!
+//! Lets C, C++ and C# desktop applications run the capture and
+//! transcription pipeline in-process. The header is `include/flowstt.h`,
+//! generated from this file with cbindgen; see `build.rs` for how to
+//! refresh it.
+//!
+//! A typical session:
+//!
+//! 1. `flowstt_init()` starts the engine (no IPC server, no capture).
+//! 2. `flowstt_list_devices()` enumerates sources and
+//!    `flowstt_set_sources()` chooses them.
+//! 3. `flowstt_set_transcript_callback()` registers for transcripts.
+//! 4. `flowstt_start()` and `flowstt_stop()` control capture.
+//! 5. `flowstt_shutdown()` finishes queued transcriptions and stops.
+//!
+//! Every function returns a [`FlowsttStatus`]; on failure
+//! `flowstt_last_error()` describes what went wrong. Strings are UTF-8 and
+//! only valid for the duration of the call or callback that provides them.
+//! Callbacks run on engine threads and must not call back into this API.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use flowstt_common::ipc::{EventType, Request, Response};
+use flowstt_common::AudioSourceType;
+use flowstt_engine::ipc::EventCallback;
+use flowstt_engine::{Engine, EngineBuilder};
+use tokio::runtime::Runtime;
+
+/// Result of a FlowSTT call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowsttStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// The call failed; see `flowstt_last_error()`
+    Error = 1,
+    /// An argument was null where it may not be, or not valid UTF-8
+    InvalidArgument = 2,
+    /// `flowstt_init()` has not been called
+    NotInitialized = 3,
+    /// `flowstt_init()` was already called in this process
+    AlreadyInitialized = 4,
+}
+
+/// Which devices `flowstt_list_devices()` reports
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowsttDeviceKind {
+    /// Microphones and other inputs
+    Input = 0,
+    /// System audio (monitor or loopback) sources
+    System = 1,
+    /// Both
+    All = 2,
+}
+
+impl TryFrom<u32> for FlowsttDeviceKind {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, u32> {
+        match value {
+            0 => Ok(Self::Input),
+            1 => Ok(Self::System),
+            2 => Ok(Self::All),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Called once per device with its ID, display name and the caller's
+/// `user_data`
+pub type FlowsttDeviceCallback =
+    Option<unsafe extern "C" fn(id: *const c_char, name: *const c_char, user_data: *mut c_void)>;
+
+/// Called with the text of each completed transcription and the caller's
+/// `user_data`
+pub type FlowsttTranscriptCallback =
+    Option<unsafe extern "C" fn(text: *const c_char, user_data: *mut c_void)>;
+
+/// Runtime the engine runs on, created by the first `flowstt_init()`
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// The running engine
+static ENGINE: Mutex<Option<Engine>> = Mutex::new(None);
+
+/// Registered transcript callback
+static TRANSCRIPT_CALLBACK: Mutex<Option<TranscriptCallback>> = Mutex::new(None);
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Copy)]
+struct TranscriptCallback {
+    callback: unsafe extern "C" fn(*const c_char, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// The caller is responsible for `user_data` being usable from engine threads
+unsafe impl Send for TranscriptCallback {}
+
+/// Forwards completed transcriptions to the registered C callback.
+struct TranscriptForwarder;
+
+impl EventCallback for TranscriptForwarder {
+    fn on_event(&self, event: &EventType) {
+        let EventType::TranscriptionComplete(result) = event else {
+            return;
+        };
+        // Copied out so the callback may replace itself
+        let Some(registered) = *TRANSCRIPT_CALLBACK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        else {
+            return;
+        };
+        let Ok(text) = CString::new(result.text.as_str()) else {
+            return;
+        };
+        unsafe { (registered.callback)(text.as_ptr(), registered.user_data) };
+    }
+}
+
+/// Record `message` as this thread's last error and return `status`.
+fn fail(status: FlowsttStatus, message: impl Into<String>) -> FlowsttStatus {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// Run `f`, turning a panic into an error instead of unwinding into C.
+///
+/// A panic leaves any lock `f` held poisoned, so locks are always taken
+/// with `unwrap_or_else(PoisonError::into_inner)`: one failed call must not
+/// fail every later one, `flowstt_shutdown()` included.
+fn guarded(f: impl FnOnce() -> FlowsttStatus) -> FlowsttStatus {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| fail(FlowsttStatus::Error, "Internal error in the FlowSTT engine"))
+}
+
+/// Run `f` with the engine and its runtime, if initialized.
+fn with_engine(f: impl FnOnce(&Runtime, &Engine) -> FlowsttStatus) -> FlowsttStatus {
+    let engine = ENGINE.lock().unwrap_or_else(PoisonError::into_inner);
+    match (RUNTIME.get(), engine.as_ref()) {
+        (Some(runtime), Some(engine)) => f(runtime, engine),
+        _ => fail(
+            FlowsttStatus::NotInitialized,
+            "flowstt_init() has not been called",
+        ),
+    }
+}
+
+/// Read an optional C string argument.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string.
+unsafe fn optional_str(ptr: *const c_char) -> Result<Option<String>, FlowsttStatus> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    match CStr::from_ptr(ptr).to_str() {
+        Ok(s) => Ok(Some(s.to_string())),
+        Err(_) => Err(fail(
+            FlowsttStatus::InvalidArgument,
+            "String argument is not valid UTF-8",
+        )),
+    }
+}
+
+/// Start the engine with the saved configuration. Capture does not start
+/// until `flowstt_start()` or `flowstt_set_sources()`, and no IPC server is
+/// opened.
+///
+/// The engine can be started once per process.
+#[no_mangle]
+pub extern "C" fn flowstt_init() -> FlowsttStatus {
+    guarded(|| {
+        let mut engine = ENGINE.lock().unwrap_or_else(PoisonError::into_inner);
+        if engine.is_some() {
+            return fail(
+                FlowsttStatus::AlreadyInitialized,
+                "The engine is already running",
+            );
+        }
+
+        if RUNTIME.get().is_none() {
+            match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => {
+                    let _ = RUNTIME.set(runtime);
+                }
+                Err(e) => {
+                    return fail(
+                        FlowsttStatus::Error,
+                        format!("Failed to start runtime: {}", e),
+                    )
+                }
+            }
+        }
+        let runtime = RUNTIME.get().unwrap();
+
+        let built = runtime.block_on(
+            EngineBuilder::new()
+                .ipc_server(false)
+                .auto_capture(false)
+                .on_event(TranscriptForwarder)
+                .build(),
+        );
+        match built {
+            Ok(started) => {
+                *engine = Some(started);
+                FlowsttStatus::Ok
+            }
+            Err(flowstt_engine::EngineError::AlreadyRunning) => fail(
+                FlowsttStatus::AlreadyInitialized,
+                "The engine can only be started once per process",
+            ),
+            Err(e) => fail(FlowsttStatus::Error, e.to_string()),
+        }
+    })
+}
+
+/// Call `callback` for each audio device of `kind`, a `FlowsttDeviceKind`
+/// value.
+///
+/// # Safety
+///
+/// `callback` must be a valid function pointer; `user_data` is passed to it
+/// unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn flowstt_list_devices(
+    kind: u32,
+    callback: FlowsttDeviceCallback,
+    user_data: *mut c_void,
+) -> FlowsttStatus {
+    guarded(|| {
+        let Some(callback) = callback else {
+            return fail(FlowsttStatus::InvalidArgument, "callback is null");
+        };
+        // Taken as an integer, since an out-of-range value in a Rust enum is
+        // undefined behavior
+        let Ok(kind) = FlowsttDeviceKind::try_from(kind) else {
+            return fail(
+                FlowsttStatus::InvalidArgument,
+                format!("Unknown device kind {}", kind),
+            );
+        };
+        let source_type = match kind {
+            FlowsttDeviceKind::Input => Some(AudioSourceType::Input),
+            FlowsttDeviceKind::System => Some(AudioSourceType::System),
+            FlowsttDeviceKind::All => None,
+        };
+
+        with_engine(|runtime, engine| {
+            match runtime.block_on(engine.request(Request::ListDevices { source_type })) {
+                Response::Devices { devices } => {
+                    for device in devices {
+                        let (Ok(id), Ok(name)) =
+                            (CString::new(device.id), CString::new(device.name))
+                        else {
+                            continue;
+                        };
+                        callback(id.as_ptr(), name.as_ptr(), user_data);
+                    }
+                    FlowsttStatus::Ok
+                }
+                Response::Error { message } => fail(FlowsttStatus::Error, message),
+                _ => fail(FlowsttStatus::Error, "Unexpected response"),
+            }
+        })
+    })
+}
+
+/// Choose the primary source (usually a microphone) and an optional
+/// secondary source (system audio). Either may be null. Capture starts once
+/// a primary source is set.
+///
+/// # Safety
+///
+/// Each argument must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn flowstt_set_sources(
+    source1_id: *const c_char,
+    source2_id: *const c_char,
+) -> FlowsttStatus {
+    guarded(|| {
+        let source1_id = match optional_str(source1_id) {
+            Ok(id) => id,
+            Err(status) => return status,
+        };
+        let source2_id = match optional_str(source2_id) {
+            Ok(id) => id,
+            Err(status) => return status,
+        };
+
+        with_engine(|runtime, engine| {
+            let request = Request::SetSources {
+                source1_id,
+                source2_id,
+            };
+            if let Err(message) = request.validate() {
+                return fail(FlowsttStatus::InvalidArgument, message);
+            }
+            match runtime.block_on(engine.request(request)) {
+                Response::Ok => FlowsttStatus::Ok,
+                Response::Error { message } => fail(FlowsttStatus::Error, message),
+                _ => fail(FlowsttStatus::Error, "Unexpected response"),
+            }
+        })
+    })
+}
+
+/// Start capturing from the chosen sources.
+#[no_mangle]
+pub extern "C" fn flowstt_start() -> FlowsttStatus {
+    guarded(|| {
+        with_engine(
+            |runtime, engine| match runtime.block_on(engine.start_capture()) {
+                Ok(()) => FlowsttStatus::Ok,
+                Err(message) => fail(FlowsttStatus::Error, message),
+            },
+        )
+    })
+}
+
+/// Stop capturing. Speech already recorded is still transcribed.
+#[no_mangle]
+pub extern "C" fn flowstt_stop() -> FlowsttStatus {
+    guarded(|| {
+        with_engine(|runtime, engine| {
+            runtime.block_on(engine.stop_capture());
+            FlowsttStatus::Ok
+        })
+    })
+}
+
+/// Register `callback` for completed transcriptions, replacing any earlier
+/// one. A null callback unregisters. May be called before `flowstt_init()`.
+///
+/// # Safety
+///
+/// `callback` must stay valid until it is replaced or the process exits,
+/// and `user_data` must be usable from other threads.
+#[no_mangle]
+pub unsafe extern "C" fn flowstt_set_transcript_callback(
+    callback: FlowsttTranscriptCallback,
+    user_data: *mut c_void,
+) -> FlowsttStatus {
+    guarded(|| {
+        *TRANSCRIPT_CALLBACK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) =
+            callback.map(|callback| TranscriptCallback {
+                callback,
+                user_data,
+            });
+        FlowsttStatus::Ok
+    })
+}
+
+/// Stop capture, wait for queued transcriptions (up to 30 seconds), save
+/// history and stop the engine.
+#[no_mangle]
+pub extern "C" fn flowstt_shutdown() -> FlowsttStatus {
+    guarded(|| {
+        let Some(engine) = ENGINE.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+            return fail(
+                FlowsttStatus::NotInitialized,
+                "flowstt_init() has not been called",
+            );
+        };
+        if let Some(runtime) = RUNTIME.get() {
+            runtime.block_on(engine.shutdown());
+        }
+        FlowsttStatus::Ok
+    })
+}
+
+/// Message describing the last failed call on this thread, or null. The
+/// string is valid until the next FlowSTT call on the same thread.
+#[no_mangle]
+pub extern "C" fn flowstt_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        let message = flowstt_last_error();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn calls_before_init_fail() {
+        assert_eq!(flowstt_start(), FlowsttStatus::NotInitialized);
+        assert!(last_error().contains("flowstt_init"));
+        assert_eq!(flowstt_shutdown(), FlowsttStatus::NotInitialized);
+    }
+
+    #[test]
+    fn null_device_callback_is_rejected() {
+        let status =
+            unsafe { flowstt_list_devices(FlowsttDeviceKind::All as u32, None, ptr::null_mut()) };
+        assert_eq!(status, FlowsttStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn unknown_device_kind_is_rejected() {
+        unsafe extern "C" fn ignore(_: *const c_char, _: *const c_char, _: *mut c_void) {}
+
+        let status = unsafe { flowstt_list_devices(7, Some(ignore), ptr::null_mut()) };
+        assert_eq!(status, FlowsttStatus::InvalidArgument);
+        assert!(last_error().contains("device kind 7"));
+    }
+
+    #[test]
+    fn device_kinds_round_trip() {
+        for kind in [
+            FlowsttDeviceKind::Input,
+            FlowsttDeviceKind::System,
+            FlowsttDeviceKind::All,
+        ] {
+            assert_eq!(FlowsttDeviceKind::try_from(kind as u32), Ok(kind));
+        }
+        assert_eq!(FlowsttDeviceKind::try_from(3), Err(3));
+    }
+
+    #[test]
+    fn a_panic_does_not_poison_later_calls() {
+        let status = guarded(|| {
+            let _engine = ENGINE.lock().unwrap_or_else(PoisonError::into_inner);
+            panic!("engine bug");
+        });
+        assert_eq!(status, FlowsttStatus::Error);
+
+        assert_eq!(flowstt_shutdown(), FlowsttStatus::NotInitialized);
+    }
+
+    #[test]
+    fn committed_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/flowstt.h"));
+        // Git may check the header out with CRLF line endings on Windows
+        let committed = include_str!("../include/flowstt.h").replace("\r\n", "\n");
+        assert!(
+            generated == committed,
+            "include/flowstt.h is out of date; regenerate it as described in build.rs"
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_source_is_rejected() {
+        let bad = [0xffu8 as c_char, 0];
+        let status = unsafe { flowstt_set_sources(bad.as_ptr(), ptr::null()) };
+        assert_eq!(status, FlowsttStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn successful_call_clears_last_error() {
+        assert_eq!(flowstt_stop(), FlowsttStatus::NotInitialized);
+        let status = unsafe { flowstt_set_transcript_callback(None, ptr::null_mut()) };
+        assert_eq!(status, FlowsttStatus::Ok);
+        assert!(flowstt_last_error().is_null());
+    }
+}
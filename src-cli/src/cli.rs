@@ -0,0 +1,503 @@
+//! Command-line definition.
+//!
+//! Kept apart from the command handlers so the completion and man page
+//! generators can build the same `clap::Command` the CLI parses with.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::status_bar;
+
+#[derive(Parser)]
+#[command(name = "flowstt")]
+#[command(author = "FlowSTT")]
+#[command(version)]
+#[command(about = "Voice transcription CLI", long_about = None)]
+#[command(after_help = crate::EXIT_CODES_HELP)]
+pub struct Cli {
+    /// Output format
+    #[arg(long, global = true, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Suppress non-essential output
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Increase verbosity
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Keep config, models and history next to the executable and use an IPC
+    /// endpoint of its own (also on when a `flowstt-portable` file is there)
+    #[arg(long, global = true)]
+    pub portable: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    /// Status bar line for a waybar custom module (`status` only)
+    Waybar,
+    /// Status bar line for a polybar custom module (`status` only)
+    Polybar,
+}
+
+impl OutputFormat {
+    pub fn bar_format(&self) -> Option<status_bar::BarFormat> {
+        match self {
+            OutputFormat::Waybar => Some(status_bar::BarFormat::Waybar),
+            OutputFormat::Polybar => Some(status_bar::BarFormat::Polybar),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// List available audio devices
+    #[command(alias = "ls")]
+    List {
+        /// Filter by source type
+        #[arg(short, long)]
+        source: Option<SourceFilter>,
+    },
+
+    /// Start transcription
+    Transcribe {
+        /// Primary audio source ID (use 'list' to see available devices;
+        /// defaults to the configured sources)
+        #[arg(short = '1', long)]
+        source1: Option<String>,
+
+        /// Secondary audio source ID for mixing or AEC
+        #[arg(short = '2', long)]
+        source2: Option<String>,
+
+        /// Exit after the first transcription, printing only its text
+        #[arg(long)]
+        once: bool,
+
+        /// With --once, give up after this many seconds without a
+        /// transcription
+        #[arg(long, value_name = "SECS", requires = "once")]
+        timeout: Option<u64>,
+
+        /// Enable acoustic echo cancellation
+        #[arg(long)]
+        aec: bool,
+
+        /// Recording mode (mixed, echo-cancel, or split)
+        #[arg(short, long, default_value = "mixed")]
+        mode: RecordingModeArg,
+
+        /// Print live speech detector metrics to stderr
+        #[arg(long)]
+        metrics: bool,
+
+        /// Privacy mode: keep recordings and history in memory only
+        #[arg(long)]
+        private: bool,
+    },
+
+    /// Transcribe 16-bit PCM or WAV audio piped to stdin
+    /// (e.g. `arecord -f cd - | flowstt transcribe-stdin --rate 44100 --channels 2`)
+    TranscribeStdin {
+        /// Sample rate of raw input in Hz (a WAV header overrides it)
+        #[arg(long, default_value_t = 16000)]
+        rate: u32,
+
+        /// Interleaved channels of raw input (a WAV header overrides it)
+        #[arg(long, default_value_t = 1)]
+        channels: u16,
+    },
+
+    /// Get current transcription status
+    Status {
+        /// Keep running and print the status again whenever it changes
+        /// (with `--format waybar` or `--format polybar`)
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Show a live waveform and level meter for the active input
+    Monitor,
+
+    /// Stop transcription
+    Stop,
+
+    /// Show Whisper model status
+    Model {
+        #[command(subcommand)]
+        action: Option<ModelAction>,
+    },
+
+    /// Show GPU/CUDA acceleration status
+    Gpu,
+
+    /// Show which platform features work in this session
+    Capabilities,
+
+    /// Check for a newer FlowSTT release
+    Update {
+        #[command(subcommand)]
+        action: UpdateAction,
+    },
+
+    /// Show the operating-system permissions the service needs
+    Permissions {
+        /// Ask for a permission (shows the system prompt or opens Settings)
+        #[arg(long, value_enum)]
+        request: Option<PermissionArg>,
+    },
+
+    /// Collect logs, crash reports and system details for a bug report
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+
+    /// Read or write persisted configuration values
+    #[command(alias = "cfg")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Toggle between Automatic and Push-to-Talk transcription modes
+    ToggleAuto,
+
+    /// Resume capture after an idle auto-suspend
+    Resume,
+
+    /// Run interactive first-time setup wizard
+    Setup,
+
+    /// Measure input levels and suggest per-device VAD/gain settings
+    Calibrate {
+        /// Device ID to calibrate (defaults to the configured primary source)
+        #[arg(short, long)]
+        device: Option<String>,
+
+        /// Save the suggested settings without prompting
+        #[arg(long)]
+        write: bool,
+    },
+
+    /// Only transcribe your own voice in Automatic mode
+    Speaker {
+        #[command(subcommand)]
+        action: SpeakerAction,
+    },
+
+    /// Show speaking time and words transcribed from the activity log
+    /// (enable with "activity_log": true in the config file)
+    Stats {
+        /// Since midnight, by hour (the default)
+        #[arg(long, group = "period")]
+        today: bool,
+
+        /// The last 7 days, by day
+        #[arg(long, group = "period")]
+        week: bool,
+
+        /// The last 30 days, by day
+        #[arg(long, group = "period")]
+        month: bool,
+
+        /// Everything logged, by day
+        #[arg(long, group = "period")]
+        all: bool,
+    },
+
+    /// List, play back or re-transcribe transcription history
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// Paste every segment into the focused window until the session ends
+    Dictation {
+        #[command(subcommand)]
+        action: DictationAction,
+    },
+
+    /// Transcribe a call from the microphone and system audio as one timeline
+    Meeting {
+        #[command(subcommand)]
+        action: MeetingAction,
+    },
+
+    /// Export or import phrase replacement, redaction and capitalization rules as TOML
+    Rules {
+        #[command(subcommand)]
+        action: RulesAction,
+    },
+
+    /// Back up or restore the config, history, meetings and activity log
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// Play WAV files through push-to-talk and score the transcriptions
+    Test {
+        #[command(subcommand)]
+        action: TestAction,
+    },
+
+    /// Measure engine performance
+    Bench {
+        #[command(subcommand)]
+        action: BenchAction,
+    },
+
+    /// Ping the service
+    Ping,
+
+    /// Stop the background service
+    Shutdown,
+
+    /// Show version information
+    Version,
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+
+    /// Print the man page to stdout, or write pages for every subcommand
+    Manpage {
+        /// Directory to write flowstt.1 and the subcommand pages to
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum SourceFilter {
+    Input,
+    System,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum PermissionArg {
+    Microphone,
+    Accessibility,
+    InputMonitoring,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum RecordingModeArg {
+    Mixed,
+    EchoCancel,
+    Split,
+}
+
+#[derive(Subcommand)]
+pub enum ModelAction {
+    /// Download the Whisper model
+    Download,
+}
+
+#[derive(Subcommand)]
+pub enum UpdateAction {
+    /// Check the release feed now (downloads the installer if
+    /// `updates.stage_downloads` is on)
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum DebugAction {
+    /// Bundle the logs, crash reports, the config (with personal details
+    /// removed) and system information into a zip file
+    Report {
+        /// Zip file to write (defaults to flowstt-report-<time>.zip in the
+        /// current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List history entries with their IDs
+    List,
+
+    /// Show the number of entries, words and audio length by day
+    Summary {
+        /// Break the history down by hour instead
+        #[arg(long)]
+        hourly: bool,
+    },
+
+    /// Play the saved recording of a history entry on the default output device
+    Play {
+        /// History entry ID (use 'history list' to see IDs)
+        id: String,
+
+        /// Start playback this many seconds into the recording
+        #[arg(long)]
+        from: Option<f64>,
+    },
+
+    /// Correct the text of a history entry (the original transcript is kept)
+    Edit {
+        /// History entry ID (use 'history list' to see IDs)
+        id: String,
+
+        /// The corrected text
+        text: String,
+    },
+
+    /// Transcribe the saved recording of a history entry again and update its text
+    Retranscribe {
+        /// History entry ID (use 'history list' to see IDs)
+        id: String,
+
+        /// Whisper model to use, by name (e.g. "small.en") or path
+        /// (defaults to the configured model)
+        #[arg(short, long)]
+        model: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DictationAction {
+    /// Start a session targeting the window that is focused now
+    Start,
+
+    /// End the session in progress
+    Stop,
+}
+
+#[derive(Subcommand)]
+pub enum MeetingAction {
+    /// Start a meeting (needs both a microphone and a system audio source)
+    Start {
+        /// Title for the meeting notes
+        #[arg(short, long)]
+        title: Option<String>,
+    },
+
+    /// Stop the meeting in progress and save it
+    Stop,
+
+    /// List saved meetings with their IDs
+    List,
+
+    /// Write a meeting transcript as Markdown to a file (or stdout)
+    Export {
+        /// Meeting ID (use 'meeting list' to see IDs)
+        id: String,
+
+        /// Output file (defaults to stdout)
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RulesAction {
+    /// Write the current rules to a TOML file (or stdout)
+    Export {
+        /// Output file (defaults to stdout)
+        file: Option<PathBuf>,
+    },
+
+    /// Add rules from a TOML file to the current ones
+    Import {
+        /// TOML file written by 'rules export'
+        file: PathBuf,
+
+        /// Replace the current rules instead of merging
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// Write a backup archive (history and recordings are stored unencrypted)
+    Create {
+        /// Archive to write (e.g. flowstt-backup.zip)
+        file: PathBuf,
+
+        /// Also back up the saved recordings of history entries
+        #[arg(long)]
+        recordings: bool,
+    },
+
+    /// Replace this machine's data with a backup archive's
+    Restore {
+        /// Archive written by 'backup create'
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TestAction {
+    /// Run every WAV file in a directory and write a results report there
+    Run {
+        /// Directory containing the WAV files
+        dir: PathBuf,
+
+        /// Manifest of expected transcripts (JSON or CSV; defaults to
+        /// manifest.json or manifest.csv in the directory)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SpeakerAction {
+    /// Record your voice and turn on the speaker filter
+    Enroll {
+        /// Device ID to record from (defaults to the configured primary source)
+        #[arg(short, long)]
+        device: Option<String>,
+    },
+    /// Delete your voiceprint and turn off the speaker filter
+    Clear,
+}
+
+#[derive(Subcommand)]
+pub enum BenchAction {
+    /// Play audio through the engine in real time and report how long
+    /// transcriptions take to arrive after speech ends
+    Latency {
+        /// WAV file to play (defaults to a built-in tone and speech sample)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Number of times to play the audio
+        #[arg(short, long, default_value_t = 10)]
+        runs: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Display all persisted configuration values
+    Show,
+
+    /// Get the value of a configuration key
+    Get {
+        /// Configuration key (e.g. transcription_mode, ptt_hotkeys, auto_paste_enabled)
+        key: String,
+    },
+
+    /// Set the value of a configuration key
+    Set {
+        /// Configuration key (e.g. transcription_mode, ptt_hotkeys, auto_paste_enabled)
+        key: String,
+
+        /// Value to set (e.g. "automatic", "push_to_talk", or key names such as
+        /// "ctrl+alt" or JSON for ptt_hotkeys)
+        value: String,
+    },
+}
@@ -112,8 +112,11 @@ impl Client {
     }
 
     /// Subscribe to events. After this, use `read_event()` to read events.
-    pub async fn subscribe_events(&mut self) -> Result<(), IpcError> {
-        let response = self.request(Request::SubscribeEvents).await?;
+    /// Set `speech_metrics` to also receive the high-frequency `SpeechMetrics` stream.
+    pub async fn subscribe_events(&mut self, speech_metrics: bool) -> Result<(), IpcError> {
+        let response = self
+            .request(Request::SubscribeEvents { speech_metrics })
+            .await?;
         match response {
             Response::Subscribed => Ok(()),
             Response::Error { message } => Err(IpcError::ParseError(message)),
@@ -153,6 +156,11 @@ fn get_app_path() -> PathBuf {
         "flowstt-app"
     };
 
+    // A portable copy only ever runs the app from its own folder
+    if let Some(dir) = flowstt_common::portable::portable_dir() {
+        return dir.join(app_name);
+    }
+
     // macOS: check standard application locations
     #[cfg(target_os = "macos")]
     {
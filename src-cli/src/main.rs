@@ -3,173 +3,131 @@
 //! This is the command-line interface for FlowSTT voice transcription.
 //! It communicates with the background service via IPC.
 
+mod cli;
 mod client;
+mod monitor;
+mod report;
+mod status_bar;
+mod stdin_audio;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser};
 use colored::Colorize;
-use flowstt_common::config::Config;
-use flowstt_common::ipc::{EventType, Request, Response};
-use flowstt_common::{runtime_mode, AudioSourceType, ConfigValues, HotkeyCombination, KeyCode, RecordingMode, TranscriptionMode};
-
+use flowstt_common::config::{Config, DeviceCalibration, UserRules, MAX_AUTO_PASTE_DELAY_MS};
+use flowstt_common::i18n::{self, tr, Message};
+use flowstt_common::ipc::{EventType, Request, Response, PROTOCOL_VERSION};
+use flowstt_common::settings::{self, key};
+use flowstt_common::{runtime_mode, ActivityPeriod, AudioSelfTestReport, AudioSourceType, Capabilities, Capability, ConfigValues, HistoryGrouping, HotkeyCombination, KeyCode, Language, LatencyReport, LetterLayout, MeetingRecord, PermissionKind, PermissionState, RecordingMode, TestFileOutcome, TestFileResult, TestRunSummary, TranscriptionMode};
+use std::path::Path;
+
+use cli::{
+    BackupAction, BenchAction, Cli, Commands, ConfigAction, DebugAction, DictationAction,
+    HistoryAction, MeetingAction, ModelAction, OutputFormat, PermissionArg, RecordingModeArg,
+    RulesAction, SourceFilter, SpeakerAction, TestAction, UpdateAction,
+};
 use client::Client;
 
-#[derive(Parser)]
-#[command(name = "flowstt")]
-#[command(author = "FlowSTT")]
-#[command(version)]
-#[command(about = "Voice transcription CLI", long_about = None)]
-struct Cli {
-    /// Output format
-    #[arg(long, default_value = "text")]
-    format: OutputFormat,
-
-    /// Suppress non-essential output
-    #[arg(short, long)]
-    quiet: bool,
-
-    /// Increase verbosity
-    #[arg(short, long)]
-    verbose: bool,
-
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Clone, ValueEnum)]
-enum OutputFormat {
-    Text,
-    Json,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// List available audio devices
-    #[command(alias = "ls")]
-    List {
-        /// Filter by source type
-        #[arg(short, long)]
-        source: Option<SourceFilter>,
-    },
-
-    /// Start transcription
-    Transcribe {
-        /// Primary audio source ID (use 'list' to see available devices)
-        #[arg(short = '1', long)]
-        source1: Option<String>,
-
-        /// Secondary audio source ID for mixing or AEC
-        #[arg(short = '2', long)]
-        source2: Option<String>,
-
-        /// Enable acoustic echo cancellation
-        #[arg(long)]
-        aec: bool,
-
-        /// Recording mode (mix or echo-cancel)
-        #[arg(short, long, default_value = "mixed")]
-        mode: RecordingModeArg,
-    },
-
-    /// Get current transcription status
-    Status,
-
-    /// Stop transcription
-    Stop,
-
-    /// Show Whisper model status
-    Model {
-        #[command(subcommand)]
-        action: Option<ModelAction>,
-    },
-
-    /// Show GPU/CUDA acceleration status
-    Gpu,
-
-    /// Read or write persisted configuration values
-    #[command(alias = "cfg")]
-    Config {
-        #[command(subcommand)]
-        action: ConfigAction,
-    },
-
-    /// Toggle between Automatic and Push-to-Talk transcription modes
-    ToggleAuto,
-
-    /// Run interactive first-time setup wizard
-    Setup,
-
-    /// Ping the service
-    Ping,
-
-    /// Stop the background service
-    Shutdown,
-
-    /// Show version information
-    Version,
-}
-
-#[derive(Clone, ValueEnum)]
-enum SourceFilter {
-    Input,
-    System,
-}
-
-#[derive(Clone, ValueEnum)]
-enum RecordingModeArg {
-    Mixed,
-    EchoCancel,
-}
-
-#[derive(Subcommand)]
-enum ModelAction {
-    /// Download the Whisper model
-    Download,
-}
-
-#[derive(Subcommand)]
-enum ConfigAction {
-    /// Display all persisted configuration values
-    Show,
-
-    /// Get the value of a configuration key
-    Get {
-        /// Configuration key (transcription_mode, ptt_hotkeys)
-        key: String,
-    },
-
-    /// Set the value of a configuration key
-    Set {
-        /// Configuration key (transcription_mode, ptt_hotkeys)
-        key: String,
-
-        /// Value to set (e.g. "automatic", "push_to_talk", or JSON for ptt_hotkeys)
-        value: String,
-    },
-}
-
 /// Valid configuration key names.
-const VALID_CONFIG_KEYS: &[&str] = &["transcription_mode", "ptt_hotkeys", "auto_toggle_hotkeys"];
+const VALID_CONFIG_KEYS: &[&str] = &[
+    "transcription_mode",
+    "ptt_hotkeys",
+    "auto_toggle_hotkeys",
+    "auto_paste_enabled",
+    "auto_paste_delay_ms",
+    "autostart",
+    "language",
+];
+
+/// General failure
+const EXIT_ERROR: i32 = 1;
+
+/// Invalid arguments or input files
+const EXIT_USAGE: i32 = 64;
+
+/// An audio device given or configured doesn't exist
+const EXIT_DEVICE_NOT_FOUND: i32 = 66;
+
+/// The service couldn't be reached or started
+const EXIT_SERVICE_UNAVAILABLE: i32 = 69;
+
+/// `transcribe --once --timeout` expired before anything was transcribed
+const EXIT_TIMEOUT: i32 = 75;
+
+/// The Whisper model hasn't been downloaded
+const EXIT_MODEL_MISSING: i32 = 78;
+
+/// Exit code table for `--help` and the man page. Scripts depend on these
+/// codes, so existing ones must never change meaning.
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0   Success
+  1   General failure
+  64  Invalid arguments or input files
+  66  Audio device not found
+  69  Service unreachable
+  75  Timed out (transcribe --once --timeout)
+  78  Whisper model missing";
 
 /// Error with an associated exit code.
 struct CliError {
     message: String,
     exit_code: i32,
+    /// Stable identifier for scripts, reported with `--format json`
+    code: &'static str,
 }
 
 impl CliError {
-    fn new(message: impl Into<String>, exit_code: i32) -> Self {
+    fn new(message: impl Into<String>, exit_code: i32, code: &'static str) -> Self {
         Self {
             message: message.into(),
             exit_code,
+            code,
         }
     }
 
     fn general(message: impl Into<String>) -> Self {
-        Self::new(message, 1)
+        Self::new(message, EXIT_ERROR, "error")
     }
 
     fn usage(message: impl Into<String>) -> Self {
-        Self::new(message, 64)
+        Self::new(message, EXIT_USAGE, "usage")
+    }
+
+    fn device_not_found(device_id: &str) -> Self {
+        Self::new(
+            format!(
+                "Device not found: {}. Use 'flowstt list' to see devices.",
+                device_id
+            ),
+            EXIT_DEVICE_NOT_FOUND,
+            "device_not_found",
+        )
+    }
+
+    /// The service could not be reached or started.
+    fn unavailable(message: impl Into<String>) -> Self {
+        Self::new(message, EXIT_SERVICE_UNAVAILABLE, "service_unavailable")
+    }
+
+    fn timeout(message: impl Into<String>) -> Self {
+        Self::new(message, EXIT_TIMEOUT, "timeout")
+    }
+
+    fn model_missing() -> Self {
+        Self::new(
+            "Whisper model not downloaded. Run 'flowstt model download' first.",
+            EXIT_MODEL_MISSING,
+            "model_missing",
+        )
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "code": self.code,
+                "exit_code": self.exit_code,
+                "message": self.message,
+            }
+        })
     }
 }
 
@@ -187,10 +145,22 @@ impl From<&str> for CliError {
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    // Argument errors exit with the usage code like every other usage error
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() { EXIT_USAGE } else { 0 });
+    });
+    if cli.portable {
+        flowstt_common::portable::enable();
+    }
+    let json = matches!(cli.format, OutputFormat::Json);
 
     if let Err(e) = run(cli).await {
-        eprintln!("{}: {}", "Error".red().bold(), e.message);
+        if json {
+            println!("{}", e.to_json());
+        } else {
+            eprintln!("{}: {}", tr(Message::CliError).red().bold(), e.message);
+        }
         std::process::exit(e.exit_code);
     }
 }
@@ -198,27 +168,53 @@ async fn main() {
 async fn run(cli: Cli) -> Result<(), CliError> {
     let mut client = Client::new();
 
-    // Handle version separately (doesn't need service)
+    // Handle version separately (doesn't start the service)
     if matches!(cli.command, Commands::Version) {
-        println!("flowstt {}", env!("CARGO_PKG_VERSION"));
+        return handle_version(&mut client, &cli).await;
+    }
+
+    // Generators only need the command definition
+    if let Commands::Completions { shell } = cli.command {
+        let mut command = Cli::command();
+        clap_complete::generate(shell, &mut command, "flowstt", &mut std::io::stdout());
         return Ok(());
     }
+    if let Commands::Manpage { ref dir } = cli.command {
+        return write_man_pages(dir.as_deref());
+    }
 
     // Handle config commands (can work offline)
     if let Commands::Config { ref action } = cli.command {
         return handle_config(&mut client, action, &cli).await;
     }
 
+    // A bug report is wanted most when the service is down
+    if let Commands::Debug {
+        action: DebugAction::Report { ref output },
+    } = cli.command
+    {
+        return handle_debug_report(&mut client, output.as_deref(), &cli).await;
+    }
+
     // Handle setup command
     if matches!(cli.command, Commands::Setup) {
         return handle_setup(&mut client, &cli).await;
     }
 
+    // A status bar watcher waits for the service instead of starting it
+    if let Commands::Status { watch: true } = cli.command {
+        let format = cli
+            .format
+            .bar_format()
+            .ok_or_else(|| CliError::usage("--watch needs --format waybar or --format polybar"))?;
+        return status_bar::watch(format).await.map_err(Into::into);
+    }
+
     // Connect to service (spawn if needed)
     client
         .connect_or_spawn()
         .await
-        .map_err(|e| format!("Failed to connect to service: {}", e))?;
+        .map_err(|e| CliError::unavailable(format!("Failed to connect to service: {}", e)))?;
 
     // Run the command
     run_command(&mut client, &cli).await
@@ -242,7 +238,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                     if matches!(cli.format, OutputFormat::Json) {
                         println!("{}", serde_json::to_string_pretty(&devices).unwrap());
                     } else if devices.is_empty() {
-                        println!("No audio devices found");
+                        println!("{}", tr(Message::CliNoDevices));
                     } else {
                         println!(
                             "{} {} found:\n",
@@ -261,6 +257,9 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                             };
                             println!("  {} {}", source_badge, device.name);
                             println!("    ID: {}", device.id.dimmed());
+                            if let Some(rate) = device.sample_rate {
+                                println!("    Sample rate: {} Hz", rate);
+                            }
                         }
                     }
                 }
@@ -274,19 +273,39 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             source2,
             aec,
             mode,
+            metrics,
+            private,
+            once,
+            timeout,
         } => {
+            let json = matches!(cli.format, OutputFormat::Json);
+
+            let (source1, source2) = if source1.is_none() && source2.is_none() {
+                let config = Config::load();
+                (config.preferred_source1_id, config.preferred_source2_id)
+            } else {
+                (source1.clone(), source2.clone())
+            };
             if source1.is_none() && source2.is_none() {
                 return Err(
                     "At least one audio source is required. Use 'flowstt list' to see devices."
                         .into(),
                 );
             }
+            let device_ids = [source1.as_deref(), source2.as_deref()];
+            check_devices_exist(client, device_ids.into_iter().flatten()).await?;
+            check_model_available(client).await?;
 
             let recording_mode = match mode {
                 RecordingModeArg::Mixed => RecordingMode::Mixed,
                 RecordingModeArg::EchoCancel => RecordingMode::EchoCancel,
+                RecordingModeArg::Split => RecordingMode::Split,
             };
 
+            if !cli.quiet && (*aec || recording_mode != RecordingMode::Mixed) {
+                warn_if_unsupported(client, "Echo cancellation", |c| &c.aec).await;
+            }
+
             // Set AEC and recording mode first
             if *aec {
                 let _ = client
@@ -299,20 +318,35 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                 })
                 .await;
 
+            // Privacy mode stays on for the rest of the service session
+            if *private {
+                let response = client
+                    .request(Request::SetPrivacyMode { enabled: true })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if let Response::Error { message } = response {
+                    return Err(message.into());
+                }
+            }
+
             // Set sources - this starts capture automatically
             let response = client
                 .request(Request::SetSources {
-                    source1_id: source1.clone(),
-                    source2_id: source2.clone(),
+                    source1_id: source1,
+                    source2_id: source2,
                 })
                 .await
                 .map_err(|e| e.to_string())?;
 
             match response {
                 Response::Ok => {
-                    if !cli.quiet {
-                        println!("{}", "Transcription started".green());
-                        println!("Press Ctrl+C to stop, or run 'flowstt stop'");
+                    // With --once only the transcription goes to stdout
+                    if !cli.quiet && !json && !*once {
+                        println!("{}", tr(Message::CliTranscriptionStarted).green());
+                        if *private {
+                            println!("{}", tr(Message::CliPrivacyOn).dimmed());
+                        }
+                        println!("{}", tr(Message::CliStopHint));
                     }
 
                     // Create a dedicated event client (separate connection)
@@ -323,7 +357,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         .map_err(|e| format!("Failed to connect event client: {}", e))?;
 
                     event_client
-                        .subscribe_events()
+                        .subscribe_events(*metrics)
                         .await
                         .map_err(|e| format!("Failed to subscribe: {}", e))?;
 
@@ -331,49 +365,101 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                     let shutdown = tokio::signal::ctrl_c();
                     tokio::pin!(shutdown);
 
+                    let deadline = async {
+                        match timeout {
+                            Some(secs) => {
+                                tokio::time::sleep(std::time::Duration::from_secs(*secs)).await
+                            }
+                            None => std::future::pending().await,
+                        }
+                    };
+                    tokio::pin!(deadline);
+                    let mut transcribed = false;
+
                     // Stream events until Ctrl+C or capture stops
                     loop {
+                        if *once && transcribed {
+                            break;
+                        }
                         tokio::select! {
                             _ = &mut shutdown => {
-                                if !cli.quiet {
-                                    eprintln!("\n{}", "Interrupted".yellow());
+                                if !cli.quiet && !json {
+                                    eprintln!("\n{}", tr(Message::CliInterrupted).yellow());
                                 }
                                 break;
                             }
+                            _ = &mut deadline => {
+                                stop_capture(client).await;
+                                return Err(CliError::timeout(format!(
+                                    "Nothing was transcribed within {} seconds",
+                                    timeout.unwrap_or_default()
+                                )));
+                            }
                             event_result = event_client.read_event() => {
                                 match event_result {
+                                    // One event object per line, tagged with "event"
+                                    Ok(Response::Event { event }) if json => {
+                                        transcribed |= matches!(event, EventType::TranscriptionComplete(_));
+                                        if is_transcribe_event(&event) {
+                                            println!("{}", serde_json::to_string(&event).unwrap());
+                                        }
+                                        if matches!(
+                                            event,
+                                            EventType::CaptureStateChanged { capturing: false, .. }
+                                                | EventType::Shutdown
+                                        ) {
+                                            break;
+                                        }
+                                    }
                                     Ok(Response::Event { event }) => {
                                         match event {
                                             EventType::TranscriptionComplete(result) => {
-                                                if matches!(cli.format, OutputFormat::Json) {
-                                                    println!("{}", serde_json::to_string(&result).unwrap());
-                                                } else {
-                                                    println!("{}", result.text);
-                                                }
+                                                println!("{}", result.text);
+                                                transcribed = true;
                                             }
-                                            EventType::SpeechStarted => {
-                                                if cli.verbose {
-                                                    eprintln!("{}", "[speech started]".dimmed());
-                                                }
+                                            EventType::SpeechMetrics(m) => {
+                                                eprintln!(
+                                                    "{}",
+                                                    format!(
+                                                        "[vad] {:6.1} dB  zcr {:.2}  centroid {:5.0} Hz  {}",
+                                                        m.amplitude_db,
+                                                        m.zcr,
+                                                        m.centroid_hz,
+                                                        if m.is_speaking { "speech" } else { "-" }
+                                                    )
+                                                    .dimmed()
+                                                );
                                             }
-                                            EventType::SpeechEnded { duration_ms } => {
-                                                if cli.verbose {
-                                                    eprintln!("{}", format!("[speech ended: {}ms]", duration_ms).dimmed());
-                                                }
+                                            EventType::SpeechStarted if cli.verbose => {
+                                                eprintln!("{}", "[speech started]".dimmed());
+                                            }
+                                            EventType::SpeechEnded { duration_ms } if cli.verbose => {
+                                                eprintln!("{}", format!("[speech ended: {}ms]", duration_ms).dimmed());
                                             }
-                                            EventType::CaptureStateChanged { capturing, error } => {
-                                                if !capturing {
-                                                    if let Some(err) = error {
-                                                        eprintln!("{}: {}", "Capture error".red(), err);
-                                                    } else if !cli.quiet {
-                                                        eprintln!("{}", "Capture stopped".yellow());
-                                                    }
-                                                    break;
+                                            EventType::CaptureStateChanged { capturing: false, error } => {
+                                                if let Some(err) = error {
+                                                    eprintln!("{}: {}", tr(Message::CliCaptureError).red(), err);
+                                                } else if !cli.quiet {
+                                                    eprintln!("{}", tr(Message::CliCaptureStopped).yellow());
                                                 }
+                                                break;
+                                            }
+                                            EventType::LoadSheddingChanged { active, reason } if !cli.quiet => {
+                                                let change = if active { "Falling behind; using faster settings" } else { "Caught up; back to normal settings" };
+                                                eprintln!("{}", format!("{} ({})", change, reason).yellow());
+                                            }
+                                            EventType::LowDiskSpace { path, available_mb, .. } if !cli.quiet => {
+                                                eprintln!("{}", i18n::format(Message::CliLowDiskSpace, &[("path", &path), ("mb", &available_mb)]).yellow());
+                                            }
+                                            EventType::PasteFailed { reason } if !cli.quiet => {
+                                                eprintln!("{}", i18n::format(Message::CliPasteFailed, &[("reason", &reason)]).yellow());
+                                            }
+                                            EventType::ShutdownProgress { phase, pending_segments } if cli.verbose => {
+                                                eprintln!("{}", format!("[shutdown: {:?}, {} pending]", phase, pending_segments).dimmed());
                                             }
                                             EventType::Shutdown => {
                                                 if !cli.quiet {
-                                                    eprintln!("{}", "Service shutting down".yellow());
+                                                    eprintln!("{}", tr(Message::CliServiceShuttingDown).yellow());
                                                 }
                                                 break;
                                             }
@@ -384,21 +470,37 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                                     Ok(_) => {
                                         // Non-event response in stream, ignore
                                     }
+                                    Err(e) if json => {
+                                        return Err(format!("Event stream error: {}", e).into());
+                                    }
                                     Err(e) => {
-                                        eprintln!("{}: {}", "Event stream error".red(), e);
+                                        eprintln!("{}: {}", tr(Message::CliEventStreamError).red(), e);
                                         break;
                                     }
                                 }
                             }
                         }
                     }
+
+                    if *once {
+                        stop_capture(client).await;
+                        if !transcribed {
+                            return Err("Capture ended before anything was transcribed".into());
+                        }
+                    }
                 }
                 Response::Error { message } => return Err(message.into()),
                 _ => return Err("Unexpected response".into()),
             }
         }
 
-        Commands::Status => {
+        Commands::TranscribeStdin { rate, channels } => {
+            check_model_available(client).await?;
+            let json = matches!(cli.format, OutputFormat::Json);
+            stdin_audio::run(client, *rate, *channels, json).await?;
+        }
+
+        Commands::Status { .. } => {
             let response = client
                 .request(Request::GetStatus)
                 .await
@@ -406,11 +508,15 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
 
             match response {
                 Response::Status(status) => {
-                    if matches!(cli.format, OutputFormat::Json) {
+                    if let Some(format) = cli.format.bar_format() {
+                        status_bar::print_status(&status, format);
+                    } else if matches!(cli.format, OutputFormat::Json) {
                         println!("{}", serde_json::to_string_pretty(&status).unwrap());
                     } else {
                         let capture_str = if status.capturing {
                             "capturing".green().bold()
+                        } else if status.auto_suspended {
+                            "suspended (no speech) - run `flowstt resume`".yellow()
                         } else {
                             "idle".dimmed()
                         };
@@ -432,6 +538,25 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         if let Some(error) = &status.error {
                             println!("Error: {}", error.red());
                         }
+                        if let Some(retry) = &status.capture_retry {
+                            println!(
+                                "Retrying: {} (attempt {}/{}, last error: {})",
+                                format!("in {:.1}s", retry.delay_ms as f64 / 1000.0).yellow(),
+                                retry.attempt,
+                                retry.max_attempts,
+                                retry.last_error
+                            );
+                        }
+
+                        if status.privacy_mode {
+                            println!("Privacy: {}", "on (nothing saved to disk)".yellow());
+                        }
+                        if status.output_paused {
+                            println!(
+                                "Output: {}",
+                                "paused (say \"resume dictation\" to continue)".yellow()
+                            );
+                        }
 
                         if status.capturing {
                             let speech_str = if status.in_speech {
@@ -443,6 +568,33 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                             println!("Queue depth: {}", status.queue_depth);
                         }
 
+                        if status.dropped_samples > 0 {
+                            println!(
+                                "Dropped samples: {}",
+                                status.dropped_samples.to_string().yellow()
+                            );
+                        }
+
+                        if status.worker_restarts > 0 {
+                            println!(
+                                "Transcription engine restarts: {}",
+                                status.worker_restarts.to_string().yellow()
+                            );
+                        }
+
+                        let glitches = &status.capture_glitches;
+                        if glitches.any() {
+                            let counts = format!(
+                                "{} overruns, {} underruns, {} frames dropped",
+                                glitches.overruns, glitches.underruns, glitches.dropped_frames
+                            );
+                            println!(
+                                "Capture glitches ({}): {}",
+                                glitches.backend,
+                                counts.yellow()
+                            );
+                        }
+
                         // Show runtime mode in verbose output
                         if cli.verbose {
                             let mode_str = runtime_mode().as_str();
@@ -455,6 +607,10 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             }
         }
 
+        Commands::Monitor => {
+            monitor::run_monitor(client, matches!(cli.format, OutputFormat::Json)).await?;
+        }
+
         Commands::Stop => {
             // Clear sources to stop capture
             let response = client
@@ -467,9 +623,11 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
 
             match response {
                 Response::Ok => {
-                    if !cli.quiet {
-                        println!("{}", "Capture stopped".green());
-                    }
+                    print_done(
+                        cli,
+                        "Capture stopped".green(),
+                        serde_json::json!({ "capturing": false }),
+                    );
                 }
                 Response::Error { message } => return Err(message.into()),
                 _ => return Err("Unexpected response".into()),
@@ -479,7 +637,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
         Commands::Model { action } => {
             match action {
                 Some(ModelAction::Download) => {
-                    if !cli.quiet {
+                    if !cli.quiet && !matches!(cli.format, OutputFormat::Json) {
                         println!("Downloading Whisper model...");
                     }
 
@@ -490,13 +648,19 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
 
                     match response {
                         Response::Ok => {
-                            if !cli.quiet {
-                                println!("{}", "Model download started".green());
-                            }
+                            print_done(
+                                cli,
+                                "Model download started".green(),
+                                serde_json::json!({ "download": "started" }),
+                            );
                         }
                         Response::Error { message } => {
                             if message.contains("already downloaded") {
-                                println!("{}", "Model already downloaded".yellow());
+                                print_done(
+                                    cli,
+                                    "Model already downloaded".yellow(),
+                                    serde_json::json!({ "download": "already_downloaded" }),
+                                );
                             } else {
                                 return Err(message.into());
                             }
@@ -573,6 +737,133 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             }
         }
 
+        Commands::Capabilities => {
+            let response = client
+                .request(Request::GetCapabilities)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            match response {
+                Response::Capabilities(capabilities) => {
+                    if matches!(cli.format, OutputFormat::Json) {
+                        println!("{}", serde_json::to_string_pretty(&capabilities).unwrap());
+                    } else {
+                        println!("Platform Capabilities");
+                        for (name, capability) in [
+                            ("System audio", &capabilities.system_audio),
+                            ("Echo cancellation", &capabilities.aec),
+                            ("Hotkeys", &capabilities.hotkeys),
+                            ("Clipboard", &capabilities.clipboard),
+                            ("Paste simulation", &capabilities.paste_simulation),
+                        ] {
+                            if capability.available {
+                                println!("  {}: {}", name, "available".green());
+                            } else {
+                                println!(
+                                    "  {}: {} {}",
+                                    name,
+                                    "unavailable".red(),
+                                    format!(
+                                        "({})",
+                                        capability.reason.as_deref().unwrap_or("unknown reason")
+                                    )
+                                    .dimmed()
+                                );
+                            }
+                        }
+                    }
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
+        Commands::Update {
+            action: UpdateAction::Check,
+        } => {
+            let response = client
+                .request(Request::CheckForUpdate)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            match response {
+                Response::UpdateInfo(info) => {
+                    if matches!(cli.format, OutputFormat::Json) {
+                        println!("{}", serde_json::to_string_pretty(&info).unwrap());
+                    } else if info.available {
+                        println!(
+                            "{}",
+                            format!(
+                                "FlowSTT {} is available (running {})",
+                                info.latest_version, info.current_version
+                            )
+                            .green()
+                        );
+                        if let Some(url) = &info.notes_url {
+                            println!("  Release notes: {}", url);
+                        }
+                        if let Some(path) = &info.staged_path {
+                            println!("  Installer downloaded to {}", path);
+                        }
+                    } else {
+                        println!("FlowSTT {} is up to date", info.current_version);
+                    }
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
+        Commands::Permissions { request } => {
+            let request = match request {
+                Some(kind) => Request::RequestPermission {
+                    kind: match kind {
+                        PermissionArg::Microphone => PermissionKind::Microphone,
+                        PermissionArg::Accessibility => PermissionKind::Accessibility,
+                        PermissionArg::InputMonitoring => PermissionKind::InputMonitoring,
+                    },
+                },
+                None => Request::GetPermissionsStatus,
+            };
+            let requested = match &request {
+                Request::RequestPermission { kind } => Some(*kind),
+                _ => None,
+            };
+            let response = client.request(request).await.map_err(|e| e.to_string())?;
+
+            match response {
+                Response::PermissionsStatus(status) => {
+                    if matches!(cli.format, OutputFormat::Json) {
+                        println!("{}", serde_json::to_string_pretty(&status).unwrap());
+                    } else {
+                        println!("Permissions");
+                        for (name, kind) in [
+                            ("Microphone", PermissionKind::Microphone),
+                            ("Accessibility", PermissionKind::Accessibility),
+                            ("Input monitoring", PermissionKind::InputMonitoring),
+                        ] {
+                            let state = match status.get(kind) {
+                                PermissionState::Granted => "granted".green(),
+                                PermissionState::Denied => "denied".red(),
+                                PermissionState::NotDetermined => "not requested".yellow(),
+                                PermissionState::NotRequired => "not required".dimmed(),
+                            };
+                            println!("  {}: {}", name, state);
+                        }
+                        if requested.is_some_and(|kind| !status.get(kind).allows()) {
+                            println!(
+                                "{}",
+                                "Grant it in the system prompt or settings; the service notices without a restart."
+                                    .dimmed()
+                            );
+                        }
+                    }
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
         Commands::Ping => match client.ping().await {
             Ok(true) => {
                 if matches!(cli.format, OutputFormat::Json) {
@@ -593,9 +884,11 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
 
             match response {
                 Response::Ok => {
-                    if !cli.quiet {
-                        println!("{}", "Service shutdown initiated".green());
-                    }
+                    print_done(
+                        cli,
+                        "Service shutdown initiated".green(),
+                        serde_json::json!({}),
+                    );
                 }
                 Response::Error { message } => return Err(message.into()),
                 _ => return Err("Unexpected response".into()),
@@ -620,9 +913,6 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                             TranscriptionMode::Automatic => "Automatic",
                             TranscriptionMode::PushToTalk => "Push-to-Talk",
                         };
-                        if !cli.quiet {
-                            println!("{} transcription mode: {}", "Toggled".green().bold(), mode_str);
-                        }
                         if matches!(cli.format, OutputFormat::Json) {
                             println!(
                                 "{}",
@@ -631,6 +921,8 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                                 }))
                                 .map_err(|e| e.to_string())?
                             );
+                        } else if !cli.quiet {
+                            println!("{} transcription mode: {}", "Toggled".green().bold(), mode_str);
                         }
                     }
                 }
@@ -639,88 +931,386 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             }
         }
 
+        Commands::Resume => {
+            let response = client
+                .request(Request::ResumeCapture)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            match response {
+                Response::Ok => {
+                    print_done(
+                        cli,
+                        "Capture resumed".green().bold(),
+                        serde_json::json!({ "capturing": true }),
+                    );
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
         Commands::Setup => {
             // Already handled above
             unreachable!()
         }
 
-        Commands::Config { .. } => {
-            // Already handled above
-            unreachable!()
+        Commands::Calibrate { device, write } => {
+            handle_calibrate(client, device.clone(), *write, cli).await?;
         }
 
-        Commands::Version => {
-            // Already handled above
-            unreachable!()
+        Commands::Speaker { action } => {
+            handle_speaker(client, action, cli).await?;
         }
-    }
 
-    Ok(())
-}
+        Commands::Stats {
+            week, month, all, ..
+        } => {
+            let period = if *week {
+                ActivityPeriod::Week
+            } else if *month {
+                ActivityPeriod::Month
+            } else if *all {
+                ActivityPeriod::All
+            } else {
+                ActivityPeriod::Today
+            };
+            handle_stats(client, period, cli).await?;
+        }
 
-/// Handle config subcommands. Tries IPC first, falls back to direct file access.
-async fn handle_config(
-    client: &mut Client,
-    action: &ConfigAction,
-    cli: &Cli,
-) -> Result<(), CliError> {
-    match action {
-        ConfigAction::Show => handle_config_show(client, cli).await,
-        ConfigAction::Get { key } => handle_config_get(client, key, cli).await,
-        ConfigAction::Set { key, value } => handle_config_set(client, key, value, cli).await,
-    }
-}
+        Commands::History {
+            action: HistoryAction::List,
+        } => {
+            handle_history_list(client, cli).await?;
+        }
 
-/// Retrieve config values from the service or fall back to the config file.
-async fn get_config_values(client: &mut Client) -> Result<ConfigValues, CliError> {
-    // Try connecting to the service
-    if client.connect().await.is_ok() {
-        let response = client
-            .request(Request::GetConfig)
-            .await
-            .map_err(|e| e.to_string())?;
-        match response {
-            Response::ConfigValues(values) => return Ok(values),
-            Response::Error { message } => return Err(CliError::general(message)),
-            _ => return Err(CliError::general("Unexpected response from service")),
+        Commands::History {
+            action: HistoryAction::Summary { hourly },
+        } => {
+            let group_by = if *hourly {
+                HistoryGrouping::Hour
+            } else {
+                HistoryGrouping::Day
+            };
+            handle_history_summary(client, group_by, cli).await?;
         }
-    }
 
-    // Service not running -- read from disk
-    let config = Config::load();
-    Ok(ConfigValues {
-        transcription_mode: config.transcription_mode,
-        ptt_hotkeys: config.ptt_hotkeys,
-        auto_toggle_hotkeys: config.auto_toggle_hotkeys,
-        auto_paste_enabled: config.auto_paste_enabled,
-        auto_paste_delay_ms: config.auto_paste_delay_ms,
-    })
-}
+        Commands::History {
+            action: HistoryAction::Play { id, from },
+        } => {
+            handle_history_play(client, id, *from, cli).await?;
+        }
 
-/// Validate that a config key name is recognized.
-fn validate_config_key(key: &str) -> Result<(), CliError> {
-    if VALID_CONFIG_KEYS.contains(&key) {
-        Ok(())
-    } else {
-        Err(CliError::usage(format!(
-            "Unknown configuration key '{}'. Valid keys: {}",
-            key,
-            VALID_CONFIG_KEYS.join(", ")
-        )))
-    }
-}
+        Commands::History {
+            action: HistoryAction::Edit { id, text },
+        } => {
+            let response = client
+                .request(Request::EditHistoryEntry {
+                    id: id.clone(),
+                    text: text.clone(),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
 
-/// Format hotkeys for human-readable display.
-fn format_hotkeys_display(hotkeys: &[HotkeyCombination]) -> String {
-    if hotkeys.is_empty() {
-        "(none)".to_string()
-    } else {
-        hotkeys
-            .iter()
-            .map(|h| h.display())
-            .collect::<Vec<_>>()
-            .join(", ")
-    }
+            match response {
+                Response::Ok => {
+                    print_done(
+                        cli,
+                        format!("{} {}", "Updated".green().bold(), id),
+                        serde_json::json!({ "id": id }),
+                    );
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
+        Commands::History {
+            action: HistoryAction::Retranscribe { id, model },
+        } => {
+            handle_history_retranscribe(client, id, model.clone(), cli).await?;
+        }
+
+        Commands::Dictation { action } => {
+            let active = matches!(action, DictationAction::Start);
+            let response = client
+                .request(Request::SetDictation { active })
+                .await
+                .map_err(|e| e.to_string())?;
+
+            match response {
+                Response::Ok => {
+                    let status = if active {
+                        "Dictation session started"
+                    } else {
+                        "Dictation session ended"
+                    };
+                    print_done(
+                        cli,
+                        status.green().bold(),
+                        serde_json::json!({ "dictation": active }),
+                    );
+                }
+                Response::Error { message } => return Err(message.into()),
+                _ => return Err("Unexpected response".into()),
+            }
+        }
+
+        Commands::Meeting {
+            action: MeetingAction::Start { title },
+        } => {
+            let response = client
+                .request(Request::StartMeeting {
+                    title: title.clone(),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+            print_meeting_response(response, "Meeting started", cli)?;
+        }
+
+        Commands::Meeting {
+            action: MeetingAction::Stop,
+        } => {
+            let response = client
+                .request(Request::StopMeeting)
+                .await
+                .map_err(|e| e.to_string())?;
+            print_meeting_response(response, "Meeting ended", cli)?;
+        }
+
+        Commands::Meeting {
+            action: MeetingAction::List,
+        } => {
+            handle_meeting_list(client, cli).await?;
+        }
+
+        Commands::Meeting {
+            action: MeetingAction::Export { id, file },
+        } => {
+            handle_meeting_export(client, id, file.as_deref(), cli).await?;
+        }
+
+        Commands::Rules {
+            action: RulesAction::Export { file },
+        } => {
+            handle_rules_export(client, file.as_deref(), cli).await?;
+        }
+
+        Commands::Rules {
+            action: RulesAction::Import { file, replace },
+        } => {
+            handle_rules_import(client, file, *replace, cli).await?;
+        }
+
+        Commands::Backup {
+            action: BackupAction::Create { file, recordings },
+        } => {
+            handle_backup_create(client, file, *recordings, cli).await?;
+        }
+
+        Commands::Backup {
+            action: BackupAction::Restore { file },
+        } => {
+            handle_backup_restore(client, file, cli).await?;
+        }
+
+        Commands::Test {
+            action: TestAction::Run { dir, manifest },
+        } => {
+            handle_test_run(client, dir, manifest.as_deref(), cli).await?;
+        }
+
+        Commands::Bench {
+            action: BenchAction::Latency { file, runs },
+        } => {
+            handle_bench_latency(client, file.as_deref(), *runs, cli).await?;
+        }
+
+        Commands::Config { .. } | Commands::Debug { .. } => {
+            // Already handled above
+            unreachable!()
+        }
+
+        Commands::Version | Commands::Completions { .. } | Commands::Manpage { .. } => {
+            // Already handled above
+            unreachable!()
+        }
+    }
+
+    Ok(())
+}
+
+/// Fail with the device-not-found exit code unless every given device exists.
+async fn check_devices_exist(
+    client: &mut Client,
+    device_ids: impl IntoIterator<Item = &str>,
+) -> Result<(), CliError> {
+    let response = client
+        .request(Request::ListDevices { source_type: None })
+        .await
+        .map_err(|e| e.to_string())?;
+    let devices = match response {
+        Response::Devices { devices } => devices,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    for id in device_ids {
+        if !devices.iter().any(|device| device.id == id) {
+            return Err(CliError::device_not_found(id));
+        }
+    }
+    Ok(())
+}
+
+/// Fail with the model-missing exit code unless the Whisper model is available.
+async fn check_model_available(client: &mut Client) -> Result<(), CliError> {
+    let response = client
+        .request(Request::GetModelStatus)
+        .await
+        .map_err(|e| e.to_string())?;
+    match response {
+        Response::ModelStatus(status) if status.available => Ok(()),
+        Response::ModelStatus(_) => Err(CliError::model_missing()),
+        Response::Error { message } => Err(message.into()),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Stop capture, as `flowstt stop` does.
+async fn stop_capture(client: &mut Client) {
+    let _ = client
+        .request(Request::SetSources {
+            source1_id: None,
+            source2_id: None,
+        })
+        .await;
+}
+
+/// Whether `transcribe --format json` prints an event.
+fn is_transcribe_event(event: &EventType) -> bool {
+    matches!(
+        event,
+        EventType::TranscriptionComplete(_)
+            | EventType::SpeechMetrics(_)
+            | EventType::SpeechStarted
+            | EventType::SpeechEnded { .. }
+            | EventType::CaptureStateChanged { .. }
+            | EventType::LoadSheddingChanged { .. }
+            | EventType::LowDiskSpace { .. }
+            | EventType::PasteFailed { .. }
+            | EventType::ShutdownProgress { .. }
+            | EventType::Shutdown
+    )
+}
+
+/// Report a command that has no output of its own: `message` in text mode
+/// (unless quiet), or `{"status": "ok"}` plus `fields` in JSON mode.
+fn print_done(cli: &Cli, message: impl std::fmt::Display, fields: serde_json::Value) {
+    if matches!(cli.format, OutputFormat::Json) {
+        let mut result = serde_json::json!({ "status": "ok" });
+        if let (Some(result), serde_json::Value::Object(fields)) = (result.as_object_mut(), fields)
+        {
+            result.extend(fields);
+        }
+        println!("{}", result);
+    } else if !cli.quiet {
+        println!("{}", message);
+    }
+}
+
+/// Print the man page for `flowstt`, or write it and one page per subcommand
+/// (`flowstt-history.1`, `flowstt-history-list.1`, ...) to `dir`.
+fn write_man_pages(dir: Option<&Path>) -> Result<(), CliError> {
+    let command = Cli::command();
+    let Some(dir) = dir else {
+        return clap_mangen::Man::new(command)
+            .render(&mut std::io::stdout())
+            .map_err(|e| format!("Failed to write man page: {}", e).into());
+    };
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    clap_mangen::generate_to(command, dir)
+        .map_err(|e| format!("Failed to write man pages to {}: {}", dir.display(), e))?;
+    println!("Man pages written to {}", dir.display());
+    Ok(())
+}
+
+/// Handle config subcommands. Tries IPC first, falls back to direct file access.
+async fn handle_config(
+    client: &mut Client,
+    action: &ConfigAction,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    match action {
+        ConfigAction::Show => handle_config_show(client, cli).await,
+        ConfigAction::Get { key } => handle_config_get(client, key, cli).await,
+        ConfigAction::Set { key, value } => handle_config_set(client, key, value, cli).await,
+    }
+}
+
+/// Retrieve config values from the service or fall back to the config file.
+async fn get_config_values(client: &mut Client) -> Result<ConfigValues, CliError> {
+    // Try connecting to the service
+    if client.connect().await.is_ok() {
+        let response = client
+            .request(Request::GetConfig)
+            .await
+            .map_err(|e| e.to_string())?;
+        match response {
+            Response::ConfigValues(values) => return Ok(values),
+            Response::Error { message } => return Err(CliError::general(message)),
+            _ => return Err(CliError::general("Unexpected response from service")),
+        }
+    }
+
+    // Service not running -- read from disk
+    let config = Config::load();
+    Ok(ConfigValues {
+        transcription_mode: config.transcription_mode,
+        ptt_hotkeys: config.ptt_hotkeys,
+        auto_toggle_hotkeys: config.auto_toggle_hotkeys,
+        auto_paste_enabled: config.auto_paste_enabled,
+        auto_paste_delay_ms: config.auto_paste_delay_ms,
+        // Only the service can check the login registration
+        autostart_enabled: None,
+        language: config.language,
+    })
+}
+
+/// Format the autostart setting, which is unknown without the service.
+fn format_autostart(enabled: Option<bool>) -> String {
+    match enabled {
+        Some(enabled) => enabled.to_string(),
+        None => "unknown (service not running)".to_string(),
+    }
+}
+
+/// Validate that a config key name is recognized.
+fn validate_config_key(key: &str) -> Result<(), CliError> {
+    if VALID_CONFIG_KEYS.contains(&key) {
+        Ok(())
+    } else {
+        Err(CliError::usage(format!(
+            "Unknown configuration key '{}'. Valid keys: {}",
+            key,
+            VALID_CONFIG_KEYS.join(", ")
+        )))
+    }
+}
+
+/// Format hotkeys for human-readable display.
+fn format_hotkeys_display(hotkeys: &[HotkeyCombination]) -> String {
+    if hotkeys.is_empty() {
+        "(none)".to_string()
+    } else {
+        hotkeys
+            .iter()
+            .map(|h| h.display())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 /// Handle `config show` -- display all config values.
@@ -748,6 +1338,22 @@ async fn handle_config_show(client: &mut Client, cli: &Cli) -> Result<(), CliErr
             "auto_toggle_hotkeys".bold(),
             format_hotkeys_display(&values.auto_toggle_hotkeys)
         );
+        println!(
+            "{}: {}",
+            "auto_paste_enabled".bold(),
+            values.auto_paste_enabled
+        );
+        println!(
+            "{}: {}",
+            "auto_paste_delay_ms".bold(),
+            values.auto_paste_delay_ms
+        );
+        println!(
+            "{}: {}",
+            "autostart".bold(),
+            format_autostart(values.autostart_enabled)
+        );
+        println!("{}: {}", "language".bold(), values.language.as_str());
     }
 
     Ok(())
@@ -801,6 +1407,29 @@ async fn handle_config_get(
                 println!("{}", format_hotkeys_display(&values.auto_toggle_hotkeys));
             }
         }
+        // Plain values print the same in either format
+        "auto_paste_enabled" => println!("{}", values.auto_paste_enabled),
+        "auto_paste_delay_ms" => println!("{}", values.auto_paste_delay_ms),
+        "autostart" => {
+            if matches!(cli.format, OutputFormat::Json) {
+                println!(
+                    "{}",
+                    serde_json::to_value(values.autostart_enabled).map_err(|e| e.to_string())?
+                );
+            } else {
+                println!("{}", format_autostart(values.autostart_enabled));
+            }
+        }
+        "language" => {
+            if matches!(cli.format, OutputFormat::Json) {
+                println!(
+                    "{}",
+                    serde_json::to_value(values.language).map_err(|e| e.to_string())?
+                );
+            } else {
+                println!("{}", values.language.as_str());
+            }
+        }
         _ => unreachable!(), // validate_config_key already checked
     }
 
@@ -844,10 +1473,7 @@ async fn handle_config_set(
                 }
             } else {
                 // Offline: write directly to config file
-                let mut config = Config::load();
-                config.transcription_mode = mode;
-                config
-                    .save()
+                settings::set(key::TRANSCRIPTION_MODE, mode)
                     .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
             }
 
@@ -860,14 +1486,19 @@ async fn handle_config_set(
             }
         }
         "ptt_hotkeys" => {
-            let hotkeys: Vec<HotkeyCombination> =
-                serde_json::from_str(value).map_err(|e| {
-                    CliError::usage(format!(
-                        "Invalid JSON for ptt_hotkeys: {}\nExpected format: {}",
-                        e,
-                        r#"'[{"keys":["left_control","left_alt"]}]'"#
-                    ))
-                })?;
+            let layout = if service_available {
+                letter_layout(client).await
+            } else {
+                LetterLayout::default()
+            };
+            let hotkeys = parse_hotkeys(value, layout).map_err(|e| {
+                CliError::usage(format!(
+                    "Invalid value for ptt_hotkeys: {}\nExpected key names such as {} or JSON such as {}",
+                    e,
+                    "'ctrl+alt'",
+                    r#"'[{"keys":["left_control","left_alt"]}]'"#
+                ))
+            })?;
 
             if service_available {
                 let response = client
@@ -883,10 +1514,7 @@ async fn handle_config_set(
                 }
             } else {
                 // Offline: write directly to config file
-                let mut config = Config::load();
-                config.ptt_hotkeys = hotkeys.clone();
-                config
-                    .save()
+                settings::set(key::PTT_HOTKEYS, hotkeys.clone())
                     .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
             }
 
@@ -902,10 +1530,16 @@ async fn handle_config_set(
             let hotkeys: Vec<HotkeyCombination> = if value == "null" || value == "none" || value == "[]" {
                 vec![]
             } else {
-                serde_json::from_str(value).map_err(|e| {
+                let layout = if service_available {
+                    letter_layout(client).await
+                } else {
+                    LetterLayout::default()
+                };
+                parse_hotkeys(value, layout).map_err(|e| {
                     CliError::usage(format!(
-                        "Invalid JSON for auto_toggle_hotkeys: {}\nExpected format: {} or []",
+                        "Invalid value for auto_toggle_hotkeys: {}\nExpected key names such as {}, JSON such as {}, or []",
                         e,
+                        "'f13'",
                         r#"[{"keys":["f13"]}]"#
                     ))
                 })?
@@ -925,10 +1559,7 @@ async fn handle_config_set(
                 }
             } else {
                 // Offline: write directly to config file
-                let mut config = Config::load();
-                config.auto_toggle_hotkeys = hotkeys.clone();
-                config
-                    .save()
+                settings::set(key::AUTO_TOGGLE_HOTKEYS, hotkeys.clone())
                     .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
             }
 
@@ -940,52 +1571,243 @@ async fn handle_config_set(
                 );
             }
         }
-        _ => unreachable!(), // validate_config_key already checked
-    }
-
-    Ok(())
-}
-
-/// Handle the `setup` interactive wizard command.
-async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
-    use std::io::{self, BufRead, IsTerminal, Write};
-
-    // TTY detection
-    if !std::io::stdin().is_terminal() {
-        return Err(CliError::new(
-            "Setup requires an interactive terminal (TTY)",
-            1,
-        ));
-    }
+        "auto_paste_enabled" => {
+            let enabled = parse_bool(value).ok_or_else(|| {
+                CliError::usage(format!(
+                    "Invalid value '{}' for auto_paste_enabled. Expected: true, false",
+                    value
+                ))
+            })?;
+            if enabled && service_available && !cli.quiet {
+                warn_if_unsupported(client, "Paste simulation", |c| &c.paste_simulation).await;
+            }
 
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+            if service_available {
+                let response = client
+                    .request(Request::SetAutoPaste { enabled })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match response {
+                    Response::Ok => {}
+                    Response::Error { message } => return Err(CliError::general(message)),
+                    _ => return Err(CliError::general("Unexpected response")),
+                }
+            } else {
+                // Offline: write directly to config file
+                settings::set(key::AUTO_PASTE_ENABLED, enabled)
+                    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+            }
 
-    // Check if already configured
-    if !Config::needs_setup() {
-        print!(
-            "{}: Setup has already been completed. Run again? [y/N] ",
-            "Warning".yellow().bold()
-        );
-        stdout.flush().unwrap();
-        let mut answer = String::new();
-        stdin.lock().read_line(&mut answer).unwrap();
-        if !answer.trim().eq_ignore_ascii_case("y") {
-            println!("Setup cancelled.");
-            return Ok(());
+            if !cli.quiet {
+                println!("{} auto_paste_enabled = {}", "Set".green().bold(), enabled);
+            }
         }
-    }
-
-    println!("\n{}\n", "FlowSTT Setup".bold());
+        "auto_paste_delay_ms" => {
+            let delay_ms = value
+                .parse::<u32>()
+                .ok()
+                .filter(|ms| *ms <= MAX_AUTO_PASTE_DELAY_MS)
+                .ok_or_else(|| {
+                    CliError::usage(format!(
+                        "Invalid value '{}' for auto_paste_delay_ms. Expected: 0 to {}",
+                        value, MAX_AUTO_PASTE_DELAY_MS
+                    ))
+                })?;
 
-    // Connect to service (needed for model download and device listing)
-    client
-        .connect_or_spawn()
+            if service_available {
+                let response = client
+                    .request(Request::SetAutoPasteDelay { delay_ms })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match response {
+                    Response::Ok => {}
+                    Response::Error { message } => return Err(CliError::general(message)),
+                    _ => return Err(CliError::general("Unexpected response")),
+                }
+            } else {
+                // Offline: write directly to config file
+                settings::set(key::AUTO_PASTE_DELAY_MS, delay_ms)
+                    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+            }
+
+            if !cli.quiet {
+                println!(
+                    "{} auto_paste_delay_ms = {}",
+                    "Set".green().bold(),
+                    delay_ms
+                );
+            }
+        }
+        "autostart" => {
+            let enabled = parse_bool(value).ok_or_else(|| {
+                CliError::usage(format!(
+                    "Invalid value '{}' for autostart. Expected: true, false",
+                    value
+                ))
+            })?;
+            // Registering with the desktop is done by the service
+            if !service_available {
+                return Err(CliError::general(
+                    "FlowSTT must be running to change autostart",
+                ));
+            }
+
+            let response = client
+                .request(Request::SetAutostart { enabled })
+                .await
+                .map_err(|e| e.to_string())?;
+            match response {
+                Response::Ok => {}
+                Response::Error { message } => return Err(CliError::general(message)),
+                _ => return Err(CliError::general("Unexpected response")),
+            }
+
+            if !cli.quiet {
+                println!("{} autostart = {}", "Set".green().bold(), enabled);
+            }
+        }
+        "language" => {
+            let language = Language::parse(value).ok_or_else(|| {
+                let supported: Vec<&str> = Language::SUPPORTED.iter().map(|l| l.as_str()).collect();
+                CliError::usage(format!(
+                    "Invalid value '{}' for language. Expected: auto, {}",
+                    value,
+                    supported.join(", ")
+                ))
+            })?;
+            // Only read at startup, so the config file is written even while
+            // the service runs
+            settings::set(key::LANGUAGE, language)
+                .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+
+            if !cli.quiet {
+                println!("{} language = {}", "Set".green().bold(), language.as_str());
+                if service_available {
+                    println!("Restart FlowSTT for the tray and notifications to use it");
+                }
+            }
+        }
+        _ => unreachable!(), // validate_config_key already checked
+    }
+
+    Ok(())
+}
+
+/// Warn on stderr when a platform feature doesn't work in this session.
+/// Failures to query the service are ignored.
+async fn warn_if_unsupported(
+    client: &mut Client,
+    feature: &str,
+    capability: fn(&Capabilities) -> &Capability,
+) {
+    if let Ok(Response::Capabilities(capabilities)) = client.request(Request::GetCapabilities).await
+    {
+        let capability = capability(&capabilities);
+        if !capability.available {
+            eprintln!(
+                "{}: {} is unavailable: {}",
+                "Warning".yellow().bold(),
+                feature,
+                capability.reason.as_deref().unwrap_or("unknown reason")
+            );
+        }
+    }
+}
+
+/// Parse a boolean config value.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => Some(true),
+        "false" | "off" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse hotkeys given as key names (`ctrl+shift+f5`, with several
+/// combinations separated by commas) or as JSON.
+fn parse_hotkeys(value: &str, layout: LetterLayout) -> Result<Vec<HotkeyCombination>, String> {
+    if value.trim_start().starts_with('[') {
+        return serde_json::from_str(value).map_err(|e| e.to_string());
+    }
+    value
+        .split(',')
+        .map(|combo| HotkeyCombination::parse(combo, layout))
+        .collect()
+}
+
+/// Letter arrangement of the service's keyboard layout, or QWERTY if it
+/// can't be read.
+async fn letter_layout(client: &mut Client) -> LetterLayout {
+    match client.request(Request::GetPttStatus).await {
+        Ok(Response::PttStatus(status)) => status.letter_layout,
+        _ => LetterLayout::default(),
+    }
+}
+
+/// Handle the `setup` interactive wizard command.
+async fn handle_setup(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
+    use std::io::{self, BufRead, IsTerminal, Write};
+
+    // TTY detection
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::general(
+            "Setup requires an interactive terminal (TTY)",
+        ));
+    }
+
+    // With `--format json` the wizard talks on stderr so that stdout only
+    // carries the result
+    let json = matches!(cli.format, OutputFormat::Json);
+    macro_rules! say {
+        ($($arg:tt)*) => {
+            if json {
+                eprintln!($($arg)*);
+            } else {
+                println!($($arg)*);
+            }
+        };
+    }
+    macro_rules! ask {
+        ($($arg:tt)*) => {
+            if json {
+                eprint!($($arg)*);
+                io::stderr().flush().unwrap();
+            } else {
+                print!($($arg)*);
+                io::stdout().flush().unwrap();
+            }
+        };
+    }
+
+    let stdin = io::stdin();
+
+    // Check if already configured
+    if !Config::needs_setup() {
+        ask!(
+            "{}: Setup has already been completed. Run again? [y/N] ",
+            "Warning".yellow().bold()
+        );
+        let mut answer = String::new();
+        stdin.lock().read_line(&mut answer).unwrap();
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            say!("Setup cancelled.");
+            if json {
+                println!("{}", serde_json::json!({ "status": "cancelled" }));
+            }
+            return Ok(());
+        }
+    }
+
+    say!("\n{}\n", "FlowSTT Setup".bold());
+
+    // Connect to service (needed for model download and device listing)
+    client
+        .connect_or_spawn()
         .await
-        .map_err(|e| format!("Failed to connect to service: {}", e))?;
+        .map_err(|e| CliError::unavailable(format!("Failed to connect to service: {}", e)))?;
 
     // --- Step 1: Model Download ---
-    println!("{}", "Step 1: Speech Model".bold());
+    say!("{}", "Step 1: Speech Model".bold());
     let model_response = client
         .request(Request::GetModelStatus)
         .await
@@ -993,16 +1815,15 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
 
     match model_response {
         Response::ModelStatus(status) if status.available => {
-            println!("  Model: {}", "already downloaded".green());
-            println!("  Path: {}", status.path.dimmed());
+            say!("  Model: {}", "already downloaded".green());
+            say!("  Path: {}", status.path.dimmed());
         }
         _ => {
-            print!("  Download Whisper model (~145 MB)? [Y/n] ");
-            stdout.flush().unwrap();
+            ask!("  Download Whisper model (~145 MB)? [Y/n] ");
             let mut answer = String::new();
             stdin.lock().read_line(&mut answer).unwrap();
             if answer.trim().is_empty() || answer.trim().eq_ignore_ascii_case("y") {
-                println!("  Downloading...");
+                say!("  Downloading...");
                 let response = client
                     .request(Request::DownloadModel)
                     .await
@@ -1016,27 +1837,26 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
                                 client.request(Request::GetModelStatus).await
                             {
                                 if s.available {
-                                    println!("  {}", "Download complete!".green());
+                                    say!("  {}", "Download complete!".green());
                                     break;
                                 }
                             }
-                            print!(".");
-                            stdout.flush().unwrap();
+                            ask!(".");
                         }
                     }
                     Response::Error { message } => {
-                        println!("  Download failed: {}", message.red());
+                        say!("  Download failed: {}", message.red());
                     }
                     _ => {}
                 }
             } else {
-                println!("  Skipping model download.");
+                say!("  Skipping model download.");
             }
         }
     }
 
     // --- Step 2: Device Selection ---
-    println!("\n{}", "Step 2: Microphone".bold());
+    say!("\n{}", "Step 2: Microphone".bold());
     let device_response = client
         .request(Request::ListDevices {
             source_type: Some(AudioSourceType::Input),
@@ -1048,38 +1868,71 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
 
     match device_response {
         Response::Devices { devices } if !devices.is_empty() => {
-            println!("  Available input devices:");
+            say!("  Available input devices:");
             for (i, device) in devices.iter().enumerate() {
-                println!("    {}: {}", (i + 1).to_string().cyan(), device.name);
+                say!("    {}: {}", (i + 1).to_string().cyan(), device.name);
             }
-            print!("  Select device [1-{}]: ", devices.len());
-            stdout.flush().unwrap();
+            ask!("  Select device [1-{}]: ", devices.len());
             let mut answer = String::new();
             stdin.lock().read_line(&mut answer).unwrap();
             if let Ok(idx) = answer.trim().parse::<usize>() {
                 if idx >= 1 && idx <= devices.len() {
                     selected_device_id = Some(devices[idx - 1].id.clone());
-                    println!("  Selected: {}", devices[idx - 1].name.green());
+                    say!("  Selected: {}", devices[idx - 1].name.green());
                 }
             }
             if selected_device_id.is_none() {
-                println!("  {}", "No device selected, skipping.".yellow());
+                say!("  {}", "No device selected, skipping.".yellow());
+            }
+
+            if let Some(ref device_id) = selected_device_id {
+                say!("  Live input level - speak and check that the meter moves:");
+                if let Err(e) = show_level_meter(client, device_id, json).await {
+                    say!("  {}: Level meter unavailable: {}", "Warning".yellow(), e);
+                }
+
+                say!("  Testing microphone - speak normally for 2 seconds...");
+                let response = client
+                    .request(Request::RunAudioSelfTest {
+                        device_id: device_id.clone(),
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match response {
+                    Response::AudioSelfTest(report) => {
+                        say!(
+                            "  Level: {:.1} dB (peak {:.1} dB, noise floor {:.1} dB)",
+                            report.rms_db,
+                            report.peak_db,
+                            report.noise_floor_db
+                        );
+                        if report.is_ok() {
+                            say!("  {}", "Microphone looks good.".green());
+                        }
+                        for warning in &report.warnings {
+                            say!("  {}: {}", "Warning".yellow().bold(), warning);
+                        }
+                    }
+                    Response::Error { message } => {
+                        say!("  {}: Self-test failed: {}", "Warning".yellow(), message);
+                    }
+                    _ => {}
+                }
             }
         }
         _ => {
-            println!("  {}", "No input devices found.".yellow());
+            say!("  {}", "No input devices found.".yellow());
         }
     }
 
     // --- Step 3: Transcription Mode ---
-    println!("\n{}", "Step 3: Transcription Mode".bold());
-    println!("  1: {} - always listening, VAD-triggered", "Automatic".cyan());
-    println!(
+    say!("\n{}", "Step 3: Transcription Mode".bold());
+    say!("  1: {} - always listening, VAD-triggered", "Automatic".cyan());
+    say!(
         "  2: {} - hold a key to transcribe (default)",
         "Push-to-Talk".cyan()
     );
-    print!("  Select mode [1-2, default=2]: ");
-    stdout.flush().unwrap();
+    ask!("  Select mode [1-2, default=2]: ");
     let mut answer = String::new();
     stdin.lock().read_line(&mut answer).unwrap();
 
@@ -1092,83 +1945,113 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
         TranscriptionMode::Automatic => "Automatic",
         TranscriptionMode::PushToTalk => "Push-to-Talk",
     };
-    println!("  Selected: {}", mode_name.green());
+    say!("  Selected: {}", mode_name.green());
 
     let mut hotkey = HotkeyCombination::new(vec![KeyCode::RightShift, KeyCode::RightControl]);
+    // Letters are typed as labelled on the user's keyboard
+    let layout = letter_layout(client).await;
 
     if mode == TranscriptionMode::PushToTalk {
-        print!(
-            "  PTT key [default=RightShift+RightControl, or type key name e.g. f5, left_control]: "
-        );
-        stdout.flush().unwrap();
+        ask!("  PTT key [default=RightShift+RightControl, or type key names e.g. f5, ctrl+alt]: ");
         let mut key_answer = String::new();
         stdin.lock().read_line(&mut key_answer).unwrap();
         let key_str = key_answer.trim();
         if !key_str.is_empty() {
-            // Try to parse the key name via serde
-            let key_json = format!("\"{}\"", key_str);
-            match serde_json::from_str::<KeyCode>(&key_json) {
-                Ok(key) => {
-                    hotkey = HotkeyCombination::single(key);
-                    println!("  PTT key: {}", key_str.green());
+            match HotkeyCombination::parse(key_str, layout) {
+                Ok(combo) => {
+                    say!("  PTT key: {}", combo.display().green());
+                    hotkey = combo;
                 }
-                Err(_) => {
-                    println!(
-                        "  {}: Unknown key '{}', using RightShift+RightControl",
+                Err(e) => {
+                    say!(
+                        "  {}: {}, using RightShift+RightControl",
                         "Warning".yellow(),
-                        key_str
+                        e
                     );
                 }
             }
         } else {
-            println!("  PTT key: {}", "RightShift+RightControl".green());
+            say!("  PTT key: {}", "RightShift+RightControl".green());
+        }
+
+        // Confirm the key is actually seen by the hotkey backend
+        loop {
+            say!(
+                "  Press and release the PTT key to test it (waiting {}s)...",
+                PTT_TEST_TIMEOUT_SECS
+            );
+            let response = client
+                .request(Request::TestPttHotkey {
+                    hotkeys: vec![hotkey.clone()],
+                    timeout_ms: PTT_TEST_TIMEOUT_SECS * 1000,
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+            match response {
+                Response::PttHotkeyTest { detected: true } => {
+                    say!("  {}", "PTT key detected.".green());
+                    break;
+                }
+                Response::PttHotkeyTest { detected: false } => {
+                    say!("  {}: No key press detected.", "Warning".yellow());
+                    if cfg!(target_os = "macos") {
+                        say!("  Check that FlowSTT has Accessibility permission.");
+                    }
+                }
+                Response::Error { message } => {
+                    say!("  {}: PTT test failed: {}", "Warning".yellow(), message);
+                }
+                _ => break,
+            }
+
+            ask!("  Try again? [Y/n] ");
+            let mut retry = String::new();
+            stdin.lock().read_line(&mut retry).unwrap();
+            if retry.trim().eq_ignore_ascii_case("n") {
+                say!("  Skipping PTT key test.");
+                break;
+            }
         }
     }
 
     // --- Step 4: Auto-mode Toggle Hotkey ---
     let mut toggle_hotkeys: Vec<HotkeyCombination> = vec![HotkeyCombination::single(KeyCode::F13)];
     
-    println!("\n{}", "Step 4: Auto-mode Toggle Hotkey".bold());
-    println!("  This hotkey toggles between Automatic and Push-to-Talk modes.");
-    print!("  Toggle key [default=F13, or type key name, or 'none' to disable]: ");
-    stdout.flush().unwrap();
+    say!("\n{}", "Step 4: Auto-mode Toggle Hotkey".bold());
+    say!("  This hotkey toggles between Automatic and Push-to-Talk modes.");
+    ask!("  Toggle key [default=F13, or type key name, or 'none' to disable]: ");
     let mut toggle_answer = String::new();
     stdin.lock().read_line(&mut toggle_answer).unwrap();
     let toggle_str = toggle_answer.trim();
     
     if toggle_str.eq_ignore_ascii_case("none") || toggle_str.eq_ignore_ascii_case("disabled") {
         toggle_hotkeys = vec![];
-        println!("  Toggle hotkey: {}", "disabled".yellow());
+        say!("  Toggle hotkey: {}", "disabled".yellow());
     } else if !toggle_str.is_empty() {
-        let key_json = format!("\"{}\"", toggle_str);
-        match serde_json::from_str::<KeyCode>(&key_json) {
-            Ok(key) => {
-                toggle_hotkeys = vec![HotkeyCombination::single(key)];
-                println!("  Toggle hotkey: {}", toggle_str.green());
+        match HotkeyCombination::parse(toggle_str, layout) {
+            Ok(combo) => {
+                say!("  Toggle hotkey: {}", combo.display().green());
+                toggle_hotkeys = vec![combo];
             }
-            Err(_) => {
-                println!(
-                    "  {}: Unknown key '{}', using F13",
-                    "Warning".yellow(),
-                    toggle_str
-                );
+            Err(e) => {
+                say!("  {}: {}, using F13", "Warning".yellow(), e);
             }
         }
     } else {
-        println!("  Toggle hotkey: {}", "F13".green());
+        say!("  Toggle hotkey: {}", "F13".green());
     }
 
     // --- Save config ---
-    println!("\n{}", "Saving configuration...".bold());
-    let config = Config {
-        transcription_mode: mode,
-        ptt_hotkeys: vec![hotkey],
-        auto_toggle_hotkeys: toggle_hotkeys,
-        ..Config::default_with_hotkeys()
-    };
-    config
-        .save()
-        .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+    say!("\n{}", "Saving configuration...".bold());
+    let config = settings::update(|config| {
+        *config = Config {
+            transcription_mode: mode,
+            ptt_hotkeys: vec![hotkey],
+            auto_toggle_hotkeys: toggle_hotkeys,
+            ..Config::default_with_hotkeys()
+        };
+    })
+    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
 
     // Configure service with chosen device
     if let Some(ref device_id) = selected_device_id {
@@ -1185,11 +2068,1312 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
         .request(Request::SetTranscriptionMode { mode })
         .await;
 
-    println!("\n{}", "Setup complete!".green().bold());
-    println!(
+    say!("\n{}", "Setup complete!".green().bold());
+    say!(
         "  Config saved to: {}",
         Config::config_path().display().to_string().dimmed()
     );
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "ok",
+                "config_path": Config::config_path(),
+                "device_id": selected_device_id,
+                "transcription_mode": config.transcription_mode,
+                "ptt_hotkeys": config.ptt_hotkeys,
+                "auto_toggle_hotkeys": config.auto_toggle_hotkeys,
+            })
+        );
+    }
+
+    Ok(())
+}
+
+/// How long the setup wizard shows the live input level meter
+const LEVEL_METER_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Width of the level meter bar in characters
+const LEVEL_METER_WIDTH: usize = 30;
+
+/// Level shown as an empty meter
+const LEVEL_METER_FLOOR_DB: f32 = -60.0;
+
+/// Level above which the meter warns of clipping
+const LEVEL_METER_HOT_DB: f32 = -6.0;
+
+/// How long the setup wizard waits for the PTT key
+const PTT_TEST_TIMEOUT_SECS: u64 = 15;
+
+/// Show a live input level meter for a device using a test capture, on
+/// stderr if `to_stderr` is set.
+async fn show_level_meter(
+    client: &mut Client,
+    device_id: &str,
+    to_stderr: bool,
+) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut out: Box<dyn Write> = if to_stderr {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+
+    // Level updates are delivered as events on a separate connection
+    let mut event_client = Client::new();
+    event_client
+        .connect_or_spawn()
+        .await
+        .map_err(|e| format!("Failed to connect event client: {}", e))?;
+    event_client
+        .subscribe_events(false)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let response = client
+        .request(Request::TestAudioDevice {
+            device_id: device_id.to_string(),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Response::Error { message } = response {
+        return Err(message);
+    }
+
+    let deadline = tokio::time::Instant::now() + LEVEL_METER_DURATION;
+    let mut result = Ok(());
+    loop {
+        match tokio::time::timeout_at(deadline, event_client.read_event()).await {
+            Ok(Ok(Response::Event { event })) => {
+                if let EventType::AudioLevelUpdate {
+                    device_id: id,
+                    level_db,
+                } = event
+                {
+                    if id == device_id {
+                        let _ = write!(out, "\r  {} {:6.1} dB", level_bar(level_db), level_db);
+                        let _ = out.flush();
+                    }
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                result = Err(e.to_string());
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+    let _ = writeln!(out);
+
+    let _ = client.request(Request::StopTestAudioDevice).await;
+    result
+}
+
+/// Render a level in dB as a fixed-width bar.
+fn level_bar(level_db: f32) -> String {
+    let fraction = ((level_db - LEVEL_METER_FLOOR_DB) / -LEVEL_METER_FLOOR_DB).clamp(0.0, 1.0);
+    let filled = (fraction * LEVEL_METER_WIDTH as f32).round() as usize;
+    let bar = format!(
+        "{}{}",
+        "#".repeat(filled),
+        "-".repeat(LEVEL_METER_WIDTH - filled)
+    );
+    if level_db > LEVEL_METER_HOT_DB {
+        bar.yellow().to_string()
+    } else {
+        bar.green().to_string()
+    }
+}
+
+/// Sentence the user reads aloud during calibration.
+const CALIBRATION_SENTENCE: &str = "The quick brown fox jumps over the lazy dog.";
+
+/// Handle the calibrate command: measure silence and speech, then suggest settings.
+async fn handle_calibrate(
+    client: &mut Client,
+    device: Option<String>,
+    write: bool,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    use std::io::{self, BufRead, IsTerminal, Write};
+
+    let config = Config::load();
+    let device_id = device
+        .or_else(|| config.preferred_source1_id.clone())
+        .ok_or_else(|| {
+            CliError::usage("No device specified. Use --device or run 'flowstt setup' first.")
+        })?;
+    check_devices_exist(client, [device_id.as_str()]).await?;
+
+    let interactive = std::io::stdin().is_terminal();
+    let json = matches!(cli.format, OutputFormat::Json);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    let mut wait_for_enter = |prompt: &str| {
+        if !json {
+            println!("{}", prompt);
+        }
+        if interactive {
+            if !json {
+                print!("  Press Enter to start...");
+                let _ = stdout.flush();
+            }
+            let mut line = String::new();
+            let _ = stdin.lock().read_line(&mut line);
+        }
+    };
+
+    if !json {
+        println!("\n{}", "FlowSTT Calibration".bold());
+        println!("  Device: {}\n", device_id.dimmed());
+    }
+
+    // --- Step 1: Background noise ---
+    wait_for_enter(&format!(
+        "{}\n  Stay quiet for 2 seconds.",
+        "Step 1: Background noise".bold()
+    ));
+    let silence = run_self_test(client, &device_id).await?;
+    if silence.is_dead {
+        return Err(format!(
+            "No usable audio from device ({})",
+            silence.warnings.join("; ")
+        )
+        .into());
+    }
+
+    // --- Step 2: Speech ---
+    wait_for_enter(&format!(
+        "\n{}\n  Read aloud at a normal volume: \"{}\"",
+        "Step 2: Speech".bold(),
+        CALIBRATION_SENTENCE
+    ));
+    let speech = run_self_test(client, &device_id).await?;
+
+    let current = config
+        .calibration_for(&device_id)
+        .cloned()
+        .unwrap_or_default();
+    let suggested = DeviceCalibration::suggest(
+        silence.noise_floor_db,
+        speech.rms_db,
+        speech.peak_db,
+    );
+
+    let current_gain = current.gain_db;
+    let speech_triggers = speech.rms_db + current_gain >= current.voiced_threshold_db;
+    let noise_triggers = silence.rms_db + current_gain >= current.whisper_threshold_db;
+
+    if !json {
+        println!("\n{}", "Results".bold());
+        println!("  Noise floor:  {:.1} dB", silence.noise_floor_db);
+        println!(
+            "  Speech level: {:.1} dB (peak {:.1} dB)",
+            speech.rms_db, speech.peak_db
+        );
+        for warning in &speech.warnings {
+            println!("  {}: {}", "Warning".yellow().bold(), warning);
+        }
+
+        println!(
+            "\n  Current thresholds: voiced {:.0} dB, whisper {:.0} dB, gain {:+.1} dB",
+            current.voiced_threshold_db, current.whisper_threshold_db, current.gain_db
+        );
+        if speech_triggers {
+            println!("  Speech {} trigger detection", "would".green());
+        } else {
+            println!("  Speech {} trigger detection", "would NOT".red().bold());
+        }
+        if noise_triggers {
+            println!("  Background noise {} cause false triggers", "may".red().bold());
+        }
+
+        println!(
+            "\n  Suggested: voiced {:.0} dB, whisper {:.0} dB, gain {:+.1} dB",
+            suggested.voiced_threshold_db, suggested.whisper_threshold_db, suggested.gain_db
+        );
+    }
+
+    let save = if write {
+        true
+    } else if interactive && !json {
+        print!("\n  Save these settings for this device? [y/N] ");
+        // A closed stdin reads as no answer, which doesn't save
+        let mut answer = String::new();
+        stdout
+            .flush()
+            .and_then(|()| stdin.lock().read_line(&mut answer))
+            .map_err(|e| CliError::general(format!("Failed to read answer: {}", e)))?;
+        answer.trim().eq_ignore_ascii_case("y")
+    } else {
+        false
+    };
+
+    if save {
+        settings::update(|config| {
+            config
+                .device_calibrations
+                .insert(device_id.clone(), suggested.clone());
+        })
+        .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+        if !json && !cli.quiet {
+            println!(
+                "  {} (takes effect the next time capture starts)",
+                "Calibration saved".green()
+            );
+        }
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "device_id": device_id,
+                "silence": silence,
+                "speech": speech,
+                "current": current,
+                "suggested": suggested,
+                "speech_triggers": speech_triggers,
+                "noise_triggers": noise_triggers,
+                "saved": save,
+            }))
+            .map_err(|e| e.to_string())?
+        );
+    }
+
+    Ok(())
+}
+
+/// Passage read aloud during speaker enrollment, long enough to fill the
+/// eight-second recording
+const ENROLLMENT_PASSAGE: &str = "When the sunlight strikes raindrops in the air, they act as a \
+prism and form a rainbow. The rainbow is a division of white light into many beautiful colors.";
+
+/// Handle `speaker enroll` and `speaker clear`.
+async fn handle_speaker(
+    client: &mut Client,
+    action: &SpeakerAction,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let json = matches!(cli.format, OutputFormat::Json);
+
+    let request = match action {
+        SpeakerAction::Enroll { device } => {
+            let device_id = device
+                .clone()
+                .or_else(|| Config::load().preferred_source1_id)
+                .ok_or_else(|| {
+                    CliError::usage(
+                        "No device specified. Use --device or run 'flowstt setup' first.",
+                    )
+                })?;
+            check_devices_exist(client, [device_id.as_str()]).await?;
+            if !json && !cli.quiet {
+                println!("{}", "Speaker enrollment".bold());
+                println!("  Read aloud at a normal volume for 8 seconds:");
+                println!("  \"{}\"\n", ENROLLMENT_PASSAGE);
+            }
+            Request::EnrollSpeaker { device_id }
+        }
+        SpeakerAction::Clear => Request::ClearSpeakerEnrollment,
+    };
 
+    let response = client.request(request).await.map_err(|e| e.to_string())?;
+    match response {
+        Response::SpeakerEnrolled { duration_ms } => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "enrolled": true, "duration_ms": duration_ms })
+                );
+            } else if !cli.quiet {
+                println!(
+                    "{} Only your voice is transcribed in Automatic mode from the next time capture starts",
+                    "Voiceprint saved.".green()
+                );
+            }
+        }
+        Response::Ok => {
+            if json {
+                println!("{}", serde_json::json!({ "enrolled": false }));
+            } else if !cli.quiet {
+                println!("{}", "Voiceprint deleted; the speaker filter is off".green());
+            }
+        }
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    }
     Ok(())
 }
+
+/// Run a single audio self-test on the service and return its report.
+async fn run_self_test(
+    client: &mut Client,
+    device_id: &str,
+) -> Result<AudioSelfTestReport, CliError> {
+    let response = client
+        .request(Request::RunAudioSelfTest {
+            device_id: device_id.to_string(),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response {
+        Response::AudioSelfTest(report) => Ok(report),
+        Response::Error { message } => Err(message.into()),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+async fn handle_stats(
+    client: &mut Client,
+    period: ActivityPeriod,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let response = client
+        .request(Request::GetActivitySummary { period })
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = match response {
+        Response::ActivitySummary(summary) => summary,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        return Ok(());
+    }
+    if !Config::load().activity_log && !cli.quiet {
+        eprintln!(
+            "{}",
+            format!(
+                "Activity logging is off; set \"activity_log\": true in {}",
+                Config::config_path().display()
+            )
+            .yellow()
+        );
+    }
+
+    let title = match period {
+        ActivityPeriod::Today => "Today",
+        ActivityPeriod::Week => "Last 7 days",
+        ActivityPeriod::Month => "Last 30 days",
+        ActivityPeriod::All => "All time",
+    };
+    println!("{}", title.bold());
+    println!(
+        "  Speaking:     {} ({} utterances)",
+        format_speech_time(summary.speech_ms),
+        summary.utterances
+    );
+    println!(
+        "  Transcribed:  {} words in {} transcriptions",
+        summary.words, summary.transcriptions
+    );
+    if !summary.intervals.is_empty() {
+        println!();
+    }
+    for interval in &summary.intervals {
+        // Hours are shown by time of day, days by date
+        let label = match period {
+            ActivityPeriod::Today => interval.start.get(11..).unwrap_or(&interval.start),
+            _ => &interval.start,
+        };
+        println!(
+            "  {:<10}  {:>8}  {:>6} words",
+            label,
+            format_speech_time(interval.speech_ms),
+            interval.words
+        );
+    }
+    Ok(())
+}
+
+/// Format speaking time as `1h 02m`, `4m 05s` or `12s`.
+fn format_speech_time(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Show an RFC 3339 timestamp in local time, or as given if it can't be parsed.
+fn format_local_time(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+async fn handle_history_summary(
+    client: &mut Client,
+    group_by: HistoryGrouping,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let response = client
+        .request(Request::GetHistorySummary { group_by })
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = match response {
+        Response::HistorySummary(summary) => summary,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        return Ok(());
+    }
+    if summary.buckets.is_empty() {
+        if !cli.quiet {
+            println!("No history entries");
+        }
+        return Ok(());
+    }
+
+    for bucket in &summary.buckets {
+        println!(
+            "  {:<16}  {:>4} entries  {:>6} words  {:>8}",
+            bucket.start,
+            bucket.entries,
+            bucket.words,
+            format_speech_time(bucket.duration_ms)
+        );
+    }
+    let entries: u32 = summary.buckets.iter().map(|b| b.entries).sum();
+    let words: u64 = summary.buckets.iter().map(|b| b.words).sum();
+    let duration_ms: u64 = summary.buckets.iter().map(|b| b.duration_ms).sum();
+    println!(
+        "  {:<16}  {:>4} entries  {:>6} words  {:>8}",
+        "Total".bold(),
+        entries,
+        words,
+        format_speech_time(duration_ms)
+    );
+    Ok(())
+}
+
+async fn handle_history_list(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
+    let response = client
+        .request(Request::GetHistory)
+        .await
+        .map_err(|e| e.to_string())?;
+    let entries = match response {
+        Response::History { entries } => entries,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!("{}", serde_json::to_string(&entries).unwrap());
+        return Ok(());
+    }
+    if entries.is_empty() {
+        if !cli.quiet {
+            println!("No history entries");
+        }
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let mut details = format_local_time(&entry.timestamp);
+        if let Some(ms) = entry.duration_ms {
+            details.push_str(&format!("  {:.1}s", ms as f64 / 1000.0));
+        }
+        if entry.mode == Some(TranscriptionMode::PushToTalk) {
+            details.push_str("  push-to-talk");
+        }
+        let recording = if entry.wav_path.is_some() {
+            " [recording]".green()
+        } else {
+            "".normal()
+        };
+        let edited = if entry.edited {
+            " [edited]".yellow()
+        } else {
+            "".normal()
+        };
+        println!(
+            "{}  {}{}{}",
+            entry.id.bold(),
+            details.dimmed(),
+            recording,
+            edited
+        );
+        println!("    {}", entry.text);
+    }
+    Ok(())
+}
+
+async fn handle_history_play(
+    client: &mut Client,
+    id: &str,
+    from: Option<f64>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let position_ms = match from {
+        Some(secs) if !secs.is_finite() || secs < 0.0 => {
+            return Err(CliError::usage("--from must be zero or more seconds"));
+        }
+        Some(secs) => Some((secs * 1000.0) as u64),
+        None => None,
+    };
+
+    // Subscribe before starting so the finished event is not missed
+    let mut event_client = Client::new();
+    event_client
+        .connect_or_spawn()
+        .await
+        .map_err(|e| format!("Failed to connect event client: {}", e))?;
+    event_client
+        .subscribe_events(false)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let response = client
+        .request(Request::PlayHistoryAudio { id: id.to_string() })
+        .await
+        .map_err(|e| e.to_string())?;
+    let duration_ms = match response {
+        Response::HistoryAudioPlaying { duration_ms, .. } => duration_ms,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if let Some(position_ms) = position_ms {
+        let response = client
+            .request(Request::SeekHistoryAudio { position_ms })
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Response::Error { message } = response {
+            return Err(message.into());
+        }
+    }
+
+    let json = matches!(cli.format, OutputFormat::Json);
+    if !cli.quiet && !json {
+        match duration_ms {
+            Some(ms) => println!(
+                "{} {} ({:.1}s)",
+                "Playing".green().bold(),
+                id,
+                ms as f64 / 1000.0
+            ),
+            None => println!("{} {}", "Playing".green().bold(), id),
+        }
+        println!("Press Ctrl+C to stop");
+    }
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    // Whether playback ran to the end rather than being interrupted
+    let mut completed = true;
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                let _ = client.request(Request::StopHistoryAudio).await;
+                if !cli.quiet && !json {
+                    eprintln!("\n{}", "Stopped".yellow());
+                }
+                completed = false;
+                break;
+            }
+            event_result = event_client.read_event() => {
+                match event_result {
+                    Ok(Response::Event {
+                        event: EventType::HistoryAudioFinished { id: finished },
+                    }) if finished == id => break,
+                    Ok(Response::Event {
+                        event: EventType::Shutdown,
+                    }) => return Err("Service shut down during playback".into()),
+                    Ok(_) => {}
+                    Err(e) => return Err(format!("Lost connection to service: {}", e).into()),
+                }
+            }
+        }
+    }
+
+    if json {
+        print_done(
+            cli,
+            "",
+            serde_json::json!({ "id": id, "duration_ms": duration_ms, "completed": completed }),
+        );
+    }
+    Ok(())
+}
+
+async fn handle_history_retranscribe(
+    client: &mut Client,
+    id: &str,
+    model: Option<String>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    // Subscribe before queueing so the result event is not missed
+    let mut event_client = Client::new();
+    event_client
+        .connect_or_spawn()
+        .await
+        .map_err(|e| format!("Failed to connect event client: {}", e))?;
+    event_client
+        .subscribe_events(false)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let response = client
+        .request(Request::RetranscribeEntry {
+            id: id.to_string(),
+            model,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    match response {
+        Response::Ok => {}
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    }
+
+    if !cli.quiet && !matches!(cli.format, OutputFormat::Json) {
+        println!("{} {}", "Transcribing".green().bold(), id);
+    }
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                // The job stays queued; the entry is still updated when it completes
+                if !cli.quiet {
+                    eprintln!("\n{}", "Stopped waiting".yellow());
+                }
+                return Ok(());
+            }
+            event_result = event_client.read_event() => {
+                match event_result {
+                    Ok(Response::Event {
+                        event: EventType::HistoryEntryUpdated { entry },
+                    }) if entry.id == id => {
+                        if matches!(cli.format, OutputFormat::Json) {
+                            println!("{}", serde_json::to_string(&entry).unwrap());
+                        } else {
+                            if let Some(ref model) = entry.model {
+                                println!("{} {}", "Model:".bold(), model);
+                            }
+                            if let Some(ref original) = entry.original_text {
+                                println!("{} {}", "Original:".bold(), original.dimmed());
+                            }
+                            println!("{} {}", "Updated:".bold(), entry.text);
+                        }
+                        return Ok(());
+                    }
+                    Ok(Response::Event {
+                        event: EventType::RetranscriptionFailed { id: failed, error },
+                    }) if failed == id => return Err(error.into()),
+                    Ok(Response::Event {
+                        event: EventType::Shutdown,
+                    }) => return Err("Service shut down during transcription".into()),
+                    Ok(_) => {}
+                    Err(e) => return Err(format!("Lost connection to service: {}", e).into()),
+                }
+            }
+        }
+    }
+}
+
+/// Report the meeting returned by a start or stop request.
+fn print_meeting_response(response: Response, status: &str, cli: &Cli) -> Result<(), CliError> {
+    let meeting = match response {
+        Response::Meeting(meeting) => meeting,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!("{}", serde_json::to_string(&meeting).unwrap());
+    } else if !cli.quiet {
+        println!("{} {}", status.green().bold(), meeting.id);
+        if meeting.ended_at.is_some() {
+            println!("  Lines: {}", meeting.lines.len());
+        }
+    }
+    Ok(())
+}
+
+async fn get_meetings(client: &mut Client) -> Result<Vec<MeetingRecord>, CliError> {
+    let response = client
+        .request(Request::GetMeetings)
+        .await
+        .map_err(|e| e.to_string())?;
+    match response {
+        Response::Meetings { meetings } => Ok(meetings),
+        Response::Error { message } => Err(message.into()),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+async fn handle_meeting_list(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
+    let meetings = get_meetings(client).await?;
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!("{}", serde_json::to_string(&meetings).unwrap());
+        return Ok(());
+    }
+    if meetings.is_empty() {
+        if !cli.quiet {
+            println!("No meetings");
+        }
+        return Ok(());
+    }
+
+    for meeting in &meetings {
+        let in_progress = if meeting.ended_at.is_none() {
+            " [in progress]".green()
+        } else {
+            "".normal()
+        };
+        println!(
+            "{}  {}{}",
+            meeting.id.bold(),
+            meeting.started_at.dimmed(),
+            in_progress
+        );
+        println!(
+            "    {} ({} lines)",
+            meeting.title.as_deref().unwrap_or("Meeting"),
+            meeting.lines.len()
+        );
+    }
+    Ok(())
+}
+
+async fn handle_meeting_export(
+    client: &mut Client,
+    id: &str,
+    file: Option<&Path>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let meetings = get_meetings(client).await?;
+    let meeting = meetings
+        .iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| CliError::general(format!("Meeting not found: {}", id)))?;
+
+    let contents = meeting.to_markdown();
+    match file {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            print_done(
+                cli,
+                format!(
+                    "{} {} lines to {}",
+                    "Exported".green().bold(),
+                    meeting.lines.len(),
+                    path.display()
+                ),
+                serde_json::json!({ "lines": meeting.lines.len(), "file": path }),
+            );
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+/// Print the CLI version and, if the service is running, its version and
+/// build details, warning when the two don't match.
+async fn handle_version(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
+    let cli_version = env!("CARGO_PKG_VERSION");
+    let service = match client.connect().await {
+        Ok(()) => match client.request(Request::GetVersionInfo).await {
+            Ok(Response::VersionInfo(info)) => Some(Ok(info)),
+            // Services from before GetVersionInfo can't answer it
+            _ => Some(Err(())),
+        },
+        Err(_) => None,
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        let service = match &service {
+            Some(Ok(info)) => serde_json::to_value(info).unwrap_or_default(),
+            _ => serde_json::Value::Null,
+        };
+        println!(
+            "{}",
+            serde_json::json!({
+                "version": cli_version,
+                "protocol_version": PROTOCOL_VERSION,
+                "service": service,
+            })
+        );
+        return Ok(());
+    }
+
+    println!("flowstt {}", cli_version);
+    match service {
+        None => println!("{}", "Service: not running".dimmed()),
+        Some(Err(())) => println!(
+            "{}",
+            "Service: older than this CLI (it doesn't report its version); restart it to update"
+                .yellow()
+        ),
+        Some(Ok(info)) => {
+            let mut build = Vec::new();
+            build.extend(info.git_hash.clone());
+            build.extend(info.build_date.clone());
+            if build.is_empty() {
+                println!("Service: {}", info.version);
+            } else {
+                println!("Service: {} ({})", info.version, build.join(", "));
+            }
+            println!("  whisper.cpp: {}", info.whisper_version);
+            if !info.features.is_empty() {
+                println!("  Acceleration: {}", info.features.join(", "));
+            }
+            println!("  Protocol: {}", info.protocol_version);
+
+            if info.protocol_version != PROTOCOL_VERSION {
+                println!(
+                    "{}",
+                    format!(
+                        "The service speaks protocol {} but this CLI speaks {}; \
+                         some commands will fail until both are the same version",
+                        info.protocol_version, PROTOCOL_VERSION
+                    )
+                    .yellow()
+                );
+            } else if info.version != cli_version {
+                println!(
+                    "{}",
+                    format!(
+                        "The service is version {} but this CLI is {}",
+                        info.version, cli_version
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_debug_report(
+    client: &mut Client,
+    output: Option<&Path>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let path = match output {
+        Some(path) => path.to_path_buf(),
+        None => std::path::PathBuf::from(format!(
+            "flowstt-report-{}.zip",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )),
+    };
+    let logs = report::write(client, &path).await?;
+    print_done(
+        cli,
+        format!(
+            "{} {} ({} log and crash files)\n{}",
+            "Wrote".green().bold(),
+            path.display(),
+            logs,
+            "Logs may include transcribed text; look through the report before sharing it."
+                .dimmed()
+        ),
+        serde_json::json!({ "file": path, "logs": logs }),
+    );
+    Ok(())
+}
+
+async fn handle_rules_export(
+    client: &mut Client,
+    file: Option<&Path>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let response = client
+        .request(Request::ExportRules)
+        .await
+        .map_err(|e| e.to_string())?;
+    let rules = match response {
+        Response::Rules { rules } => rules,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    let contents =
+        toml::to_string_pretty(&rules).map_err(|e| format!("Failed to encode rules: {}", e))?;
+    match file {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            print_done(
+                cli,
+                format!(
+                    "{} {} replacement rules, {} redaction rules and {} dictionary terms to {}",
+                    "Exported".green().bold(),
+                    rules.replacements.len(),
+                    rules.redaction.len(),
+                    rules.dictionary.len(),
+                    path.display()
+                ),
+                serde_json::json!({
+                    "replacements": rules.replacements.len(),
+                    "redaction": rules.redaction.len(),
+                    "dictionary": rules.dictionary.len(),
+                    "file": path,
+                }),
+            );
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+async fn handle_rules_import(
+    client: &mut Client,
+    file: &Path,
+    replace: bool,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| CliError::usage(format!("{}: {}", file.display(), e)))?;
+    let rules: UserRules = toml::from_str(&contents)
+        .map_err(|e| CliError::usage(format!("Invalid rules file {}: {}", file.display(), e)))?;
+
+    let response = client
+        .request(Request::ImportRules { rules, replace })
+        .await
+        .map_err(|e| e.to_string())?;
+    let (added, updated) = match response {
+        Response::RulesImported { added, updated } => (added, updated),
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if matches!(cli.format, OutputFormat::Json) {
+        println!(
+            "{}",
+            serde_json::json!({ "added": added, "updated": updated })
+        );
+    } else if !cli.quiet {
+        println!(
+            "{} {} added, {} updated",
+            "Imported".green().bold(),
+            added,
+            updated
+        );
+    }
+    Ok(())
+}
+
+async fn handle_backup_create(
+    client: &mut Client,
+    file: &Path,
+    recordings: bool,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    // The service resolves relative paths against its own working directory
+    let path = std::path::absolute(file)
+        .map_err(|e| CliError::usage(format!("{}: {}", file.display(), e)))?;
+    let response = client
+        .request(Request::CreateBackup {
+            path: path.to_string_lossy().to_string(),
+            include_recordings: recordings,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response {
+        Response::BackupCreated {
+            history_entries,
+            recordings,
+        } => {
+            print_done(
+                cli,
+                format!(
+                    "{} {} history entries and {} recordings to {}",
+                    "Backed up".green().bold(),
+                    history_entries,
+                    recordings,
+                    path.display()
+                ),
+                serde_json::json!({
+                    "history_entries": history_entries,
+                    "recordings": recordings,
+                    "file": path,
+                }),
+            );
+            Ok(())
+        }
+        Response::Error { message } => Err(message.into()),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+async fn handle_backup_restore(
+    client: &mut Client,
+    file: &Path,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    let path = std::fs::canonicalize(file)
+        .map_err(|e| CliError::usage(format!("{}: {}", file.display(), e)))?;
+    let response = client
+        .request(Request::RestoreBackup {
+            path: path.to_string_lossy().to_string(),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response {
+        Response::BackupRestored {
+            created_at,
+            history_entries,
+            recordings,
+        } => {
+            print_done(
+                cli,
+                format!(
+                    "{} {} history entries and {} recordings from the backup of {} \
+                     (restart FlowSTT to apply all restored settings)",
+                    "Restored".green().bold(),
+                    history_entries,
+                    recordings,
+                    format_local_time(&created_at)
+                ),
+                serde_json::json!({
+                    "created_at": created_at,
+                    "history_entries": history_entries,
+                    "recordings": recordings,
+                }),
+            );
+            Ok(())
+        }
+        Response::Error { message } => Err(message.into()),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Handle `test run`: start a test mode run on the service and stream its
+/// progress until it finishes.
+async fn handle_test_run(
+    client: &mut Client,
+    dir: &Path,
+    manifest: Option<&Path>,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    // The service resolves relative paths against its own working directory
+    let absolute = |path: &Path| {
+        std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().to_string())
+            .map_err(|e| CliError::usage(format!("{}: {}", path.display(), e)))
+    };
+    let dir = absolute(dir)?;
+    let manifest = manifest.map(absolute).transpose()?;
+    let json = matches!(cli.format, OutputFormat::Json);
+
+    // Subscribe before starting the run so no progress events are missed
+    let mut event_client = Client::new();
+    event_client
+        .connect_or_spawn()
+        .await
+        .map_err(|e| format!("Failed to connect event client: {}", e))?;
+    event_client
+        .subscribe_events(false)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let response = client
+        .request(Request::RunTestMode {
+            dir: dir.clone(),
+            manifest,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    match response {
+        Response::Ok => {}
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    }
+
+    if !cli.quiet && !json {
+        println!("{} test run in {}", "Started".green().bold(), dir);
+        println!("Press Ctrl+C to stop watching (the run continues in the service)");
+    }
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                if !cli.quiet {
+                    eprintln!("\n{}", tr(Message::CliInterrupted).yellow());
+                }
+                break;
+            }
+            event_result = event_client.read_event() => {
+                let event = match event_result {
+                    Ok(Response::Event { event }) => event,
+                    Ok(_) => continue,
+                    Err(e) => return Err(format!("Lost connection to service: {}", e).into()),
+                };
+                match event {
+                    EventType::TestFileStarted { index, total, file } if !json && !cli.quiet => {
+                        println!("[{}/{}] {}", index + 1, total, file.bold());
+                    }
+                    EventType::TestFileComplete { result, .. } => {
+                        if json {
+                            println!("{}", serde_json::to_string(&result).unwrap());
+                        } else {
+                            print_test_result(&result);
+                        }
+                    }
+                    EventType::TestRunComplete {
+                        summary,
+                        report_path,
+                    } => {
+                        if json {
+                            println!(
+                                "{}",
+                                serde_json::to_string(&serde_json::json!({
+                                    "summary": summary,
+                                    "report_path": report_path,
+                                }))
+                                .unwrap()
+                            );
+                        } else {
+                            print_test_summary(&summary, report_path.as_deref());
+                        }
+                        break;
+                    }
+                    EventType::Shutdown => {
+                        return Err("Service shut down during the test run".into());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print one file's outcome and scores.
+fn print_test_result(result: &TestFileResult) {
+    let outcome = match result.outcome {
+        TestFileOutcome::Transcribed => "transcribed".green(),
+        TestFileOutcome::NoSpeech => "no speech".yellow(),
+        TestFileOutcome::Timeout => "timed out".red(),
+        TestFileOutcome::Skipped => "skipped".red(),
+    };
+    println!("  Result:   {}", outcome);
+    if let Some(ref actual) = result.actual {
+        println!("  Got:      {}", actual);
+    }
+    if let Some(ref expected) = result.expected {
+        println!("  Expected: {}", expected.dimmed());
+    }
+    if let (Some(wer), Some(cer)) = (result.wer, result.cer) {
+        println!("  WER {:.1}%  CER {:.1}%", wer * 100.0, cer * 100.0);
+    }
+}
+
+/// Print the totals for a finished test run.
+fn print_test_summary(summary: &TestRunSummary, report_path: Option<&str>) {
+    println!("\n{}", "Test run complete".green().bold());
+    println!(
+        "  Files: {} ({} transcribed, {} no speech, {} timed out, {} skipped)",
+        summary.total, summary.transcribed, summary.no_speech, summary.timed_out, summary.skipped
+    );
+    if let (Some(wer), Some(cer)) = (summary.mean_wer, summary.mean_cer) {
+        println!(
+            "  Mean WER {:.1}%  CER {:.1}% over {} scored file(s)",
+            wer * 100.0,
+            cer * 100.0,
+            summary.scored
+        );
+    }
+    if let Some(path) = report_path {
+        println!("  Report: {}", path.dimmed());
+    }
+}
+
+/// Play audio through the engine and report transcription latency.
+async fn handle_bench_latency(
+    client: &mut Client,
+    file: Option<&Path>,
+    runs: u32,
+    cli: &Cli,
+) -> Result<(), CliError> {
+    // The service resolves relative paths against its own working directory
+    let file = file
+        .map(|path| {
+            std::fs::canonicalize(path)
+                .map(|p| p.to_string_lossy().to_string())
+                .map_err(|e| CliError::usage(format!("{}: {}", path.display(), e)))
+        })
+        .transpose()?;
+    let json = matches!(cli.format, OutputFormat::Json);
+
+    if !cli.quiet && !json {
+        println!(
+            "Playing {} {} time(s) in real time...",
+            file.as_deref().unwrap_or("the built-in sample"),
+            runs
+        );
+    }
+
+    let response = client
+        .request(Request::RunLatencyBenchmark { file, runs })
+        .await
+        .map_err(|e| e.to_string())?;
+    let report = match response {
+        Response::LatencyBenchmark(report) => report,
+        Response::Error { message } => return Err(message.into()),
+        _ => return Err("Unexpected response".into()),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string(&report).unwrap());
+    } else {
+        print_latency_report(&report);
+    }
+
+    if report.latencies_ms.is_empty() {
+        return Err("Every run failed; see the service log for details".into());
+    }
+    Ok(())
+}
+
+/// Print the latency percentiles for a benchmark.
+fn print_latency_report(report: &LatencyReport) {
+    if report.latencies_ms.is_empty() {
+        println!("{}", "No run produced a transcription".red().bold());
+        return;
+    }
+    println!(
+        "{} p50 {} ms, p95 {} ms, max {} ms over {} run(s)",
+        "Latency".green().bold(),
+        report.p50_ms,
+        report.p95_ms,
+        report.max_ms,
+        report.latencies_ms.len()
+    );
+    if report.failed > 0 {
+        println!("  {} run(s) failed", report.failed.to_string().yellow());
+    }
+    println!("  Audio: {:.1} s per run", report.audio_ms as f64 / 1000.0);
+}
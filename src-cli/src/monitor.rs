@@ -0,0 +1,320 @@
+//! Live terminal audio monitor.
+//!
+//! Renders a scrolling braille waveform and a block-character dB meter from
+//! the service's visualization and speech metrics events, so headless users
+//! can check that the right device is selected and audio is flowing.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use crossterm::{cursor, execute, queue, terminal};
+use flowstt_common::ipc::{EventType, Response};
+
+use crate::client::Client;
+
+/// Height of the waveform in terminal rows (each row holds 4 braille dots)
+const WAVEFORM_ROWS: usize = 4;
+
+/// Lower bound of the dB meter scale
+const METER_MIN_DB: f32 = -60.0;
+
+/// Fallback terminal width when the size cannot be queried
+const DEFAULT_WIDTH: usize = 80;
+
+/// How long the status line shows a clipping warning
+const CLIPPING_DISPLAY: Duration = Duration::from_secs(5);
+
+/// Braille dot bits for the left and right dot columns, top to bottom.
+const BRAILLE_LEFT: [u32; 4] = [0x01, 0x02, 0x04, 0x40];
+const BRAILLE_RIGHT: [u32; 4] = [0x08, 0x10, 0x20, 0x80];
+
+/// Partial block characters for the meter, in eighths.
+const PARTIAL_BLOCKS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Live state for the monitor display.
+struct MonitorState {
+    /// Waveform peak amplitudes, one per braille dot column (oldest first)
+    columns: VecDeque<f32>,
+    /// Maximum number of dot columns to keep
+    max_columns: usize,
+    /// Latest amplitude in dB from speech metrics
+    level_db: f32,
+    /// Whether the speech detector currently reports speech
+    is_speaking: bool,
+    /// Whether capture is running
+    capturing: bool,
+    /// When the service last reported clipping
+    clipped_at: Option<Instant>,
+    /// Native rate of the input while it is narrowband
+    narrowband_rate: Option<u32>,
+    /// Whether the waveform has been drawn at least once
+    drawn: bool,
+}
+
+impl MonitorState {
+    fn new(width: usize) -> Self {
+        let max_columns = width.max(10) * 2;
+        Self {
+            columns: VecDeque::with_capacity(max_columns),
+            max_columns,
+            level_db: METER_MIN_DB,
+            is_speaking: false,
+            capturing: false,
+            clipped_at: None,
+            narrowband_rate: None,
+            drawn: false,
+        }
+    }
+
+    /// Append a chunk of waveform samples, reduced to a few dot columns.
+    fn push_waveform(&mut self, waveform: &[f32]) {
+        // Each visualization update becomes 4 dot columns (2 terminal cells)
+        let chunk = (waveform.len() / 4).max(1);
+        for part in waveform.chunks(chunk) {
+            let peak = part.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+            if self.columns.len() == self.max_columns {
+                self.columns.pop_front();
+            }
+            self.columns.push_back(peak.min(1.0));
+        }
+    }
+}
+
+/// Run the monitor until Ctrl+C, capture error, or service shutdown.
+pub async fn run_monitor(client: &mut Client, json: bool) -> Result<(), String> {
+    client
+        .subscribe_events(true)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let width = terminal::size()
+        .map(|(cols, _)| cols as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+        .saturating_sub(2);
+    let mut state = MonitorState::new(width);
+    let mut stdout = io::stdout();
+
+    if !json {
+        println!("Monitoring audio input. Press Ctrl+C to stop.\n");
+        let _ = execute!(stdout, cursor::Hide);
+    }
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    let result = loop {
+        tokio::select! {
+            _ = &mut shutdown => break Ok(()),
+            event_result = client.read_event() => {
+                let event = match event_result {
+                    Ok(Response::Event { event }) => event,
+                    Ok(_) => continue,
+                    Err(e) => break Err(format!("Event stream error: {}", e)),
+                };
+
+                match event {
+                    EventType::VisualizationData(data) if !json => {
+                        state.push_waveform(&data.waveform);
+                        draw(&mut stdout, &mut state, width);
+                    }
+                    EventType::SpeechMetrics(metrics) => {
+                        if json {
+                            println!("{}", serde_json::to_string(&metrics).unwrap());
+                        } else {
+                            state.level_db = metrics.amplitude_db;
+                            state.is_speaking = metrics.is_speaking;
+                        }
+                    }
+                    EventType::CaptureStateChanged { capturing, error } => {
+                        if let Some(err) = error {
+                            break Err(format!("Capture error: {}", err));
+                        }
+                        state.capturing = capturing;
+                        if !json {
+                            draw(&mut stdout, &mut state, width);
+                        }
+                    }
+                    EventType::ClippingDetected { clipped_samples } => {
+                        if json {
+                            let event = EventType::ClippingDetected { clipped_samples };
+                            println!("{}", serde_json::to_string(&event).unwrap());
+                        } else {
+                            state.clipped_at = Some(Instant::now());
+                        }
+                    }
+                    EventType::DeviceProfileChanged {
+                        sample_rate,
+                        narrowband,
+                        ..
+                    } if !json => {
+                        state.narrowband_rate = narrowband.then_some(sample_rate);
+                        draw(&mut stdout, &mut state, width);
+                    }
+                    event @ EventType::DeviceProfileChanged { .. } => {
+                        println!("{}", serde_json::to_string(&event).unwrap());
+                    }
+                    EventType::Shutdown => break Err("Service shutting down".to_string()),
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    if !json {
+        let _ = execute!(stdout, cursor::Show);
+        println!();
+    }
+
+    result
+}
+
+/// Redraw the waveform, meter, and status line in place.
+fn draw(stdout: &mut io::Stdout, state: &mut MonitorState, width: usize) {
+    let lines = WAVEFORM_ROWS + 2;
+    if state.drawn {
+        let _ = queue!(stdout, cursor::MoveUp(lines as u16));
+    }
+    state.drawn = true;
+
+    let columns: Vec<f32> = state.columns.iter().copied().collect();
+    for row in render_braille(&columns, WAVEFORM_ROWS, width) {
+        let _ = queue!(stdout, terminal::Clear(terminal::ClearType::CurrentLine));
+        let _ = writeln!(stdout, " {}", row.cyan());
+    }
+
+    let meter_width = width.saturating_sub(12).max(10);
+    let meter = render_meter(state.level_db, meter_width);
+    let meter = if state.level_db > -3.0 {
+        meter.red()
+    } else if state.level_db > -12.0 {
+        meter.yellow()
+    } else {
+        meter.green()
+    };
+    let _ = queue!(stdout, terminal::Clear(terminal::ClearType::CurrentLine));
+    let _ = writeln!(stdout, " {} {:6.1} dB", meter, state.level_db);
+
+    let clipping = state
+        .clipped_at
+        .is_some_and(|at| at.elapsed() < CLIPPING_DISPLAY);
+    let status = if !state.capturing {
+        "capture idle - start transcription or set a source".dimmed()
+    } else if clipping {
+        "clipping - lower the input gain".red().bold()
+    } else if let Some(rate) = state.narrowband_rate {
+        format!(
+            "narrowband input ({} kHz) - transcription quality reduced",
+            rate / 1000
+        )
+        .yellow()
+    } else if state.is_speaking {
+        "speech".green().bold()
+    } else {
+        "listening".dimmed()
+    };
+    let _ = queue!(stdout, terminal::Clear(terminal::ClearType::CurrentLine));
+    let _ = writeln!(stdout, " {}", status);
+    let _ = stdout.flush();
+}
+
+/// Render waveform peaks as rows of braille characters, centered vertically.
+///
+/// Each character covers two dot columns and four dot rows. The newest data is
+/// right-aligned so the waveform scrolls from right to left.
+fn render_braille(columns: &[f32], rows: usize, width: usize) -> Vec<String> {
+    let dot_rows = rows * 4;
+    let center = dot_rows as f32 / 2.0;
+    let mut cells = vec![vec![0u32; width]; rows];
+
+    let visible = columns.len().min(width * 2);
+    let offset = width * 2 - visible;
+    for (i, &peak) in columns[columns.len() - visible..].iter().enumerate() {
+        let dot_col = offset + i;
+        let half = (peak * center)
+            .round()
+            .max(if peak > 0.0 { 1.0 } else { 0.0 });
+        let top = (center - half).max(0.0) as usize;
+        let bottom = ((center + half) as usize).min(dot_rows);
+        for dot_row in top..bottom {
+            let bits = if dot_col.is_multiple_of(2) {
+                BRAILLE_LEFT
+            } else {
+                BRAILLE_RIGHT
+            };
+            cells[dot_row / 4][dot_col / 2] |= bits[dot_row % 4];
+        }
+    }
+
+    cells
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|bits| char::from_u32(0x2800 + bits).unwrap_or(' '))
+                .collect()
+        })
+        .collect()
+}
+
+/// Render a horizontal dB meter using full and partial block characters.
+fn render_meter(level_db: f32, width: usize) -> String {
+    let fraction = ((level_db - METER_MIN_DB) / -METER_MIN_DB).clamp(0.0, 1.0);
+    let eighths = (fraction * width as f32 * 8.0).round() as usize;
+    let full = eighths / 8;
+    let partial = eighths % 8;
+
+    let mut meter = "█".repeat(full);
+    if full < width {
+        meter.push(PARTIAL_BLOCKS[partial]);
+        meter.push_str(&" ".repeat(width - full - 1));
+    }
+    meter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silence_renders_blank_braille() {
+        let rows = render_braille(&[0.0; 10], 2, 3);
+        assert_eq!(rows, ["\u{2800}".repeat(3), "\u{2800}".repeat(3)]);
+    }
+
+    #[test]
+    fn full_scale_fills_the_column() {
+        // A lone column is the newest, so it lands in the right dot column
+        assert_eq!(render_braille(&[1.0], 1, 1), ["⢸"]);
+        assert_eq!(render_braille(&[1.0, 1.0], 2, 1), ["⣿", "⣿"]);
+    }
+
+    #[test]
+    fn quiet_audio_still_shows_a_dot() {
+        // Rounds to no dots, but anything above silence shows around the center
+        assert_eq!(render_braille(&[0.01, 0.01], 1, 1), ["⠶"]);
+    }
+
+    #[test]
+    fn newest_columns_are_kept_right_aligned() {
+        // Only the last two columns fit in one character
+        assert_eq!(render_braille(&[1.0, 1.0, 0.0, 0.0], 1, 1), ["\u{2800}"]);
+        // The oldest of three columns lands in the right half of the middle one
+        assert_eq!(
+            render_braille(&[1.0, 0.0, 0.0], 1, 3),
+            ["\u{2800}⢸\u{2800}"]
+        );
+    }
+
+    #[test]
+    fn meter_spans_the_scale() {
+        assert_eq!(render_meter(-90.0, 4), "    ");
+        assert_eq!(render_meter(0.0, 4), "████");
+        assert_eq!(render_meter(-30.0, 4), "██  ");
+        // Five eighths of the third cell
+        assert_eq!(render_meter(-30.0 + 60.0 * 5.0 / 32.0, 4), "██▋ ");
+        for db in [-60.0, -45.5, -12.3, -0.1] {
+            assert_eq!(render_meter(db, 10).chars().count(), 10);
+        }
+    }
+}
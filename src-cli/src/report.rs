@@ -0,0 +1,194 @@
+//! Bug report bundles.
+//!
+//! `flowstt debug report` zips up what is needed to look into a problem: the
+//! log files and crash reports from the logs directory, the config file with
+//! personal details taken out, system information (including the IPC socket
+//! or pipe clients connect to) and, when the service is running, its
+//! capabilities and permissions. It doesn't need the service, so it still
+//! works after a crash.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use flowstt_common::config::Config;
+use flowstt_common::ipc::{Request, Response};
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::client::Client;
+
+/// Config fields holding text the user wrote, whose strings are left out
+const PRIVATE_FIELDS: &[&str] = &["redaction", "corrections", "spoken_commands"];
+
+/// Stands in for a string left out of the config
+const REDACTED: &str = "<redacted>";
+
+/// Write a report to `path`, returning the number of log and crash files in it.
+pub async fn write(client: &mut Client, path: &Path) -> Result<usize, String> {
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let zip_error = |e: zip::result::ZipError| format!("Failed to write the report: {}", e);
+    let io_error = |e: std::io::Error| format!("Failed to write the report: {}", e);
+
+    zip.start_file("system.txt", options).map_err(zip_error)?;
+    zip.write_all(system_info().as_bytes()).map_err(io_error)?;
+    zip.start_file("config.json", options).map_err(zip_error)?;
+    zip.write_all(redacted_config().as_bytes())
+        .map_err(io_error)?;
+    if let Some(service) = service_info(client).await {
+        zip.start_file("service.json", options).map_err(zip_error)?;
+        zip.write_all(service.as_bytes()).map_err(io_error)?;
+    }
+
+    let mut logs = 0;
+    let log_dir = flowstt_common::paths::log_dir();
+    for entry in fs::read_dir(&log_dir).into_iter().flatten().flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(mut log) = File::open(entry.path()) else {
+            continue;
+        };
+        let name = format!("logs/{}", entry.file_name().to_string_lossy());
+        zip.start_file(name, options).map_err(zip_error)?;
+        std::io::copy(&mut log, &mut zip).map_err(io_error)?;
+        logs += 1;
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(logs)
+}
+
+fn system_info() -> String {
+    let mut info = format!(
+        "FlowSTT CLI {}\nOS: {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    if let Some(release) = os_release() {
+        info.push_str(&format!("Release: {}\n", release));
+    }
+    if std::env::consts::OS == "linux" {
+        for var in ["XDG_SESSION_TYPE", "XDG_CURRENT_DESKTOP"] {
+            if let Ok(value) = std::env::var(var) {
+                info.push_str(&format!("{}: {}\n", var, value));
+            }
+        }
+    }
+    info.push_str(&format!(
+        "IPC endpoint: {}\nPortable: {}\nCreated: {}\n",
+        flowstt_common::ipc::get_socket_path().display(),
+        flowstt_common::portable::portable_dir().is_some(),
+        chrono::Local::now().to_rfc3339()
+    ));
+    info
+}
+
+/// Name and version of the operating system.
+fn os_release() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let contents = fs::read_to_string("/etc/os-release").ok()?;
+        contents.lines().find_map(|line| {
+            let name = line.strip_prefix("PRETTY_NAME=")?;
+            Some(name.trim_matches('"').to_string())
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        command_output("sw_vers", &["-productVersion"]).map(|version| format!("macOS {}", version))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        command_output("cmd", &["/C", "ver"])
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+/// The config file as written, with the strings of [`PRIVATE_FIELDS`] left
+/// out and the home directory shortened to `~`.
+fn redacted_config() -> String {
+    let path = Config::config_path();
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => return format!("\"Failed to read {}: {}\"\n", path.display(), e),
+    };
+    let mut config: Value = match serde_json::from_str(&contents) {
+        Ok(config) => config,
+        // Don't include a file that couldn't be checked for private text
+        Err(e) => return format!("\"Not valid JSON: {}\"\n", e),
+    };
+
+    if let Some(fields) = config.as_object_mut() {
+        for field in PRIVATE_FIELDS {
+            if let Some(value) = fields.get_mut(*field) {
+                redact_strings(value);
+            }
+        }
+    }
+    if let Some(home) = dirs::home_dir() {
+        shorten_home(&mut config, &home.to_string_lossy());
+    }
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+fn redact_strings(value: &mut Value) {
+    match value {
+        Value::String(s) if !s.is_empty() => *s = REDACTED.to_string(),
+        Value::Array(items) => items.iter_mut().for_each(redact_strings),
+        Value::Object(fields) => fields.values_mut().for_each(redact_strings),
+        _ => {}
+    }
+}
+
+fn shorten_home(value: &mut Value, home: &str) {
+    match value {
+        Value::String(s) if !home.is_empty() && s.contains(home) => *s = s.replace(home, "~"),
+        Value::Array(items) => items.iter_mut().for_each(|item| shorten_home(item, home)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| shorten_home(field, home)),
+        _ => {}
+    }
+}
+
+/// Capabilities and permissions reported by the service, if it is running.
+async fn service_info(client: &mut Client) -> Option<String> {
+    client.connect().await.ok()?;
+    let mut info = serde_json::Map::new();
+    for (name, request) in [
+        ("capabilities", Request::GetCapabilities),
+        ("permissions", Request::GetPermissionsStatus),
+    ] {
+        let value = match client.request(request).await {
+            Ok(Response::Capabilities(capabilities)) => serde_json::to_value(capabilities),
+            Ok(Response::PermissionsStatus(status)) => serde_json::to_value(status),
+            _ => continue,
+        };
+        if let Ok(value) = value {
+            info.insert(name.to_string(), value);
+        }
+    }
+    serde_json::to_string_pretty(&info).ok()
+}
@@ -0,0 +1,234 @@
+//! Status bar output.
+//!
+//! `flowstt status --format waybar` (or `polybar`) prints the service state as
+//! one line for a status bar custom module: a JSON object with `text`, `alt`,
+//! `class` and `tooltip` for waybar, plain text for polybar. With `--watch`
+//! it subscribes to events and prints a new line whenever the state changes,
+//! reconnecting if the service stops or restarts, so the module can run for
+//! the whole session.
+
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Request, Response};
+use flowstt_common::{TranscribeStatus, TranscriptionMode};
+
+use crate::client::Client;
+
+/// Delay between attempts to reach the service
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Line format for a status bar.
+#[derive(Clone, Copy)]
+pub enum BarFormat {
+    Waybar,
+    Polybar,
+}
+
+/// What the status bar shows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BarState {
+    /// The service isn't running
+    Offline,
+    /// Capture isn't running
+    Idle,
+    /// Automatic capture was suspended after a long silence
+    Suspended,
+    /// Capturing, no speech
+    Listening,
+    /// Capturing speech in automatic mode
+    Speaking,
+    /// Push-to-talk key held
+    Recording,
+    /// Capture failed
+    Error,
+}
+
+impl BarState {
+    fn name(self) -> &'static str {
+        match self {
+            BarState::Offline => "offline",
+            BarState::Idle => "idle",
+            BarState::Suspended => "suspended",
+            BarState::Listening => "listening",
+            BarState::Speaking => "speaking",
+            BarState::Recording => "recording",
+            BarState::Error => "error",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            BarState::Offline => "⊘",
+            BarState::Idle => "○",
+            BarState::Suspended => "◌",
+            BarState::Listening => "◎",
+            BarState::Speaking => "◉",
+            BarState::Recording => "●",
+            BarState::Error => "⚠",
+        }
+    }
+}
+
+/// Service state as far as the bar is concerned.
+#[derive(Default)]
+struct Tracker {
+    online: bool,
+    capturing: bool,
+    in_speech: bool,
+    ptt_held: bool,
+    suspended: bool,
+    privacy: bool,
+    mode: TranscriptionMode,
+    error: Option<String>,
+}
+
+impl Tracker {
+    fn from_status(status: &TranscribeStatus) -> Self {
+        Self {
+            online: true,
+            capturing: status.capturing,
+            in_speech: status.in_speech,
+            ptt_held: false,
+            suspended: status.auto_suspended,
+            privacy: status.privacy_mode,
+            mode: status.transcription_mode,
+            error: status.error.clone(),
+        }
+    }
+
+    /// Update from an event; returns `false` if the service is shutting down.
+    fn apply(&mut self, event: &EventType) -> bool {
+        match event {
+            EventType::CaptureStateChanged { capturing, error } => {
+                self.capturing = *capturing;
+                self.error = error.clone();
+                if !capturing {
+                    self.in_speech = false;
+                }
+            }
+            EventType::SpeechStarted => self.in_speech = true,
+            EventType::SpeechEnded { .. } => self.in_speech = false,
+            EventType::PttPressed => self.ptt_held = true,
+            EventType::PttReleased => self.ptt_held = false,
+            EventType::TranscriptionModeChanged { mode } | EventType::AutoModeToggled { mode } => {
+                self.mode = *mode;
+            }
+            EventType::AutoSuspended { suspended } => self.suspended = *suspended,
+            EventType::PrivacyModeChanged { enabled } => self.privacy = *enabled,
+            EventType::Shutdown => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn state(&self) -> BarState {
+        if !self.online {
+            BarState::Offline
+        } else if self.error.is_some() && !self.capturing {
+            BarState::Error
+        } else if self.suspended {
+            BarState::Suspended
+        } else if self.ptt_held {
+            BarState::Recording
+        } else if !self.capturing {
+            BarState::Idle
+        } else if self.in_speech && self.mode == TranscriptionMode::Automatic {
+            BarState::Speaking
+        } else {
+            BarState::Listening
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        if !self.online {
+            return "FlowSTT: service not running".to_string();
+        }
+        let mode = match self.mode {
+            TranscriptionMode::Automatic => "automatic",
+            TranscriptionMode::PushToTalk => "push-to-talk",
+        };
+        let mut tooltip = format!("FlowSTT: {}\nMode: {}", self.state().name(), mode);
+        if self.privacy {
+            tooltip.push_str("\nPrivacy mode on");
+        }
+        if let Some(error) = &self.error {
+            tooltip.push_str(&format!("\nError: {}", error));
+        }
+        tooltip
+    }
+
+    fn line(&self, format: BarFormat) -> String {
+        let state = self.state();
+        let text = format!("{} {}", state.icon(), state.name());
+        match format {
+            BarFormat::Waybar => serde_json::json!({
+                "text": text,
+                "alt": state.name(),
+                "class": state.name(),
+                "tooltip": self.tooltip(),
+            })
+            .to_string(),
+            BarFormat::Polybar => text,
+        }
+    }
+}
+
+/// Print the status bar line for a status snapshot.
+pub fn print_status(status: &TranscribeStatus, format: BarFormat) {
+    println!("{}", Tracker::from_status(status).line(format));
+}
+
+/// Print a line whenever the state changes, until Ctrl+C.
+pub async fn watch(format: BarFormat) -> Result<(), String> {
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+    let mut last_line = String::new();
+
+    loop {
+        let mut client = Client::new();
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            _ = follow(&mut client, format, &mut last_line) => {}
+        }
+
+        emit(&Tracker::default().line(format), &mut last_line);
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+        }
+    }
+}
+
+/// Follow the service's events until the connection ends.
+async fn follow(client: &mut Client, format: BarFormat, last_line: &mut String) {
+    if client.connect().await.is_err() {
+        return;
+    }
+    let mut tracker = match client.request(Request::GetStatus).await {
+        Ok(Response::Status(status)) => Tracker::from_status(&status),
+        _ => return,
+    };
+    if client.subscribe_events(false).await.is_err() {
+        return;
+    }
+    emit(&tracker.line(format), last_line);
+
+    while let Ok(response) = client.read_event().await {
+        let Response::Event { event } = response else {
+            continue;
+        };
+        if !tracker.apply(&event) {
+            return;
+        }
+        emit(&tracker.line(format), last_line);
+    }
+}
+
+/// Print `line` unless it is the line printed last.
+fn emit(line: &str, last_line: &mut String) {
+    if line == last_line {
+        return;
+    }
+    println!("{}", line);
+    *last_line = line.to_string();
+}
@@ -0,0 +1,345 @@
+//! Transcription of audio piped to stdin.
+//!
+//! `flowstt transcribe-stdin` reads 16-bit PCM from stdin, either raw
+//! (`--rate` and `--channels` describe it) or as a WAV file, whose header
+//! takes precedence. The audio is sent to the service with `StreamAudio`
+//! requests, and each segment's text is printed as soon as it arrives:
+//!
+//! ```text
+//! arecord -f cd - | flowstt transcribe-stdin --rate 44100 --channels 2
+//! ```
+
+use flowstt_common::ipc::{EventType, Request, Response};
+use flowstt_common::MAX_STREAM_CHUNK_SAMPLES;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc;
+
+use crate::client::Client;
+use crate::CliError;
+
+/// WAV format tags for integer PCM
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Stream stdin to the service and print the transcriptions. Input ends at
+/// end of file or Ctrl+C; either way the speech in progress is transcribed.
+pub async fn run(
+    client: &mut Client,
+    rate: u32,
+    channels: u16,
+    json: bool,
+) -> Result<(), CliError> {
+    let mut stdin = tokio::io::stdin();
+    let mut head = [0u8; 4];
+    let head_len = read_full(&mut stdin, &mut head).await?;
+    let (rate, channels, mut carry) = if &head[..head_len] == b"RIFF" {
+        let (rate, channels) = read_wav_header(&mut stdin).await?;
+        (rate, channels, Vec::new())
+    } else {
+        (rate, channels, head[..head_len].to_vec())
+    };
+
+    // Subscribe first so no result can be missed
+    let mut event_client = Client::new();
+    event_client
+        .connect_or_spawn()
+        .await
+        .map_err(|e| format!("Failed to connect event client: {}", e))?;
+    event_client
+        .subscribe_events(false)
+        .await
+        .map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    let stream_id = match request(
+        client,
+        Request::StartAudioStream {
+            sample_rate: rate,
+            channels,
+        },
+    )
+    .await?
+    {
+        Response::AudioStreamStarted { stream_id } => stream_id,
+        _ => return Err("Unexpected response".into()),
+    };
+
+    let (tx, mut results) = mpsc::unbounded_channel();
+    let reader = tokio::spawn(forward_results(event_client, stream_id.clone(), tx));
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+    let mut buf = vec![0u8; MAX_STREAM_CHUNK_SAMPLES * 2];
+    let mut printed = 0;
+    loop {
+        let read = tokio::select! {
+            _ = &mut shutdown => break,
+            read = stdin.read(&mut buf) => read.map_err(|e| format!("Failed to read stdin: {}", e))?,
+        };
+        if read == 0 {
+            break;
+        }
+
+        carry.extend_from_slice(&buf[..read]);
+        let whole = carry.len() / 2 * 2;
+        let samples: Vec<i16> = carry
+            .drain(..whole)
+            .collect::<Vec<u8>>()
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        for chunk in samples.chunks(MAX_STREAM_CHUNK_SAMPLES) {
+            request(
+                client,
+                Request::StreamAudio {
+                    stream_id: stream_id.clone(),
+                    samples: chunk.to_vec(),
+                },
+            )
+            .await?;
+        }
+
+        while let Ok(event) = results.try_recv() {
+            print_result(&event, json);
+            printed += 1;
+        }
+    }
+
+    let segments = match request(client, Request::EndAudioStream { stream_id }).await? {
+        Response::AudioStreamEnded { segments } => segments,
+        _ => return Err("Unexpected response".into()),
+    };
+
+    // Every queued segment is reported, so wait for the rest
+    while printed < segments {
+        match results.recv().await {
+            Some(event) => {
+                print_result(&event, json);
+                printed += 1;
+            }
+            None => {
+                return Err("Service stopped before the stream was transcribed".into());
+            }
+        }
+    }
+    reader.abort();
+    Ok(())
+}
+
+/// Send a request, turning error responses into errors.
+async fn request(client: &mut Client, request: Request) -> Result<Response, CliError> {
+    match client.request(request).await.map_err(|e| e.to_string())? {
+        Response::Error { message } => Err(message.into()),
+        response => Ok(response),
+    }
+}
+
+/// Pass the stream's results on until the service shuts down or the
+/// connection ends.
+async fn forward_results(
+    mut event_client: Client,
+    stream_id: String,
+    tx: mpsc::UnboundedSender<EventType>,
+) {
+    while let Ok(response) = event_client.read_event().await {
+        let Response::Event { event } = response else {
+            continue;
+        };
+        if matches!(event, EventType::Shutdown) {
+            return;
+        }
+        let ours = matches!(
+            &event,
+            EventType::StreamTranscription { stream_id: id, .. } if *id == stream_id
+        );
+        if ours && tx.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+fn print_result(event: &EventType, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string(event).unwrap());
+    } else if let EventType::StreamTranscription { text, .. } = event {
+        if !text.is_empty() {
+            println!("{}", text);
+        }
+    }
+}
+
+/// Read until `buf` is full or input ends; returns the bytes read.
+async fn read_full(
+    input: &mut (impl AsyncRead + Unpin),
+    buf: &mut [u8],
+) -> Result<usize, CliError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let read = input
+            .read(&mut buf[filled..])
+            .await
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+/// Read the rest of a WAV header, up to the start of the samples, and
+/// return the sample rate and channel count. The data chunk's size is
+/// ignored, since tools writing to a pipe can't fill it in.
+async fn read_wav_header(input: &mut (impl AsyncRead + Unpin)) -> Result<(u32, u16), CliError> {
+    let truncated = || CliError::usage("Input ends inside the WAV header");
+
+    let mut riff = [0u8; 8];
+    if read_full(input, &mut riff).await? < riff.len() {
+        return Err(truncated());
+    }
+    if &riff[4..] != b"WAVE" {
+        return Err(CliError::usage("Input is a RIFF file but not a WAV file"));
+    }
+
+    let mut format = None;
+    loop {
+        let mut header = [0u8; 8];
+        if read_full(input, &mut header).await? < header.len() {
+            return Err(truncated());
+        }
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+        if &header[..4] == b"data" {
+            return format.ok_or_else(|| CliError::usage("WAV data comes before its format"));
+        }
+
+        // Chunks are padded to an even length
+        let mut body = vec![0u8; size + size % 2];
+        if read_full(input, &mut body).await? < body.len() {
+            return Err(truncated());
+        }
+        if &header[..4] == b"fmt " {
+            if body.len() < 16 {
+                return Err(CliError::usage("WAV format chunk is too short"));
+            }
+            let tag = u16::from_le_bytes([body[0], body[1]]);
+            let channels = u16::from_le_bytes([body[2], body[3]]);
+            let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+            let bits = u16::from_le_bytes([body[14], body[15]]);
+            if !matches!(tag, WAVE_FORMAT_PCM | WAVE_FORMAT_EXTENSIBLE) || bits != 16 {
+                return Err(CliError::usage(
+                    "Only 16-bit PCM WAV input is supported (try 'sox in.wav -b 16 -t wav -')",
+                ));
+            }
+            format = Some((rate, channels));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A format chunk body
+    fn fmt(tag: u16, channels: u16, rate: u32, bits: u16) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+        let mut body = Vec::new();
+        body.extend_from_slice(&tag.to_le_bytes());
+        body.extend_from_slice(&channels.to_le_bytes());
+        body.extend_from_slice(&rate.to_le_bytes());
+        body.extend_from_slice(&(rate * u32::from(block_align)).to_le_bytes());
+        body.extend_from_slice(&block_align.to_le_bytes());
+        body.extend_from_slice(&bits.to_le_bytes());
+        body
+    }
+
+    /// A WAV file after its leading "RIFF", which `run` has already read
+    fn wav(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut file = Vec::new();
+        file.extend_from_slice(&0u32.to_le_bytes());
+        file.extend_from_slice(b"WAVE");
+        for (id, body) in chunks {
+            file.extend_from_slice(*id);
+            file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            file.extend_from_slice(body);
+            if body.len() % 2 == 1 {
+                file.push(0);
+            }
+        }
+        file
+    }
+
+    async fn read(file: &[u8]) -> Result<(u32, u16), String> {
+        let mut input = file;
+        read_wav_header(&mut input).await.map_err(|e| e.message)
+    }
+
+    #[tokio::test]
+    async fn header_is_read_up_to_the_samples() {
+        let file = wav(&[
+            (b"fmt ", fmt(WAVE_FORMAT_PCM, 2, 44100, 16)),
+            (b"data", vec![1, 2, 3, 4]),
+        ]);
+        let mut input = file.as_slice();
+        let format = read_wav_header(&mut input).await.map_err(|e| e.message);
+        assert_eq!(format, Ok((44100, 2)));
+        // The data chunk's samples are left for the caller
+        assert_eq!(input, [1, 2, 3, 4]);
+
+        let file = wav(&[
+            (b"fmt ", fmt(WAVE_FORMAT_EXTENSIBLE, 1, 16000, 16)),
+            (b"data", Vec::new()),
+        ]);
+        assert_eq!(read(&file).await, Ok((16000, 1)));
+    }
+
+    #[tokio::test]
+    async fn odd_chunks_are_skipped_with_their_padding() {
+        let file = wav(&[
+            (b"LIST", vec![7; 3]),
+            (b"fmt ", fmt(WAVE_FORMAT_PCM, 1, 8000, 16)),
+            (b"junk", vec![7; 5]),
+            (b"data", Vec::new()),
+        ]);
+        assert_eq!(read(&file).await, Ok((8000, 1)));
+    }
+
+    #[tokio::test]
+    async fn data_before_format_is_rejected() {
+        let file = wav(&[
+            (b"data", Vec::new()),
+            (b"fmt ", fmt(WAVE_FORMAT_PCM, 1, 8000, 16)),
+        ]);
+        assert!(read(&file).await.unwrap_err().contains("before its format"));
+    }
+
+    #[tokio::test]
+    async fn only_16_bit_pcm_is_accepted() {
+        for format in [
+            fmt(WAVE_FORMAT_PCM, 1, 48000, 24),
+            fmt(WAVE_FORMAT_PCM, 1, 48000, 8),
+            // IEEE float
+            fmt(3, 1, 48000, 32),
+        ] {
+            let file = wav(&[(b"fmt ", format), (b"data", Vec::new())]);
+            assert!(read(&file).await.unwrap_err().contains("16-bit PCM"));
+        }
+
+        let file = wav(&[(b"fmt ", vec![0; 14]), (b"data", Vec::new())]);
+        assert!(read(&file).await.unwrap_err().contains("too short"));
+    }
+
+    #[tokio::test]
+    async fn truncated_and_non_wav_input_is_rejected() {
+        let file = wav(&[(b"fmt ", fmt(WAVE_FORMAT_PCM, 1, 8000, 16))]);
+        for len in [4, 12, file.len() - 1, file.len()] {
+            assert!(read(&file[..len])
+                .await
+                .unwrap_err()
+                .contains("ends inside"));
+        }
+
+        let mut file = wav(&[]);
+        file[4..8].copy_from_slice(b"AVI ");
+        assert!(read(&file).await.unwrap_err().contains("not a WAV file"));
+    }
+}
@@ -4,12 +4,13 @@
 //! in the user's configuration directory. It is shared between the CLI and
 //! service so both can read/write config in offline mode.
 
-use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::i18n::Language;
 use crate::types::{HotkeyCombination, KeyCode, TranscriptionMode};
 
 /// Theme mode for the application UI.
@@ -22,6 +23,192 @@ pub enum ThemeMode {
     Dark,
 }
 
+/// Color gradient of the spectrogram, from silence to the loudest frequency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VisualizationPalette {
+    /// Dark blue through green and yellow to red
+    #[default]
+    Classic,
+    /// Dark purple through teal to yellow
+    Viridis,
+    /// Black through purple and orange to pale yellow
+    Magma,
+    /// Black to white
+    Grayscale,
+    /// Dark to the accent color to white
+    Accent,
+}
+
+/// A color stop of a custom spectrogram gradient.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradientStop {
+    /// Position in the gradient from 0.0 (silence) to 1.0 (loudest)
+    pub position: f32,
+    /// Color as `#rrggbb`
+    pub color: String,
+}
+
+/// Quality/performance trade-off for the waveform and spectrogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VisualizationPreset {
+    /// Coarse spectrogram and fewer updates, for slow machines
+    Low,
+    /// The defaults
+    Balanced,
+    /// Finer frequency resolution and a taller spectrogram
+    High,
+}
+
+/// How the waveform and spectrogram sent to clients are computed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisualizationSettings {
+    /// Samples per spectrogram FFT; a power of two in `FFT_SIZES`
+    #[serde(default = "default_fft_size")]
+    pub fft_size: u32,
+    /// Rows in each spectrogram column (within `OUTPUT_HEIGHTS`)
+    #[serde(default = "default_output_height")]
+    pub output_height: u32,
+    /// Waveform points sent per update
+    #[serde(default = "default_waveform_samples")]
+    pub waveform_samples: u32,
+    /// Most visualization updates sent per second (0 = one per audio chunk)
+    #[serde(default)]
+    pub target_fps: u32,
+    /// Spectrogram gradient that replaces the palette when not empty
+    #[serde(default)]
+    pub gradient: Vec<GradientStop>,
+}
+
+fn default_fft_size() -> u32 {
+    512
+}
+
+fn default_output_height() -> u32 {
+    256
+}
+
+fn default_waveform_samples() -> u32 {
+    64
+}
+
+/// Accepted spectrogram FFT sizes
+pub const FFT_SIZES: std::ops::RangeInclusive<u32> = 128..=4096;
+
+/// Accepted spectrogram column heights
+pub const OUTPUT_HEIGHTS: std::ops::RangeInclusive<u32> = 32..=1024;
+
+/// Accepted waveform points per update
+pub const WAVEFORM_SAMPLES: std::ops::RangeInclusive<u32> = 16..=512;
+
+/// Highest accepted target update rate
+pub const MAX_TARGET_FPS: u32 = 120;
+
+impl Default for VisualizationSettings {
+    fn default() -> Self {
+        Self::preset(VisualizationPreset::Balanced)
+    }
+}
+
+impl VisualizationSettings {
+    /// Settings for a quality/performance preset.
+    pub fn preset(preset: VisualizationPreset) -> Self {
+        let (fft_size, output_height, waveform_samples, target_fps) = match preset {
+            VisualizationPreset::Low => (256, 128, 32, 30),
+            VisualizationPreset::Balanced => (
+                default_fft_size(),
+                default_output_height(),
+                default_waveform_samples(),
+                0,
+            ),
+            VisualizationPreset::High => (1024, 512, 128, 0),
+        };
+        Self {
+            fft_size,
+            output_height,
+            waveform_samples,
+            target_fps,
+            gradient: Vec::new(),
+        }
+    }
+
+    /// Check that every value is in range.
+    pub fn validate(&self) -> Result<(), String> {
+        if !FFT_SIZES.contains(&self.fft_size) || !self.fft_size.is_power_of_two() {
+            return Err(format!(
+                "fft_size must be a power of two from {} to {}",
+                FFT_SIZES.start(),
+                FFT_SIZES.end()
+            ));
+        }
+        if !OUTPUT_HEIGHTS.contains(&self.output_height) {
+            return Err(format!(
+                "output_height must be between {} and {}",
+                OUTPUT_HEIGHTS.start(),
+                OUTPUT_HEIGHTS.end()
+            ));
+        }
+        if !WAVEFORM_SAMPLES.contains(&self.waveform_samples) {
+            return Err(format!(
+                "waveform_samples must be between {} and {}",
+                WAVEFORM_SAMPLES.start(),
+                WAVEFORM_SAMPLES.end()
+            ));
+        }
+        if self.target_fps > MAX_TARGET_FPS {
+            return Err(format!("target_fps must be at most {}", MAX_TARGET_FPS));
+        }
+        if self.gradient.len() == 1 {
+            return Err("gradient needs at least two stops".to_string());
+        }
+        let mut previous = 0.0;
+        for (i, stop) in self.gradient.iter().enumerate() {
+            let last = i + 1 == self.gradient.len();
+            let in_order = (previous..=1.0).contains(&stop.position);
+            if !in_order || (i == 0 && stop.position != 0.0) || (last && stop.position != 1.0) {
+                return Err("gradient stop positions must rise from 0.0 to 1.0".to_string());
+            }
+            if parse_hex_color(&stop.color).is_none() {
+                return Err(format!(
+                    "gradient color must be a #rrggbb color, got '{}'",
+                    stop.color
+                ));
+            }
+            previous = stop.position;
+        }
+        Ok(())
+    }
+}
+
+/// Appearance settings shared by every window: the theme mode, the accent
+/// color and the spectrogram palette.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    /// Light, dark, or follow the OS
+    #[serde(default)]
+    pub mode: ThemeMode,
+    /// Accent color as `#rrggbb`; `None` uses the theme's own
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Spectrogram color gradient
+    #[serde(default)]
+    pub visualization_palette: VisualizationPalette,
+}
+
+/// Accent color used by the `Accent` palette when none is configured
+pub const DEFAULT_ACCENT_COLOR: [u8; 3] = [0x3b, 0x82, 0xf6];
+
+/// Parse a `#rrggbb` color.
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
 /// Minimum log level for the tracing subscriber.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -47,6 +234,845 @@ impl LogLevel {
     }
 }
 
+/// Speech-to-text backend used by the transcription worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionEngineKind {
+    /// whisper.cpp with the downloaded model
+    #[default]
+    Whisper,
+    /// Deterministic canned text without a model, for integration tests
+    Mock,
+}
+
+impl TranscriptionEngineKind {
+    /// Environment variable that overrides the configured engine
+    pub const ENV_VAR: &'static str = "FLOWSTT_TRANSCRIPTION_ENGINE";
+
+    /// Parse an engine name as used in config and [`Self::ENV_VAR`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "whisper" => Some(Self::Whisper),
+            "mock" => Some(Self::Mock),
+            _ => None,
+        }
+    }
+}
+
+/// Audio the speech detector listens to when a secondary source is mixed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VadSource {
+    /// The recorded audio, including the secondary source
+    #[default]
+    Mixed,
+    /// Only the primary source (usually the microphone), so system audio
+    /// can't start or extend segments. The mixed audio is still recorded.
+    Primary,
+}
+
+/// Level adjustment applied to each segment before transcription.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentNormalization {
+    /// Transcribe segments at the level they were captured
+    #[default]
+    Off,
+    /// Scale each segment so its loudest sample is just below full scale
+    Peak,
+    /// Scale each segment to a common speech loudness, without clipping
+    Loudness,
+}
+
+/// How consecutive pasted segments are joined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentJoining {
+    /// Separate segments with a single space
+    #[default]
+    Space,
+    /// Put each segment on a line of its own
+    Newline,
+    /// Separate segments with a space and carry the sentence across them:
+    /// capitalize after a full stop, lowercase after a comma
+    SmartSentence,
+}
+
+/// How speech in Automatic mode is cut into segments for transcription.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentationStrategy {
+    /// Short segments split at the first pause after a few seconds, so text
+    /// appears quickly
+    #[default]
+    Standard,
+    /// Segments run until the speaker goes quiet, up to about 30 seconds,
+    /// giving whisper whole sentences for context; segments cut mid-speech
+    /// overlap the next one and the repeated words are dropped
+    LongForm,
+}
+
+/// What to do when the primary input drops to a narrowband (telephony)
+/// sample rate, as a Bluetooth headset does when it switches from A2DP to the
+/// hands-free profile to open its microphone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NarrowbandInput {
+    /// Keep capturing and warn that transcription quality will suffer
+    #[default]
+    Warn,
+    /// Switch to the first fallback input that is not narrowband, so the
+    /// headset can stay in its high-quality profile
+    PreferHighQuality,
+}
+
+/// Per-device input calibration, produced by `flowstt calibrate`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCalibration {
+    /// Amplitude threshold in dB for voiced speech detection
+    pub voiced_threshold_db: f32,
+    /// Amplitude threshold in dB for soft/whispered speech detection
+    pub whisper_threshold_db: f32,
+    /// Software gain in dB applied to the input before processing
+    #[serde(default)]
+    pub gain_db: f32,
+}
+
+impl Default for DeviceCalibration {
+    /// Matches the built-in speech detector thresholds with no extra gain.
+    fn default() -> Self {
+        Self {
+            voiced_threshold_db: flowstt_dsp::DEFAULT_VOICED_THRESHOLD_DB,
+            whisper_threshold_db: flowstt_dsp::DEFAULT_WHISPER_THRESHOLD_DB,
+            gain_db: 0.0,
+        }
+    }
+}
+
+impl DeviceCalibration {
+    /// Suggest a calibration from a measured noise floor and speech sample.
+    ///
+    /// Gain brings typical speech toward -26 dB while keeping 3 dB of headroom
+    /// below the measured peak. Thresholds are then placed between the
+    /// (gained) noise floor and speech level.
+    pub fn suggest(noise_floor_db: f32, speech_rms_db: f32, speech_peak_db: f32) -> Self {
+        const TARGET_SPEECH_DB: f32 = -26.0;
+        const HEADROOM_DB: f32 = 3.0;
+
+        let wanted_gain = TARGET_SPEECH_DB - speech_rms_db;
+        let max_gain = -HEADROOM_DB - speech_peak_db;
+        let mut gain_db = wanted_gain.min(max_gain).clamp(-12.0, 24.0);
+        // Round to 0.5 dB and ignore insignificant adjustments
+        gain_db = (gain_db * 2.0).round() / 2.0;
+        if gain_db.abs() < 1.0 {
+            gain_db = 0.0;
+        }
+
+        let floor = noise_floor_db + gain_db;
+        let speech = speech_rms_db + gain_db;
+
+        let voiced_threshold_db = ((floor + speech) / 2.0)
+            .min(speech - 6.0)
+            .clamp(-60.0, -20.0);
+        let whisper_threshold_db = (voiced_threshold_db - 10.0)
+            .max(floor + 6.0)
+            .min(voiced_threshold_db - 3.0)
+            .clamp(-70.0, -25.0);
+
+        Self {
+            voiced_threshold_db: voiced_threshold_db.round(),
+            whisper_threshold_db: whisper_threshold_db.round(),
+            gain_db,
+        }
+    }
+}
+
+/// Per-category desktop notification switches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// Show the transcribed text when a segment completes
+    #[serde(default)]
+    pub transcription: bool,
+    /// Notify when a model download finishes or fails
+    #[serde(default = "default_true")]
+    pub model_download: bool,
+    /// Notify when audio capture stops because of an error
+    #[serde(default = "default_true")]
+    pub capture_errors: bool,
+    /// Warn when captured audio is clipping
+    #[serde(default = "default_true")]
+    pub clipping: bool,
+    /// Warn when the input switches to a low-quality narrowband profile
+    #[serde(default = "default_true")]
+    pub input_quality: bool,
+    /// Notify when a transcription was copied but couldn't be pasted
+    #[serde(default = "default_true")]
+    pub paste_failures: bool,
+    /// Notify when a new release is available
+    #[serde(default = "default_true")]
+    pub updates: bool,
+}
+
+impl Default for NotificationSettings {
+    /// Transcription toasts are opt-in; download and error notices are on.
+    fn default() -> Self {
+        Self {
+            transcription: false,
+            model_download: true,
+            capture_errors: true,
+            clipping: true,
+            input_quality: true,
+            paste_failures: true,
+            updates: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Audio feedback cues played through the default output device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoundCueSettings {
+    /// Playback volume from 0.0 (silent) to 1.0 (full scale)
+    #[serde(default = "default_cue_volume")]
+    pub volume: f32,
+    /// Play a cue when the push-to-talk key is pressed
+    #[serde(default)]
+    pub ptt_start: bool,
+    /// Play a cue when the push-to-talk key is released
+    #[serde(default)]
+    pub ptt_stop: bool,
+    /// Play a cue when a transcription completes
+    #[serde(default)]
+    pub transcription_complete: bool,
+}
+
+impl Default for SoundCueSettings {
+    /// All cues are opt-in.
+    fn default() -> Self {
+        Self {
+            volume: default_cue_volume(),
+            ptt_start: false,
+            ptt_stop: false,
+            transcription_complete: false,
+        }
+    }
+}
+
+fn default_cue_volume() -> f32 {
+    0.3
+}
+
+/// When a process rule applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRuleTrigger {
+    /// The process is running anywhere in the session
+    #[default]
+    Running,
+    /// The process owns the foreground window
+    Foreground,
+}
+
+/// What FlowSTT does while a process rule applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRuleAction {
+    /// Stop capture until the rule no longer applies
+    #[default]
+    Pause,
+    /// Switch to push-to-talk, restoring the previous mode afterwards
+    PushToTalk,
+    /// Transform transcribed text before it is pasted, e.g. into an editor
+    Transform(OutputTransform),
+    /// Paste whole segments instead of typing as you speak, e.g. into a
+    /// terminal where a Backspace can't take back what was typed
+    NoPartialTyping,
+}
+
+/// A change made to transcribed text before it is pasted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTransform {
+    /// Lowercase all of the text
+    Lowercase,
+    /// Lowercase words joined with underscores: `user_name`
+    SnakeCase,
+    /// Words joined with every one but the first capitalized: `userName`
+    CamelCase,
+    /// Remove punctuation, keeping the words
+    StripPunctuation,
+}
+
+/// Pause, switch modes or transform pasted text while a specific application
+/// is running or focused.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessRule {
+    /// Process or executable name, e.g. `zoom` or `Game.exe` (case-insensitive)
+    pub process_name: String,
+    #[serde(default)]
+    pub trigger: ProcessRuleTrigger,
+    #[serde(default)]
+    pub action: ProcessRuleAction,
+}
+
+impl ProcessRule {
+    /// Check whether a process name matches this rule.
+    ///
+    /// Comparison ignores case and a trailing `.exe` on either side so the
+    /// same rule works across platforms.
+    pub fn matches(&self, process_name: &str) -> bool {
+        fn normalize(name: &str) -> String {
+            let name = name.trim().to_lowercase();
+            name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+        }
+        let wanted = normalize(&self.process_name);
+        !wanted.is_empty() && normalize(process_name) == wanted
+    }
+}
+
+/// A pattern whose matches are replaced with `[REDACTED]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RedactionRule {
+    /// Credit card numbers (13-19 digits, optionally grouped by spaces or dashes)
+    CreditCard,
+    /// Email addresses
+    Email,
+    /// A custom regular expression
+    Custom { pattern: String },
+}
+
+/// Redaction of sensitive content in transcribed text.
+///
+/// Rules apply to every output sink unless that sink is bypassed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedactionSettings {
+    /// Patterns to redact, applied in order
+    #[serde(default)]
+    pub rules: Vec<RedactionRule>,
+    /// Copy and paste the unredacted text
+    #[serde(default)]
+    pub bypass_clipboard: bool,
+    /// Store and display the unredacted text in history
+    #[serde(default)]
+    pub bypass_history: bool,
+}
+
+/// What the profanity filter does with a swear word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityMode {
+    /// Leave transcriptions as they are
+    #[default]
+    Off,
+    /// Keep the first letter and replace the rest with asterisks ("f***")
+    Mask,
+    /// Delete the word
+    Remove,
+}
+
+/// Filtering of swear words in transcribed text.
+///
+/// The filter applies to the clipboard and to the transcript and caption
+/// files, and to history unless `keep_raw_history` is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfanitySettings {
+    /// What to do with matched words
+    #[serde(default)]
+    pub mode: ProfanityMode,
+    /// Words filtered on top of the built-in list; whole words, ignoring case
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Store and display the unfiltered text in history
+    #[serde(default)]
+    pub keep_raw_history: bool,
+}
+
+/// A phrase replaced in transcribed text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplacementRule {
+    /// Phrase to replace; matches whole words, ignoring case
+    pub from: String,
+    /// Text to put in its place
+    pub to: String,
+    /// Disabled rules are kept but not applied (and never learned again)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Added automatically from manual corrections
+    #[serde(default)]
+    pub learned: bool,
+}
+
+/// Phrase replacements applied to transcribed text, and learning them from
+/// corrections made to history entries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrectionSettings {
+    /// Replacements applied in order, before redaction
+    #[serde(default)]
+    pub rules: Vec<ReplacementRule>,
+    /// Add a rule once the same correction has been made `learn_threshold` times
+    #[serde(default = "default_true")]
+    pub learn: bool,
+    /// Number of matching corrections needed before a rule is learned
+    #[serde(default = "default_learn_threshold")]
+    pub learn_threshold: u32,
+    /// Names and product terms (e.g. "FlowSTT", "McDonald", "iPhone")
+    /// rewritten with the capitalization given here wherever they are
+    /// transcribed, in any case, after the replacements
+    #[serde(default)]
+    pub dictionary: Vec<String>,
+}
+
+impl Default for CorrectionSettings {
+    /// No rules; learning is on.
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            learn: true,
+            learn_threshold: default_learn_threshold(),
+            dictionary: Vec::new(),
+        }
+    }
+}
+
+fn default_learn_threshold() -> u32 {
+    3
+}
+
+/// Spoken punctuation and formatting commands, such as "comma" or "new line".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpokenCommandSettings {
+    /// Replace spoken commands with the symbols they name
+    #[serde(default)]
+    pub enabled: bool,
+    /// Language of the command phrases: "en", "de", "fr" or "es"
+    #[serde(default = "default_spoken_command_locale")]
+    pub locale: String,
+}
+
+impl Default for SpokenCommandSettings {
+    /// Off, with English phrases.
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            locale: default_spoken_command_locale(),
+        }
+    }
+}
+
+fn default_spoken_command_locale() -> String {
+    "en".to_string()
+}
+
+/// Spoken phrases that pause and resume output, such as "stop dictation"
+/// and "resume dictation". Speech is still transcribed while output is
+/// paused, so the resume phrase is heard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PauseWords {
+    /// Listen for the pause and resume phrases
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Language of the built-in phrases: "en", "de", "fr" or "es"
+    #[serde(default = "default_spoken_command_locale")]
+    pub locale: String,
+    /// Phrases that pause output, used instead of the locale's
+    #[serde(default)]
+    pub pause_phrases: Vec<String>,
+    /// Phrases that resume output, used instead of the locale's
+    #[serde(default)]
+    pub resume_phrases: Vec<String>,
+}
+
+impl Default for PauseWords {
+    /// On, with the English phrases.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            locale: default_spoken_command_locale(),
+            pause_phrases: Vec::new(),
+            resume_phrases: Vec::new(),
+        }
+    }
+}
+
+/// Sharing history and rules with other machines through a folder kept in
+/// sync by a service such as Dropbox or Syncthing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncSettings {
+    /// Write changes to the folder and merge other machines' at startup
+    #[serde(default)]
+    pub enabled: bool,
+    /// The synced folder
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Also share replacement, redaction and dictionary rules
+    #[serde(default = "default_true")]
+    pub rules: bool,
+}
+
+impl Default for SyncSettings {
+    /// Off, sharing rules once a folder is chosen.
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: None,
+            rules: true,
+        }
+    }
+}
+
+/// What changes while a laptop runs on battery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PowerPolicy {
+    /// Apply the battery settings below while on battery power
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Whisper threads on battery (0 = the same as on AC power)
+    #[serde(default = "default_battery_threads")]
+    pub battery_threads: u32,
+    /// Model to transcribe with on battery, e.g. "tiny.en"; it must already
+    /// be downloaded. None keeps the usual model.
+    #[serde(default)]
+    pub battery_model: Option<String>,
+    /// Switch Automatic mode to push-to-talk on battery, restoring it on AC
+    /// power
+    #[serde(default)]
+    pub battery_push_to_talk: bool,
+}
+
+impl Default for PowerPolicy {
+    /// On, with fewer threads but the same model and mode.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            battery_threads: default_battery_threads(),
+            battery_model: None,
+            battery_push_to_talk: false,
+        }
+    }
+}
+
+fn default_battery_threads() -> u32 {
+    2
+}
+
+/// Lighter transcription while the machine is overloaded and segments pile
+/// up in the transcription queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadShedding {
+    /// Watch the CPU load and the transcription queue
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// CPU load, as a fraction of all cores, at which shedding starts while
+    /// segments are waiting
+    #[serde(default = "default_shed_cpu_load")]
+    pub cpu_load: f32,
+    /// Segments waiting for transcription at which shedding starts whatever
+    /// the load
+    #[serde(default = "default_shed_queue_depth")]
+    pub queue_depth: u32,
+    /// Model to transcribe with while shedding, e.g. "tiny.en"; it must
+    /// already be downloaded. None keeps the usual model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// While shedding, speech isn't split at word breaks until a segment is
+    /// this long (normally 4 seconds), so fewer segments are transcribed
+    /// (0 = split as usual)
+    #[serde(default = "default_shed_min_segment_ms")]
+    pub min_segment_ms: u32,
+}
+
+impl Default for LoadShedding {
+    /// On, with longer segments past 90% load or three waiting segments.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cpu_load: default_shed_cpu_load(),
+            queue_depth: default_shed_queue_depth(),
+            model: None,
+            min_segment_ms: default_shed_min_segment_ms(),
+        }
+    }
+}
+
+fn default_shed_cpu_load() -> f32 {
+    0.9
+}
+
+fn default_shed_queue_depth() -> u32 {
+    3
+}
+
+fn default_shed_min_segment_ms() -> u32 {
+    10_000
+}
+
+/// Only transcribe the enrolled speaker in Automatic mode, dropping segments
+/// whose voice doesn't match, such as a TV or a roommate. Enroll with
+/// `flowstt speaker enroll`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeakerFilter {
+    /// Drop segments from other speakers (needs a voiceprint)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Similarity to the voiceprint, from 0.0 to 1.0, below which a segment
+    /// is dropped; lower it if your own speech is dropped
+    #[serde(default = "default_min_speaker_similarity")]
+    pub min_similarity: f32,
+    /// Voiceprint recorded at enrollment
+    #[serde(default)]
+    pub voiceprint: Option<Vec<f32>>,
+}
+
+impl Default for SpeakerFilter {
+    /// Off, with no voiceprint.
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_similarity: default_min_speaker_similarity(),
+            voiceprint: None,
+        }
+    }
+}
+
+fn default_min_speaker_similarity() -> f32 {
+    0.8
+}
+
+/// Typing text into the foreground application while speech is still in
+/// progress. Interim transcriptions of the segment so far are typed as they
+/// arrive and corrected in place, with Backspace, when the segment ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialTyping {
+    /// Type interim transcriptions (needs auto-paste)
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often, in milliseconds of speech, the segment so far is
+    /// transcribed
+    #[serde(default = "default_partial_typing_interval_ms")]
+    pub interval_ms: u32,
+}
+
+impl Default for PartialTyping {
+    /// Off, transcribing every second of speech when turned on.
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: default_partial_typing_interval_ms(),
+        }
+    }
+}
+
+fn default_partial_typing_interval_ms() -> u32 {
+    1000
+}
+
+/// Limits on the disk space saved audio may take.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskSpace {
+    /// Megabytes to keep free on the disk; audio that would leave less isn't
+    /// saved (0 = no check)
+    #[serde(default = "default_min_free_mb")]
+    pub min_free_mb: u64,
+    /// Most megabytes the recordings folder may hold; the oldest recordings
+    /// are deleted to stay under it (0 = no limit)
+    #[serde(default = "default_max_recordings_mb")]
+    pub max_recordings_mb: u64,
+}
+
+impl Default for DiskSpace {
+    /// Keep 500 MB free and at most 2 GB of recordings.
+    fn default() -> Self {
+        Self {
+            min_free_mb: default_min_free_mb(),
+            max_recordings_mb: default_max_recordings_mb(),
+        }
+    }
+}
+
+fn default_min_free_mb() -> u64 {
+    500
+}
+
+fn default_max_recordings_mb() -> u64 {
+    2048
+}
+
+/// Folders that override the default locations (see `crate::paths`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PathSettings {
+    /// Folder recordings are saved to; none keeps them in the data folder
+    #[serde(default)]
+    pub recordings_dir: Option<String>,
+    /// Folder whisper models are downloaded to and loaded from; none keeps
+    /// them in the data folder
+    #[serde(default)]
+    pub models_dir: Option<String>,
+}
+
+/// Checking for new FlowSTT releases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// Check the release feed in the background
+    #[serde(default)]
+    pub check_enabled: bool,
+    /// Hours between background checks
+    #[serde(default = "default_update_interval_hours")]
+    pub interval_hours: u32,
+    /// Download the installer of a new release so it is ready to run
+    #[serde(default)]
+    pub stage_downloads: bool,
+    /// Release feed in the format of the GitHub releases API
+    #[serde(default = "default_update_feed_url")]
+    pub feed_url: String,
+}
+
+impl Default for UpdateSettings {
+    /// Background checks are opt-in; a check asked for still works.
+    fn default() -> Self {
+        Self {
+            check_enabled: false,
+            interval_hours: default_update_interval_hours(),
+            stage_downloads: false,
+            feed_url: default_update_feed_url(),
+        }
+    }
+}
+
+fn default_update_interval_hours() -> u32 {
+    24
+}
+
+fn default_update_feed_url() -> String {
+    "https://api.github.com/repos/keathmilligan/flowstt/releases/latest".to_string()
+}
+
+/// An output sink's place in the order transcriptions are delivered, and
+/// whether it is on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputSinkSettings {
+    /// Name of the sink, one of `OUTPUT_SINKS` for the built-in ones
+    pub name: String,
+    /// Deliver transcriptions to the sink
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// Built-in output sinks, in their default order
+pub const OUTPUT_SINKS: [&str; 5] = [
+    "history",
+    "transcript_file",
+    "caption_file",
+    "activity_log",
+    "clipboard",
+];
+
+fn default_output_sinks() -> Vec<OutputSinkSettings> {
+    OUTPUT_SINKS
+        .iter()
+        .map(|name| OutputSinkSettings {
+            name: name.to_string(),
+            enabled: true,
+        })
+        .collect()
+}
+
+/// Templates that format transcribed text for each output sink.
+///
+/// `{text}` is the transcript; `{time}`, `{date}` and `{timestamp}` are the
+/// local time it completed (`14:05:09`, `2024-05-01` and RFC 3339). `{{` and
+/// `}}` write literal braces, and unknown variables are left as written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputTemplates {
+    /// Text copied to the clipboard and pasted. When pasting, surrounding
+    /// whitespace gives way to the `segment_joining` separator.
+    #[serde(default = "default_clipboard_template")]
+    pub clipboard: String,
+    /// Line appended to the `transcript_sink` file or named pipe
+    #[serde(default = "default_transcript_sink_template")]
+    pub transcript_sink: String,
+}
+
+impl Default for OutputTemplates {
+    /// The transcript followed by a space, so copied segments don't merge,
+    /// and the bare transcript for the transcript sink.
+    fn default() -> Self {
+        Self {
+            clipboard: default_clipboard_template(),
+            transcript_sink: default_transcript_sink_template(),
+        }
+    }
+}
+
+fn default_clipboard_template() -> String {
+    "{text} ".to_string()
+}
+
+fn default_transcript_sink_template() -> String {
+    "{text}".to_string()
+}
+
+/// A file holding only the latest captions, rewritten after every
+/// transcription, for streaming overlays such as an OBS text source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptionFileSettings {
+    /// File to write; no path turns the caption file off
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Seconds a transcription stays in the file (0 = until newer text
+    /// pushes it out)
+    #[serde(default = "default_caption_window_secs")]
+    pub window_secs: u32,
+    /// Longest line, in characters; longer text is wrapped at word boundaries
+    #[serde(default = "default_caption_line_chars")]
+    pub max_line_chars: u32,
+    /// Lines kept in the file, newest last
+    #[serde(default = "default_caption_lines")]
+    pub max_lines: u32,
+}
+
+impl Default for CaptionFileSettings {
+    /// Off; two lines of up to 42 characters, cleared after 10 seconds.
+    fn default() -> Self {
+        Self {
+            path: None,
+            window_secs: default_caption_window_secs(),
+            max_line_chars: default_caption_line_chars(),
+            max_lines: default_caption_lines(),
+        }
+    }
+}
+
+fn default_caption_window_secs() -> u32 {
+    10
+}
+
+fn default_caption_line_chars() -> u32 {
+    42
+}
+
+fn default_caption_lines() -> u32 {
+    2
+}
+
+/// Text rules in a form that can be shared between machines: the format of
+/// `flowstt rules export` and `flowstt rules import`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserRules {
+    /// Phrase replacements
+    #[serde(default)]
+    pub replacements: Vec<ReplacementRule>,
+    /// Redaction patterns
+    #[serde(default)]
+    pub redaction: Vec<RedactionRule>,
+    /// Terms written with the capitalization given
+    #[serde(default)]
+    pub dictionary: Vec<String>,
+}
+
 /// Service configuration that persists across restarts.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -59,6 +1085,12 @@ pub struct Config {
     /// Configured auto-mode toggle hotkeys
     #[serde(default = "default_auto_toggle_hotkeys")]
     pub auto_toggle_hotkeys: Vec<HotkeyCombination>,
+    /// Hotkeys that start a dictation session, or end the one in progress
+    #[serde(default)]
+    pub dictation_hotkeys: Vec<HotkeyCombination>,
+    /// Hotkeys that turn spelling mode on or off
+    #[serde(default)]
+    pub spelling_hotkeys: Vec<HotkeyCombination>,
     /// Whether auto-paste into the foreground application is enabled
     #[serde(default = "default_auto_paste_enabled")]
     pub auto_paste_enabled: bool,
@@ -68,6 +1100,15 @@ pub struct Config {
     /// UI theme mode: auto (follow OS), light, or dark
     #[serde(default)]
     pub theme_mode: ThemeMode,
+    /// Accent color of the UI as `#rrggbb`; `None` uses the theme's own
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Spectrogram color gradient
+    #[serde(default)]
+    pub visualization_palette: VisualizationPalette,
+    /// Resolution, update rate and custom gradient of the visualization
+    #[serde(default)]
+    pub visualization: VisualizationSettings,
     /// Whether the main window is pinned above all other windows
     #[serde(default)]
     pub always_on_top: bool,
@@ -77,15 +1118,146 @@ pub struct Config {
     /// Preferred reference (system) audio device ID (restored on startup)
     #[serde(default)]
     pub preferred_source2_id: Option<String>,
+    /// Input device IDs to capture from, in order, when the primary source is
+    /// held exclusively by another application
+    #[serde(default)]
+    pub fallback_source_ids: Vec<String>,
     /// Minimum log level for the tracing subscriber (default: info)
     #[serde(default)]
     pub log_level: LogLevel,
+    /// Per-device VAD threshold and gain calibration, keyed by device ID
+    #[serde(default)]
+    pub device_calibrations: HashMap<String, DeviceCalibration>,
+    /// Desktop notification categories
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Audio feedback cues
+    #[serde(default)]
+    pub sound_cues: SoundCueSettings,
+    /// Rules that pause capture or switch modes based on running applications
+    #[serde(default)]
+    pub process_rules: Vec<ProcessRule>,
+    /// Suspend Automatic mode capture after this many minutes without speech (0 = never)
+    #[serde(default)]
+    pub auto_suspend_minutes: u32,
+    /// Audio that Automatic mode speech detection runs on
+    #[serde(default)]
+    pub vad_source: VadSource,
+    /// Encrypt the history file and saved recordings with a key from the OS keychain
+    #[serde(default)]
+    pub encrypt_history: bool,
+    /// Redaction rules for transcribed text
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    /// Swear word filtering for transcribed text
+    #[serde(default)]
+    pub profanity: ProfanitySettings,
+    /// Phrase replacements for transcribed text
+    #[serde(default)]
+    pub corrections: CorrectionSettings,
+    /// Spoken punctuation and formatting commands, interpreted before the
+    /// phrase replacements
+    #[serde(default)]
+    pub spoken_commands: SpokenCommandSettings,
+    /// Spoken phrases that pause and resume output
+    #[serde(default)]
+    pub pause_words: PauseWords,
+    /// Formatting of transcribed text for each output sink
+    #[serde(default)]
+    pub output_templates: OutputTemplates,
+    /// Submit and restart the segment after holding PTT this many seconds (0 = only
+    /// when the capture buffer is nearly full)
+    #[serde(default = "default_ptt_max_duration_secs")]
+    pub ptt_max_duration_secs: u32,
+    /// Transcription backend (overridden by `FLOWSTT_TRANSCRIPTION_ENGINE`)
+    #[serde(default)]
+    pub transcription_engine: TranscriptionEngineKind,
+    /// Save each segment as submitted for transcription (16kHz mono WAV plus
+    /// segmentation metadata) to the `segment-dumps` data directory
+    #[serde(default)]
+    pub dump_segments: bool,
+    /// Level adjustment applied to each segment before transcription, so quiet
+    /// and loud speakers are transcribed alike
+    #[serde(default)]
+    pub segment_normalization: SegmentNormalization,
+    /// Response to the primary input dropping to a narrowband sample rate
+    #[serde(default)]
+    pub narrowband_input: NarrowbandInput,
+    /// Separator and case handling between segments pasted one after another
+    /// into the same window
+    #[serde(default)]
+    pub segment_joining: SegmentJoining,
+    /// File or named pipe each transcription is appended to as one line, for
+    /// caption overlays and `tail -f`
+    #[serde(default)]
+    pub transcript_sink: Option<String>,
+    /// Rolling caption file for streaming overlays
+    #[serde(default)]
+    pub caption_file: CaptionFileSettings,
+    /// Record when speech starts and ends and how many words are transcribed,
+    /// for `flowstt stats`
+    #[serde(default)]
+    pub activity_log: bool,
+    /// Sharing history and rules with other machines
+    #[serde(default)]
+    pub sync: SyncSettings,
+    /// Lighter transcription while running on battery
+    #[serde(default)]
+    pub power_policy: PowerPolicy,
+    /// Lighter transcription while transcription falls behind
+    #[serde(default)]
+    pub load_shedding: LoadShedding,
+    /// Seconds of audio the capture buffer holds, bounding the longest
+    /// utterance (clamped to `CAPTURE_BUFFER_SECS`)
+    #[serde(default = "default_capture_buffer_secs")]
+    pub capture_buffer_secs: u32,
+    /// How speech in Automatic mode is cut into segments
+    #[serde(default)]
+    pub segmentation: SegmentationStrategy,
+    /// Audio in milliseconds repeated at the start of a segment when the one
+    /// before it was cut mid-speech; words transcribed twice are dropped
+    /// (0 = no overlap, at most `MAX_SEGMENT_OVERLAP_MS`)
+    #[serde(default = "default_segment_overlap_ms")]
+    pub segment_overlap_ms: u32,
+    /// Folders that override where recordings and models are kept
+    #[serde(default)]
+    pub paths: PathSettings,
+    /// Free space to keep and the most space recordings may take
+    #[serde(default)]
+    pub disk_space: DiskSpace,
+    /// Order transcriptions are delivered to the output sinks in, and which
+    /// are on; sinks left out are delivered to last
+    #[serde(default = "default_output_sinks")]
+    pub output_sinks: Vec<OutputSinkSettings>,
+    /// Checking for and downloading new releases
+    #[serde(default)]
+    pub updates: UpdateSettings,
+    /// Write a report to the logs directory when FlowSTT crashes, for
+    /// `flowstt debug report`
+    #[serde(default)]
+    pub crash_reports: bool,
+    /// Pause capture while the session is locked (Windows)
+    #[serde(default)]
+    pub pause_on_lock: bool,
+    /// Language of tray labels, notifications and CLI messages
+    #[serde(default)]
+    pub language: Language,
+    /// Drop Automatic mode speech from anyone but the enrolled speaker
+    #[serde(default)]
+    pub speaker_filter: SpeakerFilter,
+    /// Type interim transcriptions as you speak
+    #[serde(default)]
+    pub partial_typing: PartialTyping,
 }
 
 fn default_auto_toggle_hotkeys() -> Vec<HotkeyCombination> {
     vec![]
 }
 
+fn default_ptt_max_duration_secs() -> u32 {
+    25
+}
+
 fn default_auto_paste_enabled() -> bool {
     true
 }
@@ -94,6 +1266,23 @@ fn default_auto_paste_delay_ms() -> u32 {
     50
 }
 
+/// Longest accepted delay between the clipboard write and the simulated paste
+pub const MAX_AUTO_PASTE_DELAY_MS: u32 = 5000;
+
+fn default_capture_buffer_secs() -> u32 {
+    30
+}
+
+/// Accepted capture buffer lengths in seconds
+pub const CAPTURE_BUFFER_SECS: std::ops::RangeInclusive<u32> = 30..=120;
+
+fn default_segment_overlap_ms() -> u32 {
+    300
+}
+
+/// Longest accepted overlap between consecutive segments
+pub const MAX_SEGMENT_OVERLAP_MS: u32 = 2000;
+
 /// Legacy configuration format for backward-compatible loading.
 #[derive(Debug, Deserialize)]
 struct LegacyConfig {
@@ -107,35 +1296,164 @@ struct LegacyConfig {
     auto_toggle_hotkey: Option<HotkeyCombination>,
     /// New multi auto-toggle hotkeys field
     auto_toggle_hotkeys: Option<Vec<HotkeyCombination>>,
+    /// Dictation session hotkeys (may be absent in old configs)
+    #[serde(default)]
+    dictation_hotkeys: Vec<HotkeyCombination>,
+    /// Spelling mode hotkeys (may be absent in old configs)
+    #[serde(default)]
+    spelling_hotkeys: Vec<HotkeyCombination>,
     /// Whether auto-paste is enabled (may be absent in old configs)
     auto_paste_enabled: Option<bool>,
     /// Auto-paste delay in ms (may be absent in old configs)
     auto_paste_delay_ms: Option<u32>,
     /// UI theme mode (may be absent in old configs)
     theme_mode: Option<ThemeMode>,
+    /// Accent color (may be absent in old configs)
+    #[serde(default)]
+    accent_color: Option<String>,
+    /// Spectrogram palette (may be absent in old configs)
+    #[serde(default)]
+    visualization_palette: VisualizationPalette,
+    /// Visualization settings (may be absent in old configs)
+    #[serde(default)]
+    visualization: VisualizationSettings,
+    /// Always-on-top flag (may be absent in old configs)
+    #[serde(default)]
+    always_on_top: bool,
     /// Preferred primary audio input device ID
     #[serde(default)]
     preferred_source1_id: Option<String>,
     /// Preferred reference (system) audio device ID
     #[serde(default)]
     preferred_source2_id: Option<String>,
+    /// Fallback input devices (may be absent in old configs)
+    #[serde(default)]
+    fallback_source_ids: Vec<String>,
     /// Minimum log level (may be absent in old configs)
     log_level: Option<LogLevel>,
+    /// Per-device calibration (may be absent in old configs)
+    #[serde(default)]
+    device_calibrations: HashMap<String, DeviceCalibration>,
+    /// Notification categories (may be absent in old configs)
+    #[serde(default)]
+    notifications: NotificationSettings,
+    /// Sound cue settings (may be absent in old configs)
+    #[serde(default)]
+    sound_cues: SoundCueSettings,
+    /// Process rules (may be absent in old configs)
+    #[serde(default)]
+    process_rules: Vec<ProcessRule>,
+    /// Idle auto-suspend timeout (may be absent in old configs)
+    #[serde(default)]
+    auto_suspend_minutes: u32,
+    /// Speech detection source (may be absent in old configs)
+    #[serde(default)]
+    vad_source: VadSource,
+    /// History encryption flag (may be absent in old configs)
+    #[serde(default)]
+    encrypt_history: bool,
+    /// Redaction settings (may be absent in old configs)
+    #[serde(default)]
+    redaction: RedactionSettings,
+    /// Profanity filter (may be absent in old configs)
+    #[serde(default)]
+    profanity: ProfanitySettings,
+    /// Phrase replacements (may be absent in old configs)
+    #[serde(default)]
+    corrections: CorrectionSettings,
+    /// Spoken commands (may be absent in old configs)
+    #[serde(default)]
+    spoken_commands: SpokenCommandSettings,
+    /// Pause words (may be absent in old configs)
+    #[serde(default)]
+    pause_words: PauseWords,
+    /// Output templates (may be absent in old configs)
+    #[serde(default)]
+    output_templates: OutputTemplates,
+    /// PTT maximum duration (may be absent in old configs)
+    #[serde(default = "default_ptt_max_duration_secs")]
+    ptt_max_duration_secs: u32,
+    /// Transcription backend (may be absent in old configs)
+    #[serde(default)]
+    transcription_engine: TranscriptionEngineKind,
+    /// Segment dump flag (may be absent in old configs)
+    #[serde(default)]
+    dump_segments: bool,
+    /// Segment normalization (may be absent in old configs)
+    #[serde(default)]
+    segment_normalization: SegmentNormalization,
+    /// Narrowband input response (may be absent in old configs)
+    #[serde(default)]
+    narrowband_input: NarrowbandInput,
+    /// Segment joining (may be absent in old configs)
+    #[serde(default)]
+    segment_joining: SegmentJoining,
+    /// Transcript sink path (may be absent in old configs)
+    #[serde(default)]
+    transcript_sink: Option<String>,
+    /// Caption file settings (may be absent in old configs)
+    #[serde(default)]
+    caption_file: CaptionFileSettings,
+    /// Activity log flag (may be absent in old configs)
+    #[serde(default)]
+    activity_log: bool,
+    /// Sync settings (may be absent in old configs)
+    #[serde(default)]
+    sync: SyncSettings,
+    /// Power policy (may be absent in old configs)
+    #[serde(default)]
+    power_policy: PowerPolicy,
+    /// Load shedding (may be absent in old configs)
+    #[serde(default)]
+    load_shedding: LoadShedding,
+    /// Capture buffer length (may be absent in old configs)
+    #[serde(default = "default_capture_buffer_secs")]
+    capture_buffer_secs: u32,
+    /// Segmentation strategy (may be absent in old configs)
+    #[serde(default)]
+    segmentation: SegmentationStrategy,
+    /// Segment overlap (may be absent in old configs)
+    #[serde(default = "default_segment_overlap_ms")]
+    segment_overlap_ms: u32,
+    /// Path overrides (may be absent in old configs)
+    #[serde(default)]
+    paths: PathSettings,
+    /// Disk space limits (may be absent in old configs)
+    #[serde(default)]
+    disk_space: DiskSpace,
+    /// Output sinks (may be absent in old configs)
+    #[serde(default = "default_output_sinks")]
+    output_sinks: Vec<OutputSinkSettings>,
+    /// Update checks (may be absent in old configs)
+    #[serde(default)]
+    updates: UpdateSettings,
+    /// Crash reports flag (may be absent in old configs)
+    #[serde(default)]
+    crash_reports: bool,
+    /// Pause on lock flag (may be absent in old configs)
+    #[serde(default)]
+    pause_on_lock: bool,
+    /// Language (may be absent in old configs)
+    #[serde(default)]
+    language: Language,
+    /// Speaker filter (may be absent in old configs)
+    #[serde(default)]
+    speaker_filter: SpeakerFilter,
+    /// Partial typing (may be absent in old configs)
+    #[serde(default)]
+    partial_typing: PartialTyping,
 }
 
 impl Config {
     /// Get the path to the configuration file.
     ///
     /// Returns platform-specific path:
-    /// - Linux: ~/.config/flowstt/config.json
+    /// - Linux: $XDG_CONFIG_HOME/flowstt/config.json
     /// - macOS: ~/Library/Application Support/flowstt/config.json
     /// - Windows: %APPDATA%\flowstt\config.json
+    /// - Portable mode: data\config.json next to the executable
     pub fn config_path() -> PathBuf {
-        BaseDirs::new()
-            .map(|d| d.config_dir().to_path_buf())
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("flowstt")
-            .join("config.json")
+        crate::paths::config_dir().join("config.json")
     }
 
     /// Load configuration from disk.
@@ -147,34 +1465,43 @@ impl Config {
     /// Returns the loaded configuration, or a default configuration if the file
     /// doesn't exist or can't be parsed.
     pub fn load() -> Self {
-        let path = Self::config_path();
+        Self::load_from(&Self::config_path())
+    }
 
+    /// Load configuration from `path`, or the defaults if it can't be read.
+    pub(crate) fn load_from(path: &Path) -> Self {
         if !path.exists() {
             return Self::default_with_hotkeys();
         }
 
-        match fs::read_to_string(&path) {
-            Ok(contents) => match serde_json::from_str::<LegacyConfig>(&contents) {
-                Ok(legacy) => Self::from_legacy(legacy),
-                Err(_) => Self::default_with_hotkeys(),
-            },
-            Err(_) => Self::default_with_hotkeys(),
-        }
+        fs::read(path)
+            .ok()
+            .and_then(|contents| Self::from_json(&contents).ok())
+            .unwrap_or_else(Self::default_with_hotkeys)
     }
 
-    /// Save configuration to disk.
-    ///
-    /// Creates the configuration directory if it doesn't exist.
-    pub fn save(&self) -> io::Result<()> {
-        let path = Self::config_path();
+    /// Parse the contents of a config file, migrating older formats the same
+    /// way [`Config::load`] does.
+    pub fn from_json(contents: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice::<LegacyConfig>(contents).map(Self::from_legacy)
+    }
 
+    /// Save configuration to `path`, creating its directory if it doesn't
+    /// exist. The file is replaced in one step, so a reader never sees it
+    /// half written.
+    ///
+    /// Changes are saved through [`crate::settings::update`], which keeps
+    /// concurrent writers from overwriting each other.
+    pub(crate) fn save_to(&self, path: &Path) -> io::Result<()> {
         // Create parent directories if they don't exist
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
         let contents = serde_json::to_string_pretty(self)?;
-        fs::write(&path, contents)?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, contents)?;
+        fs::rename(&temp, path)?;
 
         Ok(())
     }
@@ -188,6 +1515,104 @@ impl Config {
         !Self::config_path().exists()
     }
 
+    /// Get the calibration for a device, if one has been saved.
+    pub fn calibration_for(&self, device_id: &str) -> Option<&DeviceCalibration> {
+        self.device_calibrations.get(device_id)
+    }
+
+    /// Transcription engine to use: [`TranscriptionEngineKind::ENV_VAR`] when
+    /// it names a valid engine, otherwise the configured one.
+    pub fn effective_transcription_engine(&self) -> TranscriptionEngineKind {
+        std::env::var(TranscriptionEngineKind::ENV_VAR)
+            .ok()
+            .and_then(|name| TranscriptionEngineKind::from_name(&name))
+            .unwrap_or(self.transcription_engine)
+    }
+
+    /// The appearance settings in this config.
+    pub fn theme(&self) -> ThemeSettings {
+        ThemeSettings {
+            mode: self.theme_mode.clone(),
+            accent_color: self.accent_color.clone(),
+            visualization_palette: self.visualization_palette,
+        }
+    }
+
+    /// Replace the appearance settings.
+    pub fn set_theme(&mut self, theme: ThemeSettings) {
+        self.theme_mode = theme.mode;
+        self.accent_color = theme.accent_color;
+        self.visualization_palette = theme.visualization_palette;
+    }
+
+    /// The shareable text rules in this config.
+    pub fn user_rules(&self) -> UserRules {
+        UserRules {
+            replacements: self.corrections.rules.clone(),
+            redaction: self.redaction.rules.clone(),
+            dictionary: self.corrections.dictionary.clone(),
+        }
+    }
+
+    /// Add imported text rules, or swap them in for the existing ones when
+    /// `replace` is set. A replacement for a phrase that already has one
+    /// overwrites it, as does a dictionary term that differs only in case.
+    /// Returns how many rules were added and updated.
+    pub fn import_rules(&mut self, rules: UserRules, replace: bool) -> (usize, usize) {
+        if replace {
+            let added = rules.replacements.len() + rules.redaction.len() + rules.dictionary.len();
+            self.corrections.rules = rules.replacements;
+            self.redaction.rules = rules.redaction;
+            self.corrections.dictionary = rules.dictionary;
+            return (added, 0);
+        }
+
+        let (mut added, mut updated) = (0, 0);
+        for rule in rules.replacements {
+            let existing = self
+                .corrections
+                .rules
+                .iter_mut()
+                .find(|r| r.from.to_lowercase() == rule.from.to_lowercase());
+            match existing {
+                Some(existing) if *existing == rule => {}
+                Some(existing) => {
+                    *existing = rule;
+                    updated += 1;
+                }
+                None => {
+                    self.corrections.rules.push(rule);
+                    added += 1;
+                }
+            }
+        }
+        for rule in rules.redaction {
+            if !self.redaction.rules.contains(&rule) {
+                self.redaction.rules.push(rule);
+                added += 1;
+            }
+        }
+        for term in rules.dictionary {
+            let existing = self
+                .corrections
+                .dictionary
+                .iter_mut()
+                .find(|t| t.to_lowercase() == term.to_lowercase());
+            match existing {
+                Some(existing) if *existing == term => {}
+                Some(existing) => {
+                    *existing = term;
+                    updated += 1;
+                }
+                None => {
+                    self.corrections.dictionary.push(term);
+                    added += 1;
+                }
+            }
+        }
+        (added, updated)
+    }
+
     /// Create a default config with the default hotkey binding.
     pub fn default_with_hotkeys() -> Self {
         Self {
@@ -197,13 +1622,56 @@ impl Config {
                 KeyCode::RightShift,
             ])],
             auto_toggle_hotkeys: vec![],
+            dictation_hotkeys: vec![],
+            spelling_hotkeys: vec![],
             auto_paste_enabled: true,
             auto_paste_delay_ms: 50,
             theme_mode: ThemeMode::default(),
+            accent_color: None,
+            visualization_palette: VisualizationPalette::default(),
+            visualization: VisualizationSettings::default(),
             always_on_top: false,
             preferred_source1_id: None,
             preferred_source2_id: None,
+            fallback_source_ids: Vec::new(),
             log_level: LogLevel::default(),
+            device_calibrations: HashMap::new(),
+            notifications: NotificationSettings::default(),
+            sound_cues: SoundCueSettings::default(),
+            process_rules: Vec::new(),
+            auto_suspend_minutes: 0,
+            vad_source: VadSource::default(),
+            encrypt_history: false,
+            redaction: RedactionSettings::default(),
+            profanity: ProfanitySettings::default(),
+            corrections: CorrectionSettings::default(),
+            spoken_commands: SpokenCommandSettings::default(),
+            pause_words: PauseWords::default(),
+            output_templates: OutputTemplates::default(),
+            ptt_max_duration_secs: default_ptt_max_duration_secs(),
+            transcription_engine: TranscriptionEngineKind::default(),
+            dump_segments: false,
+            segment_normalization: SegmentNormalization::default(),
+            narrowband_input: NarrowbandInput::default(),
+            segment_joining: SegmentJoining::default(),
+            transcript_sink: None,
+            caption_file: CaptionFileSettings::default(),
+            activity_log: false,
+            sync: SyncSettings::default(),
+            power_policy: PowerPolicy::default(),
+            load_shedding: LoadShedding::default(),
+            capture_buffer_secs: default_capture_buffer_secs(),
+            segmentation: SegmentationStrategy::default(),
+            segment_overlap_ms: default_segment_overlap_ms(),
+            paths: PathSettings::default(),
+            disk_space: DiskSpace::default(),
+            output_sinks: default_output_sinks(),
+            updates: UpdateSettings::default(),
+            crash_reports: false,
+            pause_on_lock: false,
+            language: Language::default(),
+            speaker_filter: SpeakerFilter::default(),
+            partial_typing: PartialTyping::default(),
         }
     }
 
@@ -245,13 +1713,56 @@ impl Config {
             transcription_mode: legacy.transcription_mode,
             ptt_hotkeys,
             auto_toggle_hotkeys,
+            dictation_hotkeys: legacy.dictation_hotkeys,
+            spelling_hotkeys: legacy.spelling_hotkeys,
             auto_paste_enabled: legacy.auto_paste_enabled.unwrap_or(true),
             auto_paste_delay_ms: legacy.auto_paste_delay_ms.unwrap_or(50),
             theme_mode: legacy.theme_mode.unwrap_or_default(),
-            always_on_top: false,
+            accent_color: legacy.accent_color,
+            visualization_palette: legacy.visualization_palette,
+            visualization: legacy.visualization,
+            always_on_top: legacy.always_on_top,
             preferred_source1_id: legacy.preferred_source1_id,
             preferred_source2_id: legacy.preferred_source2_id,
+            fallback_source_ids: legacy.fallback_source_ids,
             log_level: legacy.log_level.unwrap_or_default(),
+            device_calibrations: legacy.device_calibrations,
+            notifications: legacy.notifications,
+            sound_cues: legacy.sound_cues,
+            process_rules: legacy.process_rules,
+            auto_suspend_minutes: legacy.auto_suspend_minutes,
+            vad_source: legacy.vad_source,
+            encrypt_history: legacy.encrypt_history,
+            redaction: legacy.redaction,
+            profanity: legacy.profanity,
+            corrections: legacy.corrections,
+            spoken_commands: legacy.spoken_commands,
+            pause_words: legacy.pause_words,
+            output_templates: legacy.output_templates,
+            ptt_max_duration_secs: legacy.ptt_max_duration_secs,
+            transcription_engine: legacy.transcription_engine,
+            dump_segments: legacy.dump_segments,
+            segment_normalization: legacy.segment_normalization,
+            narrowband_input: legacy.narrowband_input,
+            segment_joining: legacy.segment_joining,
+            transcript_sink: legacy.transcript_sink,
+            caption_file: legacy.caption_file,
+            activity_log: legacy.activity_log,
+            sync: legacy.sync,
+            power_policy: legacy.power_policy,
+            load_shedding: legacy.load_shedding,
+            capture_buffer_secs: legacy.capture_buffer_secs,
+            segmentation: legacy.segmentation,
+            segment_overlap_ms: legacy.segment_overlap_ms,
+            paths: legacy.paths,
+            disk_space: legacy.disk_space,
+            output_sinks: legacy.output_sinks,
+            updates: legacy.updates,
+            crash_reports: legacy.crash_reports,
+            pause_on_lock: legacy.pause_on_lock,
+            language: legacy.language,
+            speaker_filter: legacy.speaker_filter,
+            partial_typing: legacy.partial_typing,
         }
     }
 }
@@ -290,6 +1801,41 @@ mod tests {
         assert_eq!(parsed.ptt_hotkeys[0].keys, vec![KeyCode::F13]);
     }
 
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#3B82f6"), Some([0x3b, 0x82, 0xf6]));
+        assert_eq!(parse_hex_color("3b82f6"), None);
+        assert_eq!(parse_hex_color("#3b82f"), None);
+        assert_eq!(parse_hex_color("#3b82fg"), None);
+    }
+
+    #[test]
+    fn test_visualization_settings_validation() {
+        for preset in [
+            VisualizationPreset::Low,
+            VisualizationPreset::Balanced,
+            VisualizationPreset::High,
+        ] {
+            assert!(VisualizationSettings::preset(preset).validate().is_ok());
+        }
+
+        let settings = VisualizationSettings {
+            fft_size: 500,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let stop = |position: f32| GradientStop {
+            position,
+            color: "#ffffff".to_string(),
+        };
+        let settings = VisualizationSettings {
+            gradient: vec![stop(0.0), stop(0.6), stop(0.4)],
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
     #[test]
     fn test_legacy_ptt_key_migration() {
         let json = r#"{"transcription_mode": "push_to_talk", "ptt_key": "f13"}"#;
@@ -311,6 +1857,7 @@ mod tests {
         assert_eq!(config.ptt_hotkeys.len(), 1);
         assert!(config.ptt_hotkeys[0].keys.contains(&KeyCode::RightControl));
         assert!(config.ptt_hotkeys[0].keys.contains(&KeyCode::RightShift));
+        assert_eq!(config.ptt_max_duration_secs, 25);
     }
 
     #[test]
@@ -334,6 +1881,24 @@ mod tests {
         assert_eq!(config.auto_toggle_hotkeys[0].keys, vec![KeyCode::F14]);
     }
 
+    #[test]
+    fn test_calibration_suggest_quiet_mic() {
+        // Quiet mic: speech well below target gets positive gain
+        let cal = DeviceCalibration::suggest(-70.0, -50.0, -35.0);
+        assert!(cal.gain_db > 0.0);
+        assert!(cal.voiced_threshold_db < -50.0 + cal.gain_db);
+        assert!(cal.whisper_threshold_db < cal.voiced_threshold_db);
+        assert!(cal.whisper_threshold_db > -70.0 + cal.gain_db);
+    }
+
+    #[test]
+    fn test_calibration_suggest_hot_mic() {
+        // Near-clipping peaks limit gain to keep headroom
+        let cal = DeviceCalibration::suggest(-60.0, -20.0, -1.0);
+        assert!(cal.gain_db < 0.0);
+        assert!(cal.voiced_threshold_db <= -20.0);
+    }
+
     #[test]
     fn test_new_auto_toggle_hotkeys_format() {
         let json = r#"{"auto_toggle_hotkeys": [{"keys": ["f13"]}, {"keys": ["f14"]}]}"#;
@@ -342,4 +1907,105 @@ mod tests {
 
         assert_eq!(config.auto_toggle_hotkeys.len(), 2);
     }
+
+    #[test]
+    fn test_process_rule_matching() {
+        let json = r#"{"process_rules": [{"process_name": "Zoom.exe", "action": "push_to_talk"}]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+
+        let rule = &config.process_rules[0];
+        assert_eq!(rule.trigger, ProcessRuleTrigger::Running);
+        assert_eq!(rule.action, ProcessRuleAction::PushToTalk);
+        assert!(rule.matches("zoom"));
+        assert!(rule.matches("ZOOM.EXE"));
+        assert!(!rule.matches("zoom.us"));
+    }
+
+    #[test]
+    fn test_process_rule_transform_format() {
+        let json = r#"{"process_rules": [{"process_name": "code", "trigger": "foreground", "action": {"transform": "snake_case"}}]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+
+        assert_eq!(
+            config.process_rules[0].action,
+            ProcessRuleAction::Transform(OutputTransform::SnakeCase)
+        );
+    }
+
+    #[test]
+    fn test_redaction_rules_format() {
+        let json = r#"{"redaction": {"rules": [{"kind": "email"}, {"kind": "custom", "pattern": "\\d{6}"}], "bypass_clipboard": true}}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+
+        assert_eq!(
+            config.redaction.rules,
+            vec![
+                RedactionRule::Email,
+                RedactionRule::Custom {
+                    pattern: r"\d{6}".to_string()
+                },
+            ]
+        );
+        assert!(config.redaction.bypass_clipboard);
+        assert!(!config.redaction.bypass_history);
+    }
+
+    #[test]
+    fn test_import_rules_merges_by_phrase() {
+        let replacement = |from: &str, to: &str| ReplacementRule {
+            from: from.to_string(),
+            to: to.to_string(),
+            enabled: true,
+            learned: false,
+        };
+        let mut config = Config::default_with_hotkeys();
+        config.corrections.rules = vec![replacement("get hub", "Github")];
+        config.redaction.rules = vec![RedactionRule::Email];
+
+        let rules = UserRules {
+            replacements: vec![
+                replacement("Get Hub", "GitHub"),
+                replacement("flow s t t", "FlowSTT"),
+            ],
+            redaction: vec![RedactionRule::Email, RedactionRule::CreditCard],
+            dictionary: vec!["FlowSTT".to_string()],
+        };
+        assert_eq!(config.import_rules(rules.clone(), false), (3, 1));
+        assert_eq!(config.corrections.rules.len(), 2);
+        assert_eq!(config.corrections.rules[0].to, "GitHub");
+        assert_eq!(config.redaction.rules.len(), 2);
+
+        // A dictionary term differing only in case is updated
+        let recased = UserRules {
+            dictionary: vec!["FlowStt".to_string()],
+            ..UserRules::default()
+        };
+        assert_eq!(config.import_rules(recased, false), (0, 1));
+        assert_eq!(config.corrections.dictionary, ["FlowStt"]);
+        assert_eq!(config.import_rules(rules.clone(), false), (0, 1));
+
+        // Importing the same rules again changes nothing
+        assert_eq!(config.import_rules(rules.clone(), false), (0, 0));
+        assert_eq!(config.import_rules(UserRules::default(), true), (0, 0));
+        assert!(config.user_rules().replacements.is_empty());
+    }
+
+    #[test]
+    fn test_correction_settings_defaults() {
+        let legacy: LegacyConfig = serde_json::from_str("{}").unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.corrections, CorrectionSettings::default());
+        assert!(config.corrections.learn);
+
+        let json = r#"{"corrections": {"rules": [{"from": "flow s t t", "to": "FlowSTT"}]}}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        let rule = &config.corrections.rules[0];
+        assert!(rule.enabled);
+        assert!(!rule.learned);
+        assert_eq!(config.corrections.learn_threshold, 3);
+    }
 }
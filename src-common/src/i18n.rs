@@ -0,0 +1,506 @@
+//! Translations of user-facing strings.
+//!
+//! Tray labels, desktop notifications and CLI messages are looked up here by
+//! [`Message`] instead of being written out in English. Each language is a
+//! plain table from message to text; a message a table doesn't have falls
+//! back to English. Arguments are written as `{name}` in the text and filled
+//! in by [`format`].
+//!
+//! The language comes from `language` in the config file. `auto` (the
+//! default) follows the system locale: `LC_ALL`, `LC_MESSAGES` and `LANG`,
+//! then the user locale on Windows. It is resolved once per process, so a
+//! change applies the next time FlowSTT or the CLI starts.
+
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+/// Language of user-facing strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Follow the system locale
+    #[default]
+    Auto,
+    En,
+    De,
+    Es,
+    Fr,
+}
+
+impl Language {
+    /// Languages that have a table, in the order they are listed to users
+    pub const SUPPORTED: &'static [Language] =
+        &[Language::En, Language::De, Language::Es, Language::Fr];
+
+    /// Parse a config value (`auto`, `en`, `de`, ...).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(Language::Auto),
+            other => Self::from_locale(other),
+        }
+    }
+
+    /// Config value for this language.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Auto => "auto",
+            Language::En => "en",
+            Language::De => "de",
+            Language::Es => "es",
+            Language::Fr => "fr",
+        }
+    }
+
+    /// Match a locale name such as `de_DE.UTF-8` or `fr-CA` to a supported
+    /// language.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match code.as_str() {
+            "en" => Some(Language::En),
+            "de" => Some(Language::De),
+            "es" => Some(Language::Es),
+            "fr" => Some(Language::Fr),
+            _ => None,
+        }
+    }
+
+    /// Turn `Auto` into the language of the system locale, English if that
+    /// isn't one with a table.
+    pub fn resolve(self) -> Self {
+        match self {
+            Language::Auto => system_language().unwrap_or(Language::En),
+            language => language,
+        }
+    }
+}
+
+/// A user-facing string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    TrayShow,
+    TrayAlwaysOnTop,
+    TraySettings,
+    TrayLogs,
+    TrayAbout,
+    TrayRunTest,
+    TrayRecent,
+    TrayRecentEmpty,
+    TrayMicrophone,
+    TrayMicrophoneNone,
+    TrayMode,
+    TrayModeAutomatic,
+    TrayModePushToTalk,
+    TrayPrivacyMode,
+    TrayAutostart,
+    TrayExit,
+    /// Stands in for `{holder}` when the application holding a device is unknown
+    AnotherApplication,
+    NotifyModelDownloadComplete,
+    NotifyModelDownloadFailed,
+    /// `{error}`
+    NotifyCaptureStopped,
+    /// `{holder}`
+    NotifyDeviceBusy,
+    /// `{holder}`
+    NotifyDeviceBusyFallback,
+    /// `{secs}`
+    NotifyCaptureStalled,
+    /// `{khz}`
+    NotifyNarrowband,
+    NotifyNarrowbandFallback,
+    NotifyClipping,
+    /// `{version}`
+    NotifyUpdateAvailable,
+    /// `{reason}`
+    NotifyPasteFailed,
+    CliError,
+    CliNoDevices,
+    CliTranscriptionStarted,
+    CliPrivacyOn,
+    CliStopHint,
+    CliInterrupted,
+    CliCaptureError,
+    CliCaptureStopped,
+    /// `{path}`, `{mb}`
+    CliLowDiskSpace,
+    /// `{reason}`
+    CliPasteFailed,
+    CliServiceShuttingDown,
+    CliEventStreamError,
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Language strings are shown in, read from the config file on first use.
+pub fn language() -> Language {
+    *LANGUAGE.get_or_init(|| Config::load().language.resolve())
+}
+
+/// Text of a message in the current language.
+pub fn tr(message: Message) -> &'static str {
+    text(language(), message)
+}
+
+/// Text of a message in the current language with its `{name}` arguments
+/// filled in.
+pub fn format(message: Message, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    fill(tr(message), args)
+}
+
+/// Text of a message in the given language, English if it has no translation.
+pub fn text(language: Language, message: Message) -> &'static str {
+    let translated = match language.resolve() {
+        Language::De => german(message),
+        Language::Es => spanish(message),
+        Language::Fr => french(message),
+        Language::En | Language::Auto => None,
+    };
+    translated.unwrap_or_else(|| english(message))
+}
+
+/// Replace each `{name}` in `template` with its argument.
+fn fill(template: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+fn system_language() -> Option<Language> {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(locale) = std::env::var(var) {
+            // An empty value or the C locale says nothing about the language
+            if locale.is_empty() || locale == "C" || locale == "POSIX" {
+                continue;
+            }
+            return Language::from_locale(&locale);
+        }
+    }
+    platform_locale().and_then(|locale| Language::from_locale(&locale))
+}
+
+#[cfg(windows)]
+fn platform_locale() -> Option<String> {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(windows))]
+fn platform_locale() -> Option<String> {
+    None
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::TrayShow => "Show",
+        Message::TrayAlwaysOnTop => "Always on Top",
+        Message::TraySettings => "Settings",
+        Message::TrayLogs => "Logs",
+        Message::TrayAbout => "About",
+        Message::TrayRunTest => "Run Test (WAV Directory)...",
+        Message::TrayRecent => "Recent Transcriptions",
+        Message::TrayRecentEmpty => "No transcriptions yet",
+        Message::TrayMicrophone => "Microphone",
+        Message::TrayMicrophoneNone => "No input devices",
+        Message::TrayMode => "Mode",
+        Message::TrayModeAutomatic => "Automatic",
+        Message::TrayModePushToTalk => "Push-to-Talk",
+        Message::TrayPrivacyMode => "Privacy Mode",
+        Message::TrayAutostart => "Start at Login",
+        Message::TrayExit => "Exit",
+        Message::AnotherApplication => "another application",
+        Message::NotifyModelDownloadComplete => "Whisper model download complete",
+        Message::NotifyModelDownloadFailed => "Whisper model download failed",
+        Message::NotifyCaptureStopped => "Audio capture stopped: {error}",
+        Message::NotifyDeviceBusy => "The microphone is in use by {holder}",
+        Message::NotifyDeviceBusyFallback => {
+            "The microphone is in use by {holder}; using a fallback device"
+        }
+        Message::NotifyCaptureStalled => {
+            "No audio received from the capture device for {secs} seconds"
+        }
+        Message::NotifyNarrowband => {
+            "The microphone is running at {khz} kHz, which reduces transcription quality. \
+             A Bluetooth headset may have switched to its hands-free profile."
+        }
+        Message::NotifyNarrowbandFallback => {
+            "The microphone switched to a low-quality profile; using a fallback device"
+        }
+        Message::NotifyClipping => {
+            "Audio input is clipping. Lower the input gain or recalibrate the device."
+        }
+        Message::NotifyUpdateAvailable => "FlowSTT {version} is available",
+        Message::NotifyPasteFailed => {
+            "The transcription is on the clipboard but wasn't pasted: {reason}"
+        }
+        Message::CliError => "Error",
+        Message::CliNoDevices => "No audio devices found",
+        Message::CliTranscriptionStarted => "Transcription started",
+        Message::CliPrivacyOn => "Privacy mode on: nothing will be written to disk",
+        Message::CliStopHint => "Press Ctrl+C to stop, or run 'flowstt stop'",
+        Message::CliInterrupted => "Interrupted",
+        Message::CliCaptureError => "Capture error",
+        Message::CliCaptureStopped => "Capture stopped",
+        Message::CliLowDiskSpace => "Low disk space: audio not saved to {path} ({mb} MB free)",
+        Message::CliPasteFailed => "Paste failed, text left on the clipboard: {reason}",
+        Message::CliServiceShuttingDown => "Service shutting down",
+        Message::CliEventStreamError => "Event stream error",
+    }
+}
+
+fn german(message: Message) -> Option<&'static str> {
+    Some(match message {
+        Message::TrayShow => "Anzeigen",
+        Message::TrayAlwaysOnTop => "Immer im Vordergrund",
+        Message::TraySettings => "Einstellungen",
+        Message::TrayLogs => "Protokolle",
+        Message::TrayAbout => "Über",
+        Message::TrayRunTest => "Test ausführen (WAV-Ordner)...",
+        Message::TrayRecent => "Letzte Transkriptionen",
+        Message::TrayRecentEmpty => "Noch keine Transkriptionen",
+        Message::TrayMicrophone => "Mikrofon",
+        Message::TrayMicrophoneNone => "Keine Eingabegeräte",
+        Message::TrayMode => "Modus",
+        Message::TrayModeAutomatic => "Automatisch",
+        Message::TrayModePushToTalk => "Push-to-Talk",
+        Message::TrayPrivacyMode => "Privatmodus",
+        Message::TrayAutostart => "Bei Anmeldung starten",
+        Message::TrayExit => "Beenden",
+        Message::AnotherApplication => "einer anderen Anwendung",
+        Message::NotifyModelDownloadComplete => "Whisper-Modell heruntergeladen",
+        Message::NotifyModelDownloadFailed => "Download des Whisper-Modells fehlgeschlagen",
+        Message::NotifyCaptureStopped => "Audioaufnahme angehalten: {error}",
+        Message::NotifyDeviceBusy => "Das Mikrofon wird von {holder} verwendet",
+        Message::NotifyDeviceBusyFallback => {
+            "Das Mikrofon wird von {holder} verwendet; ein Ersatzgerät wird genutzt"
+        }
+        Message::NotifyCaptureStalled => "Seit {secs} Sekunden kein Audio vom Aufnahmegerät",
+        Message::NotifyNarrowband => {
+            "Das Mikrofon läuft mit {khz} kHz, was die Transkriptionsqualität mindert. \
+             Ein Bluetooth-Headset ist eventuell ins Freisprechprofil gewechselt."
+        }
+        Message::NotifyNarrowbandFallback => {
+            "Das Mikrofon ist in ein Profil niedriger Qualität gewechselt; ein Ersatzgerät wird genutzt"
+        }
+        Message::NotifyClipping => {
+            "Der Audioeingang übersteuert. Verringern Sie die Eingangsverstärkung oder kalibrieren Sie das Gerät neu."
+        }
+        Message::NotifyUpdateAvailable => "FlowSTT {version} ist verfügbar",
+        Message::NotifyPasteFailed => {
+            "Die Transkription liegt in der Zwischenablage, wurde aber nicht eingefügt: {reason}"
+        }
+        Message::CliError => "Fehler",
+        Message::CliNoDevices => "Keine Audiogeräte gefunden",
+        Message::CliTranscriptionStarted => "Transkription gestartet",
+        Message::CliPrivacyOn => "Privatmodus an: nichts wird auf die Festplatte geschrieben",
+        Message::CliStopHint => "Strg+C zum Beenden drücken oder 'flowstt stop' ausführen",
+        Message::CliInterrupted => "Abgebrochen",
+        Message::CliCaptureError => "Aufnahmefehler",
+        Message::CliCaptureStopped => "Aufnahme angehalten",
+        Message::CliLowDiskSpace => {
+            "Wenig Speicherplatz: Audio nicht in {path} gespeichert ({mb} MB frei)"
+        }
+        Message::CliPasteFailed => {
+            "Einfügen fehlgeschlagen, Text liegt in der Zwischenablage: {reason}"
+        }
+        Message::CliServiceShuttingDown => "Dienst wird beendet",
+        Message::CliEventStreamError => "Fehler im Ereignisstrom",
+    })
+}
+
+fn spanish(message: Message) -> Option<&'static str> {
+    Some(match message {
+        Message::TrayShow => "Mostrar",
+        Message::TrayAlwaysOnTop => "Siempre visible",
+        Message::TraySettings => "Configuración",
+        Message::TrayLogs => "Registros",
+        Message::TrayAbout => "Acerca de",
+        Message::TrayRunTest => "Ejecutar prueba (carpeta WAV)...",
+        Message::TrayRecent => "Transcripciones recientes",
+        Message::TrayRecentEmpty => "Aún no hay transcripciones",
+        Message::TrayMicrophone => "Micrófono",
+        Message::TrayMicrophoneNone => "No hay dispositivos de entrada",
+        Message::TrayMode => "Modo",
+        Message::TrayModeAutomatic => "Automático",
+        Message::TrayModePushToTalk => "Pulsar para hablar",
+        Message::TrayPrivacyMode => "Modo privado",
+        Message::TrayAutostart => "Iniciar al iniciar sesión",
+        Message::TrayExit => "Salir",
+        Message::AnotherApplication => "otra aplicación",
+        Message::NotifyModelDownloadComplete => "Descarga del modelo Whisper completada",
+        Message::NotifyModelDownloadFailed => "Error al descargar el modelo Whisper",
+        Message::NotifyCaptureStopped => "La captura de audio se detuvo: {error}",
+        Message::NotifyDeviceBusy => "El micrófono está en uso por {holder}",
+        Message::NotifyDeviceBusyFallback => {
+            "El micrófono está en uso por {holder}; se usa un dispositivo alternativo"
+        }
+        Message::NotifyCaptureStalled => {
+            "No se recibe audio del dispositivo de captura desde hace {secs} segundos"
+        }
+        Message::NotifyNarrowband => {
+            "El micrófono funciona a {khz} kHz, lo que reduce la calidad de la transcripción. \
+             Es posible que unos auriculares Bluetooth hayan cambiado al perfil manos libres."
+        }
+        Message::NotifyNarrowbandFallback => {
+            "El micrófono cambió a un perfil de baja calidad; se usa un dispositivo alternativo"
+        }
+        Message::NotifyClipping => {
+            "La entrada de audio está saturando. Baje la ganancia o vuelva a calibrar el dispositivo."
+        }
+        Message::NotifyUpdateAvailable => "FlowSTT {version} está disponible",
+        Message::NotifyPasteFailed => {
+            "La transcripción está en el portapapeles pero no se pegó: {reason}"
+        }
+        Message::CliError => "Error",
+        Message::CliNoDevices => "No se encontraron dispositivos de audio",
+        Message::CliTranscriptionStarted => "Transcripción iniciada",
+        Message::CliPrivacyOn => "Modo privado activado: no se escribirá nada en el disco",
+        Message::CliStopHint => "Pulse Ctrl+C para detener o ejecute 'flowstt stop'",
+        Message::CliInterrupted => "Interrumpido",
+        Message::CliCaptureError => "Error de captura",
+        Message::CliCaptureStopped => "Captura detenida",
+        Message::CliLowDiskSpace => {
+            "Poco espacio en disco: audio no guardado en {path} ({mb} MB libres)"
+        }
+        Message::CliPasteFailed => {
+            "No se pudo pegar, el texto quedó en el portapapeles: {reason}"
+        }
+        Message::CliServiceShuttingDown => "El servicio se está cerrando",
+        Message::CliEventStreamError => "Error en el flujo de eventos",
+    })
+}
+
+fn french(message: Message) -> Option<&'static str> {
+    Some(match message {
+        Message::TrayShow => "Afficher",
+        Message::TrayAlwaysOnTop => "Toujours au premier plan",
+        Message::TraySettings => "Paramètres",
+        Message::TrayLogs => "Journaux",
+        Message::TrayAbout => "À propos",
+        Message::TrayRunTest => "Lancer un test (dossier WAV)...",
+        Message::TrayRecent => "Transcriptions récentes",
+        Message::TrayRecentEmpty => "Aucune transcription pour l'instant",
+        Message::TrayMicrophone => "Microphone",
+        Message::TrayMicrophoneNone => "Aucun périphérique d'entrée",
+        Message::TrayMode => "Mode",
+        Message::TrayModeAutomatic => "Automatique",
+        Message::TrayModePushToTalk => "Appuyer pour parler",
+        Message::TrayPrivacyMode => "Mode privé",
+        Message::TrayAutostart => "Lancer à l'ouverture de session",
+        Message::TrayExit => "Quitter",
+        Message::AnotherApplication => "une autre application",
+        Message::NotifyModelDownloadComplete => "Téléchargement du modèle Whisper terminé",
+        Message::NotifyModelDownloadFailed => "Échec du téléchargement du modèle Whisper",
+        Message::NotifyCaptureStopped => "La capture audio s'est arrêtée : {error}",
+        Message::NotifyDeviceBusy => "Le microphone est utilisé par {holder}",
+        Message::NotifyDeviceBusyFallback => {
+            "Le microphone est utilisé par {holder} ; un périphérique de secours est utilisé"
+        }
+        Message::NotifyCaptureStalled => {
+            "Aucun son reçu du périphérique de capture depuis {secs} secondes"
+        }
+        Message::NotifyNarrowband => {
+            "Le microphone fonctionne à {khz} kHz, ce qui réduit la qualité de la transcription. \
+             Un casque Bluetooth est peut-être passé en profil mains libres."
+        }
+        Message::NotifyNarrowbandFallback => {
+            "Le microphone est passé à un profil de faible qualité ; un périphérique de secours est utilisé"
+        }
+        Message::NotifyClipping => {
+            "L'entrée audio sature. Baissez le gain d'entrée ou recalibrez le périphérique."
+        }
+        Message::NotifyUpdateAvailable => "FlowSTT {version} est disponible",
+        Message::NotifyPasteFailed => {
+            "La transcription est dans le presse-papiers mais n'a pas été collée : {reason}"
+        }
+        Message::CliError => "Erreur",
+        Message::CliNoDevices => "Aucun périphérique audio trouvé",
+        Message::CliTranscriptionStarted => "Transcription démarrée",
+        Message::CliPrivacyOn => "Mode privé activé : rien ne sera écrit sur le disque",
+        Message::CliStopHint => "Appuyez sur Ctrl+C pour arrêter, ou lancez 'flowstt stop'",
+        Message::CliInterrupted => "Interrompu",
+        Message::CliCaptureError => "Erreur de capture",
+        Message::CliCaptureStopped => "Capture arrêtée",
+        Message::CliLowDiskSpace => {
+            "Espace disque faible : audio non enregistré dans {path} ({mb} Mo libres)"
+        }
+        Message::CliPasteFailed => {
+            "Échec du collage, le texte est resté dans le presse-papiers : {reason}"
+        }
+        Message::CliServiceShuttingDown => "Arrêt du service",
+        Message::CliEventStreamError => "Erreur du flux d'événements",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_names_map_to_languages() {
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), Some(Language::De));
+        assert_eq!(Language::from_locale("fr-CA"), Some(Language::Fr));
+        assert_eq!(Language::from_locale("es"), Some(Language::Es));
+        assert_eq!(Language::from_locale("ja_JP.UTF-8"), None);
+        assert_eq!(Language::parse("auto"), Some(Language::Auto));
+        assert_eq!(Language::parse("EN"), Some(Language::En));
+    }
+
+    #[test]
+    fn arguments_are_filled_in() {
+        let text = fill(
+            text(Language::En, Message::CliLowDiskSpace),
+            &[("path", &"/tmp"), ("mb", &12)],
+        );
+        assert_eq!(text, "Low disk space: audio not saved to /tmp (12 MB free)");
+    }
+
+    #[test]
+    fn translations_keep_the_english_arguments() {
+        let arguments = |text: &str| {
+            let mut names: Vec<String> = text
+                .split('{')
+                .skip(1)
+                .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+                .collect();
+            names.sort();
+            names
+        };
+        for message in [
+            Message::NotifyCaptureStopped,
+            Message::NotifyDeviceBusy,
+            Message::NotifyDeviceBusyFallback,
+            Message::NotifyCaptureStalled,
+            Message::NotifyNarrowband,
+            Message::NotifyUpdateAvailable,
+            Message::NotifyPasteFailed,
+            Message::CliLowDiskSpace,
+            Message::CliPasteFailed,
+        ] {
+            let english = arguments(text(Language::En, message));
+            for language in Language::SUPPORTED {
+                assert_eq!(
+                    arguments(text(*language, message)),
+                    english,
+                    "{:?} in {:?}",
+                    message,
+                    language
+                );
+            }
+        }
+    }
+}
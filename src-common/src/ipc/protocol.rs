@@ -1,11 +1,15 @@
 //! IPC message framing and transport protocol.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 /// Maximum IPC message size (64 KB)
 pub const MAX_MESSAGE_SIZE: usize = 65536;
 
+/// Version of the IPC protocol, bumped when a request or response changes in
+/// a way older clients or services can't read
+pub const PROTOCOL_VERSION: u32 = 1;
+
 /// Error type for IPC operations.
 #[derive(Debug)]
 pub enum IpcError {
@@ -45,28 +49,98 @@ impl From<std::io::Error> for IpcError {
 }
 
 /// Get the platform-specific socket path for the IPC connection.
+///
+/// A portable copy gets its own socket or pipe, named after its folder.
+/// Every user gets endpoints of their own: sockets are kept in the user's
+/// runtime or temporary directory, and pipe names include the user name.
+/// The name only keeps users apart; the engine creates the pipe with an ACL
+/// that admits no one else.
 pub fn get_socket_path() -> PathBuf {
-    #[cfg(target_os = "linux")]
-    {
-        let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
-            .unwrap_or_else(|_| format!("/run/user/{}", unsafe { libc::getuid() }));
-        PathBuf::from(runtime_dir)
-            .join("flowstt")
-            .join("service.sock")
-    }
+    let name = match crate::portable::endpoint_suffix() {
+        Some(suffix) => format!("service-{}", suffix),
+        None => "service".to_string(),
+    };
 
-    #[cfg(target_os = "macos")]
+    #[cfg(unix)]
     {
-        let tmpdir = std::env::var("TMPDIR").unwrap_or_else(|_| "/tmp".to_string());
-        PathBuf::from(tmpdir).join("flowstt").join("service.sock")
+        socket_dir().join(format!("{}.sock", name))
     }
 
     #[cfg(target_os = "windows")]
     {
-        PathBuf::from(r"\\.\pipe\flowstt-service")
+        // Pipe names are shared by every session on the machine
+        let user: String = std::env::var("USERNAME")
+            .unwrap_or_default()
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        if user.is_empty() {
+            PathBuf::from(format!(r"\\.\pipe\flowstt-{}", name))
+        } else {
+            PathBuf::from(format!(r"\\.\pipe\flowstt-{}-{}", user, name))
+        }
     }
 }
 
+/// Directory holding the IPC socket.
+///
+/// `XDG_RUNTIME_DIR` on Linux and `TMPDIR` on macOS are private to the user,
+/// but either can be inherited from another user through `su` or `sudo`, so
+/// they are only used when the current user owns them. Otherwise the socket
+/// goes in a per-user folder of the shared `/tmp`.
+#[cfg(unix)]
+fn socket_dir() -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+
+    #[cfg(target_os = "linux")]
+    let candidates = [
+        std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from),
+        Some(PathBuf::from(format!("/run/user/{}", uid))),
+    ];
+    #[cfg(not(target_os = "linux"))]
+    let candidates = [std::env::var_os("TMPDIR").map(PathBuf::from)];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .find(|dir| !dir.as_os_str().is_empty() && is_owned_by(dir, uid))
+        .map(|dir| dir.join("flowstt"))
+        .unwrap_or_else(|| PathBuf::from(format!("/tmp/flowstt-{}", uid)))
+}
+
+#[cfg(unix)]
+fn is_owned_by(path: &Path, uid: u32) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.uid() == uid)
+}
+
+/// Create the directory holding the IPC socket, accessible only to the
+/// current user.
+///
+/// Fails if the directory belongs to someone else, who could otherwise stand
+/// in for the service or read what passes through it.
+#[cfg(unix)]
+pub fn prepare_socket_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} is not a directory owned by this user", dir.display()),
+        ));
+    }
+    if metadata.mode() & 0o777 != 0o700 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
 /// Read a length-prefixed message with size validation.
 ///
 /// Message format:
@@ -165,4 +239,23 @@ mod tests {
         let result = write_message(&mut buf, &oversized).await;
         assert!(matches!(result, Err(IpcError::MessageTooLarge { .. })));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prepare_socket_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("flowstt-test-{}", std::process::id()));
+        let socket_dir = dir.join("flowstt");
+        prepare_socket_dir(&socket_dir).unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&socket_dir), 0o700);
+
+        // A directory left too open is tightened
+        std::fs::set_permissions(&socket_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        prepare_socket_dir(&socket_dir).unwrap();
+        assert_eq!(mode(&socket_dir), 0o700);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
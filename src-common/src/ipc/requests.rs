@@ -2,7 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{AudioSourceType, HotkeyCombination, RecordingMode, TranscriptionMode};
+use crate::config::{
+    parse_hex_color, ThemeSettings, UserRules, VisualizationSettings, MAX_AUTO_PASTE_DELAY_MS,
+};
+use crate::types::{
+    ActivityPeriod, AudioSourceType, HistoryGrouping, HotkeyCombination, PermissionKind,
+    RecordingMode, TranscriptionMode, MAX_LATENCY_RUNS, MAX_RECENT_AUDIO_SECS, MAX_STREAM_CHANNELS,
+    MAX_STREAM_CHUNK_SAMPLES, STREAM_SAMPLE_RATES,
+};
 
 /// IPC request from client to service.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,7 +44,11 @@ pub enum Request {
     /// Get current transcription status
     GetStatus,
     /// Subscribe to real-time events (visualization, transcription results)
-    SubscribeEvents,
+    SubscribeEvents {
+        /// Also stream high-frequency `SpeechMetrics` events (VAD internals)
+        #[serde(default)]
+        speech_metrics: bool,
+    },
 
     // === Model Management ===
     /// Get Whisper model status
@@ -46,6 +57,10 @@ pub enum Request {
     DownloadModel,
     /// Get CUDA/GPU acceleration status
     GetCudaStatus,
+    /// Get which platform features work in the current session
+    GetCapabilities,
+    /// Get the version and build details of the service
+    GetVersionInfo,
 
     // === Configuration ===
     /// Get all persisted configuration values
@@ -64,6 +79,15 @@ pub enum Request {
     },
     /// Get the current PTT status
     GetPttStatus,
+    /// Listen for one press and release of a PTT hotkey without recording.
+    /// Capture is paused while listening. Responds once the key is released
+    /// or the timeout elapses.
+    TestPttHotkey {
+        /// The hotkey combinations to listen for
+        hotkeys: Vec<HotkeyCombination>,
+        /// How long to wait for the key, in milliseconds
+        timeout_ms: u64,
+    },
     /// Set the auto-mode toggle hotkeys
     SetAutoToggleHotkeys {
         /// The hotkey combinations to use for toggling auto mode
@@ -73,6 +97,8 @@ pub enum Request {
     GetAutoToggleHotkeys,
     /// Toggle between Automatic and PushToTalk modes
     ToggleAutoMode,
+    /// Resume Automatic mode capture after an idle auto-suspend
+    ResumeCapture,
 
     // === Clipboard / Auto-Paste ===
     /// Enable or disable automatic paste after transcription
@@ -80,15 +106,183 @@ pub enum Request {
         /// Whether auto-paste should be enabled
         enabled: bool,
     },
+    /// Set the delay between writing the clipboard and simulating the paste
+    SetAutoPasteDelay {
+        /// Delay in milliseconds (at most `MAX_AUTO_PASTE_DELAY_MS`)
+        delay_ms: u32,
+    },
+
+    // === Appearance ===
+    /// Get the theme mode, accent color and spectrogram palette
+    GetTheme,
+    /// Change the theme mode, accent color and spectrogram palette. Every
+    /// client is told with a `ThemeChanged` event.
+    SetTheme {
+        /// The new appearance settings
+        theme: ThemeSettings,
+    },
+
+    /// Get the resolution, update rate and gradient of the visualization
+    GetVisualizationConfig,
+    /// Change the resolution, update rate and gradient of the visualization;
+    /// running captures pick them up straight away
+    SetVisualizationConfig {
+        /// The new visualization settings
+        settings: VisualizationSettings,
+    },
+
+    // === Updates ===
+    /// Check the release feed for a newer version now. With
+    /// `updates.stage_downloads` on, a newer release's installer is
+    /// downloaded before the response.
+    CheckForUpdate,
+
+    // === Autostart ===
+    /// Register or unregister FlowSTT to start in the tray at login
+    SetAutostart {
+        /// Whether FlowSTT should start at login
+        enabled: bool,
+    },
+
+    // === Privacy ===
+    /// Enable or disable privacy mode (no WAV files or history written) for this session
+    SetPrivacyMode {
+        /// Whether privacy mode should be enabled
+        enabled: bool,
+    },
+
+    // === Dictation ===
+    /// Start a dictation session, pasting every segment into the window that
+    /// is focused now, or end the session in progress
+    SetDictation {
+        /// Whether a session should be in progress
+        active: bool,
+    },
+
+    // === Meetings ===
+    /// Start meeting mode: transcribe the primary and secondary sources as
+    /// separate streams into one timeline
+    StartMeeting {
+        /// Optional title for the meeting record
+        #[serde(default)]
+        title: Option<String>,
+    },
+    /// Stop meeting mode and save the meeting record
+    StopMeeting,
+    /// Get all saved meeting records
+    GetMeetings,
 
     // === History Management ===
     /// Get all transcription history entries
     GetHistory,
+    /// Get entry counts, word counts and audio length of the history by day
+    /// or hour, without the entries themselves
+    GetHistorySummary {
+        /// Length of the buckets
+        #[serde(default)]
+        group_by: HistoryGrouping,
+    },
     /// Delete a single history entry by ID
     DeleteHistoryEntry {
         /// The ID of the history entry to delete
         id: String,
     },
+    /// Replace the text of a history entry with a manual correction, keeping
+    /// the original transcript
+    EditHistoryEntry {
+        /// The ID of the history entry to edit
+        id: String,
+        /// The corrected text
+        text: String,
+    },
+    /// Transcribe the saved recording of a history entry again and replace its
+    /// text; the result arrives as a `HistoryEntryUpdated` event
+    RetranscribeEntry {
+        /// The ID of the history entry to re-transcribe
+        id: String,
+        /// Model file name in the model directory (e.g. `ggml-medium.en.bin`
+        /// or `medium.en`), or `None` for the default model
+        model: Option<String>,
+    },
+    /// Play the saved recording of a history entry on the default output device
+    PlayHistoryAudio {
+        /// The ID of the history entry to play
+        id: String,
+    },
+    /// Stop history recording playback
+    StopHistoryAudio,
+    /// Move history recording playback to a position
+    SeekHistoryAudio {
+        /// Position from the start of the recording in milliseconds
+        position_ms: u64,
+    },
+
+    // === Backup ===
+    /// Write the config, history, meetings and activity log to a backup
+    /// archive
+    CreateBackup {
+        /// Absolute path of the archive to write
+        path: String,
+        /// Also back up the saved recordings of history entries
+        #[serde(default)]
+        include_recordings: bool,
+    },
+    /// Replace the data on this machine with a backup archive's
+    RestoreBackup {
+        /// Absolute path of the archive to restore
+        path: String,
+    },
+
+    // === Recent Audio ===
+    /// Get the audio captured in the last few seconds, written to a WAV file,
+    /// with a waveform summary for display
+    GetRecentAudioWindow {
+        /// How far back to go (at most `MAX_RECENT_AUDIO_SECS`)
+        seconds: u32,
+    },
+
+    // === Activity ===
+    /// Get speech and dictation totals from the activity log
+    GetActivitySummary {
+        /// Span of time to summarize
+        period: ActivityPeriod,
+    },
+
+    // === Audio Streams ===
+    /// Start transcribing audio sent by this client with `StreamAudio`.
+    /// Each speech segment's text arrives as a `StreamTranscription` event.
+    /// The stream is dropped if the connection closes before
+    /// `EndAudioStream`.
+    StartAudioStream {
+        /// Sample rate of the audio in Hz
+        sample_rate: u32,
+        /// Number of interleaved channels
+        channels: u16,
+    },
+    /// The next chunk of an audio stream
+    StreamAudio {
+        /// ID from `AudioStreamStarted`
+        stream_id: String,
+        /// Interleaved 16-bit PCM samples (at most `MAX_STREAM_CHUNK_SAMPLES`)
+        samples: Vec<i16>,
+    },
+    /// End an audio stream, transcribing any speech still in progress
+    EndAudioStream {
+        /// ID from `AudioStreamStarted`
+        stream_id: String,
+    },
+
+    // === Text Rules ===
+    /// Get the phrase replacement, redaction and capitalization rules for sharing
+    ExportRules,
+    /// Add shared phrase replacement, redaction and capitalization rules to the config
+    ImportRules {
+        /// The rules to import
+        rules: UserRules,
+        /// Replace the existing rules instead of merging with them
+        #[serde(default)]
+        replace: bool,
+    },
 
     // === Audio Device Testing ===
     /// Start a lightweight test capture on a device to report audio levels
@@ -98,6 +292,42 @@ pub enum Request {
     },
     /// Stop any active audio device test capture
     StopTestAudioDevice,
+    /// Record a short sample from a device and report levels and problems
+    RunAudioSelfTest {
+        /// The device ID to test
+        device_id: String,
+    },
+
+    // === Speaker Filter ===
+    /// Record the user reading aloud from a device and save their voiceprint,
+    /// turning on the speaker filter
+    EnrollSpeaker {
+        /// The device ID to record from
+        device_id: String,
+    },
+    /// Delete the voiceprint and turn off the speaker filter
+    ClearSpeakerEnrollment,
+
+    // === Test Mode ===
+    /// Play a directory of WAV files through push-to-talk and score the
+    /// results. Progress is reported with `TestFile*` and `TestRunComplete` events.
+    RunTestMode {
+        /// Absolute path of the directory containing the WAV files
+        dir: String,
+        /// Manifest of expected transcripts (defaults to one found in `dir`)
+        #[serde(default)]
+        manifest: Option<String>,
+    },
+    /// Play audio through the engine in real time and report the latency
+    /// from the end of speech to the transcription event
+    RunLatencyBenchmark {
+        /// Absolute path of a WAV file to play (defaults to a built-in tone
+        /// and speech-like sample)
+        #[serde(default)]
+        file: Option<String>,
+        /// Times to play the audio (1 to `MAX_LATENCY_RUNS`)
+        runs: u32,
+    },
 
     // === Platform Permissions ===
     /// Check whether the service process has macOS Accessibility permission.
@@ -109,6 +339,16 @@ pub enum Request {
     /// which causes macOS to show a system dialog prompting the user to grant access
     /// to the service binary. Returns the current trust state.
     RequestAccessibilityPermission,
+    /// Get the state of every permission the service needs on this platform
+    GetPermissionsStatus,
+    /// Ask the operating system for a permission: shows the system prompt
+    /// where there is one, otherwise opens the matching settings page.
+    /// Returns the permission states afterwards; a grant made in the prompt
+    /// or settings arrives later as `PermissionChanged`.
+    RequestPermission {
+        /// The permission to ask for
+        kind: PermissionKind,
+    },
 
     // === Service Control ===
     /// Ping for health check
@@ -140,12 +380,144 @@ impl Request {
                 }
                 Ok(())
             }
-            Request::TestAudioDevice { device_id } => {
+            Request::EditHistoryEntry { id, text } => {
+                if id.is_empty() {
+                    return Err("id cannot be empty".to_string());
+                }
+                if text.trim().is_empty() {
+                    return Err("text cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::SetAutoPasteDelay { delay_ms } => {
+                if *delay_ms > MAX_AUTO_PASTE_DELAY_MS {
+                    return Err(format!(
+                        "delay_ms must be at most {}",
+                        MAX_AUTO_PASTE_DELAY_MS
+                    ));
+                }
+                Ok(())
+            }
+            Request::SetTheme { theme } => {
+                if let Some(color) = &theme.accent_color {
+                    if parse_hex_color(color).is_none() {
+                        return Err(format!(
+                            "accent_color must be a #rrggbb color, got '{}'",
+                            color
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Request::SetVisualizationConfig { settings } => settings.validate(),
+            Request::ImportRules { rules, .. } => {
+                if rules.replacements.iter().any(|r| r.from.trim().is_empty()) {
+                    return Err("replacement phrase cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::RetranscribeEntry { id, model } => {
+                if id.is_empty() {
+                    return Err("id cannot be empty".to_string());
+                }
+                if model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+                    return Err("model cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::CreateBackup { path, .. } | Request::RestoreBackup { path } => {
+                if path.trim().is_empty() {
+                    return Err("path cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::PlayHistoryAudio { id } => {
+                if id.is_empty() {
+                    return Err("id cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::GetRecentAudioWindow { seconds } => {
+                if *seconds == 0 || *seconds > MAX_RECENT_AUDIO_SECS {
+                    return Err(format!(
+                        "seconds must be between 1 and {}",
+                        MAX_RECENT_AUDIO_SECS
+                    ));
+                }
+                Ok(())
+            }
+            Request::StartAudioStream {
+                sample_rate,
+                channels,
+            } => {
+                if !STREAM_SAMPLE_RATES.contains(sample_rate) {
+                    return Err(format!(
+                        "sample_rate must be between {} and {}",
+                        STREAM_SAMPLE_RATES.start(),
+                        STREAM_SAMPLE_RATES.end()
+                    ));
+                }
+                if *channels == 0 || *channels > MAX_STREAM_CHANNELS {
+                    return Err(format!(
+                        "channels must be between 1 and {}",
+                        MAX_STREAM_CHANNELS
+                    ));
+                }
+                Ok(())
+            }
+            Request::StreamAudio { stream_id, samples } => {
+                if stream_id.is_empty() {
+                    return Err("stream_id cannot be empty".to_string());
+                }
+                if samples.len() > MAX_STREAM_CHUNK_SAMPLES {
+                    return Err(format!(
+                        "at most {} samples can be sent at once",
+                        MAX_STREAM_CHUNK_SAMPLES
+                    ));
+                }
+                Ok(())
+            }
+            Request::EndAudioStream { stream_id } => {
+                if stream_id.is_empty() {
+                    return Err("stream_id cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::TestAudioDevice { device_id }
+            | Request::RunAudioSelfTest { device_id }
+            | Request::EnrollSpeaker { device_id } => {
                 if device_id.is_empty() {
                     return Err("device_id cannot be empty".to_string());
                 }
                 Ok(())
             }
+            Request::TestPttHotkey {
+                hotkeys,
+                timeout_ms,
+            } => {
+                if hotkeys.is_empty() {
+                    return Err("hotkeys cannot be empty".to_string());
+                }
+                if *timeout_ms == 0 {
+                    return Err("timeout_ms must be greater than zero".to_string());
+                }
+                Ok(())
+            }
+            Request::RunTestMode { dir, .. } => {
+                if dir.is_empty() {
+                    return Err("dir cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            Request::RunLatencyBenchmark { file, runs } => {
+                if file.as_deref().is_some_and(|f| f.trim().is_empty()) {
+                    return Err("file cannot be empty".to_string());
+                }
+                if *runs == 0 || *runs > MAX_LATENCY_RUNS {
+                    return Err(format!("runs must be between 1 and {}", MAX_LATENCY_RUNS));
+                }
+                Ok(())
+            }
             // Other requests have no parameters to validate
             _ => Ok(()),
         }
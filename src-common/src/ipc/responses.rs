@@ -2,9 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::{ThemeSettings, UserRules, VisualizationSettings};
 use crate::types::{
-    AudioDevice, ConfigValues, CudaStatus, HistoryEntry, ModelStatus, PttStatus, TranscribeStatus,
-    TranscriptionResult, VisualizationData,
+    ActivitySummary, AudioDevice, AudioSelfTestReport, Capabilities, ConfigValues, CudaStatus,
+    EngineComponent, HistoryEntry, HistorySummary, LatencyReport, MeetingLine, MeetingRecord,
+    ModelStatus, PermissionKind, PermissionState, PermissionsStatus, PttStatus, RecentAudioWindow,
+    SpeechMetrics, TranscribeStatus, TranscriptionResult, UpdateInfo, VersionInfo,
+    VisualizationData,
 };
 
 /// IPC response from service to client.
@@ -24,9 +28,21 @@ pub enum Response {
     /// CUDA/GPU status
     CudaStatus(CudaStatus),
 
+    /// Platform features available in the current session
+    Capabilities(Capabilities),
+
+    /// Version and build details of the service
+    VersionInfo(VersionInfo),
+
     /// Push-to-talk status
     PttStatus(PttStatus),
 
+    /// Result of a PTT hotkey test
+    PttHotkeyTest {
+        /// Whether the hotkey was pressed and released before the timeout
+        detected: bool,
+    },
+
     /// Persisted configuration values
     ConfigValues(ConfigValues),
 
@@ -36,9 +52,99 @@ pub enum Response {
         granted: bool,
     },
 
+    /// Theme mode, accent color and spectrogram palette
+    Theme(ThemeSettings),
+
+    /// Resolution, update rate and gradient of the visualization
+    VisualizationConfig(VisualizationSettings),
+
+    /// Result of an update check
+    UpdateInfo(UpdateInfo),
+
+    /// State of the permissions the service needs
+    PermissionsStatus(PermissionsStatus),
+
+    /// Audio self-test report for a device
+    AudioSelfTest(AudioSelfTestReport),
+
+    /// Result of a latency benchmark
+    LatencyBenchmark(LatencyReport),
+
+    /// A voiceprint was saved
+    SpeakerEnrolled {
+        /// Length of the recording in milliseconds
+        duration_ms: u64,
+    },
+
     /// Transcription history entries
     History { entries: Vec<HistoryEntry> },
 
+    /// Transcription history totals by day or hour
+    HistorySummary(HistorySummary),
+
+    /// History recording playback started
+    HistoryAudioPlaying {
+        /// The ID of the history entry being played
+        id: String,
+        /// Length of the recording in milliseconds, if known
+        duration_ms: Option<u64>,
+    },
+
+    /// A backup archive was written
+    BackupCreated {
+        /// Number of history entries backed up
+        history_entries: usize,
+        /// Number of recordings backed up
+        recordings: usize,
+    },
+
+    /// A backup archive was restored
+    BackupRestored {
+        /// RFC 3339 UTC time the backup was made
+        created_at: String,
+        /// Number of history entries restored
+        history_entries: usize,
+        /// Number of recordings restored
+        recordings: usize,
+    },
+
+    /// Recently captured audio
+    RecentAudioWindow(RecentAudioWindow),
+
+    /// Speech and dictation totals
+    ActivitySummary(ActivitySummary),
+
+    /// A client audio stream started
+    AudioStreamStarted {
+        /// Identifies the stream's `StreamTranscription` events
+        stream_id: String,
+    },
+
+    /// A client audio stream ended
+    AudioStreamEnded {
+        /// Segments queued for transcription over the whole stream; one
+        /// `StreamTranscription` event is sent for each
+        segments: u32,
+    },
+
+    /// A meeting record
+    Meeting(MeetingRecord),
+
+    /// Saved meeting records
+    Meetings { meetings: Vec<MeetingRecord> },
+
+    /// Shareable phrase replacement and redaction rules
+    Rules { rules: UserRules },
+
+    /// Rules were imported
+    RulesImported {
+        /// Rules that were not in the config before
+        added: usize,
+        /// Existing replacements whose text changed, and dictionary terms
+        /// whose capitalization changed
+        updated: usize,
+    },
+
     /// Subscribed to events
     Subscribed,
 
@@ -67,6 +173,9 @@ pub enum EventType {
     /// Visualization data update
     VisualizationData(VisualizationData),
 
+    /// Live speech detector metrics (only sent to clients that opted in)
+    SpeechMetrics(SpeechMetrics),
+
     /// Transcription result for a segment
     TranscriptionComplete(TranscriptionResult),
 
@@ -85,6 +194,45 @@ pub enum EventType {
         error: Option<String>,
     },
 
+    /// Capture is running but the backend has stopped delivering audio, and
+    /// restarting its streams did not help
+    CaptureStalled {
+        /// Seconds since audio was last received
+        stalled_secs: u64,
+    },
+
+    /// A capture device could not be opened because another application
+    /// holds it exclusively
+    DeviceBusy {
+        /// The busy device
+        device_id: String,
+        /// Name of the process that appears to hold it, if known
+        #[serde(skip_serializing_if = "Option::is_none")]
+        holder_hint: Option<String>,
+        /// Fallback device capture started on instead, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fallback_id: Option<String>,
+    },
+
+    /// The primary input's native sample rate changed, typically because a
+    /// Bluetooth headset switched profile, or capture started on a narrowband
+    /// input
+    DeviceProfileChanged {
+        /// The primary input device
+        #[serde(skip_serializing_if = "Option::is_none")]
+        device_id: Option<String>,
+        /// Native sample rate before the change, if capture was already running
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous_sample_rate: Option<u32>,
+        /// Native sample rate now, in Hz
+        sample_rate: u32,
+        /// Whether the new rate is a narrowband (telephony) rate
+        narrowband: bool,
+        /// Input capture switched to instead, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        switched_to: Option<String>,
+    },
+
     /// Model download progress
     ModelDownloadProgress { percent: u8 },
 
@@ -111,18 +259,239 @@ pub enum EventType {
         mode: crate::types::TranscriptionMode,
     },
 
-    /// Auto mode toggled via hotkey
+    /// Auto mode toggled via hotkey or request
     AutoModeToggled {
         /// The new transcription mode
         mode: crate::types::TranscriptionMode,
     },
 
+    /// What toggled auto mode; sent right after `AutoModeToggled`
+    AutoModeToggledBy {
+        /// The new transcription mode
+        mode: crate::types::TranscriptionMode,
+        /// What switched the mode
+        source: crate::types::ModeToggleSource,
+    },
+
+    /// Capture paused or resumed by a process rule
+    PausedByProcess {
+        /// Matching process name while paused; absent when capture resumes
+        #[serde(skip_serializing_if = "Option::is_none")]
+        process: Option<String>,
+    },
+
+    /// Automatic mode capture suspended after inactivity, or resumed
+    AutoSuspended {
+        /// Whether capture is now suspended
+        suspended: bool,
+    },
+
+    /// Captured audio is clipping; the input gain is probably too high
+    ClippingDetected {
+        /// Samples at or near full scale in the last second
+        clipped_samples: u32,
+    },
+
+    /// Privacy mode was turned on or off
+    PrivacyModeChanged {
+        /// Whether privacy mode is now enabled
+        enabled: bool,
+    },
+
+    /// A newer FlowSTT was released
+    UpdateAvailable {
+        /// The new version
+        version: String,
+        /// Release notes of the new version
+        notes_url: Option<String>,
+    },
+
+    /// The installer of a new release was downloaded and is ready to run
+    UpdateStaged {
+        /// The version downloaded
+        version: String,
+        /// Path of the installer
+        path: String,
+    },
+
+    /// The theme mode, accent color or spectrogram palette changed
+    ThemeChanged {
+        /// The appearance settings as they are now
+        theme: ThemeSettings,
+    },
+
+    /// Starting FlowSTT at login was turned on or off
+    AutostartChanged {
+        /// Whether FlowSTT now starts at login
+        enabled: bool,
+    },
+
+    /// A dictation session started or ended
+    DictationChanged {
+        /// Whether a session is now in progress
+        active: bool,
+    },
+
+    /// Spelling mode was turned on or off
+    SpellingModeChanged {
+        /// Whether spelling mode is now on
+        active: bool,
+    },
+
+    /// Output was paused or resumed by a spoken pause or resume phrase
+    OutputPausedChanged {
+        /// Whether transcriptions are now held back from the output sinks
+        paused: bool,
+    },
+
+    /// Settings were saved with new values
+    ConfigChanged {
+        /// Names of the changed settings in the config file
+        keys: Vec<String>,
+    },
+
     /// A history entry was deleted
     HistoryEntryDeleted {
         /// The ID of the deleted entry
         id: String,
     },
 
+    /// A history entry's text changed
+    HistoryEntryUpdated {
+        /// The entry as it is now
+        entry: HistoryEntry,
+    },
+
+    /// Re-transcribing a history entry failed; the entry is unchanged
+    RetranscriptionFailed {
+        /// The ID of the history entry
+        id: String,
+        /// What went wrong
+        error: String,
+    },
+
+    /// Meeting mode started
+    MeetingStarted {
+        /// The new meeting record
+        meeting: MeetingRecord,
+    },
+
+    /// A line was added to the meeting timeline
+    MeetingLine {
+        /// The ID of the meeting
+        meeting_id: String,
+        /// The transcribed line
+        line: MeetingLine,
+    },
+
+    /// The machine switched between battery and AC power
+    PowerStateChanged {
+        /// Whether it now runs on battery
+        on_battery: bool,
+    },
+
+    /// Transcription started or stopped trading accuracy for speed because it
+    /// was falling behind
+    LoadSheddingChanged {
+        /// Whether load shedding is now in effect
+        active: bool,
+        /// What triggered the change, e.g. "CPU load 95%, 4 segments waiting"
+        reason: String,
+    },
+
+    /// Audio wasn't saved because the disk is nearly full
+    LowDiskSpace {
+        /// Folder the audio was to be saved in
+        path: String,
+        /// Megabytes free on its disk
+        available_mb: u64,
+        /// Megabytes the `disk_space` config keeps free
+        min_free_mb: u64,
+    },
+
+    /// A transcription was copied to the clipboard but the paste keystroke
+    /// couldn't be sent; reported once until a paste succeeds or fails
+    /// differently
+    PasteFailed {
+        /// Why the paste failed, e.g. a missing tool
+        reason: String,
+    },
+
+    /// A permission was granted or revoked since it was last checked
+    PermissionChanged {
+        /// The permission that changed
+        kind: PermissionKind,
+        /// Its new state
+        state: PermissionState,
+    },
+
+    /// An engine thread stopped responding and was restarted
+    ComponentRestarted {
+        /// The thread that was restarted
+        component: EngineComponent,
+        /// Seconds since it last showed it was alive
+        stalled_secs: u64,
+    },
+
+    /// A segment of a client audio stream was transcribed
+    StreamTranscription {
+        /// The stream the segment came from
+        stream_id: String,
+        /// Where the segment started, in milliseconds from the start of the stream
+        offset_ms: u64,
+        /// The transcribed text; empty if nothing was recognized or
+        /// transcription failed
+        text: String,
+    },
+
+    /// Meeting mode stopped
+    MeetingEnded {
+        /// The finished meeting record
+        meeting: MeetingRecord,
+    },
+
+    /// History recording playback ended or was stopped
+    HistoryAudioFinished {
+        /// The ID of the history entry that was playing
+        id: String,
+    },
+
+    /// A test mode run started playing a file
+    TestFileStarted {
+        /// Position of the file in the run (0-based)
+        index: usize,
+        /// Number of files in the run
+        total: usize,
+        /// WAV file name
+        file: String,
+    },
+
+    /// A test mode run finished a file
+    TestFileComplete {
+        /// Position of the file in the run (0-based)
+        index: usize,
+        /// Number of files in the run
+        total: usize,
+        /// Outcome and scores for the file
+        result: crate::types::TestFileResult,
+    },
+
+    /// A test mode run finished
+    TestRunComplete {
+        /// Totals across all files
+        summary: crate::types::TestRunSummary,
+        /// Where the results report was written, if it could be
+        report_path: Option<String>,
+    },
+
+    /// Progress of a graceful shutdown, sent before `Shutdown`
+    ShutdownProgress {
+        /// The phase that has just started
+        phase: crate::types::ShutdownPhase,
+        /// Segments still waiting to be transcribed
+        pending_segments: usize,
+    },
+
     /// Service is shutting down
     Shutdown,
 }
@@ -0,0 +1,374 @@
+//! Key name parsing and keyboard layouts.
+//!
+//! Hotkeys are stored as physical [`KeyCode`]s named after the US layout.
+//! This module lets users type key names the way their keyboard labels them:
+//! English and localized aliases ("ctrl", "strg", "maj", "alt gr"), display
+//! names ("Right Ctrl"), and letters as printed on the active layout.
+
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{HotkeyCombination, KeyCode};
+
+/// How the letter keys are arranged on a keyboard layout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LetterLayout {
+    /// US, UK and most other Latin layouts
+    #[default]
+    Qwerty,
+    /// German, Austrian, Swiss and Central European layouts (Y and Z swapped)
+    Qwertz,
+    /// French and Belgian layouts (A/Q and Z/W swapped, M right of L)
+    Azerty,
+}
+
+/// XKB layouts with a QWERTZ letter arrangement
+const XKB_QWERTZ: &[&str] = &[
+    "de", "at", "ch", "li", "lu", "cz", "sk", "hu", "si", "hr", "ba",
+];
+
+/// XKB layouts with an AZERTY letter arrangement
+const XKB_AZERTY: &[&str] = &["fr", "be"];
+
+/// Windows language IDs (the low half of a KLID) with a QWERTZ layout
+const LANGID_QWERTZ: &[&str] = &[
+    "0407", "0c07", "0807", "1007", "1407", "100c", "0405", "041b", "040e", "0424", "041a",
+];
+
+/// Windows language IDs with an AZERTY layout
+const LANGID_AZERTY: &[&str] = &["040c", "080c", "0813", "180c"];
+
+impl LetterLayout {
+    /// Guess the letter arrangement from a platform layout identifier: an XKB
+    /// layout (`de`, `fr(oss)`), a Windows KLID (`00000407`), or a macOS input
+    /// source ID (`com.apple.keylayout.German`). Unknown layouts are QWERTY.
+    pub fn from_layout_id(id: &str) -> Self {
+        let id = id.trim().to_lowercase();
+
+        if let Some(name) = id.strip_prefix("com.apple.keylayout.") {
+            let name: String = name.chars().filter(|c| c.is_alphanumeric()).collect();
+            let starts = |prefixes: &[&str]| prefixes.iter().any(|p| name.starts_with(p));
+            return if name.contains("qwerty") {
+                Self::Qwerty
+            } else if starts(&["french", "belgian"]) {
+                Self::Azerty
+            } else if starts(&[
+                "german",
+                "austrian",
+                "swiss",
+                "czech",
+                "slovak",
+                "hungarian",
+                "slovenian",
+                "croatian",
+            ]) {
+                Self::Qwertz
+            } else {
+                Self::Qwerty
+            };
+        }
+
+        if id.len() == 8 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+            let langid = &id[4..];
+            return if LANGID_QWERTZ.contains(&langid) {
+                Self::Qwertz
+            } else if LANGID_AZERTY.contains(&langid) {
+                Self::Azerty
+            } else {
+                Self::Qwerty
+            };
+        }
+
+        // XKB: the first of a comma-separated list, without its variant
+        let layout = id.split(',').next().unwrap_or_default();
+        let layout = layout.split('(').next().unwrap_or_default().trim();
+        if XKB_QWERTZ.contains(&layout) {
+            Self::Qwertz
+        } else if XKB_AZERTY.contains(&layout) {
+            Self::Azerty
+        } else {
+            Self::Qwerty
+        }
+    }
+
+    /// The physical key that types `letter` on this layout.
+    pub fn letter_key(self, letter: char) -> Option<KeyCode> {
+        let letter = letter.to_ascii_lowercase();
+        let position = match (self, letter) {
+            (Self::Qwertz, 'y') => 'z',
+            (Self::Qwertz, 'z') => 'y',
+            (Self::Azerty, 'a') => 'q',
+            (Self::Azerty, 'q') => 'a',
+            (Self::Azerty, 'z') => 'w',
+            (Self::Azerty, 'w') => 'z',
+            (Self::Azerty, 'm') => return Some(KeyCode::Semicolon),
+            _ => letter,
+        };
+        if !position.is_ascii_lowercase() {
+            return None;
+        }
+        canonical(&format!("key_{}", position))
+    }
+}
+
+/// Words that put a modifier on the left ("left ctrl", "strg links")
+const LEFT: &[&str] = &["left", "links", "gauche", "izquierda", "sinistra", "l"];
+
+/// Words that put a modifier on the right
+const RIGHT: &[&str] = &["right", "rechts", "droite", "derecha", "destra", "r"];
+
+/// Modifier names by family, as (left key, right key, names)
+const MODIFIERS: &[(KeyCode, KeyCode, &[&str])] = &[
+    (
+        KeyCode::LeftControl,
+        KeyCode::RightControl,
+        &["control", "ctrl", "ctl", "strg", "controle", "contrôle"],
+    ),
+    (
+        KeyCode::LeftAlt,
+        KeyCode::RightAlt,
+        &["alt", "option", "opt"],
+    ),
+    (
+        KeyCode::LeftShift,
+        KeyCode::RightShift,
+        &["shift", "umschalt", "maj", "mayus", "mayús", "maiusc"],
+    ),
+    (
+        KeyCode::LeftMeta,
+        KeyCode::RightMeta,
+        &[
+            "meta", "win", "windows", "super", "cmd", "command", "befehl", "commande",
+        ],
+    ),
+];
+
+impl KeyCode {
+    /// Parse a key name typed by a user, with letters as on a US keyboard.
+    /// See [`KeyCode::from_name_for_layout`].
+    pub fn from_name(name: &str) -> Option<KeyCode> {
+        Self::from_name_for_layout(name, LetterLayout::Qwerty)
+    }
+
+    /// Parse a key name typed by a user.
+    ///
+    /// Accepts config names (`left_control`), display names (`Right Ctrl`),
+    /// common and localized aliases (`ctrl`, `strg`, `maj`, `alt gr`, `entf`),
+    /// and single characters. Letters are mapped to the key that types them
+    /// on `layout`. A modifier without a side means the left one.
+    pub fn from_name_for_layout(name: &str, layout: LetterLayout) -> Option<KeyCode> {
+        let name = name.trim().to_lowercase();
+
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return single_char(c, layout);
+        }
+        if let Some(key) = numpad(&name) {
+            return Some(key);
+        }
+
+        let normalized = name
+            .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("_");
+        canonical(&normalized)
+            .or_else(|| modifier(&normalized))
+            .or_else(|| alias(&normalized))
+    }
+}
+
+impl HotkeyCombination {
+    /// Parse a combination typed as key names joined by `+`, such as
+    /// `strg+alt gr` or `ctrl+shift+f5`.
+    pub fn parse(text: &str, layout: LetterLayout) -> Result<HotkeyCombination, String> {
+        let keys = text
+            .split('+')
+            .map(|name| {
+                KeyCode::from_name_for_layout(name, layout)
+                    .ok_or_else(|| format!("Unknown key: '{}'", name.trim()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HotkeyCombination::new(keys))
+    }
+}
+
+/// Look up a config (serde) key name.
+fn canonical(name: &str) -> Option<KeyCode> {
+    let deserializer: StrDeserializer<ValueError> = name.into_deserializer();
+    KeyCode::deserialize(deserializer).ok()
+}
+
+fn single_char(c: char, layout: LetterLayout) -> Option<KeyCode> {
+    if c.is_alphabetic() {
+        return layout.letter_key(c);
+    }
+    if c.is_ascii_digit() {
+        return canonical(&format!("digit{}", c));
+    }
+    Some(match c {
+        '-' => KeyCode::Minus,
+        '=' => KeyCode::Equal,
+        '[' => KeyCode::BracketLeft,
+        ']' => KeyCode::BracketRight,
+        '\\' => KeyCode::Backslash,
+        ';' => KeyCode::Semicolon,
+        '\'' => KeyCode::Quote,
+        '`' => KeyCode::Backquote,
+        ',' => KeyCode::Comma,
+        '.' => KeyCode::Period,
+        '/' => KeyCode::Slash,
+        _ => return None,
+    })
+}
+
+/// Numpad keys written as `num 5`, `numpad *` or `kp_+`.
+fn numpad(name: &str) -> Option<KeyCode> {
+    let rest = ["numpad", "num", "kp"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))?;
+    let mut chars = rest.trim_start_matches([' ', '_']).chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    Some(match c {
+        '0'..='9' => return canonical(&format!("numpad{}", c)),
+        '*' => KeyCode::NumpadMultiply,
+        '+' => KeyCode::NumpadAdd,
+        '-' => KeyCode::NumpadSubtract,
+        '.' => KeyCode::NumpadDecimal,
+        '/' => KeyCode::NumpadDivide,
+        _ => return None,
+    })
+}
+
+/// Modifier names with an optional side before or after them.
+fn modifier(name: &str) -> Option<KeyCode> {
+    // AltGr is the right Alt key on every layout that has it
+    if name == "altgr" || name == "alt_gr" {
+        return Some(KeyCode::RightAlt);
+    }
+
+    let base = |base: &str, right: bool| {
+        MODIFIERS
+            .iter()
+            .find(|(_, _, names)| names.contains(&base))
+            .map(|&(left_key, right_key, _)| if right { right_key } else { left_key })
+    };
+    let sided = |sides: &[&str], right: bool| {
+        sides.iter().find_map(|side| {
+            let before = name
+                .strip_prefix(side)
+                .map(|rest| rest.strip_prefix('_').unwrap_or(rest));
+            let after = name
+                .strip_suffix(side)
+                .and_then(|rest| rest.strip_suffix('_'));
+            before
+                .and_then(|rest| base(rest, right))
+                .or_else(|| after.and_then(|rest| base(rest, right)))
+        })
+    };
+
+    base(name, false)
+        .or_else(|| sided(LEFT, false))
+        .or_else(|| sided(RIGHT, true))
+}
+
+/// Other English and localized key names.
+fn alias(name: &str) -> Option<KeyCode> {
+    Some(match name {
+        "esc" | "echap" | "échap" => KeyCode::Escape,
+        "return" | "eingabe" | "entree" | "entrée" | "intro" => KeyCode::Enter,
+        "spacebar" | "leertaste" | "espace" | "espacio" | "spazio" => KeyCode::Space,
+        "rücktaste" | "ruecktaste" | "retour_arriere" | "retroceso" => KeyCode::Backspace,
+        "tabulator" | "tabulation" => KeyCode::Tab,
+        "caps" | "capslock" | "feststelltaste" | "verr_maj" | "bloq_mayus" => KeyCode::CapsLock,
+        "del" | "entf" | "suppr" | "supr" => KeyCode::Delete,
+        "ins" | "einfg" | "inser" => KeyCode::Insert,
+        "pos1" | "debut" | "début" | "inicio" => KeyCode::Home,
+        "ende" | "fin" => KeyCode::End,
+        "pgup" | "bild_auf" => KeyCode::PageUp,
+        "pgdn" | "bild_ab" => KeyCode::PageDown,
+        "up" => KeyCode::ArrowUp,
+        "down" => KeyCode::ArrowDown,
+        "left" => KeyCode::ArrowLeft,
+        "right" => KeyCode::ArrowRight,
+        "prtsc" | "print" | "druck" => KeyCode::PrintScreen,
+        "numlock" => KeyCode::NumLock,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_name_aliases() {
+        let parse = KeyCode::from_name;
+        assert_eq!(parse("left_control"), Some(KeyCode::LeftControl));
+        assert_eq!(parse("Right Ctrl"), Some(KeyCode::RightControl));
+        assert_eq!(parse("strg"), Some(KeyCode::LeftControl));
+        assert_eq!(parse("Strg rechts"), Some(KeyCode::RightControl));
+        assert_eq!(parse("rshift"), Some(KeyCode::RightShift));
+        assert_eq!(parse("Alt Gr"), Some(KeyCode::RightAlt));
+        assert_eq!(parse("maj droite"), Some(KeyCode::RightShift));
+        assert_eq!(parse("cmd"), Some(KeyCode::LeftMeta));
+        assert_eq!(parse("Entf"), Some(KeyCode::Delete));
+        assert_eq!(parse("left"), Some(KeyCode::ArrowLeft));
+        assert_eq!(parse("Num 5"), Some(KeyCode::Numpad5));
+        assert_eq!(parse("Num -"), Some(KeyCode::NumpadSubtract));
+        assert_eq!(parse("F13"), Some(KeyCode::F13));
+        assert_eq!(parse("7"), Some(KeyCode::Digit7));
+        assert_eq!(parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_layout_letters() {
+        assert_eq!(
+            LetterLayout::from_layout_id("de(nodeadkeys)"),
+            LetterLayout::Qwertz
+        );
+        assert_eq!(LetterLayout::from_layout_id("fr,us"), LetterLayout::Azerty);
+        assert_eq!(
+            LetterLayout::from_layout_id("00000407"),
+            LetterLayout::Qwertz
+        );
+        assert_eq!(
+            LetterLayout::from_layout_id("com.apple.keylayout.French-PC"),
+            LetterLayout::Azerty
+        );
+        assert_eq!(LetterLayout::from_layout_id("us"), LetterLayout::Qwerty);
+
+        let qwertz = LetterLayout::Qwertz;
+        assert_eq!(
+            KeyCode::from_name_for_layout("z", qwertz),
+            Some(KeyCode::KeyY)
+        );
+        assert_eq!(
+            KeyCode::from_name_for_layout("a", qwertz),
+            Some(KeyCode::KeyA)
+        );
+        let azerty = LetterLayout::Azerty;
+        assert_eq!(
+            KeyCode::from_name_for_layout("A", azerty),
+            Some(KeyCode::KeyQ)
+        );
+        assert_eq!(
+            KeyCode::from_name_for_layout("m", azerty),
+            Some(KeyCode::Semicolon)
+        );
+
+        assert_eq!(
+            HotkeyCombination::parse("strg + umschalt + z", qwertz),
+            Ok(HotkeyCombination::new(vec![
+                KeyCode::LeftControl,
+                KeyCode::LeftShift,
+                KeyCode::KeyY,
+            ]))
+        );
+        assert!(HotkeyCombination::parse("ctrl+nope", qwertz).is_err());
+    }
+}
@@ -6,12 +6,19 @@
 use std::sync::OnceLock;
 
 pub mod config;
+pub mod i18n;
 pub mod ipc;
+pub mod keys;
 pub mod logging;
+pub mod paths;
+pub mod portable;
 pub mod security;
+pub mod settings;
 pub mod types;
 
 pub use config::ThemeMode;
+pub use i18n::Language;
+pub use keys::LetterLayout;
 pub use types::*;
 
 static RUNTIME_MODE: OnceLock<RuntimeMode> = OnceLock::new();
@@ -8,40 +8,10 @@ use std::path::PathBuf;
 /// |----------|-----------|
 /// | Linux | `$XDG_STATE_HOME/flowstt/logs` or `~/.local/state/flowstt/logs` |
 /// | macOS | `~/Library/Logs/flowstt` |
-/// | Windows | `%APPDATA%/flowstt/logs` |
+/// | Windows | `%LOCALAPPDATA%/flowstt/logs` |
+/// | Portable mode | `data/logs` next to the executable |
 pub fn log_dir() -> PathBuf {
-    #[cfg(target_os = "linux")]
-    {
-        let base = directories::ProjectDirs::from("io", "flowstt", "flowstt")
-            .expect("Failed to determine project directories");
-        base.state_dir()
-            .unwrap_or_else(|| base.data_local_dir().join("state"))
-            .join("logs")
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        // Use standard macOS log location: ~/Library/Logs/<app>/
-        dirs::home_dir()
-            .expect("Failed to determine home directory")
-            .join("Library")
-            .join("Logs")
-            .join("flowstt")
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let base = directories::ProjectDirs::from("io", "flowstt", "flowstt")
-            .expect("Failed to determine project directories");
-        base.data_local_dir().join("logs")
-    }
-
-    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-    {
-        let base = directories::ProjectDirs::from("io", "flowstt", "flowstt")
-            .expect("Failed to determine project directories");
-        base.data_local_dir().join("logs")
-    }
+    crate::paths::log_dir()
 }
 
 /// Ensures the log directory exists, creating it if necessary.
@@ -0,0 +1,287 @@
+//! Where FlowSTT keeps its files.
+//!
+//! Every directory is resolved here, following the conventions of each
+//! platform:
+//!
+//! | | Linux | macOS | Windows |
+//! |---|---|---|---|
+//! | Config | `$XDG_CONFIG_HOME/flowstt` | `~/Library/Application Support/flowstt` | `%APPDATA%\flowstt` |
+//! | Data | `$XDG_DATA_HOME/flowstt` | `~/Library/Application Support/flowstt` | `%LOCALAPPDATA%\flowstt` |
+//! | Logs | `$XDG_STATE_HOME/flowstt/logs` | `~/Library/Logs/flowstt` | `%LOCALAPPDATA%\flowstt\logs` |
+//!
+//! History, recordings and models are kept in the data directory. The
+//! `paths` section of the config file can move recordings and models
+//! elsewhere, and in portable mode everything is kept in a `data` folder next
+//! to the executable (see `crate::portable`).
+//!
+//! Older versions kept data on Windows in the roaming profile and logs in a
+//! nested `flowstt\flowstt\data` folder; [`migrate`] moves them once. Models
+//! used to be downloaded to a `whisper` cache directory that other tools may
+//! share, so they are left there and still used until the models directory
+//! exists. Portable copies kept them in `data/whisper`, which only FlowSTT
+//! uses, so [`migrate`] moves them to `data/models`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::{BaseDirs, ProjectDirs};
+
+use crate::config::Config;
+
+const APP_DIR: &str = "flowstt";
+
+fn base_dirs() -> Option<BaseDirs> {
+    BaseDirs::new()
+}
+
+/// Directory holding `config.json`.
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = crate::portable::data_dir() {
+        return dir;
+    }
+    base_dirs()
+        .map(|d| d.config_dir().join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from(".").join(APP_DIR))
+}
+
+/// Directory holding history and, by default, recordings and models.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = crate::portable::data_dir() {
+        return dir;
+    }
+    base_dirs()
+        .map(|d| d.data_local_dir().join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from(".").join("flowstt-data"))
+}
+
+/// Directory recordings are saved to.
+pub fn recordings_dir() -> PathBuf {
+    Config::load()
+        .paths
+        .recordings_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(default_recordings_dir)
+}
+
+/// Directory recordings are saved to when the config doesn't move them.
+pub fn default_recordings_dir() -> PathBuf {
+    data_dir().join("recordings")
+}
+
+/// Directory whisper models are downloaded to and loaded from.
+pub fn models_dir() -> PathBuf {
+    if let Some(dir) = Config::load().paths.models_dir {
+        return PathBuf::from(dir);
+    }
+    existing_models_dir(default_models_dir(), legacy_models_dir())
+}
+
+/// Directory models are kept in when the config doesn't move them.
+fn default_models_dir() -> PathBuf {
+    data_dir().join("models")
+}
+
+/// `dir`, or the legacy directory while `dir` doesn't exist yet and the
+/// legacy one holds models.
+fn existing_models_dir(dir: PathBuf, legacy: Option<PathBuf>) -> PathBuf {
+    if dir.exists() {
+        return dir;
+    }
+    legacy.filter(|d| has_models(d)).unwrap_or(dir)
+}
+
+/// Directory log files are written to.
+pub fn log_dir() -> PathBuf {
+    if let Some(dir) = crate::portable::data_dir() {
+        return dir.join("logs");
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        base_dirs()
+            .and_then(|d| d.state_dir().map(Path::to_path_buf))
+            .unwrap_or_else(|| data_dir().join("state"))
+            .join(APP_DIR)
+            .join("logs")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        dirs::home_dir()
+            .expect("Failed to determine home directory")
+            .join("Library")
+            .join("Logs")
+            .join(APP_DIR)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        data_dir().join("logs")
+    }
+}
+
+/// Per-user directory the desktop starts login items from: the XDG
+/// `autostart` directory on Linux and `~/Library/LaunchAgents` on macOS.
+/// Windows keeps them in the registry instead.
+pub fn login_items_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        base_dirs().map(|d| d.config_dir().join("autostart"))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        base_dirs().map(|d| d.home_dir().join("Library").join("LaunchAgents"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Model directory of older versions: shared with other whisper tools, or in
+/// portable mode the `whisper` folder beside the data.
+fn legacy_models_dir() -> Option<PathBuf> {
+    if let Some(dir) = crate::portable::data_dir() {
+        return Some(dir.join("whisper"));
+    }
+    base_dirs().map(|d| d.cache_dir().join("whisper"))
+}
+
+fn has_models(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("ggml-") && name.ends_with(".bin")
+        })
+    })
+}
+
+/// Data and log directories of older versions, with where they belong now.
+/// On Linux and macOS they are the same. Portable copies only ever moved
+/// their models.
+fn legacy_dirs() -> Vec<(PathBuf, PathBuf)> {
+    if crate::portable::portable_dir().is_some() {
+        return legacy_models_dir()
+            .map(|old| vec![(old, default_models_dir())])
+            .unwrap_or_default();
+    }
+
+    let mut dirs = Vec::new();
+    if let Some(old) = ProjectDirs::from("", "", APP_DIR) {
+        dirs.push((old.data_dir().to_path_buf(), data_dir()));
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    if let Some(old) = ProjectDirs::from("io", APP_DIR, APP_DIR) {
+        dirs.push((old.data_local_dir().join("logs"), log_dir()));
+    }
+    dirs.retain(|(old, new)| old != new);
+    dirs
+}
+
+/// Move files left by older versions into the current layout.
+///
+/// Files already present at the destination are kept and the old copies
+/// left in place. Returns a description of each problem, for logging once
+/// logging is up.
+pub fn migrate() -> Vec<String> {
+    move_dirs(legacy_dirs())
+}
+
+/// Move the contents of each old directory into its new one.
+fn move_dirs(dirs: Vec<(PathBuf, PathBuf)>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (old, new) in dirs {
+        let Ok(entries) = fs::read_dir(&old) else {
+            continue;
+        };
+        if let Err(e) = fs::create_dir_all(&new) {
+            problems.push(format!("Failed to create {:?}: {}", new, e));
+            continue;
+        }
+        for entry in entries.flatten() {
+            let target = new.join(entry.file_name());
+            if target.exists() {
+                problems.push(format!(
+                    "Not moving {:?}: {:?} exists",
+                    entry.path(),
+                    target
+                ));
+                continue;
+            }
+            if let Err(e) = fs::rename(entry.path(), &target) {
+                problems.push(format!("Failed to move {:?}: {}", entry.path(), e));
+            }
+        }
+        // Remove the old directory, and its parents that only held it
+        for dir in old.ancestors().take(3) {
+            if fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+    problems
+}
+
+/// Where a file recorded under an older version's data directory is now.
+pub fn relocate(path: &Path) -> Option<PathBuf> {
+    legacy_dirs()
+        .into_iter()
+        .find_map(|(old, new)| Some(new.join(path.strip_prefix(&old).ok()?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flowstt-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn legacy_models_are_used_until_the_models_dir_exists() {
+        let data = temp_dir("lookup");
+        let (models, legacy) = (data.join("models"), data.join("whisper"));
+
+        // An empty legacy directory is not worth keeping
+        fs::create_dir_all(&legacy).unwrap();
+        assert_eq!(
+            existing_models_dir(models.clone(), Some(legacy.clone())),
+            models
+        );
+
+        fs::write(legacy.join("ggml-base.en.bin"), b"model").unwrap();
+        assert_eq!(
+            existing_models_dir(models.clone(), Some(legacy.clone())),
+            legacy
+        );
+
+        fs::create_dir_all(&models).unwrap();
+        assert_eq!(existing_models_dir(models.clone(), Some(legacy)), models);
+
+        let _ = fs::remove_dir_all(&data);
+    }
+
+    #[test]
+    fn portable_models_move_to_the_models_dir() {
+        let data = temp_dir("portable");
+        let (models, legacy) = (data.join("models"), data.join("whisper"));
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("ggml-base.en.bin"), b"model").unwrap();
+        fs::write(data.join("config.json"), b"{}").unwrap();
+
+        assert!(move_dirs(vec![(legacy.clone(), models.clone())]).is_empty());
+        assert_eq!(fs::read(models.join("ggml-base.en.bin")).unwrap(), b"model");
+        assert!(!legacy.exists());
+        // The data folder holding it stays
+        assert!(data.join("config.json").exists());
+
+        let _ = fs::remove_dir_all(&data);
+    }
+}
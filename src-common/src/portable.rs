@@ -0,0 +1,71 @@
+//! Portable mode.
+//!
+//! A copy of FlowSTT run from a folder (a USB stick, say) without being
+//! installed can keep everything in that folder instead of the user profile.
+//! Portable mode is on when a `flowstt-portable` file sits next to the
+//! executable, or when it is started with `--portable`. Config, models,
+//! history and logs then live in a `data` folder beside the executables, and
+//! the IPC socket or pipe name is made unique to the folder so a portable
+//! copy never talks to an installed one.
+//!
+//! `--portable` is passed on to the processes FlowSTT spawns through the
+//! `FLOWSTT_PORTABLE` environment variable, which holds the portable folder.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// File next to the executable that turns portable mode on
+pub const MARKER_FILE: &str = "flowstt-portable";
+
+/// Environment variable holding the portable folder
+const PORTABLE_ENV: &str = "FLOWSTT_PORTABLE";
+
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Turn portable mode on for this process and the processes it spawns.
+///
+/// Must be called at startup, before any path is resolved.
+pub fn enable() {
+    if let Some(dir) = exe_dir() {
+        std::env::set_var(PORTABLE_ENV, dir);
+    }
+}
+
+/// Folder of the portable copy, if running in portable mode.
+pub fn portable_dir() -> Option<&'static Path> {
+    PORTABLE_DIR.get_or_init(detect).as_deref()
+}
+
+/// Folder config, models, history and logs are kept in, if running in
+/// portable mode.
+pub fn data_dir() -> Option<PathBuf> {
+    portable_dir().map(|dir| dir.join("data"))
+}
+
+/// Suffix that keeps the IPC endpoint of a portable copy apart from an
+/// installed copy and from other portable copies.
+pub fn endpoint_suffix() -> Option<String> {
+    let dir = portable_dir()?;
+    // FNV-1a: stable across builds, so every binary in the folder agrees
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in dir.to_string_lossy().to_lowercase().bytes() {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    Some(format!("{:08x}", hash))
+}
+
+fn detect() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(PORTABLE_ENV).filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    let dir = exe_dir()?;
+    dir.join(MARKER_FILE).exists().then_some(dir)
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(Path::to_path_buf)
+}
@@ -59,7 +59,8 @@ impl std::error::Error for PeerVerifyError {}
 ///
 /// The executable must:
 /// 1. Have a filename matching one of TRUSTED_EXECUTABLES
-/// 2. Be located in a TRUSTED_DIRECTORY OR the same directory as the service
+/// 2. Be located in a TRUSTED_DIRECTORY, the same directory as the service,
+///    or the portable folder
 fn verify_executable(exe_path: &Path) -> Result<(), PeerVerifyError> {
     // Get executable name
     let exe_name = exe_path
@@ -90,21 +91,41 @@ fn verify_executable(exe_path: &Path) -> Result<(), PeerVerifyError> {
         .ok()
         .and_then(|p| p.parent().map(|d| d.to_path_buf()));
 
-    let in_trusted_dir = TRUSTED_DIRECTORIES.iter().any(|d| exe_dir == Path::new(d));
-    let same_as_self = self_dir.as_ref().map(|d| exe_dir == d).unwrap_or(false);
+    let in_trusted_dir = TRUSTED_DIRECTORIES
+        .iter()
+        .any(|d| same_dir(exe_dir, Path::new(d)));
+    let same_as_self = self_dir
+        .as_ref()
+        .map(|d| same_dir(exe_dir, d))
+        .unwrap_or(false);
+    let in_portable_dir = crate::portable::portable_dir().is_some_and(|d| same_dir(exe_dir, d));
 
     // During development, also allow cargo target directories
     let exe_dir_str = exe_dir.to_string_lossy();
     let in_target_dir = exe_dir_str.contains("target")
         && (exe_dir_str.contains("debug") || exe_dir_str.contains("release"));
 
-    if !in_trusted_dir && !same_as_self && !in_target_dir {
+    if !in_trusted_dir && !same_as_self && !in_portable_dir && !in_target_dir {
         return Err(PeerVerifyError::UntrustedDirectory(exe_path.to_path_buf()));
     }
 
     Ok(())
 }
 
+/// Whether two directory paths name the same directory. Windows paths are
+/// case-insensitive and may come back with a different drive letter case.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        a.to_string_lossy()
+            .eq_ignore_ascii_case(&b.to_string_lossy())
+    }
+    #[cfg(not(windows))]
+    {
+        a == b
+    }
+}
+
 /// Verify connecting peer on Linux using SO_PEERCRED.
 #[cfg(target_os = "linux")]
 pub fn verify_peer(stream: &std::os::unix::net::UnixStream) -> Result<PeerInfo, PeerVerifyError> {
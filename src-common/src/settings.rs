@@ -0,0 +1,374 @@
+//! Settings shared by the engine, the GUI and the CLI.
+//!
+//! Every change to the config file goes through [`update`], or [`set`] for a
+//! single [`Key`]. Both reload the file, apply the change and save it while
+//! holding a lock, both within the process and on `config.lock` next to the
+//! config file, so the service, the GUI and the CLI never overwrite each
+//! other's changes. Callbacks registered with [`subscribe`] are told which
+//! settings each change made in this process touched.
+//!
+//! [`current`] returns the configuration without reading the file each time:
+//! it is read again only when its modification time changes, so hot paths
+//! such as hotkey presses and finished segments can check a setting cheaply.
+
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::config::Config;
+
+/// Serializes updates within this process; the lock file only keeps other
+/// processes out
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A config file as last read or saved: its path, its modification time
+/// then and its contents
+type Cached = (PathBuf, Option<SystemTime>, Arc<Config>);
+
+/// The configuration [`current`] returns until the file changes, by path
+static CURRENT: Mutex<Vec<Cached>> = Mutex::new(Vec::new());
+
+/// A callback told about changes
+type Subscriber = Box<dyn Fn(&Config, &Change) + Send>;
+
+/// Callbacks told about every change
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(Vec::new());
+
+/// A typed setting: one top-level field of [`Config`].
+pub struct Key<T: 'static> {
+    name: &'static str,
+    get: fn(&Config) -> &T,
+    get_mut: fn(&mut Config) -> &mut T,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+impl<T> Key<T> {
+    /// Name of the setting in the config file.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The setting's value in `config`.
+    pub fn get<'a>(&self, config: &'a Config) -> &'a T {
+        (self.get)(config)
+    }
+
+    /// The setting's value in `config`, to change it.
+    pub fn get_mut<'a>(&self, config: &'a mut Config) -> &'a mut T {
+        (self.get_mut)(config)
+    }
+}
+
+macro_rules! keys {
+    ($($key:ident: $ty:ty = $field:ident;)*) => {
+        $(
+            #[doc = concat!("`", stringify!($field), "`")]
+            pub const $key: Key<$ty> = Key {
+                name: stringify!($field),
+                get: |config| &config.$field,
+                get_mut: |config| &mut config.$field,
+                _type: std::marker::PhantomData,
+            };
+        )*
+
+        /// Names of every setting, in config file order.
+        pub const ALL: &[&str] = &[$(stringify!($field)),*];
+    };
+}
+
+/// The typed settings.
+pub mod key {
+    use std::collections::HashMap;
+
+    use super::Key;
+    use crate::config::*;
+    use crate::i18n::Language;
+    use crate::types::{HotkeyCombination, TranscriptionMode};
+
+    keys! {
+        TRANSCRIPTION_MODE: TranscriptionMode = transcription_mode;
+        PTT_HOTKEYS: Vec<HotkeyCombination> = ptt_hotkeys;
+        AUTO_TOGGLE_HOTKEYS: Vec<HotkeyCombination> = auto_toggle_hotkeys;
+        DICTATION_HOTKEYS: Vec<HotkeyCombination> = dictation_hotkeys;
+        SPELLING_HOTKEYS: Vec<HotkeyCombination> = spelling_hotkeys;
+        AUTO_PASTE_ENABLED: bool = auto_paste_enabled;
+        AUTO_PASTE_DELAY_MS: u32 = auto_paste_delay_ms;
+        THEME_MODE: ThemeMode = theme_mode;
+        ACCENT_COLOR: Option<String> = accent_color;
+        VISUALIZATION_PALETTE: VisualizationPalette = visualization_palette;
+        VISUALIZATION: VisualizationSettings = visualization;
+        ALWAYS_ON_TOP: bool = always_on_top;
+        PREFERRED_SOURCE1_ID: Option<String> = preferred_source1_id;
+        PREFERRED_SOURCE2_ID: Option<String> = preferred_source2_id;
+        FALLBACK_SOURCE_IDS: Vec<String> = fallback_source_ids;
+        LOG_LEVEL: LogLevel = log_level;
+        DEVICE_CALIBRATIONS: HashMap<String, DeviceCalibration> = device_calibrations;
+        NOTIFICATIONS: NotificationSettings = notifications;
+        SOUND_CUES: SoundCueSettings = sound_cues;
+        PROCESS_RULES: Vec<ProcessRule> = process_rules;
+        AUTO_SUSPEND_MINUTES: u32 = auto_suspend_minutes;
+        VAD_SOURCE: VadSource = vad_source;
+        ENCRYPT_HISTORY: bool = encrypt_history;
+        REDACTION: RedactionSettings = redaction;
+        PROFANITY: ProfanitySettings = profanity;
+        CORRECTIONS: CorrectionSettings = corrections;
+        SPOKEN_COMMANDS: SpokenCommandSettings = spoken_commands;
+        PAUSE_WORDS: PauseWords = pause_words;
+        OUTPUT_TEMPLATES: OutputTemplates = output_templates;
+        PTT_MAX_DURATION_SECS: u32 = ptt_max_duration_secs;
+        TRANSCRIPTION_ENGINE: TranscriptionEngineKind = transcription_engine;
+        DUMP_SEGMENTS: bool = dump_segments;
+        SEGMENT_NORMALIZATION: SegmentNormalization = segment_normalization;
+        NARROWBAND_INPUT: NarrowbandInput = narrowband_input;
+        SEGMENT_JOINING: SegmentJoining = segment_joining;
+        TRANSCRIPT_SINK: Option<String> = transcript_sink;
+        CAPTION_FILE: CaptionFileSettings = caption_file;
+        ACTIVITY_LOG: bool = activity_log;
+        SYNC: SyncSettings = sync;
+        POWER_POLICY: PowerPolicy = power_policy;
+        LOAD_SHEDDING: LoadShedding = load_shedding;
+        CAPTURE_BUFFER_SECS: u32 = capture_buffer_secs;
+        SEGMENTATION: SegmentationStrategy = segmentation;
+        SEGMENT_OVERLAP_MS: u32 = segment_overlap_ms;
+        PATHS: PathSettings = paths;
+        DISK_SPACE: DiskSpace = disk_space;
+        OUTPUT_SINKS: Vec<OutputSinkSettings> = output_sinks;
+        UPDATES: UpdateSettings = updates;
+        CRASH_REPORTS: bool = crash_reports;
+        PAUSE_ON_LOCK: bool = pause_on_lock;
+        LANGUAGE: Language = language;
+        SPEAKER_FILTER: SpeakerFilter = speaker_filter;
+        PARTIAL_TYPING: PartialTyping = partial_typing;
+    }
+}
+
+/// The settings one update changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Change {
+    keys: Vec<String>,
+}
+
+impl Change {
+    /// Names of the changed settings, in config file order.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Whether the setting changed.
+    pub fn contains<T>(&self, key: Key<T>) -> bool {
+        self.keys.iter().any(|name| name == key.name())
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The settings that differ between two configs.
+    fn between(old: &Config, new: &Config) -> Self {
+        let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+            return Self::default();
+        };
+        let keys = key::ALL
+            .iter()
+            .filter(|name| old.get(**name) != new.get(**name))
+            .map(|name| name.to_string())
+            .collect();
+        Self { keys }
+    }
+}
+
+/// Load the configuration, apply `change` and save it. Returns the saved
+/// configuration.
+pub fn update(change: impl FnOnce(&mut Config)) -> io::Result<Config> {
+    let (config, changed) = update_file(&Config::config_path(), change)?;
+    if !changed.is_empty() {
+        let subscribers = SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner());
+        for subscriber in subscribers.iter() {
+            subscriber(&config, &changed);
+        }
+    }
+    Ok(config)
+}
+
+/// Save one setting. Returns the saved configuration.
+pub fn set<T>(key: Key<T>, value: T) -> io::Result<Config> {
+    update(|config| *key.get_mut(config) = value)
+}
+
+/// The current value of one setting.
+pub fn get<T: Clone>(key: Key<T>) -> T {
+    key.get(&current()).clone()
+}
+
+/// The current configuration. The file is only read when it changed since it
+/// was last read or saved by this process.
+pub fn current() -> Arc<Config> {
+    current_from(&Config::config_path())
+}
+
+fn current_from(path: &Path) -> Arc<Config> {
+    let modified = modified(path);
+    let current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    let cached = current.iter().find(|(cached, _, _)| cached == path);
+    if let Some((_, cached_modified, config)) = cached {
+        if *cached_modified == modified {
+            return Arc::clone(config);
+        }
+    }
+    drop(current);
+
+    cache(path, modified, Config::load_from(path))
+}
+
+/// Remember `config` as the contents of the file at `path` as of `modified`.
+fn cache(path: &Path, modified: Option<SystemTime>, config: Config) -> Arc<Config> {
+    let config = Arc::new(config);
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    current.retain(|(cached, _, _)| cached != path);
+    current.push((path.to_path_buf(), modified, Arc::clone(&config)));
+    config
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Call `callback` after every update made in this process that changed a
+/// setting, with the saved configuration and what changed. Callbacks run on
+/// the thread that made the update and must not subscribe themselves.
+pub fn subscribe(callback: impl Fn(&Config, &Change) + Send + 'static) {
+    SUBSCRIBERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Box::new(callback));
+}
+
+/// Reload, change and save the config file at `path` under both locks.
+fn update_file(path: &Path, change: impl FnOnce(&mut Config)) -> io::Result<(Config, Change)> {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let lock = lock_file(path)?;
+
+    let old = Config::load_from(path);
+    let mut config = old.clone();
+    change(&mut config);
+    config.save_to(path)?;
+    cache(path, modified(path), config.clone());
+
+    drop(lock);
+    let changed = Change::between(&old, &config);
+    Ok((config, changed))
+}
+
+/// Take the lock file next to the config file at `path`. The lock is held
+/// until the file is closed.
+fn lock_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("lock"))?;
+    file.lock()?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogLevel;
+
+    fn temp_config(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flowstt-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("config.json")
+    }
+
+    #[test]
+    fn keys_read_and_write_their_field() {
+        let mut config = Config::default_with_hotkeys();
+        *key::LOG_LEVEL.get_mut(&mut config) = LogLevel::Debug;
+        assert_eq!(config.log_level, LogLevel::Debug);
+        assert_eq!(*key::LOG_LEVEL.get(&config), LogLevel::Debug);
+        assert_eq!(key::LOG_LEVEL.name(), "log_level");
+    }
+
+    #[test]
+    fn every_key_names_a_config_field() {
+        let value = serde_json::to_value(Config::default_with_hotkeys()).unwrap();
+        let fields = value.as_object().unwrap();
+        for name in key::ALL {
+            assert!(fields.contains_key(*name), "{} is not a config field", name);
+        }
+        assert_eq!(fields.len(), key::ALL.len());
+    }
+
+    #[test]
+    fn updates_keep_changes_saved_in_between() {
+        let path = temp_config("interleaved");
+        update_file(&path, |config| config.always_on_top = true).unwrap();
+        update_file(&path, |config| config.auto_paste_delay_ms = 250).unwrap();
+        let (saved, changed) = update_file(&path, |config| {
+            *key::LOG_LEVEL.get_mut(config) = LogLevel::Trace;
+        })
+        .unwrap();
+
+        assert!(saved.always_on_top);
+        assert_eq!(saved.auto_paste_delay_ms, 250);
+        assert_eq!(saved.log_level, LogLevel::Trace);
+        assert_eq!(Config::load_from(&path).log_level, LogLevel::Trace);
+        assert_eq!(changed.keys(), ["log_level"]);
+        assert!(changed.contains(key::LOG_LEVEL));
+        assert!(!changed.contains(key::ALWAYS_ON_TOP));
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn current_config_follows_saves_and_edits() {
+        let path = temp_config("current");
+        assert_eq!(current_from(&path).log_level, LogLevel::default());
+
+        update_file(&path, |config| config.log_level = LogLevel::Trace).unwrap();
+        let config = current_from(&path);
+        assert_eq!(config.log_level, LogLevel::Trace);
+        // Unchanged files aren't read again
+        assert!(Arc::ptr_eq(&config, &current_from(&path)));
+
+        // A write by another process shows up once the file's time changes
+        let mut edited = Config::load_from(&path);
+        edited.log_level = LogLevel::Debug;
+        let modified = modified(&path).unwrap();
+        edited.save_to(&path).unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(current_from(&path).log_level, LogLevel::Debug);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn unchanged_settings_report_no_change() {
+        let path = temp_config("unchanged");
+        let (_, changed) = update_file(&path, |_| {}).unwrap();
+        assert!(changed.is_empty());
+        assert!(path.exists());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -5,6 +5,9 @@ use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
+use crate::i18n::Language;
+use crate::keys::LetterLayout;
+
 /// Audio source type for capture.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -27,6 +30,8 @@ pub enum RecordingMode {
     Mixed,
     /// Echo cancellation mode - output only echo-cancelled primary source
     EchoCancel,
+    /// Keep sources apart - primary source on the left channel, secondary on the right
+    Split,
 }
 
 /// Transcription mode - determines how speech segment boundaries are identified.
@@ -40,6 +45,30 @@ pub enum TranscriptionMode {
     PushToTalk,
 }
 
+/// What switched between automatic and push-to-talk mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModeToggleSource {
+    /// The toggle hotkey
+    Hotkey,
+    /// A `ToggleAutoMode` request from an IPC client
+    Client,
+}
+
+/// Phase of a graceful shutdown, reported through `ShutdownProgress` events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownPhase {
+    /// Capture is stopping; no new audio is accepted
+    StoppingCapture,
+    /// Waiting for queued segments to finish transcribing
+    DrainingQueue,
+    /// Writing history to disk
+    FlushingHistory,
+    /// All work is finished and the service is about to exit
+    Complete,
+}
+
 /// Runtime mode - determines behavior for service lifecycle management.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -465,6 +494,13 @@ pub struct ConfigValues {
     /// Delay in milliseconds between clipboard write and paste simulation
     #[serde(default = "default_auto_paste_delay_ms")]
     pub auto_paste_delay_ms: u32,
+    /// Whether FlowSTT starts at login; `None` when the service isn't
+    /// running to check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autostart_enabled: Option<bool>,
+    /// Language of user-facing strings, as set in the config file
+    #[serde(default)]
+    pub language: Language,
 }
 
 fn default_auto_paste_enabled() -> bool {
@@ -499,6 +535,13 @@ pub struct PttStatus {
     /// Always true on non-macOS platforms (permission not applicable).
     #[serde(default = "default_true")]
     pub accessibility_permission_granted: bool,
+    /// Active keyboard layout as the platform names it (XKB layout, Windows
+    /// KLID or macOS input source ID), if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyboard_layout: Option<String>,
+    /// Letter arrangement of the active keyboard layout
+    #[serde(default)]
+    pub letter_layout: LetterLayout,
 }
 
 fn default_true() -> bool {
@@ -515,6 +558,22 @@ pub struct AudioDevice {
     /// Type of audio source
     #[serde(default)]
     pub source_type: AudioSourceType,
+    /// Native sample rate in Hz, if the backend reports it
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+}
+
+/// A pending attempt to start capture again after it failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureRetry {
+    /// Attempt about to be made (1-based)
+    pub attempt: u32,
+    /// Attempts made before giving up
+    pub max_attempts: u32,
+    /// Wait before this attempt, in milliseconds
+    pub delay_ms: u64,
+    /// Why the previous attempt failed
+    pub last_error: String,
 }
 
 /// Status of the transcription system.
@@ -537,6 +596,188 @@ pub struct TranscribeStatus {
     pub source2_id: Option<String>,
     /// Current transcription mode
     pub transcription_mode: TranscriptionMode,
+    /// Whether Automatic mode capture was suspended after a period without speech
+    #[serde(default)]
+    pub auto_suspended: bool,
+    /// Whether privacy mode is on (no recordings or history written to disk)
+    #[serde(default)]
+    pub privacy_mode: bool,
+    /// Whether output was paused by saying a pause phrase ("stop dictation")
+    #[serde(default)]
+    pub output_paused: bool,
+    /// Samples dropped on the audio path because processing fell behind
+    #[serde(default)]
+    pub dropped_samples: u64,
+    /// Overruns and underruns reported by the platform audio backend
+    #[serde(default)]
+    pub capture_glitches: CaptureGlitches,
+    /// Times the transcription engine was restarted after crashing
+    #[serde(default)]
+    pub worker_restarts: u64,
+    /// Retry in progress after capture failed to start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_retry: Option<CaptureRetry>,
+}
+
+/// Glitches reported by the platform audio backend since the engine started,
+/// which point at the audio path rather than the model when quality drops.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureGlitches {
+    /// Backend that reported them ("pipewire", "wasapi" or "coreaudio")
+    pub backend: String,
+    /// Audio was lost or corrupted because the device delivered it faster
+    /// than it was read (PipeWire xruns, WASAPI data discontinuities)
+    pub overruns: u64,
+    /// The device had no audio ready when the backend asked for it
+    pub underruns: u64,
+    /// Frames lost to overruns and underruns, where the backend can tell
+    pub dropped_frames: u64,
+}
+
+impl CaptureGlitches {
+    /// Whether any glitch was reported.
+    pub fn any(&self) -> bool {
+        self.overruns > 0 || self.underruns > 0 || self.dropped_frames > 0
+    }
+}
+
+/// Result of a short audio self-test recording on a single input device.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioSelfTestReport {
+    /// The device that was tested
+    pub device_id: String,
+    /// Length of audio actually captured, in milliseconds
+    pub duration_ms: u64,
+    /// Overall RMS level in dB (0 dB is full scale)
+    pub rms_db: f32,
+    /// Peak sample level in dB
+    pub peak_db: f32,
+    /// Estimated noise floor in dB (quietest windows of the recording)
+    pub noise_floor_db: f32,
+    /// Fraction of samples at or near full scale (0.0-1.0)
+    pub clipped_ratio: f32,
+    /// Whether the device appears dead (no audio or digital silence)
+    pub is_dead: bool,
+    /// Whether the device is clipping
+    pub is_clipping: bool,
+    /// Human-readable warnings about detected problems
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl AudioSelfTestReport {
+    /// Whether the test found no problems with the device.
+    pub fn is_ok(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// How a single file in a test mode run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFileOutcome {
+    /// A transcription was received
+    Transcribed,
+    /// The segment was submitted but Whisper found no speech
+    NoSpeech,
+    /// No result arrived before the timeout
+    Timeout,
+    /// The file could not be played
+    Skipped,
+}
+
+/// Result for one WAV file in a test mode run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestFileResult {
+    /// WAV file name
+    pub file: String,
+    /// How the file's run ended
+    pub outcome: TestFileOutcome,
+    /// Expected transcript from the manifest, if the file is listed
+    #[serde(default)]
+    pub expected: Option<String>,
+    /// Transcribed text, if any
+    #[serde(default)]
+    pub actual: Option<String>,
+    /// Word error rate against the expected transcript (0.0 is a perfect match)
+    #[serde(default)]
+    pub wer: Option<f64>,
+    /// Character error rate against the expected transcript
+    #[serde(default)]
+    pub cer: Option<f64>,
+}
+
+/// Totals for a test mode run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestRunSummary {
+    /// Files in the run
+    pub total: usize,
+    /// Files that produced a transcription
+    pub transcribed: usize,
+    /// Files where Whisper found no speech
+    pub no_speech: usize,
+    /// Files with no result before the timeout
+    pub timed_out: usize,
+    /// Files that could not be played
+    pub skipped: usize,
+    /// Files with an expected transcript
+    pub scored: usize,
+    /// Mean word error rate over scored files
+    #[serde(default)]
+    pub mean_wer: Option<f64>,
+    /// Mean character error rate over scored files
+    #[serde(default)]
+    pub mean_cer: Option<f64>,
+}
+
+impl TestRunSummary {
+    /// Tally per-file results.
+    pub fn from_results(results: &[TestFileResult]) -> Self {
+        let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+        let mean = |scores: Vec<f64>| {
+            (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64)
+        };
+        let wers: Vec<f64> = results.iter().filter_map(|r| r.wer).collect();
+        let cers: Vec<f64> = results.iter().filter_map(|r| r.cer).collect();
+
+        Self {
+            total: results.len(),
+            transcribed: count(TestFileOutcome::Transcribed),
+            no_speech: count(TestFileOutcome::NoSpeech),
+            timed_out: count(TestFileOutcome::Timeout),
+            skipped: count(TestFileOutcome::Skipped),
+            scored: wers.len(),
+            mean_wer: mean(wers),
+            mean_cer: mean(cers),
+        }
+    }
+}
+
+/// Most runs a latency benchmark may make
+pub const MAX_LATENCY_RUNS: u32 = 100;
+
+/// End-to-end latency measured by a latency benchmark.
+///
+/// Each run plays the audio through the engine in real time and measures
+/// from the end of the speech in the audio to the transcription event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyReport {
+    /// The WAV file that was played, or `None` for the built-in tone and
+    /// speech-like sample
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Length of the audio played each run, in milliseconds
+    pub audio_ms: u64,
+    /// Latency of each run that produced a transcription event, in milliseconds
+    pub latencies_ms: Vec<u64>,
+    /// Runs with no transcription event before the timeout
+    pub failed: u32,
+    /// Median latency in milliseconds
+    pub p50_ms: u64,
+    /// 95th percentile latency in milliseconds
+    pub p95_ms: u64,
+    /// Longest latency in milliseconds
+    pub max_ms: u64,
 }
 
 /// Status of the Whisper model.
@@ -559,6 +800,294 @@ pub struct CudaStatus {
     pub system_info: String,
 }
 
+/// Whether a platform feature works in the current session.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Capability {
+    /// Whether the feature can be used
+    pub available: bool,
+    /// Why the feature can't be used (e.g. a missing tool or permission)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Capability {
+    /// A feature that works.
+    pub fn supported() -> Self {
+        Self {
+            available: true,
+            reason: None,
+        }
+    }
+
+    /// A feature that doesn't work, and why.
+    pub fn unsupported(reason: impl Into<String>) -> Self {
+        Self {
+            available: false,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Platform features available to clients on this machine and session.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Capturing system audio (monitor or loopback sources)
+    pub system_audio: Capability,
+    /// Acoustic echo cancellation, which needs system audio as its reference
+    pub aec: Capability,
+    /// Global push-to-talk and toggle hotkeys
+    pub hotkeys: Capability,
+    /// Writing the system clipboard (absent from older services)
+    #[serde(default)]
+    pub clipboard: Capability,
+    /// Simulating a paste keystroke into the foreground application
+    pub paste_simulation: Capability,
+}
+
+/// A permission the operating system must grant before a feature works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionKind {
+    /// Recording from microphones
+    Microphone,
+    /// Simulating keystrokes and watching the foreground application (macOS)
+    Accessibility,
+    /// Observing keyboard input for global hotkeys (macOS)
+    InputMonitoring,
+}
+
+impl PermissionKind {
+    /// Every permission kind, in display order.
+    pub const ALL: [PermissionKind; 3] = [
+        PermissionKind::Microphone,
+        PermissionKind::Accessibility,
+        PermissionKind::InputMonitoring,
+    ];
+}
+
+/// Where a permission stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    /// The permission has been granted
+    Granted,
+    /// The user or a policy refused the permission
+    Denied,
+    /// The user hasn't been asked yet
+    NotDetermined,
+    /// This platform doesn't gate the feature behind a permission
+    NotRequired,
+}
+
+impl PermissionState {
+    /// Whether the feature the permission guards can be used.
+    pub fn allows(self) -> bool {
+        matches!(
+            self,
+            PermissionState::Granted | PermissionState::NotRequired
+        )
+    }
+}
+
+/// Where each permission FlowSTT needs stands for the service process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionsStatus {
+    /// Recording from microphones
+    pub microphone: PermissionState,
+    /// Simulating keystrokes and watching the foreground application
+    pub accessibility: PermissionState,
+    /// Observing keyboard input for global hotkeys
+    pub input_monitoring: PermissionState,
+}
+
+impl PermissionsStatus {
+    /// The state of one permission.
+    pub fn get(&self, kind: PermissionKind) -> PermissionState {
+        match kind {
+            PermissionKind::Microphone => self.microphone,
+            PermissionKind::Accessibility => self.accessibility,
+            PermissionKind::InputMonitoring => self.input_monitoring,
+        }
+    }
+}
+
+/// A long-running engine thread watched for hangs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineComponent {
+    /// Speech detection on captured audio in Automatic mode
+    AudioLoop,
+    /// The platform hotkey listener
+    Hotkeys,
+    /// Push-to-talk and mode hotkey handling
+    PttController,
+    /// The thread transcribing queued segments
+    TranscriptionWorker,
+}
+
+impl EngineComponent {
+    /// Every component.
+    pub const ALL: [EngineComponent; 4] = [
+        EngineComponent::AudioLoop,
+        EngineComponent::Hotkeys,
+        EngineComponent::PttController,
+        EngineComponent::TranscriptionWorker,
+    ];
+
+    /// Name for messages.
+    pub fn label(self) -> &'static str {
+        match self {
+            EngineComponent::AudioLoop => "audio loop",
+            EngineComponent::Hotkeys => "hotkey listener",
+            EngineComponent::PttController => "push-to-talk controller",
+            EngineComponent::TranscriptionWorker => "transcription worker",
+        }
+    }
+}
+
+/// Version and build details of the running service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// FlowSTT version
+    pub version: String,
+    /// Commit the service was built from, if it was built from a git checkout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_hash: Option<String>,
+    /// Day the service was built (`YYYY-MM-DD`, UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_date: Option<String>,
+    /// Version of the bundled whisper.cpp
+    pub whisper_version: String,
+    /// Optional acceleration built in (`cuda`, `metal`)
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// IPC protocol version (see `ipc::PROTOCOL_VERSION`)
+    pub protocol_version: u32,
+}
+
+/// Result of checking the release feed for a newer FlowSTT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    /// Version running now
+    pub current_version: String,
+    /// Newest released version
+    pub latest_version: String,
+    /// Whether the newest release is newer than the running one
+    pub available: bool,
+    /// Release notes of the newest release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_url: Option<String>,
+    /// Installer downloaded for the newest release, ready to run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staged_path: Option<String>,
+}
+
+/// Longest stretch of recent audio the engine keeps, in seconds
+pub const MAX_RECENT_AUDIO_SECS: u32 = 60;
+
+/// The last stretch of captured audio, for scrolling back through the
+/// waveform and replaying what was said.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentAudioWindow {
+    /// Mono WAV file holding the audio; the next request replaces it
+    pub wav_path: String,
+    /// Length of the audio in milliseconds
+    pub duration_ms: u64,
+    /// Peak amplitude of each waveform column, oldest first
+    pub peaks: Vec<f32>,
+    /// Whether speech was detected in each waveform column
+    pub speech: Vec<bool>,
+}
+
+/// Span of time an activity summary covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityPeriod {
+    /// Since midnight, broken down by hour
+    #[default]
+    Today,
+    /// The last 7 days including today, broken down by day
+    Week,
+    /// The last 30 days including today, broken down by day
+    Month,
+    /// Everything in the activity log, broken down by day
+    All,
+}
+
+/// Speech and dictation totals from the activity log.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivitySummary {
+    /// Span of time summarized
+    pub period: ActivityPeriod,
+    /// Total time spent speaking in milliseconds
+    pub speech_ms: u64,
+    /// Number of utterances (speech detections or push-to-talk presses)
+    pub utterances: u32,
+    /// Number of transcriptions
+    pub transcriptions: u32,
+    /// Number of words transcribed
+    pub words: u64,
+    /// Breakdown by hour or day, oldest first; intervals without activity are
+    /// left out
+    pub intervals: Vec<ActivityInterval>,
+}
+
+/// Activity within one hour or day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityInterval {
+    /// Local start of the interval, ISO 8601 (`2024-05-01T09:00` for an hour,
+    /// `2024-05-01` for a day)
+    pub start: String,
+    /// Time spent speaking in milliseconds
+    pub speech_ms: u64,
+    /// Number of words transcribed
+    pub words: u64,
+}
+
+/// Length of the buckets in a history summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryGrouping {
+    /// One bucket per local calendar day
+    #[default]
+    Day,
+    /// One bucket per local hour
+    Hour,
+}
+
+/// Transcription history totalled by day or hour.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistorySummary {
+    /// Length of the buckets
+    pub group_by: HistoryGrouping,
+    /// Totals by bucket, oldest first; buckets without entries are left out
+    pub buckets: Vec<HistoryBucket>,
+}
+
+/// History entries within one day or hour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryBucket {
+    /// Local start of the bucket, ISO 8601 (`2024-05-01T09:00` for an hour,
+    /// `2024-05-01` for a day)
+    pub start: String,
+    /// Number of history entries
+    pub entries: u32,
+    /// Number of words in the entries' current text
+    pub words: u64,
+    /// Total length of the entries' audio in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Most samples in one `StreamAudio` request, which keeps it well under the
+/// IPC message size limit
+pub const MAX_STREAM_CHUNK_SAMPLES: usize = 8192;
+
+/// Lowest and highest sample rates accepted for a client audio stream
+pub const STREAM_SAMPLE_RATES: std::ops::RangeInclusive<u32> = 8000..=192_000;
+
+/// Most channels accepted for a client audio stream
+pub const MAX_STREAM_CHANNELS: u16 = 8;
+
 /// A single column of spectrogram data ready for rendering.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpectrogramColumn {
@@ -609,11 +1138,117 @@ pub struct HistoryEntry {
     pub id: String,
     /// Transcribed text
     pub text: String,
-    /// ISO 8601 timestamp of when the transcription occurred
+    /// RFC 3339 UTC timestamp of when the transcription occurred; clients
+    /// show it in local time
     pub timestamp: String,
     /// Path to the cached WAV file, if it still exists
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wav_path: Option<String>,
+    /// Size of the cached WAV file in bytes, while it exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_bytes: Option<u64>,
+    /// Length of the transcribed audio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Mode the audio was captured in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TranscriptionMode>,
+    /// Model file the entry was first transcribed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_used: Option<String>,
+    /// The first transcript, kept once the text has been replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_text: Option<String>,
+    /// Model file the current text was re-transcribed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The current text was corrected by hand
+    #[serde(default)]
+    pub edited: bool,
+}
+
+/// Which side of a meeting a line was heard from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingSource {
+    /// The primary source (the local microphone)
+    Microphone,
+    /// The secondary source (system audio from the call)
+    System,
+}
+
+impl MeetingSource {
+    /// Label shown in the timeline and exported minutes.
+    pub fn label(self) -> &'static str {
+        match self {
+            MeetingSource::Microphone => "Me",
+            MeetingSource::System => "Others",
+        }
+    }
+}
+
+/// One transcribed line of a meeting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingLine {
+    /// Source the speech was captured from
+    pub source: MeetingSource,
+    /// When the speech started, in milliseconds from the start of the meeting
+    pub offset_ms: u64,
+    /// Transcribed text
+    pub text: String,
+}
+
+/// A meeting transcribed in meeting mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingRecord {
+    /// Unique identifier for this meeting
+    pub id: String,
+    /// Title given when the meeting was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// ISO 8601 timestamp of when the meeting started
+    pub started_at: String,
+    /// ISO 8601 timestamp of when the meeting ended (absent while in progress)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    /// Transcribed lines from both sources, ordered by offset
+    #[serde(default)]
+    pub lines: Vec<MeetingLine>,
+}
+
+impl MeetingRecord {
+    /// Render the meeting as Markdown minutes.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title.as_deref().unwrap_or("Meeting"));
+        out.push_str(&format!("- Started: {}\n", self.started_at));
+        if let Some(ended_at) = &self.ended_at {
+            out.push_str(&format!("- Ended: {}\n", ended_at));
+        }
+        out.push_str("\n## Transcript\n\n");
+        if self.lines.is_empty() {
+            out.push_str("_No speech was transcribed._\n");
+        }
+        for line in &self.lines {
+            out.push_str(&format!(
+                "- **[{}] {}:** {}\n",
+                format_offset(line.offset_ms),
+                line.source.label(),
+                line.text.trim()
+            ));
+        }
+        out
+    }
+}
+
+/// Format a meeting offset as `m:ss`, or `h:mm:ss` past the first hour.
+fn format_offset(offset_ms: u64) -> String {
+    let secs = offset_ms / 1000;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
 }
 
 /// Transcription result for a speech segment.
@@ -0,0 +1,95 @@
+//! Throughput benchmarks for the DSP processors.
+//!
+//! Run with `cargo bench -p flowstt-dsp`. Each benchmark processes ten
+//! seconds of 48kHz audio in 10ms chunks and reports the time per chunk and
+//! how many times faster than real time that is.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use flowstt_dsp::{
+    normalize, Normalization, SpeechDetector, StreamResampler, VisualizationConfig,
+    VisualizationProcessor,
+};
+
+const RATE: u32 = 48000;
+const CHUNK: usize = 480;
+const CHUNKS: usize = 1000;
+
+/// Ten seconds of a speech-like tone with some noise
+fn signal() -> Vec<f32> {
+    let mut state = 0x1234_5678u32;
+    (0..CHUNK * CHUNKS)
+        .map(|i| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+            let t = i as f32 / RATE as f32;
+            0.3 * (2.0 * std::f32::consts::PI * 220.0 * t).sin() + 0.02 * noise
+        })
+        .collect()
+}
+
+fn bench(name: &str, audio_secs: f64, mut run: impl FnMut()) {
+    // Warm up, then take the best of several runs
+    run();
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or(Duration::ZERO);
+
+    let per_chunk = best / CHUNKS as u32;
+    let realtime = audio_secs / best.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{:<28} {:>10.2?} total {:>10.2?}/chunk {:>8.0}x real time",
+        name, best, per_chunk, realtime
+    );
+}
+
+fn main() {
+    let audio = signal();
+    let audio_secs = audio.len() as f64 / RATE as f64;
+
+    bench("speech detection", audio_secs, || {
+        let mut detector = SpeechDetector::new(RATE);
+        for chunk in audio.chunks(CHUNK) {
+            detector.process(black_box(chunk));
+            black_box(detector.take_state_change());
+        }
+    });
+
+    bench("visualization (512 fft)", audio_secs, || {
+        let mut processor = VisualizationProcessor::new(RATE, &VisualizationConfig::default());
+        for chunk in audio.chunks(CHUNK) {
+            processor.process(black_box(chunk));
+        }
+    });
+
+    bench("visualization (4096 fft)", audio_secs, || {
+        let config = VisualizationConfig {
+            fft_size: 4096,
+            output_height: 1024,
+            ..Default::default()
+        };
+        let mut processor = VisualizationProcessor::new(RATE, &config);
+        for chunk in audio.chunks(CHUNK) {
+            processor.process(black_box(chunk));
+        }
+    });
+
+    bench("resample 44.1kHz -> 48kHz", audio_secs, || {
+        let mut resampler = StreamResampler::new(44100, RATE, 1).unwrap();
+        for chunk in audio.chunks(CHUNK) {
+            black_box(resampler.process(black_box(chunk)));
+        }
+    });
+
+    bench("loudness normalization", audio_secs, || {
+        let mut samples = audio.clone();
+        normalize(black_box(&mut samples), Normalization::Loudness);
+        black_box(samples);
+    });
+}
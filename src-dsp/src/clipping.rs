@@ -0,0 +1,85 @@
+//! Clipping detection.
+
+/// Samples at or above this magnitude count as clipped
+const CLIPPING_LEVEL: f32 = 0.99;
+
+/// Clipped samples per second of audio that trigger a warning
+const CLIPPED_SAMPLES_PER_SECOND: u32 = 32;
+
+/// Seconds of audio between repeated clipping warnings
+const CLIPPING_WARNING_INTERVAL_SECS: u32 = 30;
+
+/// Counts samples at or near full scale, one second of audio at a time.
+pub struct ClippingDetector {
+    /// Sample rate of the analyzed audio
+    sample_rate: u32,
+    /// Frames analyzed in the current one-second window
+    window_frames: u32,
+    /// Clipped samples seen in the current window
+    clipped: u32,
+    /// Seconds left before another warning may be reported
+    cooldown_secs: u32,
+}
+
+impl ClippingDetector {
+    /// Create a detector for audio at `sample_rate`
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            window_frames: 0,
+            clipped: 0,
+            cooldown_secs: 0,
+        }
+    }
+
+    /// Analyze interleaved samples. Returns the number of clipped samples in
+    /// the last second when it crosses the warning threshold.
+    pub fn process(&mut self, samples: &[f32], channels: u16) -> Option<u32> {
+        let channels = channels.max(1) as usize;
+        let mut warning = None;
+
+        for frame in samples.chunks(channels) {
+            self.clipped += frame.iter().filter(|s| s.abs() >= CLIPPING_LEVEL).count() as u32;
+            self.window_frames += 1;
+
+            if self.window_frames >= self.sample_rate {
+                if self.cooldown_secs > 0 {
+                    self.cooldown_secs -= 1;
+                } else if self.clipped >= CLIPPED_SAMPLES_PER_SECOND {
+                    warning = Some(self.clipped);
+                    self.cooldown_secs = CLIPPING_WARNING_INTERVAL_SECS;
+                }
+                self.window_frames = 0;
+                self.clipped = 0;
+            }
+        }
+
+        warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_once_per_interval() {
+        let mut detector = ClippingDetector::new(1000);
+        let clipped = vec![1.0; 1000];
+
+        assert_eq!(detector.process(&clipped, 1), Some(1000));
+        // Still clipping, but within the cooldown
+        for _ in 0..CLIPPING_WARNING_INTERVAL_SECS {
+            assert_eq!(detector.process(&clipped, 1), None);
+        }
+        assert_eq!(detector.process(&clipped, 1), Some(1000));
+    }
+
+    #[test]
+    fn ignores_occasional_peaks() {
+        let mut detector = ClippingDetector::new(1000);
+        let mut samples = vec![0.5; 2000];
+        samples[10] = 1.0;
+        assert_eq!(detector.process(&samples, 2), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! Gain control: fixed gain, level normalization and level measurement.
+
+/// Peak level audio is normalized to (-1 dBFS)
+pub const NORMALIZED_PEAK: f32 = 0.891;
+
+/// RMS level [`Normalization::Loudness`] aims for (-20 dBFS)
+pub const TARGET_RMS: f32 = 0.1;
+
+/// Largest gain normalization applies (+30 dB), so near-silent audio isn't
+/// raised to the level of speech
+pub const MAX_NORMALIZATION_GAIN: f32 = 31.6;
+
+/// Lowest level [`amplitude_to_db`] reports, for silence (-96 dBFS)
+pub const SILENCE_DB: f32 = -96.0;
+
+/// Frame length for the loudness measurement (20ms at 16kHz and below)
+const LOUDNESS_FRAME: usize = 320;
+
+/// How [`normalize`] levels a block of audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Leave the level unchanged
+    Off,
+    /// Scale so the peak sits at [`NORMALIZED_PEAK`]
+    Peak,
+    /// Scale toward [`TARGET_RMS`], limited by the peak
+    Loudness,
+}
+
+/// Convert a gain in dB to a linear factor. Returns None for unity gain.
+pub fn db_to_gain(gain_db: f32) -> Option<f32> {
+    if gain_db == 0.0 {
+        None
+    } else {
+        Some(10.0f32.powf(gain_db / 20.0))
+    }
+}
+
+/// RMS amplitude of samples, 0.0 for none.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f32 = samples.iter().map(|s| s * s).sum();
+    (sum_sq / samples.len() as f32).sqrt()
+}
+
+/// Convert a linear amplitude to dBFS, floored at [`SILENCE_DB`].
+pub fn amplitude_to_db(amplitude: f32) -> f32 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+/// Apply a linear gain to samples in place, clamping to full scale.
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    for s in samples.iter_mut() {
+        *s = (*s * gain).clamp(-1.0, 1.0);
+    }
+}
+
+/// Scale mono samples to a consistent level.
+///
+/// Loudness is measured as the RMS of the louder half of 20ms frames, so the
+/// pauses around speech don't count, and the gain is limited so the peak stays
+/// at [`NORMALIZED_PEAK`] and never exceeds [`MAX_NORMALIZATION_GAIN`].
+pub fn normalize(samples: &mut [f32], mode: Normalization) {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak <= f32::EPSILON {
+        return;
+    }
+    let peak_gain = NORMALIZED_PEAK / peak;
+
+    let gain = match mode {
+        Normalization::Off => return,
+        Normalization::Peak => peak_gain,
+        Normalization::Loudness => {
+            let mut frames: Vec<f32> = samples
+                .chunks(LOUDNESS_FRAME)
+                .map(|frame| frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32)
+                .collect();
+            frames.sort_by(|a, b| b.total_cmp(a));
+            let loud = &frames[..frames.len().div_ceil(2)];
+            let rms = (loud.iter().sum::<f32>() / loud.len() as f32).sqrt();
+            if rms <= f32::EPSILON {
+                return;
+            }
+            (TARGET_RMS / rms).min(peak_gain)
+        }
+    };
+
+    let gain = gain.min(MAX_NORMALIZATION_GAIN);
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |p, s| p.max(s.abs()))
+    }
+
+    #[test]
+    fn levels_are_measured_in_dbfs() {
+        assert_eq!(rms(&[]), 0.0);
+        assert!((rms(&[0.5, -0.5, 0.5, -0.5]) - 0.5).abs() < 1e-6);
+        assert!(amplitude_to_db(1.0).abs() < 1e-6);
+        assert!((amplitude_to_db(TARGET_RMS) + 20.0).abs() < 1e-4);
+        assert_eq!(amplitude_to_db(0.0), SILENCE_DB);
+        assert_eq!(amplitude_to_db(1e-9), SILENCE_DB);
+    }
+
+    #[test]
+    fn unity_gain_is_none() {
+        assert_eq!(db_to_gain(0.0), None);
+        assert!((db_to_gain(6.0).unwrap() - 1.995).abs() < 0.01);
+    }
+
+    #[test]
+    fn apply_gain_clamps_to_full_scale() {
+        let mut samples = [0.4, -0.6, 0.1];
+        apply_gain(&mut samples, 2.0);
+        assert_eq!(samples, [0.8, -1.0, 0.2]);
+    }
+
+    #[test]
+    fn peak_normalization_hits_target_peak() {
+        let mut samples = vec![0.1, -0.2, 0.05];
+        normalize(&mut samples, Normalization::Peak);
+        assert!((peak(&samples) - NORMALIZED_PEAK).abs() < 1e-5);
+    }
+
+    #[test]
+    fn loudness_normalization_is_limited_by_peak() {
+        // A single spike in quiet audio must not clip
+        let mut samples = vec![0.01; 3200];
+        samples[100] = 0.5;
+        normalize(&mut samples, Normalization::Loudness);
+        assert!(peak(&samples) <= NORMALIZED_PEAK + 1e-5);
+    }
+
+    #[test]
+    fn silence_and_off_are_untouched() {
+        let mut silence = vec![0.0; 100];
+        normalize(&mut silence, Normalization::Peak);
+        assert!(silence.iter().all(|&s| s == 0.0));
+
+        let mut samples = vec![0.1, -0.2];
+        normalize(&mut samples, Normalization::Off);
+        assert_eq!(samples, [0.1, -0.2]);
+    }
+}
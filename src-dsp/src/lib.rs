@@ -0,0 +1,64 @@
+//! Audio signal processing for FlowSTT.
+//!
+//! This crate holds the DSP used by the FlowSTT engine, with no dependency on
+//! audio backends, configuration or IPC:
+//!
+//! - [`speech`]: voice activity detection with lookback and word breaks
+//! - [`visualization`]: waveform downsampling and FFT spectrogram columns
+//! - [`resample`]: streaming and one-shot sample-rate conversion
+//! - [`gain`]: fixed gain, peak/loudness normalization and level measurement
+//! - [`clipping`]: detection of clipped input
+//! - [`speaker`]: voiceprints for telling speakers apart
+//!
+//! All processors work on `f32` samples in the range -1.0 to 1.0.
+//!
+//! ```
+//! use flowstt_dsp::{SpeechDetector, SpeechStateChange};
+//!
+//! let mut detector = SpeechDetector::new(48000);
+//! detector.process(&[0.0; 480]);
+//! assert!(matches!(detector.take_state_change(), SpeechStateChange::None));
+//! ```
+
+pub mod clipping;
+pub mod gain;
+pub mod resample;
+pub mod speaker;
+pub mod speech;
+pub mod visualization;
+
+pub use clipping::ClippingDetector;
+pub use gain::{
+    amplitude_to_db, apply_gain, db_to_gain, normalize, rms, Normalization, SILENCE_DB,
+};
+pub use resample::{resample_linear, StreamResampler};
+pub use speech::{
+    SpeechDetector, SpeechEventCallback, SpeechEventPayload, SpeechMetrics, SpeechStateChange,
+    WordBreakEvent, WordBreakPayload, DEFAULT_VOICED_THRESHOLD_DB, DEFAULT_WHISPER_THRESHOLD_DB,
+};
+pub use visualization::{
+    ColorStop, SpectrogramColumn, VisualizationCallback, VisualizationConfig, VisualizationPayload,
+    VisualizationProcessor,
+};
+
+/// Convert interleaved multi-channel audio to mono by averaging channels
+pub fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(channels)
+        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mono_conversion_averages_frames() {
+        assert_eq!(convert_to_mono(&[0.2, 0.4, -1.0, 1.0], 2), [0.3, 0.0]);
+        assert_eq!(convert_to_mono(&[0.5, 0.25], 1), [0.5, 0.25]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Sample-rate conversion.
+//!
+//! [`StreamResampler`] is a band-limited FFT resampler for continuous
+//! interleaved streams; [`resample_linear`] is a cheap one-shot linear
+//! interpolator, good enough for speech headed to a recognizer.
+
+use rubato::{FftFixedIn, Resampler};
+use tracing::{info, warn};
+
+/// Frames fed to the resampler per call (~21ms at 48kHz)
+const CHUNK_FRAMES: usize = 1024;
+
+/// Resampler state for one stream of interleaved audio.
+///
+/// Input is buffered until a full block is available, so a call to
+/// [`StreamResampler::process`] may return no output.
+pub struct StreamResampler {
+    source_rate: u32,
+    target_rate: u32,
+    channels: u16,
+    resampler: FftFixedIn<f32>,
+    /// Per-channel input waiting for a full block
+    pending: Vec<Vec<f32>>,
+}
+
+impl StreamResampler {
+    /// Create a resampler from `source_rate` to `target_rate` for audio with
+    /// `channels` interleaved channels. Returns `None` if the conversion is
+    /// not supported.
+    pub fn new(source_rate: u32, target_rate: u32, channels: u16) -> Option<Self> {
+        if channels == 0 {
+            return None;
+        }
+        match FftFixedIn::new(
+            source_rate as usize,
+            target_rate as usize,
+            CHUNK_FRAMES,
+            2,
+            channels as usize,
+        ) {
+            Ok(resampler) => {
+                info!(
+                    "[Resample] Converting {}Hz {}-channel audio to {}Hz",
+                    source_rate, channels, target_rate
+                );
+                Some(Self {
+                    source_rate,
+                    target_rate,
+                    channels,
+                    resampler,
+                    pending: vec![Vec::new(); channels as usize],
+                })
+            }
+            Err(e) => {
+                warn!("[Resample] Cannot resample from {}Hz: {}", source_rate, e);
+                None
+            }
+        }
+    }
+
+    /// Input sample rate in Hz
+    pub fn source_rate(&self) -> u32 {
+        self.source_rate
+    }
+
+    /// Output sample rate in Hz
+    pub fn target_rate(&self) -> u32 {
+        self.target_rate
+    }
+
+    /// Number of interleaved channels
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Resample interleaved samples, returning interleaved output.
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let channels = self.channels as usize;
+        for frame in samples.chunks_exact(channels) {
+            for (pending, &sample) in self.pending.iter_mut().zip(frame) {
+                pending.push(sample);
+            }
+        }
+
+        let mut output = Vec::new();
+        loop {
+            let needed = self.resampler.input_frames_next();
+            if self.pending[0].len() < needed {
+                break;
+            }
+
+            let block: Vec<Vec<f32>> = self
+                .pending
+                .iter_mut()
+                .map(|ch| ch.drain(..needed).collect())
+                .collect();
+
+            match self.resampler.process(&block, None) {
+                Ok(resampled) => {
+                    let frames = resampled[0].len();
+                    output.reserve(frames * channels);
+                    for i in 0..frames {
+                        output.extend(resampled.iter().map(|ch| ch[i]));
+                    }
+                }
+                Err(e) => {
+                    warn!("[Resample] Resampling failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Resample mono audio from `source_rate` to `target_rate` using linear
+/// interpolation.
+pub fn resample_linear(samples: &[f32], source_rate: u32, target_rate: u32) -> Vec<f32> {
+    if source_rate == target_rate || source_rate == 0 || target_rate == 0 {
+        return samples.to_vec();
+    }
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let ratio = source_rate as f64 / target_rate as f64;
+    let output_len = (samples.len() as f64 / ratio).ceil() as usize;
+    let mut output = Vec::with_capacity(output_len);
+
+    for i in 0..output_len {
+        let src_pos = i as f64 * ratio;
+        let src_idx = src_pos.floor() as usize;
+        let frac = src_pos - src_idx as f64;
+
+        let sample = if src_idx + 1 < samples.len() {
+            // Linear interpolation between samples
+            samples[src_idx] * (1.0 - frac as f32) + samples[src_idx + 1] * frac as f32
+        } else if src_idx < samples.len() {
+            samples[src_idx]
+        } else {
+            0.0
+        };
+
+        output.push(sample);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_resample_scales_length() {
+        let input = vec![0.5; 48000];
+        let output = resample_linear(&input, 48000, 16000);
+        assert_eq!(output.len(), 16000);
+        assert!(output.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+
+        assert_eq!(resample_linear(&input, 16000, 16000).len(), 48000);
+        assert!(resample_linear(&[], 48000, 16000).is_empty());
+    }
+
+    #[test]
+    fn stream_resampler_buffers_until_block_is_full() {
+        let mut stream = StreamResampler::new(44100, 48000, 2).unwrap();
+        assert!(stream.process(&[0.0; 20]).is_empty());
+
+        // One second of stereo audio comes out at roughly the target rate
+        let output = stream.process(&vec![0.25; 44100 * 2]);
+        assert_eq!(output.len() % 2, 0);
+        let frames = output.len() / 2;
+        assert!(frames > 46000 && frames <= 48000, "got {} frames", frames);
+    }
+
+    #[test]
+    fn stream_resampler_rejects_zero_channels() {
+        assert!(StreamResampler::new(44100, 48000, 0).is_none());
+    }
+}
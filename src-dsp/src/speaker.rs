@@ -0,0 +1,244 @@
+//! Speaker voiceprints.
+//!
+//! A voiceprint is the average mel-frequency cepstrum of the voiced frames
+//! in a recording: a summary of the shape of the speaker's vocal tract that
+//! stays much the same whatever is said. Comparing the voiceprint of a
+//! segment with one recorded at enrollment tells the enrolled speaker apart
+//! from clearly different voices, such as a TV or someone across the room.
+//! It is not meant to separate similar voices.
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+/// Length of an analysis frame
+const FRAME_MS: u32 = 25;
+
+/// Step between analysis frames
+const HOP_MS: u32 = 10;
+
+/// Mel filters spanning `MIN_HZ` to `MAX_HZ`
+const MEL_BANDS: usize = 26;
+
+/// Lowest frequency analyzed
+const MIN_HZ: f32 = 100.0;
+
+/// Highest frequency analyzed (lowered to the Nyquist frequency if needed)
+const MAX_HZ: f32 = 7600.0;
+
+/// Frames quieter than this are silence
+const SILENCE_DB: f32 = -60.0;
+
+/// Frames more than this far below the loudest frame are background noise
+const DYNAMIC_RANGE_DB: f32 = 30.0;
+
+/// Fewest voiced frames (half a second) that give a usable voiceprint
+const MIN_VOICED_FRAMES: usize = 50;
+
+/// Number of values in a voiceprint: cepstral coefficients 1 to 20
+/// (coefficient 0, the overall level, is left out)
+pub const VOICEPRINT_LEN: usize = 20;
+
+/// Compute the voiceprint of mono audio. Returns `None` if there is less
+/// than half a second of voiced audio.
+pub fn voiceprint(samples: &[f32], sample_rate: u32) -> Option<Vec<f32>> {
+    let frame_len = (sample_rate * FRAME_MS / 1000) as usize;
+    let hop = (sample_rate * HOP_MS / 1000) as usize;
+    if frame_len == 0 || hop == 0 || samples.len() < frame_len {
+        return None;
+    }
+
+    // Keep the frames loud enough to be the speaker
+    let frames: Vec<(&[f32], f32)> = samples
+        .windows(frame_len)
+        .step_by(hop)
+        .map(|frame| (frame, level_db(frame)))
+        .collect();
+    let loudest = frames
+        .iter()
+        .map(|&(_, db)| db)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let floor = SILENCE_DB.max(loudest - DYNAMIC_RANGE_DB);
+    let voiced: Vec<&[f32]> = frames
+        .into_iter()
+        .filter(|&(_, db)| db > floor)
+        .map(|(frame, _)| frame)
+        .collect();
+    if voiced.len() < MIN_VOICED_FRAMES {
+        return None;
+    }
+
+    let fft_size = frame_len.next_power_of_two();
+    let fft = FftPlanner::new().plan_fft_forward(fft_size);
+    let window: Vec<f32> = (0..frame_len)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (frame_len - 1) as f32).cos())
+        .collect();
+    let filters = mel_filters(fft_size, sample_rate);
+
+    let mut sum = vec![0.0f32; VOICEPRINT_LEN];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); fft_size];
+    for frame in &voiced {
+        for (slot, (&sample, &w)) in buffer.iter_mut().zip(frame.iter().zip(&window)) {
+            *slot = Complex::new(sample * w, 0.0);
+        }
+        buffer[frame_len..].fill(Complex::new(0.0, 0.0));
+        fft.process(&mut buffer);
+
+        let log_energies: Vec<f32> = filters
+            .iter()
+            .map(|(start, weights)| {
+                let energy: f32 = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| w * buffer[start + i].norm_sqr())
+                    .sum();
+                energy.max(1e-10).ln()
+            })
+            .collect();
+        for (k, total) in sum.iter_mut().enumerate() {
+            *total += dct(&log_energies, k + 1);
+        }
+    }
+
+    let count = voiced.len() as f32;
+    Some(sum.into_iter().map(|total| total / count).collect())
+}
+
+/// Cosine similarity of two voiceprints, from -1.0 to 1.0 (1.0 = the same
+/// voice). Returns 0.0 for voiceprints of different lengths.
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms =
+        a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    if norms > 0.0 {
+        dot / norms
+    } else {
+        0.0
+    }
+}
+
+/// RMS level of a frame in dBFS
+fn level_db(frame: &[f32]) -> f32 {
+    let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+    20.0 * rms.max(1e-10).log10()
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Triangular mel filters over the FFT bins, each as its first bin and
+/// weights. Weights are scaled so every filter has the same area, which
+/// keeps the cepstrum independent of the FFT size.
+fn mel_filters(fft_size: usize, sample_rate: u32) -> Vec<(usize, Vec<f32>)> {
+    let max_hz = MAX_HZ.min(sample_rate as f32 / 2.0);
+    let (low, high) = (hz_to_mel(MIN_HZ), hz_to_mel(max_hz));
+    let edges: Vec<f32> = (0..MEL_BANDS + 2)
+        .map(|i| mel_to_hz(low + (high - low) * i as f32 / (MEL_BANDS + 1) as f32))
+        .map(|hz| hz * fft_size as f32 / sample_rate as f32)
+        .collect();
+
+    edges
+        .windows(3)
+        .map(|edge| {
+            let (left, center, right) = (edge[0], edge[1], edge[2]);
+            let start = left.ceil() as usize;
+            let end = (right.floor() as usize).max(start);
+            let mut weights: Vec<f32> = (start..=end)
+                .map(|bin| {
+                    let bin = bin as f32;
+                    if bin <= center {
+                        (bin - left) / (center - left).max(f32::EPSILON)
+                    } else {
+                        (right - bin) / (right - center).max(f32::EPSILON)
+                    }
+                    .max(0.0)
+                })
+                .collect();
+            let area: f32 = weights.iter().sum();
+            if area > 0.0 {
+                weights.iter_mut().for_each(|w| *w /= area);
+            }
+            (start, weights)
+        })
+        .collect()
+}
+
+/// Coefficient `k` of the DCT-II of the log mel energies
+fn dct(log_energies: &[f32], k: usize) -> f32 {
+    let bands = log_energies.len() as f32;
+    log_energies
+        .iter()
+        .enumerate()
+        .map(|(m, e)| e * (std::f32::consts::PI * k as f32 * (m as f32 + 0.5) / bands).cos())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vowel-like signal: harmonics of `pitch` shaped by two formants,
+    /// with a slow level change like syllables
+    fn voice(pitch: f32, formants: [f32; 2], secs: f32, rate: u32, level: f32) -> Vec<f32> {
+        let harmonics: Vec<(f32, f32)> = (1..)
+            .map(|k| pitch * k as f32)
+            .take_while(|&hz| hz < 7000.0)
+            .map(|hz| {
+                let gain: f32 = formants
+                    .iter()
+                    .map(|f| 1.0 / (1.0 + ((hz - f) / 150.0).powi(2)))
+                    .sum();
+                (hz, gain)
+            })
+            .collect();
+        (0..(secs * rate as f32) as usize)
+            .map(|i| {
+                let t = i as f32 / rate as f32;
+                let tone: f32 = harmonics
+                    .iter()
+                    .map(|(hz, gain)| gain * (2.0 * std::f32::consts::PI * hz * t).sin())
+                    .sum();
+                level * tone * (0.6 + 0.4 * (std::f32::consts::PI * 3.0 * t).sin().abs())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_voice_is_more_similar_than_another() {
+        let enrolled = voiceprint(&voice(130.0, [700.0, 1200.0], 2.0, 16000, 0.05), 16000).unwrap();
+        // Same voice, louder and captured at a different rate
+        let again = voiceprint(&voice(130.0, [700.0, 1200.0], 1.5, 48000, 0.2), 48000).unwrap();
+        let other = voiceprint(&voice(240.0, [400.0, 2600.0], 1.5, 16000, 0.1), 16000).unwrap();
+
+        assert_eq!(enrolled.len(), VOICEPRINT_LEN);
+        let same = similarity(&enrolled, &again);
+        let different = similarity(&enrolled, &other);
+        assert!(same > 0.9, "same voice similarity {}", same);
+        assert!(
+            same - different > 0.3,
+            "same {} vs different {}",
+            same,
+            different
+        );
+    }
+
+    #[test]
+    fn short_or_silent_audio_has_no_voiceprint() {
+        assert!(voiceprint(&voice(130.0, [700.0, 1200.0], 0.3, 16000, 0.1), 16000).is_none());
+        assert!(voiceprint(&[0.0; 32000], 16000).is_none());
+        assert!(voiceprint(&[], 16000).is_none());
+    }
+
+    #[test]
+    fn similarity_of_mismatched_lengths_is_zero() {
+        assert_eq!(similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(similarity(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+        assert!((similarity(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+    }
+}
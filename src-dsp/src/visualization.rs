@@ -0,0 +1,534 @@
+//! Waveform and spectrogram data for display.
+//!
+//! [`VisualizationProcessor`] turns a mono stream into render-ready updates:
+//! a peak-downsampled waveform and, each time an FFT window fills, one
+//! spectrogram column of RGB pixels on a log frequency scale, colored with a
+//! gradient from [`gradients`].
+
+use rustfft::{num_complex::Complex, FftPlanner};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::speech::SpeechMetrics;
+
+/// A single column of spectrogram data ready for rendering
+#[derive(Clone, Debug, Serialize)]
+pub struct SpectrogramColumn {
+    /// RGB triplets for each pixel row (height * 3 bytes)
+    pub colors: Vec<u8>,
+}
+
+/// Payload for visualization data events
+#[derive(Clone, Debug, Serialize)]
+pub struct VisualizationPayload {
+    /// Pre-downsampled waveform amplitudes
+    pub waveform: Vec<f32>,
+    /// Spectrogram column with RGB colors (present when FFT buffer fills)
+    pub spectrogram: Option<SpectrogramColumn>,
+    /// Speech detection metrics (present when speech processor is active)
+    pub speech_metrics: Option<SpeechMetrics>,
+}
+
+/// Callback trait for receiving visualization data
+pub trait VisualizationCallback: Send {
+    /// Called when new visualization data is available
+    fn on_visualization_data(&self, payload: VisualizationPayload);
+}
+
+/// Color stop for gradient interpolation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    /// Position in the gradient from 0.0 (silence) to 1.0 (loudest)
+    pub position: f32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl ColorStop {
+    /// Create a stop at `position` with an RGB color
+    pub fn new(position: f32, [r, g, b]: [u8; 3]) -> Self {
+        Self { position, r, g, b }
+    }
+}
+
+/// Built-in spectrogram gradients, from silence to the loudest bin.
+pub mod gradients {
+    use super::ColorStop;
+
+    /// Dark blue through green and yellow to red
+    pub fn classic() -> Vec<ColorStop> {
+        vec![
+            ColorStop::new(0.00, [10, 15, 26]),
+            ColorStop::new(0.15, [0, 50, 200]),
+            ColorStop::new(0.35, [0, 255, 150]),
+            ColorStop::new(0.60, [200, 255, 0]),
+            ColorStop::new(0.80, [255, 155, 0]),
+            ColorStop::new(1.00, [255, 0, 0]),
+        ]
+    }
+
+    /// Dark purple through teal to yellow
+    pub fn viridis() -> Vec<ColorStop> {
+        vec![
+            ColorStop::new(0.00, [68, 1, 84]),
+            ColorStop::new(0.25, [59, 82, 139]),
+            ColorStop::new(0.50, [33, 145, 140]),
+            ColorStop::new(0.75, [94, 201, 98]),
+            ColorStop::new(1.00, [253, 231, 37]),
+        ]
+    }
+
+    /// Black through purple and orange to pale yellow
+    pub fn magma() -> Vec<ColorStop> {
+        vec![
+            ColorStop::new(0.00, [0, 0, 4]),
+            ColorStop::new(0.25, [81, 18, 124]),
+            ColorStop::new(0.50, [183, 55, 121]),
+            ColorStop::new(0.75, [252, 137, 97]),
+            ColorStop::new(1.00, [252, 253, 191]),
+        ]
+    }
+
+    /// Black to white
+    pub fn grayscale() -> Vec<ColorStop> {
+        vec![
+            ColorStop::new(0.00, [10, 10, 10]),
+            ColorStop::new(1.00, [255, 255, 255]),
+        ]
+    }
+
+    /// Dark to `accent` to white
+    pub fn accent(accent: [u8; 3]) -> Vec<ColorStop> {
+        vec![
+            ColorStop::new(0.00, [10, 15, 26]),
+            ColorStop::new(0.60, accent),
+            ColorStop::new(1.00, [255, 255, 255]),
+        ]
+    }
+}
+
+/// Resolution, update rate and colors of a [`VisualizationProcessor`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisualizationConfig {
+    /// Samples per spectrogram FFT; a power of two of at least 2
+    pub fft_size: usize,
+    /// Rows in each spectrogram column
+    pub output_height: usize,
+    /// Waveform points per update
+    pub waveform_samples: usize,
+    /// Most updates per second (0 = one per call to `process`)
+    pub target_fps: u32,
+    /// Spectrogram gradient; needs at least two stops, from 0.0 to 1.0
+    pub gradient: Vec<ColorStop>,
+}
+
+impl Default for VisualizationConfig {
+    fn default() -> Self {
+        Self {
+            fft_size: 512,
+            output_height: 256,
+            waveform_samples: 64,
+            target_fps: 0,
+            gradient: gradients::classic(),
+        }
+    }
+}
+
+/// Visualization processor that computes render-ready waveform and spectrogram data.
+pub struct VisualizationProcessor {
+    /// Sample rate for frequency calculations
+    sample_rate: u32,
+    /// Target height for spectrogram output (pixels)
+    output_height: usize,
+    /// FFT size (must be power of 2)
+    fft_size: usize,
+    /// FFT planner/executor
+    fft: Arc<dyn rustfft::Fft<f32>>,
+    /// Pre-computed Hanning window
+    hanning_window: Vec<f32>,
+    /// Buffer for accumulating samples for FFT
+    fft_buffer: Vec<f32>,
+    /// Current write position in FFT buffer
+    fft_write_index: usize,
+    /// Pre-computed color lookup table (256 entries, RGB)
+    color_lut: Vec<[u8; 3]>,
+    /// Waveform accumulator for downsampling
+    waveform_buffer: Vec<f32>,
+    /// Target waveform output samples per emit
+    waveform_target_samples: usize,
+    /// Shortest time between emits, from the target FPS
+    min_emit_interval: Option<Duration>,
+    /// When the last visualization event was emitted
+    last_emit: Option<Instant>,
+    /// Latest spectrogram column not yet emitted
+    pending_spectrogram: Option<SpectrogramColumn>,
+    /// Speech metrics to include in next visualization event
+    pending_speech_metrics: Option<SpeechMetrics>,
+    /// Callback for visualization events
+    callback: Option<Arc<dyn VisualizationCallback>>,
+}
+
+impl VisualizationProcessor {
+    /// Create a visualization processor for audio at `sample_rate`.
+    ///
+    /// # Panics
+    ///
+    /// If `config.fft_size` is below 2 or `config.gradient` has fewer than
+    /// two stops.
+    pub fn new(sample_rate: u32, config: &VisualizationConfig) -> Self {
+        assert!(config.fft_size >= 2, "fft_size must be at least 2");
+        assert!(
+            config.gradient.len() >= 2,
+            "gradient needs at least two stops"
+        );
+
+        let fft_size = config.fft_size;
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(fft_size);
+
+        let hanning_window: Vec<f32> = (0..fft_size)
+            .map(|i| {
+                0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (fft_size - 1) as f32).cos())
+            })
+            .collect();
+
+        Self {
+            sample_rate,
+            output_height: config.output_height,
+            fft_size,
+            fft,
+            hanning_window,
+            fft_buffer: Vec::with_capacity(fft_size),
+            fft_write_index: 0,
+            color_lut: Self::build_color_lut(&config.gradient),
+            waveform_buffer: Vec::with_capacity(256),
+            waveform_target_samples: config.waveform_samples.max(1),
+            min_emit_interval: (config.target_fps > 0)
+                .then(|| Duration::from_secs_f64(1.0 / f64::from(config.target_fps))),
+            last_emit: None,
+            pending_spectrogram: None,
+            pending_speech_metrics: None,
+            callback: None,
+        }
+    }
+
+    /// Switch to a new resolution, update rate and gradient. Audio buffered
+    /// for the next update is dropped; the callback is kept.
+    ///
+    /// # Panics
+    ///
+    /// Under the same conditions as [`VisualizationProcessor::new`].
+    pub fn reconfigure(&mut self, config: &VisualizationConfig) {
+        let callback = self.callback.take();
+        *self = Self::new(self.sample_rate, config);
+        self.callback = callback;
+    }
+
+    /// Set the callback for visualization events
+    pub fn set_callback(&mut self, callback: Arc<dyn VisualizationCallback>) {
+        self.callback = Some(callback);
+    }
+
+    /// Set speech metrics to include in the next visualization event
+    pub fn set_speech_metrics(&mut self, metrics: SpeechMetrics) {
+        self.pending_speech_metrics = Some(metrics);
+    }
+
+    /// Build the color lookup table from gradient stops
+    fn build_color_lut(stops: &[ColorStop]) -> Vec<[u8; 3]> {
+        let mut lut = Vec::with_capacity(256);
+
+        for i in 0..256 {
+            let t_raw = i as f32 / 255.0;
+            let t = t_raw.powf(0.7);
+
+            let mut color = [255u8, 0, 0];
+
+            for j in 0..stops.len() - 1 {
+                let s1 = &stops[j];
+                let s2 = &stops[j + 1];
+
+                if t >= s1.position && t <= s2.position {
+                    let s = (t - s1.position) / (s2.position - s1.position);
+                    color[0] = (s1.r as f32 + s * (s2.r as f32 - s1.r as f32)).round() as u8;
+                    color[1] = (s1.g as f32 + s * (s2.g as f32 - s1.g as f32)).round() as u8;
+                    color[2] = (s1.b as f32 + s * (s2.b as f32 - s1.b as f32)).round() as u8;
+                    break;
+                }
+            }
+
+            lut.push(color);
+        }
+
+        lut
+    }
+
+    /// Convert normalized position to fractional frequency bin
+    fn position_to_freq_bin(&self, pos: f32, num_bins: usize) -> f32 {
+        const MIN_FREQ: f32 = 20.0;
+        const MAX_FREQ: f32 = 24000.0;
+
+        let min_log = MIN_FREQ.log10();
+        let max_log = MAX_FREQ.log10();
+
+        let log_freq = min_log + pos * (max_log - min_log);
+        let freq = 10.0f32.powf(log_freq);
+
+        let bin_index = freq * self.fft_size as f32 / self.sample_rate as f32;
+        bin_index.clamp(0.0, (num_bins - 1) as f32)
+    }
+
+    /// Get magnitude for a pixel row
+    fn get_magnitude_for_pixel(&self, magnitudes: &[f32], y: usize, height: usize) -> f32 {
+        let num_bins = magnitudes.len();
+
+        let pos1 = (height - 1 - y) as f32 / height as f32;
+        let pos2 = (height - y) as f32 / height as f32;
+
+        let bin1 = self.position_to_freq_bin(pos1, num_bins);
+        let bin2 = self.position_to_freq_bin(pos2, num_bins);
+
+        let bin_low = bin1.min(bin2).max(0.0);
+        let bin_high = bin1.max(bin2).min((num_bins - 1) as f32);
+
+        if bin_high - bin_low < 1.0 {
+            let bin_floor = bin_low.floor() as usize;
+            let bin_ceil = (bin_floor + 1).min(num_bins - 1);
+            let frac = bin_low - bin_floor as f32;
+            return magnitudes[bin_floor] * (1.0 - frac) + magnitudes[bin_ceil] * frac;
+        }
+
+        let mut sum = 0.0f32;
+        let mut weight = 0.0f32;
+
+        let start_bin = bin_low.floor() as usize;
+        let end_bin = bin_high.ceil() as usize;
+
+        #[allow(clippy::needless_range_loop)]
+        for b in start_bin..=end_bin.min(num_bins - 1) {
+            let bin_start = b as f32;
+            let bin_end = (b + 1) as f32;
+            let overlap_start = bin_low.max(bin_start);
+            let overlap_end = bin_high.min(bin_end);
+            let overlap_weight = (overlap_end - overlap_start).max(0.0);
+
+            if overlap_weight > 0.0 {
+                sum += magnitudes[b] * overlap_weight;
+                weight += overlap_weight;
+            }
+        }
+
+        if weight > 0.0 {
+            sum / weight
+        } else {
+            0.0
+        }
+    }
+
+    /// Process FFT buffer and generate spectrogram column
+    fn process_fft(&self) -> SpectrogramColumn {
+        let mut complex_buffer: Vec<Complex<f32>> = self
+            .fft_buffer
+            .iter()
+            .zip(self.hanning_window.iter())
+            .map(|(&sample, &window)| Complex::new(sample * window, 0.0))
+            .collect();
+
+        complex_buffer.resize(self.fft_size, Complex::new(0.0, 0.0));
+
+        self.fft.process(&mut complex_buffer);
+
+        let num_bins = self.fft_size / 2;
+        let magnitudes: Vec<f32> = complex_buffer[..num_bins]
+            .iter()
+            .map(|c| (c.re * c.re + c.im * c.im).sqrt() / self.fft_size as f32)
+            .collect();
+
+        let max_mag = magnitudes.iter().cloned().fold(0.001f32, f32::max);
+        let ref_level = max_mag.max(0.05);
+
+        let mut colors = Vec::with_capacity(self.output_height * 3);
+
+        for y in 0..self.output_height {
+            let magnitude = self.get_magnitude_for_pixel(&magnitudes, y, self.output_height);
+
+            let normalized_db = (1.0 + magnitude / ref_level * 9.0).log10();
+            let normalized = normalized_db.clamp(0.0, 1.0);
+
+            let color_idx = (normalized * 255.0).floor() as usize;
+            let color = &self.color_lut[color_idx.min(255)];
+
+            colors.push(color[0]);
+            colors.push(color[1]);
+            colors.push(color[2]);
+        }
+
+        SpectrogramColumn { colors }
+    }
+
+    /// Downsample waveform buffer using peak detection
+    fn downsample_waveform(&self, samples: &[f32]) -> Vec<f32> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+
+        let window_size = (samples.len() / self.waveform_target_samples).max(1);
+        let output_count = samples.len().div_ceil(window_size);
+
+        let mut output = Vec::with_capacity(output_count);
+
+        for chunk in samples.chunks(window_size) {
+            let peak = chunk
+                .iter()
+                .max_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap())
+                .copied()
+                .unwrap_or(0.0);
+            output.push(peak);
+        }
+
+        output
+    }
+
+    /// Process audio samples for visualization
+    pub fn process(&mut self, samples: &[f32]) {
+        // Accumulate samples for FFT
+        for &sample in samples {
+            if self.fft_write_index < self.fft_size {
+                if self.fft_buffer.len() <= self.fft_write_index {
+                    self.fft_buffer.push(sample);
+                } else {
+                    self.fft_buffer[self.fft_write_index] = sample;
+                }
+                self.fft_write_index += 1;
+            }
+        }
+
+        // Accumulate samples for waveform
+        self.waveform_buffer.extend_from_slice(samples);
+
+        // Check if FFT buffer is full
+        if self.fft_write_index >= self.fft_size {
+            self.pending_spectrogram = Some(self.process_fft());
+            self.fft_write_index = 0;
+        }
+
+        // Hold updates back to the target FPS
+        if let (Some(interval), Some(last)) = (self.min_emit_interval, self.last_emit) {
+            if last.elapsed() < interval {
+                return;
+            }
+        }
+        self.last_emit = Some(Instant::now());
+        let spectrogram = self.pending_spectrogram.take();
+
+        // Downsample waveform
+        let waveform = self.downsample_waveform(&self.waveform_buffer);
+        self.waveform_buffer.clear();
+
+        // Take speech metrics
+        let speech_metrics = self.pending_speech_metrics.take();
+
+        // Emit visualization data
+        let payload = VisualizationPayload {
+            waveform,
+            spectrogram,
+            speech_metrics,
+        };
+
+        if let Some(ref callback) = self.callback {
+            callback.on_visualization_data(payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Collector(Mutex<Vec<VisualizationPayload>>);
+
+    impl VisualizationCallback for Collector {
+        fn on_visualization_data(&self, payload: VisualizationPayload) {
+            self.0.lock().unwrap().push(payload);
+        }
+    }
+
+    fn processor(config: &VisualizationConfig) -> (VisualizationProcessor, Arc<Collector>) {
+        let collector = Arc::new(Collector::default());
+        let mut processor = VisualizationProcessor::new(48000, config);
+        processor.set_callback(collector.clone());
+        (processor, collector)
+    }
+
+    #[test]
+    fn emits_column_when_fft_fills() {
+        let config = VisualizationConfig {
+            fft_size: 256,
+            output_height: 32,
+            waveform_samples: 16,
+            ..Default::default()
+        };
+        let (mut processor, collector) = processor(&config);
+
+        processor.process(&[0.1; 128]);
+        processor.process(&[0.1; 128]);
+
+        let payloads = collector.0.lock().unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert!(payloads[0].spectrogram.is_none());
+        assert_eq!(payloads[0].waveform.len(), 16);
+        let column = payloads[1].spectrogram.as_ref().unwrap();
+        assert_eq!(column.colors.len(), 32 * 3);
+    }
+
+    #[test]
+    fn target_fps_holds_updates_back() {
+        let config = VisualizationConfig {
+            target_fps: 1,
+            ..Default::default()
+        };
+        let (mut processor, collector) = processor(&config);
+
+        for _ in 0..10 {
+            processor.process(&[0.0; 480]);
+        }
+        assert_eq!(collector.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn reconfigure_keeps_callback() {
+        let (mut processor, collector) = processor(&VisualizationConfig::default());
+        processor.reconfigure(&VisualizationConfig {
+            fft_size: 128,
+            output_height: 8,
+            gradient: gradients::grayscale(),
+            ..Default::default()
+        });
+
+        processor.process(&[0.2; 128]);
+        let payloads = collector.0.lock().unwrap();
+        assert_eq!(
+            payloads[0].spectrogram.as_ref().unwrap().colors.len(),
+            8 * 3
+        );
+    }
+
+    #[test]
+    fn builtin_gradients_span_full_range() {
+        for gradient in [
+            gradients::classic(),
+            gradients::viridis(),
+            gradients::magma(),
+            gradients::grayscale(),
+            gradients::accent([1, 2, 3]),
+        ] {
+            assert_eq!(gradient.first().unwrap().position, 0.0);
+            assert_eq!(gradient.last().unwrap().position, 1.0);
+        }
+    }
+}
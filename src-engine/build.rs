@@ -12,6 +12,8 @@ const WHISPER_VERSION: &str = "1.8.2";
 const GITHUB_RELEASE_BASE: &str = "https://github.com/ggml-org/whisper.cpp/releases/download";
 
 fn main() {
+    emit_build_info();
+
     let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
 
     // Check if CUDA feature is enabled (set by Cargo when --features cuda is used)
@@ -159,6 +161,41 @@ fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 }
 
+/// Pass version details to the engine for `GetVersionInfo`: the whisper.cpp
+/// version, the build time and, in a git checkout, the commit.
+fn emit_build_info() {
+    println!(
+        "cargo:rustc-env=FLOWSTT_WHISPER_VERSION={}",
+        WHISPER_VERSION
+    );
+
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    let build_time = env::var("SOURCE_DATE_EPOCH").unwrap_or_else(|_| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs().to_string())
+            .unwrap_or_default()
+    });
+    println!("cargo:rustc-env=FLOWSTT_BUILD_TIME={}", build_time);
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    if let Some(hash) = git_hash.filter(|hash| !hash.is_empty()) {
+        println!("cargo:rustc-env=FLOWSTT_GIT_HASH={}", hash);
+        // Only watch files that exist; a missing one reruns every build
+        for file in ["../.git/HEAD", "../.git/logs/HEAD"] {
+            if Path::new(file).exists() {
+                println!("cargo:rerun-if-changed={}", file);
+            }
+        }
+    }
+}
+
 /// Build whisper.cpp from source on Linux using CMake
 fn build_whisper_linux(cuda_enabled: bool) {
     let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set"));
@@ -0,0 +1,172 @@
+//! Speech activity log.
+//!
+//! With `activity_log` on, a line is appended to `activity.jsonl` in the
+//! data directory whenever an utterance ends (when it started and how long
+//! it lasted) and whenever a transcription completes (how many words it
+//! had). Only times and counts are kept, never text. `GetActivitySummary`
+//! totals the log over a period for `flowstt stats`.
+//!
+//! Nothing is recorded while privacy mode is on.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Days, Local};
+use flowstt_common::{ActivityInterval, ActivityPeriod, ActivitySummary};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::current_config;
+use crate::history::TranscriptionHistory;
+
+/// One line of the activity log.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Activity {
+    /// An utterance: speech detected in Automatic mode, or a push-to-talk press
+    Speech {
+        /// RFC 3339 local time the utterance started
+        started_at: String,
+        duration_ms: u64,
+    },
+    /// A completed transcription
+    Transcription {
+        /// RFC 3339 local time it completed
+        at: String,
+        words: u32,
+    },
+}
+
+/// Start of the utterance in progress
+static SPEECH_STARTED: Mutex<Option<DateTime<Local>>> = Mutex::new(None);
+
+fn log_path() -> PathBuf {
+    TranscriptionHistory::data_dir().join("activity.jsonl")
+}
+
+/// Note that an utterance started.
+pub(crate) fn speech_started() {
+    *SPEECH_STARTED.lock().unwrap() = Some(Local::now());
+}
+
+/// Record the utterance in progress, if there is one.
+pub(crate) fn speech_ended() {
+    let Some(started) = SPEECH_STARTED.lock().unwrap().take() else {
+        return;
+    };
+    let duration_ms = (Local::now() - started).num_milliseconds().max(0) as u64;
+    record(Activity::Speech {
+        started_at: started.to_rfc3339(),
+        duration_ms,
+    });
+}
+
+/// Record a completed transcription of `text`.
+pub(crate) fn transcribed(text: &str) {
+    record(Activity::Transcription {
+        at: Local::now().to_rfc3339(),
+        words: text.split_whitespace().count() as u32,
+    });
+}
+
+fn record(activity: Activity) {
+    if crate::privacy::is_privacy_mode() || !current_config().activity_log {
+        return;
+    }
+
+    let path = log_path();
+    let result = (|| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let line = serde_json::to_string(&activity).map_err(std::io::Error::other)?;
+        writeln!(file, "{}", line)
+    })();
+    if let Err(e) = result {
+        warn!("[ActivityLog] Failed to write {:?}: {}", path, e);
+    }
+}
+
+/// Total the activity log over `period`.
+pub fn summary(period: ActivityPeriod) -> Result<ActivitySummary, String> {
+    let path = log_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {:?}: {}", path, e)),
+    };
+
+    let now = Local::now();
+    let days_back = match period {
+        ActivityPeriod::Today => Some(0),
+        ActivityPeriod::Week => Some(6),
+        ActivityPeriod::Month => Some(29),
+        ActivityPeriod::All => None,
+    };
+    let since = days_back.and_then(|days| {
+        (now.date_naive() - Days::new(days))
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(Local)
+            .earliest()
+    });
+    let interval_format = match period {
+        ActivityPeriod::Today => "%Y-%m-%dT%H:00",
+        _ => "%Y-%m-%d",
+    };
+
+    let mut summary = ActivitySummary {
+        period,
+        ..Default::default()
+    };
+    // (speech_ms, words) by interval start
+    let mut intervals: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for line in contents.lines() {
+        // Skip lines cut short by a crash
+        let Ok(activity) = serde_json::from_str::<Activity>(line) else {
+            continue;
+        };
+        let time = match &activity {
+            Activity::Speech { started_at, .. } => started_at,
+            Activity::Transcription { at, .. } => at,
+        };
+        let Ok(time) = DateTime::parse_from_rfc3339(time) else {
+            continue;
+        };
+        let time = time.with_timezone(&Local);
+        if since.is_some_and(|since| time < since) {
+            continue;
+        }
+
+        let interval = intervals
+            .entry(time.format(interval_format).to_string())
+            .or_default();
+        match activity {
+            Activity::Speech { duration_ms, .. } => {
+                summary.speech_ms += duration_ms;
+                summary.utterances += 1;
+                interval.0 += duration_ms;
+            }
+            Activity::Transcription { words, .. } => {
+                summary.words += u64::from(words);
+                summary.transcriptions += 1;
+                interval.1 += u64::from(words);
+            }
+        }
+    }
+
+    summary.intervals = intervals
+        .into_iter()
+        .map(|(start, (speech_ms, words))| ActivityInterval {
+            start,
+            speech_ms,
+            words,
+        })
+        .collect();
+    Ok(summary)
+}
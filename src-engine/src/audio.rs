@@ -5,6 +5,11 @@
 
 use std::path::PathBuf;
 
+use flowstt_common::config::SegmentNormalization;
+use flowstt_dsp::{resample_linear, Normalization};
+
+pub use flowstt_dsp::convert_to_mono;
+
 /// Raw recorded audio data before processing
 pub struct RawRecordedAudio {
     pub samples: Vec<f32>,
@@ -12,73 +17,58 @@ pub struct RawRecordedAudio {
     pub channels: u16,
 }
 
-/// Convert multi-channel audio to mono by averaging channels
-pub fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    samples
-        .chunks(channels)
-        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-        .collect()
-}
-
 /// Process raw recorded audio into format suitable for transcription
 /// This is CPU-intensive and should be called in a separate thread/task
-pub fn process_recorded_audio(raw: RawRecordedAudio) -> Result<Vec<f32>, String> {
+pub fn process_recorded_audio(
+    raw: RawRecordedAudio,
+    normalization: SegmentNormalization,
+) -> Result<Vec<f32>, String> {
     // Convert to mono if stereo
-    let mono_samples = if raw.channels > 1 {
+    let mut mono_samples = if raw.channels > 1 {
         convert_to_mono(&raw.samples, raw.channels as usize)
     } else {
         raw.samples
     };
 
+    normalize(&mut mono_samples, normalization);
+
     // Resample to 16kHz for Whisper
     resample_to_16khz(&mono_samples, raw.sample_rate)
 }
 
+/// Sample rate Whisper expects
+const TRANSCRIPTION_SAMPLE_RATE: u32 = 16000;
+
+/// Scale mono samples to a consistent level
+fn normalize(samples: &mut [f32], mode: SegmentNormalization) {
+    let mode = match mode {
+        SegmentNormalization::Off => Normalization::Off,
+        SegmentNormalization::Peak => Normalization::Peak,
+        SegmentNormalization::Loudness => Normalization::Loudness,
+    };
+    flowstt_dsp::normalize(samples, mode);
+}
+
 /// Resample audio to 16kHz using linear interpolation
 /// This is a simple resampler suitable for speech-to-text
 fn resample_to_16khz(samples: &[f32], source_rate: u32) -> Result<Vec<f32>, String> {
-    const TARGET_RATE: u32 = 16000;
-
-    if source_rate == TARGET_RATE {
-        return Ok(samples.to_vec());
-    }
-
-    if samples.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let ratio = source_rate as f64 / TARGET_RATE as f64;
-    let output_len = (samples.len() as f64 / ratio).ceil() as usize;
-    let mut output = Vec::with_capacity(output_len);
-
-    for i in 0..output_len {
-        let src_pos = i as f64 * ratio;
-        let src_idx = src_pos.floor() as usize;
-        let frac = src_pos - src_idx as f64;
-
-        let sample = if src_idx + 1 < samples.len() {
-            // Linear interpolation between samples
-            samples[src_idx] * (1.0 - frac as f32) + samples[src_idx + 1] * frac as f32
-        } else if src_idx < samples.len() {
-            samples[src_idx]
-        } else {
-            0.0
-        };
-
-        output.push(sample);
-    }
-
-    Ok(output)
+    Ok(resample_linear(
+        samples,
+        source_rate,
+        TRANSCRIPTION_SAMPLE_RATE,
+    ))
 }
 
-/// Save raw audio samples to a WAV file
+/// Save raw audio samples to a WAV file, encrypted if `encrypted` is set
 pub fn save_to_wav(
     samples: &[f32],
     sample_rate: u32,
     channels: u16,
     output_path: &PathBuf,
+    encrypted: bool,
 ) -> Result<(), String> {
     use hound::{SampleFormat, WavSpec, WavWriter};
+    use std::io::Cursor;
 
     let spec = WavSpec {
         channels,
@@ -87,7 +77,9 @@ pub fn save_to_wav(
         sample_format: SampleFormat::Float,
     };
 
-    let mut writer = WavWriter::create(output_path, spec)
+    // Encode in memory so the file can be encrypted before it touches the disk
+    let mut buffer = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut buffer, spec)
         .map_err(|e| format!("Failed to create WAV file: {}", e))?;
 
     for &sample in samples {
@@ -100,7 +92,36 @@ pub fn save_to_wav(
         .finalize()
         .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
 
-    Ok(())
+    crate::history_crypto::write_file(output_path, buffer.get_ref(), encrypted)
+}
+
+/// Decode WAV data, such as a recording written by [`save_to_wav`].
+///
+/// Integer samples are scaled to the -1.0..1.0 float range.
+pub fn decode_wav(data: &[u8]) -> Result<RawRecordedAudio, String> {
+    use hound::{SampleFormat, WavReader};
+
+    let mut reader = WavReader::new(std::io::Cursor::new(data))
+        .map_err(|e| format!("Failed to read WAV data: {}", e))?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 * scale))
+                .collect()
+        }
+    }
+    .map_err(|e| format!("Failed to decode WAV samples: {}", e))?;
+
+    Ok(RawRecordedAudio {
+        samples,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+    })
 }
 
 /// Generate a timestamped filename for recording
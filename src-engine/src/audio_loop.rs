@@ -4,23 +4,52 @@
 //! and transcription systems. In Automatic mode, uses VAD to trigger transcription.
 //! In PTT mode, the PTT controller manages transcription triggers.
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use flowstt_common::config::{DeviceCalibration, VadSource};
 use flowstt_common::ipc::{EventType, Response};
-use flowstt_common::{TranscriptionResult, VisualizationData};
-use tracing::{debug, error, info};
-
-use crate::ipc::broadcast_event;
+use flowstt_common::{EngineComponent, TranscriptionResult, VisualizationData};
+use flowstt_dsp::{apply_gain, convert_to_mono, db_to_gain};
+use tracing::{debug, error, info, warn};
+
+use crate::audio_stream::StreamSegment;
+use crate::ipc::{broadcast_event, speech_metrics_requested};
+use crate::meeting::MeetingSegment;
+use crate::partial_typing::InterimSegment;
 use crate::platform;
+use crate::platform::realtime::StopSignal;
 use crate::processor::{
-    SpeechDetector, SpeechEventCallback, SpeechEventPayload, SpeechStateChange,
-    VisualizationCallback, VisualizationPayload, VisualizationProcessor, WordBreakEvent,
-    WordBreakPayload,
+    ClippingDetector, SpeechDetector, SpeechEventCallback, SpeechEventPayload, SpeechMetrics,
+    SpeechStateChange, VisualizationCallback, VisualizationPayload, VisualizationProcessor,
+    WordBreakEvent, WordBreakPayload,
+};
+use crate::resample::{RateNormalizer, PROCESSING_SAMPLE_RATE};
+use crate::transcription::{
+    CompletedSegment, Retranscription, TranscribeState, TranscriptionCallback, TranscriptionQueue,
 };
-use crate::transcription::{TranscribeState, TranscriptionCallback, TranscriptionQueue};
+use crate::watchdog;
+
+/// Minimum interval between `SpeechMetrics` events (~30 Hz)
+const SPEECH_METRICS_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Samples held while the transcription state is locked elsewhere before
+/// the oldest are dropped (~2 seconds of 48kHz stereo)
+pub(crate) const MAX_BACKLOG_SAMPLES: usize = PROCESSING_SAMPLE_RATE as usize * 2 * 2;
+
+/// Longest the processing loop blocks waiting for audio before re-checking
+/// its stop flag and idle timer
+pub(crate) const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Time without audio from the backend before its streams are restarted, and
+/// again before the capture is reported as stalled
+const STALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wakes the processing loop when it is stopped
+static AUDIO_LOOP_STOP: StopSignal = StopSignal::new();
 
 /// Global audio processing thread control
 static AUDIO_LOOP_ACTIVE: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
@@ -47,24 +76,58 @@ pub fn start_audio_loop(
 
     let loop_active = get_loop_active();
     loop_active.store(true, Ordering::SeqCst);
+    let stop = AUDIO_LOOP_STOP.arm();
 
-    // Get sample rate from backend
-    let sample_rate = platform::get_backend()
-        .map(|b| b.sample_rate())
-        .unwrap_or(48000);
+    // Captured audio is normalized to the processing rate
+    let sample_rate = PROCESSING_SAMPLE_RATE;
 
     thread::spawn(move || {
         tracing::info!("[AudioLoop] Starting audio processing loop");
+        let heartbeat = watchdog::register(EngineComponent::AudioLoop);
+
+        // Loaded on this thread; it locks the service state
+        let calibration = current_device_calibration();
 
         // Create speech detector
         let mut speech_detector = SpeechDetector::new(sample_rate);
         speech_detector.set_callback(Arc::new(SpeechEventBroadcaster));
+        let vad =
+            futures::executor::block_on(crate::state::get_service_state().lock()).vad_override;
+        if let Some(ref vad) = vad {
+            speech_detector.set_thresholds(vad.voiced_threshold_db, vad.whisper_threshold_db);
+        } else if let Some(ref cal) = calibration {
+            speech_detector.set_thresholds(cal.voiced_threshold_db, cal.whisper_threshold_db);
+        }
+        let gain = calibration.as_ref().and_then(|c| db_to_gain(c.gain_db));
+
+        let mut clipping_detector = ClippingDetector::new(sample_rate);
 
         // Create visualization processor
-        let mut viz_processor = VisualizationProcessor::new(sample_rate, 256);
+        let mut viz_processor = VisualizationProcessor::new(sample_rate);
         viz_processor.set_callback(Arc::new(VisualizationBroadcaster));
 
+        // Idle auto-suspend; this loop only runs in Automatic mode
+        let config = crate::config::current_config();
+        let idle_limit = match config.auto_suspend_minutes {
+            0 => None,
+            minutes => Some(Duration::from_secs(u64::from(minutes) * 60)),
+        };
+        let vad_source = vad.map_or(config.vad_source, |vad| vad.source);
+        let mut last_speech = Instant::now();
+
+        // Watchdog for streams that stop delivering audio without an error
+        let mut last_audio = Instant::now();
+        let mut stall_restarted = false;
+        let mut stall_reported = false;
+
+        // Native rate of the primary input, to notice Bluetooth profile switches
+        let mut input_rate: Option<u32> = None;
+
+        let mut normalizer = RateNormalizer::new();
+        let mut backlog: VecDeque<PendingChunk> = VecDeque::new();
+        let mut backlog_samples = 0usize;
         let loop_active = get_loop_active();
+        let mut last_metrics_sent = Instant::now();
 
         loop {
             // Check if we should stop
@@ -77,18 +140,119 @@ pub fn start_audio_loop(
                 break;
             }
 
-            // Try to receive audio from backend
-            let audio_data = platform::get_backend().and_then(|b| b.try_recv());
+            // Leave everything to the loop the watchdog started in our place
+            if !heartbeat.beat() {
+                warn!("[AudioLoop] Replaced by the watchdog, exiting");
+                break;
+            }
+
+            if idle_limit.is_some_and(|limit| last_speech.elapsed() >= limit) {
+                info!("[AudioLoop] No speech detected recently, suspending capture");
+                loop_active.store(false, Ordering::SeqCst);
+                crate::ipc::handlers::suspend_for_idle();
+                break;
+            }
+
+            let silent_for = last_audio.elapsed();
+            if !stall_restarted && silent_for >= STALL_TIMEOUT {
+                warn!(
+                    "[AudioLoop] No audio for {:?}, restarting capture",
+                    silent_for
+                );
+                stall_restarted = true;
+                if let Err(e) = crate::ipc::handlers::restart_stalled_capture() {
+                    warn!("[AudioLoop] Failed to restart capture: {}", e);
+                }
+            } else if stall_restarted && !stall_reported && silent_for >= STALL_TIMEOUT * 2 {
+                error!("[AudioLoop] Capture stalled: no audio for {:?}", silent_for);
+                stall_reported = true;
+                crate::ipc::handlers::set_capture_stalled(Some(silent_for.as_secs()));
+            }
+
+            // Wait for audio from the backend; stopping the loop wakes this early
+            let audio_data = match platform::get_backend() {
+                Some(backend) => backend.recv(&stop, RECV_TIMEOUT),
+                None => {
+                    stop.wait(RECV_TIMEOUT);
+                    None
+                }
+            };
+
+            if let Some(rate) = platform::get_backend()
+                .and_then(|backend| backend.input_sample_rate())
+                .filter(|&rate| Some(rate) != input_rate)
+            {
+                crate::ipc::handlers::report_input_rate(input_rate, rate);
+                input_rate = Some(rate);
+            }
 
             if let Some(data) = audio_data {
+                // Digital silence counts as no audio: a dead stream may keep
+                // delivering zeroed buffers
+                if data.samples.iter().any(|&s| s != 0.0) {
+                    last_audio = Instant::now();
+                    if stall_restarted {
+                        info!("[AudioLoop] Audio is being received again");
+                        if stall_reported {
+                            crate::ipc::handlers::set_capture_stalled(None);
+                        }
+                        stall_restarted = false;
+                        stall_reported = false;
+                    }
+                }
+
+                let mut data = normalizer.normalize(data);
+                if let Some(gain) = gain {
+                    apply_gain(&mut data.samples, gain);
+                    if let Some(primary) = data.primary.as_mut() {
+                        apply_gain(primary, gain);
+                    }
+                }
+
+                // Warn when the (gain-adjusted) input is clipping
+                if let Some(clipped_samples) =
+                    clipping_detector.process(&data.samples, data.channels)
+                {
+                    warn!(
+                        "[AudioLoop] Input clipping: {} samples in the last second",
+                        clipped_samples
+                    );
+                    broadcast_event(Response::Event {
+                        event: EventType::ClippingDetected { clipped_samples },
+                    });
+                }
+
                 // Convert to mono for processing
                 let mono_samples = convert_to_mono(&data.samples, data.channels as usize);
 
+                // Detect speech on the primary source alone if configured, so
+                // system audio in the mix can't trigger segments
+                let primary_mono = match (vad_source, &data.primary) {
+                    (VadSource::Primary, Some(primary)) => {
+                        Some(convert_to_mono(primary, data.channels as usize))
+                    }
+                    _ => None,
+                };
+
                 // Process through speech detector (always run for visualization)
-                speech_detector.process(&mono_samples);
+                speech_detector.process(primary_mono.as_deref().unwrap_or(&mono_samples));
 
                 // Get speech metrics for visualization
                 let speech_metrics = speech_detector.get_metrics();
+                if speech_metrics.is_speaking {
+                    last_speech = Instant::now();
+                }
+
+                // Stream raw metrics to IPC clients that opted in
+                if speech_metrics_requested()
+                    && last_metrics_sent.elapsed() >= SPEECH_METRICS_INTERVAL
+                {
+                    last_metrics_sent = Instant::now();
+                    broadcast_event(Response::Event {
+                        event: EventType::SpeechMetrics(to_common_metrics(&speech_metrics)),
+                    });
+                }
+                crate::recent_audio::push(&mono_samples, speech_metrics.is_speaking);
                 viz_processor.set_speech_metrics(speech_metrics);
 
                 // Process visualization
@@ -101,45 +265,31 @@ pub fn start_audio_loop(
                 // Update transcribe state if active
                 // Note: In Automatic mode, VAD triggers segments
                 // In PTT mode, PTT controller triggers segments (not audio_loop)
+                backlog_samples += data.samples.len();
+                backlog.push_back(PendingChunk {
+                    samples: data.samples,
+                    state_change,
+                    word_break,
+                });
+
+                // Never wait on the transcription state: if another thread
+                // holds it, keep the chunk and replay it on a later pass.
                 if let Ok(mut transcribe) = transcribe_state.try_lock() {
-                    if transcribe.is_active {
-                        // Write samples to ring buffer
-                        transcribe.process_samples(&data.samples);
-
-                        // Use speech detection events to trigger segments
-                        match state_change {
-                            SpeechStateChange::Started { lookback_samples } => {
-                                transcribe.on_speech_started(lookback_samples);
-
-                                // Broadcast speech started event
-                                broadcast_event(Response::Event {
-                                    event: EventType::SpeechStarted,
-                                });
-                            }
-                            SpeechStateChange::Ended { duration_ms } => {
-                                transcribe.on_speech_ended();
-
-                                // Broadcast speech ended event
-                                broadcast_event(Response::Event {
-                                    event: EventType::SpeechEnded { duration_ms },
-                                });
-                            }
-                            SpeechStateChange::None => {}
-                        }
-
-                        // Handle word breaks for timed segment submission
-                        if let Some(WordBreakEvent {
-                            offset_ms,
-                            gap_duration_ms,
-                        }) = word_break
-                        {
-                            transcribe.on_word_break(offset_ms, gap_duration_ms);
+                    for chunk in backlog.drain(..) {
+                        if transcribe.is_active {
+                            apply_chunk(&mut transcribe, chunk);
                         }
                     }
+                    backlog_samples = 0;
+                } else {
+                    while backlog_samples > MAX_BACKLOG_SAMPLES {
+                        let Some(oldest) = backlog.pop_front() else {
+                            break;
+                        };
+                        backlog_samples -= oldest.samples.len();
+                        platform::realtime::record_dropped_samples(oldest.samples.len());
+                    }
                 }
-            } else {
-                // No data available, sleep briefly
-                thread::sleep(Duration::from_millis(1));
             }
         }
 
@@ -149,20 +299,90 @@ pub fn start_audio_loop(
     Ok(())
 }
 
+/// Audio and speech events waiting to be applied to the transcription state
+struct PendingChunk {
+    samples: Vec<f32>,
+    state_change: SpeechStateChange,
+    word_break: Option<WordBreakEvent>,
+}
+
+/// Write a chunk to the transcription ring buffer and act on its speech events.
+fn apply_chunk(transcribe: &mut TranscribeState, chunk: PendingChunk) {
+    transcribe.process_samples(&chunk.samples);
+
+    // Use speech detection events to trigger segments
+    match chunk.state_change {
+        SpeechStateChange::Started { lookback_samples } => {
+            transcribe.on_speech_started(lookback_samples);
+            crate::activity_log::speech_started();
+
+            // Broadcast speech started event
+            broadcast_event(Response::Event {
+                event: EventType::SpeechStarted,
+            });
+        }
+        SpeechStateChange::Ended { duration_ms } => {
+            transcribe.on_speech_ended();
+            crate::activity_log::speech_ended();
+
+            // Broadcast speech ended event
+            broadcast_event(Response::Event {
+                event: EventType::SpeechEnded { duration_ms },
+            });
+        }
+        SpeechStateChange::None => {}
+    }
+
+    // Handle word breaks for timed segment submission
+    if let Some(WordBreakEvent {
+        offset_ms,
+        gap_duration_ms,
+    }) = chunk.word_break
+    {
+        transcribe.on_word_break(offset_ms, gap_duration_ms);
+    }
+}
+
 /// Stop the audio processing loop
 pub fn stop_audio_loop() {
     get_loop_active().store(false, Ordering::SeqCst);
+    AUDIO_LOOP_STOP.stop();
 }
 
-/// Convert multi-channel audio to mono
-fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return samples.to_vec();
+/// Look up the saved calibration for the configured primary source, if any.
+pub(crate) fn current_device_calibration() -> Option<DeviceCalibration> {
+    let source1_id = {
+        let state_arc = crate::state::get_service_state();
+        let state = futures::executor::block_on(state_arc.lock());
+        state.source1_id.clone()
+    }?;
+
+    let calibration = crate::config::current_config()
+        .calibration_for(&source1_id)
+        .cloned()?;
+    info!(
+        "[AudioLoop] Using calibration for {}: voiced={} dB, whisper={} dB, gain={} dB",
+        source1_id,
+        calibration.voiced_threshold_db,
+        calibration.whisper_threshold_db,
+        calibration.gain_db
+    );
+    Some(calibration)
+}
+
+/// Convert processor speech metrics to the IPC representation
+fn to_common_metrics(m: &SpeechMetrics) -> flowstt_common::SpeechMetrics {
+    flowstt_common::SpeechMetrics {
+        amplitude_db: m.amplitude_db,
+        zcr: m.zcr,
+        centroid_hz: m.centroid_hz,
+        is_speaking: m.is_speaking,
+        voiced_onset_pending: m.is_voiced_pending,
+        whisper_onset_pending: m.is_whisper_pending,
+        is_transient: m.is_transient,
+        is_lookback_speech: m.is_lookback_speech,
+        is_word_break: m.is_word_break,
     }
-    samples
-        .chunks(channels)
-        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-        .collect()
 }
 
 /// Broadcaster for speech events
@@ -193,19 +413,7 @@ impl VisualizationCallback for VisualizationBroadcaster {
             spectrogram: payload
                 .spectrogram
                 .map(|s| flowstt_common::SpectrogramColumn { colors: s.colors }),
-            speech_metrics: payload
-                .speech_metrics
-                .map(|m| flowstt_common::SpeechMetrics {
-                    amplitude_db: m.amplitude_db,
-                    zcr: m.zcr,
-                    centroid_hz: m.centroid_hz,
-                    is_speaking: m.is_speaking,
-                    voiced_onset_pending: m.is_voiced_pending,
-                    whisper_onset_pending: m.is_whisper_pending,
-                    is_transient: m.is_transient,
-                    is_lookback_speech: m.is_lookback_speech,
-                    is_word_break: m.is_word_break,
-                }),
+            speech_metrics: payload.speech_metrics.as_ref().map(to_common_metrics),
         };
         broadcast_event(Response::Event {
             event: EventType::VisualizationData(data),
@@ -221,46 +429,196 @@ impl TranscriptionCallback for TranscriptionEventBroadcaster {
         debug!("[Transcription] Started");
     }
 
-    fn on_transcription_complete(&self, text: String, wav_path: Option<String>) {
+    fn on_transcription_complete(&self, text: String, segment: CompletedSegment) {
         let trimmed = text.trim();
         if trimmed.is_empty() || trimmed == "(No speech detected)" {
             debug!("[Transcription] Skipping empty/no-speech result");
+            crate::partial_typing::discard();
             return;
         }
 
-        // Append a trailing space so pasted segments don't merge with adjacent text
-        let text = format!("{} ", trimmed);
+        // Cached config, reloaded when the file changes
+        let config = crate::config::current_config();
 
-        info!("[Transcription] Complete: {}", text);
+        // Pause words are heard even while output is paused
+        let spoken = crate::pause_words::apply(&config.pause_words, trimmed);
+        if spoken.is_empty() {
+            debug!("[Transcription] Output is paused, dropping segment");
+            crate::partial_typing::discard();
+            return;
+        }
+
+        let spelling = crate::spelling::apply(&spoken);
+        if spelling.text.is_empty() {
+            debug!("[Transcription] Segment only switched spelling mode");
+            crate::partial_typing::discard();
+            return;
+        }
 
-        // Add to persistent history and get the enriched entry
-        let history = crate::history::get_history();
-        let entry = {
-            let mut h = history.lock().unwrap();
-            h.add_entry(text.clone(), wav_path)
+        // Spelled text is kept exactly as spelled
+        let mut output = if spelling.spelled {
+            crate::postprocess::filter(&config, &spelling.text)
+        } else {
+            crate::postprocess::apply(&config, &spelling.text)
         };
 
+        // Append a trailing space so pasted segments don't merge with adjacent text
+        output.history.push(' ');
+        output.clipboard.push(' ');
+
+        info!("[Transcription] Complete: {}", output.history);
+
+        let entry = crate::history::TranscriptionHistory::new_entry(output.history, &segment);
+
         broadcast_event(Response::Event {
             event: EventType::TranscriptionComplete(TranscriptionResult {
-                id: Some(entry.id),
+                id: Some(entry.id.clone()),
                 text: entry.text.clone(),
-                timestamp: Some(entry.timestamp),
-                audio_path: entry.wav_path,
+                timestamp: Some(entry.timestamp.clone()),
+                audio_path: entry.wav_path.clone(),
             }),
         });
-
-        // Copy to clipboard and optionally paste into the foreground app.
-        // Config is loaded from disk so runtime changes take effect immediately.
-        let config = crate::config::Config::load();
-        crate::clipboard::copy_and_paste(
-            &entry.text,
-            config.auto_paste_enabled,
-            config.auto_paste_delay_ms,
-        );
+        crate::sound_cues::play(crate::sound_cues::SoundCue::TranscriptionComplete);
+
+        // History, files and the clipboard, in the configured order
+        crate::output_sink::dispatch(crate::output_sink::Delivery {
+            entry,
+            clipboard_text: output.clipboard,
+            completed_at: chrono::Local::now(),
+            config,
+        });
     }
 
     fn on_transcription_error(&self, error: String) {
         error!("[Transcription] Error: {}", error);
+        crate::partial_typing::discard();
+    }
+
+    fn on_retranscription_complete(&self, job: Retranscription, result: Result<String, String>) {
+        let model = job
+            .model_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string());
+
+        // Same clean-up, replacements, redaction and profanity filter as live
+        // results, but no clipboard or paste
+        let updated = result
+            .and_then(|text| {
+                let trimmed = text.trim();
+                if trimmed.is_empty() || trimmed == "(No speech detected)" {
+                    Err("No speech detected".to_string())
+                } else {
+                    Ok(trimmed.to_string())
+                }
+            })
+            .and_then(|text| {
+                let config = crate::config::current_config();
+                // Trailing space, as on live results
+                let history = format!("{} ", crate::postprocess::apply(&config, &text).history);
+                crate::history::get_history()
+                    .lock()
+                    .unwrap()
+                    .update_transcript(&job.entry_id, history, model)
+                    .ok_or_else(|| "History entry no longer exists".to_string())
+            });
+
+        match updated {
+            Ok(entry) => {
+                info!(
+                    "[Transcription] Re-transcribed {}: {}",
+                    entry.id, entry.text
+                );
+                broadcast_event(Response::Event {
+                    event: EventType::HistoryEntryUpdated {
+                        entry: entry.to_common(),
+                    },
+                });
+            }
+            Err(error) => {
+                warn!(
+                    "[Transcription] Re-transcribing {} failed: {}",
+                    job.entry_id, error
+                );
+                broadcast_event(Response::Event {
+                    event: EventType::RetranscriptionFailed {
+                        id: job.entry_id,
+                        error,
+                    },
+                });
+            }
+        }
+    }
+
+    fn on_stream_transcription_complete(
+        &self,
+        segment: StreamSegment,
+        result: Result<String, String>,
+    ) {
+        crate::audio_stream::report(segment, result);
+    }
+
+    fn on_meeting_transcription_complete(
+        &self,
+        segment: MeetingSegment,
+        result: Result<String, String>,
+    ) {
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("[Transcription] Meeting segment failed: {}", e);
+                return;
+            }
+        };
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed == "(No speech detected)" {
+            debug!("[Transcription] Skipping empty/no-speech meeting segment");
+            return;
+        }
+
+        // Same replacements, redaction and profanity filter as live results;
+        // meeting lines go to the meeting's timeline, not history or the
+        // clipboard
+        let config = crate::config::current_config();
+        let output = crate::postprocess::apply(&config, trimmed);
+
+        let meeting_id = segment.meeting_id.clone();
+        let line = match crate::meeting::add_line(segment, output.history) {
+            Some(line) => line,
+            None => {
+                debug!("[Transcription] Meeting {} no longer exists", meeting_id);
+                return;
+            }
+        };
+        info!(
+            "[Transcription] Meeting line ({}): {}",
+            line.source.label(),
+            line.text
+        );
+        broadcast_event(Response::Event {
+            event: EventType::MeetingLine { meeting_id, line },
+        });
+    }
+
+    fn on_interim_transcription(&self, segment: InterimSegment, result: Result<String, String>) {
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                debug!("[Transcription] Interim transcription failed: {}", e);
+                return;
+            }
+        };
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed == "(No speech detected)" {
+            return;
+        }
+
+        // Same replacements, redaction and profanity filter as the final
+        // result, so little changes when it arrives
+        let config = crate::config::current_config();
+        let output = crate::postprocess::apply(&config, trimmed);
+        debug!("[Transcription] Interim: {}", output.clipboard);
+        crate::partial_typing::type_interim(segment, output.clipboard, config);
     }
 
     fn on_transcription_finished(&self) {
@@ -0,0 +1,376 @@
+//! Audio streamed in by a client.
+//!
+//! `flowstt transcribe-stdin` reads audio from a pipe and sends it with the
+//! `StartAudioStream`, `StreamAudio` and `EndAudioStream` requests. The
+//! stream gets its own speech detector to cut it into segments, which are
+//! transcribed on the regular queue. Results are broadcast as
+//! `StreamTranscription` events; they don't go to history or the clipboard.
+//!
+//! Several clients can stream at once. Chunks name their stream, and a
+//! stream is dropped when the connection that started it closes.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Response};
+use tracing::{info, warn};
+
+use crate::audio::convert_to_mono;
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::get_transcription_queue;
+use crate::processor::{SpeechDetector, SpeechStateChange};
+use crate::transcription::queue::QueuedSegment;
+
+/// Audio kept before speech starts while the stream is silent, in seconds;
+/// longer than the speech detector's lookback
+const SILENCE_KEEP_SECS: u32 = 1;
+
+/// Longest segment; longer speech is cut into several segments
+const MAX_SEGMENT_SECS: u32 = 30;
+
+/// Shortest segment worth transcribing, in milliseconds
+const MIN_SEGMENT_MS: u32 = 250;
+
+/// Longest wait for room in the transcription queue before checking for
+/// shutdown again
+const QUEUE_WAIT_LIMIT: Duration = Duration::from_millis(500);
+
+/// A segment of a client audio stream, queued for transcription.
+#[derive(Debug, Clone)]
+pub struct StreamSegment {
+    /// Stream the segment belongs to
+    pub stream_id: String,
+    /// When the segment started, in milliseconds from the start of the stream
+    pub offset_ms: u64,
+}
+
+struct AudioStream {
+    id: String,
+    sample_rate: u32,
+    channels: u16,
+    detector: SpeechDetector,
+    /// Mono audio since the last segment was cut
+    pending: Vec<f32>,
+    /// Position of `pending[0]` in the stream, in samples
+    pending_offset: u64,
+    /// Index in `pending` where the speech in progress started
+    speech_start: Option<usize>,
+    /// Interleaved samples short of a full detector frame
+    partial: Vec<f32>,
+    /// Segments queued so far
+    segments: u32,
+}
+
+impl AudioStream {
+    fn new(id: String, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            id,
+            sample_rate,
+            channels,
+            detector: SpeechDetector::new(sample_rate),
+            pending: Vec::new(),
+            pending_offset: 0,
+            speech_start: None,
+            partial: Vec::new(),
+            segments: 0,
+        }
+    }
+
+    /// Samples the speech detector looks at at once (10ms)
+    fn frame_len(&self) -> usize {
+        (self.sample_rate / 100) as usize
+    }
+
+    /// Run interleaved samples through the speech detector and return the
+    /// segments that ended.
+    fn push(&mut self, samples: &[f32]) -> Vec<(StreamSegment, Vec<f32>)> {
+        self.partial.extend_from_slice(samples);
+        let frame_samples = self.frame_len() * self.channels as usize;
+        let whole = self.partial.len() / frame_samples * frame_samples;
+        let interleaved: Vec<f32> = self.partial.drain(..whole).collect();
+
+        let mut ended = Vec::new();
+        for frame in interleaved.chunks(frame_samples) {
+            let mono = convert_to_mono(frame, self.channels as usize);
+            self.pending.extend_from_slice(&mono);
+            self.detector.process(&mono);
+
+            match self.detector.take_state_change() {
+                SpeechStateChange::Started { lookback_samples } => {
+                    self.speech_start = Some(self.pending.len().saturating_sub(lookback_samples));
+                }
+                SpeechStateChange::Ended { .. } => {
+                    ended.extend(self.cut(false));
+                }
+                SpeechStateChange::None => {}
+            }
+
+            let max_samples = (self.sample_rate * MAX_SEGMENT_SECS) as usize;
+            if self
+                .speech_start
+                .is_some_and(|start| self.pending.len() - start >= max_samples)
+            {
+                ended.extend(self.cut(true));
+            }
+
+            // Only the audio a segment could start in is needed while silent
+            let keep = (self.sample_rate * SILENCE_KEEP_SECS) as usize;
+            if self.speech_start.is_none() && self.pending.len() > keep * 2 {
+                let drop = self.pending.len() - keep;
+                self.pending.drain(..drop);
+                self.pending_offset += drop as u64;
+            }
+        }
+        ended
+    }
+
+    /// Cut the speech in progress into a segment, if there is any; with
+    /// `continued` the next segment starts where this one ends.
+    fn cut(&mut self, continued: bool) -> Option<(StreamSegment, Vec<f32>)> {
+        let start = self.speech_start.take()?;
+        let samples = self.pending.split_off(start);
+        let offset = self.pending_offset + start as u64;
+        self.pending_offset = offset + samples.len() as u64;
+        self.pending.clear();
+        if continued {
+            self.speech_start = Some(0);
+        }
+
+        let min_samples = (self.sample_rate * MIN_SEGMENT_MS / 1000) as usize;
+        if samples.len() < min_samples {
+            return None;
+        }
+        self.segments += 1;
+        let segment = StreamSegment {
+            stream_id: self.id.clone(),
+            offset_ms: offset * 1000 / u64::from(self.sample_rate),
+        };
+        Some((segment, samples))
+    }
+}
+
+/// Streams in progress; rarely more than one
+static STREAMS: Mutex<Vec<AudioStream>> = Mutex::new(Vec::new());
+
+/// Remove a stream from `streams`
+fn take(streams: &mut Vec<AudioStream>, stream_id: &str) -> Option<AudioStream> {
+    let index = streams.iter().position(|stream| stream.id == stream_id)?;
+    Some(streams.swap_remove(index))
+}
+
+fn unknown(stream_id: &str) -> String {
+    format!("No audio stream {} is in progress", stream_id)
+}
+
+/// Start a stream. Returns its ID.
+pub fn start(sample_rate: u32, channels: u16) -> String {
+    let id = crate::history::generate_id();
+    STREAMS
+        .lock()
+        .unwrap()
+        .push(AudioStream::new(id.clone(), sample_rate, channels));
+    info!(
+        "[AudioStream] Started {} ({} Hz, {} channels)",
+        id, sample_rate, channels
+    );
+    id
+}
+
+/// Add a chunk of interleaved 16-bit PCM to a stream, waiting for room in
+/// the transcription queue if a segment ended.
+pub fn push(stream_id: &str, samples: &[i16]) -> Result<(), String> {
+    let samples: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+    let (ended, sample_rate) = {
+        let mut streams = STREAMS.lock().unwrap();
+        let stream = streams
+            .iter_mut()
+            .find(|stream| stream.id == stream_id)
+            .ok_or_else(|| unknown(stream_id))?;
+        (stream.push(&samples), stream.sample_rate)
+    };
+    for (segment, samples) in ended {
+        enqueue(segment, samples, sample_rate);
+    }
+    Ok(())
+}
+
+/// End a stream, queueing the speech in progress. Returns how many
+/// segments were queued over the whole stream.
+pub fn end(stream_id: &str) -> Result<u32, String> {
+    let mut stream =
+        take(&mut STREAMS.lock().unwrap(), stream_id).ok_or_else(|| unknown(stream_id))?;
+    if let Some((segment, samples)) = stream.cut(false) {
+        enqueue(segment, samples, stream.sample_rate);
+    }
+    info!(
+        "[AudioStream] Ended {} after {} segments",
+        stream.id, stream.segments
+    );
+    Ok(stream.segments)
+}
+
+/// Drop a stream without transcribing the speech in progress, if it hasn't
+/// ended; for when its client goes away.
+pub fn discard(stream_id: &str) {
+    if take(&mut STREAMS.lock().unwrap(), stream_id).is_some() {
+        warn!(
+            "[AudioStream] Dropped {}; its client disconnected",
+            stream_id
+        );
+    }
+}
+
+/// Queue a segment, waiting for room rather than dropping audio the client
+/// has already sent.
+fn enqueue(segment: StreamSegment, samples: Vec<f32>, sample_rate: u32) {
+    let queue = get_transcription_queue();
+    while !queue.wait_for_room(QUEUE_WAIT_LIMIT) && !crate::is_shutdown_requested() {}
+
+    let queued = QueuedSegment {
+        samples,
+        sample_rate,
+        channels: 1,
+        wav_path: None,
+        ephemeral: crate::privacy::is_privacy_mode(),
+        metadata: None,
+        retranscription: None,
+        meeting: None,
+        stream: Some(segment.clone()),
+        interim: None,
+    };
+    if !queue.enqueue(queued) {
+        report(segment, Err("Transcription queue is full".to_string()));
+    }
+}
+
+/// Broadcast the result of transcribing a segment. Every queued segment is
+/// reported, so clients can tell when the last one is done.
+pub(crate) fn report(segment: StreamSegment, result: Result<String, String>) {
+    let text = match result {
+        Ok(text) if text.trim() != "(No speech detected)" => {
+            // Same replacements, redaction and profanity filter as live
+            // results; the client outputs the text, like a paste
+            let config = crate::config::current_config();
+            crate::postprocess::apply(&config, text.trim()).clipboard
+        }
+        Ok(_) => String::new(),
+        Err(e) => {
+            warn!("[AudioStream] Segment failed: {}", e);
+            String::new()
+        }
+    };
+    broadcast_event(Response::Event {
+        event: EventType::StreamTranscription {
+            stream_id: segment.stream_id,
+            offset_ms: segment.offset_ms,
+            text,
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(secs: f32) -> Vec<f32> {
+        (0..(RATE as f32 * secs) as usize)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 500.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence(secs: f32) -> Vec<f32> {
+        vec![0.0; (RATE as f32 * secs) as usize]
+    }
+
+    /// Push mono audio as `channels` identical channels, in chunks that
+    /// don't line up with detector frames
+    fn feed(stream: &mut AudioStream, mono: &[f32]) -> Vec<(StreamSegment, Vec<f32>)> {
+        let channels = stream.channels as usize;
+        let interleaved: Vec<f32> = mono
+            .iter()
+            .flat_map(|&s| std::iter::repeat_n(s, channels))
+            .collect();
+        interleaved
+            .chunks(37 * channels)
+            .flat_map(|chunk| stream.push(chunk))
+            .collect()
+    }
+
+    #[test]
+    fn speech_between_silences_is_one_segment() {
+        for channels in [1, 2] {
+            let mut stream = AudioStream::new("s".to_string(), RATE, channels);
+            let mut audio = silence(3.0);
+            audio.extend(tone(1.0));
+            audio.extend(silence(1.0));
+
+            let segments = feed(&mut stream, &audio);
+            assert_eq!(segments.len(), 1);
+            let (segment, samples) = &segments[0];
+            assert_eq!(segment.stream_id, "s");
+            // Starts in the lookback before the tone, in mono samples
+            assert!((2000..=3000).contains(&segment.offset_ms), "{:?}", segment);
+            assert!(samples.len() >= RATE as usize);
+            assert_eq!(stream.segments, 1);
+        }
+    }
+
+    #[test]
+    fn long_speech_is_split_into_contiguous_segments() {
+        let mut stream = AudioStream::new("s".to_string(), RATE, 1);
+        let mut audio = tone(MAX_SEGMENT_SECS as f32 * 2.0 + 5.0);
+        audio.extend(silence(1.0));
+
+        let segments = feed(&mut stream, &audio);
+        assert_eq!(segments.len(), 3);
+        let max_samples = (RATE * MAX_SEGMENT_SECS) as usize;
+        for pair in segments.windows(2) {
+            let (first, samples) = &pair[0];
+            assert_eq!(samples.len(), max_samples);
+            assert_eq!(
+                pair[1].0.offset_ms,
+                first.offset_ms + u64::from(MAX_SEGMENT_SECS) * 1000
+            );
+        }
+    }
+
+    #[test]
+    fn short_segments_are_dropped_but_keep_their_place() {
+        let mut stream = AudioStream::new("s".to_string(), RATE, 1);
+        let min_samples = (RATE * MIN_SEGMENT_MS / 1000) as usize;
+
+        stream.pending = silence(1.0);
+        stream.speech_start = Some(stream.pending.len() - (min_samples - 1));
+        assert!(stream.cut(false).is_none());
+        assert_eq!(stream.segments, 0);
+        assert_eq!(stream.pending_offset, u64::from(RATE));
+
+        stream.pending = silence(0.5);
+        stream.speech_start = Some(0);
+        let (segment, samples) = stream.cut(false).unwrap();
+        assert_eq!(segment.offset_ms, 1000);
+        assert_eq!(samples.len(), RATE as usize / 2);
+        assert_eq!(stream.segments, 1);
+    }
+
+    #[test]
+    fn silence_is_trimmed_without_losing_the_offset() {
+        let mut stream = AudioStream::new("s".to_string(), RATE, 1);
+        assert!(feed(&mut stream, &silence(10.0)).is_empty());
+        let keep = (RATE * SILENCE_KEEP_SECS) as usize;
+        assert!(stream.pending.len() <= keep * 2);
+        assert_eq!(
+            stream.pending_offset + stream.pending.len() as u64,
+            u64::from(RATE) * 10
+        );
+    }
+
+    #[test]
+    fn unknown_streams_are_rejected() {
+        assert!(push("missing", &[0; 160]).unwrap_err().contains("missing"));
+        assert!(end("missing").is_err());
+        discard("missing");
+    }
+}
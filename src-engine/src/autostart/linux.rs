@@ -0,0 +1,85 @@
+//! Linux autostart.
+//!
+//! Writes `flowstt.desktop` to the XDG autostart directory, which desktop
+//! environments following the XDG Autostart specification start at login.
+
+use super::AutostartBackend;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ENTRY_FILE: &str = "flowstt.desktop";
+
+pub struct LinuxAutostart;
+
+impl AutostartBackend for LinuxAutostart {
+    fn is_enabled(&self) -> bool {
+        entry_path().is_some_and(|path| {
+            fs::read_to_string(path).is_ok_and(|entry| {
+                !entry.lines().any(|line| {
+                    line.trim() == "Hidden=true" || line.trim() == "X-GNOME-Autostart-enabled=false"
+                })
+            })
+        })
+    }
+
+    fn enable(&self, exe: &Path, args: &[&str]) -> Result<(), String> {
+        let path = entry_path().ok_or("Failed to determine the autostart directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+
+        let exec = std::iter::once(exe.to_string_lossy().as_ref())
+            .chain(args.iter().copied())
+            .map(quote_exec_arg)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=FlowSTT\n\
+             Comment=Voice transcription\n\
+             Exec={}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            exec
+        );
+        fs::write(&path, entry).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        let Some(path) = entry_path() else {
+            return Ok(());
+        };
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove {:?}: {}", path, e)),
+        }
+    }
+}
+
+fn entry_path() -> Option<PathBuf> {
+    flowstt_common::paths::login_items_dir().map(|dir| dir.join(ENTRY_FILE))
+}
+
+/// Quote an argument for the `Exec` key of a desktop entry.
+fn quote_exec_arg(arg: &str) -> String {
+    // Field codes start with `%`, even inside quotes
+    let arg = arg.replace('%', "%%");
+    if !arg
+        .chars()
+        .any(|c| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c))
+    {
+        return arg;
+    }
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // The key file's own escaping applies on top of the quoting
+    quoted.replace('\\', "\\\\")
+}
@@ -0,0 +1,71 @@
+//! macOS autostart.
+//!
+//! Writes a LaunchAgent to `~/Library/LaunchAgents`, which launchd runs at
+//! login.
+
+use super::AutostartBackend;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LABEL: &str = "io.flowstt";
+
+pub struct MacOSAutostart;
+
+impl AutostartBackend for MacOSAutostart {
+    fn is_enabled(&self) -> bool {
+        agent_path().is_some_and(|path| path.exists())
+    }
+
+    fn enable(&self, exe: &Path, args: &[&str]) -> Result<(), String> {
+        let path = agent_path().ok_or("Failed to determine the LaunchAgents directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+
+        let arguments: String = std::iter::once(exe.to_string_lossy().as_ref())
+            .chain(args.iter().copied())
+            .map(|arg| format!("        <string>{}</string>\n", escape_xml(arg)))
+            .collect();
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#,
+            LABEL, arguments
+        );
+        fs::write(&path, plist).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        let Some(path) = agent_path() else {
+            return Ok(());
+        };
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove {:?}: {}", path, e)),
+        }
+    }
+}
+
+fn agent_path() -> Option<PathBuf> {
+    flowstt_common::paths::login_items_dir().map(|dir| dir.join(format!("{}.plist", LABEL)))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
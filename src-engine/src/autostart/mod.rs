@@ -0,0 +1,82 @@
+//! Starting FlowSTT at login.
+//!
+//! `SetAutostart` registers the running app binary with the desktop so it
+//! starts in the tray (`--headless`) when the user logs in: a `Run` registry
+//! value on Windows, a LaunchAgent on macOS and an XDG autostart entry on
+//! Linux. The registration itself is the setting; nothing is kept in the
+//! config file, so removing it in system settings is picked up too.
+//!
+//! Platform-specific registration lives in submodules following the same
+//! backend-trait pattern used by `crate::power`.
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use std::path::Path;
+
+use tracing::info;
+
+/// Arguments the app is started with at login
+const LOGIN_ARGS: &[&str] = &["--headless"];
+
+/// Platform-agnostic login item backend.
+pub trait AutostartBackend: Send + Sync {
+    /// Whether FlowSTT is registered to start at login.
+    fn is_enabled(&self) -> bool;
+
+    /// Register `exe` to start with `args` at login, replacing any earlier
+    /// registration.
+    fn enable(&self, exe: &Path, args: &[&str]) -> Result<(), String>;
+
+    /// Remove the registration, if there is one.
+    fn disable(&self) -> Result<(), String>;
+}
+
+/// Create the platform-specific backend.
+fn create_backend() -> Box<dyn AutostartBackend> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsAutostart)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::MacOSAutostart)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxAutostart)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        compile_error!("Unsupported platform for autostart");
+    }
+}
+
+/// Whether FlowSTT starts at login.
+pub fn is_enabled() -> bool {
+    create_backend().is_enabled()
+}
+
+/// Start FlowSTT at login, or stop doing so.
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let backend = create_backend();
+    if enabled {
+        let exe = std::env::current_exe()
+            .map_err(|e| format!("Failed to locate the FlowSTT executable: {}", e))?;
+        backend.enable(&exe, LOGIN_ARGS)?;
+        info!("[Autostart] Registered {:?} to start at login", exe);
+    } else {
+        backend.disable()?;
+        info!("[Autostart] Removed the login registration");
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Windows autostart.
+//!
+//! Sets a `FlowSTT` value under the user's
+//! `Software\Microsoft\Windows\CurrentVersion\Run` registry key, whose
+//! commands Windows runs at login.
+
+use super::AutostartBackend;
+use std::path::Path;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows::Win32::System::Registry::{
+    RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ,
+};
+
+const RUN_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+const VALUE_NAME: PCWSTR = w!("FlowSTT");
+
+pub struct WindowsAutostart;
+
+impl AutostartBackend for WindowsAutostart {
+    fn is_enabled(&self) -> bool {
+        unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                RUN_KEY,
+                VALUE_NAME,
+                RRF_RT_REG_SZ,
+                None,
+                None,
+                None,
+            )
+        }
+        .is_ok()
+    }
+
+    fn enable(&self, exe: &Path, args: &[&str]) -> Result<(), String> {
+        let mut command = format!("\"{}\"", exe.display());
+        for arg in args {
+            command.push(' ');
+            command.push_str(arg);
+        }
+        let data: Vec<u16> = command.encode_utf16().chain(Some(0)).collect();
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                RUN_KEY,
+                VALUE_NAME,
+                REG_SZ.0,
+                Some(data.as_ptr().cast()),
+                (data.len() * 2) as u32,
+            )
+        };
+        status
+            .ok()
+            .map_err(|e| format!("Failed to write the Run registry value: {}", e))
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, RUN_KEY, VALUE_NAME) };
+        if status == ERROR_FILE_NOT_FOUND {
+            return Ok(());
+        }
+        status
+            .ok()
+            .map_err(|e| format!("Failed to remove the Run registry value: {}", e))
+    }
+}
@@ -0,0 +1,375 @@
+//! Backup and restore of FlowSTT data.
+//!
+//! A backup is a zip archive holding:
+//!
+//! - `manifest.json`: the backup format version, the FlowSTT version that
+//!   wrote it, when, and what it holds
+//! - `config.json`: the config file, including rules and the dictionary
+//! - `history.json`, `meetings.json` and `activity.jsonl`
+//! - `recordings/`: the saved recordings of history entries, if asked for
+//!
+//! Files are stored decrypted so a backup can be restored on another
+//! machine, where the history key in the keychain is different; restoring
+//! encrypts them again if `encrypt_history` is on. Restoring replaces each
+//! file the backup holds and leaves the others alone. Backups written by a
+//! newer format are refused, and files this version doesn't know are
+//! skipped, so newer versions can add to the archive without breaking older
+//! ones.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use flowstt_common::MeetingRecord;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::config::{current_config, update_config, Config};
+use crate::history::{get_history, HistoryEntry, TranscriptionHistory};
+use crate::history_crypto;
+
+/// Version of the archive layout, raised when a change would be misread by
+/// older versions
+const FORMAT_VERSION: u32 = 1;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const MANIFEST: &str = "manifest.json";
+const CONFIG: &str = "config.json";
+const HISTORY: &str = "history.json";
+const MEETINGS: &str = "meetings.json";
+const ACTIVITY: &str = "activity.jsonl";
+
+/// Directory of the recordings inside the archive
+const RECORDINGS: &str = "recordings/";
+
+/// What a backup holds, stored as `manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    /// Archive layout version
+    format: u32,
+    /// FlowSTT version that wrote the backup
+    #[serde(default)]
+    app_version: String,
+    /// RFC 3339 UTC time the backup was made
+    #[serde(default)]
+    created_at: String,
+    /// Files in the archive, other than the manifest
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// What a backup held or now holds.
+#[derive(Debug)]
+pub struct BackupContents {
+    /// RFC 3339 UTC time the backup was made
+    pub created_at: String,
+    /// Number of history entries
+    pub history_entries: usize,
+    /// Number of recordings
+    pub recordings: usize,
+}
+
+/// Write a backup of the config, history, meetings and activity log, and
+/// optionally the recordings, to `path`.
+pub fn create(path: &Path, include_recordings: bool) -> Result<BackupContents, String> {
+    let result = write_archive(path, include_recordings);
+    if result.is_err() {
+        // Don't leave a partial archive that looks like a backup
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn write_archive(path: &Path, include_recordings: bool) -> Result<BackupContents, String> {
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let mut files = Vec::new();
+    let mut add = |name: &str, data: &[u8]| -> Result<(), String> {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(name, options)
+            .and_then(|()| zip.write_all(data).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {} to the backup: {}", name, e))?;
+        files.push(name.to_string());
+        Ok(())
+    };
+
+    let config_path = Config::config_path();
+    if let Some(config) = read_optional(&config_path, |path| {
+        fs::read(path).map_err(|e| e.to_string())
+    })? {
+        add(CONFIG, &config)?;
+    }
+
+    let mut entries: Vec<HistoryEntry> = {
+        let history = get_history();
+        let history = history.lock().unwrap();
+        let entries = history.get_entries().iter();
+        entries.filter(|e| !e.ephemeral).cloned().collect()
+    };
+    // Archived entries point at their recording inside the archive
+    let mut recordings = 0;
+    for entry in entries.iter_mut().filter(|_| include_recordings) {
+        let Some(wav_path) = entry.wav_path.clone() else {
+            continue;
+        };
+        let Some(name) = Path::new(&wav_path).file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        match history_crypto::read_file(Path::new(&wav_path)) {
+            Ok(data) => {
+                let archived = format!("{}{}", RECORDINGS, name);
+                add(&archived, &data)?;
+                entry.wav_path = Some(archived);
+                recordings += 1;
+            }
+            Err(e) => warn!("[Backup] Skipping recording {}: {}", wav_path, e),
+        }
+    }
+    let history = serde_json::to_vec_pretty(&entries)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    add(HISTORY, &history)?;
+
+    let data_dir = TranscriptionHistory::data_dir();
+    for name in [MEETINGS, ACTIVITY] {
+        if let Some(data) = read_optional(&data_dir.join(name), history_crypto::read_file)? {
+            add(name, &data)?;
+        }
+    }
+
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        app_version: VERSION.to_string(),
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        files,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file(MANIFEST, SimpleFileOptions::default())
+        .and_then(|()| zip.write_all(&manifest_json).map_err(Into::into))
+        .and_then(|()| zip.finish().map(drop))
+        .map_err(|e| format!("Failed to write the backup: {}", e))?;
+
+    info!(
+        "[Backup] Wrote {} history entries and {} recordings to {}",
+        entries.len(),
+        recordings,
+        path.display()
+    );
+    Ok(BackupContents {
+        created_at: manifest.created_at,
+        history_entries: entries.len(),
+        recordings,
+    })
+}
+
+/// Read a file that may not exist.
+fn read_optional(
+    path: &Path,
+    read: impl Fn(&Path) -> Result<Vec<u8>, String>,
+) -> Result<Option<Vec<u8>>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    read(path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Replace the data on this machine with what the backup at `path` holds.
+/// Nothing is replaced unless every file in the backup can be read.
+/// Settings that are applied at startup take effect after a restart.
+pub fn restore(path: &Path) -> Result<BackupContents, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut zip =
+        ZipArchive::new(file).map_err(|e| format!("{} is not a backup: {}", path.display(), e))?;
+
+    let manifest: Manifest = serde_json::from_slice(
+        &read_entry(&mut zip, MANIFEST)?
+            .ok_or_else(|| format!("{} is not a FlowSTT backup", path.display()))?,
+    )
+    .map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!(
+            "The backup was made by FlowSTT {} in a newer format; update FlowSTT to restore it",
+            manifest.app_version
+        ));
+    }
+
+    // Check everything parses before anything is replaced
+    let config: Option<Config> = read_entry(&mut zip, CONFIG)?
+        .map(|data| Config::from_json(&data))
+        .transpose()
+        .map_err(|e| format!("Invalid config in backup: {}", e))?;
+    let mut entries: Option<Vec<HistoryEntry>> = read_entry(&mut zip, HISTORY)?
+        .map(|data| serde_json::from_slice(&data))
+        .transpose()
+        .map_err(|e| format!("Invalid history in backup: {}", e))?;
+    let meetings = read_entry(&mut zip, MEETINGS)?;
+    if let Some(meetings) = &meetings {
+        serde_json::from_slice::<Vec<MeetingRecord>>(meetings)
+            .map_err(|e| format!("Invalid meetings in backup: {}", e))?;
+    }
+    let activity = read_entry(&mut zip, ACTIVITY)?;
+
+    if let Some(entries) = &mut entries {
+        forget_unarchived_recordings(entries, |name| zip.index_for_name(name).is_some());
+    }
+
+    if let Some(config) = config {
+        update_config(|current| *current = config)
+            .map_err(|e| format!("Failed to save the restored config: {}", e))?;
+    }
+
+    // The restored config may move the recordings directory or turn on
+    // encryption
+    let recordings_dir = TranscriptionHistory::recordings_dir();
+    let encrypted = current_config().encrypt_history;
+    let mut recordings = 0;
+    let history_entries = match entries {
+        Some(mut entries) => {
+            for entry in &mut entries {
+                let Some(archived) = entry.wav_path.take() else {
+                    continue;
+                };
+                match restore_recording(&mut zip, &archived, &recordings_dir, encrypted) {
+                    Ok(path) => {
+                        entry.wav_path = Some(path.to_string_lossy().into_owned());
+                        recordings += 1;
+                    }
+                    Err(e) => {
+                        warn!("[Backup] Skipping recording {}: {}", archived, e);
+                        entry.audio_bytes = None;
+                    }
+                }
+            }
+            let count = entries.len();
+            get_history().lock().unwrap().restore(entries)?;
+            count
+        }
+        None => get_history().lock().unwrap().get_entries().len(),
+    };
+
+    let data_dir = TranscriptionHistory::data_dir();
+    if let Some(data) = meetings {
+        history_crypto::write_file(&data_dir.join(MEETINGS), &data, encrypted)?;
+    }
+    // The activity log is appended to in plaintext
+    if let Some(data) = activity {
+        let path = data_dir.join(ACTIVITY);
+        fs::write(&path, data).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    }
+
+    info!(
+        "[Backup] Restored {} history entries and {} recordings from {} (made {})",
+        history_entries,
+        recordings,
+        path.display(),
+        manifest.created_at
+    );
+    Ok(BackupContents {
+        created_at: manifest.created_at,
+        history_entries,
+        recordings,
+    })
+}
+
+/// Take the recording away from entries whose recording isn't in the
+/// archive, according to `archived`.
+fn forget_unarchived_recordings(entries: &mut [HistoryEntry], archived: impl Fn(&str) -> bool) {
+    for entry in entries {
+        let in_archive = entry.wav_path.as_deref().is_some_and(|path| {
+            path.strip_prefix(RECORDINGS).is_some_and(is_plain_name) && archived(path)
+        });
+        if !in_archive {
+            entry.wav_path = None;
+            entry.audio_bytes = None;
+        }
+    }
+}
+
+/// Write the recording at `archived` in the archive to `dir`, returning
+/// where it was written.
+fn restore_recording(
+    zip: &mut ZipArchive<File>,
+    archived: &str,
+    dir: &Path,
+    encrypted: bool,
+) -> Result<PathBuf, String> {
+    let name = archived.strip_prefix(RECORDINGS).ok_or("not a recording")?;
+    let data = read_entry(zip, archived)?.ok_or("not in the backup")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let path = dir.join(name);
+    history_crypto::write_file(&path, &data, encrypted)?;
+    Ok(path)
+}
+
+/// Read a file from the archive, or `None` if it isn't there.
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let mut file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {} from the backup: {}", name, e)),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {} from the backup: {}", name, e))?;
+    Ok(Some(data))
+}
+
+/// Whether an archived file name can be written into a directory as is,
+/// without reaching outside it.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+        && Path::new(name).file_name().is_some_and(|n| n == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recordings_cannot_escape_their_directory() {
+        assert!(is_plain_name("20240501-120000.wav"));
+        assert!(!is_plain_name("../config.json"));
+        assert!(!is_plain_name("..\\config.json"));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name(""));
+    }
+
+    #[test]
+    fn entries_lose_recordings_missing_from_the_archive() {
+        let mut entries: Vec<HistoryEntry> = serde_json::from_value(serde_json::json!([
+            {"id": "a", "text": "kept", "timestamp": "2024-05-01T12:00:00.000Z",
+             "wav_path": "recordings/a.wav", "audio_bytes": 44},
+            {"id": "b", "text": "missing", "timestamp": "2024-05-01T12:00:01.000Z",
+             "wav_path": "recordings/b.wav", "audio_bytes": 44},
+            {"id": "c", "text": "outside", "timestamp": "2024-05-01T12:00:02.000Z",
+             "wav_path": "/home/me/c.wav", "audio_bytes": 44},
+        ]))
+        .unwrap();
+        forget_unarchived_recordings(&mut entries, |name| name == "recordings/a.wav");
+
+        assert_eq!(entries[0].wav_path.as_deref(), Some("recordings/a.wav"));
+        assert_eq!(entries[0].audio_bytes, Some(44));
+        for entry in &entries[1..] {
+            assert!(entry.wav_path.is_none());
+            assert!(entry.audio_bytes.is_none());
+        }
+    }
+
+    #[test]
+    fn manifest_ignores_fields_it_does_not_know() {
+        let manifest: Manifest =
+            serde_json::from_str(r#"{"format": 1, "created_at": "x", "checksums": {}}"#).unwrap();
+        assert_eq!(manifest.format, 1);
+        assert!(manifest.files.is_empty());
+    }
+}
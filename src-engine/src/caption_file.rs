@@ -0,0 +1,174 @@
+//! Rolling caption file for streaming overlays.
+//!
+//! With `caption_file.path` set in the config file, the latest transcribed
+//! text is kept in that file, wrapped to `max_line_chars` and cut to the
+//! last `max_lines` lines, for a text source in OBS or similar to display.
+//! Text older than `window_secs` is dropped, clearing the file once the
+//! speaker goes quiet. The file is written to a temporary file and renamed
+//! into place, so a reader never sees it half written.
+//!
+//! Ephemeral (privacy mode) transcriptions are never shown.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use flowstt_common::config::CaptionFileSettings;
+use tracing::warn;
+
+use crate::config::current_config;
+
+/// How often expired captions are looked for
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A transcription shown in the caption file.
+struct Caption {
+    added: Instant,
+    text: String,
+}
+
+static CAPTIONS: Mutex<VecDeque<Caption>> = Mutex::new(VecDeque::new());
+
+/// Add a transcription to the caption file.
+pub fn add(settings: &CaptionFileSettings, text: &str) {
+    let Some(path) = settings.path.as_deref() else {
+        return;
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return;
+    }
+
+    let mut captions = CAPTIONS.lock().unwrap();
+    captions.push_back(Caption {
+        added: Instant::now(),
+        text,
+    });
+    expire(&mut captions, settings);
+
+    // Drop captions that newer text has pushed off the top
+    let capacity = settings.max_line_chars as usize * settings.max_lines.max(1) as usize;
+    while settings.max_line_chars > 0
+        && captions
+            .iter()
+            .skip(1)
+            .map(|c| c.text.chars().count())
+            .sum::<usize>()
+            >= capacity
+    {
+        captions.pop_front();
+    }
+
+    write(path, &captions, settings);
+}
+
+/// Clear expired captions from the file until shutdown.
+pub async fn run_caption_expiry() {
+    let mut interval = tokio::time::interval(EXPIRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        let mut captions = CAPTIONS.lock().unwrap();
+        if captions.is_empty() {
+            continue;
+        }
+        let config = current_config();
+        let settings = &config.caption_file;
+        let Some(path) = settings.path.as_deref() else {
+            captions.clear();
+            continue;
+        };
+        if expire(&mut captions, &settings) {
+            write(path, &captions, &settings);
+        }
+    }
+}
+
+/// Drop captions older than the window; returns whether any were dropped.
+fn expire(captions: &mut VecDeque<Caption>, settings: &CaptionFileSettings) -> bool {
+    if settings.window_secs == 0 {
+        return false;
+    }
+    let window = Duration::from_secs(u64::from(settings.window_secs));
+    let before = captions.len();
+    captions.retain(|caption| caption.added.elapsed() < window);
+    captions.len() != before
+}
+
+fn write(path: &str, captions: &VecDeque<Caption>, settings: &CaptionFileSettings) {
+    let text = captions
+        .iter()
+        .map(|caption| caption.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let lines = wrap(&text, settings.max_line_chars as usize);
+    let keep = settings.max_lines.max(1) as usize;
+    let contents = lines[lines.len().saturating_sub(keep)..].join("\n");
+
+    if let Err(e) = replace_file(Path::new(path), &contents) {
+        warn!("[CaptionFile] Failed to write {}: {}", path, e);
+    }
+}
+
+/// Replace the contents of `path` in one step.
+fn replace_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Wrap `text` into lines of at most `width` characters, breaking at spaces
+/// and splitting words longer than a line. A width of 0 doesn't wrap.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        let mut chars: Vec<char> = word.chars().collect();
+        if line_len > 0 && line_len + 1 + chars.len() > width {
+            lines.push(std::mem::take(&mut line));
+            line_len = 0;
+        }
+        while chars.len() > width {
+            lines.push(chars.drain(..width).collect());
+        }
+        if line_len > 0 {
+            line.push(' ');
+            line_len += 1;
+        }
+        line_len += chars.len();
+        line.extend(chars);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("the quick brown fox jumps over", 10),
+            vec!["the quick", "brown fox", "jumps over"]
+        );
+        assert_eq!(
+            wrap("a supercalifragilistic b", 8),
+            vec!["a", "supercal", "ifragili", "stic b"]
+        );
+        assert_eq!(wrap("", 10), vec![""]);
+        assert_eq!(wrap("no wrapping here", 0), vec!["no wrapping here"]);
+    }
+}
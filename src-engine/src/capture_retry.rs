@@ -0,0 +1,111 @@
+//! Retrying capture after it fails to start.
+//!
+//! A device that is briefly busy (held by another app, or still being set up
+//! after a hotplug) makes `start_capture` fail even though it would succeed a
+//! moment later. After such a failure capture is retried with exponential
+//! backoff, up to [`MAX_ATTEMPTS`] times. The retry in progress is reported in
+//! the status, and choosing new sources cancels it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::CaptureRetry;
+use tracing::{error, info, warn};
+
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::start_capture;
+use crate::state::get_service_state;
+
+/// Attempts made to start capture again before giving up
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first attempt; doubled after each failure
+const INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Identifies the current retry sequence; bumped to cancel it
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Start retrying capture after it failed with `error`, replacing any retry
+/// sequence already running.
+pub(crate) fn schedule(error: String) {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::spawn(run(generation, error));
+}
+
+/// Stop retrying and clear the retry status.
+pub(crate) async fn cancel() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    set_status(None).await;
+}
+
+async fn run(generation: u64, mut last_error: String) {
+    let mut delay = INITIAL_DELAY;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        set_status(Some(CaptureRetry {
+            attempt,
+            max_attempts: MAX_ATTEMPTS,
+            delay_ms: delay.as_millis() as u64,
+            last_error: last_error.clone(),
+        }))
+        .await;
+        info!(
+            "[CaptureRetry] Retrying capture in {:?} (attempt {}/{})",
+            delay, attempt, MAX_ATTEMPTS
+        );
+        tokio::time::sleep(delay).await;
+
+        if GENERATION.load(Ordering::SeqCst) != generation || crate::shutdown::is_shutting_down() {
+            return;
+        }
+        let needed = {
+            let state_arc = get_service_state();
+            let state = state_arc.lock().await;
+            state.should_capture() && !state.transcribe_status.capturing
+        };
+        if !needed {
+            set_status(None).await;
+            return;
+        }
+
+        match start_capture().await {
+            Ok(()) => {
+                info!("[CaptureRetry] Capture started on attempt {}", attempt);
+                set_status(None).await;
+                return;
+            }
+            Err(e) => {
+                warn!("[CaptureRetry] Attempt {} failed: {}", attempt, e);
+                last_error = e;
+                delay *= 2;
+            }
+        }
+    }
+
+    if GENERATION.load(Ordering::SeqCst) != generation {
+        return;
+    }
+    error!(
+        "[CaptureRetry] Giving up after {} attempts: {}",
+        MAX_ATTEMPTS, last_error
+    );
+    {
+        let state_arc = get_service_state();
+        let mut state = state_arc.lock().await;
+        state.transcribe_status.capture_retry = None;
+        state.transcribe_status.error = Some(last_error.clone());
+    }
+    broadcast_event(Response::Event {
+        event: EventType::CaptureStateChanged {
+            capturing: false,
+            error: Some(last_error),
+        },
+    });
+}
+
+async fn set_status(retry: Option<CaptureRetry>) {
+    let state_arc = get_service_state();
+    let mut state = state_arc.lock().await;
+    state.transcribe_status.capture_retry = retry;
+}
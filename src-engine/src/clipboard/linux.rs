@@ -1,64 +1,288 @@
 //! Linux clipboard, foreground detection, and paste simulation.
 //!
-//! Uses system CLI tools with graceful fallback:
-//! - Clipboard: `xclip` (X11) or `wl-copy` (Wayland)
+//! The session type picks the tools, from `XDG_SESSION_TYPE` or, when that
+//! isn't set, from `WAYLAND_DISPLAY` and `DISPLAY`:
+//! - Clipboard: `wl-copy` (Wayland, through the wlr/ext data-control protocol
+//!   where the compositor has it) or `xclip` (X11). A Wayland session without
+//!   `wl-copy` falls back to `xclip` through XWayland.
 //! - Foreground: `xdotool getactivewindow getwindowpid` (X11) or best-effort
-//! - Paste: `xdotool key ctrl+v` (X11) or `wtype -M ctrl -k v` (Wayland)
+//! - Paste: `xdotool key ctrl+v` (X11). On Wayland, `wtype -M ctrl -k v`
+//!   through the virtual-keyboard protocol, or `ydotool` through the
+//!   `ydotoold` daemon on compositors without it (GNOME, KDE).
+//! - Backspace, to correct text typed as you speak: the same tools
+//! - Refocus: `xdotool windowactivate` (X11 only)
 
 use super::ClipboardPaster;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tracing::{debug, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, info, warn};
+
+/// Set once wtype has failed, which it does on compositors without the
+/// virtual-keyboard protocol; later pastes go straight to ydotool.
+static WTYPE_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
 
 pub struct LinuxClipboardPaster;
 
+/// The kind of graphical session FlowSTT runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Session {
+    Wayland,
+    X11,
+    None,
+}
+
 impl ClipboardPaster for LinuxClipboardPaster {
     fn write_clipboard(&self, text: &str) -> Result<(), String> {
-        // Try wl-copy first (Wayland), then xclip (X11).
-        if is_wayland() {
-            run_clipboard_write("wl-copy", &["--"], text)
-        } else {
-            run_clipboard_write("xclip", &["-selection", "clipboard"], text)
+        match clipboard_tool() {
+            Some(ClipboardTool::WlCopy) => run_clipboard_write("wl-copy", &["--"], text),
+            Some(ClipboardTool::Xclip) => {
+                run_clipboard_write("xclip", &["-selection", "clipboard"], text)
+            }
+            None => Err(clipboard_unavailable_reason()
+                .unwrap_or_else(|| "No clipboard tool is available".to_string())),
         }
     }
 
     fn is_flowstt_foreground(&self) -> bool {
-        if is_wayland() {
+        match session() {
             // Wayland does not expose a reliable way to query the focused
             // window from an unprivileged process. Default to allowing paste.
-            false
-        } else {
-            is_flowstt_foreground_x11()
+            Session::Wayland => false,
+            Session::X11 => is_flowstt_foreground_x11(),
+            Session::None => false,
         }
     }
 
     fn simulate_paste(&self) -> Result<(), String> {
-        if is_wayland() {
-            let status = Command::new("wtype")
-                .args(["-M", "ctrl", "-k", "v", "-m", "ctrl"])
-                .status()
-                .map_err(|e| format!("Failed to run wtype: {} (is wtype installed?)", e))?;
-
-            if !status.success() {
-                return Err(format!("wtype exited with status {}", status));
+        match session() {
+            Session::Wayland => simulate_paste_wayland(),
+            Session::X11 => {
+                let status = Command::new("xdotool")
+                    .args(["key", "ctrl+v"])
+                    .status()
+                    .map_err(|e| format!("Failed to run xdotool: {} (is xdotool installed?)", e))?;
+
+                if !status.success() {
+                    return Err(format!("xdotool exited with status {}", status));
+                }
+                Ok(())
+            }
+            Session::None => Err("No graphical session to paste into".to_string()),
+        }
+    }
+
+    fn simulate_backspace(&self, count: usize) -> Result<(), String> {
+        match session() {
+            Session::Wayland => simulate_backspace_wayland(count),
+            Session::X11 => {
+                let status = Command::new("xdotool")
+                    .args(["key", "--repeat", &count.to_string(), "BackSpace"])
+                    .status()
+                    .map_err(|e| format!("Failed to run xdotool: {} (is xdotool installed?)", e))?;
+
+                if !status.success() {
+                    return Err(format!("xdotool exited with status {}", status));
+                }
+                Ok(())
+            }
+            Session::None => Err("No graphical session to type into".to_string()),
+        }
+    }
+
+    fn foreground_window(&self) -> Option<u64> {
+        if session() != Session::X11 {
+            return None;
+        }
+        let output = Command::new("xdotool")
+            .arg("getactivewindow")
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    fn focus_window(&self, window: u64) -> Result<(), String> {
+        let status = Command::new("xdotool")
+            .args(["windowactivate", "--sync", &window.to_string()])
+            .status()
+            .map_err(|e| format!("Failed to run xdotool: {} (is xdotool installed?)", e))?;
+
+        if !status.success() {
+            return Err("The window has been closed".to_string());
+        }
+        Ok(())
+    }
+
+    fn paste_unavailable_reason(&self) -> Option<String> {
+        match session() {
+            Session::Wayland => (!wtype_usable() && ydotool_socket().is_none())
+                .then(|| NO_WAYLAND_PASTE.to_string()),
+            Session::X11 => {
+                (!is_installed("xdotool")).then(|| "xdotool is not installed".to_string())
+            }
+            Session::None => Some(NO_SESSION.to_string()),
+        }
+    }
+
+    fn clipboard_unavailable_reason(&self) -> Option<String> {
+        if clipboard_tool().is_some() {
+            return None;
+        }
+        Some(match session() {
+            Session::Wayland => "wl-copy is not installed (install wl-clipboard)".to_string(),
+            Session::X11 => "xclip is not installed".to_string(),
+            Session::None => NO_SESSION.to_string(),
+        })
+    }
+}
+
+const NO_SESSION: &str = "No graphical session (neither WAYLAND_DISPLAY nor DISPLAY is set)";
+
+const NO_WAYLAND_PASTE: &str = "Neither wtype nor ydotool (with ydotoold running) is available";
+
+/// Detect the session type.
+///
+/// `XDG_SESSION_TYPE` is trusted when it names a session whose display is
+/// reachable; otherwise the display variables decide, Wayland first.
+fn session() -> Session {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let x11 = std::env::var_os("DISPLAY").is_some();
+    match std::env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") if wayland => Session::Wayland,
+        Ok("x11") if x11 => Session::X11,
+        _ if wayland => Session::Wayland,
+        _ if x11 => Session::X11,
+        _ => Session::None,
+    }
+}
+
+/// A command-line tool that can write the clipboard.
+enum ClipboardTool {
+    WlCopy,
+    Xclip,
+}
+
+/// The clipboard tool to use in this session, if one is installed.
+fn clipboard_tool() -> Option<ClipboardTool> {
+    match session() {
+        Session::Wayland if is_installed("wl-copy") => Some(ClipboardTool::WlCopy),
+        // XWayland shares its clipboard with Wayland applications
+        Session::Wayland if std::env::var_os("DISPLAY").is_some() && is_installed("xclip") => {
+            debug!("[Clipboard] wl-copy not found, using xclip through XWayland");
+            Some(ClipboardTool::Xclip)
+        }
+        Session::X11 if is_installed("xclip") => Some(ClipboardTool::Xclip),
+        _ => None,
+    }
+}
+
+/// Whether wtype is installed and hasn't been turned away by the compositor.
+fn wtype_usable() -> bool {
+    !WTYPE_UNSUPPORTED.load(Ordering::Relaxed) && is_installed("wtype")
+}
+
+/// Socket of a running `ydotoold`, if ydotool is installed.
+fn ydotool_socket() -> Option<PathBuf> {
+    if !is_installed("ydotool") {
+        return None;
+    }
+    let mut candidates = Vec::new();
+    if let Some(socket) = std::env::var_os("YDOTOOL_SOCKET") {
+        candidates.push(PathBuf::from(socket));
+    }
+    if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR") {
+        candidates.push(PathBuf::from(runtime).join(".ydotool_socket"));
+    }
+    candidates.push(PathBuf::from("/tmp/.ydotool_socket"));
+    candidates.into_iter().find(|socket| socket.exists())
+}
+
+/// Press Ctrl+V with wtype, or with ydotool when wtype can't be used.
+fn simulate_paste_wayland() -> Result<(), String> {
+    let mut wtype_error = None;
+    if wtype_usable() {
+        match run_wtype(&["-M", "ctrl", "-k", "v", "-m", "ctrl"]) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                info!("[Clipboard] wtype failed, trying ydotool: {}", e);
+                WTYPE_UNSUPPORTED.store(true, Ordering::Relaxed);
+                wtype_error = Some(e);
             }
-            Ok(())
-        } else {
-            let status = Command::new("xdotool")
-                .args(["key", "ctrl+v"])
-                .status()
-                .map_err(|e| format!("Failed to run xdotool: {} (is xdotool installed?)", e))?;
-
-            if !status.success() {
-                return Err(format!("xdotool exited with status {}", status));
+        }
+    }
+
+    let Some(socket) = ydotool_socket() else {
+        return Err(wtype_error.unwrap_or_else(|| NO_WAYLAND_PASTE.to_string()));
+    };
+    // Key codes from linux/input-event-codes.h: 29 is left Ctrl, 47 is V
+    run_ydotool(&socket, &["key", "29:1", "47:1", "47:0", "29:0"])
+}
+
+/// Press Backspace `count` times with wtype, or with ydotool when wtype
+/// can't be used.
+fn simulate_backspace_wayland(count: usize) -> Result<(), String> {
+    let mut wtype_error = None;
+    if wtype_usable() {
+        let args: Vec<&str> = std::iter::repeat(["-k", "BackSpace"])
+            .take(count)
+            .flatten()
+            .collect();
+        match run_wtype(&args) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                info!("[Clipboard] wtype failed, trying ydotool: {}", e);
+                WTYPE_UNSUPPORTED.store(true, Ordering::Relaxed);
+                wtype_error = Some(e);
             }
-            Ok(())
         }
     }
+
+    let Some(socket) = ydotool_socket() else {
+        return Err(wtype_error.unwrap_or_else(|| NO_WAYLAND_PASTE.to_string()));
+    };
+    // Key code 14 is Backspace
+    let mut args = vec!["key"];
+    args.extend(std::iter::repeat(["14:1", "14:0"]).take(count).flatten());
+    run_ydotool(&socket, &args)
+}
+
+fn run_ydotool(socket: &Path, args: &[&str]) -> Result<(), String> {
+    let status = Command::new("ydotool")
+        .args(args)
+        .env("YDOTOOL_SOCKET", socket)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run ydotool: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("ydotool exited with status {}", status));
+    }
+    Ok(())
+}
+
+fn run_wtype(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("wtype")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run wtype: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("wtype exited with status {}", output.status),
+            message => format!("wtype: {}", message),
+        });
+    }
+    Ok(())
 }
 
-/// Detect whether we're running under Wayland.
-fn is_wayland() -> bool {
-    std::env::var("WAYLAND_DISPLAY").is_ok()
+/// Check whether an executable is on the `PATH`.
+fn is_installed(cmd: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(cmd).is_file()))
 }
 
 /// Write text to clipboard via a subprocess that reads stdin.
@@ -3,7 +3,8 @@
 //! Uses:
 //! - `NSPasteboard` for clipboard write
 //! - `NSWorkspace.shared.frontmostApplication` for foreground detection
-//! - `CGEvent` for Cmd+V paste simulation
+//! - `CGEvent` for Cmd+V paste simulation, skipped while Secure Input is on
+//! - System Events process IDs to refocus an application
 
 use super::ClipboardPaster;
 use std::process::Command;
@@ -54,6 +55,14 @@ impl ClipboardPaster for MacOSClipboardPaster {
     }
 
     fn simulate_paste(&self) -> Result<(), String> {
+        // Keystrokes sent while a password field has Secure Input on are
+        // silently dropped, so say why instead of pretending to paste
+        if secure_input_enabled() {
+            return Err(
+                "Secure Input is on, usually because a password field has focus".to_string(),
+            );
+        }
+
         // Use osascript to send Cmd+V keystroke.
         // This requires Accessibility permission (which FlowSTT already needs
         // for global hotkey capture).
@@ -68,4 +77,79 @@ impl ClipboardPaster for MacOSClipboardPaster {
         }
         Ok(())
     }
+
+    fn simulate_backspace(&self, count: usize) -> Result<(), String> {
+        if secure_input_enabled() {
+            return Err(
+                "Secure Input is on, usually because a password field has focus".to_string(),
+            );
+        }
+
+        // Key code 51 is Delete (Backspace)
+        let script = format!(
+            r#"tell application "System Events"
+repeat {} times
+key code 51
+end repeat
+end tell"#,
+            count
+        );
+        let status = Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .status()
+            .map_err(|e| format!("Failed to run osascript for backspace: {}", e))?;
+
+        if !status.success() {
+            return Err(format!("osascript backspace exited with status {}", status));
+        }
+        Ok(())
+    }
+
+    fn foreground_window(&self) -> Option<u64> {
+        // Applications rather than windows: System Events can raise a process
+        // by ID but has no stable window identifier
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(r#"tell application "System Events" to get unix id of first process whose frontmost is true"#)
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    fn focus_window(&self, window: u64) -> Result<(), String> {
+        let script = format!(
+            r#"tell application "System Events" to set frontmost of first process whose unix id is {} to true"#,
+            window
+        );
+        let status = Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .status()
+            .map_err(|e| format!("Failed to run osascript to focus: {}", e))?;
+
+        if !status.success() {
+            return Err("The application is no longer running".to_string());
+        }
+        Ok(())
+    }
+
+    fn paste_unavailable_reason(&self) -> Option<String> {
+        (!crate::hotkey::check_accessibility_permission())
+            .then(|| "Accessibility permission has not been granted".to_string())
+    }
+}
+
+/// Whether some application has turned on Secure Input, which keeps
+/// synthesized keystrokes from reaching other applications.
+fn secure_input_enabled() -> bool {
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn IsSecureEventInputEnabled() -> u8;
+    }
+    let enabled = unsafe { IsSecureEventInputEnabled() } != 0;
+    if enabled {
+        debug!("[Clipboard] Secure Input is enabled");
+    }
+    enabled
 }
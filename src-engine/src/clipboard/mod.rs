@@ -3,7 +3,13 @@
 //! After each transcription segment completes, this module copies the text to
 //! the system clipboard and optionally simulates a paste keystroke into the
 //! active foreground application. Paste simulation is suppressed when a FlowSTT
-//! window is in the foreground.
+//! window is in the foreground. When the keystroke can't be sent, the text
+//! stays on the clipboard and a `PasteFailed` event says why.
+//!
+//! That work goes through [`enqueue`], which runs it in order on a thread of
+//! its own so the paste delay never holds up transcription. A job that takes
+//! longer than its timeout, say because another application holds the
+//! clipboard, is left behind on its thread and the queue carries on.
 //!
 //! Platform-specific implementations live in submodules following the same
 //! backend-trait pattern used by `crate::hotkey`.
@@ -17,9 +23,32 @@ mod macos;
 #[cfg(target_os = "linux")]
 mod linux;
 
+use flowstt_common::ipc::{EventType, Response};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+use crate::ipc::broadcast_event;
+
+/// Time a queued job may take, on top of its paste delay
+const JOB_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A job waiting in the clipboard queue.
+struct QueuedJob {
+    run: Job,
+    timeout: Duration,
+}
+
+/// Sender to the clipboard queue thread, started with the first job
+static QUEUE: Mutex<Option<Sender<QueuedJob>>> = Mutex::new(None);
+
+/// Last paste failure reported to clients, cleared by a successful paste
+static LAST_PASTE_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
 /// Platform-agnostic clipboard and paste backend.
 pub trait ClipboardPaster: Send + Sync {
     /// Write plain text to the system clipboard.
@@ -30,6 +59,35 @@ pub trait ClipboardPaster: Send + Sync {
 
     /// Simulate a paste keystroke (Ctrl+V / Cmd+V) into the foreground window.
     fn simulate_paste(&self) -> Result<(), String>;
+
+    /// Press Backspace `count` times in the foreground window, to take back
+    /// text typed there.
+    fn simulate_backspace(&self, _count: usize) -> Result<(), String> {
+        Err("Deleting typed text is not supported in this session".to_string())
+    }
+
+    /// Why paste simulation can't work in this session, if it can't.
+    fn paste_unavailable_reason(&self) -> Option<String> {
+        None
+    }
+
+    /// Why the clipboard can't be written in this session, if it can't.
+    fn clipboard_unavailable_reason(&self) -> Option<String> {
+        None
+    }
+
+    /// Identify the foreground window so it can be focused again later, or
+    /// `None` if it can't be determined.
+    fn foreground_window(&self) -> Option<u64> {
+        None
+    }
+
+    /// Bring a window identified by [`foreground_window`] back to the front.
+    ///
+    /// [`foreground_window`]: ClipboardPaster::foreground_window
+    fn focus_window(&self, _window: u64) -> Result<(), String> {
+        Err("Focusing a window is not supported in this session".to_string())
+    }
 }
 
 /// Create the platform-specific backend.
@@ -55,6 +113,137 @@ fn create_backend() -> Box<dyn ClipboardPaster> {
     }
 }
 
+/// Run clipboard work on the clipboard queue, after the work queued before
+/// it. `delay_ms` is the paste delay the job sleeps for, which is added to
+/// its timeout.
+pub fn enqueue(delay_ms: u32, job: impl FnOnce() + Send + 'static) {
+    let job = QueuedJob {
+        run: Box::new(job),
+        timeout: JOB_TIMEOUT + Duration::from_millis(u64::from(delay_ms)),
+    };
+    let mut queue = QUEUE.lock().unwrap();
+    let job = match queue.as_ref() {
+        Some(tx) => match tx.send(job) {
+            Ok(()) => return,
+            // The queue thread is gone; start another
+            Err(e) => e.0,
+        },
+        None => job,
+    };
+
+    let (tx, rx) = channel::<QueuedJob>();
+    let spawned = thread::Builder::new()
+        .name("clipboard-queue".into())
+        .spawn(move || run_queue(rx.into_iter()));
+    match spawned {
+        Ok(_) => {
+            let _ = tx.send(job);
+            *queue = Some(tx);
+        }
+        Err(e) => warn!("[Clipboard] Failed to start clipboard queue: {}", e),
+    }
+}
+
+/// Run queued jobs one at a time, each on a worker thread so one that hangs
+/// can be left behind.
+fn run_queue(jobs: impl Iterator<Item = QueuedJob>) {
+    let mut worker: Option<Sender<(Job, Sender<()>)>> = None;
+    for job in jobs {
+        let Some(run) = worker.take().or_else(spawn_worker) else {
+            continue;
+        };
+        let (done_tx, done_rx) = channel();
+        if run.send((job.run, done_tx)).is_err() {
+            warn!("[Clipboard] Clipboard worker has stopped");
+            continue;
+        }
+        match done_rx.recv_timeout(job.timeout) {
+            Ok(()) => worker = Some(run),
+            Err(RecvTimeoutError::Timeout) => warn!(
+                "[Clipboard] Clipboard work took over {:?}, moving on without it",
+                job.timeout
+            ),
+            Err(RecvTimeoutError::Disconnected) => warn!("[Clipboard] Clipboard work panicked"),
+        }
+    }
+}
+
+/// Start a thread that runs clipboard jobs and reports each one done.
+fn spawn_worker() -> Option<Sender<(Job, Sender<()>)>> {
+    let (tx, rx) = channel::<(Job, Sender<()>)>();
+    let spawned = thread::Builder::new()
+        .name("clipboard-worker".into())
+        .spawn(move || {
+            for (job, done) in rx {
+                job();
+                let _ = done.send(());
+            }
+        });
+    match spawned {
+        Ok(_) => Some(tx),
+        Err(e) => {
+            warn!("[Clipboard] Failed to start clipboard worker: {}", e);
+            None
+        }
+    }
+}
+
+/// Copy text to the clipboard without simulating a paste.
+pub fn copy_text(text: &str) -> Result<(), String> {
+    create_backend().write_clipboard(text)
+}
+
+/// Get the reason paste simulation is unavailable, if any.
+pub fn paste_unavailable_reason() -> Option<String> {
+    create_backend().paste_unavailable_reason()
+}
+
+/// Get the reason the clipboard is unavailable, if any.
+pub fn clipboard_unavailable_reason() -> Option<String> {
+    create_backend().clipboard_unavailable_reason()
+}
+
+/// Check whether a FlowSTT window is in the foreground.
+pub fn is_flowstt_foreground() -> bool {
+    create_backend().is_flowstt_foreground()
+}
+
+/// Identify the current foreground window, for [`paste_into`].
+pub fn foreground_window() -> Option<u64> {
+    create_backend().foreground_window()
+}
+
+/// Copy text to the clipboard and paste it into `window`, focusing it first
+/// if another window has taken the foreground. Without a window, pastes into
+/// whatever is in the foreground.
+///
+/// Unlike [`copy_and_paste`] this ignores the auto-paste setting, and fails
+/// rather than pasting elsewhere when the window can't be focused.
+pub fn paste_into(text: &str, window: Option<u64>, delay_ms: u32) -> Result<(), String> {
+    let backend = create_backend();
+    backend.write_clipboard(text)?;
+
+    if let Some(window) = window {
+        if backend.foreground_window() != Some(window) {
+            debug!("[Clipboard] Refocusing dictation target window");
+            backend.focus_window(window)?;
+        }
+    }
+
+    if delay_ms > 0 {
+        std::thread::sleep(Duration::from_millis(delay_ms as u64));
+    }
+    backend.simulate_paste()
+}
+
+/// Delete the last `count` characters typed into the foreground window.
+pub fn backspace(count: usize) -> Result<(), String> {
+    if count == 0 {
+        return Ok(());
+    }
+    create_backend().simulate_backspace(count)
+}
+
 /// Perform the full clipboard-copy-and-paste flow for a transcription result.
 ///
 /// 1. Skip if the text is empty or a "no speech" placeholder.
@@ -93,9 +282,27 @@ pub fn copy_and_paste(text: &str, auto_paste_enabled: bool, delay_ms: u32) {
         std::thread::sleep(Duration::from_millis(delay_ms as u64));
     }
 
-    if let Err(e) = backend.simulate_paste() {
-        warn!("[Clipboard] Failed to simulate paste: {}", e);
-    } else {
-        debug!("[Clipboard] Paste simulated into foreground application");
+    match backend.simulate_paste() {
+        Ok(()) => {
+            debug!("[Clipboard] Paste simulated into foreground application");
+            *LAST_PASTE_ERROR.lock().unwrap() = None;
+        }
+        Err(e) => {
+            warn!("[Clipboard] Failed to simulate paste: {}", e);
+            report_paste_failure(e);
+        }
+    }
+}
+
+/// Tell clients the transcription was left on the clipboard, unless the
+/// same failure was reported last time.
+fn report_paste_failure(reason: String) {
+    let mut last = LAST_PASTE_ERROR.lock().unwrap();
+    if last.as_deref() == Some(reason.as_str()) {
+        return;
     }
+    *last = Some(reason.clone());
+    broadcast_event(Response::Event {
+        event: EventType::PasteFailed { reason },
+    });
 }
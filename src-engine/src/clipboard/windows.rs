@@ -3,7 +3,8 @@
 //! Uses Win32 APIs:
 //! - Clipboard: `OpenClipboard` / `EmptyClipboard` / `SetClipboardData` / `CloseClipboard`
 //! - Foreground: `GetForegroundWindow` / `GetWindowThreadProcessId`
-//! - Paste sim: `SendInput` with `INPUT_KEYBOARD` for Ctrl+V
+//! - Paste sim: `SendInput` with `INPUT_KEYBOARD` for Ctrl+V (and Backspace)
+//! - Refocus: `IsWindow` / `SetForegroundWindow`
 
 use super::ClipboardPaster;
 use std::ffi::OsString;
@@ -19,9 +20,11 @@ use windows::Win32::System::Threading::{
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::{
     MapVirtualKeyW, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
-    KEYEVENTF_KEYUP, MAP_VIRTUAL_KEY_TYPE, VIRTUAL_KEY, VK_CONTROL, VK_V,
+    KEYEVENTF_KEYUP, MAP_VIRTUAL_KEY_TYPE, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_V,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowThreadProcessId, IsWindow, SetForegroundWindow,
 };
-use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
 
 /// The Win32 clipboard format for Unicode text.
 const CF_UNICODETEXT: u32 = 13;
@@ -40,6 +43,44 @@ impl ClipboardPaster for WindowsClipboardPaster {
     fn simulate_paste(&self) -> Result<(), String> {
         simulate_ctrl_v()
     }
+
+    fn simulate_backspace(&self, count: usize) -> Result<(), String> {
+        let inputs: Vec<INPUT> = (0..count)
+            .flat_map(|_| {
+                [
+                    make_key_input(VK_BACK, false),
+                    make_key_input(VK_BACK, true),
+                ]
+            })
+            .collect();
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent != inputs.len() as u32 {
+            return Err(format!(
+                "SendInput sent {} of {} events",
+                sent,
+                inputs.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn foreground_window(&self) -> Option<u64> {
+        let hwnd = unsafe { GetForegroundWindow() };
+        (!hwnd.0.is_null()).then_some(hwnd.0 as u64)
+    }
+
+    fn focus_window(&self, window: u64) -> Result<(), String> {
+        let hwnd = HWND(window as *mut std::ffi::c_void);
+        unsafe {
+            if !IsWindow(hwnd).as_bool() {
+                return Err("The window has been closed".to_string());
+            }
+            if !SetForegroundWindow(hwnd).as_bool() {
+                return Err("Windows did not allow the window to be focused".to_string());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Write UTF-16 text to the Windows clipboard.
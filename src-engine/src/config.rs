@@ -1,12 +1,23 @@
 //! Configuration persistence for FlowSTT service.
 //!
 //! This module re-exports the shared Config from flowstt-common and adds
-//! service-specific logging via tracing.
+//! service-specific logging via tracing. Changes made by the service go
+//! through [`update_config`], which saves them through
+//! [`flowstt_common::settings`] like the GUI and the CLI do, so concurrent
+//! updates never overwrite each other's fields. Code that only reads
+//! settings uses [`current_config`], which doesn't read the file again until
+//! it changes.
+
+use std::sync::{Arc, Once};
 
 pub use flowstt_common::config::Config;
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::settings;
 
 use tracing::info;
 
+use crate::ipc::broadcast_event;
+
 /// Load configuration with tracing output.
 pub fn load_config() -> Config {
     let path = Config::config_path();
@@ -15,10 +26,31 @@ pub fn load_config() -> Config {
     config
 }
 
-/// Save configuration with tracing output.
-pub fn save_config(config: &Config) -> std::io::Result<()> {
-    let path = Config::config_path();
-    config.save()?;
-    info!("Saved config to {:?}", path);
-    Ok(())
+/// The current configuration, read from disk only when the file changed.
+/// Cheap enough for hot paths such as hotkey presses and finished segments.
+pub fn current_config() -> Arc<Config> {
+    settings::current()
+}
+
+/// Load the configuration, apply `change` and save it. Returns the saved
+/// configuration.
+pub fn update_config(change: impl FnOnce(&mut Config)) -> std::io::Result<Config> {
+    let config = settings::update(change)?;
+    info!("Saved config to {:?}", Config::config_path());
+    Ok(config)
+}
+
+/// Tell clients which settings changed after each update made in this
+/// process, whether by the engine or the GUI hosting it.
+pub fn broadcast_changes() {
+    static SUBSCRIBED: Once = Once::new();
+    SUBSCRIBED.call_once(|| {
+        settings::subscribe(|_, change| {
+            broadcast_event(Response::Event {
+                event: EventType::ConfigChanged {
+                    keys: change.keys().to_vec(),
+                },
+            });
+        });
+    });
 }
@@ -0,0 +1,262 @@
+//! Phrase replacements for transcribed text, learned from manual corrections.
+//!
+//! Rules from the `corrections` section of the config file replace whole-word
+//! phrases (ignoring case) before redaction runs, and terms in its
+//! `dictionary` are then rewritten with the capitalization listed there.
+//! When a history entry is edited, the words that changed between the
+//! original transcript and the correction are paired up; once the same pair
+//! has been seen in `learn_threshold` edited entries it is added to the rules
+//! automatically, unless learning is turned off.
+
+use std::collections::HashMap;
+
+use flowstt_common::config::{CorrectionSettings, ReplacementRule};
+use regex::{NoExpand, Regex};
+use tracing::{info, warn};
+
+use crate::history::HistoryEntry;
+
+/// Longest phrase, in words, that is learned from a correction
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// Apply the enabled replacement rules in order, then restore the
+/// capitalization of dictionary terms.
+pub fn apply(settings: &CorrectionSettings, text: &str) -> String {
+    let text = settings
+        .rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .fold(text.to_string(), |text, rule| {
+            replace_phrase(text, &rule.from, &rule.to)
+        });
+    capitalize_terms(&settings.dictionary, text)
+}
+
+/// Write each dictionary term exactly as listed wherever it appears in any
+/// case, e.g. "flowstt" and "FLOWSTT" become "FlowSTT".
+fn capitalize_terms(dictionary: &[String], text: String) -> String {
+    dictionary
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .fold(text, |text, term| replace_phrase(text, term, term))
+}
+
+/// Replace every whole-word match of `phrase`, in any case, with `with`.
+fn replace_phrase(text: String, phrase: &str, with: &str) -> String {
+    match phrase_regex(phrase) {
+        Some(regex) => regex.replace_all(&text, NoExpand(with)).into_owned(),
+        None => text,
+    }
+}
+
+/// Case-insensitive pattern matching `phrase` as whole words, with any run of
+/// whitespace between them.
+fn phrase_regex(phrase: &str) -> Option<Regex> {
+    let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+    let (first, last) = (phrase.trim().chars().next()?, phrase.trim().chars().last()?);
+    let boundary = |c: char| if c.is_alphanumeric() { r"\b" } else { "" };
+    let pattern = format!(
+        "(?i){}{}{}",
+        boundary(first),
+        words.join(r"\s+"),
+        boundary(last)
+    );
+    match Regex::new(&pattern) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            warn!("[Corrections] Ignoring rule for {:?}: {}", phrase, e);
+            None
+        }
+    }
+}
+
+/// Learn rules from the corrections made to `entries`, saving any new ones to
+/// the config. Returns the rules that were added.
+pub fn learn(entries: &[HistoryEntry]) -> Vec<ReplacementRule> {
+    let config = crate::config::current_config();
+    if !config.corrections.learn {
+        return Vec::new();
+    }
+
+    let mut counts: HashMap<(String, String), u32> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.edited) {
+        if let Some(original) = &entry.original_text {
+            for pair in correction_pairs(original, &entry.text) {
+                *counts.entry(pair).or_default() += 1;
+            }
+        }
+    }
+
+    let threshold = config.corrections.learn_threshold.max(1);
+    let mut learned: Vec<ReplacementRule> = Vec::new();
+    let mut pairs: Vec<_> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= threshold)
+        .collect();
+    // Most frequent first, so a phrase corrected two ways keeps the common one
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for ((from, to), _) in pairs {
+        let known = config
+            .corrections
+            .rules
+            .iter()
+            .chain(&learned)
+            .any(|rule| rule.from.to_lowercase() == from);
+        if !known {
+            learned.push(ReplacementRule {
+                from,
+                to,
+                enabled: true,
+                learned: true,
+            });
+        }
+    }
+
+    if learned.is_empty() {
+        return learned;
+    }
+    for rule in &learned {
+        info!("[Corrections] Learned {:?} -> {:?}", rule.from, rule.to);
+    }
+    if let Err(e) = crate::config::update_config(|config| {
+        config.corrections.rules.extend(learned.iter().cloned())
+    }) {
+        warn!("[Corrections] Failed to save learned rules: {}", e);
+    }
+    learned
+}
+
+/// Phrases replaced between a transcript and its correction, as
+/// `(lowercased original, corrected)` pairs. Insertions, deletions and
+/// changes that only capitalize the first letter are ignored.
+fn correction_pairs(original: &str, corrected: &str) -> Vec<(String, String)> {
+    let a = words(original);
+    let b = words(corrected);
+
+    // Longest common subsequence lengths of every suffix pair
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut from: Vec<&str> = Vec::new();
+    let mut to: Vec<&str> = Vec::new();
+    let mut flush = |from: &mut Vec<&str>, to: &mut Vec<&str>| {
+        if (1..=MAX_PHRASE_WORDS).contains(&from.len())
+            && (1..=MAX_PHRASE_WORDS).contains(&to.len())
+        {
+            let from_text = from.join(" ");
+            let to_text = to.join(" ");
+            if !is_capitalization_fix(&from_text, &to_text) {
+                pairs.push((from_text.to_lowercase(), to_text));
+            }
+        }
+        from.clear();
+        to.clear();
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            flush(&mut from, &mut to);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            to.push(b[j]);
+            j += 1;
+        } else {
+            from.push(a[i]);
+            i += 1;
+        }
+    }
+    flush(&mut from, &mut to);
+
+    pairs
+}
+
+/// Words of a transcript with surrounding punctuation removed.
+fn words(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Whether `to` is `from` with only its first letter's case changed, as when
+/// fixing the start of a sentence.
+fn is_capitalization_fix(from: &str, to: &str) -> bool {
+    let mut from_chars = from.chars();
+    let mut to_chars = to.chars();
+    match (from_chars.next(), to_chars.next()) {
+        (Some(f), Some(t)) => f.to_lowercase().eq(t.to_lowercase()) && from_chars.eq(to_chars),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(from: &str, to: &str) -> ReplacementRule {
+        ReplacementRule {
+            from: from.to_string(),
+            to: to.to_string(),
+            enabled: true,
+            learned: false,
+        }
+    }
+
+    #[test]
+    fn test_apply_whole_words_ignoring_case() {
+        let settings = CorrectionSettings {
+            rules: vec![rule("flow s t t", "FlowSTT"), rule("get", "git")],
+            ..Default::default()
+        };
+        assert_eq!(
+            apply(&settings, "Flow S  T T uses get, not getter. "),
+            "FlowSTT uses git, not getter. "
+        );
+    }
+
+    #[test]
+    fn test_dictionary_restores_capitalization() {
+        let settings = CorrectionSettings {
+            rules: vec![rule("flow s t t", "flowstt")],
+            dictionary: vec![
+                "FlowSTT".to_string(),
+                "iPhone".to_string(),
+                "New York".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            apply(
+                &settings,
+                "flow s t t on my IPHONE in new  york, not iphones. "
+            ),
+            "FlowSTT on my iPhone in New York, not iphones. "
+        );
+    }
+
+    #[test]
+    fn test_correction_pairs() {
+        assert_eq!(
+            correction_pairs(
+                "I pushed it to get hub today.",
+                "I pushed it to GitHub today."
+            ),
+            vec![("get hub".to_string(), "GitHub".to_string())]
+        );
+        // Capitalization at the start of a sentence, insertions and deletions
+        assert!(correction_pairs("the cat sat", "The cat sat down").is_empty());
+        assert!(correction_pairs("um so the plan", "so the plan").is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! Crash reports.
+//!
+//! With `crash_reports` on in the config file, a panic writes its message,
+//! location and backtrace to a `crash-<time>-panic.txt` file in the logs
+//! directory. A fatal signal (a segfault in a native library, say) on Linux
+//! and macOS is noted in `crash-<startup time>-signal.txt`, and an unhandled
+//! exception on Windows writes a `crash-<time>-minidump.dmp`. The reports are
+//! bundled by `flowstt debug report`.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::config::current_config;
+
+/// Version of the running FlowSTT
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Install the crash handlers if crash reports are on.
+///
+/// Must be called once at startup, after logging is set up.
+pub fn install() {
+    if !current_config().crash_reports {
+        return;
+    }
+    let dir = flowstt_common::paths::log_dir();
+    if let Err(e) = fs::create_dir_all(&dir) {
+        tracing::warn!("[Crash] Failed to create {:?}: {}", dir, e);
+        return;
+    }
+
+    install_panic_hook(dir.clone());
+    #[cfg(unix)]
+    fatal_signal::install(&dir.join(report_name("signal.txt")));
+    #[cfg(windows)]
+    minidump::install(&dir);
+    tracing::info!("[Crash] Crash reports are written to {:?}", dir);
+}
+
+/// Name of a report written now, ending in `suffix`.
+fn report_name(suffix: &str) -> String {
+    format!("crash-{}-{}", Local::now().format("%Y%m%d-%H%M%S"), suffix)
+}
+
+fn install_panic_hook(dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        write_panic_report(&dir, info);
+        previous(info);
+    }));
+}
+
+fn write_panic_report(dir: &Path, info: &PanicHookInfo) {
+    let thread = std::thread::current();
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("(no message)");
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "an unknown location".to_string());
+
+    let report = format!(
+        "FlowSTT {} ({} {})\nThread '{}' panicked at {}:\n{}\n\nBacktrace:\n{}\n",
+        VERSION,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("<unnamed>"),
+        location,
+        message,
+        Backtrace::force_capture()
+    );
+    // Panics on several threads at once each get a report of their own
+    let mut path = dir.join(report_name("panic.txt"));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(report_name(&format!("panic-{}.txt", n)));
+    }
+    if let Err(e) = fs::write(&path, report) {
+        eprintln!("Failed to write crash report {:?}: {}", path, e);
+    }
+}
+
+/// Notes fatal signals in a report file.
+///
+/// The handler may only make async-signal-safe calls, so the report path is
+/// prepared up front and the handler writes to it with plain `open` and
+/// `write`. It then restores the handler it replaced and raises the signal
+/// again, so the process ends as it would have.
+#[cfg(unix)]
+mod fatal_signal {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    use libc::c_int;
+
+    const SIGNALS: [c_int; 5] = [
+        libc::SIGSEGV,
+        libc::SIGBUS,
+        libc::SIGILL,
+        libc::SIGFPE,
+        libc::SIGABRT,
+    ];
+
+    static REPORT_PATH: OnceLock<CString> = OnceLock::new();
+
+    /// Handlers in place before ours, by signal
+    static PREVIOUS: OnceLock<Vec<(c_int, libc::sigaction)>> = OnceLock::new();
+
+    pub fn install(path: &Path) {
+        let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+            return;
+        };
+        if REPORT_PATH.set(path).is_err() {
+            return;
+        }
+
+        let mut previous = Vec::with_capacity(SIGNALS.len());
+        for signal in SIGNALS {
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_fatal_signal as extern "C" fn(c_int) as usize;
+                // Run on the alternate stack so a stack overflow is caught too
+                action.sa_flags = libc::SA_ONSTACK;
+                libc::sigemptyset(&mut action.sa_mask);
+                let mut old: libc::sigaction = std::mem::zeroed();
+                if libc::sigaction(signal, &action, &mut old) == 0 {
+                    previous.push((signal, old));
+                }
+            }
+        }
+        let _ = PREVIOUS.set(previous);
+    }
+
+    extern "C" fn on_fatal_signal(signal: c_int) {
+        if let Some(path) = REPORT_PATH.get() {
+            unsafe {
+                let fd = libc::open(
+                    path.as_ptr(),
+                    libc::O_WRONLY | libc::O_CREAT | libc::O_APPEND,
+                    0o644 as libc::c_uint,
+                );
+                if fd >= 0 {
+                    write_all(fd, b"FlowSTT ");
+                    write_all(fd, super::VERSION.as_bytes());
+                    write_all(fd, b" stopped on fatal signal ");
+                    write_number(fd, signal);
+                    write_all(fd, b"\n");
+                    libc::close(fd);
+                }
+            }
+        }
+
+        // The signal is blocked while this runs, so the raised one is
+        // delivered to the restored handler as soon as this returns
+        unsafe {
+            match PREVIOUS
+                .get()
+                .and_then(|previous| previous.iter().find(|(s, _)| *s == signal))
+            {
+                Some((_, old)) => libc::sigaction(signal, old, std::ptr::null_mut()),
+                None => {
+                    libc::signal(signal, libc::SIG_DFL);
+                    0
+                }
+            };
+            libc::raise(signal);
+        }
+    }
+
+    unsafe fn write_all(fd: c_int, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let written = libc::write(fd, bytes.as_ptr().cast(), bytes.len());
+            if written <= 0 {
+                return;
+            }
+            bytes = &bytes[written as usize..];
+        }
+    }
+
+    /// Write a number without allocating.
+    unsafe fn write_number(fd: c_int, number: c_int) {
+        let mut digits = [0u8; 12];
+        let mut start = digits.len();
+        let mut rest = number.unsigned_abs();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        write_all(fd, &digits[start..]);
+    }
+}
+
+/// Writes a minidump of the process on an unhandled exception.
+#[cfg(windows)]
+mod minidump {
+    use std::os::windows::io::AsRawHandle;
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Diagnostics::Debug::{
+        MiniDumpNormal, MiniDumpWriteDump, SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+        MINIDUMP_EXCEPTION_INFORMATION,
+    };
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId,
+    };
+
+    /// Let the next handler (Windows Error Reporting) deal with the exception
+    const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+    static DUMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    pub fn install(dir: &Path) {
+        if DUMP_DIR.set(dir.to_path_buf()).is_ok() {
+            unsafe { SetUnhandledExceptionFilter(Some(on_unhandled_exception)) };
+        }
+    }
+
+    unsafe extern "system" fn on_unhandled_exception(pointers: *const EXCEPTION_POINTERS) -> i32 {
+        let Some(dir) = DUMP_DIR.get() else {
+            return EXCEPTION_CONTINUE_SEARCH;
+        };
+        let path = dir.join(super::report_name("minidump.dmp"));
+        if let Ok(file) = std::fs::File::create(&path) {
+            let exception = MINIDUMP_EXCEPTION_INFORMATION {
+                ThreadId: GetCurrentThreadId(),
+                ExceptionPointers: pointers as *mut EXCEPTION_POINTERS,
+                ClientPointers: false.into(),
+            };
+            let _ = MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                HANDLE(file.as_raw_handle()),
+                MiniDumpNormal,
+                Some(&exception as *const MINIDUMP_EXCEPTION_INFORMATION),
+                None,
+                None,
+            );
+        }
+        EXCEPTION_CONTINUE_SEARCH
+    }
+}
@@ -0,0 +1,109 @@
+//! Dictation sessions.
+//!
+//! During a session every completed segment is pasted into the window that
+//! had focus when the session started, which is focused again first if
+//! another window has come to the front. Consecutive segments are always
+//! joined in smart-sentence mode, as continuous typing would join them,
+//! whatever the `segment_joining` setting.
+//!
+//! Sessions start and end with the dictation hotkey or a `SetDictation`
+//! request and are not persisted.
+
+use std::sync::Mutex;
+
+use flowstt_common::config::SegmentJoining;
+use flowstt_common::ipc::{EventType, Response};
+use tracing::{info, warn};
+
+use crate::ipc::broadcast_event;
+
+/// A dictation session in progress.
+struct Session {
+    /// Window segments are pasted into; picked up from the first segment if
+    /// FlowSTT itself had focus when the session started
+    target: Option<u64>,
+    /// The last segment pasted, as pasted
+    last_text: Option<String>,
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// Check whether a dictation session is in progress.
+pub fn is_active() -> bool {
+    SESSION.lock().unwrap().is_some()
+}
+
+/// Start or end a dictation session and notify clients if that changed.
+pub fn set_active(active: bool) {
+    {
+        let mut session = SESSION.lock().unwrap();
+        if session.is_some() == active {
+            return;
+        }
+        *session = active.then(|| Session {
+            target: current_target(),
+            last_text: None,
+        });
+    }
+
+    info!(
+        "Dictation session {}",
+        if active { "started" } else { "ended" }
+    );
+    broadcast_event(Response::Event {
+        event: EventType::DictationChanged { active },
+    });
+}
+
+/// Start a session, or end the one in progress.
+pub fn toggle() {
+    set_active(!is_active());
+}
+
+/// Paste a completed segment into the session's target window.
+///
+/// Returns `false` without doing anything if no session is in progress, so
+/// the caller can fall back to the usual clipboard output. A target window
+/// that can't be focused any more ends the session; the text is left on the
+/// clipboard.
+pub fn paste_segment(text: &str, delay_ms: u32) -> bool {
+    let (target, joined) = {
+        let mut session = SESSION.lock().unwrap();
+        let Some(session) = session.as_mut() else {
+            return false;
+        };
+        if session.target.is_none() {
+            session.target = current_target();
+        }
+        let joined = crate::joining::join(
+            SegmentJoining::SmartSentence,
+            session.last_text.as_deref(),
+            text,
+        );
+        (session.target, joined)
+    };
+    if joined.trim().is_empty() {
+        return true;
+    }
+
+    match crate::clipboard::paste_into(&joined, target, delay_ms) {
+        Ok(()) => {
+            if let Some(session) = SESSION.lock().unwrap().as_mut() {
+                session.last_text = Some(joined);
+            }
+        }
+        Err(e) => {
+            warn!("[Dictation] Failed to paste into the target window: {}", e);
+            set_active(false);
+        }
+    }
+    true
+}
+
+/// The foreground window, unless it belongs to FlowSTT.
+fn current_target() -> Option<u64> {
+    if crate::clipboard::is_flowstt_foreground() {
+        return None;
+    }
+    crate::clipboard::foreground_window()
+}
@@ -0,0 +1,151 @@
+//! Disk space guard for saved audio.
+//!
+//! Before a recording, segment dump or recent-audio clip is written, the free
+//! space on its disk is checked against `disk_space.min_free_mb` from the
+//! config file. Audio that would leave less isn't saved, and a `LowDiskSpace`
+//! event says so (at most once per [`WARN_INTERVAL`]). The recordings folder
+//! is also kept under `disk_space.max_recordings_mb` by deleting the oldest
+//! recordings first.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use flowstt_common::ipc::{EventType, Response};
+use tracing::{info, warn};
+
+use crate::config::current_config;
+use crate::history::{get_history, TranscriptionHistory};
+use crate::ipc::broadcast_event;
+
+const MB: u64 = 1024 * 1024;
+
+/// Shortest time between `LowDiskSpace` events
+const WARN_INTERVAL: Duration = Duration::from_secs(300);
+
+static LAST_WARNING: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Check that `bytes` can be written to `dir` and still leave the configured
+/// free space on its disk.
+pub fn ensure_room(dir: &Path, bytes: u64) -> Result<(), String> {
+    let min_free_mb = current_config().disk_space.min_free_mb;
+    if min_free_mb == 0 {
+        return Ok(());
+    }
+    // Don't block saving when the free space can't be determined
+    let Some(available) = available_space(dir) else {
+        return Ok(());
+    };
+    if available.saturating_sub(bytes) >= min_free_mb * MB {
+        return Ok(());
+    }
+
+    let available_mb = available / MB;
+    warn_low(dir, available_mb, min_free_mb);
+    Err(format!(
+        "Not enough disk space for {:?}: {} MB free, keeping {} MB free",
+        dir, available_mb, min_free_mb
+    ))
+}
+
+/// Delete the oldest recordings until the recordings folder, plus `incoming`
+/// bytes, fits in the configured limit.
+pub fn make_room_for_recording(incoming: u64) {
+    let max_mb = current_config().disk_space.max_recordings_mb;
+    if max_mb == 0 {
+        return;
+    }
+    let dir = TranscriptionHistory::recordings_dir();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+
+    let mut recordings: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "wav"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut total = recordings.iter().map(|(_, len, _)| len).sum::<u64>() + incoming;
+    if total <= max_mb * MB {
+        return;
+    }
+
+    recordings.sort_by_key(|(modified, _, _)| *modified);
+    let mut deleted = 0u32;
+    for (_, len, path) in recordings {
+        if total <= max_mb * MB {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                total -= len;
+                deleted += 1;
+            }
+            Err(e) => warn!("[DiskSpace] Failed to delete {:?}: {}", path, e),
+        }
+    }
+
+    if deleted > 0 {
+        info!(
+            "[DiskSpace] Deleted {} old recording(s) to stay under {} MB",
+            deleted, max_mb
+        );
+        get_history().lock().unwrap().forget_missing_recordings();
+    }
+}
+
+fn warn_low(dir: &Path, available_mb: u64, min_free_mb: u64) {
+    warn!(
+        "[DiskSpace] Not saving audio to {:?}: {} MB free, keeping {} MB free",
+        dir, available_mb, min_free_mb
+    );
+
+    let mut last = LAST_WARNING.lock().unwrap();
+    if last.is_some_and(|at| at.elapsed() < WARN_INTERVAL) {
+        return;
+    }
+    *last = Some(Instant::now());
+    broadcast_event(Response::Event {
+        event: EventType::LowDiskSpace {
+            path: dir.to_string_lossy().into_owned(),
+            available_mb,
+            min_free_mb,
+        },
+    });
+}
+
+/// Bytes free to this user on the disk holding `dir`, or its nearest
+/// existing parent.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // statvfs field types differ between platforms
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = dir.ancestors().find(|d| d.exists())?;
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Bytes free to this user on the disk holding `dir`, or its nearest
+/// existing parent.
+#[cfg(target_os = "windows")]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let dir = dir.ancestors().find(|d| d.exists())?;
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    unsafe { GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut available), None, None) }.ok()?;
+    Some(available)
+}
@@ -0,0 +1,527 @@
+//! Embedding API.
+//!
+//! [`EngineBuilder`] starts the engine in-process, the same way the FlowSTT
+//! app does, and returns an [`Engine`] handle for requests and events:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), flowstt_engine::EngineError> {
+//! use flowstt_engine::{EngineBuilder, EngineEvent, VadOptions};
+//! use futures::StreamExt;
+//!
+//! let engine = EngineBuilder::new()
+//!     .model("small.en")
+//!     .vad(VadOptions::default())
+//!     .ipc_server(false)
+//!     .build()
+//!     .await?;
+//!
+//! let mut events = engine.events();
+//! while let Some(event) = events.next().await {
+//!     if let EngineEvent::TranscriptionComplete(result) = event {
+//!         println!("{}", result.text);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Capture, transcription and history keep process-wide state, so only one
+//! engine can run in a process at a time. Another can be built once it has
+//! shut down.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use flowstt_common::config::{TranscriptionEngineKind, VadSource};
+use flowstt_common::ipc::{EventType, Request, Response};
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::ipc::EventCallback;
+#[cfg(target_os = "windows")]
+use crate::session_watch;
+use crate::transcription::model_path_for;
+use crate::{
+    caption_file, capture_retry, config, history, history_crypto, hotkey, ipc, load_shedding,
+    permissions, platform, power, process_watch, sleep_watch, state, sync, updates, watchdog,
+};
+
+/// Events delivered by [`Engine::events`]
+pub type EngineEvent = EventType;
+
+/// Speech detection settings for an embedded engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VadOptions {
+    /// Audio speech detection runs on
+    pub source: VadSource,
+    /// Level in dB at which normal speech is detected
+    pub voiced_threshold_db: f32,
+    /// Level in dB at which whispered speech is detected
+    pub whisper_threshold_db: f32,
+}
+
+impl Default for VadOptions {
+    /// The detector's own defaults, on the mixed audio.
+    fn default() -> Self {
+        Self {
+            source: VadSource::Mixed,
+            voiced_threshold_db: flowstt_dsp::DEFAULT_VOICED_THRESHOLD_DB,
+            whisper_threshold_db: flowstt_dsp::DEFAULT_WHISPER_THRESHOLD_DB,
+        }
+    }
+}
+
+/// Options an engine was built with
+struct EngineOptions {
+    /// Whisper model used instead of the default one
+    model: Option<PathBuf>,
+    /// Speech detection used instead of the config and device calibration
+    vad: Option<VadOptions>,
+}
+
+/// Why an engine couldn't be built
+#[derive(Debug)]
+pub enum EngineError {
+    /// Another engine in this process hasn't shut down yet
+    AlreadyRunning,
+    /// The requested whisper model file doesn't exist
+    ModelNotFound(PathBuf),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::AlreadyRunning => write!(f, "An engine is already running"),
+            EngineError::ModelNotFound(path) => {
+                write!(f, "Model not found: {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Configures and starts the engine.
+///
+/// Everything not set here comes from the FlowSTT config file.
+pub struct EngineBuilder {
+    model: Option<String>,
+    vad: Option<VadOptions>,
+    ipc_server: bool,
+    auto_capture: bool,
+    event_callback: Option<Box<dyn EventCallback>>,
+}
+
+impl Default for EngineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EngineBuilder {
+    /// A builder with the IPC server and auto-capture on, as in the app.
+    pub fn new() -> Self {
+        Self {
+            model: None,
+            vad: None,
+            ipc_server: true,
+            auto_capture: true,
+            event_callback: None,
+        }
+    }
+
+    /// Transcribe with this whisper model: a file name in the models
+    /// directory (`ggml-small.en.bin` or `small.en`) or a path.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Detect speech with these settings instead of the configured source
+    /// and the device calibration.
+    pub fn vad(mut self, vad: VadOptions) -> Self {
+        self.vad = Some(vad);
+        self
+    }
+
+    /// Whether to serve CLI clients over the IPC socket (default on).
+    pub fn ipc_server(mut self, enabled: bool) -> Self {
+        self.ipc_server = enabled;
+        self
+    }
+
+    /// Whether to start capturing from the saved sources once the engine is
+    /// up (default on). When off, start with [`Engine::start_capture`].
+    pub fn auto_capture(mut self, enabled: bool) -> Self {
+        self.auto_capture = enabled;
+        self
+    }
+
+    /// Deliver every event to `callback` on the thread that raised it.
+    ///
+    /// Unlike [`Engine::events`] no event is ever dropped, but the callback
+    /// must return quickly.
+    pub fn on_event(mut self, callback: impl EventCallback) -> Self {
+        self.event_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Load config and history, start the audio backends, transcription,
+    /// the IPC server and capture.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub async fn build(mut self) -> Result<Engine, EngineError> {
+        let model = self.model.as_deref().map(model_path_for);
+        if let Some(ref path) = model {
+            let whisper = config::load_config().effective_transcription_engine()
+                == TranscriptionEngineKind::Whisper;
+            if whisper && !path.exists() {
+                return Err(EngineError::ModelNotFound(path.clone()));
+            }
+        }
+
+        {
+            let state = state::get_service_state();
+            let mut state = state.lock().await;
+            if state.engine_running {
+                return Err(EngineError::AlreadyRunning);
+            }
+            state.engine_running = true;
+            state.vad_override = self.vad;
+        }
+        // Clear what an engine that ran before this one left behind
+        crate::reset_shutdown();
+        ipc::handlers::get_transcription_queue().set_model(model.clone());
+
+        if let Some(callback) = self.event_callback.take() {
+            ipc::register_boxed_event_callback(callback);
+        }
+
+        let (ipc_server, tasks) = self.start().await;
+        let (stopped, _) = watch::channel(false);
+        Ok(Engine {
+            options: EngineOptions {
+                model,
+                vad: self.vad,
+            },
+            ipc_server,
+            tasks,
+            stopped,
+        })
+    }
+
+    /// Bring up the engine subsystems. Returns the IPC server task if one was
+    /// started, and the background tasks.
+    async fn start(&self) -> (Option<JoinHandle<()>>, Vec<JoinHandle<()>>) {
+        info!("FlowSTT Engine starting (pid: {})...", std::process::id());
+
+        // Detect and store runtime mode
+        let runtime_mode = flowstt_common::runtime_mode();
+        info!("Runtime mode: {:?}", runtime_mode);
+        {
+            let state = state::get_service_state();
+            let mut state = state.lock().await;
+            state.runtime_mode = runtime_mode;
+        }
+
+        // Load transcription history and clean up old WAV files (>24h)
+        {
+            let history = history::get_history();
+            let mut h = history.lock().unwrap();
+            info!("Loaded {} history entries", h.get_entries().len());
+            h.cleanup_wav_files(std::time::Duration::from_secs(86400));
+        }
+
+        // Merge history and rules from other machines through the sync folder
+        sync::merge();
+
+        // Ensure recordings directory exists
+        {
+            let recordings_dir = history::TranscriptionHistory::recordings_dir();
+            if let Err(e) = std::fs::create_dir_all(&recordings_dir) {
+                warn!(
+                    "Failed to create recordings directory {:?}: {}",
+                    recordings_dir, e
+                );
+            }
+        }
+
+        // Load configuration from disk and apply to engine state
+        let loaded_config = config::load_config();
+        config::broadcast_changes();
+
+        // Encrypt recordings saved before history encryption was turned on
+        if loaded_config.encrypt_history {
+            tokio::task::spawn_blocking(|| {
+                history_crypto::encrypt_plaintext_files(
+                    &history::TranscriptionHistory::recordings_dir(),
+                )
+            });
+        }
+        {
+            let state = state::get_service_state();
+            let mut state = state.lock().await;
+            state.transcription_mode = loaded_config.transcription_mode;
+            state.ptt_hotkeys = loaded_config.ptt_hotkeys.clone();
+            state.auto_toggle_hotkeys = loaded_config.auto_toggle_hotkeys.clone();
+            info!(
+                "Applied config: transcription_mode={:?}, ptt_hotkeys={} combination(s), auto_toggle_hotkeys={} combination(s)",
+                state.transcription_mode,
+                state.ptt_hotkeys.len(),
+                state.auto_toggle_hotkeys.len()
+            );
+        }
+
+        // Start the IPC server so CLI clients can connect immediately.
+        let ipc_server = if self.ipc_server {
+            let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+            let handle = tokio::spawn(async {
+                if let Err(e) = ipc::run_server(Some(ready_tx)).await {
+                    if !crate::is_shutdown_requested() {
+                        error!("IPC server error: {}", e);
+                    }
+                }
+            });
+
+            // Wait until the IPC server is actually listening before proceeding.
+            let _ = ready_rx.await;
+            Some(handle)
+        } else {
+            None
+        };
+
+        // Initialize platform-specific audio backends
+        info!("Initializing audio backends...");
+        if let Err(e) = platform::init_audio_backend() {
+            error!("Failed to initialize audio backend: {}", e);
+        }
+
+        // Initialize transcription system (worker ready to process segments)
+        ipc::handlers::init_transcription_system();
+
+        // During first-time setup, skip hotkey initialization and auto-capture
+        // entirely. The setup wizard will explicitly start capture (and thus
+        // hotkey listening) only when the user reaches the test page.
+        let first_run = flowstt_common::config::Config::needs_setup();
+
+        if !first_run {
+            // Initialize hotkey backend (non-fatal if unavailable)
+            info!("Initializing hotkey backend...");
+            if let Err(e) = hotkey::init_hotkey_backend() {
+                info!("Hotkey backend not available: {}", e);
+            }
+        }
+
+        // Auto-configure audio sources and start capture immediately,
+        // but only if first-time setup is already complete.
+        if !first_run && self.auto_capture {
+            let state_arc = state::get_service_state();
+
+            // Resolve primary input device: prefer saved preference, fall back to first available.
+            let source1_id = platform::get_backend().and_then(|b| {
+                let input_devices = b.list_input_devices();
+                if let Some(preferred_id) = loaded_config.preferred_source1_id.as_deref() {
+                    if let Some(found) = input_devices.iter().find(|d| d.id == preferred_id) {
+                        info!("Restoring saved primary audio source: {}", found.id);
+                        return Some(found.id.clone());
+                    }
+                    warn!(
+                        "Saved primary device {:?} not found; falling back to first available",
+                        preferred_id
+                    );
+                }
+                input_devices.into_iter().next().map(|d| {
+                    info!("Using default primary audio source: {}", d.id);
+                    d.id
+                })
+            });
+
+            // Resolve reference (system) device: prefer saved preference, fall back to None.
+            let source2_id = platform::get_backend().and_then(|b| {
+                let preferred_id = loaded_config.preferred_source2_id.as_deref()?;
+                let system_devices = b.list_system_devices();
+                if let Some(found) = system_devices.iter().find(|d| d.id == preferred_id) {
+                    info!("Restoring saved reference audio source: {}", found.id);
+                    Some(found.id.clone())
+                } else {
+                    warn!(
+                        "Saved reference device {:?} not found; starting with no reference source",
+                        preferred_id
+                    );
+                    None
+                }
+            });
+
+            if let Some(source_id) = source1_id {
+                // Configure state with resolved sources
+                {
+                    let mut state = state_arc.lock().await;
+                    state.source1_id = Some(source_id);
+                    state.source2_id = source2_id;
+                }
+
+                // Start capture (handles both Automatic and PTT modes)
+                match ipc::handlers::start_capture().await {
+                    Ok(()) => {
+                        let state = state_arc.lock().await;
+                        info!("Capture started in {:?} mode", state.transcription_mode);
+                    }
+                    Err(e) => {
+                        error!("Failed to start capture: {}", e);
+                        capture_retry::schedule(e);
+                    }
+                }
+            } else {
+                warn!(
+                    "No audio input devices found; waiting for client to configure via SetSources"
+                );
+            }
+        } else if first_run {
+            info!("First-time setup pending; skipping auto-capture (waiting for setup wizard)");
+        }
+
+        let mut tasks = Vec::new();
+
+        // Evaluate process rules (pause / push-to-talk while specific apps run)
+        tasks.push(tokio::spawn(process_watch::run_process_watcher()));
+
+        // Clear captions from the caption file once they're old
+        tasks.push(tokio::spawn(caption_file::run_caption_expiry()));
+
+        // Apply the power policy (fewer threads, push-to-talk on battery)
+        tasks.push(tokio::spawn(power::run_power_watcher()));
+
+        // Lighten transcription when it falls behind
+        tasks.push(tokio::spawn(load_shedding::run_load_monitor()));
+
+        // Tell clients when a permission is granted or revoked
+        tasks.push(tokio::spawn(permissions::run_permission_watcher()));
+
+        // Look for new releases when the config asks for it
+        tasks.push(tokio::spawn(updates::run_update_checker()));
+
+        // Restart engine threads that stop responding
+        tasks.push(tokio::spawn(watchdog::run_watchdog()));
+
+        // Restart capture and hotkeys when the system wakes from sleep
+        tasks.push(tokio::spawn(sleep_watch::run_sleep_watcher()));
+
+        // Pause on lock and refresh capture after unlock or a Remote Desktop reconnect
+        #[cfg(target_os = "windows")]
+        tasks.push(tokio::spawn(session_watch::run_session_watcher()));
+
+        info!("Engine initialization complete");
+
+        (ipc_server, tasks)
+    }
+}
+
+/// A running engine.
+pub struct Engine {
+    options: EngineOptions,
+    ipc_server: Option<JoinHandle<()>>,
+    /// Background tasks, stopped on shutdown
+    tasks: Vec<JoinHandle<()>>,
+    /// Set once the engine has shut down, which ends the event streams
+    stopped: watch::Sender<bool>,
+}
+
+impl Engine {
+    /// Stream of engine events from now on.
+    ///
+    /// Each stream has its own buffer; a consumer that falls far behind
+    /// skips the events it missed. The stream ends after the events raised
+    /// while the engine shuts down.
+    pub fn events(&self) -> impl Stream<Item = EngineEvent> + Send + 'static {
+        let receiver = ipc::server::get_event_sender().subscribe();
+        let stopped = self.stopped.subscribe();
+        futures::stream::unfold(
+            (receiver, stopped),
+            |(mut receiver, mut stopped)| async move {
+                loop {
+                    let received = tokio::select! {
+                        biased;
+                        received = receiver.recv() => received,
+                        _ = wait_stopped(&mut stopped) => return None,
+                    };
+                    match received {
+                        Ok(Response::Event { event }) => return Some((event, (receiver, stopped))),
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!(
+                                "[Engine] Event stream fell behind, skipped {} event(s)",
+                                missed
+                            );
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        )
+    }
+
+    /// The whisper model this engine was built with, if any.
+    pub fn model(&self) -> Option<&Path> {
+        self.options.model.as_deref()
+    }
+
+    /// The speech detection settings this engine was built with, if any.
+    pub fn vad(&self) -> Option<VadOptions> {
+        self.options.vad
+    }
+
+    /// Handle a request, as if sent by an IPC client.
+    pub async fn request(&self, request: Request) -> Response {
+        ipc::handlers::handle_request(request).await
+    }
+
+    /// Start capturing from the configured sources.
+    pub async fn start_capture(&self) -> Result<(), String> {
+        ipc::handlers::start_capture().await
+    }
+
+    /// Stop capturing. Segments already recorded are still transcribed.
+    pub async fn stop_capture(&self) {
+        ipc::handlers::stop_capture().await;
+    }
+
+    /// Whether this engine serves IPC clients.
+    pub fn has_ipc_server(&self) -> bool {
+        self.ipc_server.is_some()
+    }
+
+    /// Stop capture, finish queued transcriptions, save history and stop the
+    /// engine. Another engine can be built afterwards.
+    pub async fn shutdown(self) {
+        crate::shutdown::graceful_shutdown().await;
+        crate::cleanup();
+        if let Some(server) = self.ipc_server {
+            server.abort();
+        }
+        for task in self.tasks {
+            task.abort();
+        }
+
+        ipc::server::clear_event_callback();
+        ipc::handlers::get_transcription_queue().set_model(None);
+        {
+            let state = state::get_service_state();
+            let mut state = state.lock().await;
+            state.engine_running = false;
+            state.vad_override = None;
+        }
+        let _ = self.stopped.send(true);
+        info!("Engine stopped");
+    }
+}
+
+/// Wait until the engine has shut down. Never returns if the handle is
+/// dropped without shutting the engine down, since it keeps running.
+async fn wait_stopped(stopped: &mut watch::Receiver<bool>) {
+    if stopped.wait_for(|stopped| *stopped).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
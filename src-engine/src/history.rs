@@ -2,15 +2,21 @@
 //!
 //! Stores transcription results with metadata in a JSON file alongside
 //! cached WAV recordings in the OS-standard application data directory.
+//! Both can optionally be encrypted at rest (see `crate::history_crypto`).
 
-use chrono::Utc;
+use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use flowstt_common::{paths, HistoryBucket, HistoryGrouping, HistorySummary, TranscriptionMode};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::{info, warn};
 
+use crate::history_crypto;
+use crate::transcription::CompletedSegment;
+
 /// A single entry in the transcription history.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -18,10 +24,53 @@ pub struct HistoryEntry {
     pub id: String,
     /// Transcribed text
     pub text: String,
-    /// ISO 8601 timestamp of when the transcription occurred
+    /// RFC 3339 UTC timestamp of when the transcription occurred
     pub timestamp: String,
     /// Path to the cached WAV file, if it still exists
     pub wav_path: Option<String>,
+    /// Size of the cached WAV file in bytes, while it exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_bytes: Option<u64>,
+    /// Length of the transcribed audio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Mode the audio was captured in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TranscriptionMode>,
+    /// The first transcript, kept once the text has been replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_text: Option<String>,
+    /// Model file the entry was first transcribed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_used: Option<String>,
+    /// Model file the current text was re-transcribed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The current text was corrected by hand
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub edited: bool,
+    /// Recorded in privacy mode; kept in memory only and never saved
+    #[serde(skip)]
+    pub ephemeral: bool,
+}
+
+impl HistoryEntry {
+    /// Convert to the IPC representation.
+    pub fn to_common(&self) -> flowstt_common::HistoryEntry {
+        flowstt_common::HistoryEntry {
+            id: self.id.clone(),
+            text: self.text.clone(),
+            timestamp: self.timestamp.clone(),
+            wav_path: self.wav_path.clone(),
+            audio_bytes: self.audio_bytes,
+            duration_ms: self.duration_ms,
+            mode: self.mode,
+            model_used: self.model_used.clone(),
+            original_text: self.original_text.clone(),
+            model: self.model.clone(),
+            edited: self.edited,
+        }
+    }
 }
 
 /// Manages persistent transcription history.
@@ -33,16 +82,16 @@ pub struct TranscriptionHistory {
 }
 
 impl TranscriptionHistory {
-    /// Get the application data directory for FlowSTT.
+    /// Get the application data directory for FlowSTT (next to the
+    /// executable in portable mode).
     pub fn data_dir() -> PathBuf {
-        directories::ProjectDirs::from("", "", "flowstt")
-            .map(|dirs| dirs.data_dir().to_path_buf())
-            .unwrap_or_else(|| PathBuf::from(".").join("flowstt-data"))
+        paths::data_dir()
     }
 
-    /// Get the recordings subdirectory within the data directory.
+    /// Get the directory recordings are saved to: the `paths` override from
+    /// the config, or the recordings subdirectory within the data directory.
     pub fn recordings_dir() -> PathBuf {
-        Self::data_dir().join("recordings")
+        paths::recordings_dir()
     }
 
     /// Load history from disk, creating directories as needed.
@@ -53,9 +102,9 @@ impl TranscriptionHistory {
         }
 
         let history_path = data_dir.join("history.json");
-        let entries = if history_path.exists() {
-            match fs::read_to_string(&history_path) {
-                Ok(content) => match serde_json::from_str::<Vec<HistoryEntry>>(&content) {
+        let mut entries = if history_path.exists() {
+            match history_crypto::read_file(&history_path) {
+                Ok(content) => match serde_json::from_slice::<Vec<HistoryEntry>>(&content) {
                     Ok(entries) => {
                         info!(
                             "Loaded {} history entries from {:?}",
@@ -69,14 +118,14 @@ impl TranscriptionHistory {
                             "Corrupted history file, backing up and starting fresh: {}",
                             e
                         );
-                        // Backup corrupted file
-                        let backup_path = data_dir.join("history.json.bak");
-                        let _ = fs::rename(&history_path, &backup_path);
+                        history_crypto::move_aside(&history_path);
                         Vec::new()
                     }
                 },
                 Err(e) => {
                     warn!("Failed to read history file: {}", e);
+                    // Keep the unreadable file rather than overwriting it on the next save
+                    history_crypto::move_aside(&history_path);
                     Vec::new()
                 }
             }
@@ -85,34 +134,130 @@ impl TranscriptionHistory {
             Vec::new()
         };
 
-        Self {
+        // Point entries at recordings moved out of an older data directory
+        let mut relocated = 0u32;
+        for entry in &mut entries {
+            let moved = entry
+                .wav_path
+                .as_deref()
+                .and_then(|wav_path| paths::relocate(Path::new(wav_path)));
+            if let Some(path) = moved {
+                entry.wav_path = Some(path.to_string_lossy().into_owned());
+                relocated += 1;
+            }
+        }
+        if relocated > 0 {
+            info!("Updated {} relocated recording path(s)", relocated);
+        }
+
+        let upgraded = entries.iter_mut().filter(|e| upgrade_entry(e)).count();
+        if upgraded > 0 {
+            info!("Upgraded {} history entries to UTC timestamps", upgraded);
+        }
+
+        let history = Self {
             history_path,
             entries,
+        };
+        if relocated > 0 || upgraded > 0 {
+            if let Err(e) = history.save() {
+                warn!("Failed to save updated history: {}", e);
+            }
         }
+        history
     }
 
-    /// Save history to disk.
+    /// Save history to disk. Ephemeral entries are left out.
+    ///
+    /// The file is encrypted when `encrypt_history` is enabled.
     pub fn save(&self) -> Result<(), String> {
-        let content = serde_json::to_string_pretty(&self.entries)
+        let persistent: Vec<&HistoryEntry> = self.entries.iter().filter(|e| !e.ephemeral).collect();
+        let content = serde_json::to_string_pretty(&persistent)
             .map_err(|e| format!("Failed to serialize history: {}", e))?;
-        fs::write(&self.history_path, content)
+        let encrypted = crate::config::current_config().encrypt_history;
+        history_crypto::write_file(&self.history_path, content.as_bytes(), encrypted)
             .map_err(|e| format!("Failed to write history file: {}", e))?;
         Ok(())
     }
 
-    /// Add a new entry to the history and save.
-    pub fn add_entry(&mut self, text: String, wav_path: Option<String>) -> HistoryEntry {
-        let entry = HistoryEntry {
+    /// Create the entry for a new transcription, without adding it.
+    pub fn new_entry(text: String, segment: &CompletedSegment) -> HistoryEntry {
+        HistoryEntry {
             id: generate_id(),
             text,
-            timestamp: Utc::now().to_rfc3339(),
-            wav_path,
-        };
-        self.entries.push(entry.clone());
-        if let Err(e) = self.save() {
-            warn!("Failed to save history after adding entry: {}", e);
+            timestamp: format_timestamp(Utc::now()),
+            wav_path: segment.wav_path.clone(),
+            audio_bytes: segment.wav_path.as_deref().and_then(file_size),
+            duration_ms: Some(segment.duration_ms),
+            mode: segment.capture_mode,
+            model_used: segment.model.clone(),
+            original_text: None,
+            model: None,
+            edited: false,
+            ephemeral: segment.ephemeral,
+        }
+    }
+
+    /// Add an entry to the history and save.
+    ///
+    /// Ephemeral entries are only kept in memory, so nothing is written.
+    pub fn add_entry(&mut self, entry: HistoryEntry) {
+        if !entry.ephemeral {
+            crate::sync::entry_changed(&entry);
+        }
+        let ephemeral = entry.ephemeral;
+        self.entries.push(entry);
+        if !ephemeral {
+            if let Err(e) = self.save() {
+                warn!("Failed to save history after adding entry: {}", e);
+            }
+        }
+    }
+
+    /// Replace an entry's text with a new transcription and save. The first
+    /// transcript is kept in `original_text`. Returns the updated entry, or
+    /// `None` if it no longer exists.
+    pub fn update_transcript(
+        &mut self,
+        id: &str,
+        text: String,
+        model: Option<String>,
+    ) -> Option<HistoryEntry> {
+        self.replace_text(id, text, |entry| {
+            entry.model = model;
+            entry.edited = false;
+        })
+    }
+
+    /// Replace an entry's text with a manual correction and save. The first
+    /// transcript is kept in `original_text`. Returns the updated entry, or
+    /// `None` if it no longer exists.
+    pub fn edit_text(&mut self, id: &str, text: String) -> Option<HistoryEntry> {
+        self.replace_text(id, text, |entry| entry.edited = true)
+    }
+
+    fn replace_text(
+        &mut self,
+        id: &str,
+        text: String,
+        mark: impl FnOnce(&mut HistoryEntry),
+    ) -> Option<HistoryEntry> {
+        let entry = self.entries.iter_mut().find(|e| e.id == id)?;
+        if entry.original_text.is_none() {
+            entry.original_text = Some(std::mem::replace(&mut entry.text, text));
+        } else {
+            entry.text = text;
+        }
+        mark(entry);
+        let entry = entry.clone();
+
+        if !entry.ephemeral {
+            crate::sync::entry_changed(&entry);
+            if let Err(e) = self.save() {
+                warn!("Failed to save history after updating entry: {}", e);
+            }
         }
-        entry
+        Some(entry)
     }
 
     /// Delete an entry by ID. Returns true if found and deleted.
@@ -120,6 +265,9 @@ impl TranscriptionHistory {
     pub fn delete_entry(&mut self, id: &str) -> bool {
         if let Some(pos) = self.entries.iter().position(|e| e.id == id) {
             let entry = self.entries.remove(pos);
+            if !entry.ephemeral {
+                crate::sync::entry_deleted(&entry.id);
+            }
             // Delete WAV file if it exists
             if let Some(ref wav_path) = entry.wav_path {
                 let path = Path::new(wav_path);
@@ -145,6 +293,27 @@ impl TranscriptionHistory {
         &self.entries
     }
 
+    /// Total the entries by local day or hour.
+    pub fn summary(&self, group_by: HistoryGrouping) -> HistorySummary {
+        summarize(&self.entries, group_by)
+    }
+
+    /// Read a saved recording, decrypting it if needed.
+    ///
+    /// Only files inside the recordings directory can be read, or inside the
+    /// default one for recordings made before it was moved.
+    pub fn read_recording(path: &Path) -> Result<Vec<u8>, String> {
+        let path = fs::canonicalize(path).map_err(|e| format!("Recording not found: {}", e))?;
+        let allowed = [Self::recordings_dir(), paths::default_recordings_dir()]
+            .iter()
+            .filter_map(|dir| fs::canonicalize(dir).ok())
+            .any(|dir| path.starts_with(dir));
+        if !allowed {
+            return Err("Path is outside the recordings directory".into());
+        }
+        history_crypto::read_file(&path)
+    }
+
     /// Clean up WAV files older than the specified duration.
     /// Sets wav_path to None for affected entries but preserves the text.
     pub fn cleanup_wav_files(&mut self, max_age: Duration) {
@@ -180,16 +349,84 @@ impl TranscriptionHistory {
 
         if cleaned > 0 {
             info!("Cleaned up {} old WAV file(s)", cleaned);
+            self.forget_missing_recordings();
+        }
+    }
 
-            // Nullify wav_path references for deleted files
-            for entry in &mut self.entries {
-                if let Some(ref wav_path) = entry.wav_path {
-                    if !Path::new(wav_path).exists() {
-                        entry.wav_path = None;
+    /// Replace all entries with ones restored from a backup and save them.
+    /// Entries whose recording isn't on this machine lose it.
+    pub fn restore(&mut self, entries: Vec<HistoryEntry>) -> Result<(), String> {
+        self.entries = entries;
+        self.save()?;
+        self.forget_missing_recordings();
+        Ok(())
+    }
+
+    /// Apply the state of entries merged from the sync folder: entries that
+    /// are new here are added without a recording, changed text is taken
+    /// over and deleted entries are removed with their recording. Returns
+    /// the number added, updated and removed.
+    pub fn apply_synced(
+        &mut self,
+        synced: HashMap<String, Option<HistoryEntry>>,
+    ) -> (usize, usize, usize) {
+        let (mut added, mut updated, mut removed) = (0, 0, 0);
+        for (id, synced) in synced {
+            let local = self.entries.iter().position(|e| e.id == id);
+            match (local, synced) {
+                (Some(pos), None) => {
+                    let entry = self.entries.remove(pos);
+                    if let Some(wav_path) = entry.wav_path {
+                        let _ = fs::remove_file(wav_path);
                     }
+                    removed += 1;
+                }
+                (Some(pos), Some(synced)) => {
+                    let entry = &mut self.entries[pos];
+                    if entry.text != synced.text || entry.edited != synced.edited {
+                        entry.text = synced.text;
+                        entry.original_text = synced.original_text;
+                        entry.model = synced.model;
+                        entry.edited = synced.edited;
+                        updated += 1;
+                    }
+                }
+                (None, Some(mut synced)) => {
+                    synced.wav_path = None;
+                    synced.audio_bytes = None;
+                    self.entries.push(synced);
+                    added += 1;
                 }
+                (None, None) => {}
             }
+        }
+
+        if added + updated + removed > 0 {
+            if added > 0 {
+                self.entries
+                    .sort_by_cached_key(|e| parse_timestamp(&e.timestamp).unwrap_or_default());
+            }
+            if let Err(e) = self.save() {
+                warn!("Failed to save history after syncing: {}", e);
+            }
+        }
+        (added, updated, removed)
+    }
+
+    /// Clear wav_path for entries whose recording has been deleted.
+    pub fn forget_missing_recordings(&mut self) {
+        let mut changed = false;
+        for entry in &mut self.entries {
+            if let Some(ref wav_path) = entry.wav_path {
+                if !Path::new(wav_path).exists() {
+                    entry.wav_path = None;
+                    entry.audio_bytes = None;
+                    changed = true;
+                }
+            }
+        }
 
+        if changed {
             if let Err(e) = self.save() {
                 warn!("Failed to save history after WAV cleanup: {}", e);
             }
@@ -197,20 +434,113 @@ impl TranscriptionHistory {
     }
 }
 
+/// Count, words and audio length of `entries` by the local day or hour
+/// they were transcribed in.
+fn summarize(entries: &[HistoryEntry], group_by: HistoryGrouping) -> HistorySummary {
+    let bucket_format = match group_by {
+        HistoryGrouping::Day => "%Y-%m-%d",
+        HistoryGrouping::Hour => "%Y-%m-%dT%H:00",
+    };
+
+    let mut buckets: BTreeMap<String, HistoryBucket> = BTreeMap::new();
+    for entry in entries {
+        let Some(time) = parse_timestamp(&entry.timestamp) else {
+            continue;
+        };
+        let start = time.with_timezone(&Local).format(bucket_format).to_string();
+        let bucket = buckets
+            .entry(start.clone())
+            .or_insert_with(|| HistoryBucket {
+                start,
+                entries: 0,
+                words: 0,
+                duration_ms: 0,
+            });
+        bucket.entries += 1;
+        bucket.words += entry.text.split_whitespace().count() as u64;
+        bucket.duration_ms += entry.duration_ms.unwrap_or(0);
+    }
+
+    HistorySummary {
+        group_by,
+        buckets: buckets.into_values().collect(),
+    }
+}
+
+/// Format a timestamp the way history stores it: RFC 3339 in UTC.
+fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Bring an entry saved by an older version up to date: a UTC timestamp, and
+/// the length and size of its recording if it still has one. Returns whether
+/// anything changed.
+fn upgrade_entry(entry: &mut HistoryEntry) -> bool {
+    let mut changed = false;
+    if let Some(timestamp) = parse_timestamp(&entry.timestamp).map(format_timestamp) {
+        if timestamp != entry.timestamp {
+            entry.timestamp = timestamp;
+            changed = true;
+        }
+    }
+    if entry.duration_ms.is_none() {
+        let duration_ms = entry
+            .wav_path
+            .as_deref()
+            .and_then(|wav_path| recording_duration_ms(Path::new(wav_path)));
+        if duration_ms.is_some() {
+            entry.duration_ms = duration_ms;
+            changed = true;
+        }
+    }
+    if entry.audio_bytes.is_none() {
+        entry.audio_bytes = entry.wav_path.as_deref().and_then(file_size);
+        changed |= entry.audio_bytes.is_some();
+    }
+    changed
+}
+
+fn file_size(path: &str) -> Option<u64> {
+    fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// Parse an RFC 3339 timestamp, or a local time without an offset.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(timestamp, format).ok())
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Length of a saved recording, from its WAV header.
+fn recording_duration_ms(path: &Path) -> Option<u64> {
+    let data = history_crypto::read_file(path).ok()?;
+    let reader = hound::WavReader::new(std::io::Cursor::new(data)).ok()?;
+    let sample_rate = u64::from(reader.spec().sample_rate);
+    (sample_rate > 0).then(|| u64::from(reader.duration()) * 1000 / sample_rate)
+}
+
 /// Generate a unique ID for a history entry.
-fn generate_id() -> String {
+pub(crate) fn generate_id() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis();
-    // Simple ID: timestamp + random suffix
+    // Simple ID: timestamp + random suffix, with the machine while syncing
     let random: u32 = rand_u32();
-    format!("{}-{:08x}", timestamp, random)
+    match crate::sync::id_tag() {
+        Some(machine) => format!("{}-{}-{:08x}", timestamp, machine, random),
+        None => format!("{}-{:08x}", timestamp, random),
+    }
 }
 
 /// Simple pseudo-random u32 without external dependency.
-fn rand_u32() -> u32 {
+pub(crate) fn rand_u32() -> u32 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -238,3 +568,47 @@ pub fn get_history() -> Arc<Mutex<TranscriptionHistory>> {
         .get_or_init(|| Arc::new(Mutex::new(TranscriptionHistory::load())))
         .clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, text: &str, duration_ms: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            id: generate_id(),
+            text: text.to_string(),
+            timestamp: timestamp.to_string(),
+            wav_path: None,
+            audio_bytes: None,
+            duration_ms,
+            mode: None,
+            original_text: None,
+            model_used: None,
+            model: None,
+            edited: false,
+            ephemeral: false,
+        }
+    }
+
+    #[test]
+    fn summary_totals_entries_by_day_and_hour() {
+        let entries = [
+            entry("2024-05-01T12:00:00.000Z", "Hello there ", Some(1500)),
+            entry("2024-05-01T12:30:00.000Z", "How are you? ", None),
+            entry("2024-05-02T12:00:00.000Z", "Fine ", Some(800)),
+            entry("not a time", "Lost ", Some(100)),
+        ];
+
+        let days = summarize(&entries, HistoryGrouping::Day);
+        assert_eq!(days.buckets.len(), 2);
+        assert_eq!(days.buckets[0].entries, 2);
+        assert_eq!(days.buckets[0].words, 5);
+        assert_eq!(days.buckets[0].duration_ms, 1500);
+        assert_eq!(days.buckets[1].words, 1);
+        assert!(days.buckets[0].start < days.buckets[1].start);
+
+        let hours = summarize(&entries, HistoryGrouping::Hour);
+        assert_eq!(hours.buckets.len(), 2);
+        assert!(hours.buckets[0].start.ends_with(":00"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Encryption at rest for the history file and saved recordings.
+//!
+//! When `encrypt_history` is enabled in the config, files are sealed with
+//! ChaCha20-Poly1305 before they are written. The 256-bit key is generated on
+//! first use and kept in the OS keychain (Keychain on macOS, Credential
+//! Manager on Windows, Secret Service on Linux), never on disk.
+//!
+//! Encrypted files start with a magic header followed by the nonce and the
+//! ciphertext. Files without the header are read as plaintext, so history
+//! written before encryption was enabled stays readable and is re-encrypted
+//! on the next save. Recordings saved before then are encrypted by
+//! [`encrypt_plaintext_files`] the next time the engine starts. Encrypted
+//! files remain readable after the setting is turned off, as long as the
+//! keychain entry exists.
+//!
+//! A file that can't be read, say because the keychain is still locked at
+//! login, is moved aside under a timestamped name rather than overwritten
+//! by the next save.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::Local;
+use tracing::{info, warn};
+
+/// Header identifying an encrypted file
+const MAGIC: &[u8; 8] = b"FSTTENC1";
+
+/// ChaCha20-Poly1305 nonce length in bytes
+const NONCE_LEN: usize = 12;
+
+/// Keychain service name
+const KEYRING_SERVICE: &str = "flowstt";
+
+/// Keychain account holding the history key
+const KEYRING_USER: &str = "history-key";
+
+/// Key cached after the first successful keychain lookup
+static KEY: Mutex<Option<Key>> = Mutex::new(None);
+
+/// Whether the data carries the encrypted file header.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Load the history key from the keychain, creating it on first use.
+fn key() -> Result<Key, String> {
+    let mut cached = KEY.lock().unwrap();
+    if let Some(key) = *cached {
+        return Ok(key);
+    }
+
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))?;
+    let key = match entry.get_secret() {
+        Ok(secret) if secret.len() == 32 => *Key::from_slice(&secret),
+        Ok(_) => return Err("History key in keychain has an invalid length".into()),
+        Err(keyring::Error::NoEntry) => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            entry
+                .set_secret(&key)
+                .map_err(|e| format!("Failed to store history key in keychain: {}", e))?;
+            info!("[HistoryCrypto] Created history encryption key in keychain");
+            key
+        }
+        Err(e) => return Err(format!("Failed to read history key from keychain: {}", e)),
+    };
+
+    *cached = Some(key);
+    Ok(key)
+}
+
+/// Encrypt data, prefixing the header and a fresh random nonce.
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    seal(&key()?, plaintext)
+}
+
+/// Encrypt data with the given key.
+fn seal(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = ChaCha20Poly1305::new(key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt data".to_string())?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt data produced by [`encrypt`]. Data without the header is
+/// returned unchanged.
+pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) {
+        return Ok(data.to_vec());
+    }
+    open(&key()?, data)
+}
+
+/// Decrypt data carrying the header with the given key.
+fn open(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = &data[MAGIC.len()..];
+    if body.len() < NONCE_LEN {
+        return Err("Encrypted data is truncated".into());
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+    let cipher = ChaCha20Poly1305::new(key);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt data (wrong key or corrupted file)".to_string())
+}
+
+/// Write a file, encrypted first if `encrypted` is set, normally from the
+/// config's `encrypt_history`.
+pub fn write_file(path: &Path, data: &[u8], encrypted: bool) -> Result<(), String> {
+    if encrypted {
+        fs::write(path, encrypt(data)?)
+    } else {
+        fs::write(path, data)
+    }
+    .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// Read a file, decrypting it if it is encrypted.
+pub fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    decrypt(&data)
+}
+
+/// Encrypt the WAV files in `dir` that are still in plaintext, i.e.
+/// recordings saved before `encrypt_history` was turned on.
+///
+/// Each file is written under a temporary name and renamed over the
+/// original, so it is never left half-encrypted. Stops at the first
+/// failure, which is most likely the keychain being unavailable.
+pub fn encrypt_plaintext_files(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut encrypted = 0;
+    for path in entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+    {
+        match encrypt_in_place(&path) {
+            Ok(true) => encrypted += 1,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("[HistoryCrypto] Failed to encrypt {:?}: {}", path, e);
+                break;
+            }
+        }
+    }
+    if encrypted > 0 {
+        info!(
+            "[HistoryCrypto] Encrypted {} recordings saved before encryption was enabled",
+            encrypted
+        );
+    }
+}
+
+/// Encrypt a file unless it already is. Returns whether it was encrypted.
+fn encrypt_in_place(path: &Path) -> Result<bool, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read: {}", e))?;
+    if is_encrypted(&data) {
+        return Ok(false);
+    }
+    let tmp = path.with_extension("wav.tmp");
+    fs::write(&tmp, encrypt(&data)?).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace: {}", e))?;
+    Ok(true)
+}
+
+/// Move a file that couldn't be read out of the way of the next save, to
+/// `<name>.<YYYYMMDD-HHMMSS>.bak` next to it, or `...-2.bak` and so on if
+/// that is taken, so no earlier backup is ever replaced. Returns the new
+/// path.
+pub fn move_aside(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy();
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let backup = (1..)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{}.{}.bak", name, stamp)),
+            n => path.with_file_name(format!("{}.{}-{}.bak", name, stamp, n)),
+        })
+        .find(|backup| !backup.exists())?;
+    match fs::rename(path, &backup) {
+        Ok(()) => {
+            warn!(
+                "[HistoryCrypto] Moved unreadable {:?} to {:?}",
+                path, backup
+            );
+            Some(backup)
+        }
+        Err(e) => {
+            warn!(
+                "[HistoryCrypto] Failed to move unreadable {:?} aside: {}",
+                path, e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &[u8] = br#"[{"text":"hello world"}]"#;
+
+    #[test]
+    fn sealed_data_opens_with_the_same_key() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = seal(&key, HISTORY).unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(HISTORY.len()).any(|w| w == HISTORY));
+        assert_eq!(open(&key, &sealed).unwrap(), HISTORY);
+        // Every seal uses a fresh nonce
+        assert_ne!(seal(&key, HISTORY).unwrap(), sealed);
+    }
+
+    #[test]
+    fn wrong_key_is_rejected() {
+        let sealed = seal(&ChaCha20Poly1305::generate_key(&mut OsRng), HISTORY).unwrap();
+        let other = ChaCha20Poly1305::generate_key(&mut OsRng);
+
+        assert!(open(&other, &sealed).is_err());
+    }
+
+    #[test]
+    fn truncated_header_is_rejected() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = seal(&key, HISTORY).unwrap();
+
+        let err = open(&key, &sealed[..MAGIC.len() + NONCE_LEN - 1]).unwrap_err();
+        assert!(err.contains("truncated"));
+        assert!(open(&key, &sealed[..MAGIC.len() + NONCE_LEN]).is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let mut sealed = seal(&key, HISTORY).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+
+        assert!(open(&key, &sealed).is_err());
+    }
+
+    #[test]
+    fn plaintext_passes_through() {
+        assert!(!is_encrypted(HISTORY));
+        assert!(!is_encrypted(b"RIFF\0\0\0\0WAVE"));
+        assert_eq!(decrypt(HISTORY).unwrap(), HISTORY);
+    }
+
+    #[test]
+    fn unreadable_files_never_replace_an_earlier_backup() {
+        let dir = std::env::temp_dir().join(format!("flowstt-move-aside-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.json");
+
+        fs::write(&path, "first").unwrap();
+        let first = move_aside(&path).unwrap();
+        fs::write(&path, "second").unwrap();
+        let second = move_aside(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second");
+        let name = first.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("history.json.") && name.ends_with(".bak"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,97 @@
+//! Playback of saved history recordings.
+//!
+//! Lets users hear exactly what the engine heard for a history entry. The
+//! recording is read through [`TranscriptionHistory::read_recording`] so
+//! encrypted files play too, and is played on the default output device.
+//! Only one recording plays at a time: starting another stops the first.
+//! `HistoryAudioFinished` is broadcast whenever playback ends or is stopped.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Response};
+use tracing::info;
+
+use crate::history::{get_history, TranscriptionHistory};
+use crate::ipc::broadcast_event;
+use crate::playback::PlaybackHandle;
+use crate::test_mode::playback::play_wav_data;
+
+/// The recording currently playing.
+struct ActivePlayback {
+    /// Incremented per playback so a finished run never clears a newer one
+    generation: u64,
+    handle: PlaybackHandle,
+}
+
+static ACTIVE: Mutex<Option<ActivePlayback>> = Mutex::new(None);
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Start playing the recording saved for a history entry, stopping any
+/// recording already playing. Returns the recording's length if known.
+pub fn play(id: &str) -> Result<Option<Duration>, String> {
+    let wav_path = {
+        let history = get_history();
+        let history = history.lock().unwrap();
+        let entry = history
+            .get_entries()
+            .iter()
+            .find(|e| e.id == id)
+            .ok_or_else(|| format!("History entry not found: {}", id))?;
+        entry
+            .wav_path
+            .clone()
+            .ok_or_else(|| format!("No recording saved for history entry: {}", id))?
+    };
+    let data = TranscriptionHistory::read_recording(Path::new(&wav_path))?;
+
+    stop();
+
+    let (handle, done_rx) = play_wav_data(data, format!("history entry {}", id))?;
+    let duration = handle.duration();
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *ACTIVE.lock().unwrap() = Some(ActivePlayback { generation, handle });
+    info!("[HistoryPlayback] Playing recording for {}", id);
+
+    let id = id.to_string();
+    std::thread::spawn(move || {
+        let _ = done_rx.recv();
+        {
+            let mut active = ACTIVE.lock().unwrap();
+            if active.as_ref().is_some_and(|a| a.generation == generation) {
+                *active = None;
+            }
+        }
+        broadcast_event(Response::Event {
+            event: EventType::HistoryAudioFinished { id },
+        });
+    });
+
+    Ok(duration)
+}
+
+/// Stop the recording that is playing. Returns false if none was.
+pub fn stop() -> bool {
+    match ACTIVE.lock().unwrap().take() {
+        Some(active) => {
+            active.handle.stop();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Move the playing recording to a position from its start.
+pub fn seek(position: Duration) -> Result<(), String> {
+    // Seeking waits for the output thread, so don't hold the lock meanwhile
+    let handle = ACTIVE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|a| a.handle.clone())
+        .ok_or("No history recording is playing")?;
+    handle.seek(position)
+}
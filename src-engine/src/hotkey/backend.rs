@@ -1,5 +1,6 @@
 //! Platform-agnostic hotkey backend trait.
 
+use crossbeam_channel::Receiver;
 use flowstt_common::HotkeyCombination;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -13,6 +14,10 @@ pub enum HotkeyEvent {
     PttReleased,
     /// Toggle hotkey was pressed
     TogglePressed,
+    /// Dictation session hotkey was pressed
+    DictationPressed,
+    /// Spelling mode hotkey was pressed
+    SpellingPressed,
 }
 
 /// Platform-agnostic hotkey backend interface.
@@ -31,15 +36,18 @@ pub trait HotkeyBackend: Send {
         &mut self,
         ptt_hotkeys: Vec<HotkeyCombination>,
         toggle_hotkeys: Vec<HotkeyCombination>,
+        dictation_hotkeys: Vec<HotkeyCombination>,
+        spelling_hotkeys: Vec<HotkeyCombination>,
     ) -> Result<(), String>;
 
     /// Stop monitoring for hotkey events.
     fn stop(&mut self);
 
-    /// Try to receive a hotkey event (non-blocking).
+    /// Channel delivering hotkey events while the backend is running.
     ///
-    /// Returns `Some(event)` if an event is available, `None` otherwise.
-    fn try_recv(&self) -> Option<HotkeyEvent>;
+    /// The channel disconnects when the backend is stopped or restarted, so
+    /// callers should fetch the receiver again rather than hold on to it.
+    fn events(&self) -> Option<Receiver<HotkeyEvent>>;
 
     /// Check if the backend is currently running.
     #[allow(dead_code)]
@@ -87,3 +95,23 @@ impl Default for AutoModeState {
         Self::new()
     }
 }
+
+/// Longest a backend waits for its listener thread to stop
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Wait for a listener thread to exit, but no longer than [`STOP_TIMEOUT`].
+/// A hung thread is left behind; the watchdog has replaced it, so it exits
+/// on its own if it ever wakes.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn join_listener(handle: std::thread::JoinHandle<()>) {
+    let deadline = std::time::Instant::now() + STOP_TIMEOUT;
+    while !handle.is_finished() {
+        if std::time::Instant::now() >= deadline {
+            tracing::warn!("[Hotkey] Listener thread did not stop, leaving it behind");
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let _ = handle.join();
+}
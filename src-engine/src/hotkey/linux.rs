@@ -4,9 +4,31 @@
 //! X11/XCB or libinput will be implemented in a future release.
 
 use super::backend::{AutoModeState, HotkeyBackend, HotkeyEvent};
+use crossbeam_channel::Receiver;
 use flowstt_common::HotkeyCombination;
 use std::sync::Arc;
 
+/// Active XKB layout list, such as `de` or `us,fr`.
+///
+/// Read from `setxkbmap -query` under X11, falling back to `XKB_DEFAULT_LAYOUT`
+/// (set by most Wayland compositors' configuration).
+pub fn keyboard_layout() -> Option<String> {
+    let from_x11 = std::process::Command::new("setxkbmap")
+        .arg("-query")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("layout:"))
+                .map(|layout| layout.trim().to_string())
+        });
+    from_x11
+        .or_else(|| std::env::var("XKB_DEFAULT_LAYOUT").ok())
+        .filter(|layout| !layout.is_empty())
+}
+
 /// Linux hotkey backend (stub implementation)
 pub struct LinuxHotkeyBackend {
     auto_mode_state: Arc<AutoModeState>,
@@ -25,6 +47,8 @@ impl HotkeyBackend for LinuxHotkeyBackend {
         &mut self,
         _ptt_hotkeys: Vec<HotkeyCombination>,
         _toggle_hotkeys: Vec<HotkeyCombination>,
+        _dictation_hotkeys: Vec<HotkeyCombination>,
+        _spelling_hotkeys: Vec<HotkeyCombination>,
     ) -> Result<(), String> {
         Err("Push-to-talk is not yet available on Linux. This feature will be implemented in a future release.".to_string())
     }
@@ -33,7 +57,7 @@ impl HotkeyBackend for LinuxHotkeyBackend {
         // No-op for stub
     }
 
-    fn try_recv(&self) -> Option<HotkeyEvent> {
+    fn events(&self) -> Option<Receiver<HotkeyEvent>> {
         None
     }
 
@@ -3,11 +3,12 @@
 //! This implementation uses the Core Graphics Event Tap API to monitor
 //! global keyboard events. It requires Accessibility permission to function.
 
-use super::backend::{AutoModeState, HotkeyBackend, HotkeyEvent};
-use flowstt_common::{HotkeyCombination, KeyCode};
+use super::backend::{join_listener, AutoModeState, HotkeyBackend, HotkeyEvent};
+use crate::watchdog::Heartbeat;
+use crossbeam_channel::{Receiver, Sender};
+use flowstt_common::{EngineComponent, HotkeyCombination, KeyCode};
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 use tracing::{debug, error, info};
@@ -377,6 +378,21 @@ pub struct MacOSHotkeyBackend {
     auto_mode_state: Arc<AutoModeState>,
 }
 
+/// Input source ID of the selected keyboard layout, such as
+/// `com.apple.keylayout.German`.
+pub fn keyboard_layout() -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .args([
+            "read",
+            "com.apple.HIToolbox",
+            "AppleCurrentKeyboardLayoutInputSourceID",
+        ])
+        .output()
+        .ok()?;
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !id.is_empty()).then_some(id)
+}
+
 /// Returns true if the process currently has macOS Accessibility permission.
 /// This is safe to call at any time and does not show a system dialog.
 pub fn check_accessibility_permission() -> bool {
@@ -433,12 +449,18 @@ impl HotkeyBackend for MacOSHotkeyBackend {
         &mut self,
         ptt_hotkeys: Vec<HotkeyCombination>,
         toggle_hotkeys: Vec<HotkeyCombination>,
+        dictation_hotkeys: Vec<HotkeyCombination>,
+        spelling_hotkeys: Vec<HotkeyCombination>,
     ) -> Result<(), String> {
         if self.running.load(Ordering::SeqCst) {
             return Err("Hotkey backend already running".to_string());
         }
 
-        if ptt_hotkeys.is_empty() && toggle_hotkeys.is_empty() {
+        if ptt_hotkeys.is_empty()
+            && toggle_hotkeys.is_empty()
+            && dictation_hotkeys.is_empty()
+            && spelling_hotkeys.is_empty()
+        {
             return Err("No hotkey combinations configured".to_string());
         }
 
@@ -452,11 +474,12 @@ impl HotkeyBackend for MacOSHotkeyBackend {
             return Err(msg);
         }
 
-        let (sender, receiver) = mpsc::channel();
+        let (sender, receiver) = crossbeam_channel::unbounded();
         self.receiver = Some(receiver);
 
+        // A fresh flag, so a listener left behind by the watchdog can't stop this one
+        self.running = Arc::new(AtomicBool::new(true));
         let running = self.running.clone();
-        running.store(true, Ordering::SeqCst);
 
         let auto_mode_state = self.auto_mode_state.clone();
 
@@ -467,11 +490,15 @@ impl HotkeyBackend for MacOSHotkeyBackend {
                 toggle_hotkeys.len()
             );
 
+            let heartbeat = crate::watchdog::register(EngineComponent::Hotkeys);
             if let Err(e) = run_event_tap(
                 running.clone(),
+                &heartbeat,
                 sender,
                 ptt_hotkeys,
                 toggle_hotkeys,
+                dictation_hotkeys,
+                spelling_hotkeys,
                 auto_mode_state,
             ) {
                 error!("[Hotkey] Event tap error: {}", e);
@@ -497,14 +524,14 @@ impl HotkeyBackend for MacOSHotkeyBackend {
         // The thread will exit when it detects running is false
         if let Some(handle) = self.thread_handle.take() {
             // Give the thread a moment to exit gracefully
-            let _ = handle.join();
+            join_listener(handle);
         }
 
         self.receiver = None;
     }
 
-    fn try_recv(&self) -> Option<HotkeyEvent> {
-        self.receiver.as_ref()?.try_recv().ok()
+    fn events(&self) -> Option<Receiver<HotkeyEvent>> {
+        self.receiver.clone()
     }
 
     fn is_running(&self) -> bool {
@@ -536,9 +563,12 @@ impl Drop for MacOSHotkeyBackend {
 /// Run the CGEventTap on this thread
 fn run_event_tap(
     running: Arc<AtomicBool>,
+    heartbeat: &Heartbeat,
     sender: Sender<HotkeyEvent>,
     ptt_hotkeys: Vec<HotkeyCombination>,
     toggle_hotkeys: Vec<HotkeyCombination>,
+    dictation_hotkeys: Vec<HotkeyCombination>,
+    spelling_hotkeys: Vec<HotkeyCombination>,
     auto_mode_state: Arc<AutoModeState>,
 ) -> Result<(), String> {
     unsafe {
@@ -550,9 +580,13 @@ fn run_event_tap(
             sender,
             ptt_hotkeys,
             toggle_hotkeys,
+            dictation_hotkeys,
+            spelling_hotkeys,
             pressed_keys: Mutex::new(HashSet::new()),
             any_ptt_matched: AtomicBool::new(false),
             any_toggle_matched: AtomicBool::new(false),
+            any_dictation_matched: AtomicBool::new(false),
+            any_spelling_matched: AtomicBool::new(false),
             auto_mode_state,
         });
         let context_ptr = Box::into_raw(context);
@@ -586,7 +620,7 @@ fn run_event_tap(
 
         debug!("[Hotkey] Event tap created and enabled");
 
-        while running.load(Ordering::SeqCst) {
+        while running.load(Ordering::SeqCst) && heartbeat.beat() {
             let result = macos_ffi::CFRunLoopRunInMode(macos_ffi::kCFRunLoopDefaultMode, 0.1, true);
 
             if result == macos_ffi::kCFRunLoopRunFinished {
@@ -614,9 +648,13 @@ struct EventTapContext {
     sender: Sender<HotkeyEvent>,
     ptt_hotkeys: Vec<HotkeyCombination>,
     toggle_hotkeys: Vec<HotkeyCombination>,
+    dictation_hotkeys: Vec<HotkeyCombination>,
+    spelling_hotkeys: Vec<HotkeyCombination>,
     pressed_keys: Mutex<HashSet<KeyCode>>,
     any_ptt_matched: AtomicBool,
     any_toggle_matched: AtomicBool,
+    any_dictation_matched: AtomicBool,
+    any_spelling_matched: AtomicBool,
     auto_mode_state: Arc<AutoModeState>,
 }
 
@@ -712,6 +750,32 @@ fn check_combinations(context: &EventTapContext) {
         context.any_toggle_matched.store(false, Ordering::SeqCst);
     }
 
+    let now_dictation_matched = context
+        .dictation_hotkeys
+        .iter()
+        .any(|combo| combo.is_subset_of(&pressed));
+
+    if now_dictation_matched && !context.any_dictation_matched.load(Ordering::SeqCst) {
+        context.any_dictation_matched.store(true, Ordering::SeqCst);
+        debug!("[Hotkey] Dictation hotkey pressed");
+        let _ = context.sender.send(HotkeyEvent::DictationPressed);
+    } else if !now_dictation_matched && context.any_dictation_matched.load(Ordering::SeqCst) {
+        context.any_dictation_matched.store(false, Ordering::SeqCst);
+    }
+
+    let now_spelling_matched = context
+        .spelling_hotkeys
+        .iter()
+        .any(|combo| combo.is_subset_of(&pressed));
+
+    if now_spelling_matched && !context.any_spelling_matched.load(Ordering::SeqCst) {
+        context.any_spelling_matched.store(true, Ordering::SeqCst);
+        debug!("[Hotkey] Spelling hotkey pressed");
+        let _ = context.sender.send(HotkeyEvent::SpellingPressed);
+    } else if !now_spelling_matched && context.any_spelling_matched.load(Ordering::SeqCst) {
+        context.any_spelling_matched.store(false, Ordering::SeqCst);
+    }
+
     let now_ptt_matched = context
         .ptt_hotkeys
         .iter()
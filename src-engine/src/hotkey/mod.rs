@@ -18,12 +18,17 @@ mod linux;
 
 pub use backend::{HotkeyBackend, HotkeyEvent};
 
+use crossbeam_channel::Receiver;
 use flowstt_common::HotkeyCombination;
 use std::sync::{Arc, Mutex, OnceLock};
 
 /// Global hotkey backend singleton.
 static HOTKEY_BACKEND: OnceLock<Arc<Mutex<Box<dyn HotkeyBackend>>>> = OnceLock::new();
 
+/// PTT and toggle hotkeys monitoring was last started with, for restarts
+static STARTED_HOTKEYS: Mutex<Option<(Vec<HotkeyCombination>, Vec<HotkeyCombination>)>> =
+    Mutex::new(None);
+
 /// Initialize the platform-specific hotkey backend.
 pub fn init_hotkey_backend() -> Result<(), String> {
     #[cfg(target_os = "macos")]
@@ -69,13 +74,34 @@ pub fn get_hotkey_backend() -> Option<Arc<Mutex<Box<dyn HotkeyBackend>>>> {
 }
 
 /// Start hotkey monitoring with the specified PTT combinations and toggle hotkeys.
+///
+/// The dictation session and spelling mode hotkeys are always monitored too;
+/// they are read from the config file.
 pub fn start_hotkey(
     ptt_hotkeys: Vec<HotkeyCombination>,
     toggle_hotkeys: Vec<HotkeyCombination>,
 ) -> Result<(), String> {
+    let config = crate::config::current_config();
     let backend = get_hotkey_backend().ok_or("Hotkey backend not available")?;
     let mut backend = backend.lock().map_err(|e| format!("Lock error: {}", e))?;
-    backend.start(ptt_hotkeys, toggle_hotkeys)
+    *STARTED_HOTKEYS.lock().unwrap() = Some((ptt_hotkeys.clone(), toggle_hotkeys.clone()));
+    backend.start(
+        ptt_hotkeys,
+        toggle_hotkeys,
+        config.dictation_hotkeys,
+        config.spelling_hotkeys,
+    )
+}
+
+/// Stop hotkey monitoring and start it again with the same hotkeys.
+pub fn restart_hotkey() -> Result<(), String> {
+    let (ptt_hotkeys, toggle_hotkeys) = STARTED_HOTKEYS
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Hotkey monitoring was never started")?;
+    stop_hotkey();
+    start_hotkey(ptt_hotkeys, toggle_hotkeys)
 }
 
 /// Stop hotkey monitoring.
@@ -87,11 +113,11 @@ pub fn stop_hotkey() {
     }
 }
 
-/// Try to receive a hotkey event (non-blocking).
-pub fn try_recv_hotkey() -> Option<HotkeyEvent> {
+/// Get the channel delivering hotkey events, if hotkey monitoring is running.
+pub fn hotkey_events() -> Option<Receiver<HotkeyEvent>> {
     let backend = get_hotkey_backend()?;
     let backend = backend.lock().ok()?;
-    backend.try_recv()
+    backend.events()
 }
 
 /// Check if hotkey capture is available on this platform.
@@ -126,6 +152,27 @@ pub fn check_accessibility_permission() -> bool {
     }
 }
 
+/// Identifier of the active keyboard layout as the platform reports it (an XKB
+/// layout, a Windows KLID or a macOS input source ID), if it can be read.
+pub fn keyboard_layout() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::keyboard_layout()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::keyboard_layout()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux::keyboard_layout()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
 /// Request macOS Accessibility permission for the service process.
 ///
 /// On macOS, calls `AXIsProcessTrustedWithOptions` with the prompt flag,
@@ -5,15 +5,18 @@
 //! message-only window to receive WM_INPUT messages. Supports tracking multiple
 //! key combinations simultaneously.
 
-use super::backend::{AutoModeState, HotkeyBackend, HotkeyEvent};
-use flowstt_common::{HotkeyCombination, KeyCode};
+use super::backend::{join_listener, AutoModeState, HotkeyBackend, HotkeyEvent};
+use crate::watchdog::Heartbeat;
+use crossbeam_channel::{Receiver, Sender};
+use flowstt_common::{EngineComponent, HotkeyCombination, KeyCode};
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use tracing::{debug, error, info};
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayoutNameW;
 use windows::Win32::UI::Input::{
     GetRawInputData, RegisterRawInputDevices, HRAWINPUT, RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER,
     RIDEV_INPUTSINK, RID_INPUT, RIM_TYPEKEYBOARD,
@@ -296,6 +299,14 @@ fn raw_input_to_keycode(vk_code: u16, is_e0: bool, make_code: u16) -> Option<Key
 }
 
 /// Windows hotkey backend using Raw Input API
+/// Identifier (KLID) of the active keyboard layout, such as `00000407`.
+pub fn keyboard_layout() -> Option<String> {
+    let mut name = [0u16; 9];
+    unsafe { GetKeyboardLayoutNameW(&mut name) }.ok()?;
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..len]))
+}
+
 pub struct WindowsHotkeyBackend {
     /// Whether the backend is currently running
     running: Arc<AtomicBool>,
@@ -329,20 +340,27 @@ impl HotkeyBackend for WindowsHotkeyBackend {
         &mut self,
         ptt_hotkeys: Vec<HotkeyCombination>,
         toggle_hotkeys: Vec<HotkeyCombination>,
+        dictation_hotkeys: Vec<HotkeyCombination>,
+        spelling_hotkeys: Vec<HotkeyCombination>,
     ) -> Result<(), String> {
         if self.running.load(Ordering::SeqCst) {
             return Err("Hotkey backend already running".to_string());
         }
 
-        if ptt_hotkeys.is_empty() && toggle_hotkeys.is_empty() {
+        if ptt_hotkeys.is_empty()
+            && toggle_hotkeys.is_empty()
+            && dictation_hotkeys.is_empty()
+            && spelling_hotkeys.is_empty()
+        {
             return Err("No hotkey combinations configured".to_string());
         }
 
-        let (sender, receiver) = mpsc::channel();
+        let (sender, receiver) = crossbeam_channel::unbounded();
         self.receiver = Some(receiver);
 
+        // A fresh flag, so a listener left behind by the watchdog can't stop this one
+        self.running = Arc::new(AtomicBool::new(true));
         let running = self.running.clone();
-        running.store(true, Ordering::SeqCst);
 
         let auto_mode_state = self.auto_mode_state.clone();
 
@@ -361,11 +379,15 @@ impl HotkeyBackend for WindowsHotkeyBackend {
                 toggle_hotkeys.len()
             );
 
+            let heartbeat = crate::watchdog::register(EngineComponent::Hotkeys);
             if let Err(e) = run_message_loop(
                 running.clone(),
+                &heartbeat,
                 sender,
                 ptt_hotkeys,
                 toggle_hotkeys,
+                dictation_hotkeys,
+                spelling_hotkeys,
                 auto_mode_state,
             ) {
                 error!("[Hotkey] Message loop error: {}", e);
@@ -408,14 +430,14 @@ impl HotkeyBackend for WindowsHotkeyBackend {
 
         // Wait for thread to exit
         if let Some(handle) = self.thread_handle.take() {
-            let _ = handle.join();
+            join_listener(handle);
         }
 
         self.receiver = None;
     }
 
-    fn try_recv(&self) -> Option<HotkeyEvent> {
-        self.receiver.as_ref()?.try_recv().ok()
+    fn events(&self) -> Option<Receiver<HotkeyEvent>> {
+        self.receiver.clone()
     }
 
     fn is_running(&self) -> bool {
@@ -455,12 +477,20 @@ struct HotkeyContext {
     ptt_hotkeys: Vec<HotkeyCombination>,
     /// Toggle hotkey combinations
     toggle_hotkeys: Vec<HotkeyCombination>,
+    /// Dictation session hotkey combinations
+    dictation_hotkeys: Vec<HotkeyCombination>,
+    /// Spelling mode hotkey combinations
+    spelling_hotkeys: Vec<HotkeyCombination>,
     /// Currently pressed keys
     pressed_keys: HashSet<KeyCode>,
     /// Whether any PTT combination is currently matched
     any_ptt_matched: bool,
     /// Whether any toggle combination is currently matched (to avoid repeat)
     any_toggle_matched: bool,
+    /// Whether any dictation combination is currently matched (to avoid repeat)
+    any_dictation_matched: bool,
+    /// Whether any spelling combination is currently matched (to avoid repeat)
+    any_spelling_matched: bool,
     /// Auto mode state for PTT suppression
     auto_mode_state: Arc<AutoModeState>,
 }
@@ -468,9 +498,12 @@ struct HotkeyContext {
 /// Run the Windows message loop on this thread
 fn run_message_loop(
     running: Arc<AtomicBool>,
+    heartbeat: &Heartbeat,
     sender: Sender<HotkeyEvent>,
     ptt_hotkeys: Vec<HotkeyCombination>,
     toggle_hotkeys: Vec<HotkeyCombination>,
+    dictation_hotkeys: Vec<HotkeyCombination>,
+    spelling_hotkeys: Vec<HotkeyCombination>,
     auto_mode_state: Arc<AutoModeState>,
 ) -> Result<(), String> {
     unsafe {
@@ -529,9 +562,13 @@ fn run_message_loop(
                 sender,
                 ptt_hotkeys,
                 toggle_hotkeys,
+                dictation_hotkeys,
+                spelling_hotkeys,
                 pressed_keys: HashSet::new(),
                 any_ptt_matched: false,
                 any_toggle_matched: false,
+                any_dictation_matched: false,
+                any_spelling_matched: false,
                 auto_mode_state,
             });
         });
@@ -539,7 +576,7 @@ fn run_message_loop(
         // Message loop using PeekMessageW for non-blocking operation
         let mut msg = MSG::default();
 
-        while running.load(Ordering::SeqCst) {
+        while running.load(Ordering::SeqCst) && heartbeat.beat() {
             // Non-blocking message check
             while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
                 if msg.message == WM_QUIT {
@@ -659,6 +696,34 @@ unsafe fn handle_raw_input(hrawinput: HRAWINPUT) {
                 context.any_toggle_matched = false;
             }
 
+            // Dictation hotkeys start or end a session, also on press only
+            let now_dictation_matched = context
+                .dictation_hotkeys
+                .iter()
+                .any(|combo| combo.is_subset_of(&context.pressed_keys));
+
+            if now_dictation_matched && !context.any_dictation_matched {
+                context.any_dictation_matched = true;
+                info!("[Hotkey] Dictation hotkey pressed");
+                let _ = context.sender.send(HotkeyEvent::DictationPressed);
+            } else if !now_dictation_matched && context.any_dictation_matched {
+                context.any_dictation_matched = false;
+            }
+
+            // Spelling hotkeys turn spelling mode on or off, also on press only
+            let now_spelling_matched = context
+                .spelling_hotkeys
+                .iter()
+                .any(|combo| combo.is_subset_of(&context.pressed_keys));
+
+            if now_spelling_matched && !context.any_spelling_matched {
+                context.any_spelling_matched = true;
+                info!("[Hotkey] Spelling hotkey pressed");
+                let _ = context.sender.send(HotkeyEvent::SpellingPressed);
+            } else if !now_spelling_matched && context.any_spelling_matched {
+                context.any_spelling_matched = false;
+            }
+
             // Check if any PTT combination is now matched
             let now_ptt_matched = context
                 .ptt_hotkeys
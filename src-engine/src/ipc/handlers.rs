@@ -1,16 +1,25 @@
 //! IPC request handlers.
 
+use flowstt_common::config::{NarrowbandInput, TranscriptionEngineKind};
 use flowstt_common::ipc::{EventType, Request, Response};
-use flowstt_common::{ConfigValues, CudaStatus, ModelStatus, PttStatus, TranscriptionMode};
+use flowstt_common::{
+    Capabilities, Capability, ConfigValues, CudaStatus, LetterLayout, ModeToggleSource,
+    ModelStatus, PttStatus, RecordingMode, TranscriptionMode, VersionInfo,
+};
 use std::sync::Arc;
 use tracing::{info, warn};
 
 use super::broadcast_event;
 use crate::hotkey;
 use crate::platform;
+use crate::platform::CaptureError;
 use crate::ptt_controller;
-use crate::state::get_service_state;
-use crate::transcription::{download_model, TranscribeState, Transcriber, TranscriptionQueue};
+use crate::resample::PROCESSING_SAMPLE_RATE;
+use crate::state::{get_service_state, ServiceState};
+use crate::transcription::queue::QueuedSegment;
+use crate::transcription::{
+    download_model, Retranscription, TranscribeState, Transcriber, TranscriptionQueue,
+};
 use crate::{
     is_audio_loop_active, start_audio_loop, stop_audio_loop, TranscriptionEventBroadcaster,
 };
@@ -38,6 +47,13 @@ pub fn get_transcribe_state() -> Arc<std::sync::Mutex<TranscribeState>> {
         .clone()
 }
 
+/// Status error while the capture device is not delivering audio
+const CAPTURE_STALLED_ERROR: &str = "No audio is being received from the capture device";
+
+/// Highest input sample rate treated as narrowband. Bluetooth hands-free
+/// profiles run at 8kHz or 16kHz.
+const NARROWBAND_MAX_RATE: u32 = 16000;
+
 /// Initialize the transcription system at startup.
 /// Called once when the service starts - sets up the transcription worker
 /// so it's ready when audio sources are configured.
@@ -49,16 +65,27 @@ pub fn init_transcription_system() {
     queue.set_callback(Arc::new(TranscriptionEventBroadcaster));
 
     // Start transcription worker
-    let transcriber = Transcriber::new();
-    let model_path = transcriber.get_model_path().clone();
-    queue.start_worker(model_path);
+    let config = crate::config::Config::load();
+    queue.start_worker(config.effective_transcription_engine());
 
     info!("Transcription system initialized");
 }
 
+/// A transcriber for the model the transcription worker uses.
+fn worker_transcriber() -> Transcriber {
+    match get_transcription_queue().model() {
+        Some(model) => Transcriber::with_model_path(model),
+        None => Transcriber::new(),
+    }
+}
+
 /// Start audio capture with current source configuration.
 /// Returns Ok if capture started, Err with message if it failed.
 pub async fn start_capture() -> Result<(), String> {
+    if crate::shutdown::is_shutting_down() {
+        return Err("Service is shutting down".to_string());
+    }
+
     let state_arc = get_service_state();
     let state = state_arc.lock().await;
 
@@ -118,8 +145,11 @@ pub async fn start_capture() -> Result<(), String> {
         // Also start hotkey backend for toggle hotkey support
 
         // Start hotkey backend (with toggle hotkeys, empty PTT hotkeys)
-        // Only start if toggle hotkeys are configured
-        if !auto_toggle_hotkeys.is_empty() {
+        // Only start if toggle, dictation or spelling hotkeys are configured
+        let config = crate::config::Config::load();
+        let has_mode_hotkeys =
+            !config.dictation_hotkeys.is_empty() || !config.spelling_hotkeys.is_empty();
+        if !auto_toggle_hotkeys.is_empty() || has_mode_hotkeys {
             if let Err(e) = hotkey::start_hotkey(vec![], auto_toggle_hotkeys.clone()) {
                 warn!("Failed to start toggle hotkey monitoring: {}", e);
             } else {
@@ -143,10 +173,8 @@ pub async fn start_capture() -> Result<(), String> {
             state.auto_mode_active = true;
         }
 
-        // Get sample rate from backend
-        let sample_rate = platform::get_backend()
-            .map(|b| b.sample_rate())
-            .unwrap_or(48000);
+        // Captured audio is normalized to the processing rate
+        let sample_rate = PROCESSING_SAMPLE_RATE;
 
         // Initialize transcribe state
         {
@@ -161,7 +189,14 @@ pub async fn start_capture() -> Result<(), String> {
             backend.set_aec_enabled(aec_enabled);
             backend.set_recording_mode(recording_mode);
 
-            backend.start_capture_sources(source1_id, source2_id)?;
+            match backend.start_capture_sources(source1_id, source2_id.clone()) {
+                Ok(()) => {}
+                Err(CaptureError::DeviceBusy {
+                    device_id,
+                    holder_hint,
+                }) => start_fallback_capture(backend, device_id, holder_hint, source2_id).await?,
+                Err(e) => return Err(e.to_string()),
+            }
         } else {
             return Err("Audio backend not available".to_string());
         }
@@ -193,8 +228,60 @@ pub async fn start_capture() -> Result<(), String> {
     }
 }
 
+/// Start capture on the first available fallback input after the primary
+/// source turned out to be held exclusively by another application.
+///
+/// Reports the busy device either way. Fallbacks come from the
+/// `fallback_source_ids` config list; the one used replaces the primary source
+/// until sources are next chosen, but the saved preference is kept.
+async fn start_fallback_capture(
+    backend: &dyn platform::AudioBackend,
+    busy_id: String,
+    holder_hint: Option<String>,
+    source2_id: Option<String>,
+) -> Result<(), String> {
+    let busy = CaptureError::DeviceBusy {
+        device_id: busy_id.clone(),
+        holder_hint: holder_hint.clone(),
+    };
+    warn!("{}", busy);
+
+    let available: Vec<String> = backend
+        .list_input_devices()
+        .into_iter()
+        .map(|d| d.id)
+        .collect();
+    let mut fallback_id = None;
+    for id in crate::config::Config::load().fallback_source_ids {
+        if id == busy_id || !available.contains(&id) {
+            continue;
+        }
+        match backend.start_capture_sources(Some(id.clone()), source2_id.clone()) {
+            Ok(()) => {
+                fallback_id = Some(id);
+                break;
+            }
+            Err(e) => warn!("Fallback device {} failed: {}", id, e),
+        }
+    }
+
+    broadcast_event(Response::Event {
+        event: EventType::DeviceBusy {
+            device_id: busy_id,
+            holder_hint,
+            fallback_id: fallback_id.clone(),
+        },
+    });
+
+    let fallback_id = fallback_id.ok_or_else(|| busy.to_string())?;
+    info!("Capturing from fallback device {}", fallback_id);
+    let state_arc = get_service_state();
+    state_arc.lock().await.source1_id = Some(fallback_id);
+    Ok(())
+}
+
 /// Stop audio capture.
-async fn stop_capture() {
+pub(crate) async fn stop_capture() {
     // Stop PTT controller if running
     ptt_controller::stop_ptt_controller();
 
@@ -226,6 +313,420 @@ async fn stop_capture() {
     info!("Audio capture stopped");
 }
 
+/// Whether capture, or push-to-talk waiting for its hotkey, is running.
+pub(crate) async fn is_capture_active() -> bool {
+    let state_arc = get_service_state();
+    let state = state_arc.lock().await;
+    state.transcribe_status.capturing
+        || (state.transcription_mode == TranscriptionMode::PushToTalk
+            && ptt_controller::is_ptt_controller_running())
+}
+
+/// Switch transcription mode, restarting capture if it is running, and
+/// broadcast the change.
+///
+/// With `persist` the mode is also saved to the config file. Temporary
+/// overrides such as process rules leave it unsaved, so the user's mode is
+/// what comes back after a restart.
+pub(crate) async fn set_transcription_mode(mode: TranscriptionMode, persist: bool) {
+    let state_arc = get_service_state();
+
+    let (old_mode, is_ready, ptt_hotkeys) = {
+        let mut state = state_arc.lock().await;
+        let old_mode = state.transcription_mode;
+        state.transcription_mode = mode;
+        if old_mode != mode {
+            state.transcribe_status.auto_suspended = false;
+        }
+        (old_mode, state.should_capture(), state.ptt_hotkeys.clone())
+    };
+
+    info!(
+        "Transcription mode change requested: {:?} -> {:?} (ready={})",
+        old_mode, mode, is_ready
+    );
+
+    // If mode changed and system is ready, restart capture with new mode
+    if old_mode != mode && is_ready {
+        // Stop current capture
+        stop_capture().await;
+
+        // Restart with new mode
+        if let Err(e) = start_capture().await {
+            warn!("Failed to restart capture after mode change: {}", e);
+        }
+    }
+
+    // Save configuration to disk
+    if persist {
+        if let Err(e) = crate::config::update_config(|config| {
+            config.transcription_mode = mode;
+            config.ptt_hotkeys = ptt_hotkeys;
+        }) {
+            warn!("Failed to save config: {}", e);
+        }
+    }
+
+    info!("Transcription mode set to {:?}", mode);
+
+    // Broadcast mode change event
+    broadcast_event(Response::Event {
+        event: EventType::TranscriptionModeChanged { mode },
+    });
+}
+
+/// Hold off or resume capture on behalf of a process rule.
+pub(crate) async fn set_paused_by_process(paused: bool) {
+    set_paused(paused, "process rule", |state| &mut state.paused_by_process).await;
+}
+
+/// Hold off or resume capture while the session is locked.
+pub(crate) async fn set_paused_by_lock(paused: bool) {
+    set_paused(paused, "session lock", |state| &mut state.paused_by_lock).await;
+}
+
+/// Set the pause flag picked by `flag` and stop or start capture to match.
+async fn set_paused(paused: bool, reason: &str, flag: impl FnOnce(&mut ServiceState) -> &mut bool) {
+    let was_active = is_capture_active().await;
+    let should_capture = {
+        let state_arc = get_service_state();
+        let mut state = state_arc.lock().await;
+        *flag(&mut state) = paused;
+        state.should_capture()
+    };
+
+    if paused && was_active {
+        stop_capture().await;
+        broadcast_event(Response::Event {
+            event: EventType::CaptureStateChanged {
+                capturing: false,
+                error: None,
+            },
+        });
+    } else if should_capture && !was_active {
+        if let Err(e) = start_capture().await {
+            warn!("Failed to resume capture after {}: {}", reason, e);
+        }
+    }
+}
+
+/// Start capture and hotkey monitoring again with fresh streams and
+/// listeners, after the devices or input behind them may have gone away
+/// (the system slept, or the session moved to another console).
+///
+/// `was_active` says whether capture ran before; while capture is suspended
+/// for inactivity only the hotkey listener is restarted.
+pub(crate) async fn restart_capture(was_active: bool) {
+    let (should_capture, auto_suspended) = {
+        let state_arc = get_service_state();
+        let state = state_arc.lock().await;
+        (
+            state.should_capture(),
+            state.transcribe_status.auto_suspended,
+        )
+    };
+
+    if was_active {
+        // Release whatever is left of the old streams
+        stop_capture().await;
+        if !should_capture {
+            return;
+        }
+        info!("Restarting capture");
+        if let Err(e) = start_capture().await {
+            warn!("Failed to restart capture: {}", e);
+            crate::capture_retry::schedule(e);
+        }
+    } else if auto_suspended {
+        // Only the hotkey listener runs while suspended, for the toggle
+        // hotkey that resumes capture
+        info!("Restarting hotkey monitoring");
+        let restarted = tokio::task::spawn_blocking(hotkey::restart_hotkey)
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+        if let Err(e) = restarted {
+            warn!("Failed to restart hotkey monitoring: {}", e);
+        }
+    }
+}
+
+/// Suspend Automatic mode capture after a period without speech.
+///
+/// Called from the audio loop thread, which exits on its own afterwards.
+/// The hotkey backend keeps running so the toggle hotkey can resume capture.
+pub(crate) fn suspend_for_idle() {
+    {
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        state.transcribe_status.auto_suspended = true;
+        state.transcribe_status.capturing = false;
+        state.transcribe_status.in_speech = false;
+    }
+
+    {
+        let transcribe_state = get_transcribe_state();
+        let mut transcribe = transcribe_state.lock().unwrap();
+        transcribe.finalize();
+        transcribe.deactivate();
+    }
+
+    if let Some(backend) = platform::get_backend() {
+        let _ = backend.stop_capture();
+    }
+
+    info!("Automatic mode capture suspended after inactivity");
+
+    broadcast_event(Response::Event {
+        event: EventType::CaptureStateChanged {
+            capturing: false,
+            error: None,
+        },
+    });
+    broadcast_event(Response::Event {
+        event: EventType::AutoSuspended { suspended: true },
+    });
+}
+
+/// Stop and start the backend streams after the audio loop stopped receiving
+/// audio from them.
+///
+/// Called from the audio loop thread.
+pub(crate) fn restart_stalled_capture() -> Result<(), String> {
+    let (source1_id, source2_id, aec_enabled, recording_mode) = {
+        let state_arc = get_service_state();
+        let state = futures::executor::block_on(state_arc.lock());
+        (
+            state.source1_id.clone(),
+            state.source2_id.clone(),
+            state.aec_enabled,
+            state.recording_mode,
+        )
+    };
+
+    let backend = platform::get_backend().ok_or("Audio backend not available")?;
+    let _ = backend.stop_capture();
+    backend.set_aec_enabled(aec_enabled);
+    backend.set_recording_mode(recording_mode);
+    backend
+        .start_capture_sources(source1_id, source2_id)
+        .map_err(String::from)
+}
+
+/// Report a stalled capture stream in the status (`Some` seconds since audio
+/// was last received), or clear the report once audio flows again.
+///
+/// Called from the audio loop thread.
+pub(crate) fn set_capture_stalled(stalled_secs: Option<u64>) {
+    {
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        let status = &mut state.transcribe_status;
+        match stalled_secs {
+            Some(_) => status.error = Some(CAPTURE_STALLED_ERROR.to_string()),
+            None if status.error.as_deref() == Some(CAPTURE_STALLED_ERROR) => status.error = None,
+            None => {}
+        }
+    }
+
+    if let Some(stalled_secs) = stalled_secs {
+        broadcast_event(Response::Event {
+            event: EventType::CaptureStalled { stalled_secs },
+        });
+    }
+}
+
+/// Report a change in the primary input's native sample rate and, with
+/// `narrowband_input` set to `prefer_high_quality`, move capture off an input
+/// that dropped to a narrowband rate.
+///
+/// `previous_rate` is `None` for the first rate seen after capture started;
+/// that is only reported when it is narrowband. Called from the audio loop
+/// thread.
+pub(crate) fn report_input_rate(previous_rate: Option<u32>, sample_rate: u32) {
+    let narrowband = sample_rate <= NARROWBAND_MAX_RATE;
+    if previous_rate.is_none() && !narrowband {
+        return;
+    }
+
+    let (source1_id, source2_id) = {
+        let state_arc = get_service_state();
+        let state = futures::executor::block_on(state_arc.lock());
+        (state.source1_id.clone(), state.source2_id.clone())
+    };
+    if narrowband {
+        warn!(
+            "Input {:?} is running at a narrowband {} Hz; transcription quality will suffer",
+            source1_id, sample_rate
+        );
+    } else {
+        info!(
+            "Input {:?} changed rate from {:?} to {} Hz",
+            source1_id, previous_rate, sample_rate
+        );
+    }
+
+    let config = crate::config::Config::load();
+    let switched_to = if narrowband && config.narrowband_input == NarrowbandInput::PreferHighQuality
+    {
+        switch_from_narrowband_input(
+            source1_id.as_deref(),
+            source2_id,
+            &config.fallback_source_ids,
+        )
+    } else {
+        None
+    };
+
+    broadcast_event(Response::Event {
+        event: EventType::DeviceProfileChanged {
+            device_id: source1_id,
+            previous_sample_rate: previous_rate,
+            sample_rate,
+            narrowband,
+            switched_to,
+        },
+    });
+}
+
+/// Restart capture on the first fallback input not known to be narrowband,
+/// returning its ID.
+///
+/// Capture is never moved off a fallback input, so two narrowband fallbacks
+/// can't take turns. The one used replaces the primary source until sources
+/// are next chosen, like a fallback for a busy device.
+fn switch_from_narrowband_input(
+    current_id: Option<&str>,
+    source2_id: Option<String>,
+    fallback_ids: &[String],
+) -> Option<String> {
+    if current_id.is_some_and(|id| fallback_ids.iter().any(|f| f == id)) {
+        return None;
+    }
+    let backend = platform::get_backend()?;
+    let devices = backend.list_input_devices();
+    let usable = |id: &str| {
+        devices.iter().any(|d| {
+            d.id == id && !matches!(d.sample_rate, Some(rate) if rate <= NARROWBAND_MAX_RATE)
+        })
+    };
+    let candidates: Vec<&String> = fallback_ids
+        .iter()
+        .filter(|id| Some(id.as_str()) != current_id && usable(id))
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let _ = backend.stop_capture();
+    for id in candidates {
+        match backend.start_capture_sources(Some(id.clone()), source2_id.clone()) {
+            Ok(()) => {
+                info!("Moved capture off the narrowband input to {}", id);
+                let state_arc = get_service_state();
+                futures::executor::block_on(state_arc.lock()).source1_id = Some(id.clone());
+                return Some(id.clone());
+            }
+            Err(e) => warn!("Fallback device {} failed: {}", id, e),
+        }
+    }
+
+    // None of them started; stay on the narrowband input
+    if let Err(e) = backend.start_capture_sources(current_id.map(str::to_string), source2_id) {
+        warn!("Failed to restart capture on the narrowband input: {}", e);
+    }
+    None
+}
+
+/// Resume capture after an idle auto-suspend. No-op if not suspended.
+pub(crate) async fn resume_from_suspend() -> Result<(), String> {
+    let should_capture = {
+        let state_arc = get_service_state();
+        let mut state = state_arc.lock().await;
+        if !state.transcribe_status.auto_suspended {
+            return Ok(());
+        }
+        state.transcribe_status.auto_suspended = false;
+        state.should_capture()
+    };
+
+    info!("Resuming capture after auto-suspend");
+    broadcast_event(Response::Event {
+        event: EventType::AutoSuspended { suspended: false },
+    });
+
+    if should_capture {
+        start_capture().await?;
+    }
+    Ok(())
+}
+
+/// Version and build details of the running service.
+fn version_info() -> VersionInfo {
+    let mut features = Vec::new();
+    // Windows always uses CUDA binaries; Linux only with the cuda feature
+    if cfg!(any(
+        target_os = "windows",
+        all(target_os = "linux", feature = "cuda")
+    )) {
+        features.push("cuda".to_string());
+    }
+    if cfg!(target_os = "macos") {
+        features.push("metal".to_string());
+    }
+
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("FLOWSTT_GIT_HASH").map(str::to_string),
+        build_date: option_env!("FLOWSTT_BUILD_TIME")
+            .and_then(|secs| secs.parse().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|time| time.format("%Y-%m-%d").to_string()),
+        whisper_version: env!("FLOWSTT_WHISPER_VERSION").to_string(),
+        features,
+        protocol_version: flowstt_common::ipc::PROTOCOL_VERSION,
+    }
+}
+
+/// Check which platform features work in the current session.
+fn capabilities() -> Capabilities {
+    let system_audio = match platform::get_backend() {
+        None => Capability::unsupported("Audio backend is not initialized"),
+        Some(backend) if backend.list_system_devices().is_empty() => {
+            Capability::unsupported("No system audio sources were found")
+        }
+        Some(_) => Capability::supported(),
+    };
+    let aec = if system_audio.available {
+        Capability::supported()
+    } else {
+        Capability::unsupported("Echo cancellation needs a system audio source")
+    };
+    let hotkeys = if hotkey::is_hotkey_available() {
+        Capability::supported()
+    } else {
+        Capability::unsupported(
+            hotkey::hotkey_unavailable_reason()
+                .unwrap_or_else(|| "Hotkey capture is not available".to_string()),
+        )
+    };
+    let clipboard = match crate::clipboard::clipboard_unavailable_reason() {
+        Some(reason) => Capability::unsupported(reason),
+        None => Capability::supported(),
+    };
+    let paste_simulation = match crate::clipboard::paste_unavailable_reason() {
+        Some(reason) => Capability::unsupported(reason),
+        None => Capability::supported(),
+    };
+
+    Capabilities {
+        system_audio,
+        aec,
+        hotkeys,
+        clipboard,
+        paste_simulation,
+    }
+}
+
 /// Handle an IPC request and return a response.
 pub async fn handle_request(request: Request) -> Response {
     // Validate request
@@ -291,6 +792,8 @@ pub async fn handle_request(request: Request) -> Response {
                         && ptt_controller::is_ptt_controller_running());
                 state.source1_id = source1_id.clone();
                 state.source2_id = source2_id.clone();
+                // Choosing a source is explicit user activity
+                state.transcribe_status.auto_suspended = false;
                 (was, state.should_capture())
             };
 
@@ -300,15 +803,16 @@ pub async fn handle_request(request: Request) -> Response {
             );
 
             // Persist device selection to config so it is restored on next startup
-            {
-                let mut config = crate::config::Config::load();
+            if let Err(e) = crate::config::update_config(|config| {
                 config.preferred_source1_id = source1_id.clone();
                 config.preferred_source2_id = source2_id.clone();
-                if let Err(e) = crate::config::save_config(&config) {
-                    warn!("Failed to save device selection to config: {}", e);
-                }
+            }) {
+                warn!("Failed to save device selection to config: {}", e);
             }
 
+            // New sources replace any retry of the old ones
+            crate::capture_retry::cancel().await;
+
             // Stop current capture / PTT monitoring if running
             if was_active {
                 stop_capture().await;
@@ -331,6 +835,9 @@ pub async fn handle_request(request: Request) -> Response {
                             },
                         });
 
+                        // The device may only be busy for a moment
+                        crate::capture_retry::schedule(e.clone());
+
                         Response::error(e)
                     }
                 }
@@ -395,6 +902,13 @@ pub async fn handle_request(request: Request) -> Response {
             status.source1_id = state.source1_id.clone();
             status.source2_id = state.source2_id.clone();
             status.transcription_mode = state.transcription_mode;
+            status.privacy_mode = crate::privacy::is_privacy_mode();
+            status.output_paused = crate::pause_words::is_paused();
+            status.dropped_samples = crate::platform::realtime::dropped_samples();
+            status.worker_restarts = crate::transcription::queue::worker_restarts();
+            if let Some(backend) = platform::get_backend() {
+                status.capture_glitches = backend.capture_glitches();
+            }
 
             Response::Status(status)
         }
@@ -410,24 +924,29 @@ pub async fn handle_request(request: Request) -> Response {
                 auto_toggle_hotkeys: state.auto_toggle_hotkeys.clone(),
                 auto_paste_enabled: config.auto_paste_enabled,
                 auto_paste_delay_ms: config.auto_paste_delay_ms,
+                autostart_enabled: Some(crate::autostart::is_enabled()),
+                language: config.language,
             })
         }
 
-        Request::SubscribeEvents => {
+        Request::SubscribeEvents { .. } => {
             // Actual subscription is handled in the server
             Response::Subscribed
         }
 
         Request::GetModelStatus => {
-            let transcriber = Transcriber::new();
+            let transcriber = worker_transcriber();
+            // The mock engine needs no model, so setup never asks for a download
+            let mock = crate::config::Config::load().effective_transcription_engine()
+                == TranscriptionEngineKind::Mock;
             Response::ModelStatus(ModelStatus {
-                available: transcriber.is_model_available(),
+                available: mock || transcriber.is_model_available(),
                 path: transcriber.get_model_path().to_string_lossy().to_string(),
             })
         }
 
         Request::DownloadModel => {
-            let transcriber = Transcriber::new();
+            let transcriber = worker_transcriber();
             let model_path = transcriber.get_model_path().clone();
 
             if model_path.exists() {
@@ -463,56 +982,13 @@ pub async fn handle_request(request: Request) -> Response {
         }
 
         Request::SetTranscriptionMode { mode } => {
-            let state_arc = get_service_state();
-
-            let (old_mode, is_ready, ptt_hotkeys) = {
-                let mut state = state_arc.lock().await;
-                let old_mode = state.transcription_mode;
-                state.transcription_mode = mode;
-                (
-                    old_mode,
-                    state.has_primary_source(),
-                    state.ptt_hotkeys.clone(),
-                )
-            };
-
-            info!(
-                "Transcription mode change requested: {:?} -> {:?} (ready={})",
-                old_mode, mode, is_ready
-            );
-
-            // If mode changed and system is ready, restart capture with new mode
-            if old_mode != mode && is_ready {
-                // Stop current capture
-                stop_capture().await;
-
-                // Restart with new mode
-                if let Err(e) = start_capture().await {
-                    warn!("Failed to restart capture after mode change: {}", e);
-                }
-            }
-
-            // Save configuration to disk (load first to preserve other fields)
-            let mut config = crate::config::Config::load();
-            config.transcription_mode = mode;
-            config.ptt_hotkeys = ptt_hotkeys;
-            if let Err(e) = crate::config::save_config(&config) {
-                warn!("Failed to save config: {}", e);
-            }
-
-            info!("Transcription mode set to {:?}", mode);
-
-            // Broadcast mode change event
-            broadcast_event(Response::Event {
-                event: EventType::TranscriptionModeChanged { mode },
-            });
-
+            set_transcription_mode(mode, true).await;
             Response::Ok
         }
 
         Request::SetPushToTalkHotkeys { hotkeys } => {
             let state_arc = get_service_state();
-            let (old_hotkeys, old_toggle, transcription_mode, is_ptt_monitoring) = {
+            let (old_hotkeys, old_toggle, is_ptt_monitoring) = {
                 let mut state = state_arc.lock().await;
                 let old_hotkeys = state.ptt_hotkeys.clone();
                 let old_toggle = state.auto_toggle_hotkeys.clone();
@@ -520,10 +996,9 @@ pub async fn handle_request(request: Request) -> Response {
                 // The hotkey backend runs whenever the PTT controller is
                 // active, regardless of whether audio is currently capturing
                 // (audio only flows while the key is held).
-                let is_ptt_monitoring =
-                    state.transcription_mode == TranscriptionMode::PushToTalk
-                        && ptt_controller::is_ptt_controller_running();
-                (old_hotkeys, old_toggle, state.transcription_mode, is_ptt_monitoring)
+                let is_ptt_monitoring = state.transcription_mode == TranscriptionMode::PushToTalk
+                    && ptt_controller::is_ptt_controller_running();
+                (old_hotkeys, old_toggle, is_ptt_monitoring)
             };
 
             info!(
@@ -546,11 +1021,8 @@ pub async fn handle_request(request: Request) -> Response {
                 }
             }
 
-            // Save configuration to disk (load first to preserve other fields)
-            let mut config = crate::config::Config::load();
-            config.transcription_mode = transcription_mode;
-            config.ptt_hotkeys = hotkeys;
-            if let Err(e) = crate::config::save_config(&config) {
+            // Save configuration to disk
+            if let Err(e) = crate::config::update_config(|config| config.ptt_hotkeys = hotkeys) {
                 warn!("Failed to save config: {}", e);
             }
 
@@ -569,6 +1041,12 @@ pub async fn handle_request(request: Request) -> Response {
                 None
             };
 
+            let keyboard_layout = hotkey::keyboard_layout();
+            let letter_layout = keyboard_layout
+                .as_deref()
+                .map(LetterLayout::from_layout_id)
+                .unwrap_or_default();
+
             Response::PttStatus(PttStatus {
                 mode: state.transcription_mode,
                 hotkeys: state.ptt_hotkeys.clone(),
@@ -578,9 +1056,48 @@ pub async fn handle_request(request: Request) -> Response {
                 available,
                 error,
                 accessibility_permission_granted: hotkey::check_accessibility_permission(),
+                keyboard_layout,
+                letter_layout,
             })
         }
 
+        Request::TestPttHotkey {
+            hotkeys,
+            timeout_ms,
+        } => {
+            // The PTT controller would consume the key events and start
+            // recording, so capture is paused while listening
+            let resume = ptt_controller::is_ptt_controller_running() || is_audio_loop_active();
+            if resume {
+                stop_capture().await;
+            }
+
+            // Auto mode suppresses PTT events in the hotkey backend
+            hotkey::set_auto_mode_active(false);
+            let result = match hotkey::start_hotkey(hotkeys, vec![]) {
+                Ok(()) => {
+                    let timeout = std::time::Duration::from_millis(timeout_ms);
+                    let detected = tokio::task::spawn_blocking(move || {
+                        ptt_controller::wait_for_ptt_key(timeout)
+                    })
+                    .await
+                    .unwrap_or(false);
+                    hotkey::stop_hotkey();
+                    info!("[Hotkey] PTT hotkey test: detected={}", detected);
+                    Response::PttHotkeyTest { detected }
+                }
+                Err(e) => Response::error(format!("Failed to start hotkey monitoring: {}", e)),
+            };
+
+            if resume {
+                if let Err(e) = start_capture().await {
+                    warn!("Failed to resume capture after PTT hotkey test: {}", e);
+                }
+            }
+
+            result
+        }
+
         Request::SetAutoToggleHotkeys { hotkeys } => {
             let state_arc = get_service_state();
             let (ptt_hotkeys, _transcription_mode, is_ptt_monitoring) = {
@@ -604,9 +1121,9 @@ pub async fn handle_request(request: Request) -> Response {
             }
 
             // Save config
-            let mut config = crate::config::Config::load();
-            config.auto_toggle_hotkeys = hotkeys;
-            if let Err(e) = crate::config::save_config(&config) {
+            if let Err(e) =
+                crate::config::update_config(|config| config.auto_toggle_hotkeys = hotkeys)
+            {
                 warn!("Failed to save config: {}", e);
             }
 
@@ -622,6 +1139,7 @@ pub async fn handle_request(request: Request) -> Response {
                 auto_toggle_hotkeys: state.auto_toggle_hotkeys.clone(),
                 auto_paste_enabled: true,
                 auto_paste_delay_ms: 50,
+                ..Default::default()
             })
         }
 
@@ -661,9 +1179,9 @@ pub async fn handle_request(request: Request) -> Response {
             }
 
             // Save config
-            let mut config = crate::config::Config::load();
-            config.transcription_mode = new_mode;
-            if let Err(e) = crate::config::save_config(&config) {
+            if let Err(e) =
+                crate::config::update_config(|config| config.transcription_mode = new_mode)
+            {
                 warn!("Failed to save config: {}", e);
             }
 
@@ -671,10 +1189,21 @@ pub async fn handle_request(request: Request) -> Response {
             broadcast_event(Response::Event {
                 event: EventType::AutoModeToggled { mode: new_mode },
             });
+            broadcast_event(Response::Event {
+                event: EventType::AutoModeToggledBy {
+                    mode: new_mode,
+                    source: ModeToggleSource::Client,
+                },
+            });
 
             Response::Ok
         }
 
+        Request::ResumeCapture => match resume_from_suspend().await {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::error(e),
+        },
+
         Request::GetCudaStatus => {
             // Check build-time GPU support
             // Windows always uses CUDA binaries (auto CPU fallback when no GPU)
@@ -708,11 +1237,15 @@ pub async fn handle_request(request: Request) -> Response {
             })
         }
 
+        Request::GetCapabilities => Response::Capabilities(capabilities()),
+
+        Request::GetVersionInfo => Response::VersionInfo(version_info()),
+
         Request::SetAutoPaste { enabled } => {
             // Load current config, update the auto-paste setting, and save
-            let mut config = crate::config::Config::load();
-            config.auto_paste_enabled = enabled;
-            if let Err(e) = crate::config::save_config(&config) {
+            if let Err(e) =
+                crate::config::update_config(|config| config.auto_paste_enabled = enabled)
+            {
                 warn!("Failed to save config: {}", e);
             }
 
@@ -720,22 +1253,146 @@ pub async fn handle_request(request: Request) -> Response {
             Response::Ok
         }
 
+        Request::GetTheme => Response::Theme(crate::config::Config::load().theme()),
+
+        Request::SetTheme { theme } => {
+            if let Err(e) = crate::config::update_config(|config| config.set_theme(theme.clone())) {
+                return Response::error(format!("Failed to save config: {}", e));
+            }
+            crate::processor::settings_changed();
+            broadcast_event(Response::Event {
+                event: EventType::ThemeChanged { theme },
+            });
+            Response::Ok
+        }
+
+        Request::GetVisualizationConfig => {
+            Response::VisualizationConfig(crate::config::Config::load().visualization)
+        }
+
+        Request::SetVisualizationConfig { settings } => {
+            if let Err(e) = crate::config::update_config(|config| config.visualization = settings)
+            {
+                return Response::error(format!("Failed to save config: {}", e));
+            }
+            crate::processor::settings_changed();
+            Response::Ok
+        }
+
+        Request::CheckForUpdate => match crate::updates::check().await {
+            Ok(info) => Response::UpdateInfo(info),
+            Err(e) => Response::error(e),
+        },
+
+        Request::SetAutostart { enabled } => {
+            match tokio::task::spawn_blocking(move || crate::autostart::set_enabled(enabled)).await
+            {
+                Ok(Ok(())) => {
+                    broadcast_event(Response::Event {
+                        event: EventType::AutostartChanged { enabled },
+                    });
+                    Response::Ok
+                }
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Autostart task failed: {}", e)),
+            }
+        }
+
+        Request::SetAutoPasteDelay { delay_ms } => {
+            if let Err(e) =
+                crate::config::update_config(|config| config.auto_paste_delay_ms = delay_ms)
+            {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Auto-paste delay set to {} ms", delay_ms);
+            Response::Ok
+        }
+
+        Request::SetPrivacyMode { enabled } => {
+            crate::privacy::set_privacy_mode(enabled);
+            Response::Ok
+        }
+
+        Request::SetDictation { active } => {
+            crate::dictation::set_active(active);
+            Response::Ok
+        }
+
+        Request::StartMeeting { title } => {
+            let state_arc = get_service_state();
+            let mut state = state_arc.lock().await;
+            if state.source1_id.is_none() || state.source2_id.is_none() {
+                return Response::error(
+                    "Meeting mode needs a microphone and a system audio source",
+                );
+            }
+
+            let meeting = match crate::meeting::start(title, state.recording_mode) {
+                Ok(meeting) => meeting,
+                Err(e) => return Response::error(e),
+            };
+
+            // Keep the sources on separate channels for the rest of the meeting
+            state.recording_mode = RecordingMode::Split;
+            if state.transcribe_status.capturing {
+                if let Some(backend) = platform::get_backend() {
+                    backend.set_recording_mode(RecordingMode::Split);
+                }
+            }
+
+            broadcast_event(Response::Event {
+                event: EventType::MeetingStarted {
+                    meeting: meeting.clone(),
+                },
+            });
+            Response::Meeting(meeting)
+        }
+
+        Request::StopMeeting => {
+            // Queue the speech in progress while it still belongs to the meeting
+            get_transcribe_state().lock().unwrap().finalize();
+
+            let (meeting, previous_mode) = match crate::meeting::stop() {
+                Some(stopped) => stopped,
+                None => return Response::error("No meeting is in progress"),
+            };
+
+            let state_arc = get_service_state();
+            let mut state = state_arc.lock().await;
+            state.recording_mode = previous_mode;
+            if state.transcribe_status.capturing {
+                if let Some(backend) = platform::get_backend() {
+                    backend.set_recording_mode(previous_mode);
+                }
+            }
+
+            broadcast_event(Response::Event {
+                event: EventType::MeetingEnded {
+                    meeting: meeting.clone(),
+                },
+            });
+            Response::Meeting(meeting)
+        }
+
+        Request::GetMeetings => Response::Meetings {
+            meetings: crate::meeting::list(),
+        },
+
         Request::GetHistory => {
             let history = crate::history::get_history();
             let h = history.lock().unwrap();
-            let entries: Vec<flowstt_common::HistoryEntry> = h
-                .get_entries()
-                .iter()
-                .map(|e| flowstt_common::HistoryEntry {
-                    id: e.id.clone(),
-                    text: e.text.clone(),
-                    timestamp: e.timestamp.clone(),
-                    wav_path: e.wav_path.clone(),
-                })
-                .collect();
+            let entries: Vec<flowstt_common::HistoryEntry> =
+                h.get_entries().iter().map(|e| e.to_common()).collect();
             Response::History { entries }
         }
 
+        Request::GetHistorySummary { group_by } => {
+            let history = crate::history::get_history();
+            let summary = history.lock().unwrap().summary(group_by);
+            Response::HistorySummary(summary)
+        }
+
         Request::DeleteHistoryEntry { id } => {
             let history = crate::history::get_history();
             let deleted = {
@@ -754,6 +1411,124 @@ pub async fn handle_request(request: Request) -> Response {
             }
         }
 
+        Request::EditHistoryEntry { id, text } => {
+            let history = crate::history::get_history();
+            let updated = {
+                let mut h = history.lock().unwrap();
+                h.edit_text(&id, text)
+            };
+            match updated {
+                Some(entry) => {
+                    info!("Edited history entry: {}", id);
+                    let entries = history.lock().unwrap().get_entries().to_vec();
+                    crate::corrections::learn(&entries);
+                    broadcast_event(Response::Event {
+                        event: EventType::HistoryEntryUpdated {
+                            entry: entry.to_common(),
+                        },
+                    });
+                    Response::Ok
+                }
+                None => Response::error(format!("History entry not found: {}", id)),
+            }
+        }
+
+        Request::RetranscribeEntry { id, model } => {
+            if crate::shutdown::is_shutting_down() {
+                return Response::error("Service is shutting down");
+            }
+
+            let model_path = match model {
+                Some(name) => {
+                    let path = crate::transcription::model_path_for(&name);
+                    if !path.exists() {
+                        return Response::error(format!("Model not found: {}", path.display()));
+                    }
+                    Some(path)
+                }
+                None => None,
+            };
+
+            let wav_path = {
+                let history = crate::history::get_history();
+                let h = history.lock().unwrap();
+                match h.get_entries().iter().find(|e| e.id == id) {
+                    Some(entry) => entry.wav_path.clone(),
+                    None => return Response::error(format!("History entry not found: {}", id)),
+                }
+            };
+            let Some(wav_path) = wav_path else {
+                return Response::error(format!("No recording saved for history entry: {}", id));
+            };
+
+            let audio = match crate::history::TranscriptionHistory::read_recording(
+                std::path::Path::new(&wav_path),
+            )
+            .and_then(|data| crate::audio::decode_wav(&data))
+            {
+                Ok(audio) => audio,
+                Err(e) => return Response::error(e),
+            };
+
+            let queued = QueuedSegment {
+                samples: audio.samples,
+                sample_rate: audio.sample_rate,
+                channels: audio.channels,
+                wav_path: Some(wav_path.into()),
+                ephemeral: false,
+                metadata: None,
+                retranscription: Some(Retranscription {
+                    entry_id: id.clone(),
+                    model_path,
+                }),
+                meeting: None,
+                stream: None,
+                interim: None,
+            };
+            if !get_transcription_queue().enqueue(queued) {
+                return Response::error("Transcription queue is full, try again shortly");
+            }
+            info!("Queued history entry {} for re-transcription", id);
+            Response::Ok
+        }
+
+        Request::PlayHistoryAudio { id } => match crate::history_playback::play(&id) {
+            Ok(duration) => Response::HistoryAudioPlaying {
+                id,
+                duration_ms: duration.map(|d| d.as_millis() as u64),
+            },
+            Err(e) => Response::error(e),
+        },
+
+        Request::StopHistoryAudio => {
+            crate::history_playback::stop();
+            Response::Ok
+        }
+
+        Request::SeekHistoryAudio { position_ms } => {
+            match crate::history_playback::seek(std::time::Duration::from_millis(position_ms)) {
+                Ok(()) => Response::Ok,
+                Err(e) => Response::error(e),
+            }
+        }
+
+        Request::ExportRules => Response::Rules {
+            rules: crate::config::Config::load().user_rules(),
+        },
+
+        Request::ImportRules { rules, replace } => {
+            let mut counts = (0, 0);
+            if let Err(e) = crate::config::update_config(|config| {
+                counts = config.import_rules(rules, replace);
+            }) {
+                return Response::error(format!("Failed to save config: {}", e));
+            }
+            let (added, updated) = counts;
+            info!("Imported rules: {} added, {} updated", added, updated);
+            crate::sync::rules_changed();
+            Response::RulesImported { added, updated }
+        }
+
         Request::TestAudioDevice { device_id } => {
             // Stop any existing test capture (handles device switching)
             crate::test_capture::stop_test_capture();
@@ -779,6 +1554,153 @@ pub async fn handle_request(request: Request) -> Response {
             Response::Ok
         }
 
+        Request::RunAudioSelfTest { device_id } => {
+            // Same single-consumer constraint as TestAudioDevice
+            let was_active = is_audio_loop_active();
+            if was_active {
+                stop_audio_loop();
+                if let Some(backend) = platform::get_backend() {
+                    let _ = backend.stop_capture();
+                }
+            }
+
+            // Recording blocks for ~2 seconds; keep it off the async runtime
+            let result = tokio::task::spawn_blocking(move || {
+                crate::test_capture::run_self_test(&device_id)
+            })
+            .await;
+
+            // Pick transcription back up where the test interrupted it
+            if was_active {
+                restart_capture(true).await;
+            }
+
+            match result {
+                Ok(Ok(report)) => Response::AudioSelfTest(report),
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Self-test task failed: {}", e)),
+            }
+        }
+
+        Request::EnrollSpeaker { device_id } => {
+            // Same single-consumer constraint as RunAudioSelfTest
+            let was_active = is_audio_loop_active();
+            if was_active {
+                stop_audio_loop();
+                if let Some(backend) = platform::get_backend() {
+                    let _ = backend.stop_capture();
+                }
+            }
+
+            // Recording blocks for ~8 seconds; keep it off the async runtime
+            let result =
+                tokio::task::spawn_blocking(move || crate::speaker_filter::enroll(&device_id))
+                    .await;
+            if was_active {
+                restart_capture(true).await;
+            }
+
+            match result {
+                Ok(Ok(duration_ms)) => Response::SpeakerEnrolled { duration_ms },
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Enrollment task failed: {}", e)),
+            }
+        }
+
+        Request::ClearSpeakerEnrollment => match crate::speaker_filter::clear_enrollment() {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::error(e),
+        },
+
+        Request::GetRecentAudioWindow { seconds } => {
+            // Writing up to a minute of audio to disk; keep it off the async runtime
+            match tokio::task::spawn_blocking(move || crate::recent_audio::window(seconds)).await {
+                Ok(Ok(window)) => Response::RecentAudioWindow(window),
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Recent audio task failed: {}", e)),
+            }
+        }
+
+        Request::CreateBackup {
+            path,
+            include_recordings,
+        } => {
+            let path = std::path::PathBuf::from(path);
+            let task = move || crate::backup::create(&path, include_recordings);
+            match tokio::task::spawn_blocking(task).await {
+                Ok(Ok(contents)) => Response::BackupCreated {
+                    history_entries: contents.history_entries,
+                    recordings: contents.recordings,
+                },
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Backup task failed: {}", e)),
+            }
+        }
+
+        Request::RestoreBackup { path } => {
+            let path = std::path::PathBuf::from(path);
+            let task = move || crate::backup::restore(&path);
+            match tokio::task::spawn_blocking(task).await {
+                Ok(Ok(contents)) => Response::BackupRestored {
+                    created_at: contents.created_at,
+                    history_entries: contents.history_entries,
+                    recordings: contents.recordings,
+                },
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Restore task failed: {}", e)),
+            }
+        }
+
+        Request::GetActivitySummary { period } => {
+            match tokio::task::spawn_blocking(move || crate::activity_log::summary(period)).await {
+                Ok(Ok(summary)) => Response::ActivitySummary(summary),
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Activity summary task failed: {}", e)),
+            }
+        }
+
+        Request::StartAudioStream {
+            sample_rate,
+            channels,
+        } => Response::AudioStreamStarted {
+            stream_id: crate::audio_stream::start(sample_rate, channels),
+        },
+
+        Request::StreamAudio { stream_id, samples } => {
+            // Waits while the transcription queue is full
+            match tokio::task::spawn_blocking(move || {
+                crate::audio_stream::push(&stream_id, &samples)
+            })
+            .await
+            {
+                Ok(Ok(())) => Response::Ok,
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Audio stream task failed: {}", e)),
+            }
+        }
+
+        Request::EndAudioStream { stream_id } => {
+            match tokio::task::spawn_blocking(move || crate::audio_stream::end(&stream_id)).await {
+                Ok(Ok(segments)) => Response::AudioStreamEnded { segments },
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Audio stream task failed: {}", e)),
+            }
+        }
+
+        Request::RunTestMode { dir, manifest } => {
+            match crate::test_mode::start_test_run(dir.into(), manifest.map(Into::into)) {
+                Ok(()) => Response::Ok,
+                Err(e) => Response::error(e),
+            }
+        }
+
+        Request::RunLatencyBenchmark { file, runs } => {
+            match crate::latency_bench::run(file.map(Into::into), runs).await {
+                Ok(report) => Response::LatencyBenchmark(report),
+                Err(e) => Response::error(e),
+            }
+        }
+
         Request::CheckAccessibilityPermission => {
             let granted = hotkey::check_accessibility_permission();
             info!("[Hotkey] Accessibility permission check: granted={}", granted);
@@ -791,21 +1713,27 @@ pub async fn handle_request(request: Request) -> Response {
             Response::AccessibilityPermission { granted }
         }
 
-        Request::Shutdown => {
-            info!("Shutdown requested via IPC");
-
-            // Stop capture
-            stop_capture().await;
+        Request::GetPermissionsStatus => {
+            match tokio::task::spawn_blocking(crate::permissions::check).await {
+                Ok(status) => Response::PermissionsStatus(status),
+                Err(e) => Response::error(format!("Permission check failed: {}", e)),
+            }
+        }
 
-            // Stop transcription worker
-            get_transcription_queue().stop_worker();
+        Request::RequestPermission { kind } => {
+            match tokio::task::spawn_blocking(move || crate::permissions::request(kind)).await {
+                Ok(Ok(status)) => Response::PermissionsStatus(status),
+                Ok(Err(e)) => Response::error(e),
+                Err(e) => Response::error(format!("Permission request failed: {}", e)),
+            }
+        }
 
-            // Broadcast shutdown event
-            broadcast_event(Response::Event {
-                event: EventType::Shutdown,
-            });
+        Request::Shutdown => {
+            info!("Shutdown requested via IPC");
 
-            crate::request_shutdown();
+            // Drain in the background so the client gets its reply; progress
+            // and the final Shutdown event are broadcast as it runs
+            tokio::spawn(crate::shutdown::graceful_shutdown());
             Response::Ok
         }
     }
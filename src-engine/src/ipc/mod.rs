@@ -1,6 +1,11 @@
 //! IPC server for client communication.
 
 pub mod handlers;
+#[cfg(windows)]
+mod pipe_security;
 pub(crate) mod server;
 
-pub use server::{broadcast_event, register_event_callback, run_server, EventCallback};
+pub(crate) use server::register_boxed_event_callback;
+pub use server::{
+    broadcast_event, register_event_callback, run_server, speech_metrics_requested, EventCallback,
+};
@@ -0,0 +1,106 @@
+//! Access control for the IPC named pipe on Windows.
+//!
+//! Pipe names are shared by every session on the machine, so a name alone
+//! keeps nobody out. The pipe is created with a protected DACL that grants
+//! access to the current user's SID only: other users can't connect to it,
+//! and because the first instance also demands to be the first with its
+//! name, they can't create it ahead of the engine to impersonate it either.
+
+use std::ffi::c_void;
+
+use windows::core::{HSTRING, PWSTR};
+use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL};
+use windows::Win32::Security::Authorization::{
+    ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{
+    GetTokenInformation, TokenUser, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY,
+    TOKEN_USER,
+};
+use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+/// A security descriptor that only lets the current user use the pipe.
+pub(crate) struct PipeSecurity {
+    descriptor: PSECURITY_DESCRIPTOR,
+}
+
+// The descriptor is never changed after it has been built
+unsafe impl Send for PipeSecurity {}
+unsafe impl Sync for PipeSecurity {}
+
+impl PipeSecurity {
+    /// Build the descriptor for the user the engine runs as.
+    pub(crate) fn current_user() -> Result<Self, String> {
+        let sid = current_user_sid()?;
+        // Protected DACL: full access for the user, no inherited entries
+        let sddl = HSTRING::from(format!("D:P(A;;GA;;;{})", sid));
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                &sddl,
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )
+        }
+        .map_err(|e| format!("Failed to build the pipe security descriptor: {}", e))?;
+        Ok(Self { descriptor })
+    }
+
+    /// Attributes for creating a pipe instance, valid while `self` is.
+    pub(crate) fn attributes(&self) -> SECURITY_ATTRIBUTES {
+        SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: self.descriptor.0,
+            bInheritHandle: false.into(),
+        }
+    }
+}
+
+impl Drop for PipeSecurity {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = LocalFree(HLOCAL(self.descriptor.0));
+        }
+    }
+}
+
+/// The current user's SID in string form, e.g. `S-1-5-21-...`.
+fn current_user_sid() -> Result<String, String> {
+    let mut token = HANDLE::default();
+    unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }
+        .map_err(|e| format!("Failed to open the process token: {}", e))?;
+    let sid = unsafe { token_user_sid(token) };
+    unsafe {
+        let _ = CloseHandle(token);
+    }
+    sid
+}
+
+/// # Safety
+///
+/// `token` must be an open token handle with `TOKEN_QUERY` access.
+unsafe fn token_user_sid(token: HANDLE) -> Result<String, String> {
+    // The first call only reports the size needed
+    let mut len = 0u32;
+    let _ = GetTokenInformation(token, TokenUser, None, 0, &mut len);
+
+    // u64 elements keep TOKEN_USER's pointers aligned
+    let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+    GetTokenInformation(
+        token,
+        TokenUser,
+        Some(buffer.as_mut_ptr() as *mut c_void),
+        len,
+        &mut len,
+    )
+    .map_err(|e| format!("Failed to query the process user: {}", e))?;
+    let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+
+    let mut string = PWSTR::null();
+    ConvertSidToStringSidW(user.User.Sid, &mut string)
+        .map_err(|e| format!("Failed to format the user SID: {}", e))?;
+    let sid = string.to_string().map_err(|e| e.to_string());
+    let _ = LocalFree(HLOCAL(string.0 as *mut c_void));
+    sid
+}
@@ -31,6 +31,54 @@ fn get_client_count() -> usize {
     CLIENT_COUNT.load(Ordering::SeqCst)
 }
 
+/// Number of connected clients that opted in to `SpeechMetrics` events
+static SPEECH_METRICS_SUBSCRIBERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Check whether any IPC client has opted in to `SpeechMetrics` events.
+/// Producers use this to skip building the high-frequency payload.
+pub fn speech_metrics_requested() -> bool {
+    SPEECH_METRICS_SUBSCRIBERS.load(Ordering::SeqCst) > 0
+}
+
+/// Counts a client as a `SpeechMetrics` subscriber until dropped.
+struct SpeechMetricsGuard;
+
+impl SpeechMetricsGuard {
+    fn new() -> Self {
+        SPEECH_METRICS_SUBSCRIBERS.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for SpeechMetricsGuard {
+    fn drop(&mut self) {
+        SPEECH_METRICS_SUBSCRIBERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Audio streams a client started, dropped when its connection closes so a
+/// client that goes away mid-stream doesn't leave one behind.
+#[derive(Default)]
+struct AudioStreamGuard(Vec<String>);
+
+impl AudioStreamGuard {
+    /// Note a stream started by `response`
+    fn track(&mut self, response: &Response) {
+        if let Response::AudioStreamStarted { stream_id } = response {
+            self.0.push(stream_id.clone());
+        }
+    }
+}
+
+impl Drop for AudioStreamGuard {
+    fn drop(&mut self) {
+        // Streams that already ended are gone; discarding them does nothing
+        for stream_id in &self.0 {
+            crate::audio_stream::discard(stream_id);
+        }
+    }
+}
+
 /// Event broadcaster for subscribed IPC clients
 pub type EventSender = broadcast::Sender<Response>;
 
@@ -54,14 +102,29 @@ pub trait EventCallback: Send + Sync + 'static {
 }
 
 /// Global external event callback (registered by Tauri app at startup)
-static EXTERNAL_EVENT_CALLBACK: std::sync::OnceLock<Box<dyn EventCallback>> =
-    std::sync::OnceLock::new();
+static EXTERNAL_EVENT_CALLBACK: std::sync::RwLock<Option<Box<dyn EventCallback>>> =
+    std::sync::RwLock::new(None);
 
 /// Register an external event callback.
 /// This should be called once during Tauri app setup to forward engine events
 /// directly to the frontend via Tauri emit(), bypassing IPC serialization.
 pub fn register_event_callback(callback: impl EventCallback) {
-    let _ = EXTERNAL_EVENT_CALLBACK.set(Box::new(callback));
+    register_boxed_event_callback(Box::new(callback));
+}
+
+/// Register an already boxed external event callback.
+pub(crate) fn register_boxed_event_callback(callback: Box<dyn EventCallback>) {
+    *EXTERNAL_EVENT_CALLBACK
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = Some(callback);
+}
+
+/// Remove the external event callback.
+pub(crate) fn clear_event_callback() {
+    EXTERNAL_EVENT_CALLBACK
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
 }
 
 /// Broadcast an event to all subscribed IPC clients and to the external callback.
@@ -69,18 +132,22 @@ pub fn register_event_callback(callback: impl EventCallback) {
 /// log the event instead of silently dropping it.
 pub fn broadcast_event(event: Response) {
     // First, forward to external callback (Tauri frontend) if registered
-    if let Response::Event { event: ref evt } = event {
-        if let Some(callback) = EXTERNAL_EVENT_CALLBACK.get() {
+    let has_callback = {
+        let callback = EXTERNAL_EVENT_CALLBACK
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        if let (Some(callback), Response::Event { event: evt }) = (callback.as_ref(), &event) {
             callback.on_event(evt);
         }
-    }
+        callback.is_some()
+    };
 
     // Then, send to subscribed IPC clients
     let sender = get_event_sender();
     if sender.receiver_count() == 0 {
         // No IPC clients subscribed - log based on event type
         // (skip logging if external callback is registered, since events are being consumed)
-        if EXTERNAL_EVENT_CALLBACK.get().is_none() {
+        if !has_callback {
             if let Response::Event { ref event } = event {
                 match event {
                     EventType::TranscriptionComplete(result) => {
@@ -90,6 +157,9 @@ pub fn broadcast_event(event: Response) {
                         // High-frequency event - use debug level
                         debug!("Visualization data generated (no clients)");
                     }
+                    EventType::SpeechMetrics(_) => {
+                        // High-frequency event, only produced on request
+                    }
                     EventType::SpeechStarted => {
                         debug!("Speech started (no clients)");
                     }
@@ -129,9 +199,139 @@ pub fn broadcast_event(event: Response) {
                     EventType::HistoryEntryDeleted { ref id } => {
                         info!("History entry deleted (no clients): {}", id);
                     }
+                    EventType::HistoryEntryUpdated { ref entry } => {
+                        info!("History entry updated (no clients): {}", entry.id);
+                    }
+                    EventType::RetranscriptionFailed { ref id, ref error } => {
+                        info!("Re-transcription failed (no clients): {}: {}", id, error);
+                    }
+                    EventType::MeetingStarted { ref meeting } => {
+                        info!("Meeting started (no clients): {}", meeting.id);
+                    }
+                    EventType::MeetingLine { ref meeting_id, .. } => {
+                        debug!("Meeting line (no clients): {}", meeting_id);
+                    }
+                    EventType::MeetingEnded { ref meeting } => {
+                        info!("Meeting ended (no clients): {}", meeting.id);
+                    }
+                    EventType::StreamTranscription { ref stream_id, .. } => {
+                        debug!("Stream transcription (no clients): {}", stream_id);
+                    }
+                    EventType::LoadSheddingChanged { active, ref reason } => {
+                        info!("Load shedding (no clients): active={} ({})", active, reason);
+                    }
+                    EventType::PowerStateChanged { on_battery } => {
+                        info!("Power changed (no clients): on_battery={}", on_battery);
+                    }
+                    EventType::LowDiskSpace { available_mb, .. } => {
+                        warn!("Low disk space (no clients): {} MB free", available_mb);
+                    }
+                    EventType::PermissionChanged { kind, state } => {
+                        info!("Permission changed (no clients): {:?} {:?}", kind, state);
+                    }
+                    EventType::PasteFailed { ref reason } => {
+                        warn!("Paste failed (no clients): {}", reason);
+                    }
+                    EventType::ComponentRestarted { component, .. } => {
+                        warn!("Component restarted (no clients): {}", component.label());
+                    }
+                    EventType::HistoryAudioFinished { ref id } => {
+                        info!("History audio finished (no clients): {}", id);
+                    }
+                    EventType::PrivacyModeChanged { enabled } => {
+                        info!("Privacy mode changed (no clients): enabled={}", enabled);
+                    }
+                    EventType::UpdateAvailable { ref version, .. } => {
+                        info!("Update available (no clients): {}", version);
+                    }
+                    EventType::UpdateStaged { ref path, .. } => {
+                        info!("Update staged (no clients): {}", path);
+                    }
+                    EventType::ThemeChanged { ref theme } => {
+                        info!("Theme changed (no clients): {:?}", theme.mode);
+                    }
+                    EventType::AutostartChanged { enabled } => {
+                        info!("Autostart changed (no clients): enabled={}", enabled);
+                    }
+                    EventType::DictationChanged { active } => {
+                        info!("Dictation changed (no clients): active={}", active);
+                    }
+                    EventType::SpellingModeChanged { active } => {
+                        info!("Spelling mode changed (no clients): active={}", active);
+                    }
+                    EventType::OutputPausedChanged { paused } => {
+                        info!("Output pause changed (no clients): paused={}", paused);
+                    }
+                    EventType::ConfigChanged { ref keys } => {
+                        info!("Config changed (no clients): {}", keys.join(", "));
+                    }
+                    EventType::AutoSuspended { suspended } => {
+                        info!("Auto-suspend changed (no clients): suspended={}", suspended);
+                    }
+                    EventType::DeviceBusy { ref device_id, .. } => {
+                        info!("Device busy (no clients): {}", device_id);
+                    }
+                    EventType::CaptureStalled { stalled_secs } => {
+                        info!("Capture stalled (no clients): {}s", stalled_secs);
+                    }
+                    EventType::DeviceProfileChanged { sample_rate, .. } => {
+                        info!("Device profile changed (no clients): {} Hz", sample_rate);
+                    }
+                    EventType::ClippingDetected { clipped_samples } => {
+                        info!("Clipping detected (no clients): {}", clipped_samples);
+                    }
+                    EventType::PausedByProcess { ref process } => {
+                        info!("Paused by process rule (no clients): {:?}", process);
+                    }
                     EventType::AutoModeToggled { mode } => {
                         info!("Auto mode toggled (no clients): {:?}", mode);
                     }
+                    EventType::AutoModeToggledBy { mode, source } => {
+                        info!("Auto mode toggled by {:?} (no clients): {:?}", source, mode);
+                    }
+                    EventType::TestFileStarted {
+                        index,
+                        total,
+                        ref file,
+                    } => {
+                        info!(
+                            "Test file started (no clients): [{}/{}] {}",
+                            index + 1,
+                            total,
+                            file
+                        );
+                    }
+                    EventType::TestFileComplete {
+                        index,
+                        total,
+                        ref result,
+                    } => {
+                        info!(
+                            "Test file complete (no clients): [{}/{}] {} {:?}",
+                            index + 1,
+                            total,
+                            result.file,
+                            result.outcome
+                        );
+                    }
+                    EventType::TestRunComplete {
+                        ref summary,
+                        ref report_path,
+                    } => {
+                        info!(
+                            "Test run complete (no clients): {} file(s), report {:?}",
+                            summary.total, report_path
+                        );
+                    }
+                    EventType::ShutdownProgress {
+                        phase,
+                        pending_segments,
+                    } => {
+                        info!(
+                            "Shutdown progress (no clients): {:?}, {} segment(s) pending",
+                            phase, pending_segments
+                        );
+                    }
                     EventType::Shutdown => {
                         info!("Shutdown event (no clients)");
                     }
@@ -155,11 +355,9 @@ pub async fn run_server(ready_tx: Option<oneshot::Sender<()>>) -> Result<(), Ipc
 
     let socket_path = get_socket_path();
 
-    // Create parent directory if needed
+    // Create parent directory if needed, private to this user
     if let Some(parent) = socket_path.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent).map_err(IpcError::Io)?;
-        }
+        flowstt_common::ipc::prepare_socket_dir(parent).map_err(IpcError::Io)?;
     }
 
     // Remove stale socket file if it exists
@@ -170,6 +368,11 @@ pub async fn run_server(ready_tx: Option<oneshot::Sender<()>>) -> Result<(), Ipc
 
     // Bind to socket
     let listener = UnixListener::bind(&socket_path).map_err(IpcError::Io)?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o600))
+            .map_err(IpcError::Io)?;
+    }
     info!("IPC server listening on {:?}", socket_path);
 
     // Signal readiness - the socket is now bound and accepting connections
@@ -233,49 +436,65 @@ async fn handle_unix_client(stream: tokio::net::UnixStream) -> Result<(), IpcErr
 /// been created and is ready to accept client connections. This allows callers
 /// to run heavy initialization concurrently without racing the first client
 /// connect.
+///
+/// Only the current user may open the pipe. Fails if a pipe with the same
+/// name already exists, which another user could have created to pose as the
+/// engine. From then on an instance is always kept open, so the name is never
+/// free for someone else to take.
 #[cfg(windows)]
 pub async fn run_server(mut ready_tx: Option<oneshot::Sender<()>>) -> Result<(), IpcError> {
-    use tokio::net::windows::named_pipe::{PipeMode, ServerOptions};
+    use tokio::net::windows::named_pipe::{NamedPipeServer, PipeMode, ServerOptions};
 
     let pipe_name = get_socket_path();
     let pipe_name_str = pipe_name.to_string_lossy();
+    let security = super::pipe_security::PipeSecurity::current_user()
+        .map_err(|e| IpcError::Io(std::io::Error::other(e)))?;
+
+    let create = |first: bool| -> std::io::Result<NamedPipeServer> {
+        let mut attributes = security.attributes();
+        unsafe {
+            ServerOptions::new()
+                .first_pipe_instance(first)
+                .pipe_mode(PipeMode::Byte)
+                .create_with_security_attributes_raw(
+                    &pipe_name,
+                    &mut attributes as *mut _ as *mut std::ffi::c_void,
+                )
+        }
+    };
+
+    let mut server = create(true).map_err(|e| {
+        error!("Failed to create pipe {}: {}", pipe_name_str, e);
+        IpcError::Io(e)
+    })?;
     info!("IPC server listening on {}", pipe_name_str);
 
+    // Signal readiness after the first pipe instance is successfully created.
+    // At this point clients can connect via the named pipe.
+    if let Some(tx) = ready_tx.take() {
+        let _ = tx.send(());
+    }
+
     loop {
         if is_shutdown_requested() {
             info!("Shutdown requested, stopping IPC server");
             break;
         }
 
-        // Create a new pipe instance
-        let server = match ServerOptions::new()
-            .first_pipe_instance(false)
-            .pipe_mode(PipeMode::Byte)
-            .create(&pipe_name)
-        {
-            Ok(s) => s,
-            Err(e) => {
-                error!("Failed to create pipe: {}", e);
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                continue;
-            }
-        };
-
-        // Signal readiness after the first pipe instance is successfully created.
-        // At this point clients can connect via the named pipe.
-        if let Some(tx) = ready_tx.take() {
-            let _ = tx.send(());
-        }
-
         // Wait for client with timeout
         let connect_result =
             tokio::time::timeout(std::time::Duration::from_secs(1), server.connect()).await;
 
         match connect_result {
             Ok(Ok(())) => {
+                // The next instance is created before this one is handed over
+                let Some(next) = create_next_instance(&create).await else {
+                    break;
+                };
+                let connected = std::mem::replace(&mut server, next);
                 info!("Client connected");
                 tokio::spawn(async move {
-                    if let Err(e) = handle_windows_client(server).await {
+                    if let Err(e) = handle_windows_client(connected).await {
                         if !matches!(e, IpcError::ConnectionClosed) {
                             error!("Client error: {}", e);
                         }
@@ -285,6 +504,11 @@ pub async fn run_server(mut ready_tx: Option<oneshot::Sender<()>>) -> Result<(),
             }
             Ok(Err(e)) => {
                 error!("Pipe connect error: {}", e);
+                // Replace the instance, which may be unusable now
+                let Some(next) = create_next_instance(&create).await else {
+                    break;
+                };
+                server = next;
             }
             Err(_) => {
                 // Timeout, check shutdown flag again
@@ -296,6 +520,24 @@ pub async fn run_server(mut ready_tx: Option<oneshot::Sender<()>>) -> Result<(),
     Ok(())
 }
 
+/// Create another instance of the pipe, retrying until it works or shutdown
+/// is requested.
+#[cfg(windows)]
+async fn create_next_instance(
+    create: &impl Fn(bool) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeServer>,
+) -> Option<tokio::net::windows::named_pipe::NamedPipeServer> {
+    loop {
+        match create(false) {
+            Ok(server) => return Some(server),
+            Err(e) => error!("Failed to create pipe: {}", e),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        if is_shutdown_requested() {
+            return None;
+        }
+    }
+}
+
 /// Handle a Windows named pipe client connection.
 #[cfg(windows)]
 async fn handle_windows_client(
@@ -323,6 +565,9 @@ where
     let writer = Arc::new(Mutex::new(writer));
     let mut event_receiver: Option<broadcast::Receiver<Response>> = None;
     let mut subscribed = false;
+    // Held while this client is opted in to SpeechMetrics events
+    let mut speech_metrics_guard: Option<SpeechMetricsGuard> = None;
+    let mut audio_streams = AudioStreamGuard::default();
 
     loop {
         if is_shutdown_requested() {
@@ -350,6 +595,11 @@ where
                     event_result = rx.recv() => {
                         drop(r); // Release reader lock before writing
                         match event_result {
+                            Ok(Response::Event { event: EventType::SpeechMetrics(_) })
+                                if speech_metrics_guard.is_none() =>
+                            {
+                                // Not opted in to speech metrics
+                            }
                             Ok(event) => {
                                 let mut w = writer.lock().await;
                                 write_json(&mut *w, &event).await?;
@@ -372,6 +622,7 @@ where
                             Ok(Ok(request)) => {
                                 info!("Received request: {:?}", request);
                                 let response = handle_request(request.clone()).await;
+                                audio_streams.track(&response);
                                 info!("Sending response: {:?}", response);
                                 let mut w = writer.lock().await;
                                 write_json(&mut *w, &response).await?;
@@ -400,14 +651,18 @@ where
                 info!("Received request: {:?}", request);
 
                 // Check if this is a subscribe request
-                let is_subscribe = matches!(request, Request::SubscribeEvents);
-                if is_subscribe {
+                let is_subscribe = matches!(request, Request::SubscribeEvents { .. });
+                if let Request::SubscribeEvents { speech_metrics } = request {
                     subscribed = true;
                     event_receiver = Some(get_event_sender().subscribe());
+                    if speech_metrics && speech_metrics_guard.is_none() {
+                        speech_metrics_guard = Some(SpeechMetricsGuard::new());
+                    }
                 }
 
                 // Handle request
                 let response = handle_request(request.clone()).await;
+                audio_streams.track(&response);
                 info!("Sending response: {:?}", response);
 
                 // Send response
@@ -0,0 +1,155 @@
+//! Joining segments pasted one after another.
+//!
+//! Segments are transcribed separately, so pasting them back to back either
+//! runs them together or, with a separator on both ends, doubles the spaces.
+//! Instead the text last pasted is remembered along with the window it went
+//! into, and the next segment pasted into the same window gets a leading
+//! separator chosen by the `segment_joining` setting. A segment pasted into
+//! another window starts afresh.
+
+use std::sync::Mutex;
+
+use flowstt_common::config::SegmentJoining;
+
+/// Punctuation that attaches to the text before it without a space
+const CLOSING_PUNCTUATION: [char; 7] = [',', '.', ';', ':', '!', '?', ')'];
+
+/// The segment pasted last.
+struct LastPaste {
+    /// Window it was pasted into, if known
+    window: Option<u64>,
+    /// The segment as pasted, without its separator
+    text: String,
+}
+
+static LAST_PASTE: Mutex<Option<LastPaste>> = Mutex::new(None);
+
+/// Prepare `text` for pasting into `window` after the segment pasted before
+/// it, and remember it for the next one.
+pub fn next_paste(mode: SegmentJoining, text: &str, window: Option<u64>) -> String {
+    let mut last = LAST_PASTE.lock().unwrap();
+    let joined = join(mode, previous(&last, window), text);
+
+    *last = Some(LastPaste {
+        window,
+        text: joined.trim_start().to_string(),
+    });
+    joined
+}
+
+/// What [`next_paste`] would return now, without remembering it, for text
+/// typed ahead of the segment it belongs to.
+pub fn preview(mode: SegmentJoining, text: &str, window: Option<u64>) -> String {
+    let last = LAST_PASTE.lock().unwrap();
+    join(mode, previous(&last, window), text)
+}
+
+/// The segment last pasted into `window`, if that was the last paste
+fn previous(last: &Option<LastPaste>, window: Option<u64>) -> Option<&str> {
+    last.as_ref()
+        .filter(|last| last.window == window)
+        .map(|last| last.text.as_str())
+}
+
+/// Text to paste after `previous` so that `text` continues it: the separator
+/// for `mode` followed by `text`, whose first letter and punctuation are
+/// adjusted to fit in smart-sentence mode.
+pub fn join(mode: SegmentJoining, previous: Option<&str>, text: &str) -> String {
+    let text = text.trim();
+    let Some(previous) = previous.map(str::trim_end).filter(|p| !p.is_empty()) else {
+        return text.to_string();
+    };
+
+    match mode {
+        SegmentJoining::Space => space_before(text),
+        SegmentJoining::Newline => format!("\n{}", text),
+        SegmentJoining::SmartSentence => {
+            let last = previous.chars().last();
+            // Don't repeat the punctuation the previous segment ended with
+            let text = match last {
+                Some(mark) if CLOSING_PUNCTUATION.contains(&mark) && mark != ')' => {
+                    text.strip_prefix(mark).map_or(text, str::trim_start)
+                }
+                _ => text,
+            };
+            let text = match last {
+                Some('.' | '!' | '?') => capitalize_first(text),
+                Some(',' | ';' | ':') => lowercase_first(text),
+                _ => text.to_string(),
+            };
+            space_before(&text)
+        }
+    }
+}
+
+fn space_before(text: &str) -> String {
+    if text.is_empty() || text.starts_with(CLOSING_PUNCTUATION) {
+        text.to_string()
+    } else {
+        format!(" {}", text)
+    }
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Lowercase the first letter, unless the first word is "I" (or a
+/// contraction of it) or looks like an acronym.
+fn lowercase_first(text: &str) -> String {
+    let word = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .next()
+        .unwrap_or("");
+    let pronoun = word == "I" || word.starts_with("I'");
+    let acronym = word.chars().skip(1).any(char::is_uppercase);
+    if pronoun || acronym {
+        return text.to_string();
+    }
+
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_space_and_newline() {
+        use SegmentJoining::{Newline, Space};
+
+        assert_eq!(join(Space, None, " Hello there. "), "Hello there.");
+        assert_eq!(join(Space, Some("Hello there. "), " Bye."), " Bye.");
+        assert_eq!(join(Space, Some("Wait"), ", really?"), ", really?");
+        assert_eq!(join(Newline, Some("One."), "Two."), "\nTwo.");
+    }
+
+    #[test]
+    fn test_join_smart_sentence() {
+        use SegmentJoining::SmartSentence as Smart;
+
+        assert_eq!(
+            join(Smart, Some("Hello there."), "how are you?"),
+            " How are you?"
+        );
+        assert_eq!(
+            join(Smart, Some("First,"), "Then we left."),
+            " then we left."
+        );
+        assert_eq!(join(Smart, Some("Well,"), "I think so."), " I think so.");
+        assert_eq!(
+            join(Smart, Some("We met"), "NASA engineers"),
+            " NASA engineers"
+        );
+        assert_eq!(join(Smart, Some("It ended."), ". And then"), " And then");
+        assert_eq!(join(Smart, Some("Wait"), ", really?"), ", really?");
+    }
+}
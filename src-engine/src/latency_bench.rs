@@ -0,0 +1,500 @@
+//! End-to-end latency benchmark.
+//!
+//! Plays audio through the engine in real time, from a synthetic capture
+//! backend standing in for the platform one, and measures how long after
+//! the speech in it ends the transcription event arrives. The audio takes
+//! the same path as captured audio in Automatic mode: the audio loop,
+//! resampling, speech detection, the ring buffer and live segmenter, the
+//! transcription queue and the configured engine. Only the output differs:
+//! results are reported as `StreamTranscription` events, never saved or
+//! pasted. Runs are repeated to report the median and 95th percentile, so
+//! releases can be compared with `flowstt bench latency`.
+//!
+//! A WAV file can be supplied; otherwise a built-in sample is used: a short
+//! tone, then a harmonic speech-like signal, each followed by silence.
+//! Whisper may find no words in the built-in sample, which still measures
+//! the pipeline. Benchmarks take over the capture path, so they refuse to
+//! run while transcription is on.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::{AudioDevice, CaptureGlitches, LatencyReport, RecordingMode};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::audio::convert_to_mono;
+use crate::ipc::handlers::{get_transcribe_state, get_transcription_queue};
+use crate::platform::realtime::StopToken;
+use crate::platform::{self, AudioBackend, AudioData, CaptureError};
+use crate::resample::PROCESSING_SAMPLE_RATE;
+
+/// Sample rate of the built-in sample
+const SAMPLE_RATE: u32 = 16000;
+
+/// Frames with an RMS level above this count as speech when finding where
+/// speech ends (dBFS)
+const SPEECH_LEVEL_DB: f32 = -45.0;
+
+/// How long to wait for transcriptions once the audio has been played
+const RESULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often to check whether all the audio has been played
+const PLAYED_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Mono audio to play
+struct BenchAudio {
+    samples: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl BenchAudio {
+    /// Load a WAV file, mixed down to mono.
+    fn load(path: &Path) -> Result<Self, String> {
+        use hound::{SampleFormat, WavReader};
+
+        let mut reader = WavReader::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let spec = reader.spec();
+        let samples: Vec<f32> = match spec.sample_format {
+            SampleFormat::Float => reader.samples::<f32>().filter_map(Result::ok).collect(),
+            SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .filter_map(Result::ok)
+                    .map(|s| s as f32 / scale)
+                    .collect()
+            }
+        };
+        Ok(Self {
+            samples: convert_to_mono(&samples, spec.channels as usize),
+            sample_rate: spec.sample_rate,
+        })
+    }
+
+    /// The built-in sample: a 1kHz tone, then a voice-like harmonic signal
+    /// with syllable-rate amplitude changes.
+    fn synthetic() -> Self {
+        let rate = SAMPLE_RATE as f32;
+        let silence = |secs: f32| vec![0.0f32; (rate * secs) as usize];
+        let tone: Vec<f32> = (0..(rate * 0.3) as usize)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate).sin())
+            .collect();
+        let speech: Vec<f32> = (0..(rate * 1.5) as usize)
+            .map(|i| {
+                let t = i as f32 / rate;
+                let voice: f32 = (1..=10)
+                    .map(|k| (2.0 * std::f32::consts::PI * 140.0 * k as f32 * t).sin() / k as f32)
+                    .sum();
+                let syllables = 0.4 + 0.6 * (std::f32::consts::PI * 4.0 * t).sin().abs();
+                0.12 * voice * syllables
+            })
+            .collect();
+
+        let mut samples = silence(0.5);
+        samples.extend(tone);
+        samples.extend(silence(0.6));
+        samples.extend(speech);
+        samples.extend(silence(1.5));
+        Self {
+            samples,
+            sample_rate: SAMPLE_RATE,
+        }
+    }
+
+    /// Length in milliseconds
+    fn duration_ms(&self) -> u64 {
+        self.samples.len() as u64 * 1000 / u64::from(self.sample_rate.max(1))
+    }
+
+    /// Sample position just after the last 10ms frame loud enough to be speech
+    fn speech_end(&self) -> Option<usize> {
+        let frame = (self.sample_rate / 100).max(1) as usize;
+        self.samples
+            .chunks(frame)
+            .enumerate()
+            .rfind(|(_, chunk)| {
+                let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+                20.0 * rms.max(1e-10).log10() > SPEECH_LEVEL_DB
+            })
+            .map(|(i, chunk)| i * frame + chunk.len())
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Capture backend that plays the benchmark audio in real time, installed
+/// over the platform backend for the length of a run.
+struct BenchBackend {
+    playback: Mutex<Option<Playback>>,
+}
+
+static BACKEND: BenchBackend = BenchBackend {
+    playback: Mutex::new(None),
+};
+
+/// Audio being played by [`BenchBackend`]
+struct Playback {
+    audio: Arc<BenchAudio>,
+    speech_end: usize,
+    started: Instant,
+    /// Next sample to deliver
+    position: usize,
+    /// When the chunk holding the end of speech was delivered
+    speech_ended_at: Option<Instant>,
+}
+
+impl Playback {
+    /// End of the next 10ms chunk, or `None` once all the audio was played
+    fn next_end(&self) -> Option<usize> {
+        let chunk_len = (self.audio.sample_rate / 100).max(1) as usize;
+        (self.position < self.audio.samples.len())
+            .then(|| (self.position + chunk_len).min(self.audio.samples.len()))
+    }
+
+    /// When the next chunk is available: once its last sample would have
+    /// been captured
+    fn next_due(&self) -> Option<Instant> {
+        let end = self.next_end()?;
+        let nanos = end as u64 * 1_000_000_000 / u64::from(self.audio.sample_rate);
+        Some(self.started + Duration::from_nanos(nanos))
+    }
+
+    fn next_chunk(&mut self) -> Option<AudioData> {
+        let end = self.next_end()?;
+        // In stereo, like the platform backends deliver
+        let samples = self.audio.samples[self.position..end]
+            .iter()
+            .flat_map(|&s| [s, s])
+            .collect();
+        self.position = end;
+        if self.speech_ended_at.is_none() && end >= self.speech_end {
+            self.speech_ended_at = Some(Instant::now());
+        }
+        Some(AudioData {
+            samples,
+            channels: 2,
+            sample_rate: self.audio.sample_rate,
+            primary: None,
+        })
+    }
+}
+
+impl BenchBackend {
+    /// When the end of speech was delivered, once all the audio has been
+    fn played(&self) -> Option<Instant> {
+        let playback = self.playback.lock().unwrap();
+        let playback = playback.as_ref()?;
+        if playback.next_end().is_some() {
+            return None;
+        }
+        playback.speech_ended_at
+    }
+}
+
+impl AudioBackend for BenchBackend {
+    fn sample_rate(&self) -> u32 {
+        let playback = self.playback.lock().unwrap();
+        playback
+            .as_ref()
+            .map_or(SAMPLE_RATE, |playback| playback.audio.sample_rate)
+    }
+
+    fn input_sample_rate(&self) -> Option<u32> {
+        // Not a device, so there is no native rate to report
+        None
+    }
+
+    fn list_input_devices(&self) -> Vec<AudioDevice> {
+        Vec::new()
+    }
+
+    fn list_system_devices(&self) -> Vec<AudioDevice> {
+        Vec::new()
+    }
+
+    fn start_capture_sources(
+        &self,
+        _source1_id: Option<String>,
+        _source2_id: Option<String>,
+    ) -> Result<(), CaptureError> {
+        Ok(())
+    }
+
+    fn stop_capture(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn recv(&self, stop: &StopToken, timeout: Duration) -> Option<AudioData> {
+        let due = {
+            let playback = self.playback.lock().unwrap();
+            playback.as_ref().and_then(Playback::next_due)
+        };
+        let Some(due) = due else {
+            stop.wait(timeout);
+            return None;
+        };
+        let wait = due.saturating_duration_since(Instant::now());
+        if stop.wait(wait.min(timeout)) || wait > timeout {
+            return None;
+        }
+        self.playback.lock().unwrap().as_mut()?.next_chunk()
+    }
+
+    fn set_aec_enabled(&self, _enabled: bool) {}
+
+    fn set_recording_mode(&self, _mode: RecordingMode) {}
+
+    fn capture_glitches(&self) -> CaptureGlitches {
+        CaptureGlitches::default()
+    }
+}
+
+/// Whether transcription is using the capture path
+async fn capture_in_use() -> bool {
+    let state_arc = crate::state::get_service_state();
+    let state = state_arc.lock().await;
+    state.transcribe_status.capturing
+        || state.auto_mode_active
+        || crate::audio_loop::is_audio_loop_active()
+        || crate::ptt_controller::is_ptt_controller_running()
+}
+
+/// Play the audio `runs` times and report the latency from the end of
+/// speech to the transcription event.
+pub async fn run(file: Option<PathBuf>, runs: u32) -> Result<LatencyReport, String> {
+    if capture_in_use().await {
+        return Err("Stop transcription before running the latency benchmark".to_string());
+    }
+
+    let audio = match file {
+        Some(ref path) => BenchAudio::load(path)?,
+        None => BenchAudio::synthetic(),
+    };
+    let speech_end = audio
+        .speech_end()
+        .ok_or_else(|| "The audio contains no speech".to_string())?;
+    let audio = Arc::new(audio);
+
+    let mut latencies = Vec::new();
+    let mut failed = 0;
+    for run in 1..=runs {
+        match run_once(&audio, speech_end).await {
+            Ok(latency) => {
+                info!("[LatencyBench] Run {}/{}: {} ms", run, runs, latency);
+                latencies.push(latency);
+            }
+            Err(e) => {
+                warn!("[LatencyBench] Run {}/{} failed: {}", run, runs, e);
+                failed += 1;
+            }
+        }
+    }
+
+    let mut sorted = latencies.clone();
+    sorted.sort_unstable();
+    Ok(LatencyReport {
+        file: file.map(|path| path.to_string_lossy().into_owned()),
+        audio_ms: audio.duration_ms(),
+        latencies_ms: latencies,
+        failed,
+        p50_ms: percentile(&sorted, 50.0),
+        p95_ms: percentile(&sorted, 95.0),
+        max_ms: sorted.last().copied().unwrap_or(0),
+    })
+}
+
+/// Play the audio once. Returns the time from the end of speech to the
+/// transcription of the last segment, in milliseconds.
+async fn run_once(audio: &Arc<BenchAudio>, speech_end: usize) -> Result<u64, String> {
+    // Subscribe first so no transcription is missed
+    let mut events = crate::ipc::server::get_event_sender().subscribe();
+    let stream_id = crate::history::generate_id();
+    let _playing = PlayingGuard::start(audio, speech_end, &stream_id)?;
+    let deadline =
+        tokio::time::Instant::now() + Duration::from_millis(audio.duration_ms()) + RESULT_TIMEOUT;
+    let mut poll = tokio::time::interval(PLAYED_POLL_INTERVAL);
+
+    let mut played: Option<(Instant, u32)> = None;
+    // Segment offset and arrival time of each transcription
+    let mut results: Vec<(u64, Instant)> = Vec::new();
+    loop {
+        if let Some((_, segments)) = played {
+            if segments == 0 {
+                return Err("No speech was detected in the audio".to_string());
+            }
+            if results.len() >= segments as usize {
+                break;
+            }
+        }
+
+        tokio::select! {
+            _ = poll.tick(), if played.is_none() => {
+                if let Some(speech_ended_at) = BACKEND.played() {
+                    played = Some((speech_ended_at, stop_playback()));
+                }
+            }
+            event = events.recv() => match event {
+                Ok(Response::Event {
+                    event: EventType::StreamTranscription { stream_id: id, offset_ms, .. },
+                }) if id == stream_id => results.push((offset_ms, Instant::now())),
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return Err("The engine stopped".to_string()),
+            },
+            _ = tokio::time::sleep_until(deadline) => {
+                return Err("No transcription before the timeout".to_string());
+            }
+        }
+    }
+
+    let (speech_ended_at, _) = played.expect("checked above");
+    let (_, transcribed_at) = results
+        .iter()
+        .max_by_key(|(offset_ms, _)| *offset_ms)
+        .expect("checked above");
+    Ok(transcribed_at
+        .checked_duration_since(speech_ended_at)
+        .unwrap_or_default()
+        .as_millis() as u64)
+}
+
+/// Plays the audio through the capture path until dropped, or until
+/// [`stop_playback`] is called.
+struct PlayingGuard;
+
+impl PlayingGuard {
+    /// Route the segments to `stream_id`, install the benchmark backend and
+    /// start the audio loop, as starting capture in Automatic mode would.
+    fn start(audio: &Arc<BenchAudio>, speech_end: usize, stream_id: &str) -> Result<Self, String> {
+        {
+            let transcribe_state = get_transcribe_state();
+            let mut transcribe = transcribe_state.lock().unwrap();
+            // Captured audio is normalized to the processing rate
+            transcribe.init_for_capture(PROCESSING_SAMPLE_RATE, 2);
+            transcribe.route_to_stream(stream_id.to_string());
+            transcribe.activate();
+        }
+        *BACKEND.playback.lock().unwrap() = Some(Playback {
+            audio: Arc::clone(audio),
+            speech_end,
+            started: Instant::now(),
+            position: 0,
+            speech_ended_at: None,
+        });
+        platform::set_backend_override(Some(&BACKEND));
+
+        let guard = Self;
+        crate::audio_loop::start_audio_loop(get_transcription_queue(), get_transcribe_state())?;
+        Ok(guard)
+    }
+}
+
+impl Drop for PlayingGuard {
+    fn drop(&mut self) {
+        stop_playback();
+    }
+}
+
+/// Stop playing, queue the speech in progress and go back to the platform
+/// backend. Returns how many segments were queued; does nothing if nothing
+/// is playing.
+fn stop_playback() -> u32 {
+    if BACKEND.playback.lock().unwrap().take().is_none() {
+        return 0;
+    }
+    crate::audio_loop::stop_audio_loop();
+
+    let segments = {
+        let transcribe_state = get_transcribe_state();
+        let mut transcribe = transcribe_state.lock().unwrap();
+        transcribe.finalize();
+        let segments = transcribe.end_stream();
+        transcribe.deactivate();
+        segments
+    };
+    platform::set_backend_override(None);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let sorted: Vec<u64> = (1..=20).map(|i| i * 10).collect();
+        assert_eq!(percentile(&sorted, 50.0), 100);
+        assert_eq!(percentile(&sorted, 95.0), 190);
+        assert_eq!(percentile(&[42], 95.0), 42);
+        assert_eq!(percentile(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn synthetic_speech_ends_before_trailing_silence() {
+        let audio = BenchAudio::synthetic();
+        assert_eq!(audio.duration_ms(), 4400);
+        let end_ms = audio.speech_end().unwrap() as u64 * 1000 / u64::from(SAMPLE_RATE);
+        assert!(
+            (2800..=2900).contains(&end_ms),
+            "speech ends at {} ms",
+            end_ms
+        );
+    }
+
+    #[test]
+    fn playback_delivers_stereo_chunks_in_real_time() {
+        let audio = Arc::new(BenchAudio {
+            samples: (0..250).map(|i| i as f32).collect(),
+            sample_rate: 10000,
+        });
+        let started = Instant::now();
+        let mut playback = Playback {
+            audio,
+            speech_end: 150,
+            started,
+            position: 0,
+            speech_ended_at: None,
+        };
+
+        assert_eq!(
+            playback.next_due(),
+            Some(started + Duration::from_millis(10))
+        );
+        let first = playback.next_chunk().unwrap();
+        assert_eq!((first.channels, first.sample_rate), (2, 10000));
+        assert_eq!(first.samples.len(), 200);
+        assert_eq!(first.samples[..4], [0.0, 0.0, 1.0, 1.0]);
+        assert!(playback.speech_ended_at.is_none());
+
+        // The chunk holding the end of speech marks when it was played
+        playback.next_chunk().unwrap();
+        assert!(playback.speech_ended_at.is_some());
+
+        assert_eq!(
+            playback.next_due(),
+            Some(started + Duration::from_millis(25))
+        );
+        assert_eq!(playback.next_chunk().unwrap().samples.len(), 100);
+        assert!(playback.next_chunk().is_none());
+        assert!(playback.next_due().is_none());
+    }
+
+    #[test]
+    fn silence_has_no_speech_end() {
+        let audio = BenchAudio {
+            samples: vec![0.0; 16000],
+            sample_rate: 16000,
+        };
+        assert_eq!(audio.speech_end(), None);
+    }
+}
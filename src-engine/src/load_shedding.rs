@@ -0,0 +1,197 @@
+//! Load shedding for the transcription queue.
+//!
+//! When other work saturates the CPU, transcription falls behind and
+//! segments pile up in the queue. A background task watches the CPU load and
+//! the queue depth; past the `load_shedding` thresholds in the config file it
+//! transcribes with a smaller model and lets speech run longer before it is
+//! split at a word break, so whisper runs less often. `LoadSheddingChanged`
+//! events say when shedding starts and stops, and why.
+//!
+//! Shedding lasts at least [`MIN_SHED_DURATION`] and ends once the queue has
+//! drained and the load has dropped back below the threshold.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use flowstt_common::config::LoadShedding;
+use flowstt_common::ipc::{EventType, Response};
+use tracing::{debug, info};
+
+use crate::config::current_config;
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::get_transcription_queue;
+
+/// How often the load and queue are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Shortest time shedding stays on, so it doesn't flap
+const MIN_SHED_DURATION: Duration = Duration::from_secs(30);
+
+/// How far below the threshold the load must drop before shedding ends
+const LOAD_HYSTERESIS: f32 = 0.1;
+
+/// Longest segment before a word break split, leaving room in the capture
+/// ring buffer
+const MAX_SPLIT_MS: u32 = 20_000;
+
+static SHEDDING: AtomicBool = AtomicBool::new(false);
+
+/// Segment length before word break splitting while shedding, in
+/// milliseconds; 0 when splitting as usual
+static SPLIT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Whether load shedding is in effect.
+pub fn is_shedding() -> bool {
+    SHEDDING.load(Ordering::SeqCst)
+}
+
+/// Segment length at which to look for a word break to split at, if load
+/// shedding changes it.
+pub fn split_ms() -> Option<u64> {
+    let ms = SPLIT_MS.load(Ordering::Relaxed);
+    (ms > 0).then_some(ms)
+}
+
+/// Model to use instead of the usual one while shedding, if the config names
+/// one that has been downloaded.
+pub fn model_override() -> Option<PathBuf> {
+    if !is_shedding() {
+        return None;
+    }
+    let name = current_config().load_shedding.model.clone()?;
+    let path = crate::transcription::model_path_for(&name);
+    if !path.exists() {
+        debug!("[LoadShedding] Model {:?} isn't downloaded", path);
+        return None;
+    }
+    Some(path)
+}
+
+/// Run the load monitor until shutdown.
+pub async fn run_load_monitor() {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut cpu = CpuMonitor::default();
+    let mut shedding_since: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        let config = current_config();
+        let settings = &config.load_shedding;
+        let load = cpu.load();
+        let depth = get_transcription_queue().queue_depth();
+        let reason = describe(load, depth);
+
+        match shedding_since {
+            None if settings.enabled && falling_behind(&settings, load, depth) => {
+                info!("[LoadShedding] Shedding load: {}", reason);
+                start(&settings);
+                shedding_since = Some(Instant::now());
+                broadcast(true, reason);
+            }
+            Some(since) if !settings.enabled || recovered(&settings, load, depth, since) => {
+                info!("[LoadShedding] Load back to normal: {}", reason);
+                stop();
+                shedding_since = None;
+                broadcast(false, reason);
+            }
+            _ => {}
+        }
+    }
+
+    stop();
+    debug!("[LoadShedding] Monitor stopped");
+}
+
+/// Whether transcription is falling behind: many segments waiting, or some
+/// waiting on a busy machine.
+fn falling_behind(settings: &LoadShedding, load: Option<f32>, depth: usize) -> bool {
+    depth >= settings.queue_depth.max(1) as usize
+        || (depth > 0 && load.is_some_and(|load| load >= settings.cpu_load))
+}
+
+/// Whether shedding can end: the queue has drained, the load has dropped,
+/// and it has lasted long enough.
+fn recovered(settings: &LoadShedding, load: Option<f32>, depth: usize, since: Instant) -> bool {
+    since.elapsed() >= MIN_SHED_DURATION
+        && depth == 0
+        && load.is_none_or(|load| load < settings.cpu_load - LOAD_HYSTERESIS)
+}
+
+fn start(settings: &LoadShedding) {
+    SHEDDING.store(true, Ordering::SeqCst);
+    SPLIT_MS.store(
+        u64::from(settings.min_segment_ms.min(MAX_SPLIT_MS)),
+        Ordering::Relaxed,
+    );
+}
+
+fn stop() {
+    SHEDDING.store(false, Ordering::SeqCst);
+    SPLIT_MS.store(0, Ordering::Relaxed);
+}
+
+fn broadcast(active: bool, reason: String) {
+    broadcast_event(Response::Event {
+        event: EventType::LoadSheddingChanged { active, reason },
+    });
+}
+
+fn describe(load: Option<f32>, depth: usize) -> String {
+    let queue = match depth {
+        0 => "queue empty".to_string(),
+        1 => "1 segment waiting".to_string(),
+        n => format!("{} segments waiting", n),
+    };
+    match load {
+        Some(load) => format!("CPU load {:.0}%, {}", load * 100.0, queue),
+        None => queue,
+    }
+}
+
+/// CPU load as a fraction of all cores.
+#[derive(Default)]
+struct CpuMonitor {
+    /// Idle and total time at the previous reading
+    #[cfg(target_os = "windows")]
+    last: Option<(u64, u64)>,
+}
+
+impl CpuMonitor {
+    /// One-minute load average over the number of cores.
+    #[cfg(unix)]
+    fn load(&mut self) -> Option<f32> {
+        let mut average = [0f64; 1];
+        if unsafe { libc::getloadavg(average.as_mut_ptr(), 1) } < 1 {
+            return None;
+        }
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Some((average[0] / cores as f64) as f32)
+    }
+
+    /// Share of time busy since the previous reading.
+    #[cfg(target_os = "windows")]
+    fn load(&mut self) -> Option<f32> {
+        use windows::Win32::Foundation::FILETIME;
+        use windows::Win32::System::Threading::GetSystemTimes;
+
+        let mut idle = FILETIME::default();
+        let mut kernel = FILETIME::default();
+        let mut user = FILETIME::default();
+        unsafe { GetSystemTimes(Some(&mut idle), Some(&mut kernel), Some(&mut user)) }.ok()?;
+        let ticks = |t: FILETIME| (u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime);
+        // Kernel time includes idle time
+        let (idle, total) = (ticks(idle), ticks(kernel) + ticks(user));
+
+        let (last_idle, last_total) = self.last.replace((idle, total))?;
+        let elapsed = total.saturating_sub(last_total);
+        if elapsed == 0 {
+            return None;
+        }
+        Some(1.0 - idle.saturating_sub(last_idle) as f32 / elapsed as f32)
+    }
+}
@@ -0,0 +1,191 @@
+//! Meeting mode: continuous dual-stream transcription into one timeline.
+//!
+//! While a meeting runs, capture uses `RecordingMode::Split` so the primary
+//! source (the microphone) and the secondary source (system audio from the
+//! call) stay on separate channels. Each speech segment is transcribed once
+//! per source and the results are interleaved by their offset from the start
+//! of the meeting. Finished meetings are saved to `meetings.json` next to the
+//! history file, encrypted like it when `encrypt_history` is on. Meetings
+//! started in privacy mode are never saved.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use flowstt_common::{MeetingLine, MeetingRecord, MeetingSource, RecordingMode};
+use tracing::{info, warn};
+
+use crate::history::TranscriptionHistory;
+use crate::history_crypto;
+
+/// A meeting segment from one source, queued for transcription.
+#[derive(Debug, Clone)]
+pub struct MeetingSegment {
+    /// Meeting the segment belongs to
+    pub meeting_id: String,
+    /// Source the segment was captured from
+    pub source: MeetingSource,
+    /// When the segment started, in milliseconds from the start of the meeting
+    pub offset_ms: u64,
+}
+
+/// The meeting in progress.
+struct ActiveMeeting {
+    record: MeetingRecord,
+    started: Instant,
+    /// Recording mode to restore when the meeting stops
+    previous_mode: RecordingMode,
+    /// Started in privacy mode: never written to disk
+    ephemeral: bool,
+}
+
+static ACTIVE: Mutex<Option<ActiveMeeting>> = Mutex::new(None);
+
+/// Whether a meeting is in progress.
+pub fn is_active() -> bool {
+    ACTIVE.lock().unwrap().is_some()
+}
+
+/// Start a meeting. `previous_mode` is restored by [`stop`].
+pub fn start(title: Option<String>, previous_mode: RecordingMode) -> Result<MeetingRecord, String> {
+    let mut active = ACTIVE.lock().unwrap();
+    if active.is_some() {
+        return Err("A meeting is already in progress".into());
+    }
+
+    let record = MeetingRecord {
+        id: crate::history::generate_id(),
+        title: title.filter(|t| !t.trim().is_empty()),
+        started_at: Utc::now().to_rfc3339(),
+        ended_at: None,
+        lines: Vec::new(),
+    };
+    info!("[Meeting] Started {}", record.id);
+    *active = Some(ActiveMeeting {
+        record: record.clone(),
+        started: Instant::now(),
+        previous_mode,
+        ephemeral: crate::privacy::is_privacy_mode(),
+    });
+    Ok(record)
+}
+
+/// Stop the meeting in progress and save it. Returns the finished record and
+/// the recording mode to restore, or `None` if no meeting was running.
+pub fn stop() -> Option<(MeetingRecord, RecordingMode)> {
+    // Held while saving so a late line can't read the file before it exists
+    let mut active = ACTIVE.lock().unwrap();
+    let mut meeting = active.take()?;
+    meeting.record.ended_at = Some(Utc::now().to_rfc3339());
+    info!(
+        "[Meeting] Ended {} with {} lines",
+        meeting.record.id,
+        meeting.record.lines.len()
+    );
+
+    if !meeting.ephemeral {
+        let mut meetings = load_all();
+        meetings.push(meeting.record.clone());
+        if let Err(e) = save_all(&meetings) {
+            warn!("[Meeting] Failed to save meeting: {}", e);
+        }
+    }
+    Some((meeting.record, meeting.previous_mode))
+}
+
+/// Describe a segment of `duration` that just ended for each source, or
+/// `None` if no meeting is running.
+pub fn segments_ending_now(duration: Duration) -> Option<[MeetingSegment; 2]> {
+    let active = ACTIVE.lock().unwrap();
+    let meeting = active.as_ref()?;
+    let offset_ms = meeting
+        .started
+        .elapsed()
+        .saturating_sub(duration)
+        .as_millis() as u64;
+    Some(
+        [MeetingSource::Microphone, MeetingSource::System].map(|source| MeetingSegment {
+            meeting_id: meeting.record.id.clone(),
+            source,
+            offset_ms,
+        }),
+    )
+}
+
+/// Add a transcribed line to its meeting, keeping the timeline ordered.
+///
+/// Transcription can finish after the meeting stops, in which case the
+/// saved record is updated. Returns `None` if the meeting no longer exists.
+pub fn add_line(segment: MeetingSegment, text: String) -> Option<MeetingLine> {
+    let line = MeetingLine {
+        source: segment.source,
+        offset_ms: segment.offset_ms,
+        text,
+    };
+
+    let mut active = ACTIVE.lock().unwrap();
+    if let Some(meeting) = active
+        .as_mut()
+        .filter(|m| m.record.id == segment.meeting_id)
+    {
+        insert_line(&mut meeting.record, line.clone());
+        return Some(line);
+    }
+
+    let mut meetings = load_all();
+    let record = meetings.iter_mut().find(|m| m.id == segment.meeting_id)?;
+    insert_line(record, line.clone());
+    if let Err(e) = save_all(&meetings) {
+        warn!("[Meeting] Failed to save late line: {}", e);
+    }
+    Some(line)
+}
+
+/// Insert after any lines at the same or an earlier offset.
+fn insert_line(record: &mut MeetingRecord, line: MeetingLine) {
+    let index = record
+        .lines
+        .partition_point(|l| l.offset_ms <= line.offset_ms);
+    record.lines.insert(index, line);
+}
+
+/// Saved meetings followed by the meeting in progress, if any.
+pub fn list() -> Vec<MeetingRecord> {
+    let mut meetings = load_all();
+    if let Some(meeting) = ACTIVE.lock().unwrap().as_ref() {
+        meetings.push(meeting.record.clone());
+    }
+    meetings
+}
+
+fn meetings_path() -> PathBuf {
+    TranscriptionHistory::data_dir().join("meetings.json")
+}
+
+/// Load saved meetings. A missing or unreadable file yields none; an
+/// unreadable one is moved aside first.
+fn load_all() -> Vec<MeetingRecord> {
+    let path = meetings_path();
+    if !path.exists() {
+        return Vec::new();
+    }
+    match history_crypto::read_file(&path)
+        .and_then(|content| serde_json::from_slice(&content).map_err(|e| e.to_string()))
+    {
+        Ok(meetings) => meetings,
+        Err(e) => {
+            warn!("[Meeting] Failed to read {:?}: {}", path, e);
+            // Keep the unreadable file rather than overwriting it on the next save
+            history_crypto::move_aside(&path);
+            Vec::new()
+        }
+    }
+}
+
+fn save_all(meetings: &[MeetingRecord]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(meetings)
+        .map_err(|e| format!("Failed to serialize meetings: {}", e))?;
+    let encrypted = crate::config::current_config().encrypt_history;
+    history_crypto::write_file(&meetings_path(), content.as_bytes(), encrypted)
+}
@@ -0,0 +1,409 @@
+//! Output sinks: where a finished transcription goes.
+//!
+//! Each destination (history, the transcript file, the caption file, the
+//! activity log and the clipboard) is an [`OutputSink`] in one registry. The
+//! `output_sinks` list in the config file sets the order transcriptions are
+//! delivered in and turns sinks on or off; registered sinks it leaves out are
+//! delivered to last.
+//!
+//! Every sink but the clipboard runs on a worker thread of its own, so one
+//! that is slow or stuck (saving or encrypting history, a sync folder on a
+//! network share, a pipe nobody reads) can't hold up the paste. A sink that
+//! fails or panics is logged and the others carry on.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::thread;
+
+use chrono::{DateTime, Local};
+use tracing::{debug, warn};
+
+use crate::config::Config;
+use crate::history::{get_history, HistoryEntry};
+use crate::output_template::{self, TemplateValues};
+
+/// Deliveries a background sink may fall behind by before new ones are dropped
+const MAX_PENDING: usize = 32;
+
+/// A finished transcription to deliver.
+pub struct Delivery {
+    /// The history entry, with the text redacted for history
+    pub entry: HistoryEntry,
+    /// Text to copy and paste, redacted for the clipboard
+    pub clipboard_text: String,
+    /// When the transcription completed
+    pub completed_at: DateTime<Local>,
+    /// Config when the transcription completed, so every sink sees the same
+    pub config: Config,
+}
+
+/// A destination for finished transcriptions.
+pub trait OutputSink: Send + Sync {
+    /// Name used in the `output_sinks` config and in logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether the sink runs on a worker thread of its own, for sinks whose
+    /// I/O could be slow or hang.
+    fn background(&self) -> bool {
+        false
+    }
+
+    /// Deliver a transcription.
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String>;
+}
+
+struct Registered {
+    sink: Arc<dyn OutputSink>,
+    /// Queue of the worker thread, for background sinks
+    worker: Option<SyncSender<Arc<Delivery>>>,
+}
+
+static REGISTRY: OnceLock<RwLock<Vec<Registered>>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<Vec<Registered>> {
+    REGISTRY.get_or_init(|| {
+        let builtin: [Arc<dyn OutputSink>; 5] = [
+            Arc::new(HistorySink),
+            Arc::new(TranscriptFileSink),
+            Arc::new(CaptionFileSink),
+            Arc::new(ActivityLogSink),
+            Arc::new(ClipboardSink),
+        ];
+        RwLock::new(builtin.into_iter().map(registered).collect())
+    })
+}
+
+fn registered(sink: Arc<dyn OutputSink>) -> Registered {
+    let worker = sink.background().then(|| spawn_worker(sink.clone()));
+    Registered { sink, worker }
+}
+
+/// Add a sink, replacing any registered under the same name.
+pub fn register(sink: Arc<dyn OutputSink>) {
+    let mut sinks = registry().write().unwrap();
+    sinks.retain(|r| r.sink.name() != sink.name());
+    sinks.push(registered(sink));
+}
+
+/// Deliver a transcription to the enabled sinks in the configured order.
+pub fn dispatch(delivery: Delivery) {
+    let delivery = Arc::new(delivery);
+    let sinks = registry().read().unwrap();
+    for registered in ordered(&sinks, &delivery.config) {
+        let name = registered.sink.name();
+        match &registered.worker {
+            Some(worker) => match worker.try_send(delivery.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    warn!(
+                        "[OutputSink] {} is falling behind, transcription dropped",
+                        name
+                    )
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!("[OutputSink] {} worker has stopped", name)
+                }
+            },
+            None => deliver(registered.sink.as_ref(), &delivery),
+        }
+    }
+}
+
+/// Enabled sinks in the configured order, followed by those the config
+/// leaves out.
+fn ordered<'a>(sinks: &'a [Registered], config: &Config) -> Vec<&'a Registered> {
+    let mut ordered: Vec<&Registered> = Vec::new();
+    for settings in &config.output_sinks {
+        match sinks.iter().find(|r| r.sink.name() == settings.name) {
+            Some(r) if settings.enabled && !ordered.iter().any(|o| std::ptr::eq(*o, r)) => {
+                ordered.push(r)
+            }
+            Some(_) => {}
+            None => debug!("[OutputSink] No sink named {:?}", settings.name),
+        }
+    }
+    ordered.extend(sinks.iter().filter(|r| {
+        !config
+            .output_sinks
+            .iter()
+            .any(|settings| settings.name == r.sink.name())
+    }));
+    ordered
+}
+
+/// Deliver to one sink, containing its failures.
+fn deliver(sink: &dyn OutputSink, delivery: &Delivery) {
+    match catch_unwind(AssertUnwindSafe(|| sink.deliver(delivery))) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("[OutputSink] {} failed: {}", sink.name(), e),
+        Err(_) => warn!("[OutputSink] {} panicked", sink.name()),
+    }
+}
+
+fn spawn_worker(sink: Arc<dyn OutputSink>) -> SyncSender<Arc<Delivery>> {
+    let (tx, rx) = sync_channel::<Arc<Delivery>>(MAX_PENDING);
+    let name = sink.name();
+    let spawned = thread::Builder::new()
+        .name(format!("sink-{}", name))
+        .spawn(move || {
+            for delivery in rx {
+                deliver(sink.as_ref(), &delivery);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("[OutputSink] Failed to start {} worker: {}", name, e);
+    }
+    tx
+}
+
+/// Keeps the transcription in history.
+struct HistorySink;
+
+impl OutputSink for HistorySink {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn background(&self) -> bool {
+        true
+    }
+
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String> {
+        get_history()
+            .lock()
+            .map_err(|_| "History is unavailable".to_string())?
+            .add_entry(delivery.entry.clone());
+        Ok(())
+    }
+}
+
+/// Appends the transcription to the `transcript_sink` file or named pipe.
+struct TranscriptFileSink;
+
+impl OutputSink for TranscriptFileSink {
+    fn name(&self) -> &'static str {
+        "transcript_file"
+    }
+
+    fn background(&self) -> bool {
+        true
+    }
+
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String> {
+        let config = &delivery.config;
+        let Some(path) = config.transcript_sink.as_deref() else {
+            return Ok(());
+        };
+        if delivery.entry.ephemeral {
+            return Ok(());
+        }
+        // History may keep the raw text; the file never does
+        let text = crate::profanity::filter(&config.profanity, &delivery.entry.text);
+        let line = output_template::render(
+            &config.output_templates.transcript_sink,
+            &TemplateValues {
+                text: text.trim(),
+                completed_at: delivery.completed_at,
+            },
+        );
+        crate::transcript_sink::append(path, &line)
+    }
+}
+
+/// Shows the transcription in the `caption_file`.
+struct CaptionFileSink;
+
+impl OutputSink for CaptionFileSink {
+    fn name(&self) -> &'static str {
+        "caption_file"
+    }
+
+    fn background(&self) -> bool {
+        true
+    }
+
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String> {
+        if !delivery.entry.ephemeral {
+            let config = &delivery.config;
+            let text = crate::profanity::filter(&config.profanity, &delivery.entry.text);
+            crate::caption_file::add(&config.caption_file, &text);
+        }
+        Ok(())
+    }
+}
+
+/// Counts the transcription's words in the activity log.
+struct ActivityLogSink;
+
+impl OutputSink for ActivityLogSink {
+    fn name(&self) -> &'static str {
+        "activity_log"
+    }
+
+    fn background(&self) -> bool {
+        true
+    }
+
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String> {
+        if !delivery.entry.ephemeral {
+            crate::activity_log::transcribed(&delivery.entry.text);
+        }
+        Ok(())
+    }
+}
+
+/// Copies the transcription to the clipboard and pastes it, or pastes it into
+/// the dictation target.
+struct ClipboardSink;
+
+impl OutputSink for ClipboardSink {
+    fn name(&self) -> &'static str {
+        "clipboard"
+    }
+
+    fn deliver(&self, delivery: &Delivery) -> Result<(), String> {
+        let text = delivery.clipboard_text.clone();
+        let completed_at = delivery.completed_at;
+        let config = delivery.config.clone();
+        // Pasting waits for the paste delay, so it runs on the clipboard queue
+        crate::clipboard::enqueue(config.auto_paste_delay_ms, move || {
+            paste(&text, completed_at, &config)
+        });
+        Ok(())
+    }
+}
+
+fn paste(text: &str, completed_at: DateTime<Local>, config: &Config) {
+    // Applications matched by a transform rule get the text reshaped
+    let pasted = text.trim_end();
+    let pasted = match crate::process_watch::output_transform() {
+        Some(transform) => crate::output_transform::apply(transform, pasted),
+        None => pasted.to_string(),
+    };
+
+    // Text typed as it was spoken is corrected in place instead
+    if crate::partial_typing::finish(&pasted, config) {
+        return;
+    }
+
+    // A dictation session pastes into its own target window instead
+    if crate::dictation::paste_segment(&pasted, config.auto_paste_delay_ms) {
+        return;
+    }
+
+    // Copy to clipboard and optionally paste into the foreground app.
+    let clipboard = output_template::render(
+        &config.output_templates.clipboard,
+        &TemplateValues {
+            text: &pasted,
+            completed_at,
+        },
+    );
+    // Pasted segments are joined to the one pasted before them
+    let clipboard = if config.auto_paste_enabled {
+        crate::joining::next_paste(
+            config.segment_joining,
+            &clipboard,
+            crate::clipboard::foreground_window(),
+        )
+    } else {
+        clipboard
+    };
+    crate::clipboard::copy_and_paste(
+        &clipboard,
+        config.auto_paste_enabled,
+        config.auto_paste_delay_ms,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::config::OutputSinkSettings;
+
+    struct Named(&'static str);
+
+    impl OutputSink for Named {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn deliver(&self, _delivery: &Delivery) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn sinks(names: &[&'static str]) -> Vec<Registered> {
+        names
+            .iter()
+            .map(|&name| Registered {
+                sink: Arc::new(Named(name)),
+                worker: None,
+            })
+            .collect()
+    }
+
+    fn config(settings: &[(&str, bool)]) -> Config {
+        Config {
+            output_sinks: settings
+                .iter()
+                .map(|&(name, enabled)| OutputSinkSettings {
+                    name: name.to_string(),
+                    enabled,
+                })
+                .collect(),
+            ..Config::default()
+        }
+    }
+
+    fn names(ordered: Vec<&Registered>) -> Vec<&'static str> {
+        ordered.iter().map(|r| r.sink.name()).collect()
+    }
+
+    #[test]
+    fn sinks_follow_the_configured_order() {
+        let sinks = sinks(&["history", "caption_file", "clipboard"]);
+        let config = config(&[
+            ("clipboard", true),
+            ("history", true),
+            ("caption_file", true),
+        ]);
+
+        assert_eq!(
+            names(ordered(&sinks, &config)),
+            ["clipboard", "history", "caption_file"]
+        );
+    }
+
+    #[test]
+    fn disabled_sinks_are_skipped() {
+        let sinks = sinks(&["history", "clipboard"]);
+        let config = config(&[("history", false), ("clipboard", true)]);
+
+        assert_eq!(names(ordered(&sinks, &config)), ["clipboard"]);
+    }
+
+    #[test]
+    fn sinks_listed_twice_are_delivered_once() {
+        let sinks = sinks(&["history", "clipboard"]);
+        let config = config(&[("clipboard", true), ("history", true), ("clipboard", true)]);
+
+        assert_eq!(names(ordered(&sinks, &config)), ["clipboard", "history"]);
+    }
+
+    #[test]
+    fn unlisted_sinks_go_last_and_unknown_names_are_ignored() {
+        let sinks = sinks(&["history", "webhook", "clipboard"]);
+        let config = config(&[
+            ("clipboard", true),
+            ("teleprinter", true),
+            ("history", true),
+        ]);
+
+        assert_eq!(
+            names(ordered(&sinks, &config)),
+            ["clipboard", "history", "webhook"]
+        );
+    }
+}
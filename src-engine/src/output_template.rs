@@ -0,0 +1,93 @@
+//! Output templates for transcribed text.
+//!
+//! Each output sink formats a transcription with a template from the
+//! `output_templates` section of the config file, such as `[{time}] {text}`.
+//! Variables are written in braces; `{{` and `}}` produce literal braces and
+//! unknown variables are copied through unchanged so a typo stays visible.
+
+use chrono::{DateTime, Local};
+
+/// Values available to a template.
+pub struct TemplateValues<'a> {
+    /// Transcribed text after replacements and redaction
+    pub text: &'a str,
+    /// When the transcription completed
+    pub completed_at: DateTime<Local>,
+}
+
+impl TemplateValues<'_> {
+    fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "text" => Some(self.text.to_string()),
+            "time" => Some(self.completed_at.format("%H:%M:%S").to_string()),
+            "date" => Some(self.completed_at.format("%Y-%m-%d").to_string()),
+            "timestamp" => Some(self.completed_at.to_rfc3339()),
+            _ => None,
+        }
+    }
+}
+
+/// Fill in the variables of `template`.
+pub fn render(template: &str, values: &TemplateValues) -> String {
+    let mut output = String::with_capacity(template.len() + values.text.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            output.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        let value = rest
+            .strip_prefix('{')
+            .and_then(|after| after.find('}').map(|end| (end, &after[..end])))
+            .and_then(|(end, name)| values.get(name.trim()).map(|value| (end, value)));
+        match value {
+            Some((end, value)) => {
+                output.push_str(&value);
+                rest = &rest[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn values(text: &str) -> TemplateValues<'_> {
+        TemplateValues {
+            text,
+            completed_at: Local.with_ymd_and_hms(2024, 5, 1, 14, 5, 9).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_variables() {
+        assert_eq!(
+            render("[{date} {time}] {text}", &values("hello")),
+            "[2024-05-01 14:05:09] hello"
+        );
+        assert_eq!(render("> {text}\n", &values("quoted")), "> quoted\n");
+    }
+
+    #[test]
+    fn test_render_escapes_and_unknown_variables() {
+        assert_eq!(
+            render("{{text}} {confidence} {text", &values("hi")),
+            "{text} {confidence} {text"
+        );
+        assert_eq!(render("}} {text}}", &values("a")), "} a}");
+    }
+}
@@ -0,0 +1,77 @@
+//! Output transforms for transcribed text.
+//!
+//! A process rule with a `transform` action changes how text is pasted into
+//! the matching application, e.g. joining words in snake_case for a code
+//! editor. The transform applies to the transcript before the clipboard
+//! template; history keeps the text as spoken.
+
+use flowstt_common::config::OutputTransform;
+
+/// Apply `transform` to `text`.
+pub fn apply(transform: OutputTransform, text: &str) -> String {
+    match transform {
+        OutputTransform::Lowercase => text.to_lowercase(),
+        OutputTransform::SnakeCase => words(text)
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        OutputTransform::CamelCase => words(text)
+            .enumerate()
+            .map(|(i, word)| {
+                let word = word.to_lowercase();
+                if i == 0 {
+                    return word;
+                }
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => word,
+                }
+            })
+            .collect(),
+        OutputTransform::StripPunctuation => text
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// The words of `text`, without punctuation; apostrophes are dropped so
+/// "don't" stays one word.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .map(|word| word.replace(['\'', '’'], ""))
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_transforms() {
+        let text = "Get the user's Name, please.";
+        assert_eq!(
+            apply(OutputTransform::Lowercase, text),
+            "get the user's name, please."
+        );
+        assert_eq!(
+            apply(OutputTransform::SnakeCase, text),
+            "get_the_users_name_please"
+        );
+        assert_eq!(
+            apply(OutputTransform::CamelCase, text),
+            "getTheUsersNamePlease"
+        );
+        assert_eq!(
+            apply(OutputTransform::StripPunctuation, text),
+            "Get the users Name please"
+        );
+    }
+}
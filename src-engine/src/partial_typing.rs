@@ -0,0 +1,229 @@
+//! Typing as you speak.
+//!
+//! While `partial_typing` is on, the live segment in progress is transcribed
+//! every `interval_ms` of speech, whenever the transcription queue is idle,
+//! and the interim result is typed into the foreground application: the
+//! characters that changed since the last interim are deleted with Backspace
+//! and the new ending is pasted. When the segment's final transcription
+//! arrives, the typed text is corrected the same way instead of the segment
+//! being pasted again, and a segment that turns out to hold no speech has its
+//! typed text deleted.
+//!
+//! Nothing is typed during dictation sessions, meetings or spelling mode,
+//! while output is paused or FlowSTT is in the foreground, or into
+//! applications matched by a `no_partial_typing` process rule; the segment is
+//! pasted when it ends as usual. If another window comes to the front
+//! mid-segment, the text typed so far is left alone and the final
+//! transcription is pasted. Typed text skips the clipboard output template.
+//!
+//! Interims and finals of live segments are transcribed in order, so all
+//! typing runs on the clipboard queue (see [`crate::clipboard::enqueue`])
+//! in order with pastes, and the typed text always belongs to the segment
+//! whose final transcription is pasted next.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tracing::{debug, info, warn};
+
+use crate::clipboard;
+use crate::config::Config;
+
+/// Live segment in progress, counted up each time one is cut
+static SEGMENT: AtomicU64 = AtomicU64::new(1);
+
+/// Last segment an interim was queued for (0 = none yet)
+static INTERIM_QUEUED: AtomicU64 = AtomicU64::new(0);
+
+/// Segment typing gave up on after a keystroke failed (0 = none)
+static ABANDONED: AtomicU64 = AtomicU64::new(0);
+
+/// Text typed for the segment in progress
+static TYPED: Mutex<Option<Typed>> = Mutex::new(None);
+
+/// Text typed into a window for a live segment.
+struct Typed {
+    segment: u64,
+    /// Window it was typed into, if known
+    window: Option<u64>,
+    /// The text as typed
+    text: String,
+}
+
+/// A snapshot of the live segment in progress, queued for an interim
+/// transcription.
+#[derive(Debug, Clone, Copy)]
+pub struct InterimSegment {
+    segment: u64,
+}
+
+/// Mark an interim transcription of the segment in progress as queued.
+pub fn interim_segment() -> InterimSegment {
+    let segment = SEGMENT.load(Ordering::SeqCst);
+    INTERIM_QUEUED.store(segment, Ordering::SeqCst);
+    InterimSegment { segment }
+}
+
+/// Note that the live segment in progress was cut. Interims of it that are
+/// still queued are ignored from now on; if the segment wasn't `queued` for
+/// transcription, what was typed of it is deleted.
+pub fn segment_ended(queued: bool) {
+    let segment = SEGMENT.fetch_add(1, Ordering::SeqCst);
+    if !queued && INTERIM_QUEUED.load(Ordering::SeqCst) == segment {
+        clipboard::enqueue(0, move || erase(Some(segment)));
+    }
+}
+
+/// Delete the text typed for a segment whose final transcription is empty
+/// or failed.
+pub fn discard() {
+    if INTERIM_QUEUED.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+    clipboard::enqueue(0, || erase(None));
+}
+
+/// Type the interim transcription of a segment, replacing the one typed
+/// before it.
+pub fn type_interim(interim: InterimSegment, text: String, config: Config) {
+    clipboard::enqueue(config.auto_paste_delay_ms, move || {
+        let segment = interim.segment;
+        if segment != SEGMENT.load(Ordering::SeqCst) || segment == ABANDONED.load(Ordering::SeqCst)
+        {
+            debug!("[PartialTyping] Segment has ended, skipping interim");
+            return;
+        }
+        if !can_type(&config) {
+            return;
+        }
+
+        let window = clipboard::foreground_window();
+        let mut typed = TYPED.lock().unwrap();
+        let previous = match typed.take() {
+            Some(previous) if previous.segment == segment && previous.window != window => {
+                debug!("[PartialTyping] Focus has moved, no longer typing");
+                *typed = Some(previous);
+                return;
+            }
+            Some(previous) if previous.segment == segment => previous.text,
+            _ => String::new(),
+        };
+
+        let text = match crate::process_watch::output_transform() {
+            Some(transform) => crate::output_transform::apply(transform, &text),
+            None => text,
+        };
+        let text = crate::joining::preview(config.segment_joining, &text, window);
+        match retype(&previous, &text, config.auto_paste_delay_ms) {
+            Ok(()) => {
+                *typed = Some(Typed {
+                    segment,
+                    window,
+                    text,
+                })
+            }
+            Err(e) => {
+                warn!("[PartialTyping] Failed to type interim text: {}", e);
+                ABANDONED.store(segment, Ordering::SeqCst);
+            }
+        }
+    });
+}
+
+/// Correct the text typed for the segment that just ended to its final
+/// transcription, `text`. Returns `false` if nothing was typed or the
+/// window it was typed into has lost focus, so the caller pastes as usual.
+///
+/// Runs on the clipboard queue.
+pub fn finish(text: &str, config: &Config) -> bool {
+    let Some(typed) = TYPED.lock().unwrap().take() else {
+        return false;
+    };
+    let window = clipboard::foreground_window();
+    if typed.window != window {
+        info!("[PartialTyping] Focus has moved, pasting the segment instead");
+        return false;
+    }
+
+    let text = crate::joining::next_paste(config.segment_joining, text, window);
+    if let Err(e) = retype(&typed.text, &text, config.auto_paste_delay_ms) {
+        // The typed text is in an unknown state; leave the whole
+        // transcription on the clipboard rather than paste it again
+        warn!("[PartialTyping] Failed to correct typed text: {}", e);
+        if let Err(e) = clipboard::copy_text(&text) {
+            warn!("[PartialTyping] Failed to write clipboard: {}", e);
+        }
+    }
+    true
+}
+
+/// Delete the typed text, if it belongs to `segment` (any segment for
+/// `None`).
+fn erase(segment: Option<u64>) {
+    let mut typed = TYPED.lock().unwrap();
+    let Some(previous) = typed.take_if(|t| segment.is_none_or(|s| t.segment == s)) else {
+        return;
+    };
+    if clipboard::foreground_window() != previous.window {
+        debug!("[PartialTyping] Focus has moved, leaving typed text");
+        return;
+    }
+    debug!("[PartialTyping] Deleting text typed for an empty segment");
+    if let Err(e) = clipboard::backspace(previous.text.chars().count()) {
+        warn!("[PartialTyping] Failed to delete typed text: {}", e);
+    }
+}
+
+/// Whether interims may be typed into the foreground application now.
+fn can_type(config: &Config) -> bool {
+    config.partial_typing.enabled
+        && config.auto_paste_enabled
+        && !crate::dictation::is_active()
+        && !crate::spelling::is_active()
+        && !crate::pause_words::is_paused()
+        && !clipboard::is_flowstt_foreground()
+        && !crate::process_watch::partial_typing_blocked()
+}
+
+/// Change typed text `from` into `to` by deleting back to where they
+/// differ and pasting the rest.
+fn retype(from: &str, to: &str, delay_ms: u32) -> Result<(), String> {
+    let (delete, insert) = edit(from, to);
+    clipboard::backspace(delete)?;
+    if !insert.is_empty() {
+        clipboard::paste_into(insert, None, delay_ms)?;
+    }
+    Ok(())
+}
+
+/// The number of characters to delete from the end of `from`, and the text
+/// to add after them, that turn it into `to`.
+fn edit<'a>(from: &str, to: &'a str) -> (usize, &'a str) {
+    let common = from
+        .chars()
+        .zip(to.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let start = to.char_indices().nth(common).map_or(to.len(), |(i, _)| i);
+    (from.chars().count() - common, &to[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_keeps_the_common_prefix() {
+        assert_eq!(edit("", "Hello"), (0, "Hello"));
+        assert_eq!(edit("Hello wor", "Hello world"), (0, "ld"));
+        assert_eq!(edit("Hello word", "Hello world."), (1, "ld."));
+        assert_eq!(edit("I scream", "Ice cream"), (7, "ce cream"));
+    }
+
+    #[test]
+    fn edit_counts_characters_not_bytes() {
+        assert_eq!(edit("café au", "café olé"), (2, "olé"));
+        assert_eq!(edit("naïve", "naïve"), (0, ""));
+        assert_eq!(edit("über", ""), (4, ""));
+    }
+}
@@ -0,0 +1,222 @@
+//! Pause words.
+//!
+//! Saying "stop dictation" pauses output: transcriptions are held back from
+//! history, the clipboard and the other output sinks until "resume
+//! dictation" is said. Audio is still captured and transcribed while output
+//! is paused, which is how the resume phrase is heard. The phrases can be
+//! said anywhere in a segment; the words before a pause phrase and after a
+//! resume phrase are kept.
+//!
+//! Phrases come from the table for the `pause_words` locale, or from the
+//! config file. The paused state is not persisted.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use flowstt_common::config::PauseWords;
+use flowstt_common::ipc::{EventType, Response};
+use tracing::{info, warn};
+
+use crate::ipc::broadcast_event;
+
+/// Pause and resume phrases in one language.
+struct Locale {
+    code: &'static str,
+    pause: &'static [&'static str],
+    resume: &'static [&'static str],
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        pause: &["stop dictation", "pause dictation"],
+        resume: &["resume dictation", "start dictation"],
+    },
+    Locale {
+        code: "de",
+        pause: &["diktat anhalten", "diktat pausieren"],
+        resume: &["diktat fortsetzen"],
+    },
+    Locale {
+        code: "fr",
+        pause: &["arrêter la dictée", "pause dictée"],
+        resume: &["reprendre la dictée"],
+    },
+    Locale {
+        code: "es",
+        pause: &["detener dictado", "pausar dictado"],
+        resume: &["reanudar dictado"],
+    },
+];
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Check whether output is paused.
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+/// Pause or resume output and notify clients if that changed.
+pub fn set_paused(paused: bool) {
+    if PAUSED.swap(paused, Ordering::SeqCst) == paused {
+        return;
+    }
+
+    info!("Output {}", if paused { "paused" } else { "resumed" });
+    broadcast_event(Response::Event {
+        event: EventType::OutputPausedChanged { paused },
+    });
+}
+
+/// Follow the pause and resume phrases in `text` and return the words
+/// spoken while output wasn't paused, leaving output paused or not as the
+/// last phrase set it.
+pub fn apply(settings: &PauseWords, text: &str) -> String {
+    if !settings.enabled {
+        // Nothing could resume output any more
+        set_paused(false);
+        return text.to_string();
+    }
+    let (pause, resume) = phrases(settings);
+    let (kept, paused) = follow(&pause, &resume, is_paused(), text);
+    set_paused(paused);
+    kept
+}
+
+/// The pause and resume phrases, each as the words to match.
+fn phrases(settings: &PauseWords) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+    let code = settings.locale.trim();
+    let locale = LOCALES.iter().find(|l| l.code.eq_ignore_ascii_case(code));
+    if locale.is_none() {
+        warn!("[PauseWords] No phrases for locale {:?}", code);
+    }
+    let split = |phrases: &[&str]| -> Vec<Vec<String>> {
+        phrases
+            .iter()
+            .map(|phrase| phrase.split_whitespace().map(match_key).collect())
+            .filter(|words: &Vec<String>| !words.is_empty())
+            .collect()
+    };
+    let pick = |custom: &[String], builtin: Option<&[&str]>| {
+        if custom.is_empty() {
+            split(builtin.unwrap_or_default())
+        } else {
+            split(&custom.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+    };
+    (
+        pick(&settings.pause_phrases, locale.map(|l| l.pause)),
+        pick(&settings.resume_phrases, locale.map(|l| l.resume)),
+    )
+}
+
+/// Walk `text` from the `paused` state. Returns the text spoken while not
+/// paused and whether output is paused at the end.
+fn follow(
+    pause: &[Vec<String>],
+    resume: &[Vec<String>],
+    mut paused: bool,
+    text: &str,
+) -> (String, bool) {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|word| match_key(word)).collect();
+    let starts = |phrases: &[Vec<String>], i: usize| {
+        phrases
+            .iter()
+            .find(|phrase| keys[i..].starts_with(phrase))
+            .map(Vec::len)
+    };
+
+    let mut kept: Vec<&str> = Vec::new();
+    let mut found = false;
+    let mut i = 0;
+    while i < words.len() {
+        let phrase = match (starts(pause, i), starts(resume, i)) {
+            (Some(len), _) => Some((len, true)),
+            (None, Some(len)) => Some((len, false)),
+            (None, None) => None,
+        };
+        match phrase {
+            Some((len, pauses)) => {
+                paused = pauses;
+                found = true;
+                i += len;
+            }
+            None => {
+                if !paused {
+                    kept.push(words[i]);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    if !found && !paused {
+        // Leave text without phrases exactly as transcribed
+        return (text.to_string(), false);
+    }
+    (kept.join(" "), paused)
+}
+
+/// A word as compared with the phrases: lowercase, without surrounding
+/// punctuation and with typographic apostrophes straightened.
+fn match_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .replace('’', "'")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+        phrases(&PauseWords::default())
+    }
+
+    #[test]
+    fn words_between_pause_and_resume_are_dropped() {
+        let (pause, resume) = english();
+        assert_eq!(
+            follow(&pause, &resume, false, "Send it. Stop dictation. Hi Sam"),
+            ("Send it.".to_string(), true)
+        );
+        assert_eq!(
+            follow(&pause, &resume, true, "oops. Resume dictation, Sam"),
+            ("Sam".to_string(), false)
+        );
+        assert_eq!(
+            follow(&pause, &resume, true, "Still talking"),
+            (String::new(), true)
+        );
+    }
+
+    #[test]
+    fn text_without_phrases_is_unchanged() {
+        let (pause, resume) = english();
+        assert_eq!(
+            follow(&pause, &resume, false, "Stop  the dictation "),
+            ("Stop  the dictation ".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn custom_phrases_replace_the_locale() {
+        let settings = PauseWords {
+            pause_phrases: vec!["Go quiet".to_string()],
+            ..PauseWords::default()
+        };
+        let (pause, resume) = phrases(&settings);
+        assert_eq!(pause, vec![vec!["go".to_string(), "quiet".to_string()]]);
+        assert_eq!(resume.len(), 2);
+
+        let (pause, _) = phrases(&PauseWords {
+            locale: "fr".to_string(),
+            ..PauseWords::default()
+        });
+        assert!(pause.contains(&vec![
+            "arrêter".to_string(),
+            "la".to_string(),
+            "dictée".to_string()
+        ]));
+    }
+}
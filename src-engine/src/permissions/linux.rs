@@ -0,0 +1,20 @@
+//! Linux permissions.
+//!
+//! Desktop Linux doesn't gate microphones or input behind per-application
+//! permissions: PipeWire serves any process of the logged-in user, and the
+//! clipboard and paste tools need no grant.
+
+use super::PermissionProbe;
+use flowstt_common::{PermissionKind, PermissionState};
+
+pub struct LinuxPermissionProbe;
+
+impl PermissionProbe for LinuxPermissionProbe {
+    fn state(&self, _kind: PermissionKind) -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    fn request(&self, _kind: PermissionKind) -> Result<(), String> {
+        Ok(())
+    }
+}
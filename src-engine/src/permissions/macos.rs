@@ -0,0 +1,129 @@
+//! macOS permissions.
+//!
+//! Uses:
+//! - `AVCaptureDevice` authorization status for the microphone
+//! - `AXIsProcessTrusted` for Accessibility (see `crate::hotkey`)
+//! - `IOHIDCheckAccess` / `IOHIDRequestAccess` for Input Monitoring
+//!
+//! macOS prompts for each permission only once; after a refusal, requesting
+//! opens the matching pane of System Settings instead.
+
+use super::PermissionProbe;
+use block2::RcBlock;
+use flowstt_common::{PermissionKind, PermissionState};
+use objc2::msg_send;
+use objc2::runtime::{AnyClass, Bool};
+use objc2_foundation::NSString;
+use std::process::Command;
+use tracing::debug;
+
+/// `AVMediaTypeAudio`
+const MEDIA_TYPE_AUDIO: &str = "soun";
+
+/// `kIOHIDRequestTypeListenEvent`
+const LISTEN_EVENT: u32 = 1;
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOHIDCheckAccess(request_type: u32) -> u32;
+    fn IOHIDRequestAccess(request_type: u32) -> bool;
+}
+
+pub struct MacOSPermissionProbe;
+
+impl PermissionProbe for MacOSPermissionProbe {
+    fn state(&self, kind: PermissionKind) -> PermissionState {
+        match kind {
+            PermissionKind::Microphone => microphone_state(),
+            PermissionKind::Accessibility => {
+                if crate::hotkey::check_accessibility_permission() {
+                    PermissionState::Granted
+                } else {
+                    PermissionState::Denied
+                }
+            }
+            // kIOHIDAccessTypeGranted, kIOHIDAccessTypeDenied, kIOHIDAccessTypeUnknown
+            PermissionKind::InputMonitoring => match unsafe { IOHIDCheckAccess(LISTEN_EVENT) } {
+                0 => PermissionState::Granted,
+                1 => PermissionState::Denied,
+                _ => PermissionState::NotDetermined,
+            },
+        }
+    }
+
+    fn request(&self, kind: PermissionKind) -> Result<(), String> {
+        let state = self.state(kind);
+        match kind {
+            PermissionKind::Microphone if state == PermissionState::NotDetermined => {
+                request_microphone();
+                Ok(())
+            }
+            PermissionKind::Microphone => open_settings("Privacy_Microphone"),
+            // The Accessibility prompt also adds FlowSTT to the list in
+            // System Settings, so it is useful even after a refusal
+            PermissionKind::Accessibility => {
+                crate::hotkey::request_accessibility_permission();
+                Ok(())
+            }
+            PermissionKind::InputMonitoring if state == PermissionState::NotDetermined => {
+                unsafe { IOHIDRequestAccess(LISTEN_EVENT) };
+                Ok(())
+            }
+            PermissionKind::InputMonitoring => open_settings("Privacy_ListenEvent"),
+        }
+    }
+}
+
+fn microphone_state() -> PermissionState {
+    let Some(class) = AnyClass::get("AVCaptureDevice") else {
+        return PermissionState::NotRequired;
+    };
+    let media_type = NSString::from_str(MEDIA_TYPE_AUDIO);
+    let status: isize = unsafe { msg_send![class, authorizationStatusForMediaType: &*media_type] };
+    // AVAuthorizationStatus: 0 not determined, 1 restricted, 2 denied, 3 authorized
+    match status {
+        0 => PermissionState::NotDetermined,
+        3 => PermissionState::Granted,
+        _ => PermissionState::Denied,
+    }
+}
+
+/// Show the microphone prompt. The answer is picked up by the next check.
+fn request_microphone() {
+    let Some(class) = AnyClass::get("AVCaptureDevice") else {
+        return;
+    };
+    let media_type = NSString::from_str(MEDIA_TYPE_AUDIO);
+    let handler = RcBlock::new(|granted: Bool| {
+        debug!(
+            "[Permissions] Microphone prompt answered: {}",
+            granted.as_bool()
+        );
+    });
+    let _: () = unsafe {
+        msg_send![
+            class,
+            requestAccessForMediaType: &*media_type,
+            completionHandler: &*handler
+        ]
+    };
+}
+
+/// Open a pane of the Privacy & Security settings.
+fn open_settings(pane: &str) -> Result<(), String> {
+    let url = format!(
+        "x-apple.systempreferences:com.apple.preference.security?{}",
+        pane
+    );
+    let status = Command::new("open")
+        .arg(&url)
+        .status()
+        .map_err(|e| format!("Failed to open System Settings: {}", e))?;
+    if !status.success() {
+        return Err(format!("open exited with status {}", status));
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+//! Operating-system permissions the service needs.
+//!
+//! `GetPermissionsStatus` reports where the microphone, Accessibility and
+//! Input Monitoring permissions stand, and `RequestPermission` asks for one.
+//! Grants are usually made in system settings while FlowSTT runs, so a
+//! background task re-checks them and broadcasts `PermissionChanged` when one
+//! changes; clients can then guide the user through the next step instead of
+//! features failing silently.
+//!
+//! Platform-specific checks live in submodules following the same
+//! backend-trait pattern used by `crate::power`.
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::{PermissionKind, PermissionState, PermissionsStatus};
+use tracing::{debug, info, warn};
+
+use crate::ipc::broadcast_event;
+
+/// How often permissions are re-checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Permissions as of the last check, to tell which changed
+static LAST_STATUS: Mutex<Option<PermissionsStatus>> = Mutex::new(None);
+
+/// Platform-agnostic permission backend.
+pub trait PermissionProbe: Send + Sync {
+    /// Where a permission stands for this process.
+    fn state(&self, kind: PermissionKind) -> PermissionState;
+
+    /// Show the system prompt for a permission, or open the settings page
+    /// where it is granted when the system won't prompt again.
+    fn request(&self, kind: PermissionKind) -> Result<(), String>;
+}
+
+/// Create the platform-specific backend.
+fn create_backend() -> Box<dyn PermissionProbe> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsPermissionProbe)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::MacOSPermissionProbe)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxPermissionProbe)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        compile_error!("Unsupported platform for permission checks");
+    }
+}
+
+fn current_status(probe: &dyn PermissionProbe) -> PermissionsStatus {
+    PermissionsStatus {
+        microphone: probe.state(PermissionKind::Microphone),
+        accessibility: probe.state(PermissionKind::Accessibility),
+        input_monitoring: probe.state(PermissionKind::InputMonitoring),
+    }
+}
+
+/// Check every permission, broadcasting `PermissionChanged` for each that
+/// changed since the last check.
+pub fn check() -> PermissionsStatus {
+    let mut last = LAST_STATUS.lock().unwrap();
+    let status = current_status(create_backend().as_ref());
+    if let Some(previous) = last.replace(status) {
+        for kind in PermissionKind::ALL {
+            let state = status.get(kind);
+            if previous.get(kind) == state {
+                continue;
+            }
+            info!("[Permissions] {:?} is now {:?}", kind, state);
+            broadcast_event(Response::Event {
+                event: EventType::PermissionChanged { kind, state },
+            });
+        }
+    }
+    status
+}
+
+/// Ask for a permission and return where every permission stands after.
+pub fn request(kind: PermissionKind) -> Result<PermissionsStatus, String> {
+    let probe = create_backend();
+    match probe.state(kind) {
+        PermissionState::Granted | PermissionState::NotRequired => {}
+        state => {
+            info!(
+                "[Permissions] Requesting {:?} (currently {:?})",
+                kind, state
+            );
+            probe.request(kind)?;
+        }
+    }
+    Ok(check())
+}
+
+/// Re-check permissions until shutdown.
+pub async fn run_permission_watcher() {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+        if let Err(e) = tokio::task::spawn_blocking(check).await {
+            warn!("[Permissions] Permission check failed: {}", e);
+        }
+    }
+
+    debug!("[Permissions] Watcher stopped");
+}
@@ -0,0 +1,90 @@
+//! Windows permissions.
+//!
+//! Only the microphone is gated, by the privacy settings that Windows keeps
+//! in the `CapabilityAccessManager` consent store: the device-wide switch,
+//! the user's switch and the one for desktop applications. Keystroke
+//! simulation and hotkeys need no grant.
+
+use super::PermissionProbe;
+use flowstt_common::{PermissionKind, PermissionState};
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use windows::core::{w, PCWSTR};
+use windows::Win32::System::Registry::{
+    RegGetValueW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ,
+};
+use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+
+const MICROPHONE_CONSENT: PCWSTR = w!(
+    "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\microphone"
+);
+const MICROPHONE_CONSENT_DESKTOP: PCWSTR = w!(
+    "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\microphone\\NonPackaged"
+);
+
+pub struct WindowsPermissionProbe;
+
+impl PermissionProbe for WindowsPermissionProbe {
+    fn state(&self, kind: PermissionKind) -> PermissionState {
+        match kind {
+            PermissionKind::Microphone => microphone_state(),
+            PermissionKind::Accessibility | PermissionKind::InputMonitoring => {
+                PermissionState::NotRequired
+            }
+        }
+    }
+
+    fn request(&self, kind: PermissionKind) -> Result<(), String> {
+        if kind != PermissionKind::Microphone {
+            return Ok(());
+        }
+        // Windows has no prompt for desktop applications; the user flips
+        // the switch in Settings
+        Command::new("cmd")
+            .args(["/C", "start", "", "ms-settings:privacy-microphone"])
+            .creation_flags(CREATE_NO_WINDOW.0)
+            .status()
+            .map_err(|e| format!("Failed to open microphone privacy settings: {}", e))?;
+        Ok(())
+    }
+}
+
+fn microphone_state() -> PermissionState {
+    let switches = [
+        (HKEY_LOCAL_MACHINE, MICROPHONE_CONSENT),
+        (HKEY_CURRENT_USER, MICROPHONE_CONSENT),
+        (HKEY_CURRENT_USER, MICROPHONE_CONSENT_DESKTOP),
+    ];
+    // A missing switch has never been turned off
+    if switches
+        .iter()
+        .any(|&(root, key)| consent(root, key).as_deref() == Some("Deny"))
+    {
+        PermissionState::Denied
+    } else {
+        PermissionState::Granted
+    }
+}
+
+/// The `Value` of a consent store key: `Allow`, `Deny` or `Prompt`.
+fn consent(root: HKEY, key: PCWSTR) -> Option<String> {
+    let mut buffer = [0u16; 16];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            root,
+            key,
+            w!("Value"),
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    };
+    if status.is_err() {
+        return None;
+    }
+    // The size is in bytes and includes the terminating null
+    let len = (size as usize / 2).saturating_sub(1);
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
@@ -1,6 +1,11 @@
 //! Platform-agnostic audio backend trait.
 
-use flowstt_common::{AudioDevice, RecordingMode};
+use std::fmt;
+use std::time::Duration;
+
+use flowstt_common::{AudioDevice, CaptureGlitches, RecordingMode};
+
+use super::realtime::StopToken;
 
 /// Audio data received from capture
 pub struct AudioData {
@@ -9,8 +14,81 @@ pub struct AudioData {
     /// Number of channels
     pub channels: u16,
     /// Sample rate in Hz
-    #[allow(dead_code)]
     pub sample_rate: u32,
+    /// The primary source on its own, with the same layout as `samples`, when
+    /// a secondary source was mixed or split into them
+    pub primary: Option<Vec<f32>>,
+}
+
+/// Lay out two sources for `RecordingMode::Split`.
+///
+/// Both inputs are interleaved with `channels` channels. Each frame of the
+/// output keeps that layout, with the primary source downmixed into the first
+/// channel and the secondary source into the second; any further channels are
+/// silent. Mono streams have no second channel, so the sources are averaged.
+pub fn split_sources(primary: &[f32], secondary: &[f32], channels: u16) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    let mut output = vec![0.0f32; primary.len().min(secondary.len())];
+
+    for ((out, p), s) in output
+        .chunks_mut(channels)
+        .zip(primary.chunks(channels))
+        .zip(secondary.chunks(channels))
+    {
+        let p = p.iter().sum::<f32>() / p.len() as f32;
+        let s = s.iter().sum::<f32>() / s.len() as f32;
+        if out.len() >= 2 {
+            out[0] = p;
+            out[1] = s;
+        } else {
+            out[0] = (p + s) * 0.5;
+        }
+    }
+
+    output
+}
+
+/// Why capture could not be started.
+#[derive(Debug, Clone)]
+pub enum CaptureError {
+    /// Another application holds the device exclusively
+    DeviceBusy {
+        /// The device that could not be opened
+        device_id: String,
+        /// Name of the process that appears to hold it, if known
+        holder_hint: Option<String>,
+    },
+    /// Any other failure
+    Failed(String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeviceBusy {
+                device_id,
+                holder_hint: Some(holder),
+            } => write!(f, "Audio device {} is in use by {}", device_id, holder),
+            Self::DeviceBusy { device_id, .. } => write!(
+                f,
+                "Audio device {} is in use by another application",
+                device_id
+            ),
+            Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for CaptureError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+impl From<CaptureError> for String {
+    fn from(error: CaptureError) -> Self {
+        error.to_string()
+    }
 }
 
 /// Platform-agnostic audio backend interface.
@@ -18,6 +96,13 @@ pub trait AudioBackend: Send + Sync {
     /// Get the sample rate for this backend.
     fn sample_rate(&self) -> u32;
 
+    /// Native sample rate of the primary input stream, before any resampling
+    /// in the backend, or `None` if no input stream is open.
+    ///
+    /// Changes when the device renegotiates its format mid-stream, or when
+    /// the stream is reopened in a new format.
+    fn input_sample_rate(&self) -> Option<u32>;
+
     /// List available input devices (microphones).
     fn list_input_devices(&self) -> Vec<AudioDevice>;
 
@@ -29,17 +114,22 @@ pub trait AudioBackend: Send + Sync {
         &self,
         source1_id: Option<String>,
         source2_id: Option<String>,
-    ) -> Result<(), String>;
+    ) -> Result<(), CaptureError>;
 
     /// Stop audio capture.
     fn stop_capture(&self) -> Result<(), String>;
 
-    /// Try to receive audio data (non-blocking).
-    fn try_recv(&self) -> Option<AudioData>;
+    /// Wait for audio data. Returns `None` if `stop` fires or `timeout`
+    /// elapses before any arrives.
+    fn recv(&self, stop: &StopToken, timeout: Duration) -> Option<AudioData>;
 
     /// Set whether AEC is enabled.
     fn set_aec_enabled(&self, enabled: bool);
 
     /// Set the recording mode.
     fn set_recording_mode(&self, mode: RecordingMode);
+
+    /// Overruns and underruns the platform audio API has reported since the
+    /// engine started.
+    fn capture_glitches(&self) -> CaptureGlitches;
 }
@@ -2,9 +2,10 @@
 
 mod pipewire;
 
+use super::realtime::AtomicRecordingMode;
 use super::AudioBackend;
-use flowstt_common::RecordingMode;
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
 
 /// Global backend instance
 static BACKEND: OnceLock<Box<dyn AudioBackend>> = OnceLock::new();
@@ -14,8 +15,8 @@ pub fn init() -> Result<(), String> {
     tracing::info!("Initializing Linux PipeWire audio backend");
 
     // Create shared state for AEC and recording mode
-    let aec_enabled = Arc::new(Mutex::new(false));
-    let recording_mode = Arc::new(Mutex::new(RecordingMode::default()));
+    let aec_enabled = Arc::new(AtomicBool::new(false));
+    let recording_mode = Arc::new(AtomicRecordingMode::default());
 
     let backend = pipewire::create_backend(aec_enabled, recording_mode)?;
 
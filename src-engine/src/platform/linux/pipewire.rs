@@ -10,6 +10,7 @@ use pipewire::{
     main_loop::MainLoop,
     properties::properties,
     spa::{
+        buffer::ChunkFlags,
         param::audio::{AudioFormat, AudioInfoRaw},
         pod::Pod,
         utils::Direction,
@@ -21,13 +22,23 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::mem;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
-use crate::platform::backend::{AudioBackend, AudioData};
+use crate::platform::backend::{split_sources, AudioBackend, AudioData, CaptureError};
+use crate::platform::realtime::{self, AtomicRecordingMode, BackendCounters, StopToken};
 use aec3::voip::VoipAec3;
-use flowstt_common::{AudioDevice, AudioSourceType, RecordingMode};
+use crossbeam_channel::{Receiver, Sender};
+use flowstt_common::{AudioDevice, AudioSourceType, CaptureGlitches, RecordingMode};
+
+/// Negotiated rate of the primary input stream (0 when none is open)
+static INPUT_RATE: AtomicU32 = AtomicU32::new(0);
+
+/// Xruns seen by the capture streams
+static COUNTERS: BackendCounters = BackendCounters::new("pipewire");
 
 /// Commands sent to the PipeWire thread
 #[derive(Debug)]
@@ -45,14 +56,15 @@ enum PwCommand {
 struct PwAudioSamples {
     samples: Vec<f32>,
     channels: u16,
+    primary: Option<Vec<f32>>,
 }
 
 /// Handle to the PipeWire audio backend
 pub struct PipeWireBackend {
     /// Channel to send commands to PipeWire thread
     cmd_tx: mpsc::Sender<PwCommand>,
-    /// Channel to receive audio samples (wrapped in Mutex for Sync)
-    audio_rx: Mutex<mpsc::Receiver<PwAudioSamples>>,
+    /// Lock-free channel to receive mixed audio samples
+    audio_rx: Receiver<PwAudioSamples>,
     /// Cached input devices
     input_devices: Arc<Mutex<Vec<AudioDevice>>>,
     /// Cached system devices
@@ -60,24 +72,24 @@ pub struct PipeWireBackend {
     /// Thread handle
     _thread_handle: JoinHandle<()>,
     /// Sample rate from PipeWire
-    sample_rate: Arc<Mutex<u32>>,
+    sample_rate: Arc<AtomicU32>,
     /// Echo cancellation enabled flag (shared with mixer)
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode (shared with mixer)
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
 }
 
 impl PipeWireBackend {
     /// Create and start the PipeWire backend with shared AEC enabled flag and recording mode
     pub fn new(
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Result<Self, String> {
         let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = realtime::audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
-        let sample_rate = Arc::new(Mutex::new(48000u32));
+        let sample_rate = Arc::new(AtomicU32::new(48000));
 
         let input_devices_clone = Arc::clone(&input_devices);
         let system_devices_clone = Arc::clone(&system_devices);
@@ -104,7 +116,7 @@ impl PipeWireBackend {
 
         Ok(Self {
             cmd_tx,
-            audio_rx: Mutex::new(audio_rx),
+            audio_rx,
             input_devices,
             system_devices,
             _thread_handle: thread_handle,
@@ -125,14 +137,18 @@ impl AudioBackend for PipeWireBackend {
     }
 
     fn sample_rate(&self) -> u32 {
-        *self.sample_rate.lock().unwrap()
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    fn input_sample_rate(&self) -> Option<u32> {
+        Some(INPUT_RATE.load(Ordering::Relaxed)).filter(|&rate| rate != 0)
     }
 
     fn start_capture_sources(
         &self,
         source1_id: Option<String>,
         source2_id: Option<String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), CaptureError> {
         // Convert string IDs to u32 for PipeWire
         let source1: Option<u32> = source1_id.as_ref().and_then(|s| s.parse().ok());
         let source2: Option<u32> = source2_id.as_ref().and_then(|s| s.parse().ok());
@@ -142,7 +158,7 @@ impl AudioBackend for PipeWireBackend {
                 source1_id: source1,
                 source2_id: source2,
             })
-            .map_err(|e| format!("Failed to send start command: {}", e))
+            .map_err(|e| CaptureError::Failed(format!("Failed to send start command: {}", e)))
     }
 
     fn stop_capture(&self) -> Result<(), String> {
@@ -151,33 +167,33 @@ impl AudioBackend for PipeWireBackend {
             .map_err(|e| format!("Failed to send stop command: {}", e))
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
-        let sample_rate = *self.sample_rate.lock().unwrap();
-        self.audio_rx
-            .lock()
-            .unwrap()
-            .try_recv()
-            .ok()
-            .map(|pw_samples| AudioData {
-                samples: pw_samples.samples,
-                channels: pw_samples.channels,
-                sample_rate,
-            })
+    fn recv(&self, stop: &StopToken, timeout: Duration) -> Option<AudioData> {
+        let pw_samples = stop.recv(&self.audio_rx, timeout)?;
+        Some(AudioData {
+            samples: pw_samples.samples,
+            channels: pw_samples.channels,
+            sample_rate: self.sample_rate.load(Ordering::Relaxed),
+            primary: pw_samples.primary,
+        })
     }
 
     fn set_aec_enabled(&self, enabled: bool) {
-        *self.aec_enabled.lock().unwrap() = enabled;
+        self.aec_enabled.store(enabled, Ordering::Relaxed);
     }
 
     fn set_recording_mode(&self, mode: RecordingMode) {
-        *self.recording_mode.lock().unwrap() = mode;
+        self.recording_mode.store(mode);
+    }
+
+    fn capture_glitches(&self) -> CaptureGlitches {
+        COUNTERS.snapshot()
     }
 }
 
 /// Create a Linux audio backend using PipeWire
 pub fn create_backend(
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) -> Result<Box<dyn AudioBackend>, String> {
     let backend = PipeWireBackend::new(aec_enabled, recording_mode)?;
     Ok(Box::new(backend))
@@ -200,20 +216,20 @@ struct AudioMixer {
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<PwAudioSamples>,
+    output_tx: Sender<PwAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode - Mixed or EchoCancel (shared with main thread)
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
     /// AEC3 pipeline (created when in mixed mode with 2 streams)
     aec: Option<VoipAec3>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<PwAudioSamples>,
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        output_tx: Sender<PwAudioSamples>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
@@ -270,10 +286,15 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_sink_capture: bool) {
         if self.num_streams == 1 {
             // Only one stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(PwAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            realtime::send_or_drop(
+                &self.output_tx,
+                PwAudioSamples {
+                    samples: samples.to_vec(),
+                    channels: self.channels,
+                    primary: None,
+                },
+                samples.len(),
+            );
             return;
         }
 
@@ -305,8 +326,8 @@ impl AudioMixer {
 
     /// Process buffered capture samples through AEC
     fn process_capture(&mut self) {
-        let aec_enabled = *self.aec_enabled.lock().unwrap();
-        let recording_mode = *self.recording_mode.lock().unwrap();
+        let aec_enabled = self.aec_enabled.load(Ordering::Relaxed);
+        let recording_mode = self.recording_mode.load();
 
         let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
 
@@ -335,6 +356,10 @@ impl AudioMixer {
                 capture_frame
             };
 
+            // Keep the primary source alone for speech detection when it is mixed
+            let primary =
+                (recording_mode != RecordingMode::EchoCancel).then(|| processed_capture.clone());
+
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
@@ -349,11 +374,15 @@ impl AudioMixer {
                     // Output only the processed capture signal - no mixing
                     processed_capture
                 }
+                RecordingMode::Split => {
+                    // Keep sources on separate channels for post-hoc editing
+                    split_sources(&processed_capture, &render_frame, self.channels)
+                }
             };
 
             // Debug logging (periodic)
-            static LOG_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
-            let count = LOG_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            static LOG_COUNTER: AtomicU32 = AtomicU32::new(0);
+            let count = LOG_COUNTER.fetch_add(1, Ordering::Relaxed);
             if count.is_multiple_of(500) {
                 let render_rms: f32 = if !render_frame.is_empty() {
                     (render_frame.iter().map(|s| s * s).sum::<f32>() / render_frame.len() as f32)
@@ -376,10 +405,16 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(PwAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            let sample_count = output.len();
+            realtime::send_or_drop(
+                &self.output_tx,
+                PwAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                    primary,
+                },
+                sample_count,
+            );
         }
     }
 }
@@ -395,7 +430,7 @@ struct PwThreadState {
     /// Active streams (kept alive)
     streams: Vec<ActiveStream>,
     /// Sample rate (updated from param_changed)
-    sample_rate: Arc<Mutex<u32>>,
+    sample_rate: Arc<AtomicU32>,
     /// Set of sink (system audio) device IDs
     sink_ids: Rc<RefCell<std::collections::HashSet<u32>>>,
 }
@@ -403,12 +438,12 @@ struct PwThreadState {
 /// Run the PipeWire main loop thread
 fn run_pipewire_thread(
     cmd_rx: mpsc::Receiver<PwCommand>,
-    audio_tx: mpsc::Sender<PwAudioSamples>,
+    audio_tx: Sender<PwAudioSamples>,
     input_devices: Arc<Mutex<Vec<AudioDevice>>>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
-    sample_rate: Arc<Mutex<u32>>,
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    sample_rate: Arc<AtomicU32>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) -> Result<(), String> {
     // Initialize PipeWire
     pipewire::init();
@@ -441,6 +476,7 @@ fn run_pipewire_thread(
                     let media_class = props.get("media.class").unwrap_or("");
                     let node_name = props.get("node.name").unwrap_or("Unknown");
                     let node_desc = props.get("node.description").unwrap_or(node_name);
+                    let sample_rate = props.get("audio.rate").and_then(|r| r.parse().ok());
 
                     if media_class == "Audio/Source" {
                         // Input device (microphone)
@@ -448,6 +484,7 @@ fn run_pipewire_thread(
                             id: global.id.to_string(),
                             name: node_desc.to_string(),
                             source_type: AudioSourceType::Input,
+                            sample_rate,
                         };
                         input_map_clone.borrow_mut().insert(global.id, device);
                         // Update shared list
@@ -459,6 +496,7 @@ fn run_pipewire_thread(
                             id: global.id.to_string(),
                             name: format!("{} (Monitor)", node_desc),
                             source_type: AudioSourceType::System,
+                            sample_rate,
                         };
                         system_map_clone.borrow_mut().insert(global.id, device);
                         // Update shared list
@@ -540,6 +578,7 @@ fn run_pipewire_thread(
                         let mut state = state_for_timer.borrow_mut();
                         // Clear existing streams
                         state.streams.clear();
+                        INPUT_RATE.store(0, Ordering::Relaxed);
 
                         // Count how many streams we'll have
                         let num_streams =
@@ -584,6 +623,7 @@ fn run_pipewire_thread(
                     }
                     PwCommand::StopCapture => {
                         state_for_timer.borrow_mut().streams.clear();
+                        INPUT_RATE.store(0, Ordering::Relaxed);
                         mixer_for_timer.borrow_mut().set_num_streams(0);
                     }
                 }
@@ -631,7 +671,7 @@ fn create_capture_stream(
     capture_sink: bool,
     stream_index: usize, // 1 or 2
     mixer: Rc<RefCell<AudioMixer>>,
-    sample_rate: Arc<Mutex<u32>>,
+    sample_rate: Arc<AtomicU32>,
 ) -> Result<ActiveStream, String> {
     let stream_name = if capture_sink {
         format!("flowstt-system-capture-{}", stream_index)
@@ -691,7 +731,20 @@ fn create_capture_stream(
                         rate,
                         channels
                     );
-                    *sample_rate_for_param.lock().unwrap() = rate;
+                    sample_rate_for_param.store(rate, Ordering::Relaxed);
+
+                    // A Bluetooth headset switching profile renegotiates the
+                    // format of a stream that is already running
+                    if stream_index == 1 && !capture_sink {
+                        let previous = INPUT_RATE.swap(rate, Ordering::Relaxed);
+                        if previous != 0 && previous != rate {
+                            tracing::warn!(
+                                "Input stream rate changed from {} to {} Hz",
+                                previous,
+                                rate
+                            );
+                        }
+                    }
                     mixer_for_param.borrow_mut().set_channels(channels as u16);
                 }
             }
@@ -700,35 +753,43 @@ fn create_capture_stream(
             tracing::debug!("Stream {} state: {:?} -> {:?}", stream_index, old, new);
         })
         .process(move |stream, _user_data| {
-            if let Some(mut buffer) = stream.dequeue_buffer() {
-                let datas = buffer.datas_mut();
-                if datas.is_empty() {
-                    return;
-                }
+            let Some(mut buffer) = stream.dequeue_buffer() else {
+                // The graph ran without a buffer ready for this stream
+                COUNTERS.record_underrun(0);
+                return;
+            };
+            let datas = buffer.datas_mut();
+            if datas.is_empty() {
+                return;
+            }
 
-                let data = &mut datas[0];
-                // Get chunk info first
-                let chunk_size = data.chunk().size() as usize;
-                let n_samples = chunk_size / mem::size_of::<f32>();
+            let data = &mut datas[0];
+            // Get chunk info first
+            let chunk_size = data.chunk().size() as usize;
+            let n_samples = chunk_size / mem::size_of::<f32>();
 
-                if n_samples == 0 {
-                    return;
-                }
+            // PipeWire marks the chunk after an xrun
+            if data.chunk().flags().contains(ChunkFlags::CORRUPTED) {
+                COUNTERS.record_overrun(0);
+            }
 
-                if let Some(samples_data) = data.data() {
-                    // Convert bytes to f32 samples
-                    let samples: Vec<f32> = samples_data[..chunk_size]
-                        .chunks_exact(4)
-                        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
-                        .collect();
-
-                    if !samples.is_empty() {
-                        // Route to appropriate mixer buffer based on source type:
-                        // - Sink capture (system audio) goes to reference buffer for AEC
-                        // - Input capture (mic) goes to capture buffer for AEC
-                        let mut mixer = mixer_for_process.borrow_mut();
-                        mixer.push_samples(&samples, capture_sink);
-                    }
+            if n_samples == 0 {
+                return;
+            }
+
+            if let Some(samples_data) = data.data() {
+                // Convert bytes to f32 samples
+                let samples: Vec<f32> = samples_data[..chunk_size]
+                    .chunks_exact(4)
+                    .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .collect();
+
+                if !samples.is_empty() {
+                    // Route to appropriate mixer buffer based on source type:
+                    // - Sink capture (system audio) goes to reference buffer for AEC
+                    // - Input capture (mic) goes to capture buffer for AEC
+                    let mut mixer = mixer_for_process.borrow_mut();
+                    mixer.push_samples(&samples, capture_sink);
                 }
             }
         })
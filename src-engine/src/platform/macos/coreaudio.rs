@@ -6,25 +6,28 @@
 //! - Multi-source capture with mixing
 //! - Echo cancellation using AEC3
 
-use crate::platform::backend::{AudioBackend, AudioData};
-use crate::platform::macos::screencapturekit::{self, SCKAudioCapture};
+use crate::platform::backend::{split_sources, AudioBackend, AudioData, CaptureError};
+use crate::platform::macos::screencapturekit::{self, SCKAudioCapture, SCKAudioSamples};
+use crate::platform::realtime::{self, AtomicRecordingMode, BackendCounters, StopToken};
 use aec3::voip::VoipAec3;
 use coreaudio::audio_unit::macos_helpers::{
     get_audio_device_ids, get_audio_device_supports_scope, get_default_device_id, get_device_name,
 };
 use coreaudio::audio_unit::Scope;
 use coreaudio::sys::{
-    self, kAudioOutputUnitProperty_SetInputCallback, kAudioUnitProperty_StreamFormat, AudioBuffer,
-    AudioBufferList, AudioUnitRenderActionFlags,
+    self, kAudioOutputUnitProperty_SetInputCallback, kAudioTimeStampSampleTimeValid,
+    kAudioUnitProperty_StreamFormat, AudioBuffer, AudioBufferList, AudioUnitRenderActionFlags,
 };
-use flowstt_common::{AudioDevice, AudioSourceType, RecordingMode};
+use crossbeam_channel::{Receiver, Sender};
+use flowstt_common::{AudioDevice, AudioSourceType, CaptureGlitches, RecordingMode};
 use std::collections::HashSet;
 use std::os::raw::c_void;
 use std::ptr;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 /// Target sample rate for output (matches Linux/Windows backends)
 const TARGET_SAMPLE_RATE: f64 = 48000.0;
@@ -38,11 +41,14 @@ const SYSTEM_AUDIO_PREFIX: &str = "sck:";
 /// Context passed to the input callback
 struct InputCallbackContext {
     audio_unit: sys::AudioUnit,
-    audio_tx: mpsc::Sender<StreamSamples>,
+    audio_tx: Sender<StreamSamples>,
     resampler: Option<Mutex<Resampler>>,
     num_channels: usize,
     is_non_interleaved: bool,
     stream_index: usize,
+    /// Sample time the next callback should start at (`f64` bits, 0 before
+    /// the first callback), to notice frames the device skipped
+    next_sample_time: AtomicU64,
 }
 
 /// Raw input callback procedure for CoreAudio
@@ -56,6 +62,19 @@ extern "C" fn input_callback_proc(
 ) -> sys::OSStatus {
     let context = unsafe { &*(in_ref_con as *const InputCallbackContext) };
 
+    // A jump in sample time means the device overran and frames were lost
+    let time_stamp = unsafe { &*in_time_stamp };
+    if time_stamp.mFlags & kAudioTimeStampSampleTimeValid != 0 {
+        let next = time_stamp.mSampleTime + f64::from(in_number_frames);
+        let expected = context
+            .next_sample_time
+            .swap(next.to_bits(), Ordering::Relaxed);
+        let expected = f64::from_bits(expected);
+        if expected > 0.0 && time_stamp.mSampleTime >= expected + 1.0 {
+            COUNTERS.record_overrun((time_stamp.mSampleTime - expected) as u64);
+        }
+    }
+
     // Allocate buffer list for the audio data
     let num_buffers = if context.is_non_interleaved {
         context.num_channels
@@ -117,6 +136,7 @@ extern "C" fn input_callback_proc(
             "[AudioCallback] AudioUnitRender failed with OSStatus: {}",
             status
         );
+        COUNTERS.record_underrun(u64::from(in_number_frames));
         return status;
     }
 
@@ -174,11 +194,16 @@ extern "C" fn input_callback_proc(
     };
 
     if !samples.is_empty() {
-        let _ = context.audio_tx.send(StreamSamples {
-            stream_index: context.stream_index,
-            samples,
-            is_loopback: false,
-        });
+        let sample_count = samples.len();
+        realtime::send_or_drop(
+            &context.audio_tx,
+            StreamSamples {
+                stream_index: context.stream_index,
+                samples,
+                is_loopback: false,
+            },
+            sample_count,
+        );
     }
 
     0 // noErr
@@ -188,6 +213,7 @@ extern "C" fn input_callback_proc(
 struct CoreAudioSamples {
     samples: Vec<f32>,
     channels: u16,
+    primary: Option<Vec<f32>>,
 }
 
 /// Samples from a stream thread to the mixer
@@ -223,20 +249,20 @@ struct AudioMixer {
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<CoreAudioSamples>,
+    output_tx: Sender<CoreAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode - Mixed or EchoCancel (shared with main thread)
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
     /// AEC3 pipeline (created when in mixed mode with 2 streams)
     aec: Option<VoipAec3>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<CoreAudioSamples>,
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        output_tx: Sender<CoreAudioSamples>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
@@ -286,10 +312,15 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_loopback: bool) {
         if self.num_streams == 1 {
             // Single stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(CoreAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            realtime::send_or_drop(
+                &self.output_tx,
+                CoreAudioSamples {
+                    samples: samples.to_vec(),
+                    channels: self.channels,
+                    primary: None,
+                },
+                samples.len(),
+            );
             return;
         }
 
@@ -319,8 +350,8 @@ impl AudioMixer {
 
     /// Process buffered capture samples through AEC
     fn process_capture(&mut self) {
-        let aec_enabled = *self.aec_enabled.lock().unwrap();
-        let recording_mode = *self.recording_mode.lock().unwrap();
+        let aec_enabled = self.aec_enabled.load(Ordering::Relaxed);
+        let recording_mode = self.recording_mode.load();
 
         let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
 
@@ -349,6 +380,10 @@ impl AudioMixer {
                 capture_frame
             };
 
+            // Keep the primary source alone for speech detection when it is mixed
+            let primary =
+                (recording_mode != RecordingMode::EchoCancel).then(|| processed_capture.clone());
+
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
@@ -372,6 +407,10 @@ impl AudioMixer {
                     // Output only the processed capture signal
                     processed_capture
                 }
+                RecordingMode::Split => {
+                    // Keep sources on separate channels for post-hoc editing
+                    split_sources(&processed_capture, &render_frame, self.channels)
+                }
             };
 
             // Debug logging (periodic)
@@ -399,10 +438,16 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(CoreAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            let sample_count = output.len();
+            realtime::send_or_drop(
+                &self.output_tx,
+                CoreAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                    primary,
+                },
+                sample_count,
+            );
         }
     }
 }
@@ -425,7 +470,7 @@ impl MultiCaptureManager {
         is_loopback1: bool,
         source2_id: Option<String>,
         is_loopback2: bool,
-        stream_tx: mpsc::Sender<StreamSamples>,
+        stream_tx: Sender<StreamSamples>,
     ) -> Result<Self, String> {
         let mut input_stream = None;
         let mut system_capture: Option<SCKAudioCapture> = None;
@@ -486,14 +531,11 @@ impl MultiCaptureManager {
         })
     }
 
-    /// Poll system audio capture for samples
-    fn poll_system_audio(&self) -> Option<Vec<f32>> {
-        if let Some(ref capture) = self.system_capture {
-            if let Some(samples) = capture.try_recv() {
-                return Some(samples.samples);
-            }
-        }
-        None
+    /// Channel delivering system audio, if a system source is active
+    fn system_audio(&self) -> Option<&Receiver<SCKAudioSamples>> {
+        self.system_capture
+            .as_ref()
+            .map(|capture| capture.receiver())
     }
 }
 
@@ -524,7 +566,7 @@ impl Drop for MultiCaptureManager {
 fn run_input_capture(
     device_id: String,
     stream_index: usize,
-    stream_tx: mpsc::Sender<StreamSamples>,
+    stream_tx: Sender<StreamSamples>,
     stop_flag: Arc<AtomicBool>,
 ) {
     tracing::info!(
@@ -581,6 +623,7 @@ fn run_input_capture(
         num_channels,
         is_non_interleaved,
         stream_index,
+        next_sample_time: AtomicU64::new(0),
     });
     let context_ptr = Box::into_raw(callback_context);
 
@@ -626,9 +669,33 @@ fn run_input_capture(
 
     tracing::info!("CoreAudio: Input capture started");
 
-    // Wait for stop signal
+    let is_primary = stream_index == 1;
+    if is_primary {
+        INPUT_RATE.store(sample_rate as u32, Ordering::Relaxed);
+    }
+
+    // Wait for stop signal, watching the device rate: a Bluetooth headset
+    // switching profile changes it while the unit keeps running
+    let mut polls = 0u32;
     while !stop_flag.load(Ordering::SeqCst) {
         thread::sleep(std::time::Duration::from_millis(10));
+        polls += 1;
+        if is_primary && polls % 100 == 0 {
+            if let Some(rate) = get_nominal_sample_rate(device_id) {
+                let previous = INPUT_RATE.swap(rate, Ordering::Relaxed);
+                if previous != rate {
+                    tracing::warn!(
+                        "CoreAudio: Input device rate changed from {} to {} Hz",
+                        previous,
+                        rate
+                    );
+                }
+            }
+        }
+    }
+
+    if is_primary {
+        INPUT_RATE.store(0, Ordering::Relaxed);
     }
 
     // Stop and clean up
@@ -641,12 +708,18 @@ fn run_input_capture(
     tracing::info!("CoreAudio: Input capture stopped");
 }
 
+/// Native rate of the primary input stream (0 when none is open)
+static INPUT_RATE: AtomicU32 = AtomicU32::new(0);
+
+/// Glitches noticed by the input callbacks
+static COUNTERS: BackendCounters = BackendCounters::new("coreaudio");
+
 /// CoreAudio backend for macOS
 pub struct CoreAudioBackend {
     /// Channel to send commands to capture thread
-    cmd_tx: mpsc::Sender<CaptureCommand>,
+    cmd_tx: Sender<CaptureCommand>,
     /// Channel to receive audio samples from capture thread
-    audio_rx: Mutex<mpsc::Receiver<CoreAudioSamples>>,
+    audio_rx: Receiver<CoreAudioSamples>,
     /// Cached input devices
     input_devices: Arc<Mutex<Vec<AudioDevice>>>,
     /// Cached system devices
@@ -659,19 +732,19 @@ pub struct CoreAudioBackend {
     #[allow(dead_code)]
     is_capturing: Arc<AtomicBool>,
     /// AEC enabled flag
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
 }
 
 impl CoreAudioBackend {
     /// Create a new CoreAudio backend
     pub fn new(
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Result<Self, String> {
-        let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
+        let (audio_tx, audio_rx) = realtime::audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
         let is_capturing = Arc::new(AtomicBool::new(false));
@@ -701,7 +774,7 @@ impl CoreAudioBackend {
 
         Ok(Self {
             cmd_tx,
-            audio_rx: Mutex::new(audio_rx),
+            audio_rx,
             input_devices,
             system_devices,
             sample_rate: TARGET_SAMPLE_RATE as u32,
@@ -732,11 +805,15 @@ impl AudioBackend for CoreAudioBackend {
         self.sample_rate
     }
 
+    fn input_sample_rate(&self) -> Option<u32> {
+        Some(INPUT_RATE.load(Ordering::Relaxed)).filter(|&rate| rate != 0)
+    }
+
     fn start_capture_sources(
         &self,
         source1_id: Option<String>,
         source2_id: Option<String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), CaptureError> {
         let (result_tx, result_rx) = mpsc::channel();
 
         self.cmd_tx
@@ -748,13 +825,13 @@ impl AudioBackend for CoreAudioBackend {
             .map_err(|e| format!("Failed to send start command: {}", e))?;
 
         match result_rx.recv_timeout(std::time::Duration::from_secs(10)) {
-            Ok(result) => result,
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                Err("Timeout waiting for audio capture to start".to_string())
-            }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                Err("Capture thread disconnected".to_string())
-            }
+            Ok(result) => result.map_err(CaptureError::Failed),
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(CaptureError::Failed(
+                "Timeout waiting for audio capture to start".to_string(),
+            )),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(CaptureError::Failed(
+                "Capture thread disconnected".to_string(),
+            )),
         }
     }
 
@@ -765,36 +842,36 @@ impl AudioBackend for CoreAudioBackend {
         Ok(())
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
-        self.audio_rx
-            .lock()
-            .unwrap()
-            .try_recv()
-            .ok()
-            .map(|samples| AudioData {
-                samples: samples.samples,
-                channels: samples.channels,
-                sample_rate: self.sample_rate,
-            })
+    fn recv(&self, stop: &StopToken, timeout: Duration) -> Option<AudioData> {
+        stop.recv(&self.audio_rx, timeout).map(|samples| AudioData {
+            samples: samples.samples,
+            channels: samples.channels,
+            sample_rate: self.sample_rate,
+            primary: samples.primary,
+        })
     }
 
     fn set_aec_enabled(&self, enabled: bool) {
-        *self.aec_enabled.lock().unwrap() = enabled;
+        self.aec_enabled.store(enabled, Ordering::Relaxed);
     }
 
     fn set_recording_mode(&self, mode: RecordingMode) {
-        *self.recording_mode.lock().unwrap() = mode;
+        self.recording_mode.store(mode);
+    }
+
+    fn capture_glitches(&self) -> CaptureGlitches {
+        COUNTERS.snapshot()
     }
 }
 
 /// Run the capture thread
 fn run_capture_thread(
-    cmd_rx: mpsc::Receiver<CaptureCommand>,
-    audio_tx: mpsc::Sender<CoreAudioSamples>,
+    cmd_rx: Receiver<CaptureCommand>,
+    audio_tx: Sender<CoreAudioSamples>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
     is_capturing: Arc<AtomicBool>,
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) {
     tracing::debug!("CoreAudio: Capture thread started and ready to receive commands");
 
@@ -802,31 +879,41 @@ fn run_capture_thread(
     let mut mixer = AudioMixer::new(audio_tx, aec_enabled, recording_mode);
 
     // Channel for receiving samples from stream threads
-    let (stream_tx, stream_rx) = mpsc::channel::<StreamSamples>();
+    let (stream_tx, stream_rx) = realtime::audio_channel::<StreamSamples>();
 
     // Active capture state
     let mut capture_manager: Option<MultiCaptureManager> = None;
+    // Set once the system audio channel disconnects so it is not selected again
+    let mut system_audio_closed = false;
+    let no_system_audio = crossbeam_channel::never();
 
     loop {
-        // Process any samples from stream threads first
-        while let Ok(stream_samples) = stream_rx.try_recv() {
-            mixer.push_samples(&stream_samples.samples, stream_samples.is_loopback);
-        }
-
-        // Poll system audio if we have an active capture
-        if let Some(ref manager) = capture_manager {
-            if let Some(samples) = manager.poll_system_audio() {
-                mixer.push_samples(&samples, true); // is_loopback = true for system audio
+        let system_rx = capture_manager
+            .as_ref()
+            .and_then(|manager| manager.system_audio())
+            .filter(|_| !system_audio_closed)
+            .unwrap_or(&no_system_audio);
+
+        // Sleep until a capture stream delivers samples or a command arrives
+        let cmd = crossbeam_channel::select! {
+            recv(stream_rx) -> stream_samples => {
+                if let Ok(stream_samples) = stream_samples {
+                    mixer.push_samples(&stream_samples.samples, stream_samples.is_loopback);
+                }
+                continue;
             }
-        }
-
-        let timeout = if capture_manager.is_some() {
-            std::time::Duration::from_millis(1)
-        } else {
-            std::time::Duration::from_secs(1)
+            recv(system_rx) -> samples => {
+                match samples {
+                    // is_loopback = true for system audio
+                    Ok(samples) => mixer.push_samples(&samples.samples, true),
+                    Err(_) => system_audio_closed = true,
+                }
+                continue;
+            }
+            recv(cmd_rx) -> cmd => cmd,
         };
 
-        match cmd_rx.recv_timeout(timeout) {
+        match cmd {
             Ok(CaptureCommand::StartSources {
                 source1_id,
                 source2_id,
@@ -842,6 +929,7 @@ fn run_capture_thread(
                 if let Some(manager) = capture_manager.take() {
                     drop(manager);
                 }
+                system_audio_closed = false;
 
                 // Determine which sources are loopback (system audio)
                 let system_ids: HashSet<String> = system_devices
@@ -904,10 +992,8 @@ fn run_capture_thread(
                 is_capturing.store(false, Ordering::SeqCst);
                 break;
             }
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                // Continue processing samples
-            }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err(_) => {
+                // Backend dropped
                 break;
             }
         }
@@ -1096,6 +1182,7 @@ fn enumerate_input_devices() -> Result<Vec<AudioDevice>, String> {
                 id: device_id.to_string(),
                 name,
                 source_type: AudioSourceType::Input,
+                sample_rate: get_nominal_sample_rate(device_id),
             });
         }
     }
@@ -1113,6 +1200,30 @@ fn enumerate_input_devices() -> Result<Vec<AudioDevice>, String> {
     Ok(input_devices)
 }
 
+/// Read a device's nominal sample rate.
+fn get_nominal_sample_rate(device_id: sys::AudioDeviceID) -> Option<u32> {
+    let address = sys::AudioObjectPropertyAddress {
+        mSelector: sys::kAudioDevicePropertyNominalSampleRate,
+        mScope: sys::kAudioObjectPropertyScopeGlobal,
+        mElement: sys::kAudioObjectPropertyElementMaster,
+    };
+    let mut sample_rate: f64 = 0.0;
+    let mut data_size = std::mem::size_of::<f64>() as u32;
+
+    let status = unsafe {
+        sys::AudioObjectGetPropertyData(
+            device_id,
+            &address,
+            0,
+            ptr::null(),
+            &mut data_size,
+            &mut sample_rate as *mut f64 as *mut c_void,
+        )
+    };
+
+    (status == 0 && sample_rate > 0.0).then(|| sample_rate as u32)
+}
+
 /// Enumerate available system audio devices (via ScreenCaptureKit)
 fn enumerate_system_devices() -> Vec<AudioDevice> {
     if !screencapturekit::is_available() {
@@ -1126,6 +1237,7 @@ fn enumerate_system_devices() -> Vec<AudioDevice> {
                 id: format!("{}{}", SYSTEM_AUDIO_PREFIX, d.id),
                 name: d.name,
                 source_type: AudioSourceType::System,
+                sample_rate: Some(screencapturekit::TARGET_SAMPLE_RATE),
             })
             .collect(),
         Err(e) => {
@@ -1205,8 +1317,8 @@ impl Resampler {
 
 /// Create a macOS CoreAudio backend
 pub fn create_backend(
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) -> Result<Box<dyn AudioBackend>, String> {
     let backend = CoreAudioBackend::new(aec_enabled, recording_mode)?;
     Ok(Box::new(backend))
@@ -9,7 +9,9 @@
 //! - Excludes app's own audio to prevent feedback
 //! - Converts audio to f32 stereo at 48kHz
 
+use crate::platform::realtime;
 use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
+use crossbeam_channel::{Receiver, Sender};
 use screencapturekit_sys::{
     cm_sample_buffer_ref::CMSampleBufferRef,
     content_filter::{UnsafeContentFilter, UnsafeInitParams},
@@ -27,7 +29,7 @@ use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
 /// Target sample rate for output (matches encoder expectations)
-const TARGET_SAMPLE_RATE: u32 = 48000;
+pub(super) const TARGET_SAMPLE_RATE: u32 = 48000;
 
 /// Target channel count
 const TARGET_CHANNELS: u32 = 2;
@@ -129,7 +131,7 @@ enum SCKCommand {
 /// Requires Screen Recording permission.
 pub struct SCKAudioCapture {
     cmd_tx: mpsc::Sender<SCKCommand>,
-    audio_rx: Mutex<mpsc::Receiver<SCKAudioSamples>>,
+    audio_rx: Receiver<SCKAudioSamples>,
     is_capturing: Arc<AtomicBool>,
     thread_handle: Mutex<Option<JoinHandle<()>>>,
 }
@@ -160,7 +162,7 @@ impl SCKAudioCapture {
         }
 
         let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = realtime::audio_channel();
         let is_capturing = Arc::new(AtomicBool::new(false));
         let is_capturing_clone = Arc::clone(&is_capturing);
 
@@ -171,7 +173,7 @@ impl SCKAudioCapture {
 
         Ok(Self {
             cmd_tx,
-            audio_rx: Mutex::new(audio_rx),
+            audio_rx,
             is_capturing,
             thread_handle: Mutex::new(Some(thread_handle)),
         })
@@ -211,9 +213,9 @@ impl SCKAudioCapture {
         Ok(())
     }
 
-    /// Try to receive audio samples (non-blocking)
-    pub fn try_recv(&self) -> Option<SCKAudioSamples> {
-        self.audio_rx.lock().unwrap().try_recv().ok()
+    /// Channel delivering captured audio samples
+    pub fn receiver(&self) -> &Receiver<SCKAudioSamples> {
+        &self.audio_rx
     }
 
     /// Check if capture is active
@@ -240,7 +242,7 @@ impl Drop for SCKAudioCapture {
 /// never be called, causing hangs.
 fn run_sck_thread(
     cmd_rx: mpsc::Receiver<SCKCommand>,
-    audio_tx: mpsc::Sender<SCKAudioSamples>,
+    audio_tx: Sender<SCKAudioSamples>,
     is_capturing: Arc<AtomicBool>,
 ) {
     tracing::debug!("ScreenCaptureKit: Capture thread started");
@@ -323,7 +325,7 @@ impl UnsafeSCStreamError for AudioCaptureErrorHandler {
 
 /// Audio output handler that converts CMSampleBuffer to audio samples
 struct AudioOutputHandler {
-    tx: mpsc::Sender<SCKAudioSamples>,
+    tx: Sender<SCKAudioSamples>,
     stop_flag: Arc<AtomicBool>,
 }
 
@@ -458,10 +460,15 @@ impl UnsafeSCStreamOutput for AudioOutputHandler {
         };
 
         // Send to channel
-        let _ = self.tx.send(SCKAudioSamples {
-            samples: final_samples,
-            channels: TARGET_CHANNELS as u16,
-        });
+        let sample_count = final_samples.len();
+        realtime::send_or_drop(
+            &self.tx,
+            SCKAudioSamples {
+                samples: final_samples,
+                channels: TARGET_CHANNELS as u16,
+            },
+            sample_count,
+        );
     }
 }
 
@@ -517,7 +524,7 @@ impl Drop for SCKCaptureState {
 }
 
 /// Start a ScreenCaptureKit capture session
-fn start_capture(audio_tx: mpsc::Sender<SCKAudioSamples>) -> Result<SCKCaptureState, String> {
+fn start_capture(audio_tx: Sender<SCKAudioSamples>) -> Result<SCKCaptureState, String> {
     tracing::info!("ScreenCaptureKit: Starting capture - getting shareable content...");
 
     // Get shareable content
@@ -15,8 +15,14 @@ pub mod windows;
 pub mod macos;
 
 mod backend;
+pub mod realtime;
 
-pub use backend::AudioBackend;
+pub use backend::{AudioBackend, AudioData, CaptureError};
+
+use std::sync::Mutex;
+
+/// Backend used in place of the platform one while set
+static OVERRIDE: Mutex<Option<&'static dyn AudioBackend>> = Mutex::new(None);
 
 /// Initialize the platform-specific audio backend.
 pub fn init_audio_backend() -> Result<(), String> {
@@ -41,8 +47,19 @@ pub fn init_audio_backend() -> Result<(), String> {
     }
 }
 
+/// Use `backend` in place of the platform backend, e.g. to play synthetic
+/// audio through the capture path, or go back to the platform backend with
+/// `None`.
+pub fn set_backend_override(backend: Option<&'static dyn AudioBackend>) {
+    *OVERRIDE.lock().unwrap() = backend;
+}
+
 /// Get the current audio backend.
 pub fn get_backend() -> Option<&'static dyn AudioBackend> {
+    if let Some(backend) = *OVERRIDE.lock().unwrap() {
+        return Some(backend);
+    }
+
     #[cfg(target_os = "linux")]
     {
         linux::get_backend()
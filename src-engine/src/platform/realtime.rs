@@ -0,0 +1,252 @@
+//! Lock-free state shared with real-time capture threads.
+//!
+//! Capture callbacks and mixer threads must never wait on a lock held by the
+//! processing thread. Audio is handed over through bounded lock-free channels
+//! using `try_send`: if the consumer falls behind, the chunk is discarded and
+//! counted instead of stalling the capture thread. Mixer settings are atomics
+//! read once per frame.
+//!
+//! Consumers block on these channels instead of polling. A [`StopToken`] lets
+//! a blocked thread be woken when its loop is stopped or the engine shuts
+//! down.
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use flowstt_common::{CaptureGlitches, RecordingMode};
+
+/// Chunks buffered between a producer and consumer before audio is dropped.
+/// Backends deliver roughly 10ms per chunk, so this is a few seconds of audio.
+const AUDIO_CHANNEL_CAPACITY: usize = 512;
+
+/// Samples discarded because a consumer could not keep up
+static DROPPED_SAMPLES: AtomicU64 = AtomicU64::new(0);
+
+/// Total samples dropped on the audio path since the engine started.
+pub fn dropped_samples() -> u64 {
+    DROPPED_SAMPLES.load(Ordering::Relaxed)
+}
+
+/// Count samples discarded anywhere on the audio path.
+pub fn record_dropped_samples(count: usize) {
+    if count > 0 {
+        DROPPED_SAMPLES.fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+/// Overruns and underruns reported by a platform backend's capture threads.
+///
+/// Each backend keeps one in a static. A warning is logged at the 1st, 2nd,
+/// 4th, 8th... glitch of each kind so a bad device stands out in the log
+/// without flooding it from the capture thread.
+pub struct BackendCounters {
+    backend: &'static str,
+    overruns: AtomicU64,
+    underruns: AtomicU64,
+    dropped_frames: AtomicU64,
+}
+
+impl BackendCounters {
+    pub const fn new(backend: &'static str) -> Self {
+        Self {
+            backend,
+            overruns: AtomicU64::new(0),
+            underruns: AtomicU64::new(0),
+            dropped_frames: AtomicU64::new(0),
+        }
+    }
+
+    /// Count an overrun that lost `frames` frames (0 if unknown).
+    pub fn record_overrun(&self, frames: u64) {
+        let count = self.overruns.fetch_add(1, Ordering::Relaxed) + 1;
+        self.dropped_frames.fetch_add(frames, Ordering::Relaxed);
+        if count.is_power_of_two() {
+            tracing::warn!(
+                "[{}] Capture overrun ({} so far): audio was lost before it was read",
+                self.backend,
+                count
+            );
+        }
+    }
+
+    /// Count an underrun that lost `frames` frames (0 if unknown).
+    pub fn record_underrun(&self, frames: u64) {
+        let count = self.underruns.fetch_add(1, Ordering::Relaxed) + 1;
+        self.dropped_frames.fetch_add(frames, Ordering::Relaxed);
+        if count.is_power_of_two() {
+            tracing::warn!(
+                "[{}] Capture underrun ({} so far): no audio was ready",
+                self.backend,
+                count
+            );
+        }
+    }
+
+    /// The counts so far.
+    pub fn snapshot(&self) -> CaptureGlitches {
+        CaptureGlitches {
+            backend: self.backend.to_string(),
+            overruns: self.overruns.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            dropped_frames: self.dropped_frames.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Create a bounded channel for audio chunks.
+pub fn audio_channel<T>() -> (Sender<T>, Receiver<T>) {
+    crossbeam_channel::bounded(AUDIO_CHANNEL_CAPACITY)
+}
+
+/// Send a chunk without blocking, counting its samples as dropped if the
+/// channel is full. A disconnected receiver means capture is shutting down,
+/// so nothing is counted.
+pub fn send_or_drop<T>(tx: &Sender<T>, chunk: T, sample_count: usize) {
+    if let Err(crossbeam_channel::TrySendError::Full(_)) = tx.try_send(chunk) {
+        record_dropped_samples(sample_count);
+    }
+}
+
+/// Recording mode readable from capture threads without locking.
+#[derive(Debug, Default)]
+pub struct AtomicRecordingMode(AtomicU8);
+
+impl AtomicRecordingMode {
+    pub fn load(&self) -> RecordingMode {
+        match self.0.load(Ordering::Relaxed) {
+            1 => RecordingMode::EchoCancel,
+            2 => RecordingMode::Split,
+            _ => RecordingMode::Mixed,
+        }
+    }
+
+    pub fn store(&self, mode: RecordingMode) {
+        let value = match mode {
+            RecordingMode::Mixed => 0,
+            RecordingMode::EchoCancel => 1,
+            RecordingMode::Split => 2,
+        };
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+/// One-shot signal that wakes every thread blocked on its [`StopToken`].
+///
+/// Nothing is ever sent: stopping drops the sender, which disconnects the
+/// channel and makes all receivers ready at once.
+pub struct StopSignal {
+    tx: Mutex<Option<Sender<()>>>,
+}
+
+impl StopSignal {
+    pub const fn new() -> Self {
+        Self {
+            tx: Mutex::new(None),
+        }
+    }
+
+    /// Arm the signal for a new run and return the token that fires on [`stop`](Self::stop).
+    ///
+    /// Arming again replaces (and fires) the previous token.
+    pub fn arm(&self) -> StopToken {
+        let (tx, rx) = crossbeam_channel::bounded(0);
+        *self.tx.lock().unwrap() = Some(tx);
+        StopToken {
+            stop: rx,
+            shutdown: shutdown_receiver(),
+        }
+    }
+
+    /// Fire the current token.
+    pub fn stop(&self) {
+        self.tx.lock().unwrap().take();
+    }
+}
+
+impl Default for StopSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receiving side of a [`StopSignal`]. Also fires when the engine shuts down.
+#[derive(Clone)]
+pub struct StopToken {
+    stop: Receiver<()>,
+    shutdown: Receiver<()>,
+}
+
+impl StopToken {
+    /// A token that only fires on engine shutdown.
+    pub fn shutdown() -> Self {
+        Self {
+            stop: crossbeam_channel::never(),
+            shutdown: shutdown_receiver(),
+        }
+    }
+
+    /// Whether the token has fired.
+    pub fn is_stopped(&self) -> bool {
+        [&self.stop, &self.shutdown]
+            .iter()
+            .any(|rx| matches!(rx.try_recv(), Err(TryRecvError::Disconnected)))
+    }
+
+    /// Block until the token fires or `timeout` elapses. Returns true if it fired.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        crossbeam_channel::select! {
+            recv(self.stop) -> _ => true,
+            recv(self.shutdown) -> _ => true,
+            default(timeout) => false,
+        }
+    }
+
+    /// Block until a message arrives on `rx`, the token fires, or `timeout`
+    /// elapses. Returns the message if one arrived.
+    pub fn recv<T>(&self, rx: &Receiver<T>, timeout: Duration) -> Option<T> {
+        crossbeam_channel::select! {
+            recv(rx) -> msg => match msg {
+                Ok(msg) => Some(msg),
+                // Producer is gone; wait out the timeout instead of spinning
+                Err(_) => {
+                    self.wait(timeout);
+                    None
+                }
+            },
+            recv(self.stop) -> _ => None,
+            recv(self.shutdown) -> _ => None,
+            default(timeout) => None,
+        }
+    }
+}
+
+/// Sender held until engine shutdown, and the receiver every token clones
+type ShutdownChannel = (Option<Sender<()>>, Receiver<()>);
+
+/// Created on first use, and again after [`reset_shutdown`]
+static SHUTDOWN: Mutex<Option<ShutdownChannel>> = Mutex::new(None);
+
+fn with_shutdown_channel<R>(f: impl FnOnce(&mut ShutdownChannel) -> R) -> R {
+    let mut channel = SHUTDOWN.lock().unwrap();
+    f(channel.get_or_insert_with(|| {
+        let (tx, rx) = crossbeam_channel::bounded(0);
+        (Some(tx), rx)
+    }))
+}
+
+fn shutdown_receiver() -> Receiver<()> {
+    with_shutdown_channel(|(_, rx)| rx.clone())
+}
+
+/// Fire every [`StopToken`], waking all blocked audio threads for shutdown.
+pub fn signal_shutdown() {
+    with_shutdown_channel(|(tx, _)| tx.take());
+}
+
+/// Arm shutdown again for an engine started after the last one shut down.
+/// Tokens made before keep the fired channel.
+pub fn reset_shutdown() {
+    SHUTDOWN.lock().unwrap().take();
+}
@@ -6,26 +6,33 @@
 //! - Multi-source capture with mixing
 //! - Echo cancellation using AEC3
 
-use crate::platform::backend::{AudioBackend, AudioData};
-use flowstt_common::{AudioDevice, AudioSourceType, RecordingMode};
+use crate::platform::backend::{split_sources, AudioBackend, AudioData, CaptureError};
+use crate::platform::realtime::{self, AtomicRecordingMode, BackendCounters, StopToken};
+use crossbeam_channel::{Receiver, Sender};
+use flowstt_common::{AudioDevice, AudioSourceType, CaptureGlitches, RecordingMode};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 use aec3::voip::VoipAec3;
-use windows::core::{GUID, PCWSTR, PWSTR};
+use windows::core::{Interface, GUID, PCWSTR, PWSTR};
 use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
 use windows::Win32::Media::Audio::{
-    eCapture, eRender, IAudioCaptureClient, IAudioClient, IMMDevice, IMMDeviceCollection,
-    IMMDeviceEnumerator, MMDeviceEnumerator, AUDCLNT_SHAREMODE_SHARED,
-    AUDCLNT_STREAMFLAGS_EVENTCALLBACK, AUDCLNT_STREAMFLAGS_LOOPBACK, WAVEFORMATEX,
-    WAVEFORMATEXTENSIBLE,
+    eCapture, eRender, AudioSessionStateActive, IAudioCaptureClient, IAudioClient,
+    IAudioSessionControl2, IAudioSessionManager2, IMMDevice, IMMDeviceCollection,
+    IMMDeviceEnumerator, MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_DATA_DISCONTINUITY,
+    AUDCLNT_E_DEVICE_IN_USE, AUDCLNT_SHAREMODE_SHARED, AUDCLNT_STREAMFLAGS_EVENTCALLBACK,
+    AUDCLNT_STREAMFLAGS_LOOPBACK, WAVEFORMATEX, WAVEFORMATEXTENSIBLE,
 };
 use windows::Win32::System::Com::{
     CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ,
 };
-use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+use windows::Win32::System::Threading::{
+    CreateEventW, OpenProcess, QueryFullProcessImageNameW, WaitForSingleObject, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_LIMITED_INFORMATION,
+};
 use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
 
 /// WAVE_FORMAT_EXTENSIBLE constant (0xFFFE)
@@ -47,10 +54,20 @@ const TARGET_SAMPLE_RATE: u32 = 48000;
 /// AEC3 frame size: 10ms at 48kHz = 480 samples per channel
 const AEC_FRAME_SAMPLES: usize = 480;
 
+/// How long to wait for each stream thread to open its device
+const STREAM_START_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Native rate of the primary input stream (0 when none is open)
+static INPUT_RATE: AtomicU32 = AtomicU32::new(0);
+
+/// Glitches reported by the capture clients
+static COUNTERS: BackendCounters = BackendCounters::new("wasapi");
+
 /// Internal audio samples for channel communication
 struct WasapiAudioSamples {
     samples: Vec<f32>,
     channels: u16,
+    primary: Option<Vec<f32>>,
 }
 
 /// Samples from a stream thread to the mixer
@@ -65,7 +82,7 @@ enum CaptureCommand {
     StartSources {
         source1_id: Option<String>,
         source2_id: Option<String>,
-        result_tx: mpsc::Sender<Result<(), String>>,
+        result_tx: mpsc::Sender<Result<(), CaptureError>>,
     },
     Stop,
     Shutdown,
@@ -74,9 +91,9 @@ enum CaptureCommand {
 /// WASAPI audio backend for Windows
 pub struct WasapiBackend {
     /// Channel to send commands to capture thread
-    cmd_tx: mpsc::Sender<CaptureCommand>,
-    /// Channel to receive audio samples from capture thread (wrapped in Mutex for Sync)
-    audio_rx: Mutex<mpsc::Receiver<WasapiAudioSamples>>,
+    cmd_tx: Sender<CaptureCommand>,
+    /// Lock-free channel to receive mixed audio samples from capture thread
+    audio_rx: Receiver<WasapiAudioSamples>,
     /// Cached input devices
     input_devices: Arc<Mutex<Vec<AudioDevice>>>,
     /// Cached system devices (loopback sources)
@@ -86,19 +103,19 @@ pub struct WasapiBackend {
     /// Capture thread handle
     _thread_handle: JoinHandle<()>,
     /// AEC enabled flag (shared with mixer)
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode (shared with mixer)
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
 }
 
 impl WasapiBackend {
     /// Create a new WASAPI backend
     pub fn new(
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Result<Self, String> {
-        let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
+        let (audio_tx, audio_rx) = realtime::audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
         let is_capturing = Arc::new(AtomicBool::new(false));
@@ -144,7 +161,7 @@ impl WasapiBackend {
 
         Ok(Self {
             cmd_tx,
-            audio_rx: Mutex::new(audio_rx),
+            audio_rx,
             input_devices,
             system_devices,
             sample_rate: TARGET_SAMPLE_RATE,
@@ -174,11 +191,15 @@ impl AudioBackend for WasapiBackend {
         self.sample_rate
     }
 
+    fn input_sample_rate(&self) -> Option<u32> {
+        Some(INPUT_RATE.load(Ordering::Relaxed)).filter(|&rate| rate != 0)
+    }
+
     fn start_capture_sources(
         &self,
         source1_id: Option<String>,
         source2_id: Option<String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), CaptureError> {
         let (result_tx, result_rx) = mpsc::channel();
 
         self.cmd_tx
@@ -191,12 +212,12 @@ impl AudioBackend for WasapiBackend {
 
         match result_rx.recv_timeout(std::time::Duration::from_secs(5)) {
             Ok(result) => result,
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                Err("Timeout waiting for audio capture to start".to_string())
-            }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                Err("Capture thread disconnected".to_string())
-            }
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(CaptureError::Failed(
+                "Timeout waiting for audio capture to start".to_string(),
+            )),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(CaptureError::Failed(
+                "Capture thread disconnected".to_string(),
+            )),
         }
     }
 
@@ -207,32 +228,32 @@ impl AudioBackend for WasapiBackend {
         Ok(())
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
-        self.audio_rx
-            .lock()
-            .unwrap()
-            .try_recv()
-            .ok()
-            .map(|samples| AudioData {
-                samples: samples.samples,
-                channels: samples.channels,
-                sample_rate: self.sample_rate,
-            })
+    fn recv(&self, stop: &StopToken, timeout: Duration) -> Option<AudioData> {
+        stop.recv(&self.audio_rx, timeout).map(|samples| AudioData {
+            samples: samples.samples,
+            channels: samples.channels,
+            sample_rate: self.sample_rate,
+            primary: samples.primary,
+        })
     }
 
     fn set_aec_enabled(&self, enabled: bool) {
-        *self.aec_enabled.lock().unwrap() = enabled;
+        self.aec_enabled.store(enabled, Ordering::Relaxed);
     }
 
     fn set_recording_mode(&self, mode: RecordingMode) {
-        *self.recording_mode.lock().unwrap() = mode;
+        self.recording_mode.store(mode);
+    }
+
+    fn capture_glitches(&self) -> CaptureGlitches {
+        COUNTERS.snapshot()
     }
 }
 
 /// Create a Windows audio backend using WASAPI
 pub fn create_backend(
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) -> Result<Box<dyn AudioBackend>, String> {
     let backend = WasapiBackend::new(aec_enabled, recording_mode)?;
     Ok(Box::new(backend))
@@ -321,10 +342,22 @@ fn device_to_audio_device(device: &IMMDevice, source_type: AudioSourceType) -> O
             }
         };
 
+        // Shared-mode mix format rate; capture resamples from this
+        let sample_rate = device
+            .Activate::<IAudioClient>(CLSCTX_ALL, None)
+            .and_then(|client| client.GetMixFormat())
+            .ok()
+            .map(|format| {
+                let rate = (*format).nSamplesPerSec;
+                windows::Win32::System::Com::CoTaskMemFree(Some(format as *const _));
+                rate
+            });
+
         Some(AudioDevice {
             id,
             name,
             source_type,
+            sample_rate,
         })
     }
 }
@@ -354,20 +387,20 @@ struct AudioMixer {
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<WasapiAudioSamples>,
+    output_tx: Sender<WasapiAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
-    aec_enabled: Arc<Mutex<bool>>,
+    aec_enabled: Arc<AtomicBool>,
     /// Recording mode - Mixed or EchoCancel (shared with main thread)
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    recording_mode: Arc<AtomicRecordingMode>,
     /// AEC3 pipeline (created when in mixed mode with 2 streams)
     aec: Option<VoipAec3>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<WasapiAudioSamples>,
-        aec_enabled: Arc<Mutex<bool>>,
-        recording_mode: Arc<Mutex<RecordingMode>>,
+        output_tx: Sender<WasapiAudioSamples>,
+        aec_enabled: Arc<AtomicBool>,
+        recording_mode: Arc<AtomicRecordingMode>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
@@ -417,10 +450,15 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_loopback: bool) {
         if self.num_streams == 1 {
             // Single stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(WasapiAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            realtime::send_or_drop(
+                &self.output_tx,
+                WasapiAudioSamples {
+                    samples: samples.to_vec(),
+                    channels: self.channels,
+                    primary: None,
+                },
+                samples.len(),
+            );
             return;
         }
 
@@ -450,8 +488,8 @@ impl AudioMixer {
 
     /// Process buffered capture samples through AEC
     fn process_capture(&mut self) {
-        let aec_enabled = *self.aec_enabled.lock().unwrap();
-        let recording_mode = *self.recording_mode.lock().unwrap();
+        let aec_enabled = self.aec_enabled.load(Ordering::Relaxed);
+        let recording_mode = self.recording_mode.load();
 
         let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
 
@@ -480,6 +518,10 @@ impl AudioMixer {
                 capture_frame
             };
 
+            // Keep the primary source alone for speech detection when it is mixed
+            let primary =
+                (recording_mode != RecordingMode::EchoCancel).then(|| processed_capture.clone());
+
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
@@ -503,6 +545,10 @@ impl AudioMixer {
                     // Output only the processed capture signal
                     processed_capture
                 }
+                RecordingMode::Split => {
+                    // Keep sources on separate channels for post-hoc editing
+                    split_sources(&processed_capture, &render_frame, self.channels)
+                }
             };
 
             // Debug logging (periodic)
@@ -530,22 +576,28 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(WasapiAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            let sample_count = output.len();
+            realtime::send_or_drop(
+                &self.output_tx,
+                WasapiAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                    primary,
+                },
+                sample_count,
+            );
         }
     }
 }
 
 /// Run the capture thread
 fn run_capture_thread(
-    cmd_rx: mpsc::Receiver<CaptureCommand>,
-    audio_tx: mpsc::Sender<WasapiAudioSamples>,
+    cmd_rx: Receiver<CaptureCommand>,
+    audio_tx: Sender<WasapiAudioSamples>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
     is_capturing: Arc<AtomicBool>,
-    aec_enabled: Arc<Mutex<bool>>,
-    recording_mode: Arc<Mutex<RecordingMode>>,
+    aec_enabled: Arc<AtomicBool>,
+    recording_mode: Arc<AtomicRecordingMode>,
 ) {
     tracing::info!("WASAPI: Capture thread started");
 
@@ -558,8 +610,10 @@ fn run_capture_thread(
             );
             while let Ok(cmd) = cmd_rx.try_recv() {
                 if let CaptureCommand::StartSources { result_tx, .. } = cmd {
-                    let _ =
-                        result_tx.send(Err(format!("COM initialization failed: {:?}", com_result)));
+                    let _ = result_tx.send(Err(CaptureError::Failed(format!(
+                        "COM initialization failed: {:?}",
+                        com_result
+                    ))));
                 }
             }
             return;
@@ -570,24 +624,24 @@ fn run_capture_thread(
         let mut mixer = AudioMixer::new(audio_tx, aec_enabled, recording_mode);
 
         // Channel for receiving samples from stream threads
-        let (stream_tx, stream_rx) = mpsc::channel::<StreamSamples>();
+        let (stream_tx, stream_rx) = realtime::audio_channel::<StreamSamples>();
 
         // Active capture state
         let mut capture_manager: Option<MultiCaptureManager> = None;
 
         loop {
-            // Process any samples from stream threads first
-            while let Ok(stream_samples) = stream_rx.try_recv() {
-                mixer.push_samples(&stream_samples.samples, stream_samples.is_loopback);
-            }
-
-            let timeout = if capture_manager.is_some() {
-                std::time::Duration::from_millis(1)
-            } else {
-                std::time::Duration::from_secs(1)
+            // Sleep until stream threads deliver samples or a command arrives
+            let cmd = crossbeam_channel::select! {
+                recv(stream_rx) -> stream_samples => {
+                    if let Ok(stream_samples) = stream_samples {
+                        mixer.push_samples(&stream_samples.samples, stream_samples.is_loopback);
+                    }
+                    continue;
+                }
+                recv(cmd_rx) -> cmd => cmd,
             };
 
-            match cmd_rx.recv_timeout(timeout) {
+            match cmd {
                 Ok(CaptureCommand::StartSources {
                     source1_id,
                     source2_id,
@@ -655,10 +709,8 @@ fn run_capture_thread(
                     is_capturing.store(false, Ordering::SeqCst);
                     break;
                 }
-                Err(mpsc::RecvTimeoutError::Timeout) => {
-                    // Continue processing samples
-                }
-                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(_) => {
+                    // Backend dropped
                     break;
                 }
             }
@@ -682,38 +734,68 @@ impl MultiCaptureManager {
         is_loopback1: bool,
         source2_id: Option<String>,
         is_loopback2: bool,
-        stream_tx: mpsc::Sender<StreamSamples>,
-    ) -> Result<Self, String> {
-        let mut stream1 = None;
-        let mut stream2 = None;
+        stream_tx: Sender<StreamSamples>,
+    ) -> Result<Self, CaptureError> {
+        // Dropping the manager on an error stops any stream already started
+        let mut manager = Self {
+            stream1: None,
+            stream2: None,
+        };
 
         // Start stream 1 if specified
         if let Some(device_id) = source1_id {
-            let stop_flag = Arc::new(AtomicBool::new(false));
-            let stop_flag_clone = Arc::clone(&stop_flag);
-            let tx = stream_tx.clone();
-
-            let handle = thread::spawn(move || {
-                run_stream_capture(device_id, is_loopback1, 1, tx, stop_flag_clone);
-            });
-
-            stream1 = Some((handle, stop_flag));
+            manager.stream1 = Some(Self::start_stream(
+                device_id,
+                is_loopback1,
+                1,
+                stream_tx.clone(),
+            )?);
         }
 
         // Start stream 2 if specified
         if let Some(device_id) = source2_id {
-            let stop_flag = Arc::new(AtomicBool::new(false));
-            let stop_flag_clone = Arc::clone(&stop_flag);
-            let tx = stream_tx;
+            manager.stream2 = Some(Self::start_stream(device_id, is_loopback2, 2, stream_tx)?);
+        }
 
-            let handle = thread::spawn(move || {
-                run_stream_capture(device_id, is_loopback2, 2, tx, stop_flag_clone);
-            });
+        Ok(manager)
+    }
 
-            stream2 = Some((handle, stop_flag));
-        }
+    /// Spawn a stream thread and wait until it has opened its device.
+    fn start_stream(
+        device_id: String,
+        is_loopback: bool,
+        stream_index: usize,
+        stream_tx: Sender<StreamSamples>,
+    ) -> Result<(JoinHandle<()>, Arc<AtomicBool>), CaptureError> {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = Arc::clone(&stop_flag);
+        let (started_tx, started_rx) = mpsc::channel();
+        let thread_device_id = device_id.clone();
+
+        let handle = thread::spawn(move || {
+            run_stream_capture(
+                thread_device_id,
+                is_loopback,
+                stream_index,
+                stream_tx,
+                stop_flag_clone,
+                started_tx,
+            );
+        });
 
-        Ok(Self { stream1, stream2 })
+        let started = match started_rx.recv_timeout(STREAM_START_TIMEOUT) {
+            Ok(result) => result,
+            Err(_) => Err(CaptureError::Failed(format!(
+                "Timed out opening audio device {}",
+                device_id
+            ))),
+        };
+        if let Err(e) = started {
+            stop_flag.store(true, Ordering::SeqCst);
+            let _ = handle.join();
+            return Err(e);
+        }
+        Ok((handle, stop_flag))
     }
 }
 
@@ -742,8 +824,9 @@ fn run_stream_capture(
     device_id: String,
     is_loopback: bool,
     stream_index: usize,
-    stream_tx: mpsc::Sender<StreamSamples>,
+    stream_tx: Sender<StreamSamples>,
     stop_flag: Arc<AtomicBool>,
+    started_tx: mpsc::Sender<Result<(), CaptureError>>,
 ) {
     tracing::info!(
         "WASAPI: Stream {} capture thread started (device={}, loopback={})",
@@ -761,6 +844,10 @@ fn run_stream_capture(
                 stream_index,
                 com_result
             );
+            let _ = started_tx.send(Err(CaptureError::Failed(format!(
+                "COM initialization failed: {:?}",
+                com_result
+            ))));
             return;
         }
 
@@ -772,6 +859,14 @@ fn run_stream_capture(
                     stream_index,
                     device_id
                 );
+                let _ = started_tx.send(Ok(()));
+
+                // A Bluetooth headset switching profile invalidates the stream;
+                // it comes back at the new profile's rate when restarted
+                let is_primary_input = stream_index == 1 && !is_loopback;
+                if is_primary_input {
+                    INPUT_RATE.store(state.format.sample_rate, Ordering::Relaxed);
+                }
 
                 // Capture loop
                 while !stop_flag.load(Ordering::SeqCst) {
@@ -781,6 +876,10 @@ fn run_stream_capture(
                     }
                 }
 
+                if is_primary_input {
+                    INPUT_RATE.store(0, Ordering::Relaxed);
+                }
+
                 tracing::info!("WASAPI: Stream {} capture stopped", stream_index);
             }
             Err(e) => {
@@ -789,6 +888,7 @@ fn run_stream_capture(
                     stream_index,
                     e
                 );
+                let _ = started_tx.send(Err(e));
             }
         }
 
@@ -803,6 +903,9 @@ struct CaptureState {
     format: CaptureFormat,
     event_handle: windows::Win32::Foundation::HANDLE,
     resampler: Option<Resampler>,
+    /// Whether a packet has been read; the first one may report a
+    /// discontinuity from before the stream started
+    started: bool,
 }
 
 impl Drop for CaptureState {
@@ -816,6 +919,59 @@ impl Drop for CaptureState {
     }
 }
 
+/// Name of the process with an active audio session on `device`, as a hint
+/// to which application holds it exclusively.
+unsafe fn busy_device_holder(device: &IMMDevice) -> Option<String> {
+    let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None).ok()?;
+    let sessions = manager.GetSessionEnumerator().ok()?;
+    let own_pid = std::process::id();
+
+    for index in 0..sessions.GetCount().ok()? {
+        let Ok(session) = sessions.GetSession(index) else {
+            continue;
+        };
+        if session.GetState().ok() != Some(AudioSessionStateActive) {
+            continue;
+        }
+        let Some(pid) = session
+            .cast::<IAudioSessionControl2>()
+            .and_then(|s| s.GetProcessId())
+            .ok()
+        else {
+            continue;
+        };
+        if pid == 0 || pid == own_pid {
+            continue;
+        }
+        if let Some(name) = process_name(pid) {
+            return Some(name);
+        }
+    }
+    None
+}
+
+/// Executable name of a process, such as `Zoom.exe`.
+unsafe fn process_name(pid: u32) -> Option<String> {
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let mut buf = vec![0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = QueryFullProcessImageNameW(
+        handle,
+        PROCESS_NAME_WIN32,
+        PWSTR(buf.as_mut_ptr()),
+        &mut len,
+    );
+    let _ = windows::Win32::Foundation::CloseHandle(handle);
+    if ok.is_err() || len == 0 {
+        return None;
+    }
+
+    String::from_utf16_lossy(&buf[..len as usize])
+        .rsplit('\\')
+        .next()
+        .map(str::to_string)
+}
+
 /// Format information for captured audio
 struct CaptureFormat {
     sample_rate: u32,
@@ -825,7 +981,7 @@ struct CaptureFormat {
 }
 
 /// Start capturing from a device
-unsafe fn start_capture(device_id: &str, is_loopback: bool) -> Result<CaptureState, String> {
+unsafe fn start_capture(device_id: &str, is_loopback: bool) -> Result<CaptureState, CaptureError> {
     let enumerator: IMMDeviceEnumerator =
         CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
             .map_err(|e| format!("Failed to create device enumerator: {}", e))?;
@@ -876,7 +1032,16 @@ unsafe fn start_capture(device_id: &str, is_loopback: bool) -> Result<CaptureSta
             mix_format_ptr,
             None,
         )
-        .map_err(|e| format!("Failed to initialize audio client: {}", e))?;
+        .map_err(|e| {
+            if e.code() == AUDCLNT_E_DEVICE_IN_USE {
+                CaptureError::DeviceBusy {
+                    device_id: device_id.to_string(),
+                    holder_hint: busy_device_holder(&device),
+                }
+            } else {
+                CaptureError::Failed(format!("Failed to initialize audio client: {}", e))
+            }
+        })?;
 
     audio_client
         .SetEventHandle(event_handle)
@@ -904,6 +1069,7 @@ unsafe fn start_capture(device_id: &str, is_loopback: bool) -> Result<CaptureSta
         format,
         event_handle,
         resampler,
+        started: false,
     })
 }
 
@@ -947,7 +1113,7 @@ fn parse_wave_format(format: &WAVEFORMATEX) -> Result<CaptureFormat, String> {
 unsafe fn process_capture(
     state: &mut CaptureState,
     is_loopback: bool,
-    stream_tx: &mpsc::Sender<StreamSamples>,
+    stream_tx: &Sender<StreamSamples>,
 ) -> Result<(), String> {
     let wait_result = WaitForSingleObject(state.event_handle, 10);
     if wait_result.0 != 0 {
@@ -971,6 +1137,12 @@ unsafe fn process_capture(
             break;
         }
 
+        // The capture client fell behind and audio before this packet was lost
+        if flags & AUDCLNT_BUFFERFLAGS_DATA_DISCONTINUITY.0 as u32 != 0 && state.started {
+            COUNTERS.record_overrun(0);
+        }
+        state.started = true;
+
         let samples = convert_to_f32(buffer_ptr, num_frames as usize, &state.format);
 
         let _ = state.capture_client.ReleaseBuffer(num_frames);
@@ -994,10 +1166,15 @@ unsafe fn process_capture(
         };
 
         // Send to mixer thread via channel with loopback flag
-        let _ = stream_tx.send(StreamSamples {
-            samples: stereo_samples,
-            is_loopback,
-        });
+        let sample_count = stereo_samples.len();
+        realtime::send_or_drop(
+            stream_tx,
+            StreamSamples {
+                samples: stereo_samples,
+                is_loopback,
+            },
+            sample_count,
+        );
     }
 
     Ok(())
@@ -0,0 +1,109 @@
+//! Audio output playback.
+//!
+//! Plays rodio sources to the system's default audio output device on a
+//! dedicated thread. Shared by test mode WAV playback, history recording
+//! playback and sound cues.
+
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use rodio::Source;
+use tracing::debug;
+
+/// Play a sequence of sources back-to-back on the default output device.
+///
+/// Returns a `mpsc::Receiver<()>` that signals when playback has finished.
+/// The playback runs on a dedicated thread so the caller is not blocked.
+///
+/// # Errors
+///
+/// Returns an error if the audio output device is unavailable.
+pub(crate) fn play_sources<S>(sources: Vec<S>, label: String) -> Result<mpsc::Receiver<()>, String>
+where
+    S: Source + Send + 'static,
+{
+    start(sources, label).map(|(_, done_rx)| done_rx)
+}
+
+/// Play a single source with controls for stopping and seeking.
+///
+/// Returns the handle together with a receiver that signals when playback
+/// has finished, either at the end of the source or after [`PlaybackHandle::stop`].
+///
+/// # Errors
+///
+/// Returns an error if the audio output device is unavailable.
+pub(crate) fn play_controlled<S>(
+    source: S,
+    label: String,
+) -> Result<(PlaybackHandle, mpsc::Receiver<()>), String>
+where
+    S: Source + Send + 'static,
+{
+    let duration = source.total_duration();
+    let (player, done_rx) = start(vec![source], label)?;
+    Ok((PlaybackHandle { player, duration }, done_rx))
+}
+
+/// Control over a source started with [`play_controlled`].
+#[derive(Clone)]
+pub(crate) struct PlaybackHandle {
+    player: Arc<rodio::Player>,
+    duration: Option<Duration>,
+}
+
+impl PlaybackHandle {
+    /// Stop playback. The done receiver fires once the output has drained.
+    pub fn stop(&self) {
+        self.player.stop();
+    }
+
+    /// Move playback to a position from the start of the source.
+    pub fn seek(&self, position: Duration) -> Result<(), String> {
+        self.player
+            .try_seek(position)
+            .map_err(|e| format!("Failed to seek: {}", e))
+    }
+
+    /// Length of the source, if the decoder knows it.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+}
+
+/// Open the default output device and start playing `sources` on a
+/// dedicated thread that owns the device until playback ends.
+fn start<S>(
+    sources: Vec<S>,
+    label: String,
+) -> Result<(Arc<rodio::Player>, mpsc::Receiver<()>), String>
+where
+    S: Source + Send + 'static,
+{
+    // Open the default audio output device
+    let mut device_sink = rodio::DeviceSinkBuilder::from_default_device()
+        .map_err(|e| format!("Failed to find default audio output device: {}", e))?
+        .open_sink_or_fallback()
+        .map_err(|e| format!("Failed to open audio output device: {}", e))?;
+    device_sink.log_on_drop(false);
+
+    let player = Arc::new(rodio::Player::connect_new(device_sink.mixer()));
+    for source in sources {
+        player.append(source);
+    }
+
+    let (done_tx, done_rx) = mpsc::channel();
+
+    // Spawn a thread that owns the device sink and player, waits for playback to finish
+    let thread_player = Arc::clone(&player);
+    std::thread::spawn(move || {
+        debug!("[Playback] Playing: {}", label);
+        thread_player.sleep_until_end();
+        debug!("[Playback] Finished: {}", label);
+        // device_sink is kept alive by this closure; dropping it stops the output device
+        drop(device_sink);
+        let _ = done_tx.send(());
+    });
+
+    Ok((player, done_rx))
+}
@@ -0,0 +1,66 @@
+//! Text clean-up shared by every kind of transcription result.
+//!
+//! Live segments, interims typed as you speak, re-transcriptions, meeting
+//! lines and audio streams all go through the same chain: spoken commands,
+//! phrase corrections, redaction, then the profanity filter. Keeping it in
+//! one place means interim and final text can't drift apart, and a new rule
+//! applies to every result.
+
+use crate::config::Config;
+use crate::redaction::RedactedOutput;
+
+/// Run `text` through spoken commands, corrections, redaction and the
+/// profanity filter.
+pub fn apply(config: &Config, text: &str) -> RedactedOutput {
+    let text = crate::spoken_commands::apply(&config.spoken_commands, text);
+    let text = crate::corrections::apply(&config.corrections, &text);
+    filter(config, &text)
+}
+
+/// Redaction and the profanity filter alone, for text that is otherwise
+/// kept exactly as transcribed, such as spelled-out words.
+pub fn filter(config: &Config, text: &str) -> RedactedOutput {
+    let output = crate::redaction::apply(&config.redaction, text);
+    crate::profanity::apply(&config.profanity, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::config::{
+        CorrectionSettings, RedactionRule, RedactionSettings, ReplacementRule,
+    };
+
+    fn config() -> Config {
+        Config {
+            corrections: CorrectionSettings {
+                rules: vec![ReplacementRule {
+                    from: "sam at example dot com".to_string(),
+                    to: "sam@example.com".to_string(),
+                    enabled: true,
+                    learned: false,
+                }],
+                ..Default::default()
+            },
+            redaction: RedactionSettings {
+                rules: vec![RedactionRule::Email],
+                ..Default::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn corrections_run_before_redaction() {
+        let output = apply(&config(), "mail sam at example dot com");
+        assert_eq!(output.history, "mail [REDACTED]");
+        assert_eq!(output.clipboard, output.history);
+    }
+
+    #[test]
+    fn filter_leaves_the_words_alone() {
+        let text = "mail sam at example dot com";
+        let output = filter(&config(), text);
+        assert_eq!(output.history, text);
+    }
+}
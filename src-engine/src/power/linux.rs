@@ -0,0 +1,38 @@
+//! Linux power source query.
+//!
+//! Uses `/sys/class/power_supply`: a `Mains` or `USB` supply that is online
+//! means AC power; otherwise a `Battery` supply means battery power.
+
+use super::PowerProbe;
+use std::fs;
+use std::path::Path;
+
+pub struct LinuxPowerProbe;
+
+impl PowerProbe for LinuxPowerProbe {
+    fn on_battery(&self) -> Option<bool> {
+        let entries = fs::read_dir("/sys/class/power_supply").ok()?;
+
+        let mut has_battery = false;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match read(&path, "type").as_deref() {
+                Some("Mains" | "USB") if read(&path, "online").as_deref() == Some("1") => {
+                    return Some(false);
+                }
+                // Peripheral batteries (mice, headsets) don't power the machine
+                Some("Battery") if read(&path, "scope").as_deref() != Some("Device") => {
+                    has_battery = true;
+                }
+                _ => {}
+            }
+        }
+        has_battery.then_some(true)
+    }
+}
+
+fn read(supply: &Path, attribute: &str) -> Option<String> {
+    fs::read_to_string(supply.join(attribute))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
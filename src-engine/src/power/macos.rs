@@ -0,0 +1,28 @@
+//! macOS power source query.
+//!
+//! Uses `pmset -g batt`, whose first line names the source drawing power:
+//! `Now drawing from 'AC Power'` or `Now drawing from 'Battery Power'`.
+
+use super::PowerProbe;
+use std::process::Command;
+
+pub struct MacOSPowerProbe;
+
+impl PowerProbe for MacOSPowerProbe {
+    fn on_battery(&self) -> Option<bool> {
+        let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let first_line = stdout.lines().next()?;
+        if first_line.contains("'Battery Power'") {
+            Some(true)
+        } else if first_line.contains("'AC Power'") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Power policy: lighter transcription on battery.
+//!
+//! A background task polls whether the machine runs on battery, broadcasts
+//! `PowerStateChanged` when that changes, and applies the `power_policy`
+//! from the config file while it does: fewer whisper threads, a smaller
+//! model, and push-to-talk instead of Automatic mode. The previous mode is
+//! restored on AC power unless the user picked another one meanwhile; the
+//! forced mode is never saved to the config file. Machines without a
+//! battery count as on AC power.
+//!
+//! Platform-specific power queries live in submodules following the same
+//! backend-trait pattern used by `crate::process_watch`.
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::TranscriptionMode;
+use tracing::{debug, info, warn};
+
+use crate::config::current_config;
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::set_transcription_mode;
+use crate::state::get_service_state;
+
+/// How often the power source is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+/// Platform-agnostic power source query backend.
+pub trait PowerProbe: Send + Sync {
+    /// Whether the machine runs on battery; `None` if it has no battery or
+    /// the source can't be determined.
+    fn on_battery(&self) -> Option<bool>;
+}
+
+/// Create the platform-specific backend.
+fn create_backend() -> Box<dyn PowerProbe> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsPowerProbe)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::MacOSPowerProbe)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxPowerProbe)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        compile_error!("Unsupported platform for power detection");
+    }
+}
+
+/// Whether the machine ran on battery when last checked.
+pub fn is_on_battery() -> bool {
+    ON_BATTERY.load(Ordering::SeqCst)
+}
+
+/// Whisper threads to use now, if the power policy limits them.
+pub fn whisper_threads() -> Option<u32> {
+    if !is_on_battery() {
+        return None;
+    }
+    let config = current_config();
+    let policy = &config.power_policy;
+    (policy.enabled && policy.battery_threads > 0).then_some(policy.battery_threads)
+}
+
+/// Model to use now instead of the usual one, if the power policy names one
+/// that has been downloaded.
+pub fn model_override() -> Option<PathBuf> {
+    if !is_on_battery() {
+        return None;
+    }
+    let config = current_config();
+    let policy = &config.power_policy;
+    let name = policy.battery_model.as_ref().filter(|_| policy.enabled)?;
+    let path = crate::transcription::model_path_for(name);
+    if !path.exists() {
+        debug!("[Power] Battery model {:?} isn't downloaded", path);
+        return None;
+    }
+    Some(path)
+}
+
+/// Run the power watcher until shutdown.
+pub async fn run_power_watcher() {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    // Mode to restore on AC power after switching to push-to-talk
+    let mut restore_mode: Option<TranscriptionMode> = None;
+
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        let on_battery = match tokio::task::spawn_blocking(|| create_backend().on_battery()).await {
+            Ok(on_battery) => on_battery.unwrap_or(false),
+            Err(e) => {
+                warn!("[Power] Power query failed: {}", e);
+                continue;
+            }
+        };
+        if ON_BATTERY.swap(on_battery, Ordering::SeqCst) == on_battery {
+            continue;
+        }
+
+        info!(
+            "[Power] Now on {}",
+            if on_battery { "battery" } else { "AC power" }
+        );
+        broadcast_event(Response::Event {
+            event: EventType::PowerStateChanged { on_battery },
+        });
+
+        let battery_push_to_talk = {
+            let config = current_config();
+            config.power_policy.enabled && config.power_policy.battery_push_to_talk
+        };
+        if on_battery && battery_push_to_talk {
+            let current_mode = get_service_state().lock().await.transcription_mode;
+            if current_mode == TranscriptionMode::Automatic {
+                info!("[Power] Switching to push-to-talk on battery");
+                set_transcription_mode(TranscriptionMode::PushToTalk, false).await;
+                restore_mode = Some(current_mode);
+            }
+        } else if !on_battery {
+            if let Some(mode) = restore_mode.take() {
+                let current_mode = get_service_state().lock().await.transcription_mode;
+                if current_mode == TranscriptionMode::PushToTalk {
+                    info!("[Power] Restoring {:?} mode on AC power", mode);
+                    set_transcription_mode(mode, false).await;
+                }
+            }
+        }
+    }
+
+    debug!("[Power] Watcher stopped");
+}
@@ -0,0 +1,28 @@
+//! Windows power source query.
+//!
+//! Uses `GetSystemPowerStatus`.
+
+use super::PowerProbe;
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// `BatteryFlag` bit set when the machine has no battery
+const NO_SYSTEM_BATTERY: u8 = 128;
+
+pub struct WindowsPowerProbe;
+
+impl PowerProbe for WindowsPowerProbe {
+    fn on_battery(&self) -> Option<bool> {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+
+        if status.BatteryFlag & NO_SYSTEM_BATTERY != 0 {
+            return None;
+        }
+        // 0 = offline, 1 = online, 255 = unknown
+        match status.ACLineStatus {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+}
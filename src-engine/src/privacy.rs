@@ -0,0 +1,38 @@
+//! Session privacy mode.
+//!
+//! While privacy mode is on, newly captured segments are flagged ephemeral:
+//! no WAV file is written and their history entries are kept in memory only.
+//! Recent audio is cleared when it turns on and isn't kept while it is on.
+//! The flag is not persisted, so every service start begins with it off.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use flowstt_common::ipc::{EventType, Response};
+use tracing::info;
+
+use crate::ipc::broadcast_event;
+
+static PRIVACY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Check whether privacy mode is on.
+pub fn is_privacy_mode() -> bool {
+    PRIVACY_MODE.load(Ordering::SeqCst)
+}
+
+/// Turn privacy mode on or off and notify clients if it changed.
+pub fn set_privacy_mode(enabled: bool) {
+    if PRIVACY_MODE.swap(enabled, Ordering::SeqCst) == enabled {
+        return;
+    }
+    if enabled {
+        crate::recent_audio::clear();
+    }
+
+    info!(
+        "Privacy mode {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    broadcast_event(Response::Event {
+        event: EventType::PrivacyModeChanged { enabled },
+    });
+}
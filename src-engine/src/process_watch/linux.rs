@@ -0,0 +1,123 @@
+//! Linux process queries.
+//!
+//! Uses:
+//! - `/proc/<pid>` for the running process list, naming each process after
+//!   its executable
+//! - `xdotool getactivewindow getwindowpid` for the foreground process (X11 only)
+
+use super::ProcessProbe;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::process::Command;
+
+pub struct LinuxProcessProbe;
+
+impl ProcessProbe for LinuxProcessProbe {
+    fn running_processes(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .filter_map(|entry| process_name(&entry.path()))
+            .collect()
+    }
+
+    fn foreground_process(&self) -> Option<String> {
+        // Wayland does not expose the focused window to unprivileged clients
+        if std::env::var("WAYLAND_DISPLAY").is_ok() {
+            return None;
+        }
+
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowpid"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let pid: u32 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()?;
+        process_name(&Path::new("/proc").join(pid.to_string()))
+    }
+}
+
+/// Name of the process with the given `/proc/<pid>` directory.
+///
+/// `comm` is cut off at 15 bytes, so the executable is used where it can be
+/// read: the `exe` link for the user's own processes, otherwise the first
+/// word of `cmdline`. Kernel threads have neither and keep their `comm`.
+fn process_name(dir: &Path) -> Option<String> {
+    if let Some(name) = fs::read_link(dir.join("exe"))
+        .ok()
+        .and_then(|exe| exe_name(exe.as_os_str().as_bytes()))
+    {
+        return Some(name);
+    }
+    if let Some(name) = fs::read(dir.join("cmdline"))
+        .ok()
+        .and_then(|cmdline| cmdline_name(&cmdline))
+    {
+        return Some(name);
+    }
+    fs::read_to_string(dir.join("comm"))
+        .ok()
+        .map(|comm| comm.trim().to_string())
+}
+
+/// File name of an executable path, without the ` (deleted)` marker the
+/// kernel adds once the binary has been replaced, e.g. by an update.
+fn exe_name(path: &[u8]) -> Option<String> {
+    let path = path.strip_suffix(b" (deleted)").unwrap_or(path);
+    Path::new(OsStr::from_bytes(path))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Executable name from the NUL-separated arguments in `cmdline`.
+fn cmdline_name(cmdline: &[u8]) -> Option<String> {
+    let program = cmdline.split(|&b| b == 0).next()?;
+    exe_name(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_not_truncated() {
+        assert_eq!(
+            exe_name(b"/usr/lib/signal-desktop/signal-desktop").as_deref(),
+            Some("signal-desktop")
+        );
+        assert_eq!(
+            cmdline_name(b"/opt/Mattermost/mattermost-desktop\0--type=renderer\0").as_deref(),
+            Some("mattermost-desktop")
+        );
+    }
+
+    #[test]
+    fn replaced_binaries_keep_their_name() {
+        assert_eq!(
+            exe_name(b"/usr/bin/firefox (deleted)").as_deref(),
+            Some("firefox")
+        );
+    }
+
+    #[test]
+    fn empty_cmdline_has_no_name() {
+        assert_eq!(cmdline_name(b""), None);
+    }
+}
@@ -0,0 +1,37 @@
+//! macOS process queries.
+//!
+//! Uses:
+//! - `ps -axco comm=` for the running process list
+//! - `osascript` (System Events) for the frontmost application
+
+use super::ProcessProbe;
+use std::process::Command;
+
+pub struct MacOSProcessProbe;
+
+impl ProcessProbe for MacOSProcessProbe {
+    fn running_processes(&self) -> Vec<String> {
+        match Command::new("ps").args(["-axco", "comm="]).output() {
+            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn foreground_process(&self) -> Option<String> {
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(r#"tell application "System Events" to get name of first process whose frontmost is true"#)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!name.is_empty()).then_some(name)
+    }
+}
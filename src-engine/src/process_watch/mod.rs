@@ -0,0 +1,390 @@
+//! Process rules: pause or switch modes based on running applications.
+//!
+//! A background task polls the running process list and the foreground
+//! application, evaluates the `process_rules` from the config file, and
+//! pauses capture or switches to push-to-talk while a rule applies. The
+//! previous state is restored once no rule matches. A forced mode only
+//! changes the running engine, never the config file, so the user's own
+//! mode is what a restart comes back to.
+//!
+//! Transform rules aren't polled; they are checked by [`output_transform`]
+//! when a transcription is about to be pasted. Likewise rules that turn off
+//! typing as you speak are checked by [`partial_typing_blocked`] when speech
+//! starts.
+//!
+//! Platform-specific process queries live in submodules following the same
+//! backend-trait pattern used by `crate::clipboard`.
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use std::time::Duration;
+
+use flowstt_common::config::{OutputTransform, ProcessRule, ProcessRuleAction, ProcessRuleTrigger};
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::TranscriptionMode;
+use tracing::{debug, info, warn};
+
+use crate::config::current_config;
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::{set_paused_by_process, set_transcription_mode};
+use crate::state::get_service_state;
+
+/// How often process rules are evaluated
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Platform-agnostic process query backend.
+pub trait ProcessProbe: Send + Sync {
+    /// Names of all processes visible to the current user.
+    fn running_processes(&self) -> Vec<String>;
+
+    /// Name of the process owning the foreground window, if it can be determined.
+    fn foreground_process(&self) -> Option<String>;
+}
+
+/// Create the platform-specific backend.
+fn create_backend() -> Box<dyn ProcessProbe> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsProcessProbe)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::MacOSProcessProbe)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxProcessProbe)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        compile_error!("Unsupported platform for process rules");
+    }
+}
+
+/// Processes observed during one poll.
+struct ProcessSnapshot {
+    running: Vec<String>,
+    foreground: Option<String>,
+}
+
+impl ProcessSnapshot {
+    /// Query only what the given rules need.
+    fn capture(rules: &[ProcessRule]) -> Self {
+        let backend = create_backend();
+        let needs = |trigger| rules.iter().any(|r| r.trigger == trigger);
+
+        Self {
+            running: if needs(ProcessRuleTrigger::Running) {
+                backend.running_processes()
+            } else {
+                Vec::new()
+            },
+            foreground: if needs(ProcessRuleTrigger::Foreground) {
+                backend.foreground_process()
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Find the first rule with the given action that applies, returning the
+    /// name of the matching process.
+    fn find_match(&self, rules: &[ProcessRule], action: ProcessRuleAction) -> Option<String> {
+        self.find_rule(rules, |a| *a == action)
+            .map(|(_, process)| process)
+    }
+
+    /// Find the first rule with an accepted action that applies, along with
+    /// the name of the matching process.
+    fn find_rule<'a>(
+        &self,
+        rules: &'a [ProcessRule],
+        accept: impl Fn(&ProcessRuleAction) -> bool,
+    ) -> Option<(&'a ProcessRule, String)> {
+        rules
+            .iter()
+            .filter(|rule| accept(&rule.action))
+            .find_map(|rule| {
+                let process = match rule.trigger {
+                    ProcessRuleTrigger::Running => {
+                        self.running.iter().find(|name| rule.matches(name)).cloned()
+                    }
+                    ProcessRuleTrigger::Foreground => {
+                        self.foreground.clone().filter(|name| rule.matches(name))
+                    }
+                };
+                process.map(|process| (rule, process))
+            })
+    }
+}
+
+/// The transform of the first transform rule that applies now, if any.
+///
+/// Queries the processes directly, so it blocks for as long as that takes.
+pub fn output_transform() -> Option<OutputTransform> {
+    let rules: Vec<ProcessRule> = current_config()
+        .process_rules
+        .iter()
+        .filter(|rule| matches!(rule.action, ProcessRuleAction::Transform(_)))
+        .cloned()
+        .collect();
+    if rules.is_empty() {
+        return None;
+    }
+
+    let snapshot = ProcessSnapshot::capture(&rules);
+    let (rule, process) = snapshot.find_rule(&rules, |_| true)?;
+    debug!("[ProcessRules] Transforming output for {}", process);
+    match rule.action {
+        ProcessRuleAction::Transform(transform) => Some(transform),
+        _ => None,
+    }
+}
+
+/// Whether a rule turns off typing as you speak for an application running
+/// or focused now.
+///
+/// Queries the processes directly, so it blocks for as long as that takes.
+pub fn partial_typing_blocked() -> bool {
+    let rules: Vec<ProcessRule> = current_config()
+        .process_rules
+        .iter()
+        .filter(|rule| rule.action == ProcessRuleAction::NoPartialTyping)
+        .cloned()
+        .collect();
+    if rules.is_empty() {
+        return false;
+    }
+
+    let snapshot = ProcessSnapshot::capture(&rules);
+    let Some((_, process)) = snapshot.find_rule(&rules, |_| true) else {
+        return false;
+    };
+    debug!("[ProcessRules] Not typing as you speak into {}", process);
+    true
+}
+
+/// Run the process rule watcher until shutdown.
+///
+/// Rules are re-read from the config file on every poll so edits apply
+/// without a restart.
+pub async fn run_process_watcher() {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    // Process currently holding capture paused
+    let mut paused_by: Option<String> = None;
+    // Process that forced push-to-talk, and the mode to restore afterwards
+    let mut ptt_forced_by: Option<(String, TranscriptionMode)> = None;
+
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        let mut rules = current_config().process_rules.clone();
+        rules.retain(|rule| {
+            !matches!(
+                rule.action,
+                ProcessRuleAction::Transform(_) | ProcessRuleAction::NoPartialTyping
+            )
+        });
+        if rules.is_empty() && paused_by.is_none() && ptt_forced_by.is_none() {
+            continue;
+        }
+
+        let snapshot = {
+            let rules = rules.clone();
+            match tokio::task::spawn_blocking(move || ProcessSnapshot::capture(&rules)).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!("[ProcessRules] Process query failed: {}", e);
+                    continue;
+                }
+            }
+        };
+
+        // Pause rules
+        match (
+            snapshot.find_match(&rules, ProcessRuleAction::Pause),
+            &paused_by,
+        ) {
+            (Some(process), None) => {
+                info!("[ProcessRules] Pausing capture: {} detected", process);
+                set_paused_by_process(true).await;
+                broadcast_event(Response::Event {
+                    event: EventType::PausedByProcess {
+                        process: Some(process.clone()),
+                    },
+                });
+                paused_by = Some(process);
+            }
+            (None, Some(process)) => {
+                info!(
+                    "[ProcessRules] Resuming capture: {} no longer detected",
+                    process
+                );
+                paused_by = None;
+                set_paused_by_process(false).await;
+                broadcast_event(Response::Event {
+                    event: EventType::PausedByProcess { process: None },
+                });
+            }
+            _ => {}
+        }
+
+        // Push-to-talk rules
+        match (
+            snapshot.find_match(&rules, ProcessRuleAction::PushToTalk),
+            &ptt_forced_by,
+        ) {
+            (Some(process), None) => {
+                let current_mode = get_service_state().lock().await.transcription_mode;
+                if current_mode != TranscriptionMode::PushToTalk {
+                    info!(
+                        "[ProcessRules] Switching to push-to-talk: {} detected",
+                        process
+                    );
+                    set_transcription_mode(TranscriptionMode::PushToTalk, false).await;
+                    ptt_forced_by = Some((process, current_mode));
+                }
+            }
+            (None, Some((process, previous_mode))) => {
+                let current_mode = get_service_state().lock().await.transcription_mode;
+                if let Some(mode) = mode_to_restore(current_mode, *previous_mode) {
+                    info!(
+                        "[ProcessRules] Restoring {:?} mode: {} no longer detected",
+                        mode, process
+                    );
+                    set_transcription_mode(mode, false).await;
+                } else {
+                    info!(
+                        "[ProcessRules] {} no longer detected; keeping {:?} mode chosen meanwhile",
+                        process, current_mode
+                    );
+                }
+                ptt_forced_by = None;
+            }
+            _ => {}
+        }
+    }
+
+    debug!("[ProcessRules] Watcher stopped");
+}
+
+/// The mode to switch back to once a rule stops forcing push-to-talk.
+///
+/// Nothing is restored if the mode was changed while the rule applied;
+/// the user's choice wins over the mode saved when the rule kicked in.
+fn mode_to_restore(
+    current: TranscriptionMode,
+    before_rule: TranscriptionMode,
+) -> Option<TranscriptionMode> {
+    (current == TranscriptionMode::PushToTalk).then_some(before_rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, trigger: ProcessRuleTrigger, action: ProcessRuleAction) -> ProcessRule {
+        ProcessRule {
+            process_name: name.to_string(),
+            trigger,
+            action,
+        }
+    }
+
+    fn snapshot(running: &[&str], foreground: Option<&str>) -> ProcessSnapshot {
+        ProcessSnapshot {
+            running: running.iter().map(|name| name.to_string()).collect(),
+            foreground: foreground.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn running_rules_match_any_process() {
+        let rules = [rule(
+            "zoom",
+            ProcessRuleTrigger::Running,
+            ProcessRuleAction::Pause,
+        )];
+        let snapshot = snapshot(&["bash", "Zoom.exe"], Some("code"));
+
+        assert_eq!(
+            snapshot.find_match(&rules, ProcessRuleAction::Pause),
+            Some("Zoom.exe".to_string())
+        );
+        assert_eq!(
+            snapshot.find_match(&rules, ProcessRuleAction::PushToTalk),
+            None
+        );
+    }
+
+    #[test]
+    fn foreground_rules_only_match_the_focused_process() {
+        let rules = [rule(
+            "code",
+            ProcessRuleTrigger::Foreground,
+            ProcessRuleAction::PushToTalk,
+        )];
+
+        let focused = snapshot(&[], Some("Code"));
+        assert_eq!(
+            focused.find_match(&rules, ProcessRuleAction::PushToTalk),
+            Some("Code".to_string())
+        );
+
+        let background = snapshot(&["code"], Some("firefox"));
+        assert_eq!(
+            background.find_match(&rules, ProcessRuleAction::PushToTalk),
+            None
+        );
+    }
+
+    #[test]
+    fn first_applicable_rule_wins() {
+        let rules = [
+            rule(
+                "vim",
+                ProcessRuleTrigger::Foreground,
+                ProcessRuleAction::Transform(OutputTransform::SnakeCase),
+            ),
+            rule(
+                "code",
+                ProcessRuleTrigger::Foreground,
+                ProcessRuleAction::Transform(OutputTransform::CamelCase),
+            ),
+            rule(
+                "code",
+                ProcessRuleTrigger::Foreground,
+                ProcessRuleAction::Transform(OutputTransform::SnakeCase),
+            ),
+        ];
+        let snapshot = snapshot(&[], Some("code"));
+
+        let (rule, process) = snapshot.find_rule(&rules, |_| true).unwrap();
+        assert_eq!(
+            rule.action,
+            ProcessRuleAction::Transform(OutputTransform::CamelCase)
+        );
+        assert_eq!(process, "code");
+    }
+
+    #[test]
+    fn restores_the_previous_mode_only_while_still_forced() {
+        use TranscriptionMode::{Automatic, PushToTalk};
+
+        assert_eq!(mode_to_restore(PushToTalk, Automatic), Some(Automatic));
+        assert_eq!(mode_to_restore(Automatic, Automatic), None);
+    }
+}
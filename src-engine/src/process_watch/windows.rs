@@ -0,0 +1,77 @@
+//! Windows process queries.
+//!
+//! Uses:
+//! - `tasklist` for the running process list
+//! - `GetForegroundWindow` / `QueryFullProcessImageNameW` for the foreground process
+
+use super::ProcessProbe;
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, CREATE_NO_WINDOW, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+pub struct WindowsProcessProbe;
+
+impl ProcessProbe for WindowsProcessProbe {
+    fn running_processes(&self) -> Vec<String> {
+        let output = Command::new("tasklist")
+            .args(["/fo", "csv", "/nh"])
+            .creation_flags(CREATE_NO_WINDOW.0)
+            .output();
+
+        match output {
+            // Each line looks like: "Zoom.exe","1234","Console","1","120,000 K"
+            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .filter_map(|line| line.split("\",\"").next())
+                .map(|name| name.trim_start_matches('"').to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn foreground_process(&self) -> Option<String> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            let mut pid: u32 = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            if pid == 0 {
+                return None;
+            }
+
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+            let mut buf = vec![0u16; 1024];
+            let mut len = buf.len() as u32;
+            let ok = QueryFullProcessImageNameW(
+                handle,
+                PROCESS_NAME_WIN32,
+                windows::core::PWSTR(buf.as_mut_ptr()),
+                &mut len,
+            );
+
+            let _ = windows::Win32::Foundation::CloseHandle(handle);
+
+            if ok.is_err() || len == 0 {
+                return None;
+            }
+
+            let exe_path = OsString::from_wide(&buf[..len as usize]);
+            exe_path
+                .to_string_lossy()
+                .rsplit('\\')
+                .next()
+                .map(str::to_string)
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Filtering of swear words in transcribed text.
+//!
+//! Words from a built-in list and the `words` in the `profanity` section of
+//! the config file are matched as whole words, ignoring case, and masked or
+//! removed. The filter runs after redaction, on the text for the clipboard
+//! and for history (unless history keeps the raw text); the transcript and
+//! caption file sinks filter the history text again on delivery.
+
+use flowstt_common::config::{ProfanityMode, ProfanitySettings};
+use regex::Regex;
+
+use crate::redaction::RedactedOutput;
+
+/// Words filtered without any configuration
+const BUILTIN_WORDS: &[&str] = &[
+    "arse",
+    "arsehole",
+    "ass",
+    "asshole",
+    "assholes",
+    "bastard",
+    "bastards",
+    "bitch",
+    "bitches",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "cocks",
+    "crap",
+    "cunt",
+    "cunts",
+    "damn",
+    "damned",
+    "dick",
+    "dickhead",
+    "dicks",
+    "fuck",
+    "fucked",
+    "fucker",
+    "fuckers",
+    "fucking",
+    "fucks",
+    "goddamn",
+    "motherfucker",
+    "motherfuckers",
+    "motherfucking",
+    "piss",
+    "pissed",
+    "prick",
+    "shit",
+    "shits",
+    "shitty",
+    "slut",
+    "twat",
+    "wanker",
+    "whore",
+];
+
+/// A compiled word list.
+pub struct ProfanityFilter {
+    mode: ProfanityMode,
+    pattern: Regex,
+}
+
+impl ProfanityFilter {
+    /// Compile the built-in list and the user's words, or `None` if the
+    /// filter is off.
+    pub fn new(settings: &ProfanitySettings) -> Option<Self> {
+        if settings.mode == ProfanityMode::Off {
+            return None;
+        }
+        let words: Vec<String> = BUILTIN_WORDS
+            .iter()
+            .map(|word| regex::escape(word))
+            .chain(
+                settings
+                    .words
+                    .iter()
+                    .map(|word| word.trim())
+                    .filter(|word| !word.is_empty())
+                    .map(regex::escape),
+            )
+            .collect();
+        let pattern = Regex::new(&format!(r"(?i)\b(?:{})\b", words.join("|")))
+            .expect("escaped words form a valid pattern");
+        Some(Self {
+            mode: settings.mode,
+            pattern,
+        })
+    }
+
+    /// Mask or remove every listed word in `text`.
+    pub fn filter(&self, text: &str) -> String {
+        match self.mode {
+            ProfanityMode::Off => text.to_string(),
+            ProfanityMode::Mask => self
+                .pattern
+                .replace_all(text, |caps: &regex::Captures| {
+                    let mut chars = caps[0].chars();
+                    let first = chars.next().map(String::from).unwrap_or_default();
+                    first + &"*".repeat(chars.count())
+                })
+                .into_owned(),
+            ProfanityMode::Remove => {
+                let removed = self.pattern.replace_all(text, "");
+                close_gaps(&removed, text.starts_with(char::is_whitespace))
+            }
+        }
+    }
+}
+
+/// Tidy the spaces left where words were removed: runs of spaces become
+/// one, and spaces before punctuation go.
+fn close_gaps(text: &str, keep_leading: bool) -> String {
+    let mut closed = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == ' ' && closed.ends_with(' ') {
+            continue;
+        }
+        if matches!(c, ',' | '.' | '!' | '?' | ';' | ':') && closed.ends_with(' ') {
+            closed.pop();
+        }
+        closed.push(c);
+    }
+    if keep_leading {
+        closed
+    } else {
+        closed.trim_start().to_string()
+    }
+}
+
+/// Filter a single text, such as a line for a file sink.
+pub fn filter(settings: &ProfanitySettings, text: &str) -> String {
+    match ProfanityFilter::new(settings) {
+        Some(filter) => filter.filter(text),
+        None => text.to_string(),
+    }
+}
+
+/// Filter the redacted text for each output sink, leaving history raw if
+/// configured.
+pub fn apply(settings: &ProfanitySettings, output: RedactedOutput) -> RedactedOutput {
+    let Some(filter) = ProfanityFilter::new(settings) else {
+        return output;
+    };
+    RedactedOutput {
+        history: if settings.keep_raw_history {
+            output.history
+        } else {
+            filter.filter(&output.history)
+        },
+        clipboard: filter.filter(&output.clipboard),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: ProfanityMode, words: &[&str]) -> ProfanitySettings {
+        ProfanitySettings {
+            mode,
+            words: words.iter().map(|w| w.to_string()).collect(),
+            keep_raw_history: false,
+        }
+    }
+
+    #[test]
+    fn masks_whole_words_ignoring_case() {
+        let settings = settings(ProfanityMode::Mask, &[]);
+        assert_eq!(
+            filter(&settings, "Shit, the class assessment is damn late "),
+            "S***, the class assessment is d*** late "
+        );
+    }
+
+    #[test]
+    fn removes_words_and_closes_gaps() {
+        let settings = settings(ProfanityMode::Remove, &["heck"]);
+        assert_eq!(filter(&settings, "What the heck. "), "What the. ");
+        assert_eq!(filter(&settings, "Damn it, that works "), "it, that works ");
+    }
+
+    #[test]
+    fn history_can_keep_the_raw_text() {
+        let mut settings = settings(ProfanityMode::Mask, &[]);
+        settings.keep_raw_history = true;
+        let output = apply(
+            &settings,
+            RedactedOutput {
+                history: "oh crap ".to_string(),
+                clipboard: "oh crap ".to_string(),
+            },
+        );
+        assert_eq!(output.history, "oh crap ");
+        assert_eq!(output.clipboard, "oh c*** ");
+    }
+
+    #[test]
+    fn off_leaves_text_alone() {
+        assert_eq!(filter(&ProfanitySettings::default(), "shit "), "shit ");
+    }
+}
@@ -2,31 +2,39 @@
 //!
 //! This module manages the PTT lifecycle:
 //! - In PTT mode, audio capture is only active while the hotkey is held
-//! - Polls for hotkey events independently of audio loop
+//! - Waits for hotkey events independently of audio loop
 //! - Starts/stops audio capture on key press/release
 //! - Handles toggle hotkey for switching between modes
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use flowstt_common::ipc::{EventType, Response};
-use flowstt_common::TranscriptionMode;
-use tracing::{debug, error, info};
+use flowstt_common::{EngineComponent, ModeToggleSource, TranscriptionMode};
+use flowstt_dsp::{apply_gain, convert_to_mono, db_to_gain};
+use tracing::{debug, error, info, warn};
 
 use crate::audio_loop::{self, is_audio_loop_active};
 use crate::hotkey::{self, HotkeyEvent};
 use crate::ipc::broadcast_event;
 use crate::ipc::handlers::{get_transcribe_state, get_transcription_queue};
 use crate::platform;
+use crate::platform::realtime::{StopSignal, StopToken};
 use crate::processor::{VisualizationCallback, VisualizationPayload, VisualizationProcessor};
+use crate::resample::{RateNormalizer, PROCESSING_SAMPLE_RATE};
+use crate::sound_cues::{self, SoundCue};
 use crate::state::get_service_state;
+use crate::watchdog;
 
 /// Global PTT controller state
 static PTT_ACTIVE: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
 static PTT_THREAD_RUNNING: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
 
+/// Wakes the controller thread when it is stopped
+static PTT_CONTROLLER_STOP: StopSignal = StopSignal::new();
+
 fn get_ptt_active() -> Arc<AtomicBool> {
     PTT_ACTIVE
         .get_or_init(|| Arc::new(AtomicBool::new(false)))
@@ -55,9 +63,10 @@ pub fn start_ptt_controller() -> Result<(), String> {
 
     get_ptt_thread_running().store(true, Ordering::SeqCst);
     get_ptt_active().store(false, Ordering::SeqCst);
+    let stop = PTT_CONTROLLER_STOP.arm();
 
     thread::spawn(move || {
-        ptt_controller_loop();
+        ptt_controller_loop(stop);
     });
 
     Ok(())
@@ -71,6 +80,7 @@ pub fn stop_ptt_controller() {
 
     info!("[PTT Controller] Stopping...");
     get_ptt_thread_running().store(false, Ordering::SeqCst);
+    PTT_CONTROLLER_STOP.stop();
 
     // If PTT was active, stop capture
     if get_ptt_active().load(Ordering::SeqCst) {
@@ -80,8 +90,9 @@ pub fn stop_ptt_controller() {
 }
 
 /// Main PTT controller loop
-fn ptt_controller_loop() {
-    info!("[PTT] Controller polling for hotkey events...");
+fn ptt_controller_loop(stop: StopToken) {
+    info!("[PTT] Controller waiting for hotkey events...");
+    let heartbeat = watchdog::register(EngineComponent::PttController);
 
     while get_ptt_thread_running().load(Ordering::SeqCst) {
         // Check if we should stop
@@ -89,8 +100,23 @@ fn ptt_controller_loop() {
             break;
         }
 
-        // Check for hotkey events
-        if let Some(event) = hotkey::try_recv_hotkey() {
+        // The controller the watchdog started in our place owns the state now
+        if !heartbeat.beat() {
+            warn!("[PTT] Controller replaced by the watchdog, exiting");
+            return;
+        }
+
+        // Block until a hotkey event arrives. The hotkey channel is replaced
+        // whenever hotkeys are reconfigured, so fetch it again on every wake.
+        let event = match hotkey::hotkey_events() {
+            Some(events) => stop.recv(&events, audio_loop::RECV_TIMEOUT),
+            None => {
+                stop.wait(audio_loop::RECV_TIMEOUT);
+                None
+            }
+        };
+
+        if let Some(event) = event {
             match event {
                 HotkeyEvent::PttPressed => {
                     handle_ptt_pressed();
@@ -101,17 +127,44 @@ fn ptt_controller_loop() {
                 HotkeyEvent::TogglePressed => {
                     handle_toggle_pressed();
                 }
+                HotkeyEvent::DictationPressed => {
+                    crate::dictation::toggle();
+                }
+                HotkeyEvent::SpellingPressed => {
+                    crate::spelling::toggle();
+                }
             }
         }
-
-        // Sleep briefly to avoid busy-waiting
-        thread::sleep(Duration::from_millis(5));
     }
 
     info!("[PTT] Controller stopped");
     get_ptt_thread_running().store(false, Ordering::SeqCst);
 }
 
+/// Wait for one press and release of the PTT hotkey without recording.
+/// Hotkey monitoring must already be running with the keys to test.
+/// Returns false if the timeout elapses or the engine shuts down first.
+pub fn wait_for_ptt_key(timeout: Duration) -> bool {
+    let Some(events) = hotkey::hotkey_events() else {
+        return false;
+    };
+    let stop = StopToken::shutdown();
+    let deadline = Instant::now() + timeout;
+    let mut pressed = false;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || stop.is_stopped() {
+            return false;
+        }
+        match stop.recv(&events, remaining) {
+            Some(HotkeyEvent::PttPressed) => pressed = true,
+            Some(HotkeyEvent::PttReleased) if pressed => return true,
+            _ => {}
+        }
+    }
+}
+
 /// Handle PTT key press - start audio capture.
 /// Public within the crate so the test mode orchestrator can trigger PTT programmatically.
 pub(crate) fn handle_ptt_pressed() {
@@ -133,6 +186,8 @@ pub(crate) fn handle_ptt_pressed() {
     broadcast_event(Response::Event {
         event: EventType::PttPressed,
     });
+    // The cue finishes before the microphone opens so it isn't transcribed
+    sound_cues::play_and_wait(SoundCue::PttStart);
 
     // Start capture
     if let Err(e) = start_ptt_capture() {
@@ -146,6 +201,8 @@ pub(crate) fn handle_ptt_pressed() {
             },
         });
     } else {
+        crate::activity_log::speech_started();
+
         // Broadcast speech started
         broadcast_event(Response::Event {
             event: EventType::SpeechStarted,
@@ -198,11 +255,13 @@ pub(crate) fn handle_ptt_released() {
 
     // Stop capture
     stop_ptt_capture();
+    crate::activity_log::speech_ended();
 
     // Broadcast events
     broadcast_event(Response::Event {
         event: EventType::PttReleased,
     });
+    sound_cues::play(SoundCue::PttStop);
 
     broadcast_event(Response::Event {
         event: EventType::SpeechEnded { duration_ms: 0 },
@@ -221,6 +280,18 @@ fn handle_toggle_pressed() {
     info!("[Toggle] Toggle hotkey pressed");
 
     let state_arc = get_service_state();
+
+    // After an idle auto-suspend the toggle hotkey resumes capture instead
+    let suspended = futures::executor::block_on(state_arc.lock())
+        .transcribe_status
+        .auto_suspended;
+    if suspended {
+        if let Err(e) = futures::executor::block_on(crate::ipc::handlers::resume_from_suspend()) {
+            error!("[Toggle] Failed to resume capture: {}", e);
+        }
+        return;
+    }
+
     let (
         current_mode,
         auto_mode_active,
@@ -270,6 +341,10 @@ fn handle_toggle_pressed() {
             }
         }
         stop_ptt_capture();
+        crate::activity_log::speech_ended();
+        broadcast_event(Response::Event {
+            event: EventType::PttReleased,
+        });
     }
 
     // Stop any existing audio loop and capture
@@ -293,9 +368,7 @@ fn handle_toggle_pressed() {
     }
 
     // Save config
-    let mut config = crate::config::Config::load();
-    config.transcription_mode = new_mode;
-    if let Err(e) = crate::config::save_config(&config) {
+    if let Err(e) = crate::config::update_config(|config| config.transcription_mode = new_mode) {
         error!("[Toggle] Failed to save config: {}", e);
     }
 
@@ -303,6 +376,12 @@ fn handle_toggle_pressed() {
     broadcast_event(Response::Event {
         event: EventType::AutoModeToggled { mode: new_mode },
     });
+    broadcast_event(Response::Event {
+        event: EventType::AutoModeToggledBy {
+            mode: new_mode,
+            source: ModeToggleSource::Hotkey,
+        },
+    });
 
     // Start capture for the new mode
     if new_mode == TranscriptionMode::Automatic {
@@ -321,10 +400,8 @@ fn handle_toggle_pressed() {
             return;
         }
 
-        // Get sample rate from backend
-        let sample_rate = platform::get_backend()
-            .map(|b| b.sample_rate())
-            .unwrap_or(48000);
+        // Captured audio is normalized to the processing rate
+        let sample_rate = PROCESSING_SAMPLE_RATE;
 
         // Initialize transcribe state
         {
@@ -344,7 +421,7 @@ fn handle_toggle_pressed() {
                 broadcast_event(Response::Event {
                     event: EventType::CaptureStateChanged {
                         capturing: false,
-                        error: Some(e),
+                        error: Some(e.to_string()),
                     },
                 });
                 return;
@@ -409,10 +486,10 @@ fn start_ptt_capture() -> Result<(), String> {
         )
     };
 
-    // Get sample rate from backend
-    let sample_rate = platform::get_backend()
-        .map(|b| b.sample_rate())
-        .unwrap_or(48000);
+    // Captured audio is normalized to the processing rate
+    let sample_rate = PROCESSING_SAMPLE_RATE;
+
+    let ptt_max_duration_secs = crate::config::current_config().ptt_max_duration_secs;
 
     // Initialize transcribe state for PTT mode
     {
@@ -420,6 +497,7 @@ fn start_ptt_capture() -> Result<(), String> {
         let mut transcribe = transcribe_state.lock().unwrap();
         transcribe.init_for_capture(sample_rate, 2);
         transcribe.set_ptt_mode(true); // Disable automatic segmentation
+        transcribe.set_ptt_max_duration(ptt_max_duration_secs);
         transcribe.activate();
         // Immediately start speech segment (no lookback in PTT mode)
         transcribe.on_speech_started(0);
@@ -487,6 +565,9 @@ fn get_ptt_audio_loop_active() -> Arc<AtomicBool> {
         .clone()
 }
 
+/// Wakes the PTT audio loop when it is stopped
+static PTT_AUDIO_LOOP_STOP: StopSignal = StopSignal::new();
+
 /// Start PTT audio processing loop (simplified - no VAD, just process audio)
 fn start_ptt_audio_loop() {
     if get_ptt_audio_loop_active().load(Ordering::SeqCst) {
@@ -495,11 +576,10 @@ fn start_ptt_audio_loop() {
 
     let loop_active = get_ptt_audio_loop_active();
     loop_active.store(true, Ordering::SeqCst);
+    let stop = PTT_AUDIO_LOOP_STOP.arm();
 
-    // Get sample rate from backend
-    let sample_rate = platform::get_backend()
-        .map(|b| b.sample_rate())
-        .unwrap_or(48000);
+    // Captured audio is normalized to the processing rate
+    let sample_rate = PROCESSING_SAMPLE_RATE;
 
     let transcribe_state = get_transcribe_state();
 
@@ -507,10 +587,13 @@ fn start_ptt_audio_loop() {
         debug!("[PTT AudioLoop] Starting PTT audio processing loop");
 
         // Create visualization processor
-        let mut viz_processor = VisualizationProcessor::new(sample_rate, 256);
+        let mut viz_processor = VisualizationProcessor::new(sample_rate);
         viz_processor.set_callback(Arc::new(PttVisualizationBroadcaster));
 
+        let mut normalizer = RateNormalizer::new();
+        let mut backlog: Vec<f32> = Vec::new();
         let loop_active = get_ptt_audio_loop_active();
+        let gain = audio_loop::current_device_calibration().and_then(|c| db_to_gain(c.gain_db));
 
         loop {
             // Check if we should stop
@@ -523,25 +606,43 @@ fn start_ptt_audio_loop() {
                 break;
             }
 
-            // Try to receive audio from backend
-            let audio_data = platform::get_backend().and_then(|b| b.try_recv());
+            // Wait for audio from the backend; stopping the loop wakes this early
+            let audio_data = match platform::get_backend() {
+                Some(backend) => backend.recv(&stop, audio_loop::RECV_TIMEOUT),
+                None => {
+                    stop.wait(audio_loop::RECV_TIMEOUT);
+                    None
+                }
+            };
 
             if let Some(data) = audio_data {
+                let mut data = normalizer.normalize(data);
+                if let Some(gain) = gain {
+                    apply_gain(&mut data.samples, gain);
+                }
+
                 // Convert to mono for visualization
                 let mono_samples = convert_to_mono(&data.samples, data.channels as usize);
 
                 // Process visualization
                 viz_processor.process(&mono_samples);
 
+                // Held keys stand in for detected speech
+                crate::recent_audio::push(&mono_samples, is_ptt_active());
+
                 // Write audio to transcribe state (no VAD - PTT controller manages segments)
+                // If the state is locked elsewhere, hold the samples for the next pass
+                backlog.extend_from_slice(&data.samples);
                 if let Ok(mut transcribe) = transcribe_state.try_lock() {
                     if transcribe.is_active {
-                        transcribe.process_samples(&data.samples);
+                        transcribe.process_samples(&backlog);
                     }
+                    backlog.clear();
+                } else if backlog.len() > audio_loop::MAX_BACKLOG_SAMPLES {
+                    let excess = backlog.len() - audio_loop::MAX_BACKLOG_SAMPLES;
+                    backlog.drain(..excess);
+                    platform::realtime::record_dropped_samples(excess);
                 }
-            } else {
-                // No data available, sleep briefly
-                thread::sleep(Duration::from_millis(1));
             }
         }
 
@@ -552,17 +653,7 @@ fn start_ptt_audio_loop() {
 /// Stop PTT audio processing loop
 fn stop_ptt_audio_loop() {
     get_ptt_audio_loop_active().store(false, Ordering::SeqCst);
-}
-
-/// Convert multi-channel audio to mono
-fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return samples.to_vec();
-    }
-    samples
-        .chunks(channels)
-        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-        .collect()
+    PTT_AUDIO_LOOP_STOP.stop();
 }
 
 /// Broadcaster for PTT visualization events
@@ -0,0 +1,142 @@
+//! Recently captured audio.
+//!
+//! The audio loop keeps the last [`MAX_RECENT_AUDIO_SECS`] seconds of mono
+//! audio along with the speech detector's verdict, so clients can render a
+//! scrollable recent waveform and replay the last utterance. A window is
+//! handed out as a WAV file plus a per-column waveform summary, since the
+//! audio itself is far too large for an IPC message.
+//!
+//! Nothing is kept while privacy mode is on. The WAV file is written
+//! unencrypted, so windows aren't available while history encryption is on.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+
+use flowstt_common::{RecentAudioWindow, MAX_RECENT_AUDIO_SECS};
+
+use crate::history::TranscriptionHistory;
+use crate::resample::PROCESSING_SAMPLE_RATE;
+
+/// Most waveform columns in a window
+const MAX_COLUMNS: usize = 600;
+
+/// Samples kept at most
+const MAX_SAMPLES: usize = PROCESSING_SAMPLE_RATE as usize * MAX_RECENT_AUDIO_SECS as usize;
+
+struct RecentAudio {
+    samples: VecDeque<f32>,
+    /// Length and speech flag of each chunk in `samples`, oldest first
+    chunks: VecDeque<(usize, bool)>,
+}
+
+static RECENT: Mutex<RecentAudio> = Mutex::new(RecentAudio {
+    samples: VecDeque::new(),
+    chunks: VecDeque::new(),
+});
+
+/// Add a chunk of mono audio, dropping what is now too old.
+pub(crate) fn push(samples: &[f32], speaking: bool) {
+    if crate::privacy::is_privacy_mode() || samples.is_empty() {
+        return;
+    }
+
+    let mut recent = RECENT.lock().unwrap();
+    recent.samples.extend(samples);
+    recent.chunks.push_back((samples.len(), speaking));
+
+    let mut excess = recent.samples.len().saturating_sub(MAX_SAMPLES);
+    recent.samples.drain(..excess);
+    while excess > 0 {
+        let Some(oldest) = recent.chunks.front_mut() else {
+            break;
+        };
+        if oldest.0 <= excess {
+            excess -= oldest.0;
+            recent.chunks.pop_front();
+        } else {
+            oldest.0 -= excess;
+            excess = 0;
+        }
+    }
+}
+
+/// Forget all recent audio.
+pub fn clear() {
+    let mut recent = RECENT.lock().unwrap();
+    recent.samples.clear();
+    recent.chunks.clear();
+}
+
+/// Write the last `seconds` of audio to the recent audio WAV file and
+/// summarize it for display.
+pub fn window(seconds: u32) -> Result<RecentAudioWindow, String> {
+    if crate::config::current_config().encrypt_history {
+        return Err("Recent audio is unavailable while history encryption is on".to_string());
+    }
+
+    let (samples, speech) = {
+        let recent = RECENT.lock().unwrap();
+        let count = recent
+            .samples
+            .len()
+            .min(seconds as usize * PROCESSING_SAMPLE_RATE as usize);
+        let start = recent.samples.len() - count;
+        let samples: Vec<f32> = recent.samples.range(start..).copied().collect();
+
+        // Speech flag for each sample in the window
+        let mut speech: Vec<bool> = Vec::with_capacity(count);
+        let mut skip = start;
+        for &(len, speaking) in &recent.chunks {
+            let take = len.saturating_sub(skip);
+            skip = skip.saturating_sub(len);
+            speech.resize(speech.len() + take, speaking);
+        }
+        (samples, speech)
+    };
+    if samples.is_empty() {
+        return Err("No audio has been captured recently".to_string());
+    }
+
+    let dir = TranscriptionHistory::data_dir();
+    crate::disk_space::ensure_room(&dir, samples.len() as u64 * 4)?;
+    let path = dir.join("recent-audio.wav");
+    write_wav(&samples, &path)?;
+
+    let column = samples.len().div_ceil(MAX_COLUMNS);
+    Ok(RecentAudioWindow {
+        wav_path: path.to_string_lossy().to_string(),
+        duration_ms: samples.len() as u64 * 1000 / u64::from(PROCESSING_SAMPLE_RATE),
+        peaks: samples
+            .chunks(column)
+            .map(|chunk| chunk.iter().fold(0.0f32, |peak, s| peak.max(s.abs())))
+            .collect(),
+        speech: speech
+            .chunks(column)
+            .map(|chunk| chunk.contains(&true))
+            .collect(),
+    })
+}
+
+/// Write plain 32-bit float mono WAV at the processing sample rate.
+fn write_wav(samples: &[f32], path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    }
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: PROCESSING_SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    for &sample in samples {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize {:?}: {}", path, e))
+}
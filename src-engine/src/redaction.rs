@@ -0,0 +1,226 @@
+//! Redaction of sensitive content in transcribed text.
+//!
+//! Rules from the `redaction` section of the config file are compiled into
+//! regular expressions and applied before text reaches the clipboard or the
+//! history store. Each match is replaced with [`REDACTED`].
+//!
+//! Card numbers are only redacted when they pass the Luhn check, so order
+//! numbers and other long digit runs are left alone.
+
+use flowstt_common::config::{RedactionRule, RedactionSettings};
+use regex::{Captures, Regex};
+use tracing::warn;
+
+/// Replacement text for redacted spans
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Credit card numbers: 13-19 digits, optionally grouped by spaces or dashes
+const CREDIT_CARD_PATTERN: &str = r"\b\d(?:[ -]?\d){12,18}\b";
+
+/// Email addresses
+const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+
+/// Compiled redaction rules.
+pub struct Redactor {
+    patterns: Vec<Pattern>,
+}
+
+/// A compiled rule.
+struct Pattern {
+    regex: Regex,
+    /// Only redact matches whose digits pass the Luhn check
+    luhn: bool,
+}
+
+impl Redactor {
+    /// Compile the configured rules. Invalid custom patterns are logged and skipped.
+    pub fn new(rules: &[RedactionRule]) -> Self {
+        let patterns = rules
+            .iter()
+            .filter_map(|rule| {
+                let pattern = match rule {
+                    RedactionRule::CreditCard => CREDIT_CARD_PATTERN,
+                    RedactionRule::Email => EMAIL_PATTERN,
+                    RedactionRule::Custom { pattern } => pattern.as_str(),
+                };
+                match Regex::new(pattern) {
+                    Ok(regex) => Some(Pattern {
+                        regex,
+                        luhn: *rule == RedactionRule::CreditCard,
+                    }),
+                    Err(e) => {
+                        warn!("[Redaction] Ignoring invalid pattern {:?}: {}", pattern, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { patterns }
+    }
+
+    /// Replace every match of every rule with [`REDACTED`].
+    pub fn redact(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(text.to_string(), |text, pattern| pattern.redact(&text))
+    }
+}
+
+impl Pattern {
+    fn redact(&self, text: &str) -> String {
+        self.regex
+            .replace_all(text, |caps: &Captures| {
+                let found = &caps[0];
+                if self.luhn && !passes_luhn(found) {
+                    found.to_string()
+                } else {
+                    REDACTED.to_string()
+                }
+            })
+            .into_owned()
+    }
+}
+
+/// Whether the digits in `number` have a valid Luhn check digit, as every
+/// payment card number does.
+fn passes_luhn(number: &str) -> bool {
+    let sum: u32 = number
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Text prepared for each output sink.
+pub struct RedactedOutput {
+    /// Text to store in history and show in the UI
+    pub history: String,
+    /// Text to copy to the clipboard and paste
+    pub clipboard: String,
+}
+
+/// Apply the configured redaction rules to a transcription, honoring the
+/// per-sink bypass switches.
+pub fn apply(settings: &RedactionSettings, text: &str) -> RedactedOutput {
+    if settings.rules.is_empty() {
+        return RedactedOutput {
+            history: text.to_string(),
+            clipboard: text.to_string(),
+        };
+    }
+
+    let redactor = Redactor::new(&settings.rules);
+    let redacted = redactor.redact(text);
+    let pick = |bypass: bool| {
+        if bypass {
+            text.to_string()
+        } else {
+            redacted.clone()
+        }
+    };
+
+    RedactedOutput {
+        history: pick(settings.bypass_history),
+        clipboard: pick(settings.bypass_clipboard),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(rules: &[RedactionRule], text: &str) -> String {
+        Redactor::new(rules).redact(text)
+    }
+
+    #[test]
+    fn card_numbers_are_redacted_however_they_are_grouped() {
+        let rules = [RedactionRule::CreditCard];
+        for card in [
+            "4111111111111111",
+            "4111 1111 1111 1111",
+            "4111-1111-1111-1111",
+            "3782 822463 10005",
+        ] {
+            assert_eq!(
+                redact(&rules, &format!("my card is {} thanks", card)),
+                "my card is [REDACTED] thanks",
+                "{}",
+                card
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_failing_the_luhn_check_are_kept() {
+        let rules = [RedactionRule::CreditCard];
+        assert_eq!(
+            redact(&rules, "order 4111111111111112 shipped"),
+            "order 4111111111111112 shipped"
+        );
+        // Too short to be a card
+        assert_eq!(redact(&rules, "call 555 0100"), "call 555 0100");
+    }
+
+    #[test]
+    fn emails_are_redacted() {
+        assert_eq!(
+            redact(
+                &[RedactionRule::Email],
+                "write to jane.doe+work@example.co.uk or bob@test.io"
+            ),
+            "write to [REDACTED] or [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn custom_patterns_apply_and_invalid_ones_are_skipped() {
+        let rules = [
+            RedactionRule::Custom {
+                pattern: r"(?i)project \w+".to_string(),
+            },
+            RedactionRule::Custom {
+                pattern: "(unclosed".to_string(),
+            },
+        ];
+        assert_eq!(
+            redact(&rules, "status of Project Falcon"),
+            "status of [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn bypassed_sinks_get_the_original_text() {
+        let text = "mail me at jane@example.com";
+        let mut settings = RedactionSettings {
+            rules: vec![RedactionRule::Email],
+            bypass_clipboard: false,
+            bypass_history: true,
+        };
+
+        let output = apply(&settings, text);
+        assert_eq!(output.history, text);
+        assert_eq!(output.clipboard, "mail me at [REDACTED]");
+
+        settings.bypass_history = false;
+        settings.bypass_clipboard = true;
+        let output = apply(&settings, text);
+        assert_eq!(output.history, "mail me at [REDACTED]");
+        assert_eq!(output.clipboard, text);
+    }
+
+    #[test]
+    fn no_rules_leaves_text_unchanged() {
+        let output = apply(&RedactionSettings::default(), "4111111111111111");
+        assert_eq!(output.history, "4111111111111111");
+        assert_eq!(output.clipboard, "4111111111111111");
+    }
+}
@@ -0,0 +1,86 @@
+//! Sample-rate normalization for captured audio.
+//!
+//! Backends deliver audio at whatever rate the device or audio server runs
+//! at, which is not always 48kHz (many headsets only offer 44.1kHz or 16kHz).
+//! The audio loops pass every chunk through a [`RateNormalizer`] so speech
+//! detection, visualization and recording always run at
+//! [`PROCESSING_SAMPLE_RATE`].
+
+use flowstt_dsp::StreamResampler;
+
+use crate::platform::AudioData;
+
+/// Internal processing sample rate in Hz
+pub const PROCESSING_SAMPLE_RATE: u32 = 48000;
+
+/// Converts backend audio to [`PROCESSING_SAMPLE_RATE`].
+///
+/// Audio already at the processing rate passes through untouched. A
+/// resampler is created on the first chunk at a different rate and rebuilt
+/// if the rate or channel count changes. The primary-only copy of mixed
+/// audio has a resampler of its own.
+#[derive(Default)]
+pub struct RateNormalizer {
+    stream: Option<StreamResampler>,
+    primary_stream: Option<StreamResampler>,
+}
+
+impl RateNormalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Normalize one chunk of captured audio.
+    ///
+    /// The resampler works in fixed-size blocks, so output may be empty while
+    /// input is buffered.
+    pub fn normalize(&mut self, data: AudioData) -> AudioData {
+        if data.sample_rate == PROCESSING_SAMPLE_RATE || data.sample_rate == 0 {
+            self.stream = None;
+            self.primary_stream = None;
+            return data;
+        }
+
+        let channels = data.channels.max(1);
+        let Some(samples) = resample(&mut self.stream, data.sample_rate, channels, &data.samples)
+        else {
+            return data;
+        };
+        let primary = match data.primary {
+            Some(primary) => resample(
+                &mut self.primary_stream,
+                data.sample_rate,
+                channels,
+                &primary,
+            ),
+            None => {
+                self.primary_stream = None;
+                None
+            }
+        };
+
+        AudioData {
+            samples,
+            channels,
+            sample_rate: PROCESSING_SAMPLE_RATE,
+            primary,
+        }
+    }
+}
+
+/// Resample with the stream in `slot`, replacing it if the format changed.
+/// Returns `None` if no resampler can be created for the format.
+fn resample(
+    slot: &mut Option<StreamResampler>,
+    source_rate: u32,
+    channels: u16,
+    samples: &[f32],
+) -> Option<Vec<f32>> {
+    let mut stream = match slot.take() {
+        Some(s) if s.source_rate() == source_rate && s.channels() == channels => s,
+        _ => StreamResampler::new(source_rate, PROCESSING_SAMPLE_RATE, channels)?,
+    };
+    let output = stream.process(samples);
+    *slot = Some(stream);
+    Some(output)
+}
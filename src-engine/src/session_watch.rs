@@ -0,0 +1,194 @@
+//! Windows session changes: lock, unlock and Remote Desktop reconnects.
+//!
+//! A message-only window registered with `WTSRegisterSessionNotification`
+//! receives `WM_WTSSESSION_CHANGE`. While the session is locked, capture is
+//! paused if `pause_on_lock` is on in the config file, so nothing said at an
+//! unattended desk is transcribed. Raw Input stops reaching the hotkey
+//! listener while the session is locked or shown on another console, and the
+//! audio endpoints change when a Remote Desktop client connects, so after an
+//! unlock or reconnect capture and hotkey monitoring are started again with
+//! fresh streams and a fresh listener.
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::{debug, info, warn};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::RemoteDesktop::{
+    WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, PeekMessageW, RegisterClassW,
+    TranslateMessage, UnregisterClassW, HWND_MESSAGE, MSG, PM_REMOVE, WM_WTSSESSION_CHANGE,
+    WNDCLASSW, WS_OVERLAPPED,
+};
+
+use crate::config::current_config;
+use crate::ipc::handlers::{is_capture_active, restart_capture, set_paused_by_lock};
+
+/// `WM_WTSSESSION_CHANGE` reasons
+const WTS_CONSOLE_CONNECT: usize = 0x1;
+const WTS_REMOTE_CONNECT: usize = 0x3;
+const WTS_SESSION_LOCK: usize = 0x7;
+const WTS_SESSION_UNLOCK: usize = 0x8;
+
+/// How often the message loop checks for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Wait after a reconnect before capture is started again, for the
+/// session's audio devices to appear
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// A change to the session FlowSTT runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionEvent {
+    Locked,
+    Unlocked,
+    /// The session was connected to the local console or a Remote Desktop
+    /// client
+    Connected,
+}
+
+thread_local! {
+    static EVENTS: RefCell<Option<UnboundedSender<SessionEvent>>> = const { RefCell::new(None) };
+}
+
+/// Follow session changes until shutdown.
+pub async fn run_session_watcher() {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let spawned = std::thread::Builder::new()
+        .name("session-watch".to_string())
+        .spawn(move || {
+            if let Err(e) = watch(sender) {
+                warn!("[Session] Session changes won't be detected: {}", e);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("[Session] Failed to start the session monitor: {}", e);
+        return;
+    }
+
+    while let Some(event) = events.recv().await {
+        if crate::is_shutdown_requested() {
+            break;
+        }
+        match event {
+            SessionEvent::Locked => {
+                info!("[Session] Session locked");
+                if current_config().pause_on_lock {
+                    set_paused_by_lock(true).await;
+                }
+            }
+            SessionEvent::Unlocked => {
+                info!("[Session] Session unlocked");
+                let paused = {
+                    let state_arc = crate::state::get_service_state();
+                    let state = state_arc.lock().await;
+                    state.paused_by_lock
+                };
+                if paused {
+                    // Resuming starts capture and hotkeys from scratch
+                    set_paused_by_lock(false).await;
+                } else {
+                    restart_capture(is_capture_active().await).await;
+                }
+            }
+            SessionEvent::Connected => {
+                info!("[Session] Session connected");
+                tokio::time::sleep(SETTLE_DELAY).await;
+                restart_capture(is_capture_active().await).await;
+            }
+        }
+    }
+
+    debug!("[Session] Watcher stopped");
+}
+
+/// Send session changes to `events` from a message-only window, blocking
+/// until shutdown.
+fn watch(events: UnboundedSender<SessionEvent>) -> Result<(), String> {
+    unsafe {
+        let class_name = windows::core::w!("FlowSTT_SessionClass");
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&wc) == 0 {
+            let err = windows::Win32::Foundation::GetLastError();
+            if err != windows::Win32::Foundation::ERROR_CLASS_ALREADY_EXISTS {
+                return Err(format!("Failed to register window class (error {:?})", err));
+            }
+        }
+
+        let hwnd = CreateWindowExW(
+            Default::default(),
+            class_name,
+            windows::core::w!("FlowSTT Session"),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE, // Message-only window
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| format!("Failed to create message window: {}", e))?;
+
+        if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+            let _ = DestroyWindow(hwnd);
+            let _ = UnregisterClassW(class_name, None);
+            return Err(format!(
+                "Failed to register for session notifications: {}",
+                e
+            ));
+        }
+
+        EVENTS.with(|slot| *slot.borrow_mut() = Some(events));
+        debug!("[Session] Watching for session changes");
+
+        let mut msg = MSG::default();
+        while !crate::is_shutdown_requested() {
+            while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        EVENTS.with(|slot| *slot.borrow_mut() = None);
+        let _ = WTSUnRegisterSessionNotification(hwnd);
+        let _ = DestroyWindow(hwnd);
+        let _ = UnregisterClassW(class_name, None);
+    }
+    Ok(())
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_WTSSESSION_CHANGE {
+        let event = match wparam.0 {
+            WTS_SESSION_LOCK => Some(SessionEvent::Locked),
+            WTS_SESSION_UNLOCK => Some(SessionEvent::Unlocked),
+            WTS_CONSOLE_CONNECT | WTS_REMOTE_CONNECT => Some(SessionEvent::Connected),
+            _ => None,
+        };
+        if let Some(event) = event {
+            EVENTS.with(|slot| {
+                if let Some(events) = slot.borrow().as_ref() {
+                    let _ = events.send(event);
+                }
+            });
+        }
+        return LRESULT(0);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
@@ -0,0 +1,104 @@
+//! Graceful two-phase shutdown.
+//!
+//! Capture is stopped first so no new audio is accepted; the segment being
+//! recorded is finalized and queued like any other. Queued segments are then
+//! given a bounded amount of time to finish transcribing so their results
+//! still reach history and the clipboard. History is written out last.
+//! Each phase is announced with a `ShutdownProgress` event before the final
+//! `Shutdown` event.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::ShutdownPhase;
+use tracing::{info, warn};
+
+use crate::history::get_history;
+use crate::ipc::broadcast_event;
+use crate::ipc::handlers::{get_transcription_queue, stop_capture};
+
+/// Longest to wait for queued segments before discarding them
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often drain progress is checked
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Whether a graceful shutdown is in progress. New capture is refused while set.
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Allow a new shutdown once an engine starts again.
+pub(crate) fn reset() {
+    SHUTTING_DOWN.store(false, Ordering::SeqCst);
+}
+
+fn report(phase: ShutdownPhase, pending_segments: usize) {
+    info!(
+        "[Shutdown] {:?} ({} segment(s) pending)",
+        phase, pending_segments
+    );
+    broadcast_event(Response::Event {
+        event: EventType::ShutdownProgress {
+            phase,
+            pending_segments,
+        },
+    });
+}
+
+/// Stop capture, drain the transcription queue, flush history, then request
+/// engine shutdown. Calling it again while a shutdown is running does nothing.
+pub async fn graceful_shutdown() {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let queue = get_transcription_queue();
+
+    // Phase 1: stop accepting audio
+    report(ShutdownPhase::StoppingCapture, queue.queue_depth());
+    stop_capture().await;
+
+    // Phase 2: let the worker finish what is already queued
+    let mut pending = queue.queue_depth();
+    report(ShutdownPhase::DrainingQueue, pending);
+    queue.stop_worker();
+
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while queue.is_worker_running() {
+        if Instant::now() >= deadline {
+            warn!(
+                "[Shutdown] Transcription did not finish in {:?}, discarding {} segment(s)",
+                DRAIN_TIMEOUT,
+                queue.queue_depth()
+            );
+            queue.clear();
+            break;
+        }
+
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+
+        let depth = queue.queue_depth();
+        if depth != pending {
+            pending = depth;
+            report(ShutdownPhase::DrainingQueue, pending);
+        }
+    }
+
+    // Phase 3: make sure history is on disk
+    report(ShutdownPhase::FlushingHistory, queue.queue_depth());
+    let history = get_history();
+    if let Err(e) = history.lock().unwrap().save() {
+        warn!("[Shutdown] Failed to save history: {}", e);
+    }
+
+    report(ShutdownPhase::Complete, 0);
+    broadcast_event(Response::Event {
+        event: EventType::Shutdown,
+    });
+
+    crate::request_shutdown();
+}
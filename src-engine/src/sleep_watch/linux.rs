@@ -0,0 +1,76 @@
+//! Linux sleep and wake detection.
+//!
+//! Follows systemd-logind's `PrepareForSleep` signal through
+//! `gdbus monitor` on the system bus. The signal carries `true` before the
+//! system sleeps and `false` once it has resumed.
+
+use super::{SleepEvent, SleepMonitor};
+use std::io::{BufRead, BufReader};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+
+const SIGNAL: &str = "org.freedesktop.login1.Manager.PrepareForSleep";
+
+pub struct LinuxSleepMonitor;
+
+impl SleepMonitor for LinuxSleepMonitor {
+    fn watch(&self, events: UnboundedSender<SleepEvent>) -> Result<(), String> {
+        let mut command = Command::new("gdbus");
+        command
+            .args([
+                "monitor",
+                "--system",
+                "--dest",
+                "org.freedesktop.login1",
+                "--object-path",
+                "/org/freedesktop/login1",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        // Don't leave the monitor running if the service goes away
+        unsafe {
+            command.pre_exec(|| {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                Ok(())
+            });
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to run gdbus: {}", e))?;
+        let stdout = child.stdout.take().ok_or("gdbus has no output")?;
+        debug!("[Sleep] Watching logind for sleep and wake");
+
+        // Lines look like:
+        // /org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if crate::is_shutdown_requested() {
+                break;
+            }
+            let Some((_, args)) = line.split_once(SIGNAL) else {
+                continue;
+            };
+            let event = if args.contains("true") {
+                SleepEvent::Sleeping
+            } else {
+                SleepEvent::Woke
+            };
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+
+        let _ = child.kill();
+        let status = child
+            .wait()
+            .map_err(|e| format!("Failed to wait for gdbus: {}", e))?;
+        if crate::is_shutdown_requested() {
+            return Ok(());
+        }
+        Err(format!("gdbus monitor stopped ({})", status))
+    }
+}
@@ -0,0 +1,131 @@
+//! macOS sleep and wake detection.
+//!
+//! Registers for system power notifications with `IORegisterForSystemPower`
+//! and runs their run loop on the monitor thread. These are the notifications
+//! behind `NSWorkspaceWillSleepNotification` and
+//! `NSWorkspaceDidWakeNotification`, without needing the main thread's run
+//! loop. Every sleep notification must be acknowledged with
+//! `IOAllowPowerChange` or the system waits 30 seconds before sleeping.
+
+use super::{SleepEvent, SleepMonitor};
+use std::ffi::c_void;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+
+/// `kIOMessageCanSystemSleep`: idle sleep is about to start and may be vetoed
+const CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+/// `kIOMessageSystemWillSleep`: the system is going to sleep
+const SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+/// `kIOMessageSystemHasPoweredOn`: the system has woken up
+const SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+
+type IONotificationPortRef = *mut c_void;
+type CFRunLoopSourceRef = *mut c_void;
+type CFRunLoopRef = *mut c_void;
+type CFStringRef = *const c_void;
+
+type IOServiceInterestCallback =
+    extern "C" fn(refcon: *mut c_void, service: u32, message_type: u32, argument: *mut c_void);
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        port: *mut IONotificationPortRef,
+        callback: IOServiceInterestCallback,
+        notifier: *mut u32,
+    ) -> u32;
+    fn IODeregisterForSystemPower(notifier: *mut u32) -> i32;
+    fn IOAllowPowerChange(kernel_port: u32, notification_id: isize) -> i32;
+    fn IOServiceClose(connect: u32) -> i32;
+    fn IONotificationPortGetRunLoopSource(port: IONotificationPortRef) -> CFRunLoopSourceRef;
+    fn IONotificationPortDestroy(port: IONotificationPortRef);
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopDefaultMode: CFStringRef;
+
+    fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRemoveSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRunInMode(
+        mode: CFStringRef,
+        seconds: f64,
+        return_after_source_handled: bool,
+    ) -> i32;
+}
+
+/// State the power callback needs, passed to it as its refcon
+struct PowerContext {
+    /// Connection to the root power domain, for acknowledging sleep
+    root_port: u32,
+    events: UnboundedSender<SleepEvent>,
+}
+
+pub struct MacOSSleepMonitor;
+
+impl SleepMonitor for MacOSSleepMonitor {
+    fn watch(&self, events: UnboundedSender<SleepEvent>) -> Result<(), String> {
+        let context = Box::into_raw(Box::new(PowerContext {
+            root_port: 0,
+            events,
+        }));
+
+        unsafe {
+            let mut port: IONotificationPortRef = std::ptr::null_mut();
+            let mut notifier: u32 = 0;
+            let root_port = IORegisterForSystemPower(
+                context.cast(),
+                &mut port,
+                on_power_message,
+                &mut notifier,
+            );
+            if root_port == 0 {
+                drop(Box::from_raw(context));
+                return Err("IORegisterForSystemPower failed".to_string());
+            }
+            // Callbacks only arrive while the run loop below runs
+            (*context).root_port = root_port;
+
+            let source = IONotificationPortGetRunLoopSource(port);
+            let run_loop = CFRunLoopGetCurrent();
+            CFRunLoopAddSource(run_loop, source, kCFRunLoopDefaultMode);
+            debug!("[Sleep] Watching system power notifications for sleep and wake");
+
+            while !crate::is_shutdown_requested() {
+                CFRunLoopRunInMode(kCFRunLoopDefaultMode, 1.0, false);
+            }
+
+            CFRunLoopRemoveSource(run_loop, source, kCFRunLoopDefaultMode);
+            IODeregisterForSystemPower(&mut notifier);
+            IOServiceClose(root_port);
+            IONotificationPortDestroy(port);
+            drop(Box::from_raw(context));
+        }
+        Ok(())
+    }
+}
+
+extern "C" fn on_power_message(
+    refcon: *mut c_void,
+    _service: u32,
+    message_type: u32,
+    argument: *mut c_void,
+) {
+    let context = unsafe { &*(refcon as *const PowerContext) };
+    match message_type {
+        // Idle sleep isn't held up; FlowSTT doesn't keep the machine awake
+        CAN_SYSTEM_SLEEP => {
+            unsafe { IOAllowPowerChange(context.root_port, argument as isize) };
+        }
+        SYSTEM_WILL_SLEEP => {
+            let _ = context.events.send(SleepEvent::Sleeping);
+            unsafe { IOAllowPowerChange(context.root_port, argument as isize) };
+        }
+        SYSTEM_HAS_POWERED_ON => {
+            let _ = context.events.send(SleepEvent::Woke);
+        }
+        _ => {}
+    }
+}
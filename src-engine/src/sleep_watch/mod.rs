@@ -0,0 +1,121 @@
+//! Restarting capture after the system sleeps.
+//!
+//! Audio streams and hotkey listeners often don't survive a suspend: the
+//! devices behind them go away and come back as new ones. A platform monitor
+//! reports when the system is about to sleep and when it has woken up.
+//! Capture and hotkey monitoring are stopped before sleep and, once the
+//! system is back and its devices have had a moment to reappear, started
+//! again from scratch, with the usual retries if a device is still missing.
+//!
+//! Platform-specific monitors live in submodules following the same
+//! backend-trait pattern used by `crate::power`.
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use std::time::Duration;
+
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::{debug, info, warn};
+
+use crate::ipc::handlers::{is_capture_active, restart_capture, stop_capture};
+
+/// Wait after waking before capture is started again, for devices to return
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// A change in the system's sleep state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepEvent {
+    /// The system is about to sleep
+    Sleeping,
+    /// The system has woken up
+    Woke,
+}
+
+/// Platform-agnostic sleep and wake monitor.
+pub trait SleepMonitor: Send {
+    /// Send each sleep and wake of the system to `events`, blocking until
+    /// shutdown or until the monitor fails.
+    fn watch(&self, events: UnboundedSender<SleepEvent>) -> Result<(), String>;
+}
+
+/// Create the platform-specific backend.
+fn create_backend() -> Box<dyn SleepMonitor> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsSleepMonitor)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::MacOSSleepMonitor)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxSleepMonitor)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        compile_error!("Unsupported platform for sleep detection");
+    }
+}
+
+/// Stop capture before the system sleeps and restart it after, until
+/// shutdown.
+pub async fn run_sleep_watcher() {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let spawned = std::thread::Builder::new()
+        .name("sleep-watch".to_string())
+        .spawn(move || {
+            if let Err(e) = create_backend().watch(sender) {
+                warn!("[Sleep] Sleep and wake won't be detected: {}", e);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("[Sleep] Failed to start the sleep monitor: {}", e);
+        return;
+    }
+
+    // Whether capture was running when the system went to sleep
+    let mut asleep_with_capture: Option<bool> = None;
+    while let Some(event) = events.recv().await {
+        if crate::is_shutdown_requested() {
+            break;
+        }
+        match event {
+            SleepEvent::Sleeping => {
+                if asleep_with_capture.is_some() {
+                    continue;
+                }
+                let active = is_capture_active().await;
+                info!("[Sleep] System is going to sleep");
+                if active {
+                    stop_capture().await;
+                }
+                asleep_with_capture = Some(active);
+            }
+            SleepEvent::Woke => {
+                info!("[Sleep] System woke up");
+                // The clock stood still while asleep; don't count it as a stall
+                crate::watchdog::reset_beats();
+                // Without a sleep event, whatever runs now ran through the sleep
+                let was_active = match asleep_with_capture.take() {
+                    Some(active) => active,
+                    None => is_capture_active().await,
+                };
+                tokio::time::sleep(SETTLE_DELAY).await;
+                restart_capture(was_active).await;
+            }
+        }
+    }
+
+    debug!("[Sleep] Watcher stopped");
+}
@@ -0,0 +1,111 @@
+//! Windows sleep and wake detection.
+//!
+//! Creates a hidden top-level window to receive `WM_POWERBROADCAST`, which
+//! Windows sends to every top-level window before the system suspends
+//! (`PBT_APMSUSPEND`) and after it resumes (`PBT_APMRESUMEAUTOMATIC`, sent on
+//! every resume whether or not the user is present). Message-only windows
+//! don't receive broadcasts, so the hotkey backend's window can't be used.
+
+use super::{SleepEvent, SleepMonitor};
+use std::cell::RefCell;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, PeekMessageW, RegisterClassW,
+    TranslateMessage, UnregisterClassW, MSG, PM_REMOVE, WM_POWERBROADCAST, WNDCLASSW,
+    WS_OVERLAPPED,
+};
+
+/// `WM_POWERBROADCAST` event types
+const PBT_APMSUSPEND: usize = 0x4;
+const PBT_APMRESUMEAUTOMATIC: usize = 0x12;
+
+/// How often the message loop checks for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+thread_local! {
+    static EVENTS: RefCell<Option<UnboundedSender<SleepEvent>>> = const { RefCell::new(None) };
+}
+
+pub struct WindowsSleepMonitor;
+
+impl SleepMonitor for WindowsSleepMonitor {
+    fn watch(&self, events: UnboundedSender<SleepEvent>) -> Result<(), String> {
+        unsafe {
+            let class_name = windows::core::w!("FlowSTT_PowerClass");
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            if RegisterClassW(&wc) == 0 {
+                let err = windows::Win32::Foundation::GetLastError();
+                if err != windows::Win32::Foundation::ERROR_CLASS_ALREADY_EXISTS {
+                    return Err(format!("Failed to register window class (error {:?})", err));
+                }
+            }
+
+            // Never shown; only top-level windows receive power broadcasts
+            let hwnd = CreateWindowExW(
+                Default::default(),
+                class_name,
+                windows::core::w!("FlowSTT Power"),
+                WS_OVERLAPPED,
+                0,
+                0,
+                0,
+                0,
+                None,
+                None,
+                None,
+                None,
+            )
+            .map_err(|e| format!("Failed to create power window: {}", e))?;
+
+            EVENTS.with(|slot| *slot.borrow_mut() = Some(events));
+            debug!("[Sleep] Watching power broadcasts for sleep and wake");
+
+            let mut msg = MSG::default();
+            while !crate::is_shutdown_requested() {
+                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+
+            EVENTS.with(|slot| *slot.borrow_mut() = None);
+            let _ = DestroyWindow(hwnd);
+            let _ = UnregisterClassW(class_name, None);
+        }
+        Ok(())
+    }
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_POWERBROADCAST {
+        let event = match wparam.0 {
+            PBT_APMSUSPEND => Some(SleepEvent::Sleeping),
+            PBT_APMRESUMEAUTOMATIC => Some(SleepEvent::Woke),
+            _ => None,
+        };
+        if let Some(event) = event {
+            EVENTS.with(|slot| {
+                if let Some(events) = slot.borrow().as_ref() {
+                    let _ = events.send(event);
+                }
+            });
+        }
+        // TRUE grants the request, for the old query events that still ask
+        return LRESULT(1);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
@@ -0,0 +1,97 @@
+//! Audio feedback cues.
+//!
+//! Short synthesized tones played through the default output device when
+//! push-to-talk starts or stops and when a transcription completes. Each cue
+//! is enabled individually in the `sound_cues` section of the config file.
+//!
+//! The push-to-talk start cue is played to the end before capture starts,
+//! so it isn't recorded into the segment.
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rodio::source::SineWave;
+use rodio::Source;
+use tracing::warn;
+
+use crate::config::current_config;
+
+/// Length of each tone in a cue
+const TONE_DURATION: Duration = Duration::from_millis(70);
+
+/// Time left after playback ends for the output device to drain its buffer
+const OUTPUT_DRAIN: Duration = Duration::from_millis(50);
+
+/// Longest [`play_and_wait`] waits, so a stuck output device can't hold up
+/// recording
+const WAIT_LIMIT: Duration = Duration::from_millis(500);
+
+/// A feedback cue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCue {
+    /// Push-to-talk pressed: rising two-tone
+    PttStart,
+    /// Push-to-talk released: falling two-tone
+    PttStop,
+    /// Transcription complete: single high tone
+    TranscriptionComplete,
+}
+
+impl SoundCue {
+    /// Tone frequencies in Hz, played in order.
+    fn frequencies(self) -> &'static [f32] {
+        match self {
+            SoundCue::PttStart => &[660.0, 880.0],
+            SoundCue::PttStop => &[880.0, 660.0],
+            SoundCue::TranscriptionComplete => &[1046.5],
+        }
+    }
+}
+
+/// Play a cue if it is enabled in the config.
+///
+/// Playback is asynchronous; failures are logged and otherwise ignored.
+pub fn play(cue: SoundCue) {
+    start(cue);
+}
+
+/// Play a cue if it is enabled in the config and wait until it has been
+/// heard, so a microphone opened afterwards doesn't pick it up.
+pub fn play_and_wait(cue: SoundCue) {
+    if let Some(done) = start(cue) {
+        if done.recv_timeout(WAIT_LIMIT).is_ok() {
+            std::thread::sleep(OUTPUT_DRAIN);
+        }
+    }
+}
+
+/// Start playing a cue if it is enabled. Returns a receiver that signals
+/// when playback has finished.
+fn start(cue: SoundCue) -> Option<mpsc::Receiver<()>> {
+    let config = current_config();
+    let settings = &config.sound_cues;
+    let enabled = match cue {
+        SoundCue::PttStart => settings.ptt_start,
+        SoundCue::PttStop => settings.ptt_stop,
+        SoundCue::TranscriptionComplete => settings.transcription_complete,
+    };
+    let volume = settings.volume.clamp(0.0, 1.0);
+    if !enabled || volume == 0.0 {
+        return None;
+    }
+
+    let tones = cue
+        .frequencies()
+        .iter()
+        .map(|&freq| {
+            SineWave::new(freq)
+                .take_duration(TONE_DURATION)
+                .fade_out(TONE_DURATION)
+                .amplify(volume)
+        })
+        .collect();
+
+    crate::playback::play_sources(tones, format!("{:?} cue", cue))
+        .map_err(|e| warn!("[SoundCues] Failed to play {:?} cue: {}", cue, e))
+        .ok()
+}
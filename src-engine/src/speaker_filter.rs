@@ -0,0 +1,122 @@
+//! Only transcribing the enrolled speaker.
+//!
+//! Enrollment records the user reading aloud and saves a voiceprint of their
+//! voice (see [`flowstt_dsp::speaker`]) in the config. While the filter is on,
+//! each Automatic mode segment is compared with the voiceprint before it is
+//! queued, and segments in another voice (a TV, a roommate) are dropped.
+//! Push-to-talk segments are always transcribed: holding the key says the
+//! speech is meant to be typed.
+
+use flowstt_common::config::{Config, SpeakerFilter as SpeakerFilterSettings};
+use flowstt_dsp::speaker;
+use tracing::{debug, info};
+
+use crate::config::update_config;
+
+/// Length of the enrollment recording
+const ENROLL_DURATION_MS: u64 = 8000;
+
+/// Give up on enrollment if the device delivers no audio for this long
+const ENROLL_TIMEOUT_MS: u64 = 12000;
+
+/// Record the user from a device and save their voiceprint, turning the
+/// filter on. Returns the length of the recording in milliseconds.
+///
+/// Blocks for about eight seconds; the caller is responsible for stopping
+/// the main audio loop.
+pub fn enroll(device_id: &str) -> Result<u64, String> {
+    let (samples, sample_rate) =
+        crate::test_capture::record(device_id, ENROLL_DURATION_MS, ENROLL_TIMEOUT_MS)?;
+    let duration_ms = samples.len() as u64 * 1000 / u64::from(sample_rate.max(1));
+    let voiceprint = speaker::voiceprint(&samples, sample_rate)
+        .ok_or("Not enough speech was recorded; read aloud for the whole recording")?;
+
+    update_config(|config| {
+        config.speaker_filter.voiceprint = Some(voiceprint);
+        config.speaker_filter.enabled = true;
+    })
+    .map_err(|e| format!("Failed to save voiceprint: {}", e))?;
+    info!(
+        "[SpeakerFilter] Enrolled from {} ({} ms recorded)",
+        device_id, duration_ms
+    );
+    Ok(duration_ms)
+}
+
+/// Delete the voiceprint and turn the filter off.
+pub fn clear_enrollment() -> Result<(), String> {
+    update_config(|config| {
+        config.speaker_filter.voiceprint = None;
+        config.speaker_filter.enabled = false;
+    })
+    .map_err(|e| format!("Failed to clear voiceprint: {}", e))?;
+    info!("[SpeakerFilter] Enrollment cleared");
+    Ok(())
+}
+
+/// The enrolled voiceprint and how closely segments must match it.
+pub(crate) struct SpeakerFilter {
+    voiceprint: Vec<f32>,
+    min_similarity: f32,
+}
+
+impl SpeakerFilter {
+    /// The filter from the config, or `None` if it is off or nobody is enrolled
+    pub(crate) fn from_config(config: &Config) -> Option<Self> {
+        let SpeakerFilterSettings {
+            enabled,
+            min_similarity,
+            voiceprint,
+        } = &config.speaker_filter;
+        match voiceprint {
+            Some(voiceprint) if *enabled => Some(Self {
+                voiceprint: voiceprint.clone(),
+                min_similarity: *min_similarity,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether a mono segment is in the enrolled voice. Segments with too
+    /// little voiced audio to tell are accepted.
+    pub(crate) fn accepts(&self, samples: &[f32], sample_rate: u32) -> bool {
+        let Some(voiceprint) = speaker::voiceprint(samples, sample_rate) else {
+            return true;
+        };
+        let similarity = speaker::similarity(&self.voiceprint, &voiceprint);
+        let accepted = similarity >= self.min_similarity;
+        debug!(
+            "[SpeakerFilter] Segment similarity {:.2} (minimum {:.2}): {}",
+            similarity,
+            self.min_similarity,
+            if accepted { "accepted" } else { "dropped" }
+        );
+        accepted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_needs_a_voiceprint_and_enabled() {
+        let mut config = Config::default();
+        assert!(SpeakerFilter::from_config(&config).is_none());
+
+        config.speaker_filter.voiceprint = Some(vec![1.0; speaker::VOICEPRINT_LEN]);
+        assert!(SpeakerFilter::from_config(&config).is_none());
+
+        config.speaker_filter.enabled = true;
+        assert!(SpeakerFilter::from_config(&config).is_some());
+    }
+
+    #[test]
+    fn short_segments_are_accepted() {
+        let filter = SpeakerFilter {
+            voiceprint: vec![1.0; speaker::VOICEPRINT_LEN],
+            min_similarity: 0.99,
+        };
+        assert!(filter.accepts(&[0.0; 1600], 16000));
+    }
+}
@@ -0,0 +1,222 @@
+//! Spelling mode.
+//!
+//! While spelling mode is on, letters spoken one at a time or with the NATO
+//! alphabet ("alpha", "bravo"), digits and a few symbol names ("at", "dot",
+//! "underscore") become characters written without spaces, for dictating
+//! identifiers, email addresses and similar strings. Letters are lowercase
+//! unless "capital" comes first. Spelled segments skip the spoken commands
+//! and phrase replacements so nothing corrects them.
+//!
+//! The mode is turned on and off with the spelling hotkey or by saying "start
+//! spelling" and "stop spelling", anywhere in a segment. It is not persisted.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use flowstt_common::ipc::{EventType, Response};
+use tracing::info;
+
+use crate::ipc::broadcast_event;
+
+/// Phrase that turns spelling mode on
+const START_PHRASE: [&str; 2] = ["start", "spelling"];
+
+/// Phrase that turns spelling mode off
+const STOP_PHRASE: [&str; 2] = ["stop", "spelling"];
+
+const NATO_ALPHABET: [(&str, char); 28] = [
+    ("alpha", 'a'),
+    ("alfa", 'a'),
+    ("bravo", 'b'),
+    ("charlie", 'c'),
+    ("delta", 'd'),
+    ("echo", 'e'),
+    ("foxtrot", 'f'),
+    ("golf", 'g'),
+    ("hotel", 'h'),
+    ("india", 'i'),
+    ("juliet", 'j'),
+    ("juliett", 'j'),
+    ("kilo", 'k'),
+    ("lima", 'l'),
+    ("mike", 'm'),
+    ("november", 'n'),
+    ("oscar", 'o'),
+    ("papa", 'p'),
+    ("quebec", 'q'),
+    ("romeo", 'r'),
+    ("sierra", 's'),
+    ("tango", 't'),
+    ("uniform", 'u'),
+    ("victor", 'v'),
+    ("whiskey", 'w'),
+    ("x-ray", 'x'),
+    ("yankee", 'y'),
+    ("zulu", 'z'),
+];
+
+const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const SYMBOLS: [(&str, char); 10] = [
+    ("at", '@'),
+    ("dot", '.'),
+    ("dash", '-'),
+    ("hyphen", '-'),
+    ("underscore", '_'),
+    ("slash", '/'),
+    ("plus", '+'),
+    ("hash", '#'),
+    ("colon", ':'),
+    ("space", ' '),
+];
+
+static SPELLING_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Check whether spelling mode is on.
+pub fn is_active() -> bool {
+    SPELLING_MODE.load(Ordering::SeqCst)
+}
+
+/// Turn spelling mode on or off and notify clients if it changed.
+pub fn set_active(active: bool) {
+    if SPELLING_MODE.swap(active, Ordering::SeqCst) == active {
+        return;
+    }
+
+    info!("Spelling mode {}", if active { "on" } else { "off" });
+    broadcast_event(Response::Event {
+        event: EventType::SpellingModeChanged { active },
+    });
+}
+
+/// Turn spelling mode on, or off if it is on.
+pub fn toggle() {
+    set_active(!is_active());
+}
+
+/// A segment after spelling mode was applied.
+pub struct Spelled {
+    pub text: String,
+    /// Whether any of the text was spelled
+    pub spelled: bool,
+}
+
+/// Spell the parts of `text` spoken in spelling mode, following the start
+/// and stop phrases in it and leaving the mode as the last one set it.
+pub fn apply(text: &str) -> Spelled {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|word| match_key(word)).collect();
+    let mut active = is_active();
+    let mut spelled = false;
+    let mut pieces: Vec<String> = Vec::new();
+    let mut speller = Speller::default();
+    let mut i = 0;
+
+    while i < words.len() {
+        let phrase = |phrase: &[&str]| {
+            keys.len() - i >= phrase.len() && phrase.iter().zip(&keys[i..]).all(|(p, k)| p == k)
+        };
+        if phrase(&START_PHRASE) || phrase(&STOP_PHRASE) {
+            active = phrase(&START_PHRASE);
+            pieces.extend(speller.finish());
+            i += START_PHRASE.len();
+            continue;
+        }
+
+        if active {
+            speller.push(words[i], &keys[i]);
+            spelled = true;
+        } else {
+            pieces.push(words[i].to_string());
+        }
+        i += 1;
+    }
+    pieces.extend(speller.finish());
+
+    set_active(active);
+    Spelled {
+        text: pieces.join(" "),
+        spelled,
+    }
+}
+
+/// A word without surrounding punctuation, in lowercase.
+fn match_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Characters spelled so far.
+#[derive(Default)]
+struct Speller {
+    text: String,
+    /// The next letter is uppercase
+    capital: bool,
+}
+
+impl Speller {
+    fn push(&mut self, word: &str, key: &str) {
+        if matches!(key, "capital" | "uppercase") {
+            self.capital = true;
+            return;
+        }
+
+        if let Some((_, letter)) = NATO_ALPHABET
+            .iter()
+            .find(|(name, _)| *name == key || name.replace('-', "") == key)
+        {
+            self.letter(*letter);
+        } else if let Some(digit) = DIGITS.iter().position(|name| *name == key) {
+            self.text.push_str(&digit.to_string());
+        } else if let Some((_, symbol)) = SYMBOLS.iter().find(|(name, _)| *name == key) {
+            self.text.push(*symbol);
+        } else if key.split(['-', '.']).all(|c| c.chars().count() == 1) {
+            // Letters and digits as Whisper writes them: "B", "B." or "A-B-C"
+            for c in key.chars().filter(|c| c.is_alphanumeric()) {
+                self.letter(c);
+            }
+        } else if key.chars().all(|c| c.is_ascii_digit()) {
+            self.text.push_str(key);
+        } else {
+            // Anything else is written as spoken
+            self.text
+                .push_str(word.trim_matches(|c: char| !c.is_alphanumeric()));
+        }
+        self.capital = false;
+    }
+
+    fn letter(&mut self, letter: char) {
+        if self.capital {
+            self.text.extend(letter.to_uppercase());
+        } else {
+            self.text.push(letter);
+        }
+    }
+
+    /// Take the spelled text, if there is any.
+    fn finish(&mut self) -> Option<String> {
+        self.capital = false;
+        Some(std::mem::take(&mut self.text)).filter(|text| !text.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell(words: &str) -> String {
+        let mut speller = Speller::default();
+        for word in words.split_whitespace() {
+            speller.push(word, &match_key(word));
+        }
+        speller.finish().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_spell_letters_digits_and_symbols() {
+        assert_eq!(spell("capital J, O. H, N."), "John");
+        assert_eq!(spell("Kilo Echo at example dot com"), "ke@example.com");
+        assert_eq!(spell("X-ray one two 3 underscore A-B-C"), "x123_abc");
+    }
+}
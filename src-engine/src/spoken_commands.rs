@@ -0,0 +1,336 @@
+//! Spoken punctuation and formatting commands.
+//!
+//! With `spoken_commands.enabled` set, phrases such as "comma", "question
+//! mark" or "new line" in a transcript are replaced by what they name, and the
+//! punctuation Whisper put around them is dropped. Phrases come from the table
+//! for the configured locale. Saying the locale's escape word first ("literal
+//! comma") keeps the phrase as words.
+
+use flowstt_common::config::SpokenCommandSettings;
+use tracing::warn;
+
+/// Punctuation Whisper may put next to a spoken command
+const WHISPER_PUNCTUATION: [char; 6] = [',', '.', ';', ':', '!', '?'];
+
+/// What a command inserts, and how it is spaced.
+#[derive(Debug, Clone, Copy)]
+enum Symbol {
+    /// Punctuation ending the preceding word, replacing Whisper's own
+    Trailing(&'static str),
+    /// A symbol attached to the end of the preceding word, such as a
+    /// closing quote
+    Closing(&'static str),
+    /// A symbol attached to the start of the following word, such as an
+    /// opening quote
+    Opening(&'static str),
+    /// A symbol set off by spaces on both sides
+    Spaced(&'static str),
+    /// A symbol joining the words on either side, such as a hyphen
+    Joining(&'static str),
+    /// A line break; the next word starts a sentence
+    Break(&'static str),
+}
+
+/// Command phrases in one language.
+struct Locale {
+    code: &'static str,
+    /// Word that makes the command after it literal
+    escape: &'static str,
+    /// Lowercase phrases and what they insert
+    commands: &'static [(&'static str, Symbol)],
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        escape: "literal",
+        commands: &[
+            ("comma", Symbol::Trailing(",")),
+            ("period", Symbol::Trailing(".")),
+            ("full stop", Symbol::Trailing(".")),
+            ("question mark", Symbol::Trailing("?")),
+            ("exclamation mark", Symbol::Trailing("!")),
+            ("exclamation point", Symbol::Trailing("!")),
+            ("colon", Symbol::Trailing(":")),
+            ("semicolon", Symbol::Trailing(";")),
+            ("ellipsis", Symbol::Trailing("...")),
+            ("open quote", Symbol::Opening("\"")),
+            ("close quote", Symbol::Closing("\"")),
+            ("end quote", Symbol::Closing("\"")),
+            ("open paren", Symbol::Opening("(")),
+            ("open parenthesis", Symbol::Opening("(")),
+            ("close paren", Symbol::Closing(")")),
+            ("close parenthesis", Symbol::Closing(")")),
+            ("hyphen", Symbol::Joining("-")),
+            ("slash", Symbol::Joining("/")),
+            ("dash", Symbol::Spaced("-")),
+            ("ampersand", Symbol::Spaced("&")),
+            ("percent sign", Symbol::Closing("%")),
+            ("dollar sign", Symbol::Opening("$")),
+            ("number sign", Symbol::Opening("#")),
+            ("plus sign", Symbol::Spaced("+")),
+            ("minus sign", Symbol::Spaced("-")),
+            ("equals sign", Symbol::Spaced("=")),
+            ("new line", Symbol::Break("\n")),
+            ("new paragraph", Symbol::Break("\n\n")),
+        ],
+    },
+    Locale {
+        code: "de",
+        escape: "wörtlich",
+        commands: &[
+            ("komma", Symbol::Trailing(",")),
+            ("punkt", Symbol::Trailing(".")),
+            ("fragezeichen", Symbol::Trailing("?")),
+            ("ausrufezeichen", Symbol::Trailing("!")),
+            ("doppelpunkt", Symbol::Trailing(":")),
+            ("semikolon", Symbol::Trailing(";")),
+            ("anführungszeichen auf", Symbol::Opening("\"")),
+            ("anführungszeichen zu", Symbol::Closing("\"")),
+            ("klammer auf", Symbol::Opening("(")),
+            ("klammer zu", Symbol::Closing(")")),
+            ("bindestrich", Symbol::Joining("-")),
+            ("schrägstrich", Symbol::Joining("/")),
+            ("prozentzeichen", Symbol::Closing("%")),
+            ("pluszeichen", Symbol::Spaced("+")),
+            ("gleichheitszeichen", Symbol::Spaced("=")),
+            ("neue zeile", Symbol::Break("\n")),
+            ("neuer absatz", Symbol::Break("\n\n")),
+        ],
+    },
+    Locale {
+        code: "fr",
+        escape: "littéralement",
+        commands: &[
+            ("virgule", Symbol::Trailing(",")),
+            ("point", Symbol::Trailing(".")),
+            ("point d'interrogation", Symbol::Trailing("?")),
+            ("point d'exclamation", Symbol::Trailing("!")),
+            ("deux-points", Symbol::Trailing(":")),
+            ("point-virgule", Symbol::Trailing(";")),
+            ("ouvrez les guillemets", Symbol::Opening("\"")),
+            ("fermez les guillemets", Symbol::Closing("\"")),
+            ("ouvrez la parenthèse", Symbol::Opening("(")),
+            ("fermez la parenthèse", Symbol::Closing(")")),
+            ("trait d'union", Symbol::Joining("-")),
+            ("barre oblique", Symbol::Joining("/")),
+            ("signe pourcentage", Symbol::Closing("%")),
+            ("signe plus", Symbol::Spaced("+")),
+            ("signe égal", Symbol::Spaced("=")),
+            ("à la ligne", Symbol::Break("\n")),
+            ("nouveau paragraphe", Symbol::Break("\n\n")),
+        ],
+    },
+    Locale {
+        code: "es",
+        escape: "literal",
+        commands: &[
+            ("coma", Symbol::Trailing(",")),
+            ("punto", Symbol::Trailing(".")),
+            ("punto y coma", Symbol::Trailing(";")),
+            ("dos puntos", Symbol::Trailing(":")),
+            ("cierra interrogación", Symbol::Trailing("?")),
+            ("cierra exclamación", Symbol::Trailing("!")),
+            ("abre interrogación", Symbol::Opening("¿")),
+            ("abre exclamación", Symbol::Opening("¡")),
+            ("abre comillas", Symbol::Opening("\"")),
+            ("cierra comillas", Symbol::Closing("\"")),
+            ("abre paréntesis", Symbol::Opening("(")),
+            ("cierra paréntesis", Symbol::Closing(")")),
+            ("guion", Symbol::Joining("-")),
+            ("barra", Symbol::Joining("/")),
+            ("signo de porcentaje", Symbol::Closing("%")),
+            ("signo más", Symbol::Spaced("+")),
+            ("signo igual", Symbol::Spaced("=")),
+            ("nueva línea", Symbol::Break("\n")),
+            ("nuevo párrafo", Symbol::Break("\n\n")),
+        ],
+    },
+];
+
+/// Replace the spoken commands in `text`, if enabled.
+pub fn apply(settings: &SpokenCommandSettings, text: &str) -> String {
+    if !settings.enabled {
+        return text.to_string();
+    }
+    let code = settings.locale.trim();
+    let Some(locale) = LOCALES.iter().find(|l| l.code.eq_ignore_ascii_case(code)) else {
+        warn!("[SpokenCommands] No commands for locale {:?}", code);
+        return text.to_string();
+    };
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|word| match_key(word)).collect();
+    let mut output = Output::default();
+    let mut i = 0;
+
+    while i < words.len() {
+        if keys[i] == locale.escape {
+            if let Some((len, _)) = find_command(locale, &keys[i + 1..]) {
+                for word in &words[i + 1..=i + len] {
+                    output.word(word);
+                }
+                i += 1 + len;
+                continue;
+            }
+        }
+        match find_command(locale, &keys[i..]) {
+            Some((len, symbol)) => {
+                output.symbol(symbol);
+                // Keep the punctuation Whisper ended a quote or bracket with,
+                // as in "close quote."
+                if let Symbol::Closing(_) = symbol {
+                    let last = words[i + len - 1];
+                    output.text.push_str(&last[match_key_end(last)..]);
+                }
+                i += len;
+            }
+            None => {
+                output.word(words[i]);
+                i += 1;
+            }
+        }
+    }
+    output.text
+}
+
+/// A word as compared with command phrases: lowercase, without surrounding
+/// punctuation and with typographic apostrophes straightened.
+fn match_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .replace('’', "'")
+        .to_lowercase()
+}
+
+/// Byte offset of the end of the part of `word` that [`match_key`] keeps.
+fn match_key_end(word: &str) -> usize {
+    word.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .len()
+}
+
+/// The longest command whose phrase starts `keys`, with its length in words.
+fn find_command(locale: &Locale, keys: &[String]) -> Option<(usize, Symbol)> {
+    locale
+        .commands
+        .iter()
+        .filter_map(|(phrase, symbol)| {
+            let phrase: Vec<&str> = phrase.split(' ').collect();
+            let matches = phrase.len() <= keys.len()
+                && phrase.iter().zip(keys).all(|(word, key)| *word == key);
+            matches.then_some((phrase.len(), *symbol))
+        })
+        .max_by_key(|(len, _)| *len)
+}
+
+/// Text being rebuilt from words and symbols.
+#[derive(Default)]
+struct Output {
+    text: String,
+    /// The next word attaches to the text without a space
+    attach: bool,
+    /// The next word starts a sentence
+    capitalize: bool,
+}
+
+impl Output {
+    fn word(&mut self, word: &str) {
+        self.space();
+        if self.capitalize {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                self.text.extend(first.to_uppercase());
+                self.text.push_str(chars.as_str());
+            }
+        } else {
+            self.text.push_str(word);
+        }
+        self.attach = false;
+        self.capitalize = false;
+    }
+
+    fn symbol(&mut self, symbol: Symbol) {
+        match symbol {
+            Symbol::Trailing(mark) => {
+                self.trim_punctuation();
+                self.text.push_str(mark);
+                self.attach = false;
+                self.capitalize = mark.ends_with(['.', '?', '!']);
+            }
+            Symbol::Closing(mark) => {
+                self.text.push_str(mark);
+                self.attach = false;
+            }
+            Symbol::Opening(mark) => {
+                self.space();
+                self.text.push_str(mark);
+                self.attach = true;
+            }
+            Symbol::Spaced(mark) => {
+                self.space();
+                self.text.push_str(mark);
+                self.attach = false;
+            }
+            Symbol::Joining(mark) => {
+                self.trim_punctuation();
+                self.text.push_str(mark);
+                self.attach = true;
+            }
+            Symbol::Break(breaks) => {
+                self.text.truncate(self.text.trim_end_matches(' ').len());
+                self.text.push_str(breaks);
+                self.attach = true;
+                self.capitalize = true;
+            }
+        }
+    }
+
+    /// Separate what comes next from the text so far, unless it attaches.
+    fn space(&mut self) {
+        if !self.text.is_empty() && !self.attach {
+            self.text.push(' ');
+        }
+    }
+
+    /// Drop the punctuation Whisper ended the last word with.
+    fn trim_punctuation(&mut self) {
+        self.text
+            .truncate(self.text.trim_end_matches(WHISPER_PUNCTUATION).len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(locale: &str) -> SpokenCommandSettings {
+        SpokenCommandSettings {
+            enabled: true,
+            locale: locale.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_punctuation_and_formatting() {
+        let en = settings("en");
+        assert_eq!(
+            apply(&en, "Hello, comma, how are you question mark"),
+            "Hello, how are you?"
+        );
+        assert_eq!(
+            apply(&en, "Dear Sam new line thanks period see you"),
+            "Dear Sam\nThanks. See you"
+        );
+        assert_eq!(
+            apply(&en, "He said open quote hi close quote."),
+            "He said \"hi\"."
+        );
+        assert_eq!(
+            apply(&en, "Put a literal comma here, comma, please"),
+            "Put a comma here, please"
+        );
+        assert_eq!(
+            apply(&settings("es"), "Hola punto y coma adiós"),
+            "Hola; adiós"
+        );
+    }
+}
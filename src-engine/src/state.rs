@@ -9,6 +9,8 @@ use flowstt_common::{
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::engine::VadOptions;
+
 /// Global service state
 #[derive(Default)]
 pub struct ServiceState {
@@ -34,6 +36,15 @@ pub struct ServiceState {
     pub auto_mode_active: bool,
     /// Current runtime mode (development or production)
     pub runtime_mode: RuntimeMode,
+    /// Whether capture is held off by a process rule
+    pub paused_by_process: bool,
+    /// Whether capture is held off while the session is locked
+    pub paused_by_lock: bool,
+    /// Whether an engine built with `EngineBuilder` is running
+    pub engine_running: bool,
+    /// Speech detection the running engine was built with, used instead of
+    /// the config and device calibration
+    pub vad_override: Option<VadOptions>,
 }
 
 impl ServiceState {
@@ -42,9 +53,14 @@ impl ServiceState {
         self.source1_id.is_some()
     }
 
-    /// Check if capture should be active (primary source configured)
+    /// Check if capture should be active (primary source configured, not
+    /// paused by a process rule or the session lock, or suspended for
+    /// inactivity)
     pub fn should_capture(&self) -> bool {
         self.has_primary_source()
+            && !self.paused_by_process
+            && !self.paused_by_lock
+            && !self.transcribe_status.auto_suspended
     }
 }
 
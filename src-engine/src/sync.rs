@@ -0,0 +1,370 @@
+//! Syncing history and rules between machines through a shared folder.
+//!
+//! While `sync` is enabled, every change this machine makes to history is
+//! appended to `flowstt/<machine>/history.jsonl` in the configured folder,
+//! and the rules to `rules.jsonl` next to it. A service such as Dropbox or
+//! Syncthing copies the folder between machines; each machine only ever
+//! appends to its own files, so the copies never conflict. At startup the
+//! engine replays every machine's history log in time order, so the latest
+//! change to each entry wins and deletions carry over, and adopts the
+//! newest rules. Entries made while sync is on have the machine in their ID,
+//! so two machines never make the same one.
+//!
+//! Recordings stay on the machine that made them. Sync is skipped while
+//! `encrypt_history` is on, since the logs would hold the history in
+//! plaintext.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::{SecondsFormat, Utc};
+use flowstt_common::config::{Config, SyncSettings, UserRules};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::{current_config, update_config};
+use crate::history::{get_history, HistoryEntry, TranscriptionHistory};
+
+/// Directory inside the synced folder holding one directory per machine
+const SYNC_DIR: &str = "flowstt";
+
+const HISTORY_LOG: &str = "history.jsonl";
+const RULES_LOG: &str = "rules.jsonl";
+
+/// This machine's ID, once read or created
+static MACHINE_ID: OnceLock<String> = OnceLock::new();
+
+/// A change to one history entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum HistoryChange {
+    /// The entry was added or its text changed
+    Put {
+        /// RFC 3339 UTC time of the change
+        at: String,
+        entry: HistoryEntry,
+    },
+    /// The entry was deleted
+    Delete {
+        /// RFC 3339 UTC time of the change
+        at: String,
+        id: String,
+    },
+}
+
+impl HistoryChange {
+    fn at(&self) -> &str {
+        match self {
+            HistoryChange::Put { at, .. } | HistoryChange::Delete { at, .. } => at,
+        }
+    }
+}
+
+/// The rules as one machine last saved them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RulesSnapshot {
+    /// RFC 3339 UTC time of the snapshot
+    at: String,
+    rules: UserRules,
+}
+
+/// The directory of machine logs in the synced folder, or why sync can't
+/// run. `Ok(None)` means sync is off.
+fn sync_dir(config: &Config) -> Result<Option<PathBuf>, String> {
+    let SyncSettings {
+        enabled, folder, ..
+    } = &config.sync;
+    if !enabled {
+        return Ok(None);
+    }
+    let Some(folder) = folder.as_deref().map(str::trim).filter(|f| !f.is_empty()) else {
+        return Err("no folder is set".to_string());
+    };
+    if config.encrypt_history {
+        return Err("history is encrypted at rest".to_string());
+    }
+    let folder = Path::new(folder);
+    if !folder.is_dir() {
+        return Err(format!("{:?} does not exist", folder));
+    }
+    Ok(Some(folder.join(SYNC_DIR)))
+}
+
+/// The directory of machine logs, if sync is on and can run.
+fn folder(config: &Config) -> Option<PathBuf> {
+    sync_dir(config).ok().flatten()
+}
+
+/// This machine's ID: eight hex digits kept in the data directory, so a
+/// copied config doesn't make two machines look like one.
+pub fn machine_id() -> &'static str {
+    MACHINE_ID.get_or_init(|| {
+        let path = TranscriptionHistory::data_dir().join("machine-id");
+        if let Ok(id) = fs::read_to_string(&path) {
+            let id = id.trim();
+            if !id.is_empty() {
+                return id.to_string();
+            }
+        }
+        let id = format!("{:08x}", crate::history::rand_u32());
+        if let Err(e) = fs::write(&path, &id) {
+            warn!("[Sync] Failed to save machine ID to {:?}: {}", path, e);
+        }
+        id
+    })
+}
+
+/// The machine part of new history entry IDs, if sync is on.
+pub fn id_tag() -> Option<&'static str> {
+    let config = current_config();
+    folder(&config).map(|_| machine_id())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Record an added or changed history entry.
+pub fn entry_changed(entry: &HistoryEntry) {
+    let config = current_config();
+    let Some(dir) = folder(&config) else {
+        return;
+    };
+    append(&dir, HISTORY_LOG, &put(entry));
+}
+
+/// Record a deleted history entry.
+pub fn entry_deleted(id: &str) {
+    let config = current_config();
+    let Some(dir) = folder(&config) else {
+        return;
+    };
+    let change = HistoryChange::Delete {
+        at: now(),
+        id: id.to_string(),
+    };
+    append(&dir, HISTORY_LOG, &change);
+}
+
+/// Record the current rules after they changed.
+pub fn rules_changed() {
+    let config = current_config();
+    if !config.sync.rules {
+        return;
+    }
+    let Some(dir) = folder(&config) else {
+        return;
+    };
+    let snapshot = RulesSnapshot {
+        at: now(),
+        rules: config.user_rules(),
+    };
+    append(&dir, RULES_LOG, &snapshot);
+}
+
+/// A `Put` for an entry, without the recording that stays on this machine.
+fn put(entry: &HistoryEntry) -> HistoryChange {
+    let mut entry = entry.clone();
+    entry.wav_path = None;
+    entry.audio_bytes = None;
+    HistoryChange::Put { at: now(), entry }
+}
+
+/// Append a line to one of this machine's logs.
+fn append(dir: &Path, log: &str, line: &impl Serialize) {
+    let dir = dir.join(machine_id());
+    let path = dir.join(log);
+    let result = (|| {
+        fs::create_dir_all(&dir)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let line = serde_json::to_string(line).map_err(std::io::Error::other)?;
+        writeln!(file, "{}", line)
+    })();
+    if let Err(e) = result {
+        warn!("[Sync] Failed to write {:?}: {}", path, e);
+    }
+}
+
+/// Every machine's entries of one log, each with the machine's ID. Lines
+/// that don't parse, such as one still being synced, are skipped.
+fn read_logs<T: for<'de> Deserialize<'de>>(dir: &Path, log: &str) -> Vec<(String, T)> {
+    let Ok(machines) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    for machine in machines.flatten() {
+        let id = machine.file_name().to_string_lossy().into_owned();
+        let Ok(contents) = fs::read_to_string(machine.path().join(log)) else {
+            continue;
+        };
+        lines.extend(
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .map(|line| (id.clone(), line)),
+        );
+    }
+    lines
+}
+
+/// Merge other machines' history and rules into this machine's, and log
+/// this machine's entries that aren't in the folder yet.
+pub fn merge() {
+    let config = current_config();
+    let dir = match sync_dir(&config) {
+        Ok(Some(dir)) => dir,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("[Sync] Not syncing: {}", e);
+            return;
+        }
+    };
+
+    let mut changes: Vec<HistoryChange> = read_logs::<HistoryChange>(&dir, HISTORY_LOG)
+        .into_iter()
+        .map(|(_, change)| change)
+        .collect();
+    changes.sort_by(|a, b| a.at().cmp(b.at()));
+    let synced = replay(changes);
+
+    let history = get_history();
+    let mut history = history.lock().unwrap();
+    let unsynced: Vec<HistoryChange> = history
+        .get_entries()
+        .iter()
+        .filter(|e| !e.ephemeral && !synced.contains_key(&e.id))
+        .map(put)
+        .collect();
+    let (added, updated, removed) = history.apply_synced(synced);
+    drop(history);
+    for change in &unsynced {
+        append(&dir, HISTORY_LOG, change);
+    }
+    info!(
+        "[Sync] Merged history from {:?}: {} added, {} updated, {} removed, {} shared",
+        dir,
+        added,
+        updated,
+        removed,
+        unsynced.len()
+    );
+
+    if config.sync.rules {
+        merge_rules(&dir, &config);
+    }
+}
+
+/// The state of each entry after the changes, oldest first: the entry, or
+/// `None` once deleted.
+fn replay(changes: Vec<HistoryChange>) -> HashMap<String, Option<HistoryEntry>> {
+    let mut entries = HashMap::new();
+    for change in changes {
+        match change {
+            HistoryChange::Put { entry, .. } => {
+                entries.insert(entry.id.clone(), Some(entry));
+            }
+            HistoryChange::Delete { id, .. } => {
+                entries.insert(id, None);
+            }
+        }
+    }
+    entries
+}
+
+/// Adopt the newest rules from another machine, unless the rules here have
+/// changed since this machine last logged them. A machine joining sync
+/// merges the shared rules into its own.
+fn merge_rules(dir: &Path, config: &Config) {
+    let snapshots = read_logs::<RulesSnapshot>(dir, RULES_LOG);
+    let own = machine_id();
+    let latest_own = snapshots
+        .iter()
+        .filter(|(machine, _)| machine == own)
+        .max_by(|a, b| a.1.at.cmp(&b.1.at))
+        .map(|(_, snapshot)| &snapshot.rules);
+    let latest = snapshots
+        .iter()
+        .max_by(|a, b| a.1.at.cmp(&b.1.at))
+        .map(|(_, snapshot)| &snapshot.rules);
+    let local = config.user_rules();
+
+    let update = match (latest_own, latest) {
+        // Nothing shared yet
+        (_, None) => None,
+        // Joining: keep this machine's rules and add the shared ones
+        (None, Some(shared)) => Some((shared.clone(), false)),
+        // Changed here since last logged: these rules win
+        (Some(logged), _) if *logged != local => None,
+        (Some(_), Some(shared)) if *shared != local => Some((shared.clone(), true)),
+        (Some(_), Some(_)) => return,
+    };
+    if let Some((rules, replace)) = update {
+        match update_config(|config| {
+            config.import_rules(rules, replace);
+        }) {
+            Ok(_) => info!("[Sync] Updated rules from the synced folder"),
+            Err(e) => {
+                warn!("[Sync] Failed to save synced rules: {}", e);
+                return;
+            }
+        }
+    }
+    rules_changed();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, text: &str) -> HistoryEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "text": text,
+            "timestamp": "2024-05-01T12:00:00.000Z",
+            "wav_path": null,
+        }))
+        .unwrap()
+    }
+
+    fn put_at(at: &str, entry: HistoryEntry) -> HistoryChange {
+        HistoryChange::Put {
+            at: at.to_string(),
+            entry,
+        }
+    }
+
+    #[test]
+    fn latest_change_to_each_entry_wins() {
+        let mut changes = vec![
+            put_at("2024-05-01T12:00:02.000Z", entry("a", "edited")),
+            put_at("2024-05-01T12:00:00.000Z", entry("a", "first")),
+            put_at("2024-05-01T12:00:01.000Z", entry("b", "kept")),
+            HistoryChange::Delete {
+                at: "2024-05-01T12:00:03.000Z".to_string(),
+                id: "c".to_string(),
+            },
+            put_at("2024-05-01T12:00:00.500Z", entry("c", "gone")),
+        ];
+        changes.sort_by(|a, b| a.at().cmp(b.at()));
+        let entries = replay(changes);
+
+        assert_eq!(entries["a"].as_ref().unwrap().text, "edited");
+        assert_eq!(entries["b"].as_ref().unwrap().text, "kept");
+        assert!(entries["c"].is_none());
+    }
+
+    #[test]
+    fn logged_entries_leave_the_recording_behind() {
+        let mut recorded = entry("a", "hello");
+        recorded.wav_path = Some("/tmp/a.wav".to_string());
+        recorded.audio_bytes = Some(44);
+        let HistoryChange::Put { entry, .. } = put(&recorded) else {
+            panic!("expected a put");
+        };
+        assert!(entry.wav_path.is_none());
+        assert!(entry.audio_bytes.is_none());
+    }
+}
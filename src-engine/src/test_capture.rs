@@ -4,15 +4,25 @@
 //! device and broadcast audio level updates without engaging the full
 //! transcription pipeline. It is used by the setup wizard to show a live
 //! audio level meter during device selection.
+//!
+//! It also provides a one-shot self-test that records a short sample from a
+//! device and reports levels, noise floor, and dead/clipping problems.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::AudioSelfTestReport;
+use flowstt_dsp::{amplitude_to_db, rms, SILENCE_DB};
 
 use crate::ipc::broadcast_event;
 use crate::platform;
+use crate::platform::realtime::StopToken;
+
+/// How often the test capture loop re-checks its stop flag while waiting for audio
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Global state for the active test capture.
 static TEST_CAPTURE: std::sync::OnceLock<Mutex<Option<TestCaptureHandle>>> =
@@ -96,8 +106,10 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
     let samples_per_update = (sample_rate as usize) / 10;
     let mut accumulated = Vec::with_capacity(samples_per_update);
 
+    let stop = StopToken::shutdown();
+
     while !stop_flag.load(Ordering::Relaxed) {
-        if let Some(audio_data) = backend.try_recv() {
+        if let Some(audio_data) = backend.recv(&stop, STOP_CHECK_INTERVAL) {
             // Convert to mono if multi-channel
             let mono: Vec<f32> = if audio_data.channels > 1 {
                 audio_data
@@ -112,8 +124,7 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
             accumulated.extend_from_slice(&mono);
 
             if accumulated.len() >= samples_per_update {
-                let rms = compute_rms(&accumulated);
-                let level_db = if rms > 0.0 { 20.0 * rms.log10() } else { -96.0 };
+                let level_db = amplitude_to_db(rms(&accumulated));
 
                 broadcast_event(Response::Event {
                     event: EventType::AudioLevelUpdate {
@@ -124,9 +135,6 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
 
                 accumulated.clear();
             }
-        } else {
-            // No data available, sleep briefly to avoid busy-spinning
-            std::thread::sleep(std::time::Duration::from_millis(5));
         }
     }
 
@@ -135,11 +143,241 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
     Ok(())
 }
 
-/// Compute RMS amplitude of audio samples.
-fn compute_rms(samples: &[f32]) -> f32 {
+/// Duration of the self-test recording
+const SELF_TEST_DURATION_MS: u64 = 2000;
+
+/// Give up on the self-test if the device delivers no audio for this long
+const SELF_TEST_TIMEOUT_MS: u64 = 4000;
+
+/// Window size used to estimate the noise floor
+const NOISE_FLOOR_WINDOW_MS: usize = 50;
+
+/// Peak level below which the device is considered dead (digital silence)
+const DEAD_PEAK_DB: f32 = -80.0;
+
+/// Absolute sample value considered to be clipped
+const CLIP_THRESHOLD: f32 = 0.99;
+
+/// Fraction of clipped samples above which the device is reported as clipping
+const CLIP_RATIO_LIMIT: f32 = 0.001;
+
+/// RMS level below which the input is reported as very quiet
+const QUIET_RMS_DB: f32 = -55.0;
+
+/// Noise floor above which background noise is reported as high
+const NOISY_FLOOR_DB: f32 = -40.0;
+
+/// Record a short sample from a device and analyze it.
+///
+/// Blocks for about two seconds. Any active test capture is stopped first;
+/// the caller is responsible for stopping the main audio loop.
+pub fn run_self_test(device_id: &str) -> Result<AudioSelfTestReport, String> {
+    let (samples, sample_rate) = record(device_id, SELF_TEST_DURATION_MS, SELF_TEST_TIMEOUT_MS)?;
+
+    let report = analyze_self_test(device_id, &samples, sample_rate);
+    tracing::info!(
+        "[SelfTest] {}: rms={:.1} dB, peak={:.1} dB, floor={:.1} dB, dead={}, clipping={}",
+        device_id,
+        report.rms_db,
+        report.peak_db,
+        report.noise_floor_db,
+        report.is_dead,
+        report.is_clipping
+    );
+    Ok(report)
+}
+
+/// Record `duration_ms` of mono audio from a device, giving up after
+/// `timeout_ms`. Returns the samples and their sample rate.
+///
+/// Any active test capture is stopped first; the caller is responsible for
+/// stopping the main audio loop.
+pub(crate) fn record(
+    device_id: &str,
+    duration_ms: u64,
+    timeout_ms: u64,
+) -> Result<(Vec<f32>, u32), String> {
+    stop_test_capture();
+
+    let backend = platform::get_backend().ok_or("Audio backend not available")?;
+    backend.start_capture_sources(Some(device_id.to_string()), None)?;
+
+    let sample_rate = backend.sample_rate();
+    let target_samples = (sample_rate as u64 * duration_ms / 1000) as usize;
+    let mut samples = Vec::with_capacity(target_samples);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+
+    let stop = StopToken::shutdown();
+
+    while samples.len() < target_samples && std::time::Instant::now() < deadline {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if let Some(audio_data) = backend.recv(&stop, remaining) {
+            if audio_data.channels > 1 {
+                samples.extend(
+                    audio_data
+                        .samples
+                        .chunks(audio_data.channels as usize)
+                        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32),
+                );
+            } else {
+                samples.extend_from_slice(&audio_data.samples);
+            }
+        } else if stop.is_stopped() {
+            break;
+        }
+    }
+
+    let _ = backend.stop_capture();
+    Ok((samples, sample_rate))
+}
+
+/// Build a self-test report from mono samples.
+fn analyze_self_test(device_id: &str, samples: &[f32], sample_rate: u32) -> AudioSelfTestReport {
+    let duration_ms = if sample_rate > 0 {
+        samples.len() as u64 * 1000 / sample_rate as u64
+    } else {
+        0
+    };
+
+    let rms_db = amplitude_to_db(rms(samples));
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    let peak_db = amplitude_to_db(peak);
+
+    // Noise floor: RMS of the quietest 10% of short windows
+    let window = ((sample_rate as usize) * NOISE_FLOOR_WINDOW_MS / 1000).max(1);
+    let mut window_levels: Vec<f32> = samples.chunks(window).map(rms).collect();
+    window_levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let noise_floor_db = window_levels
+        .get(window_levels.len() / 10)
+        .map(|&level| amplitude_to_db(level))
+        .unwrap_or(SILENCE_DB);
+
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIP_THRESHOLD).count();
+    let clipped_ratio = if samples.is_empty() {
+        0.0
+    } else {
+        clipped as f32 / samples.len() as f32
+    };
+
+    let is_dead = samples.is_empty() || peak_db < DEAD_PEAK_DB;
+    let is_clipping = clipped_ratio > CLIP_RATIO_LIMIT;
+
+    let mut warnings = Vec::new();
     if samples.is_empty() {
-        return 0.0;
+        warnings.push("No audio was received from the device".to_string());
+    } else if is_dead {
+        warnings.push("Device is producing silence; it may be muted or disconnected".to_string());
+    } else if rms_db < QUIET_RMS_DB {
+        warnings.push("Input level is very low; consider raising the microphone gain".to_string());
+    }
+    if is_clipping {
+        warnings.push("Input is clipping; consider lowering the microphone gain".to_string());
+    }
+    if !is_dead && noise_floor_db > NOISY_FLOOR_DB {
+        warnings.push("Background noise level is high".to_string());
+    }
+
+    AudioSelfTestReport {
+        device_id: device_id.to_string(),
+        duration_ms,
+        rms_db,
+        peak_db,
+        noise_floor_db,
+        clipped_ratio,
+        is_dead,
+        is_clipping,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    /// One second of a 440 Hz tone with the given RMS level.
+    fn tone(rms: f32) -> Vec<f32> {
+        let amplitude = rms * std::f32::consts::SQRT_2;
+        (0..RATE)
+            .map(|i| {
+                amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin()
+            })
+            .collect()
+    }
+
+    fn has_warning(report: &AudioSelfTestReport, text: &str) -> bool {
+        report.warnings.iter().any(|w| w.contains(text))
+    }
+
+    #[test]
+    fn no_audio_is_dead() {
+        let report = analyze_self_test("mic", &[], RATE);
+        assert!(report.is_dead);
+        assert_eq!(report.duration_ms, 0);
+        assert!(has_warning(&report, "No audio"));
+    }
+
+    #[test]
+    fn digital_silence_is_dead() {
+        let report = analyze_self_test("mic", &vec![0.0; RATE as usize], RATE);
+        assert!(report.is_dead);
+        assert!(!report.is_clipping);
+        assert_eq!(report.duration_ms, 1000);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(has_warning(&report, "silence"));
+    }
+
+    #[test]
+    fn full_scale_input_is_clipping() {
+        let samples: Vec<f32> = (0..RATE)
+            .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let report = analyze_self_test("mic", &samples, RATE);
+        assert!(report.is_clipping);
+        assert_eq!(report.clipped_ratio, 1.0);
+        assert!(has_warning(&report, "clipping"));
+    }
+
+    #[test]
+    fn a_few_clipped_samples_are_tolerated() {
+        let mut samples = tone(0.1);
+        // 10 in 16000 is under the 0.1% limit
+        for sample in samples.iter_mut().step_by(1600) {
+            *sample = 1.0;
+        }
+        let report = analyze_self_test("mic", &samples, RATE);
+        assert!(report.clipped_ratio > 0.0);
+        assert!(!report.is_clipping);
+    }
+
+    #[test]
+    fn steady_loud_background_is_noisy() {
+        // -30 dB throughout: the quietest windows are above the -40 dB limit
+        let report = analyze_self_test("mic", &tone(0.0316), RATE);
+        assert!(report.noise_floor_db > NOISY_FLOOR_DB);
+        assert!(has_warning(&report, "Background noise"));
+    }
+
+    #[test]
+    fn speech_over_a_quiet_background_passes() {
+        // Half a second at -60 dB, then speech at -20 dB
+        let mut samples = tone(0.001);
+        samples.truncate(RATE as usize / 2);
+        samples.extend_from_slice(&tone(0.1)[..RATE as usize / 2]);
+        let report = analyze_self_test("mic", &samples, RATE);
+        assert!((report.noise_floor_db + 60.0).abs() < 1.0);
+        assert!(!report.is_dead);
+        assert!(report.is_ok(), "unexpected warnings: {:?}", report.warnings);
+    }
+
+    #[test]
+    fn very_quiet_input_is_reported() {
+        // -65 dB: above the dead threshold but below the quiet one
+        let report = analyze_self_test("mic", &tone(0.00056), RATE);
+        assert!(!report.is_dead);
+        assert!(report.rms_db < QUIET_RMS_DB);
+        assert!(has_warning(&report, "very low"));
+        assert!(!has_warning(&report, "Background noise"));
     }
-    let sum_sq: f32 = samples.iter().map(|s| s * s).sum();
-    (sum_sq / samples.len() as f32).sqrt()
 }
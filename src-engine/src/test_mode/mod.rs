@@ -4,12 +4,26 @@
 //! When activated via `--test-mode`, enables a tray menu option to select
 //! a directory of WAV files, then sequences through them: play each file
 //! to the system audio output, simulate PTT press/release, wait for
-//! transcription, and advance to the next file.
+//! transcription, and advance to the next file. Runs can also be started
+//! headless with the `RunTestMode` request (`flowstt test run`); progress
+//! is broadcast as `TestFile*` and `TestRunComplete` events.
+//!
+//! If a manifest of expected transcripts is available, each result is
+//! scored and a report is written to the directory when the run ends (see
+//! [`scoring`]).
 
 pub mod playback;
+pub mod scoring;
 
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::{TestFileOutcome, TestFileResult, TestRunSummary};
+
+use crate::ipc::broadcast_event;
+use scoring::{Manifest, TestReport};
+
 /// Global test mode flag -- set once at startup, never changes.
 static TEST_MODE_ACTIVE: AtomicBool = AtomicBool::new(false);
 
@@ -44,19 +58,18 @@ fn finish_test_run() {
 }
 
 /// Run the test mode orchestrator on a dedicated thread.
-/// Returns an error message if a run is already in progress or the directory is invalid.
-pub fn start_test_run(dir: std::path::PathBuf) -> Result<(), String> {
-    if !is_test_mode() {
-        return Err("Test mode is not active".to_string());
-    }
-
+///
+/// `manifest` overrides the manifest found in the directory, if any.
+/// Returns an error message if a run is already in progress, the directory
+/// is invalid or the manifest cannot be read.
+pub fn start_test_run(dir: PathBuf, manifest: Option<PathBuf>) -> Result<(), String> {
     if !try_start_test_run() {
         tracing::warn!("[TestMode] A test run is already in progress");
         return Err("A test run is already in progress".to_string());
     }
 
     // Enumerate WAV files
-    let mut wav_files: Vec<std::path::PathBuf> = match std::fs::read_dir(&dir) {
+    let mut wav_files: Vec<PathBuf> = match std::fs::read_dir(&dir) {
         Ok(entries) => entries
             .filter_map(|e| e.ok())
             .map(|e| e.path())
@@ -79,6 +92,24 @@ pub fn start_test_run(dir: std::path::PathBuf) -> Result<(), String> {
         return Err("No WAV files found in the selected directory".to_string());
     }
 
+    let manifest = match manifest.or_else(|| Manifest::find(&dir)) {
+        Some(path) => match Manifest::load(&path) {
+            Ok(loaded) => {
+                tracing::info!(
+                    "[TestMode] Scoring against {} expected transcript(s) from {:?}",
+                    loaded.len(),
+                    path
+                );
+                Some((path, loaded))
+            }
+            Err(e) => {
+                finish_test_run();
+                return Err(e);
+            }
+        },
+        None => None,
+    };
+
     wav_files.sort();
     let file_count = wav_files.len();
     tracing::info!(
@@ -88,9 +119,18 @@ pub fn start_test_run(dir: std::path::PathBuf) -> Result<(), String> {
     );
 
     std::thread::spawn(move || {
+        let started_at = chrono::Local::now().to_rfc3339();
+
         // Catch panics so we always reset the run-active guard
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            run_test_sequence(&wav_files);
+            let files = run_test_sequence(&wav_files, manifest.as_ref().map(|(_, m)| m));
+            write_report(TestReport {
+                started_at,
+                directory: dir,
+                manifest: manifest.map(|(path, _)| path),
+                summary: TestRunSummary::from_results(&files),
+                files,
+            });
         }));
 
         if let Err(e) = result {
@@ -104,21 +144,75 @@ pub fn start_test_run(dir: std::path::PathBuf) -> Result<(), String> {
     Ok(())
 }
 
-/// Execute the test sequence for a list of WAV files.
-fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
-    use flowstt_common::ipc::{EventType, Response};
+/// Log the run summary and write the report next to the WAV files.
+fn write_report(report: TestReport) {
+    let summary = &report.summary;
+    tracing::info!(
+        "[TestMode] Test run complete: {} files, {} transcribed, {} no speech, {} timed out, {} skipped",
+        summary.total,
+        summary.transcribed,
+        summary.no_speech,
+        summary.timed_out,
+        summary.skipped
+    );
+    if let (Some(wer), Some(cer)) = (summary.mean_wer, summary.mean_cer) {
+        tracing::info!(
+            "[TestMode] Scored {} file(s): mean WER {:.1}%, mean CER {:.1}%",
+            summary.scored,
+            wer * 100.0,
+            cer * 100.0
+        );
+    }
+
+    let report_path = match report.write() {
+        Ok(path) => {
+            tracing::info!("[TestMode] Results written to {:?}", path);
+            Some(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            tracing::error!("[TestMode] {}", e);
+            None
+        }
+    };
+
+    broadcast_event(Response::Event {
+        event: EventType::TestRunComplete {
+            summary: report.summary,
+            report_path,
+        },
+    });
+}
+
+/// Record a file's result and broadcast it.
+fn push_result(
+    results: &mut Vec<TestFileResult>,
+    index: usize,
+    total: usize,
+    result: TestFileResult,
+) {
+    broadcast_event(Response::Event {
+        event: EventType::TestFileComplete {
+            index,
+            total,
+            result: result.clone(),
+        },
+    });
+    results.push(result);
+}
+
+/// Execute the test sequence for a list of WAV files, returning a result
+/// per file processed.
+fn run_test_sequence(wav_files: &[PathBuf], manifest: Option<&Manifest>) -> Vec<TestFileResult> {
     use std::time::{Duration, Instant};
 
     let total = wav_files.len();
-    let mut success_count = 0u32;
-    let mut timeout_count = 0u32;
-    let mut skip_count = 0u32;
+    let mut results = Vec::with_capacity(total);
 
     // Subscribe to engine events for TranscriptionComplete detection.
     // get_event_sender() returns the tokio broadcast Sender; we subscribe to get a Receiver.
     let mut event_rx = crate::ipc::server::get_event_sender().subscribe();
 
-    for (idx, wav_path) in wav_files.iter().enumerate() {
+    'files: for (idx, wav_path) in wav_files.iter().enumerate() {
         let file_name = wav_path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
@@ -138,6 +232,14 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
             std::thread::sleep(Duration::from_secs(10));
         }
 
+        broadcast_event(Response::Event {
+            event: EventType::TestFileStarted {
+                index: idx,
+                total,
+                file: file_name.clone(),
+            },
+        });
+
         // 1. Drain stale events before PTT press so we have a clean slate
         while event_rx.try_recv().is_ok() {}
 
@@ -155,7 +257,9 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
                 tracing::error!("[TestMode] [{}/{}] Playback error: {}", idx + 1, total, e);
                 // Release PTT since we already pressed it
                 crate::ptt_controller::handle_ptt_released();
-                skip_count += 1;
+                let result =
+                    scoring::score_file(file_name, TestFileOutcome::Skipped, None, manifest);
+                push_result(&mut results, idx, total, result);
                 continue;
             }
         };
@@ -178,6 +282,7 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
         let deadline = Instant::now() + Duration::from_secs(30);
         let mut got_transcription = false;
         let mut got_speech_ended = false;
+        let mut actual = None;
 
         while Instant::now() < deadline {
             match event_rx.try_recv() {
@@ -190,6 +295,7 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
                         total,
                         result.text
                     );
+                    actual = Some(result.text.trim().to_string());
                     got_transcription = true;
                     break;
                 }
@@ -225,6 +331,7 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
                                 total,
                                 text
                             );
+                            actual = Some(text.trim().to_string());
                             got_transcription = true;
                         } else {
                             tracing::info!(
@@ -243,36 +350,42 @@ fn run_test_sequence(wav_files: &[std::path::PathBuf]) {
                 }
                 Err(tokio::sync::broadcast::error::TryRecvError::Closed) => {
                     tracing::error!("[TestMode] Event channel closed");
-                    return;
+                    break 'files;
                 }
             }
         }
 
-        if got_transcription {
-            success_count += 1;
-        } else if !got_speech_ended {
+        let outcome = if got_transcription {
+            TestFileOutcome::Transcribed
+        } else if got_speech_ended {
+            // Segment was submitted but Whisper found no speech in it
+            TestFileOutcome::NoSpeech
+        } else {
             tracing::warn!(
                 "[TestMode] [{}/{}] Transcription timeout for: {}",
                 idx + 1,
                 total,
                 file_name
             );
-            timeout_count += 1;
+            TestFileOutcome::Timeout
+        };
+
+        let result = scoring::score_file(file_name, outcome, actual, manifest);
+        if let Some(wer) = result.wer {
+            tracing::info!(
+                "[TestMode] [{}/{}] WER {:.1}%, CER {:.1}%",
+                idx + 1,
+                total,
+                wer * 100.0,
+                result.cer.unwrap_or_default() * 100.0
+            );
         }
-        // If got_speech_ended but not got_transcription, it was a no-speech result
-        // -- counted as neither success nor timeout (just skipped by Whisper)
+        push_result(&mut results, idx, total, result);
 
         // (no inter-segment pause in demo/test mode)
     }
 
-    tracing::info!(
-        "[TestMode] Test run complete: {}/{} files, {} successful, {} timed out, {} skipped",
-        total,
-        total,
-        success_count,
-        timeout_count,
-        skip_count
-    );
+    results
 }
 
 /// Wait for a TranscriptionComplete event until the deadline.
@@ -281,7 +394,6 @@ fn wait_for_transcription(
     event_rx: &mut tokio::sync::broadcast::Receiver<flowstt_common::ipc::Response>,
     deadline: std::time::Instant,
 ) -> Option<String> {
-    use flowstt_common::ipc::{EventType, Response};
     use std::time::Duration;
 
     while std::time::Instant::now() < deadline {
@@ -2,13 +2,15 @@
 //!
 //! Plays WAV files to the system's default audio output device using rodio.
 //! The WASAPI loopback capture will pick up this audio for transcription.
+//! History recordings are played the same way, from memory so encrypted
+//! files can be decrypted first.
 
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 use std::path::Path;
 use std::sync::mpsc;
 
-use tracing::debug;
+use crate::playback::PlaybackHandle;
 
 /// Play a WAV file to the default audio output device.
 ///
@@ -23,32 +25,33 @@ pub fn play_wav(path: &Path) -> Result<mpsc::Receiver<()>, String> {
     let file = File::open(path).map_err(|e| format!("Failed to open WAV file: {}", e))?;
     let reader = BufReader::new(file);
 
-    // Open the default audio output device
-    let device_sink = rodio::DeviceSinkBuilder::from_default_device()
-        .map_err(|e| format!("Failed to find default audio output device: {}", e))?
-        .open_sink_or_fallback()
-        .map_err(|e| format!("Failed to open audio output device: {}", e))?;
+    let decoder =
+        rodio::Decoder::new(reader).map_err(|e| format!("Failed to start playback: {}", e))?;
 
-    // rodio::play() accepts Read + Seek (it handles decoding internally)
-    let player = rodio::play(device_sink.mixer(), reader)
-        .map_err(|e| format!("Failed to start playback: {}", e))?;
-
-    let (done_tx, done_rx) = mpsc::channel();
-
-    // Spawn a thread that owns the device sink and player, waits for playback to finish
     let file_name = path
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
-    std::thread::spawn(move || {
-        debug!("[TestMode Playback] Playing: {}", file_name);
-        player.sleep_until_end();
-        debug!("[TestMode Playback] Finished: {}", file_name);
-        // device_sink is kept alive by this closure; dropping it stops the output device
-        drop(device_sink);
-        let _ = done_tx.send(());
-    });
+    crate::playback::play_sources(vec![decoder], file_name)
+}
+
+/// Play in-memory WAV data to the default audio output device, with
+/// controls for stopping and seeking.
+///
+/// Returns the playback handle and a `mpsc::Receiver<()>` that signals when
+/// playback has finished or been stopped.
+///
+/// # Errors
+///
+/// Returns an error if the data cannot be decoded or if the audio output
+/// device is unavailable.
+pub(crate) fn play_wav_data(
+    data: Vec<u8>,
+    label: String,
+) -> Result<(PlaybackHandle, mpsc::Receiver<()>), String> {
+    let decoder = rodio::Decoder::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to start playback: {}", e))?;
 
-    Ok(done_rx)
+    crate::playback::play_controlled(decoder, label)
 }
@@ -0,0 +1,319 @@
+//! Scoring test runs against expected transcripts.
+//!
+//! A manifest maps WAV file names to the text they should transcribe to. It
+//! is either JSON, as an object (`{"a.wav": "expected text"}`) or a list of
+//! `{"file": "a.wav", "expected": "expected text"}` entries, or CSV with
+//! `file,expected` rows (the header row is optional). When no manifest is
+//! given, `manifest.json` or `manifest.csv` in the WAV directory is used.
+//!
+//! Word and character error rates are computed on normalized text
+//! (lowercase, punctuation removed) so formatting differences in Whisper's
+//! output are not counted as errors.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use flowstt_common::{TestFileOutcome, TestFileResult, TestRunSummary};
+use serde::{Deserialize, Serialize};
+
+/// Manifest file names looked for in the WAV directory
+const DEFAULT_MANIFESTS: [&str; 2] = ["manifest.json", "manifest.csv"];
+
+/// Expected transcripts keyed by WAV file name.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    expected: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonManifest {
+    Map(HashMap<String, String>),
+    List(Vec<JsonEntry>),
+}
+
+#[derive(Deserialize)]
+struct JsonEntry {
+    file: String,
+    expected: String,
+}
+
+impl Manifest {
+    /// Load a JSON or CSV manifest, chosen by file extension.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read manifest {:?}: {}", path, e))?;
+
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let entries = if is_csv {
+            parse_csv(&text)?
+        } else {
+            parse_json(&text)?
+        };
+
+        // Entries may include a directory; files are matched by name only
+        let expected = entries
+            .into_iter()
+            .map(|(file, text)| (file_name(&file), text))
+            .collect();
+
+        Ok(Self { expected })
+    }
+
+    /// Find the default manifest in a WAV directory.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        DEFAULT_MANIFESTS
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Expected transcript for a WAV file name.
+    pub fn expected(&self, file_name: &str) -> Option<&str> {
+        self.expected.get(file_name).map(String::as_str)
+    }
+
+    /// Number of files listed.
+    pub fn len(&self) -> usize {
+        self.expected.len()
+    }
+
+    /// Whether no files are listed.
+    pub fn is_empty(&self) -> bool {
+        self.expected.is_empty()
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path.trim())
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_json(text: &str) -> Result<Vec<(String, String)>, String> {
+    let manifest: JsonManifest =
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON manifest: {}", e))?;
+    Ok(match manifest {
+        JsonManifest::Map(map) => map.into_iter().collect(),
+        JsonManifest::List(entries) => entries.into_iter().map(|e| (e.file, e.expected)).collect(),
+    })
+}
+
+fn parse_csv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
+
+    for (index, row) in csv_rows(text).into_iter().enumerate() {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if index == 0 && row[0].trim().eq_ignore_ascii_case("file") {
+            continue;
+        }
+        match row.as_slice() {
+            [file, expected, ..] => entries.push((file.clone(), expected.clone())),
+            _ => {
+                return Err(format!(
+                    "CSV manifest row {} needs a file and an expected transcript",
+                    index + 1
+                ))
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Split CSV text into rows of fields. Quoted fields may contain commas,
+/// newlines and doubled quotes.
+fn csv_rows(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Lowercase and strip punctuation so only the spoken words are compared.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Levenshtein distance between two sequences.
+fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, x) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(x != y);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Edits needed to turn `actual` into `expected`, relative to the length of
+/// `expected`. An empty expected transcript scores 0.0 only if nothing was
+/// transcribed.
+fn error_rate<T: PartialEq>(expected: &[T], actual: &[T]) -> f64 {
+    if expected.is_empty() {
+        return if actual.is_empty() { 0.0 } else { 1.0 };
+    }
+    edit_distance(expected, actual) as f64 / expected.len() as f64
+}
+
+/// Word error rate of a transcription.
+pub fn word_error_rate(expected: &str, actual: &str) -> f64 {
+    let expected = normalize(expected);
+    let actual = normalize(actual);
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+    let actual: Vec<&str> = actual.split_whitespace().collect();
+    error_rate(&expected, &actual)
+}
+
+/// Character error rate of a transcription.
+pub fn char_error_rate(expected: &str, actual: &str) -> f64 {
+    let expected: Vec<char> = normalize(expected).chars().collect();
+    let actual: Vec<char> = normalize(actual).chars().collect();
+    error_rate(&expected, &actual)
+}
+
+/// Build the result for one file, scoring it if the manifest lists it.
+/// Files without a transcription are scored against empty text.
+pub fn score_file(
+    file: String,
+    outcome: TestFileOutcome,
+    actual: Option<String>,
+    manifest: Option<&Manifest>,
+) -> TestFileResult {
+    let expected = manifest.and_then(|m| m.expected(&file)).map(str::to_string);
+    let scored_text = actual.as_deref().unwrap_or("");
+    let (wer, cer) = match (&expected, outcome) {
+        // Playback never happened, so there is nothing to score
+        (_, TestFileOutcome::Skipped) | (None, _) => (None, None),
+        (Some(expected), _) => (
+            Some(word_error_rate(expected, scored_text)),
+            Some(char_error_rate(expected, scored_text)),
+        ),
+    };
+
+    TestFileResult {
+        file,
+        outcome,
+        expected,
+        actual,
+        wer,
+        cer,
+    }
+}
+
+/// Report written after each test run.
+#[derive(Debug, Serialize)]
+pub struct TestReport {
+    /// When the run started (RFC 3339)
+    pub started_at: String,
+    /// Directory the WAV files were read from
+    pub directory: PathBuf,
+    /// Manifest used for scoring, if any
+    pub manifest: Option<PathBuf>,
+    /// Totals across all files
+    pub summary: TestRunSummary,
+    /// Per-file results in run order
+    pub files: Vec<TestFileResult>,
+}
+
+impl TestReport {
+    /// Write the report as `test-results-<timestamp>.json` in the WAV directory.
+    pub fn write(&self) -> Result<PathBuf, String> {
+        let name = format!(
+            "test-results-{}.json",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let path = self.directory.join(name);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize test report: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_rates_ignore_case_and_punctuation() {
+        assert_eq!(word_error_rate("Hello, world!", "hello world"), 0.0);
+        assert_eq!(char_error_rate("Hello, world!", "hello world"), 0.0);
+    }
+
+    #[test]
+    fn test_word_error_rate_counts_edits() {
+        // One substitution and one deletion against four reference words
+        assert_eq!(word_error_rate("the quick brown fox", "the quack fox"), 0.5);
+        assert_eq!(word_error_rate("one two", ""), 1.0);
+        assert_eq!(word_error_rate("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_csv_manifest_with_header_and_quotes() {
+        let entries =
+            parse_csv("file,expected\na.wav,\"Hello, \"\"world\"\"\"\r\nb.wav,bye\n").unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("a.wav".to_string(), "Hello, \"world\"".to_string()),
+                ("b.wav".to_string(), "bye".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_manifest_forms() {
+        let map = parse_json(r#"{"a.wav": "hi"}"#).unwrap();
+        let list = parse_json(r#"[{"file": "a.wav", "expected": "hi"}]"#).unwrap();
+        assert_eq!(map, list);
+    }
+}
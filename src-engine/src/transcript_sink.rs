@@ -0,0 +1,129 @@
+//! Live transcript sink.
+//!
+//! With `transcript_sink` set in the config file, each transcription is
+//! appended to that file or named pipe as one line, formatted with the
+//! `transcript_sink` output template, so caption overlays and `tail -f`
+//! can follow along without speaking IPC.
+//!
+//! A named pipe is opened without blocking and kept open between lines, so
+//! a reader sees one continuous stream; lines are dropped while no reader
+//! is attached. A regular file is reopened for every line, so it can be
+//! rotated or deleted at any time. Ephemeral (privacy mode) transcriptions
+//! are never written.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+use tracing::debug;
+
+/// The named pipe kept open, with the path it was opened at
+static PIPE: Mutex<Option<(String, File)>> = Mutex::new(None);
+
+/// Append `line` to the sink at `path`. Line breaks in `line` become spaces
+/// so every transcription stays on one line.
+///
+/// A named pipe without a reader isn't an error; the line is dropped.
+pub fn append(path: &str, line: &str) -> Result<(), String> {
+    let line = format!("{}\n", line.trim().replace(['\r', '\n'], " "));
+
+    let mut pipe = PIPE.lock().unwrap();
+    if let Some((pipe_path, file)) = pipe.as_mut() {
+        if pipe_path == path {
+            match file.write_all(line.as_bytes()) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    // The reader went away; try again with a fresh handle
+                    debug!("[TranscriptSink] Pipe write failed: {}", e);
+                }
+            }
+        }
+        *pipe = None;
+    }
+
+    let mut file = match open(path) {
+        Ok(file) => file,
+        Err(e) if is_no_reader(&e) => {
+            debug!("[TranscriptSink] No reader on {}", path);
+            return Ok(());
+        }
+        Err(e) => return Err(format!("Failed to open {}: {}", path, e)),
+    };
+    file.write_all(line.as_bytes())
+        .and_then(|()| file.flush())
+        .map_err(|e| format!("Failed to write to {}: {}", path, e))?;
+    if is_pipe(&file) {
+        *pipe = Some((path.to_string(), file));
+    }
+    Ok(())
+}
+
+fn open(path: &str) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        // Opening a FIFO for writing would otherwise wait for a reader
+        options.custom_flags(libc::O_NONBLOCK);
+    }
+    options.open(path)
+}
+
+/// Whether opening failed because a named pipe has no reader.
+fn is_no_reader(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        e.raw_os_error() == Some(libc::ENXIO)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+fn is_pipe(file: &File) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        file.metadata().is_ok_and(|m| m.file_type().is_fifo())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_appended_one_per_transcription() {
+        let path = std::env::temp_dir().join(format!(
+            "flowstt-transcript-sink-{}.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let path_str = path.to_str().unwrap();
+
+        append(path_str, "first line\nwrapped").unwrap();
+        append(path_str, "  second  ").unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "first line wrapped\nsecond\n"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn unwritable_paths_are_reported() {
+        let path = std::env::temp_dir()
+            .join("flowstt-no-such-dir")
+            .join("transcript.txt");
+        assert!(append(path.to_str().unwrap(), "lost").is_err());
+    }
+}
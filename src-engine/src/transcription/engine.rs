@@ -0,0 +1,70 @@
+//! Pluggable speech-to-text backends.
+//!
+//! The transcription worker only talks to a [`TranscriptionEngine`], so the
+//! whisper.cpp [`Transcriber`] can be swapped for the model-free
+//! [`MockTranscriptionEngine`] in integration tests. The engine is chosen by
+//! the `transcription_engine` config setting or the
+//! `FLOWSTT_TRANSCRIPTION_ENGINE` environment variable.
+
+use std::path::Path;
+
+use flowstt_common::config::TranscriptionEngineKind;
+
+use super::{MockTranscriptionEngine, Transcriber};
+
+/// A backend that turns 16kHz mono audio into text.
+pub trait TranscriptionEngine {
+    /// Short name used in logs.
+    fn name(&self) -> &'static str;
+
+    /// File name of the model transcripts come from, if the engine uses one.
+    fn model_name(&self) -> Option<String> {
+        None
+    }
+
+    /// Get ready to transcribe, e.g. by loading a model. Called once when the
+    /// worker starts; failures are logged and retried by `transcribe`.
+    fn prepare(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Transcribe a segment of 16kHz mono audio.
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String>;
+}
+
+impl TranscriptionEngine for Transcriber {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    fn model_name(&self) -> Option<String> {
+        model_file_name(self.get_model_path())
+    }
+
+    fn prepare(&mut self) -> Result<(), String> {
+        // A missing model is reported when transcription is attempted
+        if self.is_model_available() {
+            self.load_model()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+        Transcriber::transcribe(self, audio)
+    }
+}
+
+/// File name of a model path, as shown in history.
+pub fn model_file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Create the engine for a configured kind.
+pub fn create_engine(kind: TranscriptionEngineKind) -> Box<dyn TranscriptionEngine> {
+    match kind {
+        TranscriptionEngineKind::Whisper => Box::new(Transcriber::new()),
+        TranscriptionEngineKind::Mock => Box::new(MockTranscriptionEngine::new()),
+    }
+}
@@ -0,0 +1,115 @@
+//! Deterministic transcription without a model.
+//!
+//! [`MockTranscriptionEngine`] returns immediately with text derived from the
+//! segment's duration and a hash of its samples, so the same audio always
+//! produces the same transcript. This exercises IPC, history, clipboard and
+//! event flow end to end without whisper.cpp or a downloaded model.
+
+use super::engine::TranscriptionEngine;
+
+/// Sample rate of the audio handed to engines
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// Segments whose peak stays below this are treated as silence
+const SILENCE_PEAK: f32 = 1e-4;
+
+/// Seconds of audio per generated word
+const SECONDS_PER_WORD: f32 = 0.5;
+
+/// Cap on generated words so long segments stay readable
+const MAX_WORDS: usize = 24;
+
+/// Vocabulary for generated transcripts
+const WORDS: [&str; 16] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa",
+];
+
+/// Transcription engine that returns canned text instantly.
+#[derive(Debug, Default)]
+pub struct MockTranscriptionEngine;
+
+impl MockTranscriptionEngine {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TranscriptionEngine for MockTranscriptionEngine {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+        Ok(mock_transcript(audio))
+    }
+}
+
+/// Text for a segment: one word per [`SECONDS_PER_WORD`] of audio, picked by
+/// the sample hash, followed by the duration and hash.
+fn mock_transcript(audio: &[f32]) -> String {
+    // Match Whisper's output for silent segments so no-speech paths are covered
+    if audio.iter().all(|s| s.abs() < SILENCE_PEAK) {
+        return "(No speech detected)".to_string();
+    }
+
+    let seconds = audio.len() as f32 / SAMPLE_RATE;
+    let hash = fnv1a(audio);
+    let count = ((seconds / SECONDS_PER_WORD).ceil() as usize).clamp(1, MAX_WORDS);
+
+    let mut state = hash;
+    let words: Vec<&str> = (0..count)
+        .map(|_| {
+            state = xorshift(state);
+            WORDS[(state % WORDS.len() as u64) as usize]
+        })
+        .collect();
+
+    format!("Mock {} ({:.2}s, {:016x}).", words.join(" "), seconds, hash)
+}
+
+/// FNV-1a over the bit patterns of the samples.
+fn fnv1a(audio: &[f32]) -> u64 {
+    audio
+        .iter()
+        .flat_map(|s| s.to_bits().to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+fn xorshift(mut x: u64) -> u64 {
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(samples: usize, step: f32) -> Vec<f32> {
+        (0..samples)
+            .map(|i| (i as f32 * step).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_mock_transcript_is_deterministic() {
+        let audio = tone(24000, 0.05);
+        let text = mock_transcript(&audio);
+        assert_eq!(text, mock_transcript(&audio));
+        assert_ne!(text, mock_transcript(&tone(24000, 0.07)));
+        // 1.5 seconds of audio gives three words
+        assert!(text.starts_with("Mock "));
+        assert!(text.contains("(1.50s, "));
+        assert_eq!(text.split(" (").next().unwrap().split(' ').count(), 4);
+    }
+
+    #[test]
+    fn test_mock_transcript_silence() {
+        assert_eq!(mock_transcript(&[0.0; 16000]), "(No speech detected)");
+        assert_eq!(mock_transcript(&[]), "(No speech detected)");
+    }
+}
@@ -6,15 +6,25 @@
 //!
 //! - [`whisper_ffi`]: Low-level FFI bindings to whisper.cpp
 //! - [`transcriber`]: High-level transcription API
+//! - [`engine`]: Backend trait and engine selection
+//! - [`mock`]: Deterministic model-free engine for integration tests
 //! - [`queue`]: Async transcription queue with worker thread
 //! - [`transcribe_state`]: State management for continuous transcription mode
+//! - [`segment_dump`]: Debug dumps of segments as submitted for transcription
+//! - [`stitching`]: Dropping words repeated across overlapping segments
 
+pub mod engine;
+pub mod mock;
 pub mod queue;
+pub mod segment_dump;
+pub mod stitching;
 pub mod transcribe_state;
 pub mod transcriber;
 pub mod whisper_ffi;
 
 // Re-export main types
-pub use queue::{TranscriptionCallback, TranscriptionQueue};
+pub use engine::{create_engine, TranscriptionEngine};
+pub use mock::MockTranscriptionEngine;
+pub use queue::{CompletedSegment, Retranscription, TranscriptionCallback, TranscriptionQueue};
 pub use transcribe_state::TranscribeState;
-pub use transcriber::{download_model, Transcriber};
+pub use transcriber::{download_model, model_path_for, Transcriber};
@@ -1,21 +1,50 @@
 //! Transcription queue for async processing.
 //!
 //! This module provides a bounded queue for audio segments awaiting transcription,
-//! with a worker thread that processes segments sequentially.
+//! with a worker thread that processes segments sequentially. The worker
+//! sleeps on a condition variable while the queue is empty.
+//!
+//! A panic while a segment is processed (in the whisper.cpp bindings, say) is
+//! caught: the segment is reported as failed and the engine is started again,
+//! waiting longer each time it crashes in a row.
 
+use std::any::Any;
 use std::collections::VecDeque;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
+use std::time::Duration;
+
+use flowstt_common::config::TranscriptionEngineKind;
+use flowstt_common::{EngineComponent, TranscriptionMode};
 
 use crate::audio::{process_recorded_audio, RawRecordedAudio};
+use crate::audio_stream::StreamSegment;
+use crate::meeting::MeetingSegment;
+use crate::partial_typing::InterimSegment;
 
-use super::Transcriber;
+use super::engine::model_file_name;
+use super::segment_dump::{self, SegmentMetadata};
+use super::stitching::strip_overlap;
+use super::{create_engine, Transcriber, TranscriptionEngine};
 
 /// Maximum queue size for transcription segments
 const MAX_QUEUE_SIZE: usize = 10;
 
+/// Longest the idle worker sleeps before beating its heartbeat again
+const IDLE_WAKE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Wait before restarting the engine after it crashes
+const RESTART_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait before restarting an engine that keeps crashing
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// Engine restarts after a crash, for the status
+static WORKER_RESTARTS: AtomicU64 = AtomicU64::new(0);
+
 /// A segment of audio queued for transcription.
 pub struct QueuedSegment {
     /// Audio samples (raw, may be multi-channel)
@@ -26,6 +55,42 @@ pub struct QueuedSegment {
     pub channels: u16,
     /// Path to saved WAV file (if saved)
     pub wav_path: Option<PathBuf>,
+    /// Captured in privacy mode: nothing derived from it may be written to disk
+    pub ephemeral: bool,
+    /// How the segment was cut, for debug dumps (`None` when not from live capture)
+    pub metadata: Option<SegmentMetadata>,
+    /// Set when a history entry's recording is being transcribed again
+    pub retranscription: Option<Retranscription>,
+    /// Set when the segment is one source of a meeting in progress
+    pub meeting: Option<MeetingSegment>,
+    /// Set when the result goes to an audio stream rather than the output:
+    /// a client stream, or the latency benchmark
+    pub stream: Option<StreamSegment>,
+    /// Set when the segment is the live speech so far, transcribed to type
+    /// as you speak
+    pub interim: Option<InterimSegment>,
+}
+
+/// A history entry being transcribed again.
+pub struct Retranscription {
+    /// History entry to update with the result
+    pub entry_id: String,
+    /// Model to use instead of the worker's engine (`None` = the worker's engine)
+    pub model_path: Option<PathBuf>,
+}
+
+/// A transcribed segment, as recorded in history.
+pub struct CompletedSegment {
+    /// Path to the saved WAV file (if saved)
+    pub wav_path: Option<String>,
+    /// Length of the audio in milliseconds
+    pub duration_ms: u64,
+    /// Mode the segment was captured in (`None` when not from live capture)
+    pub capture_mode: Option<TranscriptionMode>,
+    /// File name of the model that transcribed it, if the engine uses one
+    pub model: Option<String>,
+    /// Captured in privacy mode: the result must not be persisted
+    pub ephemeral: bool,
 }
 
 /// Callback trait for transcription events.
@@ -36,11 +101,38 @@ pub trait TranscriptionCallback: Send + Sync + 'static {
     fn on_transcription_started(&self);
 
     /// Called when transcription completes successfully.
-    fn on_transcription_complete(&self, text: String, wav_path: Option<String>);
+    ///
+    /// Results of `ephemeral` segments come from privacy mode and must not be
+    /// persisted.
+    fn on_transcription_complete(&self, text: String, segment: CompletedSegment);
 
     /// Called when transcription fails.
     fn on_transcription_error(&self, error: String);
 
+    /// Called with the result of re-transcribing a history entry, instead of
+    /// `on_transcription_complete` or `on_transcription_error`.
+    fn on_retranscription_complete(&self, job: Retranscription, result: Result<String, String>);
+
+    /// Called with the result of transcribing one source of a meeting, instead
+    /// of `on_transcription_complete` or `on_transcription_error`.
+    fn on_meeting_transcription_complete(
+        &self,
+        segment: MeetingSegment,
+        result: Result<String, String>,
+    );
+
+    /// Called with the result of transcribing a segment of a client audio
+    /// stream, instead of `on_transcription_complete` or `on_transcription_error`.
+    fn on_stream_transcription_complete(
+        &self,
+        segment: StreamSegment,
+        result: Result<String, String>,
+    );
+
+    /// Called with the result of transcribing the live speech so far, instead
+    /// of `on_transcription_complete` or `on_transcription_error`.
+    fn on_interim_transcription(&self, segment: InterimSegment, result: Result<String, String>);
+
     /// Called when transcription finishes (GPU no longer active).
     fn on_transcription_finished(&self);
 
@@ -52,12 +144,18 @@ pub trait TranscriptionCallback: Send + Sync + 'static {
 pub struct TranscriptionQueue {
     /// The queue of segments
     queue: Arc<Mutex<VecDeque<QueuedSegment>>>,
+    /// Signalled when a segment is added or taken, or the worker is stopped
+    changed: Arc<Condvar>,
     /// Flag indicating worker should continue running
     worker_active: Arc<AtomicBool>,
+    /// Flag indicating the worker thread has not exited yet
+    worker_running: Arc<AtomicBool>,
     /// Count of segments currently in queue
     queue_count: Arc<AtomicUsize>,
     /// Callback for transcription events
     callback: Arc<Mutex<Option<Arc<dyn TranscriptionCallback>>>>,
+    /// Whisper model used instead of the default one
+    model: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl TranscriptionQueue {
@@ -65,9 +163,12 @@ impl TranscriptionQueue {
     pub fn new() -> Self {
         Self {
             queue: Arc::new(Mutex::new(VecDeque::new())),
+            changed: Arc::new(Condvar::new()),
             worker_active: Arc::new(AtomicBool::new(false)),
+            worker_running: Arc::new(AtomicBool::new(false)),
             queue_count: Arc::new(AtomicUsize::new(0)),
             callback: Arc::new(Mutex::new(None)),
+            model: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -81,6 +182,17 @@ impl TranscriptionQueue {
         *self.callback.lock().unwrap() = None;
     }
 
+    /// Transcribe with this whisper model instead of the default one, from
+    /// the next time the worker starts.
+    pub fn set_model(&self, model: Option<PathBuf>) {
+        *self.model.lock().unwrap() = model;
+    }
+
+    /// The whisper model used instead of the default one, if any.
+    pub fn model(&self) -> Option<PathBuf> {
+        self.model.lock().unwrap().clone()
+    }
+
     /// Get the current queue depth.
     pub fn queue_depth(&self) -> usize {
         self.queue_count.load(Ordering::SeqCst)
@@ -91,6 +203,28 @@ impl TranscriptionQueue {
         self.worker_active.load(Ordering::SeqCst)
     }
 
+    /// Check if the worker thread is still running. After `stop_worker` this
+    /// stays true until the remaining queue has been drained.
+    pub fn is_worker_running(&self) -> bool {
+        self.worker_running.load(Ordering::SeqCst)
+    }
+
+    /// Check if the queue has no room for another segment.
+    pub fn is_full(&self) -> bool {
+        self.queue_depth() >= MAX_QUEUE_SIZE
+    }
+
+    /// Wait until the queue has room for another segment, for at most
+    /// `timeout`. Returns whether there is room.
+    pub fn wait_for_room(&self, timeout: Duration) -> bool {
+        let queue = self.queue.lock().unwrap();
+        let (queue, _) = self
+            .changed
+            .wait_timeout_while(queue, timeout, |queue| queue.len() >= MAX_QUEUE_SIZE)
+            .unwrap();
+        queue.len() < MAX_QUEUE_SIZE
+    }
+
     /// Enqueue a segment for transcription.
     /// Returns false if queue is full (segment was not added).
     pub fn enqueue(&self, segment: QueuedSegment) -> bool {
@@ -102,6 +236,7 @@ impl TranscriptionQueue {
         queue.push_back(segment);
         let depth = queue.len();
         self.queue_count.store(depth, Ordering::SeqCst);
+        self.changed.notify_all();
 
         // Notify callback of queue update
         if let Some(ref cb) = *self.callback.lock().unwrap() {
@@ -111,30 +246,36 @@ impl TranscriptionQueue {
         true
     }
 
-    /// Start the transcription worker thread.
-    pub fn start_worker(&self, model_path: PathBuf) {
+    /// Start the transcription worker thread with the given engine.
+    pub fn start_worker(&self, engine_kind: TranscriptionEngineKind) {
         if self.worker_active.load(Ordering::SeqCst) {
             return; // Already running
         }
 
         self.worker_active.store(true, Ordering::SeqCst);
+        self.worker_running.store(true, Ordering::SeqCst);
 
         let queue = Arc::clone(&self.queue);
+        let changed = Arc::clone(&self.changed);
         let worker_active = Arc::clone(&self.worker_active);
+        let worker_running = Arc::clone(&self.worker_running);
         let queue_count = Arc::clone(&self.queue_count);
         let callback = Arc::clone(&self.callback);
+        let model = self.model();
 
         thread::spawn(move || {
-            let mut transcriber = Transcriber::new();
+            let heartbeat = crate::watchdog::register(EngineComponent::TranscriptionWorker);
+            let mut worker = Worker::start(engine_kind, model.clone());
+            // Crashes since a segment was last transcribed without one
+            let mut crashes: u32 = 0;
 
-            // Try to load model at start
-            if model_path.exists() {
-                if let Err(e) = transcriber.load_model() {
-                    tracing::error!("[TranscriptionQueue] Failed to load model: {}", e);
+            loop {
+                // The worker the watchdog started in our place owns the queue now
+                if !heartbeat.beat() {
+                    tracing::warn!("[TranscriptionQueue] Replaced by the watchdog, exiting");
+                    return;
                 }
-            }
 
-            loop {
                 // Check if we should stop
                 if !worker_active.load(Ordering::SeqCst) {
                     // Drain remaining queue before exiting
@@ -157,6 +298,7 @@ impl TranscriptionQueue {
 
                     // Notify callback of queue update
                     if seg.is_some() {
+                        changed.notify_all();
                         if let Some(ref cb) = *callback.lock().unwrap() {
                             cb.on_queue_update(depth);
                         }
@@ -165,68 +307,69 @@ impl TranscriptionQueue {
                     seg
                 };
 
-                match segment {
-                    Some(seg) => {
-                        // Process the segment
-                        let raw_audio = RawRecordedAudio {
-                            samples: seg.samples,
-                            sample_rate: seg.sample_rate,
-                            channels: seg.channels,
-                        };
-
-                        let wav_path_str = seg
-                            .wav_path
-                            .as_ref()
-                            .map(|p| p.to_string_lossy().to_string());
-
-                        // Convert to format suitable for Whisper
-                        match process_recorded_audio(raw_audio) {
-                            Ok(processed) => {
-                                // Notify that transcription is starting
-                                if let Some(ref cb) = *callback.lock().unwrap() {
-                                    cb.on_transcription_started();
-                                }
-
-                                // Transcribe
-                                match transcriber.transcribe(&processed) {
-                                    Ok(text) => {
-                                        if let Some(ref cb) = *callback.lock().unwrap() {
-                                            cb.on_transcription_complete(text, wav_path_str);
-                                        }
-                                    }
-                                    Err(e) => {
-                                        if let Some(ref cb) = *callback.lock().unwrap() {
-                                            cb.on_transcription_error(e);
-                                        }
-                                    }
-                                }
-
-                                // Notify that transcription finished
-                                if let Some(ref cb) = *callback.lock().unwrap() {
-                                    cb.on_transcription_finished();
-                                }
-                            }
-                            Err(e) => {
-                                if let Some(ref cb) = *callback.lock().unwrap() {
-                                    cb.on_transcription_error(e);
-                                }
-                            }
-                        }
+                let Some(mut seg) = segment else {
+                    // Sleep until a segment arrives or the worker is stopped,
+                    // waking now and then to beat the heartbeat
+                    let q = queue.lock().unwrap();
+                    if q.is_empty() && worker_active.load(Ordering::SeqCst) {
+                        let _ = changed.wait_timeout(q, IDLE_WAKE_INTERVAL).unwrap();
                     }
-                    None => {
-                        // No segment available, sleep briefly
-                        thread::sleep(std::time::Duration::from_millis(50));
+                    continue;
+                };
+
+                // Kept out here so a segment that crashes the engine can still be failed
+                let mut route = Some(Route {
+                    retranscription: seg.retranscription.take(),
+                    meeting: seg.meeting.take(),
+                    stream: seg.stream.take(),
+                    interim: seg.interim.take(),
+                });
+                let processed = catch_unwind(AssertUnwindSafe(|| {
+                    worker.process(seg, &mut route, &callback)
+                }));
+                let Err(payload) = processed else {
+                    crashes = 0;
+                    continue;
+                };
+
+                let error = format!("Transcription engine crashed: {}", panic_message(&*payload));
+                tracing::error!("[TranscriptionQueue] {}", error);
+                // A callback that panicked left the lock poisoned
+                callback.clear_poison();
+                if let Some(route) = route.take() {
+                    if let Some(ref cb) = *callback.lock().unwrap() {
+                        route.fail(cb.as_ref(), error);
+                        cb.on_transcription_finished();
                     }
                 }
+
+                // Whatever the engine held may be broken, so start it afresh
+                crashes += 1;
+                let delay = restart_delay(crashes);
+                WORKER_RESTARTS.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    "[TranscriptionQueue] Restarting the engine in {} ms",
+                    delay.as_millis()
+                );
+                drop(worker);
+                thread::sleep(delay);
+                worker = Worker::start(engine_kind, model.clone());
             }
 
             tracing::info!("[TranscriptionQueue] Worker thread exiting");
+            worker_running.store(false, Ordering::SeqCst);
         });
     }
 
     /// Stop the transcription worker (will drain remaining queue).
+    ///
+    /// Only signals the thread and returns at once; it never waits for the
+    /// thread to exit, so it is safe to call on a worker that is hung.
     pub fn stop_worker(&self) {
         self.worker_active.store(false, Ordering::SeqCst);
+        // Taking the lock makes sure an idle worker is waiting, not about to
+        let _queue = self.queue.lock().unwrap();
+        self.changed.notify_all();
     }
 
     /// Clear the queue (discard pending segments).
@@ -234,6 +377,7 @@ impl TranscriptionQueue {
         let mut queue = self.queue.lock().unwrap();
         queue.clear();
         self.queue_count.store(0, Ordering::SeqCst);
+        self.changed.notify_all();
 
         // Notify callback
         if let Some(ref cb) = *self.callback.lock().unwrap() {
@@ -247,3 +391,227 @@ impl Default for TranscriptionQueue {
         Self::new()
     }
 }
+
+/// Where the result of a segment goes.
+struct Route {
+    retranscription: Option<Retranscription>,
+    meeting: Option<MeetingSegment>,
+    stream: Option<StreamSegment>,
+    interim: Option<InterimSegment>,
+}
+
+impl Route {
+    /// Report that the segment could not be transcribed.
+    fn fail(self, cb: &dyn TranscriptionCallback, error: String) {
+        let Route {
+            retranscription,
+            meeting,
+            stream,
+            interim,
+        } = self;
+        match (retranscription, meeting, stream, interim) {
+            (Some(job), _, _, _) => cb.on_retranscription_complete(job, Err(error)),
+            (None, Some(segment), _, _) => {
+                cb.on_meeting_transcription_complete(segment, Err(error))
+            }
+            (None, None, Some(segment), _) => {
+                cb.on_stream_transcription_complete(segment, Err(error))
+            }
+            (None, None, None, Some(segment)) => cb.on_interim_transcription(segment, Err(error)),
+            (None, None, None, None) => cb.on_transcription_error(error),
+        }
+    }
+}
+
+/// Engine and state of the worker thread, started afresh after a crash.
+struct Worker {
+    engine: Box<dyn TranscriptionEngine>,
+    /// Last model requested for a re-transcription, kept loaded for the next one
+    alternate: Option<Transcriber>,
+    /// Last live capture transcript, for stitching overlapping segments
+    previous_text: String,
+}
+
+impl Worker {
+    fn start(engine_kind: TranscriptionEngineKind, model: Option<PathBuf>) -> Self {
+        // Engines are created on the worker thread; whisper contexts stay there
+        let mut engine: Box<dyn TranscriptionEngine> = match model {
+            Some(model) if engine_kind == TranscriptionEngineKind::Whisper => {
+                Box::new(Transcriber::with_model_path(model))
+            }
+            _ => create_engine(engine_kind),
+        };
+        tracing::info!("[TranscriptionQueue] Using {} engine", engine.name());
+
+        if let Err(e) = engine.prepare() {
+            tracing::error!("[TranscriptionQueue] Failed to load model: {}", e);
+        }
+
+        Self {
+            engine,
+            alternate: None,
+            previous_text: String::new(),
+        }
+    }
+
+    /// Transcribe a segment and report the result to wherever `route` says,
+    /// taking it once the result is reported.
+    fn process(
+        &mut self,
+        seg: QueuedSegment,
+        route: &mut Option<Route>,
+        callback: &Mutex<Option<Arc<dyn TranscriptionCallback>>>,
+    ) {
+        let frames = seg.samples.len() / usize::from(seg.channels.max(1));
+        let duration_ms = frames as u64 * 1000 / u64::from(seg.sample_rate.max(1));
+
+        // Process the segment
+        let raw_audio = RawRecordedAudio {
+            samples: seg.samples,
+            sample_rate: seg.sample_rate,
+            channels: seg.channels,
+        };
+
+        let wav_path_str = seg
+            .wav_path
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string());
+        let ephemeral = seg.ephemeral;
+        let metadata = seg.metadata;
+
+        // Convert to format suitable for Whisper
+        let normalization = crate::config::current_config().segment_normalization;
+        let processed = match process_recorded_audio(raw_audio, normalization) {
+            Ok(processed) => processed,
+            Err(e) => {
+                if let Some(ref cb) = *callback.lock().unwrap() {
+                    if let Some(route) = route.take() {
+                        route.fail(cb.as_ref(), e);
+                    }
+                }
+                return;
+            }
+        };
+
+        // Notify that transcription is starting
+        if let Some(ref cb) = *callback.lock().unwrap() {
+            cb.on_transcription_started();
+        }
+
+        // Transcribe
+        // Re-transcriptions name their model; load shedding
+        // or the power policy may pick a smaller one
+        let model_path = route
+            .as_ref()
+            .and_then(|r| r.retranscription.as_ref())
+            .and_then(|r| r.model_path.clone())
+            .or_else(|| {
+                crate::load_shedding::model_override()
+                    .or_else(crate::power::model_override)
+                    .filter(|_| self.engine.name() == "whisper")
+            });
+        let model_used = match model_path {
+            Some(ref model_path) => model_file_name(model_path),
+            None => self.engine.model_name(),
+        };
+        let result = match model_path {
+            Some(model_path) => {
+                let mut transcriber = match self.alternate.take() {
+                    Some(t) if *t.get_model_path() == model_path => t,
+                    _ => Transcriber::with_model_path(model_path),
+                };
+                let result = transcriber.transcribe(&processed);
+                self.alternate = Some(transcriber);
+                result
+            }
+            None => self.engine.transcribe(&processed),
+        };
+
+        let dump = metadata
+            .as_ref()
+            .filter(|_| !ephemeral && segment_dump::is_enabled());
+        if let Some(metadata) = dump {
+            match segment_dump::dump_segment(&processed, metadata, self.engine.name(), &result) {
+                Ok(path) => tracing::info!("[TranscriptionQueue] Dumped segment to {:?}", path),
+                Err(e) => tracing::warn!("[TranscriptionQueue] Failed to dump segment: {}", e),
+            }
+        }
+
+        if let Some(ref cb) = *callback.lock().unwrap() {
+            let Some(route) = route.take() else {
+                return;
+            };
+            let Route {
+                retranscription,
+                meeting,
+                stream,
+                interim,
+            } = route;
+            match (retranscription, meeting, stream, interim, result) {
+                (Some(job), _, _, _, result) => cb.on_retranscription_complete(job, result),
+                (None, Some(segment), _, _, result) => {
+                    cb.on_meeting_transcription_complete(segment, result)
+                }
+                (None, None, Some(segment), _, result) => {
+                    cb.on_stream_transcription_complete(segment, result)
+                }
+                (None, None, None, Some(segment), result) => {
+                    cb.on_interim_transcription(segment, result)
+                }
+                (None, None, None, None, Ok(text)) => {
+                    let overlaps = metadata.as_ref().is_some_and(|m| m.overlap_ms > 0);
+                    let text = if overlaps {
+                        strip_overlap(&self.previous_text, &text).to_string()
+                    } else {
+                        text
+                    };
+                    self.previous_text.clone_from(&text);
+                    let capture_mode = metadata.as_ref().map(|m| {
+                        if m.ptt {
+                            TranscriptionMode::PushToTalk
+                        } else {
+                            TranscriptionMode::Automatic
+                        }
+                    });
+                    cb.on_transcription_complete(
+                        text,
+                        CompletedSegment {
+                            wav_path: wav_path_str,
+                            duration_ms,
+                            capture_mode,
+                            model: model_used,
+                            ephemeral,
+                        },
+                    )
+                }
+                (None, None, None, None, Err(e)) => cb.on_transcription_error(e),
+            }
+        }
+
+        // Notify that transcription finished
+        if let Some(ref cb) = *callback.lock().unwrap() {
+            cb.on_transcription_finished();
+        }
+    }
+}
+
+/// Times the engine has been restarted after crashing since the engine started.
+pub fn worker_restarts() -> u64 {
+    WORKER_RESTARTS.load(Ordering::Relaxed)
+}
+
+/// Wait before restarting the engine after the `crashes`th crash in a row,
+/// doubling from [`RESTART_DELAY`] up to [`MAX_RESTART_DELAY`].
+fn restart_delay(crashes: u32) -> Duration {
+    RESTART_DELAY
+        .saturating_mul(1 << crashes.saturating_sub(1).min(16))
+        .min(MAX_RESTART_DELAY)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
@@ -0,0 +1,160 @@
+//! Debug dumps of the audio each segment was transcribed from.
+//!
+//! With `dump_segments` enabled in the config, the transcription worker
+//! writes every segment exactly as it was handed to the engine (mixed down
+//! and resampled to 16kHz mono) to `segment-dumps/` in the data directory.
+//! Next to each WAV is a JSON file describing how the segment was cut, its
+//! levels, and what it was transcribed as, so a segment that "heard something
+//! completely different" can be attached to a bug report as-is.
+//!
+//! Dumps are written unencrypted so they can be shared. Segments captured in
+//! privacy mode are never dumped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use flowstt_dsp::{amplitude_to_db, rms};
+use serde::Serialize;
+
+use crate::config::current_config;
+use crate::history::TranscriptionHistory;
+
+/// Sample rate of the audio handed to engines
+const DUMP_SAMPLE_RATE: u32 = 16000;
+
+/// Why a segment ended where it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentBoundary {
+    /// Speech ended (or the PTT key was released)
+    SpeechEnded,
+    /// Cut at a pause after the segment reached its maximum duration
+    WordBreak,
+    /// No pause was found in time after the maximum duration
+    GracePeriod,
+    /// Cut before the capture ring buffer overwrote the segment
+    BufferOverflow,
+    /// A long PTT hold was split
+    PttSplit,
+    /// Capture stopped mid-segment
+    CaptureStopped,
+}
+
+/// How and when a segment was captured.
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentMetadata {
+    /// When the segment was cut (RFC 3339)
+    pub captured_at: String,
+    /// Why the segment ended
+    pub boundary: SegmentBoundary,
+    /// Captured in push-to-talk mode
+    pub ptt: bool,
+    /// Sample rate of the capture before resampling
+    pub capture_sample_rate: u32,
+    /// Channel count of the capture before mixing down
+    pub capture_channels: u16,
+    /// Pre-speech audio included from before speech was detected
+    pub lookback_ms: u64,
+    /// Audio recorded after speech was detected
+    pub speech_ms: u64,
+    /// Audio repeated from the end of the previous segment, which was cut
+    /// mid-speech
+    #[serde(skip_serializing_if = "is_zero")]
+    pub overlap_ms: u64,
+    /// Offset of the pause the segment was cut at, from speech start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_break_offset_ms: Option<u32>,
+    /// Length of that pause
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_break_gap_ms: Option<u32>,
+}
+
+fn is_zero(ms: &u64) -> bool {
+    *ms == 0
+}
+
+/// JSON written next to each dumped WAV.
+#[derive(Serialize)]
+struct DumpRecord<'a> {
+    audio_file: String,
+    sample_rate: u32,
+    duration_ms: u64,
+    rms_db: f32,
+    peak_db: f32,
+    engine: &'a str,
+    segment: &'a SegmentMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transcript: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// Whether segment dumps are enabled in the config.
+pub fn is_enabled() -> bool {
+    current_config().dump_segments
+}
+
+/// Directory dumps are written to.
+pub fn dumps_dir() -> PathBuf {
+    TranscriptionHistory::data_dir().join("segment-dumps")
+}
+
+/// Write a segment's 16kHz mono audio and its metadata, returning the WAV path.
+pub fn dump_segment(
+    audio: &[f32],
+    metadata: &SegmentMetadata,
+    engine: &str,
+    result: &Result<String, String>,
+) -> Result<PathBuf, String> {
+    let dir = dumps_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    crate::disk_space::ensure_room(&dir, audio.len() as u64 * 4)?;
+
+    let stem = format!(
+        "segment-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S-%3f")
+    );
+    let wav_path = dir.join(format!("{}.wav", stem));
+    let json_path = dir.join(format!("{}.json", stem));
+
+    write_wav(audio, &wav_path)?;
+
+    let peak = audio.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+    let record = DumpRecord {
+        audio_file: format!("{}.wav", stem),
+        sample_rate: DUMP_SAMPLE_RATE,
+        duration_ms: audio.len() as u64 * 1000 / u64::from(DUMP_SAMPLE_RATE),
+        rms_db: amplitude_to_db(rms(audio)),
+        peak_db: amplitude_to_db(peak),
+        engine,
+        segment: metadata,
+        transcript: result.as_deref().ok(),
+        error: result.as_ref().err().map(String::as_str),
+    };
+    let json = serde_json::to_string_pretty(&record)
+        .map_err(|e| format!("Failed to serialize segment metadata: {}", e))?;
+    fs::write(&json_path, json).map_err(|e| format!("Failed to write {:?}: {}", json_path, e))?;
+
+    Ok(wav_path)
+}
+
+/// Write plain 32-bit float mono WAV, bypassing history encryption.
+fn write_wav(audio: &[f32], path: &Path) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: DUMP_SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    for &sample in audio {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize {:?}: {}", path, e))
+}
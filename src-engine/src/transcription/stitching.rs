@@ -0,0 +1,71 @@
+//! Stitching overlapping segments.
+//!
+//! When a segment has to be cut mid-speech (no pause came in time, the
+//! capture buffer filled up, or push-to-talk was held too long), the next
+//! segment starts `segment_overlap_ms` before the cut so a word split in half
+//! is heard whole. The words in that overlap come out at the end of one
+//! transcript and again at the start of the next; they are dropped from the
+//! second.
+
+/// Most words looked for in the overlap
+const MAX_OVERLAP_WORDS: usize = 8;
+
+/// `text` without the leading words it repeats from the end of `previous`.
+pub fn strip_overlap<'a>(previous: &str, text: &'a str) -> &'a str {
+    let previous: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let words: Vec<(usize, String)> = word_starts(text)
+        .take(MAX_OVERLAP_WORDS + 1)
+        .map(|(start, word)| (start, normalize(word)))
+        .collect();
+
+    let longest = MAX_OVERLAP_WORDS.min(previous.len()).min(words.len());
+    let repeated = (1..=longest).rev().find(|&n| {
+        let tail = &previous[previous.len() - n..];
+        let head = &words[..n];
+        tail.iter()
+            .zip(head)
+            .all(|(a, (_, b))| !a.is_empty() && a == b)
+    });
+
+    match repeated {
+        Some(n) => words.get(n).map_or("", |&(start, _)| &text[start..]),
+        None => text.trim_start(),
+    }
+}
+
+/// Whitespace-separated words of `text` with their byte offsets.
+fn word_starts(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// A word lowercased and without punctuation, for comparison.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_overlap() {
+        assert_eq!(
+            strip_overlap("we walked down to the", "to the river and back."),
+            "river and back."
+        );
+        assert_eq!(
+            strip_overlap("It was getting late,", " Late, so we left."),
+            "so we left."
+        );
+        assert_eq!(
+            strip_overlap("nothing in common", "here at all"),
+            "here at all"
+        );
+        assert_eq!(strip_overlap("the end", "The end."), "");
+        assert_eq!(strip_overlap("", " Hello"), "Hello");
+    }
+}
@@ -4,15 +4,22 @@
 //! - `SegmentRingBuffer`: A ring buffer for continuous audio capture
 //! - `TranscribeState`: State management for transcribe mode
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+
+use flowstt_common::config::{SegmentationStrategy, CAPTURE_BUFFER_SECS, MAX_SEGMENT_OVERLAP_MS};
 
 use crate::audio::{generate_recording_filename, save_to_wav};
+use crate::audio_stream::StreamSegment;
+use crate::speaker_filter::SpeakerFilter;
 
 use super::queue::{QueuedSegment, TranscriptionQueue};
+use super::segment_dump::{SegmentBoundary, SegmentMetadata};
 
-/// Ring buffer capacity: 30 seconds at 48kHz stereo
-/// 48000 * 30 * 2 = 2,880,000 samples
-const RING_BUFFER_CAPACITY: usize = 48000 * 30 * 2;
+/// Ring buffer duration before capture starts and the configured length is
+/// known
+const DEFAULT_RING_BUFFER_SECS: u32 = 30;
 
 /// Overflow threshold: 90% of buffer capacity
 const OVERFLOW_THRESHOLD_PERCENT: usize = 90;
@@ -20,6 +27,13 @@ const OVERFLOW_THRESHOLD_PERCENT: usize = 90;
 /// Maximum segment duration before seeking word break
 const MAX_SEGMENT_DURATION_MS: u64 = 4000;
 
+/// Longest long-form segment before seeking word break
+const LONG_FORM_MAX_SEGMENT_DURATION_MS: u64 = 30_000;
+
+/// Share of the ring buffer a long-form segment may fill before seeking word break,
+/// leaving room for lookback and the grace period below the overflow threshold
+const LONG_FORM_BUFFER_PERCENT: usize = 80;
+
 /// Grace period after duration threshold before forcing segment submission (500ms)
 const WORD_BREAK_GRACE_MS: u64 = 750;
 
@@ -35,6 +49,10 @@ const MIN_AUDIO_RMS_THRESHOLD: f32 = 0.01;
 /// The extraction point will be (gap_start - margin) rather than gap_midpoint
 const WORD_BREAK_PRE_MARGIN_MS: u64 = 30;
 
+/// Shortest speech between interim transcriptions when typing as you speak,
+/// so interims can't swamp the transcription worker
+const MIN_INTERIM_INTERVAL_MS: u32 = 300;
+
 // ============================================================================
 // Segment Ring Buffer
 // ============================================================================
@@ -65,9 +83,14 @@ impl SegmentRingBuffer {
         }
     }
 
-    /// Create a ring buffer with default capacity (30 seconds at 48kHz stereo)
-    pub fn with_default_capacity() -> Self {
-        Self::new(RING_BUFFER_CAPACITY)
+    /// Create a ring buffer holding `secs` seconds of audio at the given format
+    pub fn with_duration(secs: u32, sample_rate: u32, channels: u16) -> Self {
+        Self::new(Self::capacity_for(secs, sample_rate, channels))
+    }
+
+    /// Samples needed to hold `secs` seconds of audio at the given format
+    pub fn capacity_for(secs: u32, sample_rate: u32, channels: u16) -> usize {
+        secs.max(1) as usize * sample_rate.max(1) as usize * channels.max(1) as usize
     }
 
     /// Write samples to the buffer, advancing write position and wrapping
@@ -79,6 +102,11 @@ impl SegmentRingBuffer {
         }
     }
 
+    /// Samples written since the buffer was created or cleared
+    pub fn total_written(&self) -> u64 {
+        self.total_written
+    }
+
     /// Get current write position
     pub fn write_position(&self) -> usize {
         self.write_pos
@@ -208,17 +236,36 @@ pub struct TranscribeState {
     word_break_seek_start_samples: u64,
     /// Number of lookback samples at the start of the current segment
     lookback_sample_count: usize,
+    /// Number of those lookback samples repeated from the previous segment
+    overlap_sample_count: usize,
+    /// Long-form segmentation: long segments bounded by silence
+    long_form: bool,
+    /// Audio repeated at the start of the next segment after a cut mid-speech
+    overlap_ms: u64,
     /// Callback for state events
     callback: Option<Arc<dyn TranscribeStateCallback>>,
     /// PTT mode - disables automatic segmentation
     ptt_mode: bool,
+    /// Longest PTT segment before it is split and submitted (0 = no limit)
+    ptt_max_duration_ms: u64,
+    /// Drops Automatic mode segments from anyone but the enrolled speaker
+    speaker_filter: Option<SpeakerFilter>,
+    /// Speech between interim transcriptions, to type as you speak (0 = off)
+    interim_interval_ms: u64,
+    /// `segment_sample_count` when the last interim was due
+    interim_sample_count: u64,
+    /// Audio stream that segments are reported to instead of being
+    /// delivered, for the latency benchmark
+    stream_id: Option<String>,
+    /// Segments queued for `stream_id`
+    stream_segments: u32,
 }
 
 impl TranscribeState {
     /// Create a new transcribe state
     pub fn new(transcription_queue: Arc<TranscriptionQueue>) -> Self {
         Self {
-            ring_buffer: SegmentRingBuffer::with_default_capacity(),
+            ring_buffer: SegmentRingBuffer::with_duration(DEFAULT_RING_BUFFER_SECS, 48000, 2),
             is_active: false,
             in_speech: false,
             segment_start_idx: 0,
@@ -229,8 +276,17 @@ impl TranscribeState {
             seeking_word_break: false,
             word_break_seek_start_samples: 0,
             lookback_sample_count: 0,
+            overlap_sample_count: 0,
+            long_form: false,
+            overlap_ms: 0,
             callback: None,
             ptt_mode: false,
+            ptt_max_duration_ms: 0,
+            speaker_filter: None,
+            interim_interval_ms: 0,
+            interim_sample_count: 0,
+            stream_id: None,
+            stream_segments: 0,
         }
     }
 
@@ -244,6 +300,12 @@ impl TranscribeState {
         }
     }
 
+    /// Set the longest a PTT segment may run before it is split (0 = no limit).
+    /// Segments are also split when the ring buffer is about to overflow.
+    pub fn set_ptt_max_duration(&mut self, secs: u32) {
+        self.ptt_max_duration_ms = u64::from(secs) * 1000;
+    }
+
     /// Set the callback for state events.
     pub fn set_callback(&mut self, callback: Arc<dyn TranscribeStateCallback>) {
         self.callback = Some(callback);
@@ -254,17 +316,59 @@ impl TranscribeState {
         self.callback = None;
     }
 
-    /// Initialize for capture with specified parameters
+    /// Report segments as `StreamTranscription` events for `stream_id`
+    /// instead of delivering them. Nothing is saved, typed or pasted.
+    pub fn route_to_stream(&mut self, stream_id: String) {
+        self.stream_id = Some(stream_id);
+        self.stream_segments = 0;
+    }
+
+    /// Go back to delivering segments. Returns how many were queued for the
+    /// stream.
+    pub fn end_stream(&mut self) -> u32 {
+        self.stream_id = None;
+        std::mem::take(&mut self.stream_segments)
+    }
+
+    /// Initialize for capture with specified parameters.
+    /// The ring buffer is resized to hold the configured `capture_buffer_secs`
+    /// at this sample rate and channel count.
     pub fn init_for_capture(&mut self, sample_rate: u32, channels: u16) {
         self.sample_rate = sample_rate;
         self.channels = channels;
-        self.ring_buffer.clear();
+        let config = crate::config::current_config();
+        self.long_form = config.segmentation == SegmentationStrategy::LongForm;
+        self.overlap_ms = u64::from(config.segment_overlap_ms.min(MAX_SEGMENT_OVERLAP_MS));
+        self.speaker_filter = SpeakerFilter::from_config(&config);
+        let typing = &config.partial_typing;
+        self.interim_interval_ms = if typing.enabled && config.auto_paste_enabled {
+            u64::from(typing.interval_ms.max(MIN_INTERIM_INTERVAL_MS))
+        } else {
+            0
+        };
+        let secs = config
+            .capture_buffer_secs
+            .clamp(*CAPTURE_BUFFER_SECS.start(), *CAPTURE_BUFFER_SECS.end());
+        let capacity = SegmentRingBuffer::capacity_for(secs, sample_rate, channels);
+        if capacity == self.ring_buffer.capacity() {
+            self.ring_buffer.clear();
+        } else {
+            tracing::debug!(
+                "[TranscribeState] Capture buffer: {}s at {}Hz x{} ({} samples)",
+                secs,
+                sample_rate,
+                channels,
+                capacity
+            );
+            self.ring_buffer = SegmentRingBuffer::new(capacity);
+        }
         self.in_speech = false;
         self.segment_start_idx = 0;
         self.segment_sample_count = 0;
         self.seeking_word_break = false;
         self.word_break_seek_start_samples = 0;
         self.lookback_sample_count = 0;
+        self.overlap_sample_count = 0;
     }
 
     /// Activate transcribe mode
@@ -276,6 +380,7 @@ impl TranscribeState {
         self.seeking_word_break = false;
         self.word_break_seek_start_samples = 0;
         self.lookback_sample_count = 0;
+        self.overlap_sample_count = 0;
     }
 
     /// Deactivate transcribe mode
@@ -293,29 +398,34 @@ impl TranscribeState {
             return None;
         }
 
-        // In PTT mode, skip all automatic segmentation - just write samples
+        // In PTT mode, skip automatic segmentation - just write samples,
+        // splitting only when the key has been held too long
         if self.ptt_mode {
+            let split = if self.in_speech && self.ptt_segment_too_long() {
+                self.split_ptt_segment()
+            } else {
+                None
+            };
             self.ring_buffer.write(samples);
             if self.in_speech {
                 self.segment_sample_count += samples.len() as u64;
+                self.queue_interim();
             }
-            return None;
+            return split;
         }
 
         // Automatic mode: Check for overflow before writing (if in speech)
-        let overflow_segment = if self.in_speech
+        let overflow = if self.in_speech
             && self
                 .ring_buffer
                 .is_approaching_overflow(self.segment_start_idx)
         {
             // Extract current segment before it gets overwritten
             let segment = self.ring_buffer.extract_segment(self.segment_start_idx);
+            let metadata = self.segment_metadata(SegmentBoundary::BufferOverflow);
 
-            // Update segment start to current write position
-            self.segment_start_idx = self.ring_buffer.write_position();
-            self.segment_sample_count = 0;
-            self.seeking_word_break = false;
-            self.lookback_sample_count = 0; // No lookback for continuation segments
+            // Continue from the current write position
+            self.continue_with_overlap();
 
             // Remain in speech state
             tracing::debug!(
@@ -323,7 +433,7 @@ impl TranscribeState {
                 segment.len()
             );
 
-            Some(segment)
+            Some((segment, metadata))
         } else {
             None
         };
@@ -334,10 +444,18 @@ impl TranscribeState {
         // Track segment duration if in speech
         if self.in_speech {
             self.segment_sample_count += samples.len() as u64;
+            self.queue_interim();
 
             // Check if we've exceeded max duration and should start seeking word break
+            // (load shedding raises the limit so fewer segments are transcribed; long-form
+            // segments are already longer than that)
             let duration_ms = self.samples_to_ms(self.segment_sample_count);
-            if !self.seeking_word_break && duration_ms >= MAX_SEGMENT_DURATION_MS {
+            let split_ms = if self.long_form {
+                self.long_form_split_ms()
+            } else {
+                crate::load_shedding::split_ms().unwrap_or(MAX_SEGMENT_DURATION_MS)
+            };
+            if !self.seeking_word_break && duration_ms >= split_ms {
                 self.seeking_word_break = true;
                 self.word_break_seek_start_samples = self.segment_sample_count;
                 tracing::debug!(
@@ -363,11 +481,9 @@ impl TranscribeState {
         }
 
         // If we extracted a segment due to overflow, queue it
-        if let Some(segment) = overflow_segment.clone() {
-            self.queue_segment(segment);
-        }
-
-        overflow_segment
+        let (segment, metadata) = overflow?;
+        self.queue_segment(segment.clone(), metadata);
+        Some(segment)
     }
 
     /// Handle speech-started event: mark segment start including lookback
@@ -388,9 +504,11 @@ impl TranscribeState {
             .index_from_lookback(lookback_stereo_samples);
         // Start duration tracking from zero (lookback samples are pre-speech)
         self.segment_sample_count = 0;
+        self.interim_sample_count = 0;
         self.seeking_word_break = false;
         // Remember lookback count (in stereo samples) for proper word break extraction
         self.lookback_sample_count = lookback_stereo_samples;
+        self.overlap_sample_count = 0;
         tracing::debug!(
             "[TranscribeState] Speech started, segment_start_idx={}, lookback={} mono -> {} stereo",
             self.segment_start_idx,
@@ -401,17 +519,24 @@ impl TranscribeState {
 
     /// Handle speech-ended event: extract segment and queue for transcription
     pub fn on_speech_ended(&mut self) -> Option<Vec<f32>> {
+        self.end_segment(SegmentBoundary::SpeechEnded)
+    }
+
+    /// Extract the segment in progress, queue it, and leave the speech state
+    fn end_segment(&mut self, boundary: SegmentBoundary) -> Option<Vec<f32>> {
         if !self.is_active || !self.in_speech {
             return None;
         }
 
         // Extract the segment
         let segment = self.ring_buffer.extract_segment(self.segment_start_idx);
+        let metadata = self.segment_metadata(boundary);
 
         self.in_speech = false;
         self.segment_sample_count = 0;
         self.seeking_word_break = false;
         self.lookback_sample_count = 0;
+        self.overlap_sample_count = 0;
 
         if segment.is_empty() {
             tracing::debug!("[TranscribeState] Speech ended but segment is empty");
@@ -424,11 +549,45 @@ impl TranscribeState {
         );
 
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        self.queue_segment(segment.clone(), metadata);
+
+        Some(segment)
+    }
+
+    /// Whether the current PTT segment has hit the duration limit or is about
+    /// to be overwritten in the ring buffer.
+    fn ptt_segment_too_long(&self) -> bool {
+        (self.ptt_max_duration_ms > 0
+            && self.samples_to_ms(self.segment_sample_count) >= self.ptt_max_duration_ms)
+            || self
+                .ring_buffer
+                .is_approaching_overflow(self.segment_start_idx)
+    }
+
+    /// Submit the PTT audio recorded so far and continue recording into a
+    /// new segment, so a key held down indefinitely never loses audio.
+    fn split_ptt_segment(&mut self) -> Option<Vec<f32>> {
+        let segment = self.ring_buffer.extract_segment(self.segment_start_idx);
+        let metadata = self.segment_metadata(SegmentBoundary::PttSplit);
+
+        self.continue_with_overlap();
+
+        tracing::info!(
+            "[TranscribeState] PTT held past limit - submitting {} samples and continuing",
+            segment.len()
+        );
 
+        self.queue_segment(segment.clone(), metadata);
         Some(segment)
     }
 
+    /// Segment duration at which a long-form segment starts seeking a word break:
+    /// `LONG_FORM_MAX_SEGMENT_DURATION_MS`, or less if the ring buffer is shorter.
+    fn long_form_split_ms(&self) -> u64 {
+        let usable = self.ring_buffer.capacity() * LONG_FORM_BUFFER_PERCENT / 100;
+        LONG_FORM_MAX_SEGMENT_DURATION_MS.min(self.samples_to_ms(usable as u64))
+    }
+
     /// Convert sample count to milliseconds.
     /// Note: sample count here is raw samples (includes all channels),
     /// so we divide by channels to get frames, then convert to ms.
@@ -520,17 +679,27 @@ impl TranscribeState {
             extraction_point_ms
         );
 
+        let metadata = SegmentMetadata {
+            speech_ms: extraction_duration_ms
+                .saturating_sub(self.samples_to_ms(self.lookback_sample_count as u64)),
+            word_break_offset_ms: Some(offset_ms),
+            word_break_gap_ms: Some(gap_duration_ms),
+            ..self.segment_metadata(SegmentBoundary::WordBreak)
+        };
+
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        self.queue_segment(segment.clone(), metadata);
 
         // Update state for next segment - the new segment starts at the extraction point
         // No lookback for continuation segments (we already have the audio in the buffer)
         self.segment_start_idx = extraction_end_idx;
         self.lookback_sample_count = 0;
+        self.overlap_sample_count = 0;
         // Remaining samples in the segment: total minus what we extracted (excluding lookback)
         self.segment_sample_count = self
             .segment_sample_count
             .saturating_sub(extraction_point_samples);
+        self.interim_sample_count = 0;
         self.seeking_word_break = false;
 
         Some(segment)
@@ -565,27 +734,38 @@ impl TranscribeState {
         );
 
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        let metadata = self.segment_metadata(SegmentBoundary::GracePeriod);
+        self.queue_segment(segment.clone(), metadata);
 
         // Update state for next segment - remain in speech
-        self.segment_start_idx = self.ring_buffer.write_position();
-        self.segment_sample_count = 0;
-        self.lookback_sample_count = 0;
-        self.seeking_word_break = false;
+        self.continue_with_overlap();
 
         Some(segment)
     }
 
+    /// Start the next segment at the current write position after cutting one
+    /// mid-speech, backed up by the configured overlap so a word cut in half is
+    /// transcribed whole. The transcription worker drops the repeated words.
+    fn continue_with_overlap(&mut self) {
+        let overlap = self.ms_to_samples(self.overlap_ms) as usize;
+        self.segment_start_idx = self.ring_buffer.index_from_lookback(overlap);
+        self.segment_sample_count = 0;
+        self.interim_sample_count = 0;
+        self.lookback_sample_count = overlap;
+        self.overlap_sample_count = overlap;
+        self.seeking_word_break = false;
+    }
+
     /// Check if a segment has sufficient audio content for transcription
     /// Returns false if segment is too short or too quiet (likely to produce [BLANK_AUDIO])
-    fn is_segment_valid_for_transcription(&self, samples: &[f32]) -> bool {
+    fn is_segment_valid_for_transcription(&self, samples: &[f32], channels: u16) -> bool {
         if samples.is_empty() {
             return false;
         }
 
         // Check minimum duration
         // samples.len() is raw sample count (stereo), divide by channels to get frames
-        let frames = samples.len() as u64 / self.channels as u64;
+        let frames = samples.len() as u64 / channels.max(1) as u64;
         let duration_ms = frames * 1000 / self.sample_rate as u64;
         if duration_ms < MIN_SEGMENT_DURATION_MS {
             tracing::debug!(
@@ -612,18 +792,207 @@ impl TranscribeState {
         true
     }
 
-    /// Queue a segment for transcription (saves WAV and enqueues)
-    fn queue_segment(&self, samples: Vec<f32>) {
-        if samples.is_empty() {
+    /// Whether an Automatic mode segment passes the speaker filter, judged on
+    /// the primary source (the first channel). PTT segments always pass.
+    fn is_enrolled_speaker(&self, samples: &[f32]) -> bool {
+        match self.speaker_filter {
+            Some(ref filter) if !self.ptt_mode => {
+                let primary: Vec<f32> = samples
+                    .chunks_exact(self.channels.max(1) as usize)
+                    .map(|frame| frame[0])
+                    .collect();
+                filter.accepts(&primary, self.sample_rate)
+            }
+            _ => true,
+        }
+    }
+
+    /// Describe the segment in progress for debug dumps
+    fn segment_metadata(&self, boundary: SegmentBoundary) -> SegmentMetadata {
+        SegmentMetadata {
+            captured_at: chrono::Local::now().to_rfc3339(),
+            boundary,
+            ptt: self.ptt_mode,
+            capture_sample_rate: self.sample_rate,
+            capture_channels: self.channels,
+            lookback_ms: self.samples_to_ms(
+                self.lookback_sample_count
+                    .saturating_sub(self.overlap_sample_count) as u64,
+            ),
+            speech_ms: self.samples_to_ms(self.segment_sample_count),
+            overlap_ms: self.samples_to_ms(self.overlap_sample_count as u64),
+            word_break_offset_ms: None,
+            word_break_gap_ms: None,
+        }
+    }
+
+    /// Queue the speech so far for an interim transcription, to type as you
+    /// speak, every `interim_interval_ms` of speech while the transcription
+    /// queue is idle.
+    fn queue_interim(&mut self) {
+        if self.interim_interval_ms == 0 || self.stream_id.is_some() {
+            return;
+        }
+        let since = self
+            .segment_sample_count
+            .saturating_sub(self.interim_sample_count);
+        if self.samples_to_ms(since) < self.interim_interval_ms {
             return;
         }
+        self.interim_sample_count = self.segment_sample_count;
 
-        // Validate segment has sufficient content
-        if !self.is_segment_valid_for_transcription(&samples) {
+        // Interims never hold up the segments that are pasted
+        if self.transcription_queue.queue_depth() > 0 || crate::meeting::is_active() {
+            return;
+        }
+        let samples = self.ring_buffer.extract_segment(self.segment_start_idx);
+        if !self.is_enrolled_speaker(&samples) {
             return;
         }
+        self.transcription_queue.enqueue(QueuedSegment {
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            wav_path: None,
+            ephemeral: true,
+            metadata: None,
+            retranscription: None,
+            meeting: None,
+            stream: None,
+            interim: Some(crate::partial_typing::interim_segment()),
+        });
+    }
 
-        // Save to WAV file in app data directory
+    /// Queue a segment for transcription (saves WAV and enqueues), ending
+    /// the segment being typed as you speak
+    fn queue_segment(&mut self, samples: Vec<f32>, metadata: SegmentMetadata) {
+        if let Some(stream_id) = self.stream_id.clone() {
+            if self.queue_stream_segment(stream_id, samples) {
+                self.stream_segments += 1;
+            }
+            return;
+        }
+        let queued = self.submit_segment(samples, metadata);
+        crate::partial_typing::segment_ended(queued);
+    }
+
+    /// Validate and queue a segment. Returns whether it went to the
+    /// transcription queue.
+    fn submit_segment(&self, samples: Vec<f32>, metadata: SegmentMetadata) -> bool {
+        if samples.is_empty() {
+            return false;
+        }
+
+        // Privacy mode segments never touch the disk
+        let ephemeral = crate::privacy::is_privacy_mode();
+
+        // During a meeting each source is transcribed on its own
+        if self.channels >= 2 {
+            let frames = samples.len() / self.channels as usize;
+            let duration = Duration::from_millis(frames as u64 * 1000 / self.sample_rate as u64);
+            if let Some(segments) = crate::meeting::segments_ending_now(duration) {
+                for (channel, meeting) in segments.into_iter().enumerate() {
+                    let mono: Vec<f32> = samples
+                        .chunks_exact(self.channels as usize)
+                        .map(|frame| frame[channel])
+                        .collect();
+                    if !self.is_segment_valid_for_transcription(&mono, 1) {
+                        continue;
+                    }
+                    self.enqueue(QueuedSegment {
+                        samples: mono,
+                        sample_rate: self.sample_rate,
+                        channels: 1,
+                        wav_path: None,
+                        ephemeral,
+                        metadata: Some(metadata.clone()),
+                        retranscription: None,
+                        meeting: Some(meeting),
+                        stream: None,
+                        interim: None,
+                    });
+                }
+                return true;
+            }
+        }
+
+        // Validate segment has sufficient content
+        if !self.is_segment_valid_for_transcription(&samples, self.channels) {
+            return false;
+        }
+
+        if !self.is_enrolled_speaker(&samples) {
+            tracing::info!("[TranscribeState] Segment is not the enrolled speaker, skipping");
+            return false;
+        }
+
+        let wav_path = if ephemeral {
+            None
+        } else {
+            self.save_segment_wav(&samples)
+        };
+
+        self.enqueue(QueuedSegment {
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            wav_path,
+            ephemeral,
+            metadata: Some(metadata),
+            retranscription: None,
+            meeting: None,
+            stream: None,
+            interim: None,
+        })
+    }
+
+    /// Validate and queue a segment of the stream being routed to. Returns
+    /// whether it went to the transcription queue.
+    fn queue_stream_segment(&self, stream_id: String, samples: Vec<f32>) -> bool {
+        if !self.is_segment_valid_for_transcription(&samples, self.channels) {
+            return false;
+        }
+
+        // Measured back from the newest audio, where most segments end
+        let start = self
+            .ring_buffer
+            .total_written()
+            .saturating_sub(samples.len() as u64);
+        self.enqueue(QueuedSegment {
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            wav_path: None,
+            ephemeral: true,
+            metadata: None,
+            retranscription: None,
+            meeting: None,
+            stream: Some(StreamSegment {
+                stream_id,
+                offset_ms: self.samples_to_ms(start),
+            }),
+            interim: None,
+        })
+    }
+
+    /// Enqueue a segment for transcription and report the new queue depth.
+    /// Returns false if the queue was full.
+    fn enqueue(&self, queued: QueuedSegment) -> bool {
+        let queued = self.transcription_queue.enqueue(queued);
+        if !queued {
+            tracing::warn!("[TranscribeState] Transcription queue is full, segment dropped");
+        }
+
+        // Emit queue update via callback
+        let depth = self.transcription_queue.queue_depth();
+        if let Some(ref cb) = self.callback {
+            cb.on_queue_update(depth);
+        }
+        queued
+    }
+
+    /// Save a segment to a WAV file in the recordings directory.
+    fn save_segment_wav(&self, samples: &[f32]) -> Option<PathBuf> {
         let filename = generate_recording_filename();
         let recordings_dir = crate::history::TranscriptionHistory::recordings_dir();
 
@@ -635,8 +1004,23 @@ impl TranscribeState {
             );
         }
 
+        // 32-bit samples plus the WAV header
+        let size = samples.len() as u64 * 4 + 44;
+        crate::disk_space::make_room_for_recording(size);
+        if let Err(e) = crate::disk_space::ensure_room(&recordings_dir, size) {
+            tracing::warn!("[TranscribeState] Segment not saved: {}", e);
+            return None;
+        }
+
         let output_path = recordings_dir.join(&filename);
-        let wav_path = match save_to_wav(&samples, self.sample_rate, self.channels, &output_path) {
+        let encrypted = crate::config::current_config().encrypt_history;
+        match save_to_wav(
+            samples,
+            self.sample_rate,
+            self.channels,
+            &output_path,
+            encrypted,
+        ) {
             Ok(()) => {
                 tracing::info!("[TranscribeState] Saved segment to: {:?}", output_path);
                 if let Some(ref cb) = self.callback {
@@ -648,35 +1032,12 @@ impl TranscribeState {
                 tracing::error!("[TranscribeState] Failed to save WAV: {}", e);
                 None
             }
-        };
-
-        // Create queued segment
-        let queued = QueuedSegment {
-            samples,
-            sample_rate: self.sample_rate,
-            channels: self.channels,
-            wav_path,
-        };
-
-        // Enqueue for transcription
-        if !self.transcription_queue.enqueue(queued) {
-            tracing::warn!("[TranscribeState] Transcription queue is full, segment dropped");
-        }
-
-        // Emit queue update via callback
-        let depth = self.transcription_queue.queue_depth();
-        if let Some(ref cb) = self.callback {
-            cb.on_queue_update(depth);
         }
     }
 
     /// Finalize any pending segment (called when transcribe mode is stopped)
     pub fn finalize(&mut self) -> Option<Vec<f32>> {
-        if self.in_speech {
-            // Extract and queue the in-progress segment
-            self.on_speech_ended()
-        } else {
-            None
-        }
+        // Extract and queue the in-progress segment
+        self.end_segment(SegmentBoundary::CaptureStopped)
     }
 }
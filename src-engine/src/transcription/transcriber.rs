@@ -40,6 +40,15 @@ impl Transcriber {
         }
     }
 
+    /// Create a transcriber for a specific model file.
+    pub fn with_model_path(model_path: PathBuf) -> Self {
+        Self {
+            ctx: None,
+            model_path,
+            library_initialized: false,
+        }
+    }
+
     /// Get the path to the model file.
     pub fn get_model_path(&self) -> &PathBuf {
         &self.model_path
@@ -97,6 +106,9 @@ impl Transcriber {
 
         // Get default params with greedy strategy
         let mut params = whisper_ffi::full_default_params(WhisperSamplingStrategy::Greedy)?;
+        if let Some(threads) = crate::power::whisper_threads() {
+            params.n_threads = threads as i32;
+        }
 
         // Apply hallucination mitigation settings
         params.configure_with_hallucination_mitigation();
@@ -153,6 +165,9 @@ impl Transcriber {
 
         // Get default params with greedy strategy
         let mut params = whisper_ffi::full_default_params(WhisperSamplingStrategy::Greedy)?;
+        if let Some(threads) = crate::power::whisper_threads() {
+            params.n_threads = threads as i32;
+        }
 
         // Optimize for short audio if duration is known
         if duration_ms > 0 && duration_ms < 10000 {
@@ -293,10 +308,32 @@ impl Default for Transcriber {
 
 /// Get the default model path.
 fn get_default_model_path() -> PathBuf {
-    let cache_dir = directories::BaseDirs::new()
-        .map(|d| d.cache_dir().to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("."));
-    cache_dir.join("whisper").join("ggml-base.en.bin")
+    model_dir().join("ggml-base.en.bin")
+}
+
+/// Directory whisper models are stored in.
+fn model_dir() -> PathBuf {
+    flowstt_common::paths::models_dir()
+}
+
+/// Resolve a model name to a file in the model directory.
+///
+/// Accepts a file name (`ggml-medium.en.bin`) or the short form whisper.cpp
+/// uses (`medium.en`). Names containing a path separator are used as paths.
+pub fn model_path_for(name: &str) -> PathBuf {
+    let name = name.trim();
+    if name.contains('/') || name.contains('\\') {
+        return PathBuf::from(name);
+    }
+
+    let mut file_name = name.to_string();
+    if !file_name.starts_with("ggml-") {
+        file_name = format!("ggml-{}", file_name);
+    }
+    if !file_name.ends_with(".bin") {
+        file_name.push_str(".bin");
+    }
+    model_dir().join(file_name)
 }
 
 /// Download the Whisper model to the specified path with streaming progress.
@@ -459,4 +496,16 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_model_path_for_names() {
+        assert_eq!(
+            model_path_for("medium.en"),
+            model_dir().join("ggml-medium.en.bin")
+        );
+        assert_eq!(
+            model_path_for("ggml-small.bin"),
+            model_dir().join("ggml-small.bin")
+        );
+    }
 }
@@ -0,0 +1,275 @@
+//! Checking for new FlowSTT releases.
+//!
+//! The release feed (the GitHub releases API by default) is read when a
+//! client asks with `CheckForUpdate`, and in the background every
+//! `updates.interval_hours` when `updates.check_enabled` is on. A release
+//! newer than the running version is announced once with `UpdateAvailable`.
+//! With `updates.stage_downloads` on, its installer for this platform is also
+//! downloaded to the `updates` folder of the data directory and announced
+//! with `UpdateStaged`; running it is left to the user or the installer.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::UpdateInfo;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+use crate::ipc::broadcast_event;
+
+/// Version of the running FlowSTT
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How often the background checker wakes to see whether a check is due
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Wait after startup before the first background check
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Version last announced with `UpdateAvailable`
+static ANNOUNCED: Mutex<Option<String>> = Mutex::new(None);
+
+/// A release in the feed.
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    html_url: Option<String>,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+/// A file attached to a release.
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Check the release feed, announce a newer release and, if configured,
+/// stage its installer.
+pub async fn check() -> Result<UpdateInfo, String> {
+    let settings = Config::load().updates;
+    let release = fetch_release(&settings.feed_url).await?;
+    let latest = release.tag_name.trim_start_matches('v').to_string();
+    let available = is_newer(&latest, CURRENT_VERSION);
+    debug!(
+        "[Updates] Latest release {}, running {}",
+        latest, CURRENT_VERSION
+    );
+
+    let mut staged_path = None;
+    if available {
+        announce(&latest, release.html_url.clone());
+        if settings.stage_downloads {
+            match stage(&latest, &release.assets).await {
+                Ok(path) => staged_path = Some(path.to_string_lossy().into_owned()),
+                Err(e) => warn!("[Updates] Failed to stage {}: {}", latest, e),
+            }
+        }
+    }
+
+    Ok(UpdateInfo {
+        current_version: CURRENT_VERSION.to_string(),
+        latest_version: latest,
+        available,
+        notes_url: release.html_url,
+        staged_path,
+    })
+}
+
+/// Check for updates in the background until shutdown.
+pub async fn run_update_checker() {
+    tokio::time::sleep(STARTUP_DELAY).await;
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_check: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        let settings = Config::load().updates;
+        let every = Duration::from_secs(u64::from(settings.interval_hours.max(1)) * 3600);
+        if !settings.check_enabled || last_check.is_some_and(|at| at.elapsed() < every) {
+            continue;
+        }
+        last_check = Some(Instant::now());
+        if let Err(e) = check().await {
+            warn!("[Updates] Update check failed: {}", e);
+        }
+    }
+
+    debug!("[Updates] Checker stopped");
+}
+
+async fn fetch_release(feed_url: &str) -> Result<Release, String> {
+    let response = reqwest::Client::new()
+        .get(feed_url)
+        // The GitHub API turns away requests without a user agent
+        .header("User-Agent", format!("flowstt/{}", CURRENT_VERSION))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to read the release feed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to read the release feed: HTTP {}",
+            response.status()
+        ));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read the release feed: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Unexpected release feed: {}", e))
+}
+
+/// Broadcast `UpdateAvailable` unless `version` was announced already.
+fn announce(version: &str, notes_url: Option<String>) {
+    let mut announced = ANNOUNCED.lock().unwrap();
+    if announced.as_deref() == Some(version) {
+        return;
+    }
+    *announced = Some(version.to_string());
+    info!("[Updates] FlowSTT {} is available", version);
+    broadcast_event(Response::Event {
+        event: EventType::UpdateAvailable {
+            version: version.to_string(),
+            notes_url,
+        },
+    });
+}
+
+/// Download the installer for this platform, unless it already was.
+async fn stage(version: &str, assets: &[Asset]) -> Result<PathBuf, String> {
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let asset = installer_asset(assets).ok_or("No installer for this platform")?;
+    let dir = flowstt_common::paths::data_dir().join("updates");
+    let path = dir.join(&asset.name);
+    if path.exists() {
+        return Ok(path);
+    }
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    remove_stale(&dir, &asset.name).await;
+
+    info!("[Updates] Downloading {} {}", version, asset.name);
+    let response = reqwest::Client::new()
+        .get(&asset.browser_download_url)
+        .header("User-Agent", format!("flowstt/{}", CURRENT_VERSION))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download the installer: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download the installer: HTTP {}",
+            response.status()
+        ));
+    }
+
+    // Write to a temporary file first, then rename on success
+    let part = dir.join(format!("{}.part", asset.name));
+    let mut file = tokio::fs::File::create(&part)
+        .await
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to read response: {}", e))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    drop(file);
+    tokio::fs::rename(&part, &path)
+        .await
+        .map_err(|e| format!("Failed to move the installer into place: {}", e))?;
+
+    broadcast_event(Response::Event {
+        event: EventType::UpdateStaged {
+            version: version.to_string(),
+            path: path.to_string_lossy().into_owned(),
+        },
+    });
+    Ok(path)
+}
+
+/// Remove installers of earlier releases from the updates folder.
+async fn remove_stale(dir: &Path, keep: &str) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_name() != keep {
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+/// The release asset that installs FlowSTT on this platform and architecture.
+fn installer_asset(assets: &[Asset]) -> Option<&Asset> {
+    let extensions: &[&str] = if cfg!(target_os = "windows") {
+        &["-setup.exe", ".msi"]
+    } else if cfg!(target_os = "macos") {
+        &[".dmg"]
+    } else {
+        &[".AppImage", ".deb"]
+    };
+    let arch_names: &[&str] = if cfg!(target_arch = "aarch64") {
+        &["aarch64", "arm64"]
+    } else {
+        &["x64", "x86_64", "amd64"]
+    };
+
+    // Installers are preferred in the order of `extensions`
+    extensions.iter().find_map(|extension| {
+        assets.iter().find(|asset| {
+            asset.name.ends_with(extension)
+                && arch_names.iter().any(|arch| asset.name.contains(arch))
+        })
+    })
+}
+
+/// Whether version `a` is newer than `b`, comparing dotted numbers; a
+/// pre-release suffix (`-beta.1`) sorts before the release it leads to.
+fn is_newer(a: &str, b: &str) -> bool {
+    parse_version(a) > parse_version(b)
+}
+
+fn parse_version(version: &str) -> (Vec<u64>, bool) {
+    let (numbers, pre_release) = match version.split_once('-') {
+        Some((numbers, _)) => (numbers, true),
+        None => (version, false),
+    };
+    let numbers = numbers
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    (numbers, !pre_release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.1.19", "0.1.18"));
+        assert!(is_newer("0.2.0", "0.1.18"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(!is_newer("0.1.18", "0.1.18"));
+        assert!(!is_newer("0.1.17", "0.1.18"));
+        assert!(!is_newer("0.2.0-beta.1", "0.2.0"));
+        assert!(is_newer("0.2.0", "0.2.0-beta.1"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Watchdog for the engine's long-running threads.
+//!
+//! The audio loop, hotkey listener, push-to-talk controller and transcription
+//! worker each hold a [`Heartbeat`] while they run and beat it on every pass
+//! of their loop. A thread that hasn't beaten for longer than its component's
+//! limit is taken to be hung: the watchdog replaces it with a fresh thread and
+//! broadcasts `ComponentRestarted`. The hung thread can't be killed, so its
+//! heartbeat reports that it was replaced and it exits if it ever wakes,
+//! leaving the shared state to its replacement.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::EngineComponent;
+use tracing::{debug, error, info};
+
+use crate::ipc::broadcast_event;
+
+/// How often heartbeats are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time without a heartbeat before a thread is restarted
+const STALL_LIMIT: Duration = Duration::from_secs(30);
+
+/// Time without a heartbeat before the transcription worker is restarted; a
+/// long segment on a slow machine can take minutes
+const WORKER_STALL_LIMIT: Duration = Duration::from_secs(600);
+
+/// Heartbeat generation of each component, bumped when its thread is replaced
+static GENERATIONS: [AtomicU64; 4] = [const { AtomicU64::new(0) }; 4];
+
+/// Milliseconds since [`epoch`] of each component's last heartbeat; 0 while
+/// the component isn't running
+static LAST_BEATS: [AtomicU64; 4] = [const { AtomicU64::new(0) }; 4];
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// Milliseconds since the epoch, never 0.
+fn now_ms() -> u64 {
+    epoch().elapsed().as_millis() as u64 + 1
+}
+
+fn index(component: EngineComponent) -> usize {
+    match component {
+        EngineComponent::AudioLoop => 0,
+        EngineComponent::Hotkeys => 1,
+        EngineComponent::PttController => 2,
+        EngineComponent::TranscriptionWorker => 3,
+    }
+}
+
+fn stall_limit(component: EngineComponent) -> Duration {
+    match component {
+        EngineComponent::TranscriptionWorker => WORKER_STALL_LIMIT,
+        _ => STALL_LIMIT,
+    }
+}
+
+/// Proof of life from a supervised thread.
+pub struct Heartbeat {
+    component: EngineComponent,
+    generation: u64,
+}
+
+/// Start watching the calling thread as `component`, replacing any thread
+/// watched as it before.
+pub fn register(component: EngineComponent) -> Heartbeat {
+    let i = index(component);
+    let generation = GENERATIONS[i].fetch_add(1, Ordering::SeqCst) + 1;
+    LAST_BEATS[i].store(now_ms(), Ordering::SeqCst);
+    Heartbeat {
+        component,
+        generation,
+    }
+}
+
+impl Heartbeat {
+    /// Record that the thread is alive. Returns false once the thread has
+    /// been replaced, when it should exit without touching shared state.
+    pub fn beat(&self) -> bool {
+        if self.is_replaced() {
+            return false;
+        }
+        LAST_BEATS[index(self.component)].store(now_ms(), Ordering::SeqCst);
+        true
+    }
+
+    /// Whether another thread has taken over the component.
+    pub fn is_replaced(&self) -> bool {
+        GENERATIONS[index(self.component)].load(Ordering::SeqCst) != self.generation
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        // The thread stopped on its own; stop watching the component
+        if !self.is_replaced() {
+            LAST_BEATS[index(self.component)].store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Count every running component as having just beaten, after time passed
+/// that its thread couldn't account for (the system was asleep).
+pub fn reset_beats() {
+    let now = now_ms();
+    for beat in &LAST_BEATS {
+        // Leave components that aren't running unwatched
+        let _ = beat.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            (last != 0).then_some(now)
+        });
+    }
+}
+
+/// Check heartbeats and restart hung threads until shutdown.
+pub async fn run_watchdog() {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if crate::is_shutdown_requested() {
+            break;
+        }
+
+        for component in EngineComponent::ALL {
+            let i = index(component);
+            let last = LAST_BEATS[i].load(Ordering::SeqCst);
+            if last == 0 {
+                continue;
+            }
+            let stalled = Duration::from_millis(now_ms().saturating_sub(last));
+            if stalled < stall_limit(component) {
+                continue;
+            }
+
+            error!(
+                "[Watchdog] The {} has not responded for {} s, restarting it",
+                component.label(),
+                stalled.as_secs()
+            );
+            // Retire the hung thread before its replacement starts
+            GENERATIONS[i].fetch_add(1, Ordering::SeqCst);
+            LAST_BEATS[i].store(0, Ordering::SeqCst);
+            // Restarting may wait on the hung thread for a while
+            let restarted = tokio::task::spawn_blocking(move || restart(component))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+            match restarted {
+                Ok(()) => info!("[Watchdog] Restarted the {}", component.label()),
+                Err(e) => error!(
+                    "[Watchdog] Failed to restart the {}: {}",
+                    component.label(),
+                    e
+                ),
+            }
+            broadcast_event(Response::Event {
+                event: EventType::ComponentRestarted {
+                    component,
+                    stalled_secs: stalled.as_secs(),
+                },
+            });
+        }
+    }
+
+    debug!("[Watchdog] Stopped");
+}
+
+/// Stop a component and start it again.
+fn restart(component: EngineComponent) -> Result<(), String> {
+    use crate::ipc::handlers::{get_transcribe_state, get_transcription_queue};
+
+    match component {
+        EngineComponent::AudioLoop => {
+            crate::audio_loop::stop_audio_loop();
+            crate::audio_loop::start_audio_loop(get_transcription_queue(), get_transcribe_state())
+        }
+        EngineComponent::Hotkeys => crate::hotkey::restart_hotkey(),
+        EngineComponent::PttController => {
+            crate::ptt_controller::stop_ptt_controller();
+            crate::ptt_controller::start_ptt_controller()
+        }
+        EngineComponent::TranscriptionWorker => {
+            let queue = get_transcription_queue();
+            // Doesn't join the hung thread; it exits through its heartbeat
+            // if it ever wakes
+            queue.stop_worker();
+            queue.start_worker(crate::config::current_config().effective_transcription_engine());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test watches a component of its own, since the state is global
+
+    fn last_beat(component: EngineComponent) -> u64 {
+        LAST_BEATS[index(component)].load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn registering_again_replaces_the_previous_thread() {
+        let component = EngineComponent::Hotkeys;
+        let first = register(component);
+        assert!(first.beat());
+
+        let second = register(component);
+        assert!(first.is_replaced());
+        assert!(!first.beat());
+        assert!(second.beat());
+
+        // The replaced thread exiting leaves its replacement watched
+        drop(first);
+        assert_ne!(last_beat(component), 0);
+        drop(second);
+        assert_eq!(last_beat(component), 0);
+    }
+
+    #[test]
+    fn stopping_a_thread_stops_watching_it() {
+        let component = EngineComponent::PttController;
+        let heartbeat = register(component);
+        assert_ne!(last_beat(component), 0);
+
+        drop(heartbeat);
+        assert_eq!(last_beat(component), 0);
+    }
+
+    #[test]
+    fn reset_only_touches_running_components() {
+        let running = EngineComponent::TranscriptionWorker;
+        let stopped = EngineComponent::AudioLoop;
+        let _heartbeat = register(running);
+        LAST_BEATS[index(running)].store(1, Ordering::SeqCst);
+        assert_eq!(last_beat(stopped), 0);
+        std::thread::sleep(Duration::from_millis(5));
+
+        reset_beats();
+
+        assert!(last_beat(running) > 1);
+        assert_eq!(last_beat(stopped), 0);
+    }
+}
@@ -0,0 +1,56 @@
+//! Building, shutting down and rebuilding an embedded engine.
+//!
+//! Runs in its own process so the portable folder below is in place before
+//! any path is resolved.
+
+use std::time::Duration;
+
+use flowstt_engine::{EngineBuilder, EngineError, EngineEvent, VadOptions};
+use futures::StreamExt;
+
+/// Keep config, history and the IPC endpoint out of the user's profile, and
+/// transcribe without a model.
+fn isolate() {
+    let dir = std::env::temp_dir().join(format!("flowstt-engine-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("FLOWSTT_PORTABLE", &dir);
+    std::env::set_var("FLOWSTT_TRANSCRIPTION_ENGINE", "mock");
+}
+
+fn builder() -> EngineBuilder {
+    EngineBuilder::new().ipc_server(false).auto_capture(false)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn engine_can_be_rebuilt_after_shutdown() {
+    isolate();
+
+    let vad = VadOptions {
+        voiced_threshold_db: -30.0,
+        ..VadOptions::default()
+    };
+    let engine = builder().vad(vad).build().await.unwrap();
+    assert_eq!(engine.vad(), Some(vad));
+    assert!(!engine.has_ipc_server());
+
+    // Only one engine runs at a time
+    assert!(matches!(
+        builder().build().await,
+        Err(EngineError::AlreadyRunning)
+    ));
+
+    let events = engine.events();
+    engine.shutdown().await;
+
+    // The stream delivers what shutting down raised, then ends
+    let events: Vec<EngineEvent> = tokio::time::timeout(Duration::from_secs(10), events.collect())
+        .await
+        .expect("event stream did not end on shutdown");
+    assert!(events.iter().any(|e| matches!(e, EngineEvent::Shutdown)));
+
+    // The options belonged to the engine that shut down
+    let engine = builder().build().await.expect("rebuild after shutdown");
+    assert_eq!(engine.vad(), None);
+    assert!(engine.model().is_none());
+    engine.shutdown().await;
+}
@@ -4,12 +4,19 @@
 //! Tauri commands call engine functions directly without IPC serialization.
 //! The IPC socket server is hosted by this process for CLI client access.
 
+mod notifications;
 mod tray;
 
-use flowstt_common::config::{Config, LogLevel, ThemeMode};
+use flowstt_common::config::{
+    Config, LogLevel, NotificationSettings, SoundCueSettings, ThemeMode,
+    VisualizationSettings,
+};
 use flowstt_common::ipc::{EventType, Request, Response};
+use flowstt_common::settings::key;
 use flowstt_common::{
-    runtime_mode, AudioDevice, HotkeyCombination, RecordingMode, RuntimeMode, TranscriptionMode,
+    runtime_mode, AudioDevice, AudioSelfTestReport, Capabilities, HistoryGrouping, HistorySummary,
+    HotkeyCombination, LetterLayout, ModeToggleSource, RecordingMode, RuntimeMode,
+    TranscriptionMode,
 };
 use std::env;
 use std::sync::Arc;
@@ -216,8 +223,8 @@ fn init_logging(initial_level: &LogLevel) -> (LogState, tokio::sync::mpsc::Recei
 
 /// Application state shared between Tauri commands.
 struct AppState {
-    /// Handle to the IPC server task
-    ipc_server_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// The in-process engine, once started
+    engine: Mutex<Option<flowstt_engine::Engine>>,
 }
 
 // ─── Event callback for Tauri frontend ───────────────────────────────────────
@@ -235,12 +242,19 @@ impl flowstt_engine::ipc::EventCallback for TauriEventCallback {
 
 /// Forward an engine event to the Tauri frontend.
 fn forward_event_to_tauri(app_handle: &AppHandle, event: &EventType) {
+    notifications::handle_event(app_handle, event);
+    tray::indicator::handle_event(app_handle, event);
+
     match event {
         EventType::VisualizationData(data) => {
             let _ = app_handle.emit("visualization-data", data);
         }
+        EventType::SpeechMetrics(metrics) => {
+            let _ = app_handle.emit("speech-metrics", metrics);
+        }
         EventType::TranscriptionComplete(result) => {
             let _ = app_handle.emit("transcription-complete", result);
+            tray::refresh_recent_menu(app_handle);
 
             // On Windows, WebView2 can enter a frozen rendering state when
             // Alt (the default PTT key) is released while the window is focused.
@@ -281,7 +295,6 @@ fn forward_event_to_tauri(app_handle: &AppHandle, event: &EventType) {
                     error: error.clone(),
                 },
             );
-            tray::update_tray_icon(app_handle, *capturing);
         }
         EventType::ModelDownloadProgress { percent } => {
             let _ = app_handle.emit("model-download-progress", percent);
@@ -314,12 +327,340 @@ fn forward_event_to_tauri(app_handle: &AppHandle, event: &EventType) {
         }
         EventType::TranscriptionModeChanged { mode } => {
             let _ = app_handle.emit("transcription-mode-changed", mode);
+            tray::update_mode_menu(app_handle, *mode);
         }
         EventType::AutoModeToggled { mode } => {
             let _ = app_handle.emit("auto-mode-toggled", mode);
+            tray::update_mode_menu(app_handle, *mode);
+        }
+        EventType::AutoModeToggledBy { mode, source } => {
+            #[derive(serde::Serialize, Clone)]
+            struct ModeToggle {
+                mode: TranscriptionMode,
+                source: ModeToggleSource,
+            }
+            let _ = app_handle.emit(
+                "auto-mode-toggled-by",
+                ModeToggle {
+                    mode: *mode,
+                    source: *source,
+                },
+            );
+        }
+        EventType::PrivacyModeChanged { enabled } => {
+            let _ = app_handle.emit("privacy-mode-changed", enabled);
+            tray::update_privacy_menu(app_handle, *enabled);
+        }
+        EventType::UpdateAvailable { version, notes_url } => {
+            #[derive(serde::Serialize, Clone)]
+            struct UpdateAvailable {
+                version: String,
+                notes_url: Option<String>,
+            }
+            let _ = app_handle.emit(
+                "update-available",
+                UpdateAvailable {
+                    version: version.clone(),
+                    notes_url: notes_url.clone(),
+                },
+            );
+        }
+        EventType::UpdateStaged { version, path } => {
+            #[derive(serde::Serialize, Clone)]
+            struct UpdateStaged {
+                version: String,
+                path: String,
+            }
+            let _ = app_handle.emit(
+                "update-staged",
+                UpdateStaged {
+                    version: version.clone(),
+                    path: path.clone(),
+                },
+            );
+        }
+        EventType::ThemeChanged { theme } => {
+            let _ = app_handle.emit("theme-changed", &theme.mode);
+        }
+        EventType::AutostartChanged { enabled } => {
+            let _ = app_handle.emit("autostart-changed", enabled);
+            tray::update_autostart_menu(app_handle, *enabled);
+        }
+        EventType::DictationChanged { active } => {
+            let _ = app_handle.emit("dictation-changed", active);
+        }
+        EventType::SpellingModeChanged { active } => {
+            let _ = app_handle.emit("spelling-mode-changed", active);
+        }
+        EventType::OutputPausedChanged { paused } => {
+            let _ = app_handle.emit("output-paused-changed", paused);
+        }
+        EventType::ConfigChanged { keys } => {
+            let _ = app_handle.emit("config-changed", keys);
+        }
+        EventType::AutoSuspended { suspended } => {
+            let _ = app_handle.emit("auto-suspended", suspended);
+        }
+        EventType::DeviceBusy {
+            device_id,
+            holder_hint,
+            fallback_id,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct DeviceBusy {
+                device_id: String,
+                holder_hint: Option<String>,
+                fallback_id: Option<String>,
+            }
+            let _ = app_handle.emit(
+                "device-busy",
+                DeviceBusy {
+                    device_id: device_id.clone(),
+                    holder_hint: holder_hint.clone(),
+                    fallback_id: fallback_id.clone(),
+                },
+            );
+        }
+        EventType::CaptureStalled { stalled_secs } => {
+            let _ = app_handle.emit("capture-stalled", stalled_secs);
+        }
+        EventType::DeviceProfileChanged {
+            device_id,
+            previous_sample_rate,
+            sample_rate,
+            narrowband,
+            switched_to,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct DeviceProfileChanged {
+                device_id: Option<String>,
+                previous_sample_rate: Option<u32>,
+                sample_rate: u32,
+                narrowband: bool,
+                switched_to: Option<String>,
+            }
+            let _ = app_handle.emit(
+                "device-profile-changed",
+                DeviceProfileChanged {
+                    device_id: device_id.clone(),
+                    previous_sample_rate: *previous_sample_rate,
+                    sample_rate: *sample_rate,
+                    narrowband: *narrowband,
+                    switched_to: switched_to.clone(),
+                },
+            );
+        }
+        EventType::ClippingDetected { clipped_samples } => {
+            let _ = app_handle.emit("clipping-detected", clipped_samples);
+        }
+        EventType::PausedByProcess { process } => {
+            let _ = app_handle.emit("paused-by-process", process);
         }
         EventType::HistoryEntryDeleted { id } => {
             let _ = app_handle.emit("history-entry-deleted", id);
+            tray::refresh_recent_menu(app_handle);
+        }
+        EventType::HistoryEntryUpdated { entry } => {
+            let _ = app_handle.emit("history-entry-updated", entry);
+            tray::refresh_recent_menu(app_handle);
+        }
+        EventType::RetranscriptionFailed { id, error } => {
+            #[derive(serde::Serialize, Clone)]
+            struct RetranscriptionFailed {
+                id: String,
+                error: String,
+            }
+            let _ = app_handle.emit(
+                "retranscription-failed",
+                RetranscriptionFailed {
+                    id: id.clone(),
+                    error: error.clone(),
+                },
+            );
+        }
+        EventType::MeetingStarted { meeting } => {
+            let _ = app_handle.emit("meeting-started", meeting);
+        }
+        EventType::MeetingLine { meeting_id, line } => {
+            #[derive(serde::Serialize, Clone)]
+            struct MeetingLine {
+                meeting_id: String,
+                line: flowstt_common::MeetingLine,
+            }
+            let _ = app_handle.emit(
+                "meeting-line",
+                MeetingLine {
+                    meeting_id: meeting_id.clone(),
+                    line: line.clone(),
+                },
+            );
+        }
+        EventType::MeetingEnded { meeting } => {
+            let _ = app_handle.emit("meeting-ended", meeting);
+        }
+        EventType::StreamTranscription {
+            stream_id,
+            offset_ms,
+            text,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct StreamTranscription {
+                stream_id: String,
+                offset_ms: u64,
+                text: String,
+            }
+            let _ = app_handle.emit(
+                "stream-transcription",
+                StreamTranscription {
+                    stream_id: stream_id.clone(),
+                    offset_ms: *offset_ms,
+                    text: text.clone(),
+                },
+            );
+        }
+        EventType::HistoryAudioFinished { id } => {
+            let _ = app_handle.emit("history-audio-finished", id);
+        }
+        EventType::LoadSheddingChanged { active, reason } => {
+            #[derive(serde::Serialize, Clone)]
+            struct LoadSheddingChanged {
+                active: bool,
+                reason: String,
+            }
+            let _ = app_handle.emit(
+                "load-shedding-changed",
+                LoadSheddingChanged {
+                    active: *active,
+                    reason: reason.clone(),
+                },
+            );
+        }
+        EventType::PowerStateChanged { on_battery } => {
+            let _ = app_handle.emit("power-state-changed", on_battery);
+        }
+        EventType::LowDiskSpace {
+            path,
+            available_mb,
+            min_free_mb,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct LowDiskSpace {
+                path: String,
+                available_mb: u64,
+                min_free_mb: u64,
+            }
+            let _ = app_handle.emit(
+                "low-disk-space",
+                LowDiskSpace {
+                    path: path.clone(),
+                    available_mb: *available_mb,
+                    min_free_mb: *min_free_mb,
+                },
+            );
+        }
+        EventType::PermissionChanged { kind, state } => {
+            #[derive(serde::Serialize, Clone)]
+            struct PermissionChanged {
+                kind: flowstt_common::PermissionKind,
+                state: flowstt_common::PermissionState,
+            }
+            let _ = app_handle.emit(
+                "permission-changed",
+                PermissionChanged {
+                    kind: *kind,
+                    state: *state,
+                },
+            );
+        }
+        EventType::PasteFailed { reason } => {
+            let _ = app_handle.emit("paste-failed", reason);
+        }
+        EventType::ComponentRestarted {
+            component,
+            stalled_secs,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct ComponentRestarted {
+                component: flowstt_common::EngineComponent,
+                stalled_secs: u64,
+            }
+            let _ = app_handle.emit(
+                "component-restarted",
+                ComponentRestarted {
+                    component: *component,
+                    stalled_secs: *stalled_secs,
+                },
+            );
+        }
+        EventType::TestFileStarted { index, total, file } => {
+            #[derive(serde::Serialize, Clone)]
+            struct TestFileStarted {
+                index: usize,
+                total: usize,
+                file: String,
+            }
+            let _ = app_handle.emit(
+                "test-file-started",
+                TestFileStarted {
+                    index: *index,
+                    total: *total,
+                    file: file.clone(),
+                },
+            );
+        }
+        EventType::TestFileComplete {
+            index,
+            total,
+            result,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct TestFileComplete {
+                index: usize,
+                total: usize,
+                result: flowstt_common::TestFileResult,
+            }
+            let _ = app_handle.emit(
+                "test-file-complete",
+                TestFileComplete {
+                    index: *index,
+                    total: *total,
+                    result: result.clone(),
+                },
+            );
+        }
+        EventType::TestRunComplete {
+            summary,
+            report_path,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct TestRunComplete {
+                summary: flowstt_common::TestRunSummary,
+                report_path: Option<String>,
+            }
+            let _ = app_handle.emit(
+                "test-run-complete",
+                TestRunComplete {
+                    summary: summary.clone(),
+                    report_path: report_path.clone(),
+                },
+            );
+        }
+        EventType::ShutdownProgress {
+            phase,
+            pending_segments,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct ShutdownProgress {
+                phase: flowstt_common::ShutdownPhase,
+                pending_segments: usize,
+            }
+            let _ = app_handle.emit(
+                "shutdown-progress",
+                ShutdownProgress {
+                    phase: *phase,
+                    pending_segments: *pending_segments,
+                },
+            );
         }
         EventType::Shutdown => {
             let _ = app_handle.emit("service-shutdown", ());
@@ -344,12 +685,17 @@ async fn list_all_sources() -> Result<Vec<AudioDevice>, String> {
 
 /// Set audio sources
 #[tauri::command]
-async fn set_sources(source1_id: Option<String>, source2_id: Option<String>) -> Result<(), String> {
+async fn set_sources(
+    source1_id: Option<String>,
+    source2_id: Option<String>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
     let response = flowstt_engine::ipc::handlers::handle_request(Request::SetSources {
         source1_id,
         source2_id,
     })
     .await;
+    tray::refresh_microphone_menu(&app_handle);
     match response {
         Response::Ok => Ok(()),
         Response::Error { message } => Err(message),
@@ -436,6 +782,17 @@ async fn get_cuda_status() -> Result<LocalCudaStatus, String> {
     }
 }
 
+/// Get which platform features work in the current session
+#[tauri::command]
+async fn get_capabilities() -> Result<Capabilities, String> {
+    let response = flowstt_engine::ipc::handlers::handle_request(Request::GetCapabilities).await;
+    match response {
+        Response::Capabilities(capabilities) => Ok(capabilities),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
 /// Status struct for frontend
 #[derive(serde::Serialize)]
 struct LocalStatus {
@@ -477,6 +834,8 @@ struct LocalPttStatus {
     is_active: bool,
     available: bool,
     error: Option<String>,
+    keyboard_layout: Option<String>,
+    letter_layout: LetterLayout,
 }
 
 /// Set the transcription mode
@@ -523,6 +882,8 @@ async fn get_ptt_status() -> Result<LocalPttStatus, String> {
             is_active: status.is_active,
             available: status.available,
             error: status.error,
+            keyboard_layout: status.keyboard_layout,
+            letter_layout: status.letter_layout,
         }),
         Response::Error { message } => Err(message),
         _ => Err("Unexpected response".into()),
@@ -568,6 +929,12 @@ struct LocalHistoryEntry {
     text: String,
     timestamp: String,
     wav_path: Option<String>,
+    audio_bytes: Option<u64>,
+    duration_ms: Option<u64>,
+    mode: Option<TranscriptionMode>,
+    model_used: Option<String>,
+    original_text: Option<String>,
+    edited: bool,
 }
 
 /// Get transcription history
@@ -582,6 +949,12 @@ async fn get_history() -> Result<Vec<LocalHistoryEntry>, String> {
                 text: e.text,
                 timestamp: e.timestamp,
                 wav_path: e.wav_path,
+                audio_bytes: e.audio_bytes,
+                duration_ms: e.duration_ms,
+                mode: e.mode,
+                model_used: e.model_used,
+                original_text: e.original_text,
+                edited: e.edited,
             })
             .collect()),
         Response::Error { message } => Err(message),
@@ -589,6 +962,31 @@ async fn get_history() -> Result<Vec<LocalHistoryEntry>, String> {
     }
 }
 
+/// Get history totals by day or hour for the usage dashboard
+#[tauri::command]
+async fn get_history_summary(group_by: HistoryGrouping) -> Result<HistorySummary, String> {
+    let response =
+        flowstt_engine::ipc::handlers::handle_request(Request::GetHistorySummary { group_by })
+            .await;
+    match response {
+        Response::HistorySummary(summary) => Ok(summary),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Read a history recording as raw WAV bytes, decrypting it if history
+/// encryption is in use
+#[tauri::command]
+async fn read_history_audio(path: String) -> Result<tauri::ipc::Response, String> {
+    let bytes = tauri::async_runtime::spawn_blocking(move || {
+        flowstt_engine::history::TranscriptionHistory::read_recording(std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| format!("Failed to read recording: {}", e))??;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
 /// Delete a history entry
 #[tauri::command]
 async fn delete_history_entry(id: String) -> Result<(), String> {
@@ -601,6 +999,18 @@ async fn delete_history_entry(id: String) -> Result<(), String> {
     }
 }
 
+/// Replace a history entry's text with a manual correction
+#[tauri::command]
+async fn edit_history_entry(id: String, text: String) -> Result<(), String> {
+    let response =
+        flowstt_engine::ipc::handlers::handle_request(Request::EditHistoryEntry { id, text }).await;
+    match response {
+        Response::Ok => Ok(()),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
 /// Get the current theme mode from the config file.
 #[tauri::command]
 fn get_theme_mode() -> Result<ThemeMode, String> {
@@ -608,18 +1018,71 @@ fn get_theme_mode() -> Result<ThemeMode, String> {
     Ok(config.theme_mode)
 }
 
-/// Set the theme mode and persist to the config file.
+/// Set the theme mode. The engine saves it and sends `ThemeChanged`, which
+/// updates every window.
 #[tauri::command]
-fn set_theme_mode(mode: ThemeMode, app_handle: AppHandle) -> Result<(), String> {
-    let mut config = Config::load();
-    config.theme_mode = mode.clone();
-    config
-        .save()
-        .map_err(|e| format!("Failed to save config: {}", e))?;
-    app_handle
-        .emit("theme-changed", &mode)
-        .map_err(|e| format!("Failed to emit theme event: {}", e))?;
-    Ok(())
+async fn set_theme_mode(mode: ThemeMode) -> Result<(), String> {
+    let mut theme = Config::load().theme();
+    theme.mode = mode;
+    let response = flowstt_engine::ipc::handlers::handle_request(Request::SetTheme { theme }).await;
+    match response {
+        Response::Ok => Ok(()),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Get the visualization resolution, update rate and gradient.
+#[tauri::command]
+async fn get_visualization_config() -> Result<VisualizationSettings, String> {
+    let response =
+        flowstt_engine::ipc::handlers::handle_request(Request::GetVisualizationConfig).await;
+    match response {
+        Response::VisualizationConfig(settings) => Ok(settings),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Change the visualization resolution, update rate and gradient.
+#[tauri::command]
+async fn set_visualization_config(settings: VisualizationSettings) -> Result<(), String> {
+    let response =
+        flowstt_engine::ipc::handlers::handle_request(Request::SetVisualizationConfig { settings })
+            .await;
+    match response {
+        Response::Ok => Ok(()),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Get the notification category settings from the config file.
+#[tauri::command]
+fn get_notification_settings() -> NotificationSettings {
+    flowstt_common::settings::current().notifications.clone()
+}
+
+/// Set the notification category settings and persist to the config file.
+#[tauri::command]
+fn set_notification_settings(settings: NotificationSettings) -> Result<(), String> {
+    flowstt_common::settings::set(key::NOTIFICATIONS, settings)
+        .map(drop)
+        .map_err(|e| format!("Failed to save config: {}", e))
+}
+
+/// Get the sound cue settings from the config file.
+#[tauri::command]
+fn get_sound_cue_settings() -> SoundCueSettings {
+    Config::load().sound_cues
+}
+
+/// Set the sound cue settings and persist to the config file.
+#[tauri::command]
+fn set_sound_cue_settings(settings: SoundCueSettings) -> Result<(), String> {
+    flowstt_common::settings::set(key::SOUND_CUES, settings)
+        .map(drop)
+        .map_err(|e| format!("Failed to save config: {}", e))
 }
 
 /// Check if first-time setup is needed.
@@ -661,14 +1124,14 @@ async fn complete_setup(
     source2_id: Option<String>,
     app_handle: AppHandle,
 ) -> Result<(), String> {
-    let config = Config {
-        transcription_mode,
-        ptt_hotkeys: hotkeys,
-        ..Config::default_with_hotkeys()
-    };
-    config
-        .save()
-        .map_err(|e| format!("Failed to save config: {}", e))?;
+    flowstt_common::settings::update(|config| {
+        *config = Config {
+            transcription_mode,
+            ptt_hotkeys: hotkeys,
+            ..Config::default_with_hotkeys()
+        };
+    })
+    .map_err(|e| format!("Failed to save config: {}", e))?;
 
     // Configure engine with chosen sources (direct call, no IPC)
     if source1_id.is_some() || source2_id.is_some() {
@@ -716,6 +1179,19 @@ async fn stop_test_audio_device() -> Result<(), String> {
     }
 }
 
+/// Record a short sample from a device and return a level/problem report.
+#[tauri::command]
+async fn run_audio_self_test(device_id: String) -> Result<AudioSelfTestReport, String> {
+    let response =
+        flowstt_engine::ipc::handlers::handle_request(Request::RunAudioSelfTest { device_id })
+            .await;
+    match response {
+        Response::AudioSelfTest(report) => Ok(report),
+        Response::Error { message } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
 /// Open System Settings to the Accessibility pane on macOS.
 /// The Tauri app process itself now needs Accessibility permission (no more IPC delegation).
 #[tauri::command]
@@ -797,10 +1273,7 @@ fn set_log_level(level: String, state: State<LogState>) -> Result<(), String> {
         .map_err(|e| format!("Failed to reload log filter: {}", e))?;
 
     // Persist to config.
-    let mut config = Config::load();
-    config.log_level = log_level;
-    config
-        .save()
+    flowstt_common::settings::set(key::LOG_LEVEL, log_level)
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
     Ok(())
@@ -934,15 +1407,25 @@ pub fn open_log_viewer_window(app: &AppHandle) {
 pub fn run() {
     let app_t0 = Instant::now();
 
-    // Parse --headless and --test-mode flags
+    // Parse --headless, --test-mode and --portable flags
     let headless = std::env::args().any(|arg| arg == "--headless");
     let test_mode = std::env::args().any(|arg| arg == "--test-mode");
+    if std::env::args().any(|arg| arg == "--portable") {
+        flowstt_common::portable::enable();
+    }
+
+    // Move files left in older locations before anything opens them.
+    let migration_problems = flowstt_common::paths::migrate();
 
     // Read config before initializing logging so we can use the configured level.
     let initial_config = Config::load();
 
     // Initialize layered logging subscriber with reloadable filter.
     let (log_state, log_rx) = init_logging(&initial_config.log_level);
+    for problem in migration_problems {
+        warn!("[Startup] {}", problem);
+    }
+    flowstt_engine::crash::install();
 
     // Set test mode state before tray setup so conditional menu items are available
     if test_mode {
@@ -959,8 +1442,9 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState {
-            ipc_server_handle: Mutex::new(None),
+            engine: Mutex::new(None),
         })
         .manage(log_state)
         .setup(move |app| {
@@ -987,19 +1471,21 @@ pub fn run() {
 
             let app_handle = app.handle().clone();
 
-            // Register event callback so engine events go directly to Tauri frontend
-            flowstt_engine::ipc::register_event_callback(TauriEventCallback {
-                app_handle: app_handle.clone(),
-            });
-
-            // Initialize the engine (audio backends, transcription, IPC server, etc.)
-            let ipc_handle = tauri::async_runtime::block_on(async { flowstt_engine::init().await });
+            // Initialize the engine (audio backends, transcription, IPC server, etc.),
+            // with engine events going directly to the Tauri frontend
+            let engine = tauri::async_runtime::block_on(
+                flowstt_engine::EngineBuilder::new()
+                    .on_event(TauriEventCallback {
+                        app_handle: app_handle.clone(),
+                    })
+                    .build(),
+            );
 
-            match ipc_handle {
-                Ok(handle) => {
+            match engine {
+                Ok(engine) => {
                     let state: State<AppState> = app.state();
-                    let mut lock = tauri::async_runtime::block_on(state.ipc_server_handle.lock());
-                    *lock = Some(handle);
+                    let mut lock = tauri::async_runtime::block_on(state.engine.lock());
+                    *lock = Some(engine);
                     info!("[Startup] Engine initialized successfully");
                 }
                 Err(e) => {
@@ -1096,22 +1582,33 @@ pub fn run() {
             download_model,
             get_status,
             get_cuda_status,
+            get_capabilities,
             set_transcription_mode,
             set_ptt_hotkeys,
             get_ptt_status,
             set_auto_toggle_hotkeys,
             toggle_auto_mode,
             get_history,
+            get_history_summary,
             delete_history_entry,
+            edit_history_entry,
+            read_history_audio,
             connect_events,
             get_theme_mode,
             set_theme_mode,
+            get_visualization_config,
+            set_visualization_config,
+            get_notification_settings,
+            set_notification_settings,
+            get_sound_cue_settings,
+            set_sound_cue_settings,
             needs_setup,
             get_runtime_mode,
             cancel_menu_mode,
             complete_setup,
             test_audio_device,
             stop_test_audio_device,
+            run_audio_self_test,
             check_accessibility_permission,
             open_accessibility_settings,
         ])
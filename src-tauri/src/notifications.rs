@@ -0,0 +1,135 @@
+//! Native desktop notifications for engine events.
+//!
+//! Each category can be switched on or off in the `notifications` section of
+//! the config file. Settings are re-read per notification so changes made from
+//! the config window or CLI apply without a restart. Text is shown in the
+//! configured language (see `flowstt_common::i18n`).
+
+use flowstt_common::config::NotificationSettings;
+use flowstt_common::i18n::{self, tr, Message};
+use flowstt_common::ipc::EventType;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use tracing::warn;
+
+/// Notification title used for all FlowSTT toasts
+const TITLE: &str = "FlowSTT";
+
+/// Maximum number of characters of transcribed text shown in a toast
+const MAX_BODY_CHARS: usize = 200;
+
+/// Show a notification for an engine event if its category is enabled.
+pub fn handle_event(app_handle: &AppHandle, event: &EventType) {
+    let body = match event {
+        EventType::TranscriptionComplete(result) => {
+            if !settings().transcription || result.text.trim().is_empty() {
+                return;
+            }
+            truncate(result.text.trim())
+        }
+        EventType::ModelDownloadComplete { success } => {
+            if !settings().model_download {
+                return;
+            }
+            if *success {
+                tr(Message::NotifyModelDownloadComplete).to_string()
+            } else {
+                tr(Message::NotifyModelDownloadFailed).to_string()
+            }
+        }
+        EventType::CaptureStateChanged {
+            error: Some(error), ..
+        } => {
+            if !settings().capture_errors {
+                return;
+            }
+            i18n::format(Message::NotifyCaptureStopped, &[("error", error)])
+        }
+        EventType::DeviceBusy {
+            holder_hint,
+            fallback_id,
+            ..
+        } => {
+            if !settings().capture_errors {
+                return;
+            }
+            let holder = holder_hint
+                .as_deref()
+                .unwrap_or(tr(Message::AnotherApplication));
+            match fallback_id {
+                Some(_) => i18n::format(Message::NotifyDeviceBusyFallback, &[("holder", &holder)]),
+                None => i18n::format(Message::NotifyDeviceBusy, &[("holder", &holder)]),
+            }
+        }
+        EventType::CaptureStalled { stalled_secs } => {
+            if !settings().capture_errors {
+                return;
+            }
+            i18n::format(Message::NotifyCaptureStalled, &[("secs", stalled_secs)])
+        }
+        EventType::DeviceProfileChanged {
+            sample_rate,
+            narrowband: true,
+            switched_to,
+            ..
+        } => {
+            if !settings().input_quality {
+                return;
+            }
+            match switched_to {
+                Some(_) => tr(Message::NotifyNarrowbandFallback).to_string(),
+                None => i18n::format(Message::NotifyNarrowband, &[("khz", &(sample_rate / 1000))]),
+            }
+        }
+        EventType::ClippingDetected { .. } => {
+            if !settings().clipping {
+                return;
+            }
+            tr(Message::NotifyClipping).to_string()
+        }
+        EventType::UpdateAvailable { version, .. } => {
+            if !settings().updates {
+                return;
+            }
+            i18n::format(Message::NotifyUpdateAvailable, &[("version", version)])
+        }
+        EventType::PasteFailed { reason } => {
+            if !settings().paste_failures {
+                return;
+            }
+            i18n::format(Message::NotifyPasteFailed, &[("reason", reason)])
+        }
+        _ => return,
+    };
+
+    show(app_handle, &body);
+}
+
+/// The current notification settings, re-read only when the config file
+/// has changed.
+fn settings() -> NotificationSettings {
+    flowstt_common::settings::current().notifications.clone()
+}
+
+/// Display a toast with the given body text.
+fn show(app_handle: &AppHandle, body: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(TITLE)
+        .body(body)
+        .show()
+    {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// Shorten long transcriptions to fit in a toast.
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_BODY_CHARS {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(MAX_BODY_CHARS - 1).collect();
+    short.push('…');
+    short
+}
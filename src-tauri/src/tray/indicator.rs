@@ -0,0 +1,364 @@
+//! Capture-state indicator on the tray icon and taskbar.
+//!
+//! Engine events move the indicator between [`TrayState`]s. Each state uses
+//! `icons/tray/icon-<state>.png` when it is bundled; otherwise a coloured
+//! badge is drawn onto the default tray icon. While a segment is being
+//! transcribed the badge pulses until the result arrives.
+//!
+//! On macOS an `icon-<state>Template.png` variant is preferred and marked as
+//! a template image so it follows the menu bar appearance. On Windows the
+//! main window's taskbar button gets a matching overlay badge.
+//!
+//! Icon updates run on a dedicated thread so animation frames never race
+//! with state changes.
+
+use std::f32::consts::TAU;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::EventType;
+use tauri::{image::Image, AppHandle, Manager};
+use tracing::warn;
+
+use super::load_tray_icon_from_paths;
+
+/// Tray icon identifier used by the platform setup code
+const TRAY_ID: &str = "main-tray";
+
+/// Frames in the transcribing animation
+const ANIMATION_FRAMES: usize = 8;
+
+/// Time each animation frame is shown
+const FRAME_INTERVAL: Duration = Duration::from_millis(125);
+
+/// Longest to show the transcribing state. Segments without speech produce
+/// no result, so the indicator would otherwise never leave it.
+const TRANSCRIBING_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Edge length of the taskbar overlay badge in pixels
+#[cfg_attr(not(windows), allow(dead_code))]
+const OVERLAY_SIZE: u32 = 16;
+
+/// What the tray icon is currently showing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayState {
+    /// Capture is off
+    Idle,
+    /// Capturing and waiting for speech
+    Listening,
+    /// Speech is being recorded
+    SpeechDetected,
+    /// A segment is being transcribed
+    Transcribing,
+    /// Capture stopped because of an error
+    Error,
+    /// Capturing, but output is paused by a pause phrase
+    Paused,
+}
+
+impl TrayState {
+    const ALL: [TrayState; 6] = [
+        TrayState::Idle,
+        TrayState::Listening,
+        TrayState::SpeechDetected,
+        TrayState::Transcribing,
+        TrayState::Error,
+        TrayState::Paused,
+    ];
+
+    /// Icon file name without extension
+    fn icon_name(self) -> &'static str {
+        match self {
+            TrayState::Idle => "icon",
+            TrayState::Listening => "icon-recording",
+            TrayState::SpeechDetected => "icon-speech",
+            TrayState::Transcribing => "icon-transcribing",
+            TrayState::Error => "icon-error",
+            TrayState::Paused => "icon-paused",
+        }
+    }
+
+    /// Badge colour drawn when no dedicated icon is bundled
+    fn badge_color(self) -> Option<[u8; 3]> {
+        match self {
+            TrayState::Idle => None,
+            TrayState::Listening => Some([0x22, 0xc5, 0x5e]),
+            TrayState::SpeechDetected => Some([0xef, 0x44, 0x44]),
+            TrayState::Transcribing => Some([0x3b, 0x82, 0xf6]),
+            TrayState::Error => Some([0xf5, 0x9e, 0x0b]),
+            TrayState::Paused => Some([0x9c, 0xa3, 0xaf]),
+        }
+    }
+}
+
+/// Engine-derived state behind the indicator.
+struct Indicator {
+    state: TrayState,
+    capturing: bool,
+    /// Output is paused by a pause phrase
+    paused: bool,
+}
+
+impl Indicator {
+    /// State to return to once speech or transcription is over
+    fn resting_state(&self) -> TrayState {
+        if self.capturing && self.paused {
+            TrayState::Paused
+        } else if self.capturing {
+            TrayState::Listening
+        } else {
+            TrayState::Idle
+        }
+    }
+}
+
+static INDICATOR: Mutex<Indicator> = Mutex::new(Indicator {
+    state: TrayState::Idle,
+    capturing: false,
+    paused: false,
+});
+
+/// Channel to the icon update thread, started on first use
+static UPDATES: OnceLock<Sender<TrayState>> = OnceLock::new();
+
+/// Update the indicator for an engine event.
+pub fn handle_event(app_handle: &AppHandle, event: &EventType) {
+    let next = {
+        let mut indicator = INDICATOR.lock().unwrap();
+        let next = match event {
+            EventType::CaptureStateChanged { capturing, error } => {
+                indicator.capturing = *capturing;
+                if error.is_some() {
+                    TrayState::Error
+                } else {
+                    indicator.resting_state()
+                }
+            }
+            EventType::OutputPausedChanged { paused } => {
+                indicator.paused = *paused;
+                match indicator.state {
+                    TrayState::Listening | TrayState::Paused => indicator.resting_state(),
+                    _ => return,
+                }
+            }
+            EventType::SpeechStarted if indicator.capturing => TrayState::SpeechDetected,
+            EventType::SpeechEnded { .. } if indicator.capturing => TrayState::Transcribing,
+            EventType::TranscriptionComplete(_) if indicator.state == TrayState::Transcribing => {
+                indicator.resting_state()
+            }
+            EventType::Shutdown => TrayState::Idle,
+            _ => return,
+        };
+        if next == indicator.state {
+            return;
+        }
+        indicator.state = next;
+        next
+    };
+
+    let updates = UPDATES.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        let app_handle = app_handle.clone();
+        std::thread::spawn(move || run_updates(app_handle, rx));
+        tx
+    });
+    let _ = updates.send(next);
+}
+
+/// Leave the transcribing state after [`TRANSCRIBING_TIMEOUT`], unless
+/// something else has changed the state meanwhile.
+fn expire_transcribing() -> Option<TrayState> {
+    let mut indicator = INDICATOR.lock().unwrap();
+    if indicator.state != TrayState::Transcribing {
+        return None;
+    }
+    indicator.state = indicator.resting_state();
+    Some(indicator.state)
+}
+
+/// Icon update loop: applies each new state and animates the transcribing one.
+fn run_updates(app_handle: AppHandle, rx: Receiver<TrayState>) {
+    let Some(icons) = IconSet::load(&app_handle) else {
+        warn!("[Tray] Default tray icon not found – capture state will not be shown");
+        return;
+    };
+
+    let mut state = TrayState::Idle;
+    let mut frame = 0;
+    let mut entered = Instant::now();
+
+    loop {
+        let received = if state == TrayState::Transcribing {
+            rx.recv_timeout(FRAME_INTERVAL)
+        } else {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+
+        match received {
+            Ok(next) => {
+                state = next;
+                frame = 0;
+                entered = Instant::now();
+                icons.apply(&app_handle, state, frame);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if entered.elapsed() >= TRANSCRIBING_TIMEOUT {
+                    if let Some(next) = expire_transcribing() {
+                        state = next;
+                        icons.apply(&app_handle, state, 0);
+                    }
+                    continue;
+                }
+                frame += 1;
+                icons.apply(&app_handle, state, frame);
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// Icons for one state.
+struct StateIcon {
+    /// Animation frames; a single frame for static states
+    frames: Vec<Image<'static>>,
+    /// Whether the frames are macOS template images
+    template: bool,
+    /// Taskbar overlay badge, or `None` to clear it
+    #[cfg_attr(not(windows), allow(dead_code))]
+    overlay: Option<Image<'static>>,
+}
+
+/// Prepared icons for every [`TrayState`].
+struct IconSet {
+    icons: Vec<StateIcon>,
+}
+
+impl IconSet {
+    fn load(app_handle: &AppHandle) -> Option<Self> {
+        let find =
+            |name: &str| load_tray_icon_from_paths(app_handle.path().resource_dir().ok(), name);
+
+        let base = find("icon.png").or_else(|| find("32x32.png"))?;
+
+        let icons = TrayState::ALL
+            .iter()
+            .map(|&state| {
+                let template = if cfg!(target_os = "macos") {
+                    find(&format!("{}Template.png", state.icon_name()))
+                } else {
+                    None
+                };
+                let overlay = state
+                    .badge_color()
+                    .map(|color| draw_badge(&blank(OVERLAY_SIZE), color, 1.0, 0.5));
+
+                if let Some(icon) = template {
+                    return StateIcon {
+                        frames: vec![icon],
+                        template: true,
+                        overlay,
+                    };
+                }
+
+                let frames = match (
+                    find(&format!("{}.png", state.icon_name())),
+                    state.badge_color(),
+                ) {
+                    (Some(icon), _) => vec![icon],
+                    (None, None) => vec![base.clone()],
+                    (None, Some(color)) if state == TrayState::Transcribing => (0
+                        ..ANIMATION_FRAMES)
+                        .map(|frame| draw_badge(&base, color, pulse(frame), 0.25))
+                        .collect(),
+                    (None, Some(color)) => vec![draw_badge(&base, color, 1.0, 0.25)],
+                };
+
+                StateIcon {
+                    frames,
+                    template: false,
+                    overlay,
+                }
+            })
+            .collect();
+
+        Some(Self { icons })
+    }
+
+    /// Show a frame of a state's icon on the tray and taskbar.
+    fn apply(&self, app_handle: &AppHandle, state: TrayState, frame: usize) {
+        let icon = &self.icons[state as usize];
+        if frame > 0 && icon.frames.len() == 1 {
+            return;
+        }
+
+        if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+            let image = icon.frames[frame % icon.frames.len()].clone();
+            if let Err(e) = tray.set_icon(Some(image)) {
+                warn!("[Tray] Failed to update tray icon: {}", e);
+            }
+            if frame == 0 {
+                let _ = tray.set_icon_as_template(icon.template);
+            }
+        }
+
+        #[cfg(windows)]
+        if frame == 0 {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                if let Err(e) = window.set_overlay_icon(icon.overlay.clone()) {
+                    warn!("[Tray] Failed to update taskbar overlay: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Badge opacity for an animation frame, easing between dim and full.
+fn pulse(frame: usize) -> f32 {
+    let phase = frame as f32 / ANIMATION_FRAMES as f32;
+    0.35 + 0.65 * (0.5 + 0.5 * (phase * TAU).cos())
+}
+
+/// A fully transparent square image.
+fn blank(size: u32) -> Image<'static> {
+    Image::new_owned(vec![0; (size * size * 4) as usize], size, size)
+}
+
+/// Draw a filled circle in the bottom-right corner of an image.
+///
+/// `radius` is a fraction of the image width; 0.5 fills the whole image.
+fn draw_badge(image: &Image<'_>, color: [u8; 3], opacity: f32, radius: f32) -> Image<'static> {
+    let (width, height) = (image.width(), image.height());
+    let mut rgba = image.rgba().to_vec();
+
+    let radius = width as f32 * radius;
+    let cx = width as f32 - radius;
+    let cy = height as f32 - radius;
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            // Anti-aliased edge: partial coverage within half a pixel of the rim
+            let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage == 0.0 {
+                continue;
+            }
+
+            // Composite the badge over the existing pixel
+            let alpha = coverage * opacity;
+            let idx = ((y * width + x) * 4) as usize;
+            let dst_alpha = rgba[idx + 3] as f32 / 255.0;
+            let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+            for (c, &value) in color.iter().enumerate() {
+                let blended = (value as f32 * alpha
+                    + rgba[idx + c] as f32 * dst_alpha * (1.0 - alpha))
+                    / out_alpha;
+                rgba[idx + c] = blended.round() as u8;
+            }
+            rgba[idx + 3] = (out_alpha * 255.0).round() as u8;
+        }
+    }
+
+    Image::new_owned(rgba, width, height)
+}
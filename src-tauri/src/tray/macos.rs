@@ -10,8 +10,15 @@ use tauri::{
 use tracing::{error, warn};
 
 use flowstt_common::config::Config;
+use flowstt_common::i18n::{tr, Message};
+use flowstt_common::settings;
+use flowstt_common::TranscriptionMode;
 
-use super::{menu_ids, menu_labels, shutdown_engine};
+use super::{
+    build_autostart_item, build_device_mode_submenus, build_privacy_item, build_recent_submenu,
+    copy_recent_transcription, menu_ids, select_microphone, select_mode, shutdown_engine,
+    toggle_autostart, toggle_privacy_mode,
+};
 use crate::open_log_viewer_window;
 
 /// Set up the system tray on macOS.
@@ -22,11 +29,17 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::load();
     let always_on_top_enabled = config.always_on_top;
 
-    let show_item = MenuItem::with_id(app, menu_ids::SHOW, menu_labels::SHOW, true, None::<&str>)?;
+    let show_item = MenuItem::with_id(
+        app,
+        menu_ids::SHOW,
+        tr(Message::TrayShow),
+        true,
+        None::<&str>,
+    )?;
     let always_on_top_item = CheckMenuItem::with_id(
         app,
         menu_ids::ALWAYS_ON_TOP,
-        menu_labels::ALWAYS_ON_TOP,
+        tr(Message::TrayAlwaysOnTop),
         true,
         always_on_top_enabled,
         None::<&str>,
@@ -34,14 +47,35 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let settings_item = MenuItem::with_id(
         app,
         menu_ids::SETTINGS,
-        menu_labels::SETTINGS,
+        tr(Message::TraySettings),
+        true,
+        None::<&str>,
+    )?;
+    let logs_item = MenuItem::with_id(
+        app,
+        menu_ids::LOGS,
+        tr(Message::TrayLogs),
         true,
         None::<&str>,
     )?;
-    let logs_item = MenuItem::with_id(app, menu_ids::LOGS, menu_labels::LOGS, true, None::<&str>)?;
-    let about_item =
-        MenuItem::with_id(app, menu_ids::ABOUT, menu_labels::ABOUT, true, None::<&str>)?;
-    let exit_item = MenuItem::with_id(app, menu_ids::EXIT, menu_labels::EXIT, true, None::<&str>)?;
+    let about_item = MenuItem::with_id(
+        app,
+        menu_ids::ABOUT,
+        tr(Message::TrayAbout),
+        true,
+        None::<&str>,
+    )?;
+    let exit_item = MenuItem::with_id(
+        app,
+        menu_ids::EXIT,
+        tr(Message::TrayExit),
+        true,
+        None::<&str>,
+    )?;
+    let recent_submenu = build_recent_submenu(app)?;
+    let (microphone_submenu, mode_submenu) = build_device_mode_submenus(app)?;
+    let privacy_item = build_privacy_item(app)?;
+    let autostart_item = build_autostart_item(app)?;
 
     let menu = Menu::with_items(
         app,
@@ -52,6 +86,12 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
             &logs_item,
             &about_item,
             &PredefinedMenuItem::separator(app)?,
+            &microphone_submenu,
+            &mode_submenu,
+            &privacy_item,
+            &autostart_item,
+            &recent_submenu,
+            &PredefinedMenuItem::separator(app)?,
             &exit_item,
         ],
     )?;
@@ -97,6 +137,24 @@ fn handle_menu_event(
         id if id == menu_ids::ABOUT => {
             show_about_window(app);
         }
+        id if id.starts_with(menu_ids::MICROPHONE_ITEM_PREFIX) => {
+            select_microphone(app, &id[menu_ids::MICROPHONE_ITEM_PREFIX.len()..]);
+        }
+        id if id == menu_ids::MODE_AUTOMATIC => {
+            select_mode(app, TranscriptionMode::Automatic);
+        }
+        id if id == menu_ids::MODE_PUSH_TO_TALK => {
+            select_mode(app, TranscriptionMode::PushToTalk);
+        }
+        id if id == menu_ids::PRIVACY_MODE => {
+            toggle_privacy_mode();
+        }
+        id if id == menu_ids::AUTOSTART => {
+            toggle_autostart(app);
+        }
+        id if id.starts_with(menu_ids::RECENT_ITEM_PREFIX) => {
+            copy_recent_transcription(&id[menu_ids::RECENT_ITEM_PREFIX.len()..]);
+        }
         id if id == menu_ids::EXIT => {
             shutdown_engine();
             app.exit(0);
@@ -110,16 +168,16 @@ fn toggle_always_on_top(
     app: &tauri::AppHandle,
     check_item: &tauri::menu::CheckMenuItem<tauri::Wry>,
 ) {
-    let mut config = Config::load();
-    config.always_on_top = !config.always_on_top;
-    let enabled = config.always_on_top;
-
-    if let Err(e) = config.save() {
-        error!(
-            "[Tray] Failed to save config after always-on-top toggle: {}",
-            e
-        );
-    }
+    let enabled = match settings::update(|config| config.always_on_top = !config.always_on_top) {
+        Ok(config) => config.always_on_top,
+        Err(e) => {
+            error!(
+                "[Tray] Failed to save config after always-on-top toggle: {}",
+                e
+            );
+            return;
+        }
+    };
 
     if let Some(window) = app.get_webview_window("main") {
         if let Err(e) = window.set_always_on_top(enabled) {
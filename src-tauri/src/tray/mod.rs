@@ -4,9 +4,19 @@
 //! - Windows: windows.rs
 //! - macOS: macos.rs
 
+use flowstt_common::config::Config;
+use flowstt_common::i18n::{tr, Message};
+use flowstt_common::ipc::{Request, Response};
+use flowstt_common::{AudioSourceType, TranscriptionMode};
 use std::path::PathBuf;
-use tauri::{image::Image, Manager};
-use tracing::warn;
+use tauri::{
+    image::Image,
+    menu::{CheckMenuItem, MenuItem, Submenu},
+    AppHandle, Manager, Wry,
+};
+use tracing::{info, warn};
+
+pub mod indicator;
 
 #[cfg(windows)]
 pub mod windows;
@@ -23,20 +33,32 @@ pub mod menu_ids {
     pub const LOGS: &str = "logs";
     pub const ABOUT: &str = "about";
     pub const RUN_TEST: &str = "run_test";
+    pub const RECENT: &str = "recent";
+    pub const RECENT_EMPTY: &str = "recent_empty";
+    /// Prefix for recent transcription items; the history entry ID follows it
+    pub const RECENT_ITEM_PREFIX: &str = "recent:";
+    pub const MICROPHONE: &str = "microphone";
+    pub const MICROPHONE_NONE: &str = "microphone_none";
+    /// Prefix for input device items; the device ID follows it
+    pub const MICROPHONE_ITEM_PREFIX: &str = "mic:";
+    pub const MODE: &str = "mode";
+    pub const MODE_AUTOMATIC: &str = "mode_automatic";
+    pub const MODE_PUSH_TO_TALK: &str = "mode_push_to_talk";
+    pub const PRIVACY_MODE: &str = "privacy_mode";
+    pub const AUTOSTART: &str = "autostart";
     pub const EXIT: &str = "exit";
 }
 
-/// Menu item labels.
-#[allow(dead_code)]
-pub mod menu_labels {
-    pub const SHOW: &str = "Show";
-    pub const ALWAYS_ON_TOP: &str = "Always on Top";
-    pub const SETTINGS: &str = "Settings";
-    pub const LOGS: &str = "Logs";
-    pub const ABOUT: &str = "About";
-    pub const RUN_TEST: &str = "Run Test (WAV Directory)...";
-    pub const EXIT: &str = "Exit";
-}
+/// Number of history entries shown in the recent transcriptions submenu
+const RECENT_MENU_SIZE: usize = 10;
+
+/// Maximum label length (in characters) for a recent transcription item
+const RECENT_LABEL_MAX_CHARS: usize = 40;
+
+/// Handle to the recent transcriptions submenu, kept in Tauri state so it can
+/// be rebuilt when history changes.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub struct RecentMenu(Submenu<Wry>);
 
 /// Platform-specific tray setup.
 #[cfg(windows)]
@@ -51,47 +73,362 @@ pub fn setup_tray(_app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// Shut down the engine directly (in-process).
-/// Used by the tray Exit handler to stop the engine before exiting the app.
-fn shutdown_engine() {
-    flowstt_engine::request_shutdown();
-    flowstt_engine::cleanup();
+/// Build the recent transcriptions submenu and register it for later refreshes.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn build_recent_submenu(app: &tauri::App) -> tauri::Result<Submenu<Wry>> {
+    let submenu = Submenu::with_id(app, menu_ids::RECENT, tr(Message::TrayRecent), true)?;
+    populate_recent_submenu(app.handle(), &submenu);
+    app.manage(RecentMenu(submenu.clone()));
+    Ok(submenu)
 }
 
-/// Update the tray icon to reflect the current recording state.
-///
-/// When `recording` is `true` the tray shows `icon-recording.png`; when
-/// `false` it reverts to `icon.png` (the default tray icon).
-pub fn update_tray_icon(app_handle: &tauri::AppHandle, recording: bool) {
-    let icon_name = if recording {
-        "icon-recording.png"
-    } else {
-        "icon.png"
+/// Rebuild the recent transcriptions submenu from the current history.
+/// No-op on platforms without a tray menu.
+pub fn refresh_recent_menu(app_handle: &AppHandle) {
+    if let Some(recent) = app_handle.try_state::<RecentMenu>() {
+        populate_recent_submenu(app_handle, &recent.0);
+    }
+}
+
+/// Replace the submenu contents with the most recent history entries.
+fn populate_recent_submenu(app_handle: &AppHandle, submenu: &Submenu<Wry>) {
+    while let Ok(Some(_)) = submenu.remove_at(0) {}
+
+    let entries: Vec<(String, String)> = {
+        let history = flowstt_engine::history::get_history();
+        let history = history.lock().unwrap();
+        history
+            .get_entries()
+            .iter()
+            .rev()
+            .take(RECENT_MENU_SIZE)
+            .map(|e| (e.id.clone(), recent_label(&e.text)))
+            .collect()
     };
 
-    let resource_dir = app_handle.path().resource_dir().ok();
-    let icon = load_tray_icon_from_paths(resource_dir, icon_name).or_else(|| {
-        if recording {
-            // No dedicated recording icon found – keep current icon unchanged.
-            None
-        } else {
-            // Fallback: try 32x32.png for the default icon.
-            let resource_dir2 = app_handle.path().resource_dir().ok();
-            load_tray_icon_from_paths(resource_dir2, "32x32.png")
+    if entries.is_empty() {
+        if let Ok(item) = MenuItem::with_id(
+            app_handle,
+            menu_ids::RECENT_EMPTY,
+            tr(Message::TrayRecentEmpty),
+            false,
+            None::<&str>,
+        ) {
+            let _ = submenu.append(&item);
         }
+        return;
+    }
+
+    for (id, label) in entries {
+        let item_id = format!("{}{}", menu_ids::RECENT_ITEM_PREFIX, id);
+        match MenuItem::with_id(app_handle, item_id, label, true, None::<&str>) {
+            Ok(item) => {
+                if let Err(e) = submenu.append(&item) {
+                    warn!("[Tray] Failed to add recent transcription item: {}", e);
+                }
+            }
+            Err(e) => warn!("[Tray] Failed to create recent transcription item: {}", e),
+        }
+    }
+}
+
+/// Build a single-line, truncated menu label for a transcription.
+fn recent_label(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > RECENT_LABEL_MAX_CHARS {
+        let truncated: String = line.chars().take(RECENT_LABEL_MAX_CHARS - 1).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        line
+    }
+}
+
+/// Copy a history entry's text back to the clipboard (recent submenu click).
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn copy_recent_transcription(id: &str) {
+    let text = {
+        let history = flowstt_engine::history::get_history();
+        let history = history.lock().unwrap();
+        history
+            .get_entries()
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| e.text.clone())
+    };
+
+    match text {
+        Some(text) => match flowstt_engine::clipboard::copy_text(&text) {
+            Ok(()) => info!("[Tray] Copied recent transcription to clipboard"),
+            Err(e) => warn!("[Tray] Failed to copy transcription: {}", e),
+        },
+        None => warn!("[Tray] Recent transcription {} no longer in history", id),
+    }
+}
+
+/// Handles to the microphone and mode menus, kept in Tauri state so they can
+/// be updated when the device list or transcription mode changes.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub struct DeviceModeMenus {
+    microphone: Submenu<Wry>,
+    automatic: CheckMenuItem<Wry>,
+    push_to_talk: CheckMenuItem<Wry>,
+}
+
+/// Build the "Microphone" and "Mode" submenus and register them for updates.
+///
+/// The microphone list is filled in asynchronously from `ListDevices`.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn build_device_mode_submenus(app: &tauri::App) -> tauri::Result<(Submenu<Wry>, Submenu<Wry>)> {
+    let microphone =
+        Submenu::with_id(app, menu_ids::MICROPHONE, tr(Message::TrayMicrophone), true)?;
+
+    let mode = Config::load().transcription_mode;
+    let automatic = CheckMenuItem::with_id(
+        app,
+        menu_ids::MODE_AUTOMATIC,
+        tr(Message::TrayModeAutomatic),
+        true,
+        mode == TranscriptionMode::Automatic,
+        None::<&str>,
+    )?;
+    let push_to_talk = CheckMenuItem::with_id(
+        app,
+        menu_ids::MODE_PUSH_TO_TALK,
+        tr(Message::TrayModePushToTalk),
+        true,
+        mode == TranscriptionMode::PushToTalk,
+        None::<&str>,
+    )?;
+    let mode_submenu = Submenu::with_id_and_items(
+        app,
+        menu_ids::MODE,
+        tr(Message::TrayMode),
+        true,
+        &[&automatic, &push_to_talk],
+    )?;
+
+    app.manage(DeviceModeMenus {
+        microphone: microphone.clone(),
+        automatic,
+        push_to_talk,
     });
+    refresh_microphone_menu(app.handle());
+
+    Ok((microphone, mode_submenu))
+}
+
+/// Rebuild the microphone submenu from the current device list and selection.
+/// No-op on platforms without a tray menu.
+pub fn refresh_microphone_menu(app_handle: &AppHandle) {
+    if app_handle.try_state::<DeviceModeMenus>().is_none() {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let devices = match flowstt_engine::ipc::handlers::handle_request(Request::ListDevices {
+            source_type: Some(AudioSourceType::Input),
+        })
+        .await
+        {
+            Response::Devices { devices } => devices,
+            Response::Error { message } => {
+                warn!("[Tray] Failed to list input devices: {}", message);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        let selected = match flowstt_engine::ipc::handlers::handle_request(Request::GetStatus).await
+        {
+            Response::Status(status) => status.source1_id,
+            _ => None,
+        };
 
-    if let Some(icon) = icon {
-        if let Some(tray) = app_handle.tray_by_id("main-tray") {
-            if let Err(e) = tray.set_icon(Some(icon)) {
-                warn!("[Tray] Failed to update tray icon: {}", e);
+        let Some(menus) = app_handle.try_state::<DeviceModeMenus>() else {
+            return;
+        };
+        let submenu = &menus.microphone;
+        while let Ok(Some(_)) = submenu.remove_at(0) {}
+
+        if devices.is_empty() {
+            if let Ok(item) = MenuItem::with_id(
+                &app_handle,
+                menu_ids::MICROPHONE_NONE,
+                tr(Message::TrayMicrophoneNone),
+                false,
+                None::<&str>,
+            ) {
+                let _ = submenu.append(&item);
+            }
+            return;
+        }
+
+        for device in devices {
+            let checked = selected.as_deref() == Some(device.id.as_str());
+            let item_id = format!("{}{}", menu_ids::MICROPHONE_ITEM_PREFIX, device.id);
+            match CheckMenuItem::with_id(
+                &app_handle,
+                item_id,
+                &device.name,
+                true,
+                checked,
+                None::<&str>,
+            ) {
+                Ok(item) => {
+                    if let Err(e) = submenu.append(&item) {
+                        warn!("[Tray] Failed to add microphone item: {}", e);
+                    }
+                }
+                Err(e) => warn!("[Tray] Failed to create microphone item: {}", e),
             }
         }
-    } else if recording {
-        warn!("[Tray] Recording icon not found – tray icon unchanged");
+    });
+}
+
+/// Update the mode submenu check marks. No-op on platforms without a tray menu.
+pub fn update_mode_menu(app_handle: &AppHandle, mode: TranscriptionMode) {
+    if let Some(menus) = app_handle.try_state::<DeviceModeMenus>() {
+        let _ = menus
+            .automatic
+            .set_checked(mode == TranscriptionMode::Automatic);
+        let _ = menus
+            .push_to_talk
+            .set_checked(mode == TranscriptionMode::PushToTalk);
     }
 }
 
+/// Switch the primary input device (microphone submenu click).
+/// Keeps the current secondary source.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn select_microphone(app_handle: &AppHandle, device_id: &str) {
+    let app_handle = app_handle.clone();
+    let device_id = device_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let source2_id =
+            match flowstt_engine::ipc::handlers::handle_request(Request::GetStatus).await {
+                Response::Status(status) => status.source2_id,
+                _ => None,
+            };
+
+        info!("[Tray] Switching microphone to {}", device_id);
+        let response = flowstt_engine::ipc::handlers::handle_request(Request::SetSources {
+            source1_id: Some(device_id),
+            source2_id,
+        })
+        .await;
+        if let Response::Error { message } = response {
+            warn!("[Tray] Failed to switch microphone: {}", message);
+        }
+
+        refresh_microphone_menu(&app_handle);
+    });
+}
+
+/// Switch the transcription mode (mode submenu click).
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn select_mode(app_handle: &AppHandle, mode: TranscriptionMode) {
+    // Clicking a check item toggles it; restore the real state immediately
+    update_mode_menu(app_handle, mode);
+
+    tauri::async_runtime::spawn(async move {
+        info!("[Tray] Switching transcription mode to {:?}", mode);
+        let response =
+            flowstt_engine::ipc::handlers::handle_request(Request::SetTranscriptionMode { mode })
+                .await;
+        if let Response::Error { message } = response {
+            warn!("[Tray] Failed to switch transcription mode: {}", message);
+        }
+    });
+}
+
+/// Handle to the privacy mode check item, kept in Tauri state so it can be
+/// updated when privacy mode is changed from the GUI or CLI.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub struct PrivacyMenuItem(CheckMenuItem<Wry>);
+
+/// Build the "Privacy Mode" check item and register it for updates.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn build_privacy_item(app: &tauri::App) -> tauri::Result<CheckMenuItem<Wry>> {
+    let item = CheckMenuItem::with_id(
+        app,
+        menu_ids::PRIVACY_MODE,
+        tr(Message::TrayPrivacyMode),
+        true,
+        flowstt_engine::privacy::is_privacy_mode(),
+        None::<&str>,
+    )?;
+    app.manage(PrivacyMenuItem(item.clone()));
+    Ok(item)
+}
+
+/// Update the privacy mode check mark. No-op on platforms without a tray menu.
+pub fn update_privacy_menu(app_handle: &AppHandle, enabled: bool) {
+    if let Some(item) = app_handle.try_state::<PrivacyMenuItem>() {
+        let _ = item.0.set_checked(enabled);
+    }
+}
+
+/// Flip privacy mode (privacy menu click). The resulting
+/// `PrivacyModeChanged` event updates the check mark.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn toggle_privacy_mode() {
+    let enabled = !flowstt_engine::privacy::is_privacy_mode();
+    flowstt_engine::privacy::set_privacy_mode(enabled);
+    info!("[Tray] Privacy mode toggled: {}", enabled);
+}
+
+/// Handle to the "Start at Login" check item, kept in Tauri state so it can
+/// be updated when autostart is changed from the CLI.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub struct AutostartMenuItem(CheckMenuItem<Wry>);
+
+/// Build the "Start at Login" check item and register it for updates.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn build_autostart_item(app: &tauri::App) -> tauri::Result<CheckMenuItem<Wry>> {
+    let item = CheckMenuItem::with_id(
+        app,
+        menu_ids::AUTOSTART,
+        tr(Message::TrayAutostart),
+        true,
+        flowstt_engine::autostart::is_enabled(),
+        None::<&str>,
+    )?;
+    app.manage(AutostartMenuItem(item.clone()));
+    Ok(item)
+}
+
+/// Update the autostart check mark. No-op on platforms without a tray menu.
+pub fn update_autostart_menu(app_handle: &AppHandle, enabled: bool) {
+    if let Some(item) = app_handle.try_state::<AutostartMenuItem>() {
+        let _ = item.0.set_checked(enabled);
+    }
+}
+
+/// Flip autostart (autostart menu click) through the regular request path,
+/// whose `AutostartChanged` event updates the check mark.
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn toggle_autostart(app_handle: &AppHandle) {
+    let enabled = !flowstt_engine::autostart::is_enabled();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let response =
+            flowstt_engine::ipc::handlers::handle_request(Request::SetAutostart { enabled }).await;
+        if let Response::Error { message } = response {
+            warn!("[Tray] Failed to change autostart: {}", message);
+            // Put the check mark back where the registration is
+            update_autostart_menu(&app_handle, !enabled);
+        }
+    });
+}
+
+/// Shut down the engine directly (in-process).
+/// Used by the tray Exit handler to stop the engine before exiting the app.
+/// Blocks until queued transcriptions have finished (or timed out).
+fn shutdown_engine() {
+    tauri::async_runtime::block_on(flowstt_engine::shutdown::graceful_shutdown());
+    flowstt_engine::cleanup();
+}
+
 /// Load a tray icon image by searching several candidate paths.
 ///
 /// Checks bundled resource paths first (production), then relative and
@@ -29,6 +29,21 @@ interface PttStatus {
   is_active: boolean;
   available: boolean;
   error: string | null;
+  keyboard_layout?: string;
+  letter_layout?: "qwerty" | "qwertz" | "azerty";
+}
+
+interface Capability {
+  available: boolean;
+  reason?: string;
+}
+
+interface Capabilities {
+  system_audio: Capability;
+  aec: Capability;
+  hotkeys: Capability;
+  clipboard: Capability;
+  paste_simulation: Capability;
 }
 
 // Display names for key codes (snake_case serde name -> display)
@@ -137,8 +152,17 @@ const MODIFIER_KEYS = new Set([
   "right_shift", "left_shift", "left_meta", "right_meta",
 ]);
 
+// Labels of the letter keys that move on non-QWERTY layouts
+const LAYOUT_KEY_LABELS: Record<string, Record<string, string>> = {
+  qwertz: { key_y: "Z", key_z: "Y" },
+  azerty: { key_q: "A", key_a: "Q", key_w: "Z", key_z: "W", semicolon: "M", key_m: "," },
+};
+
+// Letter arrangement of the active keyboard layout, from the PTT status
+let letterLayout = "qwerty";
+
 function keyDisplayName(keyCode: string): string {
-  return KEY_DISPLAY_NAMES[keyCode] || keyCode;
+  return LAYOUT_KEY_LABELS[letterLayout]?.[keyCode] || KEY_DISPLAY_NAMES[keyCode] || keyCode;
 }
 
 function combinationDisplayName(combo: HotkeyCombination): string {
@@ -574,11 +598,12 @@ function handleToggleRecordKeyUp(e: KeyboardEvent) { ... }
 
 async function loadState() {
   try {
-    const [devices, status, pttStatus, logLevel] = await Promise.all([
+    const [devices, status, pttStatus, logLevel, capabilities] = await Promise.all([
       invoke<AudioDevice[]>("list_all_sources"),
       invoke<CaptureStatus>("get_status"),
       invoke<PttStatus>("get_ptt_status"),
       invoke<string>("get_log_level"),
+      invoke<Capabilities>("get_capabilities"),
     ]);
 
     allDevices = devices;
@@ -593,6 +618,7 @@ async function loadState() {
       source2Select.value = status.source2_id;
     }
 
+    letterLayout = pttStatus.letter_layout || "qwerty";
     hotkeys = pttStatus.hotkeys || [];
     renderHotkeyList();
 
@@ -600,6 +626,8 @@ async function loadState() {
       logLevelSelect.value = logLevel;
     }
 
+    applyCapabilities(capabilities, status);
+
     // Toggle hotkeys loading - disabled for now
     // toggleHotkeys = pttStatus.auto_toggle_hotkeys || [];
     // renderToggleHotkeys();
@@ -610,6 +638,21 @@ async function loadState() {
   }
 }
 
+// Hide options the platform can't support in this session
+function applyCapabilities(capabilities: Capabilities, status: CaptureStatus) {
+  // Keep the reference field if one is already set so it can still be cleared
+  const referenceField = source2Select.closest(".config-field");
+  referenceField?.classList.toggle(
+    "hidden",
+    !capabilities.system_audio.available && !status.source2_id
+  );
+
+  if (!capabilities.hotkeys.available) {
+    showWarning(`Hotkeys unavailable: ${capabilities.hotkeys.reason ?? "not supported"}`);
+    addHotkeyBtn.disabled = true;
+  }
+}
+
 async function onSourceChange() {
   const source1Id = source1Select.value || null;
   const source2Id = source2Select.value || null;
@@ -1,5 +1,4 @@
 import { invoke } from "@tauri-apps/api/core";
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
@@ -58,6 +57,13 @@ interface HistoryEntry {
   text: string;
   timestamp: string;
   wav_path: string | null;
+  audio_bytes?: number;
+  duration_ms?: number;
+  mode?: TranscriptionMode;
+  model_used?: string;
+  original_text?: string;
+  model?: string;
+  edited?: boolean;
 }
 
 // Enriched transcription result payload
@@ -87,6 +93,7 @@ let transcriptionCompleteUnlisten: UnlistenFn | null = null;
 let transcriptionErrorUnlisten: UnlistenFn | null = null;
 let captureStateChangedUnlisten: UnlistenFn | null = null;
 let historyEntryDeletedUnlisten: UnlistenFn | null = null;
+let historyEntryUpdatedUnlisten: UnlistenFn | null = null;
 let autoModeToggledUnlisten: UnlistenFn | null = null;
 let pttHotkeysChangedUnlisten: UnlistenFn | null = null;
 
@@ -268,6 +275,13 @@ async function setupEventListeners() {
     });
   }
 
+  // History entry text replaced (e.g. re-transcribed with another model)
+  if (!historyEntryUpdatedUnlisten) {
+    historyEntryUpdatedUnlisten = await listen<HistoryEntry>("history-entry-updated", (event) => {
+      replaceHistorySegmentInDOM(event.payload);
+    });
+  }
+
   // Auto mode toggled (via toggle hotkey)
   if (!autoModeToggledUnlisten) {
     autoModeToggledUnlisten = await listen<TranscriptionMode>("auto-mode-toggled", (event) => {
@@ -301,6 +315,9 @@ function cleanupEventListeners() {
   historyEntryDeletedUnlisten?.();
   historyEntryDeletedUnlisten = null;
 
+  historyEntryUpdatedUnlisten?.();
+  historyEntryUpdatedUnlisten = null;
+
   autoModeToggledUnlisten?.();
   autoModeToggledUnlisten = null;
 
@@ -313,7 +330,7 @@ function cleanupEventListeners() {
 // Currently playing audio element (if any)
 let currentAudio: HTMLAudioElement | null = null;
 
-/** Format an ISO 8601 timestamp for display */
+/** Format an RFC 3339 timestamp as a local time of day */
 function formatTimestamp(isoString: string): string {
   try {
     const date = new Date(isoString);
@@ -323,6 +340,26 @@ function formatTimestamp(isoString: string): string {
   }
 }
 
+/** Details of a segment for its timestamp tooltip */
+function describeSegment(entry: HistoryEntry): string {
+  const parts = [new Date(entry.timestamp).toLocaleString()];
+  if (entry.duration_ms !== undefined) {
+    parts.push(`${(entry.duration_ms / 1000).toFixed(1)} s`);
+  }
+  if (entry.mode === "push_to_talk") {
+    parts.push("push-to-talk");
+  } else if (entry.mode === "automatic") {
+    parts.push("automatic");
+  }
+  if (entry.model_used) {
+    parts.push(entry.model_used);
+  }
+  if (entry.audio_bytes !== undefined && entry.wav_path) {
+    parts.push(`${(entry.audio_bytes / 1024).toFixed(0)} KB`);
+  }
+  return parts.join(", ");
+}
+
 /** Create a DOM element for a history segment */
 function createSegmentElement(entry: HistoryEntry): HTMLElement {
   const row = document.createElement("div");
@@ -333,12 +370,17 @@ function createSegmentElement(entry: HistoryEntry): HTMLElement {
   const ts = document.createElement("span");
   ts.className = "segment-timestamp";
   ts.textContent = formatTimestamp(entry.timestamp);
+  ts.title = describeSegment(entry);
   row.appendChild(ts);
 
   // Text
   const text = document.createElement("span");
   text.className = "segment-text";
   text.textContent = entry.text;
+  if (entry.edited) {
+    text.classList.add("edited");
+    if (entry.original_text) text.title = `Original: ${entry.original_text}`;
+  }
   row.appendChild(text);
 
   // Actions
@@ -373,6 +415,17 @@ function createSegmentElement(entry: HistoryEntry): HTMLElement {
   });
   actions.appendChild(copyBtn);
 
+  // Edit button
+  const editBtn = document.createElement("button");
+  editBtn.className = "segment-btn";
+  editBtn.title = "Edit text";
+  editBtn.innerHTML = "&#9998;"; // pencil
+  editBtn.addEventListener("click", (e) => {
+    e.stopPropagation();
+    editHistorySegment(entry, text);
+  });
+  actions.appendChild(editBtn);
+
   // Delete button
   const deleteBtn = document.createElement("button");
   deleteBtn.className = "segment-btn";
@@ -413,6 +466,13 @@ function removeHistorySegmentFromDOM(id: string): void {
   }
 }
 
+/** Re-render a segment in place after its entry changed */
+function replaceHistorySegmentInDOM(entry: HistoryEntry): void {
+  if (!historyContainer) return;
+  const el = historyContainer.querySelector(`[data-id="${entry.id}"]`);
+  if (el) el.replaceWith(createSegmentElement(entry));
+}
+
 /** Show the empty state message */
 function showEmptyState(): void {
   if (!historyContainer) return;
@@ -461,8 +521,46 @@ async function deleteHistoryEntry(id: string, rowEl: HTMLElement): Promise<void>
   }
 }
 
+/** Edit a segment's text in place; Enter saves, Escape cancels */
+function editHistorySegment(entry: HistoryEntry, textEl: HTMLElement): void {
+  if (textEl.isContentEditable) return;
+  textEl.contentEditable = "true";
+  textEl.focus();
+  document.getSelection()?.selectAllChildren(textEl);
+
+  let done = false;
+  const finish = async (save: boolean) => {
+    if (done) return;
+    done = true;
+    textEl.contentEditable = "false";
+    const text = (textEl.textContent ?? "").trim();
+    if (!save || !text || text === entry.text.trim()) {
+      textEl.textContent = entry.text;
+      return;
+    }
+    try {
+      // The segment is re-rendered by the history-entry-updated event
+      await invoke("edit_history_entry", { id: entry.id, text });
+    } catch (error) {
+      console.error("Failed to edit history entry:", error);
+      textEl.textContent = entry.text;
+    }
+  };
+
+  textEl.addEventListener("keydown", (e) => {
+    if (e.key === "Enter" && !e.shiftKey) {
+      e.preventDefault();
+      finish(true);
+    } else if (e.key === "Escape") {
+      e.preventDefault();
+      finish(false);
+    }
+  });
+  textEl.addEventListener("blur", () => finish(true), { once: true });
+}
+
 /** Play a WAV file for a segment */
-function playSegmentAudio(wavPath: string, btn: HTMLButtonElement): void {
+async function playSegmentAudio(wavPath: string, btn: HTMLButtonElement): Promise<void> {
   // Stop any currently playing audio
   if (currentAudio) {
     currentAudio.pause();
@@ -471,24 +569,36 @@ function playSegmentAudio(wavPath: string, btn: HTMLButtonElement): void {
     document.querySelectorAll(".segment-btn.playing").forEach(b => b.classList.remove("playing"));
   }
 
-  const assetUrl = convertFileSrc(wavPath);
-  const audio = new Audio(assetUrl);
+  // Recordings may be encrypted at rest, so read them through the backend
+  let bytes: ArrayBuffer;
+  try {
+    bytes = await invoke<ArrayBuffer>("read_history_audio", { path: wavPath });
+  } catch (error) {
+    console.error("Failed to read audio:", wavPath, error);
+    return;
+  }
+
+  const blobUrl = URL.createObjectURL(new Blob([bytes], { type: "audio/wav" }));
+  const audio = new Audio(blobUrl);
   currentAudio = audio;
   btn.classList.add("playing");
 
   audio.addEventListener("ended", () => {
     btn.classList.remove("playing");
+    URL.revokeObjectURL(blobUrl);
     currentAudio = null;
   });
 
   audio.addEventListener("error", () => {
     btn.classList.remove("playing");
+    URL.revokeObjectURL(blobUrl);
     currentAudio = null;
     console.error("Failed to play audio:", wavPath);
   });
 
   audio.play().catch((e) => {
     btn.classList.remove("playing");
+    URL.revokeObjectURL(blobUrl);
     currentAudio = null;
     console.error("Audio playback error:", e);
   });
@@ -33,6 +33,17 @@ interface CaptureStatus {
   error: string | null;
 }
 
+interface Capabilities {
+  system_audio: { available: boolean; reason?: string };
+}
+
+interface AudioSelfTestReport {
+  rms_db: number;
+  peak_db: number;
+  noise_floor_db: number;
+  warnings: string[];
+}
+
 // ---------------------------------------------------------------------------
 // Key mapping (subset from config.ts)
 // ---------------------------------------------------------------------------
@@ -126,6 +137,8 @@ let deviceListEl: HTMLDivElement;
 let levelMeterSection: HTMLDivElement;
 let levelMeterFill: HTMLDivElement;
 let levelLabel: HTMLSpanElement;
+let selfTestBtn: HTMLButtonElement;
+let selfTestResult: HTMLSpanElement;
 let systemDeviceSelect: HTMLSelectElement;
 let hotkeyLabel: HTMLSpanElement;
 let changeHotkeyBtn: HTMLButtonElement;
@@ -388,6 +401,11 @@ async function initDeviceStep() {
     // Show level meter if a device is already selected
     levelMeterSection.classList.toggle("hidden", !selectedDeviceId);
 
+    // Hide system audio when it can't be captured in this session
+    const capabilities = await invoke<Capabilities>("get_capabilities");
+    const systemAudioSection = systemDeviceSelect.closest(".system-audio-section");
+    systemAudioSection?.classList.toggle("hidden", !capabilities.system_audio.available);
+
     // Populate system device dropdown
     systemDeviceSelect.innerHTML = '<option value="">None</option>';
     systemDevices.forEach((device) => {
@@ -425,6 +443,8 @@ async function selectDevice(deviceId: string) {
   levelMeterSection.classList.remove("hidden");
   levelMeterFill.style.width = "0%";
   levelLabel.textContent = "Starting...";
+  selfTestResult.className = "self-test-result";
+  selfTestResult.textContent = "";
 
   updateNextEnabled();
 
@@ -437,6 +457,34 @@ async function selectDevice(deviceId: string) {
   }
 }
 
+async function runSelfTest() {
+  if (!selectedDeviceId) return;
+  const deviceId = selectedDeviceId;
+
+  selfTestBtn.disabled = true;
+  selfTestResult.className = "self-test-result";
+  selfTestResult.textContent = "Speak normally for 2 seconds...";
+  try {
+    const report = await invoke<AudioSelfTestReport>("run_audio_self_test", { deviceId });
+    if (report.warnings.length === 0) {
+      selfTestResult.classList.add("success");
+      selfTestResult.textContent = `Microphone looks good (${report.rms_db.toFixed(0)} dB)`;
+    } else {
+      selfTestResult.classList.add("error");
+      selfTestResult.textContent = report.warnings.join(". ");
+    }
+  } catch (err) {
+    selfTestResult.classList.add("error");
+    selfTestResult.textContent = `Self-test failed: ${err}`;
+  } finally {
+    selfTestBtn.disabled = false;
+    // The self-test takes the device over; bring the level meter back
+    if (selectedDeviceId === deviceId) {
+      invoke("test_audio_device", { deviceId }).catch(() => {});
+    }
+  }
+}
+
 // ---------------------------------------------------------------------------
 // Step 3: Hotkey Setup
 // ---------------------------------------------------------------------------
@@ -751,6 +799,8 @@ document.addEventListener("DOMContentLoaded", async () => {
   levelMeterSection = document.getElementById("level-meter-section") as HTMLDivElement;
   levelMeterFill = document.getElementById("level-meter-fill") as HTMLDivElement;
   levelLabel = document.getElementById("level-label") as HTMLSpanElement;
+  selfTestBtn = document.getElementById("self-test-btn") as HTMLButtonElement;
+  selfTestResult = document.getElementById("self-test-result") as HTMLSpanElement;
   systemDeviceSelect = document.getElementById("system-device-select") as HTMLSelectElement;
   hotkeyLabel = document.getElementById("hotkey-label") as HTMLSpanElement;
   changeHotkeyBtn = document.getElementById("change-hotkey-btn") as HTMLButtonElement;
@@ -801,6 +851,7 @@ document.addEventListener("DOMContentLoaded", async () => {
   backBtn.addEventListener("click", handleBack);
   skipLink.addEventListener("click", (e) => { e.preventDefault(); skipSetup(); });
   downloadBtn.addEventListener("click", startDownload);
+  selfTestBtn.addEventListener("click", runSelfTest);
 
   // Hotkey recording
   changeHotkeyBtn.addEventListener("click", startHotkeyRecording);